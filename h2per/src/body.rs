@@ -0,0 +1,408 @@
+//! Incremental request and response bodies.
+//!
+//! [`HyperRequest::body_stream`](crate::HyperRequest::body_stream) hands a
+//! handler the body chunk by chunk instead of as one buffer. The `413` from
+//! [`BodyAdmission::with_max_body_size`](crate::BodyAdmission::with_max_body_size)
+//! only looks at the declared `Content-Length`, so a streamed body carries
+//! its own running total and fails once it passes the configured maximum.
+//!
+//! In the other direction, [`ReaderBody`] sends whatever an `AsyncRead`
+//! produces as the response body, without collecting it first.
+
+use std::error::Error;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::{Bytes, BytesMut};
+use futures_util::Stream;
+use http_body::{Body as _, Frame};
+use http_body_util::{BodyExt, Collected, LengthLimitError, Limited};
+use hyper::{HeaderMap, StatusCode};
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::context::{Body, BodyError};
+use crate::reset::{H2ErrorCode, reset_reason};
+
+/// A streamed body read past its byte limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BodyLimitExceeded {
+    /// The configured maximum, in bytes.
+    pub limit: u64,
+}
+
+impl fmt::Display for BodyLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "request body exceeds the {} byte stream limit",
+            self.limit
+        )
+    }
+}
+
+impl std::error::Error for BodyLimitExceeded {}
+
+/// The data chunks of a request body, counted against an optional limit.
+///
+/// The chunk that takes the running total past the limit is not yielded;
+/// the stream yields a [`BodyLimitExceeded`] error in its place and then
+/// ends. A failed read is yielded the same way. Trailers are skipped.
+pub struct BodyStream {
+    body: Body,
+    limit: Option<u64>,
+    read: u64,
+    done: bool,
+}
+
+impl BodyStream {
+    pub fn new(body: Body, limit: Option<u64>) -> Self {
+        Self {
+            body,
+            limit,
+            read: 0,
+            done: false,
+        }
+    }
+
+    /// Bytes yielded so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.read
+    }
+}
+
+impl Stream for BodyStream {
+    type Item = Result<Bytes, BodyError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while !this.done {
+            let frame = match Pin::new(&mut this.body).poll_frame(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => break,
+                Poll::Ready(Some(Ok(frame))) => frame,
+                Poll::Ready(Some(Err(e))) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            };
+            let Ok(data) = frame.into_data() else {
+                continue;
+            };
+
+            let total = this.read + data.len() as u64;
+            if let Some(limit) = this.limit
+                && total > limit
+            {
+                this.done = true;
+                return Poll::Ready(Some(Err(Box::new(BodyLimitExceeded { limit }))));
+            }
+            this.read = total;
+            return Poll::Ready(Some(Ok(data)));
+        }
+        this.done = true;
+        Poll::Ready(None)
+    }
+}
+
+/// Bytes asked of the reader per chunk, unless set otherwise.
+pub const DEFAULT_READ_CHUNK_SIZE: usize = 8 * 1024;
+
+/// A response body read from an `AsyncRead`.
+///
+/// Each read becomes one data frame, handed to the connection as soon as
+/// it completes. The reader is only polled when the connection wants the
+/// next frame, so a slow client holds the reader back instead of letting
+/// data pile up in memory.
+///
+/// A failed read is the body's error, after which the body ends. hyper
+/// aborts the response on it, so the client sees a broken transfer rather
+/// than a body that merely looks short.
+pub struct ReaderBody<R> {
+    reader: Pin<Box<R>>,
+    buf: BytesMut,
+    chunk_size: usize,
+    done: bool,
+}
+
+impl<R: AsyncRead> ReaderBody<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: Box::pin(reader),
+            buf: BytesMut::new(),
+            chunk_size: DEFAULT_READ_CHUNK_SIZE,
+            done: false,
+        }
+    }
+
+    /// Sets the most bytes read into one frame, at least 1.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+}
+
+impl<R: AsyncRead> http_body::Body for ReaderBody<R> {
+    type Data = Bytes;
+    type Error = std::io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, std::io::Error>>> {
+        let this = &mut *self;
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        // Each frame is split off the front of `buf`; once the connection
+        // has dropped it, the resize reclaims the same allocation
+        this.buf.resize(this.chunk_size, 0);
+        let mut buf = ReadBuf::new(&mut this.buf[..]);
+        match this.reader.as_mut().poll_read(cx, &mut buf) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Ok(())) if buf.filled().is_empty() => {
+                this.done = true;
+                Poll::Ready(None)
+            }
+            Poll::Ready(Ok(())) => {
+                let read = buf.filled().len();
+                let chunk = this.buf.split_to(read).freeze();
+                Poll::Ready(Some(Ok(Frame::data(chunk))))
+            }
+            Poll::Ready(Err(e)) => {
+                this.done = true;
+                Poll::Ready(Some(Err(e)))
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+}
+
+/// A request body read in full before dispatch.
+pub(crate) struct Buffered {
+    pub bytes: Bytes,
+    /// Trailer fields sent after the body, such as those following the last
+    /// chunk of an HTTP/1 chunked body
+    pub trailers: Option<HeaderMap>,
+    /// Set when the peer reset the stream before the body was complete
+    pub reset: Option<H2ErrorCode>,
+}
+
+/// Reads `body` into memory, stopping once it passes `limit` bytes.
+///
+/// Passing the limit gives the `413` to answer with, and a body hyper could
+/// not decode, such as a malformed trailer section, a `400`. Any other
+/// failed read leaves the body empty; when the failure is a stream reset its
+/// code is kept, and logged, for the handler.
+pub(crate) async fn buffer<B>(body: B, limit: Option<u64>) -> Result<Buffered, StatusCode>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<Box<dyn Error + Send + Sync>>,
+{
+    let collected: Result<Collected<Bytes>, Box<dyn Error + Send + Sync>> = match limit {
+        Some(max) => Limited::new(body, max as usize).collect().await,
+        None => body.collect().await.map_err(Into::into),
+    };
+    match collected {
+        Ok(collected) => Ok(Buffered {
+            trailers: collected.trailers().cloned(),
+            bytes: collected.to_bytes(),
+            reset: None,
+        }),
+        Err(e) if e.is::<LengthLimitError>() => Err(StatusCode::PAYLOAD_TOO_LARGE),
+        Err(e) if is_malformed(e.as_ref()) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            let reset = reset_reason(e.as_ref());
+            if let Some(_code) = reset {
+                hotaru_core::debug_warn!(
+                    "Stream reset by peer while reading request body: {}",
+                    _code
+                );
+            }
+            Ok(Buffered {
+                bytes: Bytes::new(),
+                trailers: None,
+                reset,
+            })
+        }
+    }
+}
+
+/// Whether a body read failed on bad framing from the peer, rather than on
+/// the connection going away.
+///
+/// hyper reports an undecodable HTTP/1 chunked body, trailers included, as
+/// an I/O error of kind `InvalidInput` or `InvalidData` under its own.
+fn is_malformed(err: &(dyn Error + 'static)) -> bool {
+    let mut current = Some(err);
+    while let Some(err) = current {
+        if let Some(io_err) = err.downcast_ref::<std::io::Error>() {
+            return matches!(
+                io_err.kind(),
+                std::io::ErrorKind::InvalidInput | std::io::ErrorKind::InvalidData
+            );
+        }
+        current = err.source();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HyperHttp1;
+    use crate::context::{HyperContext, empty_body};
+    use crate::response::response_templates::reader_response;
+    use crate::service::HotaruService;
+    use http::HeaderValue;
+    use http_body_util::BodyExt;
+    use hyper::Request;
+    use hyper::body::Incoming;
+    use hyper::client::conn::http1 as client_http1;
+    use hyper::server::conn::http1;
+    use hyper::service::service_fn;
+    use hyper_util::rt::TokioIo;
+    use std::convert::Infallible;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_reader_response_streams_all_bytes() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(20_000).collect();
+        let response = reader_response(std::io::Cursor::new(data.clone()), "application/pdf");
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/pdf"
+        );
+
+        let mut body = response.into_inner().into_body();
+        let mut received = Vec::new();
+        let mut frames = 0;
+        while let Some(frame) = body.frame().await {
+            let chunk = frame.unwrap().into_data().unwrap();
+            assert!(chunk.len() <= DEFAULT_READ_CHUNK_SIZE);
+            received.extend_from_slice(&chunk);
+            frames += 1;
+        }
+        assert_eq!(received, data);
+        assert_eq!(frames, 3);
+    }
+
+    #[tokio::test]
+    async fn test_reader_error_fails_body() {
+        let failing = tokio_test::io::Builder::new()
+            .read(b"first")
+            .read_error(std::io::Error::other("decrypt failed"))
+            .build();
+        let mut body = ReaderBody::new(failing).with_chunk_size(4);
+
+        let mut received = Vec::new();
+        let err = loop {
+            match body.frame().await {
+                Some(Ok(frame)) => received.extend_from_slice(&frame.into_data().unwrap()),
+                Some(Err(e)) => break e,
+                None => panic!("body ended without reporting the failed read"),
+            }
+        };
+        assert_eq!(received, b"first");
+        assert_eq!(err.to_string(), "decrypt failed");
+        assert!(body.is_end_stream());
+        assert!(body.frame().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_client_sees_reader_error() {
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let service = service_fn(|_req: Request<Incoming>| async {
+            let failing = tokio_test::io::Builder::new()
+                .read(b"partial")
+                .wait(std::time::Duration::from_millis(20))
+                .read_error(std::io::Error::other("decrypt failed"))
+                .build();
+            Ok::<_, Infallible>(reader_response(failing, "application/pdf").into_inner())
+        });
+        tokio::spawn(http1::Builder::new().serve_connection(TokioIo::new(server_io), service));
+
+        let (mut sender, conn) = client_http1::handshake(TokioIo::new(client_io))
+            .await
+            .unwrap();
+        tokio::spawn(conn);
+        let request = Request::get("/report.pdf")
+            .header("host", "localhost")
+            .body(empty_body())
+            .unwrap();
+        let response = sender.send_request(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The response is cut off mid-body instead of ending cleanly
+        assert!(response.into_body().collect().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handler_reads_chunked_trailers() {
+        let (mut client, server_io) = tokio::io::duplex(4096);
+        let (seen_tx, mut seen_rx) = mpsc::unbounded_channel();
+
+        // Reads the body, then reports the trailer it came with
+        let service = HotaruService::<HyperHttp1>::from_handler(move |mut ctx: HyperContext| {
+            let seen_tx = seen_tx.clone();
+            async move {
+                match ctx.request.body().await {
+                    Ok(_) => seen_tx
+                        .send(ctx.request.trailer("x-checksum").cloned())
+                        .unwrap(),
+                    Err(status) => ctx.response.set_status(status),
+                }
+                ctx
+            }
+        });
+        tokio::spawn(http1::Builder::new().serve_connection(TokioIo::new(server_io), service));
+
+        async fn read_head(client: &mut tokio::io::DuplexStream) -> String {
+            let mut head = Vec::new();
+            while !head.ends_with(b"\r\n\r\n") {
+                let mut byte = [0u8; 1];
+                if client.read(&mut byte).await.unwrap() == 0 {
+                    break;
+                }
+                head.push(byte[0]);
+            }
+            String::from_utf8(head).unwrap()
+        }
+
+        client
+            .write_all(
+                b"POST /upload HTTP/1.1\r\nHost: localhost\r\n\
+                  Transfer-Encoding: chunked\r\nTE: trailers\r\n\r\n\
+                  5\r\nhello\r\n0\r\nX-Checksum: 5d41402a\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            seen_rx.recv().await,
+            Some(Some(HeaderValue::from_static("5d41402a")))
+        );
+        let head = read_head(&mut client).await;
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "got {:?}", head);
+
+        // A trailer line that is not a field is rejected
+        client
+            .write_all(
+                b"POST /upload HTTP/1.1\r\nHost: localhost\r\n\
+                  Transfer-Encoding: chunked\r\n\r\n\
+                  5\r\nhello\r\n0\r\nnot a field\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        let head = read_head(&mut client).await;
+        assert!(
+            head.starts_with("HTTP/1.1 400 Bad Request\r\n"),
+            "got {:?}",
+            head
+        );
+    }
+}
@@ -4,6 +4,8 @@
 use serde::de::DeserializeOwned;
 use std::any::Any;
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::{Arc, RwLock};
 
 use akari::{
@@ -11,11 +13,17 @@ use akari::{
     extensions::{Locals, LocalsClone, Params, ParamsClone},
 };
 
+use crate::body::{BodyStream, buffer};
+use crate::raw::{RawRecvStream, RawSendStream};
+use crate::reset::H2ErrorCode;
+use crate::sse::SseSender;
+
 use hotaru_core::{
     app::application::App,
     connection::{ConnectionStatus, ProtocolRole, RequestContext},
     http::form::UrlEncodedForm,
-    url::Url,
+    protocol::{Extensions, HeaderMultiMap},
+    url::{Url, decode_segment},
 };
 
 use bytes::Bytes;
@@ -70,6 +78,12 @@ pub struct HyperContext {
 
     /// Target protocol for upgrade (using HTTP-specific enum)
     pub upgrade_target: Option<crate::upgrade::HttpProtocol>,
+
+    /// Request headers in wire order, for `RequestContext::headers`
+    raw_headers: HeaderMultiMap,
+
+    /// Typed values attached by middleware, for `RequestContext::extensions`
+    extensions: Extensions,
 }
 
 #[derive(Clone, Debug)]
@@ -80,8 +94,31 @@ pub enum HttpVersion {
     Http3,
 }
 
+/// Error a [`Body`] can fail with, such as a streamed response whose
+/// source broke off part way
+pub type BodyError = Box<dyn std::error::Error + Send + Sync>;
+
 /// Type for request/response bodies
-pub type Body = BoxBody<Bytes, std::convert::Infallible>;
+pub type Body = BoxBody<Bytes, BodyError>;
+
+/// Wraps any body as a [`Body`]
+pub fn box_body<B>(body: B) -> Body
+where
+    B: http_body::Body<Data = Bytes> + Send + Sync + 'static,
+    B::Error: Into<BodyError>,
+{
+    body.map_err(Into::into).boxed()
+}
+
+/// A [`Body`] holding `bytes`
+pub fn full_body(bytes: impl Into<Bytes>) -> Body {
+    box_body(Full::new(bytes.into()))
+}
+
+/// A [`Body`] with no data
+pub fn empty_body() -> Body {
+    box_body(Empty::<Bytes>::new())
+}
 
 /// Wrapper around Hyper's Request with convenience methods
 pub struct HyperRequest {
@@ -90,6 +127,10 @@ pub struct HyperRequest {
     pub path_params: HashMap<String, String>,
     pub query_params: HashMap<String, String>,
     pub body_bytes: Option<Vec<u8>>, // Store body bytes for form/json parsing
+    body_failure: Option<StatusCode>,
+    stream_limit: Option<u64>,
+    reset_reason: Option<H2ErrorCode>,
+    trailers: Option<HeaderMap>,
 }
 
 /// Wrapper around Hyper's Response with convenience methods  
@@ -101,6 +142,11 @@ pub struct HyperResponse {
 impl HyperContext {
     /// Create a new context for server handling
     pub fn new_server(request: Request<Body>, app: Arc<App>) -> Self {
+        Self::server_context(request, Some(app))
+    }
+
+    /// A server-side context, with the app it is served by if there is one
+    pub(crate) fn server_context(request: Request<Body>, app: Option<Arc<App>>) -> Self {
         let version = match request.version() {
             Version::HTTP_09 | Version::HTTP_10 => HttpVersion::Http1_0,
             Version::HTTP_11 => HttpVersion::Http1_1,
@@ -114,6 +160,7 @@ impl HyperContext {
 
         // Parse query parameters
         let query_params = parse_query_params(request.uri().query());
+        let raw_headers = header_multimap(request.headers());
 
         // Parse path segments
         let path = request.uri().path();
@@ -130,16 +177,20 @@ impl HyperContext {
                 path_params: HashMap::new(),
                 query_params,
                 body_bytes: None,
+                body_failure: None,
+                stream_limit: None,
+                reset_reason: None,
+                trailers: None,
             },
             response: HyperResponse {
                 inner: Response::builder()
                     .status(StatusCode::OK)
-                    .body(Empty::<Bytes>::new().boxed())
+                    .body(empty_body())
                     .unwrap(),
             },
             params: RwLock::new(params),
             locals: RwLock::new(locals),
-            app: Some(app),
+            app,
             template_manager: None,
             stream_id: None,
             role: ProtocolRole::Server,
@@ -148,6 +199,8 @@ impl HyperContext {
             connection_status: ConnectionStatus::Connected,
             upgrade_context: None,
             upgrade_target: None,
+            raw_headers,
+            extensions: Extensions::new(),
         }
     }
 
@@ -162,6 +215,7 @@ impl HyperContext {
         };
 
         let query_params = parse_query_params(request.uri().query());
+        let raw_headers = header_multimap(request.headers());
 
         // Parse path segments
         let path = request.uri().path();
@@ -178,11 +232,15 @@ impl HyperContext {
                 path_params: HashMap::new(),
                 query_params,
                 body_bytes: None,
+                body_failure: None,
+                stream_limit: None,
+                reset_reason: None,
+                trailers: None,
             },
             response: HyperResponse {
                 inner: Response::builder()
                     .status(StatusCode::OK)
-                    .body(Empty::<Bytes>::new().boxed())
+                    .body(empty_body())
                     .unwrap(),
             },
             params: RwLock::new(Params::new()),
@@ -196,6 +254,8 @@ impl HyperContext {
             connection_status: ConnectionStatus::Connected,
             upgrade_context: None,
             upgrade_target: None,
+            raw_headers,
+            extensions: Extensions::new(),
         }
     }
 
@@ -266,10 +326,98 @@ impl HyperRequest {
         self.inner.extensions_mut()
     }
 
-    /// Get the request body (consumes it)
-    pub async fn body(self) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
-        let collected = self.inner.into_body().collect().await?;
-        Ok(collected.to_bytes().to_vec())
+    /// Get the request body, reading it into memory on the first call
+    ///
+    /// The server hands handlers the body unread. The first call reads it
+    /// in full, stopping at the [stream limit](Self::stream_limit), and
+    /// keeps the bytes in `body_bytes` along with any
+    /// [trailers](Self::trailers); later calls, and the form and JSON
+    /// parsers, reuse them. A body past the limit fails with `413`, one
+    /// hyper could not decode with `400`, and a failed read keeps failing
+    /// the same way.
+    pub async fn body(&mut self) -> Result<&[u8], StatusCode> {
+        if let Some(status) = self.body_failure {
+            return Err(status);
+        }
+        if self.body_bytes.is_none() {
+            let body = std::mem::replace(self.inner.body_mut(), empty_body());
+            // Boxed so the read is proven `Send` here, where the body's
+            // error type is known in full; left inline, handler futures
+            // that await this cannot be shown to be `Send`
+            let read: Pin<Box<dyn Future<Output = _> + Send>> =
+                Box::pin(buffer(body, self.stream_limit));
+            match read.await {
+                Ok(buffered) => {
+                    self.body_bytes = Some(buffered.bytes.to_vec());
+                    self.reset_reason = buffered.reset;
+                    if buffered.trailers.is_some() {
+                        self.trailers = buffered.trailers;
+                    }
+                }
+                Err(status) => {
+                    self.body_failure = Some(status);
+                    return Err(status);
+                }
+            }
+        }
+        Ok(self.body_bytes.as_deref().unwrap_or_default())
+    }
+
+    /// The status [`body`](Self::body) failed with, if it did
+    pub(crate) fn body_failure(&self) -> Option<StatusCode> {
+        self.body_failure
+    }
+
+    /// Stream the request body chunk by chunk
+    ///
+    /// The body is taken out of the request, so this is an alternative to
+    /// [`body`](Self::body) rather than something to combine with it. The
+    /// stream errors once more bytes arrive in total than the limit from
+    /// [`BodyAdmission::with_max_stream_size`](crate::BodyAdmission::with_max_stream_size).
+    pub fn body_stream(&mut self) -> BodyStream {
+        let body = std::mem::replace(self.inner.body_mut(), empty_body());
+        BodyStream::new(body, self.stream_limit)
+    }
+
+    /// Get the byte limit applied by [`body_stream`](Self::body_stream)
+    pub fn stream_limit(&self) -> Option<u64> {
+        self.stream_limit
+    }
+
+    /// Set the byte limit applied by [`body_stream`](Self::body_stream)
+    pub fn set_stream_limit(&mut self, limit: Option<u64>) {
+        self.stream_limit = limit;
+    }
+
+    /// Get the `RST_STREAM` code if the peer reset the stream before the
+    /// body arrived in full, once [`body`](Self::body) has read it
+    pub fn reset_reason(&self) -> Option<H2ErrorCode> {
+        self.reset_reason
+    }
+
+    /// Record the `RST_STREAM` code the stream was reset with
+    pub fn set_reset_reason(&mut self, reason: Option<H2ErrorCode>) {
+        self.reset_reason = reason;
+    }
+
+    /// Get the trailer fields sent after the body, if any
+    ///
+    /// HTTP/1 clients send these after the last chunk of a chunked body
+    /// (`TE: trailers`). They are kept apart from [`headers`](Self::headers),
+    /// which only holds the fields sent before the body, and are only known
+    /// once [`body`](Self::body) has read it.
+    pub fn trailers(&self) -> Option<&HeaderMap> {
+        self.trailers.as_ref()
+    }
+
+    /// Get a single trailer field by name
+    pub fn trailer(&self, name: &str) -> Option<&HeaderValue> {
+        self.trailers.as_ref()?.get(name)
+    }
+
+    /// Record the trailer fields read after the body
+    pub fn set_trailers(&mut self, trailers: Option<HeaderMap>) {
+        self.trailers = trailers;
     }
 
     /// Take the inner Hyper request (for full control)
@@ -340,13 +488,13 @@ impl HyperResponse {
 
     /// Set the response body
     pub fn set_body(&mut self, body: Vec<u8>) {
-        let body = Full::new(Bytes::from(body)).boxed();
+        let body = full_body(body);
         *self.inner.body_mut() = body;
     }
 
     /// Set the response body from Bytes
     pub fn set_body_bytes(&mut self, bytes: Bytes) {
-        let body = Full::new(bytes).boxed();
+        let body = full_body(bytes);
         *self.inner.body_mut() = body;
     }
 
@@ -428,6 +576,18 @@ impl RequestContext for HyperContext {
     fn role(&self) -> ProtocolRole {
         self.role
     }
+
+    fn headers(&self) -> &HeaderMultiMap {
+        &self.raw_headers
+    }
+
+    fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 impl HyperContext {
@@ -511,6 +671,83 @@ impl HyperContext {
             ConnectionStatus::SwitchProtocol(std::any::TypeId::of::<HyperHttp2>());
     }
 
+    /// Accept a plain CONNECT request, tunneling to its request authority
+    ///
+    /// Sets a `200 OK` response; the service bridges the client stream to
+    /// the upstream once it has been sent. See [`crate::tunnel`].
+    pub fn accept_connect(&mut self) {
+        if let Some(authority) = crate::tunnel::connect_authority(&self.request.inner) {
+            self.accept_connect_via(authority);
+        } else {
+            self.response.set_status(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    /// Accept a plain CONNECT request, tunneling to `upstream` instead of the
+    /// request authority (e.g. to pin or rewrite proxy destinations)
+    pub fn accept_connect_via(&mut self, upstream: impl Into<String>) {
+        use crate::upgrade::{
+            HttpProtocol, HttpUpgradeType, UpgradeContext, UpgradeMetadata, UpgradeState,
+        };
+
+        self.upgrade_context = Some(UpgradeContext {
+            target_protocol: HttpProtocol::Tunnel,
+            upgrade_type: HttpUpgradeType::Connect {
+                upstream: upstream.into(),
+            },
+            state: UpgradeState::Requested,
+            metadata: UpgradeMetadata::default(),
+            initiated_at: std::time::Instant::now(),
+        });
+        self.upgrade_target = Some(HttpProtocol::Tunnel);
+        self.response.set_status(StatusCode::OK);
+
+        use crate::tunnel::TcpTunnel;
+        self.connection_status =
+            ConnectionStatus::SwitchProtocol(std::any::TypeId::of::<TcpTunnel>());
+    }
+
+    /// Take raw handles to this request's HTTP/2 stream
+    ///
+    /// For advanced handlers only; read [`crate::raw`] before using it. The
+    /// request body and trailers move into the [`RawRecvStream`], and the
+    /// response body becomes whatever the [`RawSendStream`] sends. `None`
+    /// unless the request came over HTTP/2.
+    pub fn raw_h2_stream(&mut self) -> Option<(RawSendStream, RawRecvStream)> {
+        if self.request.version() != Version::HTTP_2 {
+            return None;
+        }
+        let body = std::mem::replace(self.request.inner.body_mut(), empty_body());
+        let trailers = self.request.trailers.take();
+        let (send, recv, response) = crate::raw::raw_stream(body, trailers, self.stream_id);
+        self.response.set_body_stream(response);
+        Some((send, recv))
+    }
+
+    /// Answer with a server-sent event stream fed by `producer`
+    ///
+    /// Sets a `200 OK` `text/event-stream` response and spawns `producer`
+    /// with the [`SseSender`] for it. The producer is dropped when the
+    /// client disconnects; see [`crate::sse`].
+    pub fn sse<F, Fut>(&mut self, producer: F)
+    where
+        F: FnOnce(SseSender) -> Fut,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        self.response.set_status(StatusCode::OK);
+        let headers = self.response.headers_mut();
+        headers.insert(
+            HeaderName::from_static("content-type"),
+            HeaderValue::from_static("text/event-stream"),
+        );
+        headers.insert(
+            HeaderName::from_static("cache-control"),
+            HeaderValue::from_static("no-cache"),
+        );
+        self.response
+            .set_body_stream(crate::sse::event_stream(producer));
+    }
+
     /// Signal a generic protocol switch
     pub fn switch_protocol(&mut self, protocol_type_id: std::any::TypeId) {
         self.connection_status = ConnectionStatus::SwitchProtocol(protocol_type_id);
@@ -521,6 +758,21 @@ impl HyperContext {
 // Helper Functions
 // ============================================================================
 
+/// Copies a hyper `HeaderMap` into a `HeaderMultiMap`, keeping every value.
+///
+/// Values that are not valid UTF-8 are decoded lossily.
+pub fn header_multimap(headers: &HeaderMap) -> HeaderMultiMap {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.as_str(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
 fn parse_query_params(query: Option<&str>) -> HashMap<String, String> {
     let mut params = HashMap::new();
 
@@ -623,7 +875,8 @@ impl HyperContext {
         self.request.path()
     }
 
-    /// Get a path parameter from URL pattern matching
+    /// Get a path parameter from URL pattern matching, percent-decoded
+    /// except for `%2F`, `%5C` and `%00` (see [`decode_segment`])
     pub fn pattern<A: AsRef<str>>(&self, name: A) -> Option<String> {
         // First check if we have it in path_params (for backward compatibility)
         if let Some(value) = self.request.path_params.get(name.as_ref()) {
@@ -633,13 +886,23 @@ impl HyperContext {
         // Otherwise, try to extract from endpoint and path segments
         if let Some(endpoint) = &self.endpoint {
             if let Some(index) = endpoint.match_seg_name_with_index(name) {
-                return self.path_segments.get(index).cloned();
+                return self
+                    .path_segments
+                    .get(index)
+                    .map(|segment| decode_segment(segment).into_owned());
             }
         }
 
         None
     }
 
+    /// Same as [`pattern`](Self::pattern), but the segment exactly as sent
+    pub fn pattern_raw<A: AsRef<str>>(&self, name: A) -> Option<String> {
+        let endpoint = self.endpoint.as_ref()?;
+        let index = endpoint.match_seg_name_with_index(name)?;
+        self.path_segments.get(index).cloned()
+    }
+
     /// Get the application reference
     pub fn app(&self) -> Option<Arc<App>> {
         self.app.clone()
@@ -655,8 +918,8 @@ impl HyperContext {
             return None;
         }
 
-        // Parse form data from stored body bytes using serde_urlencoded
-        let body_bytes = self.request.body_bytes.as_ref()?;
+        // Parse form data from the buffered body using serde_urlencoded
+        let body_bytes = self.request.body().await.ok()?;
 
         // Use serde_urlencoded for proper form parsing
         match serde_urlencoded::from_bytes::<HashMap<String, String>>(body_bytes) {
@@ -676,7 +939,7 @@ impl HyperContext {
         }
 
         // Parse directly into the requested type
-        let body_bytes = self.request.body_bytes.as_ref()?;
+        let body_bytes = self.request.body().await.ok()?;
         serde_urlencoded::from_bytes(body_bytes).ok()
     }
 
@@ -690,8 +953,8 @@ impl HyperContext {
             }
         }
 
-        // Parse JSON from stored body bytes
-        let body_bytes = self.request.body_bytes.as_ref()?;
+        // Parse JSON from the buffered body
+        let body_bytes = self.request.body().await.ok()?;
         serde_json::from_slice(body_bytes).ok()
     }
 
@@ -717,7 +980,7 @@ pub fn json_response<T: serde::Serialize>(data: T) -> HyperContext {
         Request::builder()
             .method("GET")
             .uri("/")
-            .body(Empty::<Bytes>::new().boxed())
+            .body(empty_body())
             .unwrap(),
     );
     let _ = ctx.response.json(data);
@@ -730,7 +993,7 @@ pub fn text_response(text: impl Into<String>) -> HyperContext {
         Request::builder()
             .method("GET")
             .uri("/")
-            .body(Empty::<Bytes>::new().boxed())
+            .body(empty_body())
             .unwrap(),
     );
     ctx.response.text(text.into());
@@ -743,7 +1006,7 @@ pub fn html_response(html: impl Into<String>) -> HyperContext {
         Request::builder()
             .method("GET")
             .uri("/")
-            .body(Empty::<Bytes>::new().boxed())
+            .body(empty_body())
             .unwrap(),
     );
     ctx.response.html(html.into());
@@ -759,7 +1022,7 @@ pub fn switch_protocol_response(
         Request::builder()
             .method("GET")
             .uri("/")
-            .body(Empty::<Bytes>::new().boxed())
+            .body(empty_body())
             .unwrap(),
     );
     ctx.response.inner = response;
@@ -0,0 +1,269 @@
+//! `Expect: 100-continue` handling for the HTTP/1 path.
+//!
+//! Hyper writes the `100 Continue` interim response by itself the first time
+//! the request body is polled. All we have to do is decide *before* touching
+//! the body whether the request is acceptable: if it is, reading the body
+//! releases the client; if not, we answer with a final status and the body is
+//! never requested.
+
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE, EXPECT};
+use http::request::Parts;
+use hyper::{Method, Response, StatusCode};
+
+use crate::context::{Body, full_body};
+
+/// Checks applied to a request before its body is read.
+///
+/// Every limit is optional; an unset limit accepts everything.
+#[derive(Clone, Debug, Default)]
+pub struct BodyAdmission {
+    max_body_size: Option<u64>,
+    max_stream_size: Option<u64>,
+    allowed_methods: Option<Vec<Method>>,
+    allowed_content_types: Option<Vec<String>>,
+}
+
+impl BodyAdmission {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rejects bodies whose declared `Content-Length` exceeds `bytes` (413).
+    pub fn with_max_body_size(mut self, bytes: u64) -> Self {
+        self.max_body_size = Some(bytes);
+        self
+    }
+
+    /// Caps the total bytes of a body, however it is sent.
+    ///
+    /// A body with no `Content-Length` gets past
+    /// [`with_max_body_size`](Self::with_max_body_size); this limit counts
+    /// what actually arrives. Handlers reading through
+    /// [`HyperRequest::body_stream`](crate::HyperRequest::body_stream) see the
+    /// stream error at the threshold.
+    pub fn with_max_stream_size(mut self, bytes: u64) -> Self {
+        self.max_stream_size = Some(bytes);
+        self
+    }
+
+    /// The limit set by [`with_max_stream_size`](Self::with_max_stream_size).
+    pub fn max_stream_size(&self) -> Option<u64> {
+        self.max_stream_size
+    }
+
+    /// Rejects methods not in `methods` (405).
+    pub fn with_allowed_methods(mut self, methods: Vec<Method>) -> Self {
+        self.allowed_methods = Some(methods);
+        self
+    }
+
+    /// Rejects media types not in `types` (415). Parameters such as
+    /// `charset` are ignored when comparing.
+    pub fn with_allowed_content_types(mut self, types: Vec<String>) -> Self {
+        self.allowed_content_types = Some(types);
+        self
+    }
+
+    /// Runs the method, content-type and size checks against the head of a
+    /// request, returning the final status to answer with on failure.
+    pub fn check(&self, parts: &Parts) -> Result<(), StatusCode> {
+        if let Some(methods) = &self.allowed_methods
+            && !methods.contains(&parts.method)
+        {
+            return Err(StatusCode::METHOD_NOT_ALLOWED);
+        }
+
+        if let Some(types) = &self.allowed_content_types {
+            let media_type = parts
+                .headers
+                .get(CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split(';').next())
+                .map(|v| v.trim().to_ascii_lowercase());
+            if let Some(media_type) = media_type
+                && !types.iter().any(|t| t.eq_ignore_ascii_case(&media_type))
+            {
+                return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+            }
+        }
+
+        if let Some(max) = self.max_body_size {
+            let declared = parts
+                .headers
+                .get(CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok());
+            if declared.is_some_and(|len| len > max) {
+                return Err(StatusCode::PAYLOAD_TOO_LARGE);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// What the `Expect` header of a request asks for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Expectation {
+    /// No `Expect` header.
+    None,
+    /// `Expect: 100-continue`.
+    Continue,
+    /// Any other expectation, which we cannot meet.
+    Unsupported,
+}
+
+impl Expectation {
+    pub fn of(parts: &Parts) -> Self {
+        match parts.headers.get(EXPECT).map(|v| v.as_bytes()) {
+            None => Self::None,
+            Some(v) if v.eq_ignore_ascii_case(b"100-continue") => Self::Continue,
+            Some(_) => Self::Unsupported,
+        }
+    }
+}
+
+/// Decides whether the body of this request may be read.
+///
+/// Returns `None` when the caller should go ahead and read the body; for an
+/// `Expect: 100-continue` request that read is what makes hyper emit the
+/// interim `100 Continue`. Returns the final response otherwise: `417` for
+/// expectations we cannot meet, or the failing check's status.
+pub fn admit_body(parts: &Parts, admission: &BodyAdmission) -> Option<Response<Body>> {
+    let expectation = Expectation::of(parts);
+    if expectation == Expectation::Unsupported {
+        return Some(final_response(StatusCode::EXPECTATION_FAILED));
+    }
+
+    match admission.check(parts) {
+        Ok(()) => None,
+        Err(status) => Some(final_response(status)),
+    }
+}
+
+pub(crate) fn final_response(status: StatusCode) -> Response<Body> {
+    let reason = status.canonical_reason().unwrap_or("");
+    Response::builder()
+        .status(status)
+        .header(http::header::CONNECTION, "close")
+        .body(full_body(format!("{} {}", status.as_u16(), reason)))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HyperHttp1;
+    use crate::context::{HyperContext, box_body};
+    use crate::service::HotaruService;
+    use bytes::Bytes;
+    use http_body_util::{BodyExt, StreamBody};
+    use hyper::Request;
+    use hyper::body::Frame;
+    use hyper::client::conn::http1 as client_http1;
+    use hyper::server::conn::http1;
+    use hyper_util::rt::TokioIo;
+    use std::convert::Infallible;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::oneshot;
+
+    /// What the client saw of one request
+    struct Outcome {
+        continued: bool,
+        status: StatusCode,
+        text: String,
+    }
+
+    /// POSTs five bytes with `Expect: expect` to a [`HotaruService`] whose
+    /// handler reads the body
+    ///
+    /// Like a client honoring the expectation, the body is held back until
+    /// the interim `100 Continue` arrives.
+    async fn post_expecting(admission: BodyAdmission, expect: &'static str) -> Outcome {
+        let service =
+            HotaruService::<HyperHttp1>::from_handler(|mut ctx: HyperContext| async move {
+                match ctx.request.body().await {
+                    Ok(body) => {
+                        let reply = format!("read {} bytes", body.len());
+                        ctx.response.set_body(reply.into_bytes());
+                    }
+                    Err(status) => ctx.response.set_status(status),
+                }
+                ctx
+            })
+            .with_body_admission(admission);
+
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        tokio::spawn(http1::Builder::new().serve_connection(TokioIo::new(server_io), service));
+        let (mut sender, conn) = client_http1::handshake(TokioIo::new(client_io))
+            .await
+            .unwrap();
+        tokio::spawn(conn);
+
+        let (release, released) = oneshot::channel::<()>();
+        let body = StreamBody::new(futures_util::stream::once(async move {
+            let _ = released.await;
+            Ok::<_, Infallible>(Frame::data(Bytes::from_static(b"hello")))
+        }));
+        let mut request = Request::post("/upload")
+            .header("host", "localhost")
+            .header(CONTENT_LENGTH, "5")
+            .header(EXPECT, expect)
+            .body(box_body(body))
+            .unwrap();
+
+        let continued = Arc::new(AtomicBool::new(false));
+        let seen = continued.clone();
+        let release = Mutex::new(Some(release));
+        hyper::ext::on_informational(&mut request, move |response| {
+            if response.status() == StatusCode::CONTINUE {
+                seen.store(true, Ordering::SeqCst);
+                if let Some(release) = release.lock().unwrap().take() {
+                    let _ = release.send(());
+                }
+            }
+        });
+
+        let response = tokio::time::timeout(Duration::from_secs(5), sender.send_request(request))
+            .await
+            .expect("no final response")
+            .unwrap();
+        let (parts, body) = response.into_parts();
+        let bytes = body.collect().await.unwrap().to_bytes();
+        Outcome {
+            continued: continued.load(Ordering::SeqCst),
+            status: parts.status,
+            text: String::from_utf8(bytes.to_vec()).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interim_continue_precedes_body_read() {
+        let admission = BodyAdmission::new().with_max_body_size(1024);
+        let outcome = post_expecting(admission, "100-continue").await;
+
+        // The body only went out after the interim response
+        assert!(outcome.continued);
+        assert_eq!(outcome.status, StatusCode::OK);
+        assert_eq!(outcome.text, "read 5 bytes");
+    }
+
+    #[tokio::test]
+    async fn test_failed_check_skips_continue() {
+        let admission = BodyAdmission::new().with_max_body_size(4);
+        let outcome = post_expecting(admission, "100-continue").await;
+
+        assert!(!outcome.continued);
+        assert_eq!(outcome.status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_expectation_is_417() {
+        let outcome = post_expecting(BodyAdmission::new(), "something-else").await;
+
+        assert!(!outcome.continued);
+        assert_eq!(outcome.status, StatusCode::EXPECTATION_FAILED);
+    }
+}
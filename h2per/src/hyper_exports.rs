@@ -105,7 +105,7 @@ pub use bytes::{Buf, BufMut, Bytes, BytesMut};
 pub use hyper::http::Extensions;
 
 // Re-export our Body type alias
-pub use crate::context::Body;
+pub use crate::context::{Body, BodyError};
 
 // Builder patterns
 pub use hyper::http::{request::Builder as RequestBuilder, response::Builder as ResponseBuilder};
@@ -118,23 +118,23 @@ pub mod body {
     use std::task::{Context, Poll};
 
     /// Create an empty body
-    pub fn empty() -> BoxBody<Bytes, std::convert::Infallible> {
-        Empty::<Bytes>::new().boxed()
+    pub fn empty() -> Body {
+        crate::context::empty_body()
     }
 
     /// Create a body from bytes
-    pub fn from_bytes(bytes: impl Into<Bytes>) -> BoxBody<Bytes, std::convert::Infallible> {
-        Full::new(bytes.into()).boxed()
+    pub fn from_bytes(bytes: impl Into<Bytes>) -> Body {
+        crate::context::full_body(bytes)
     }
 
     /// Create a body from a string
-    pub fn from_string(s: String) -> BoxBody<Bytes, std::convert::Infallible> {
-        Full::new(Bytes::from(s)).boxed()
+    pub fn from_string(s: String) -> Body {
+        crate::context::full_body(s)
     }
 
     /// Create a body from a vector
-    pub fn from_vec(v: Vec<u8>) -> BoxBody<Bytes, std::convert::Infallible> {
-        Full::new(Bytes::from(v)).boxed()
+    pub fn from_vec(v: Vec<u8>) -> Body {
+        crate::context::full_body(v)
     }
 
     /// Stream body wrapper for custom streaming implementations
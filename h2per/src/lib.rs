@@ -3,23 +3,35 @@
 //! This crate provides Protocol trait implementations for all HTTP versions
 //! using the hyper library as the underlying engine.
 
+pub mod body;
 pub mod context;
+pub mod expect;
 pub mod hyper_exports;
 mod io_compat;
 pub mod message;
 pub mod prelude;
 pub mod protocol;
+pub mod raw;
 pub mod request;
+pub mod reset;
 pub mod response;
 mod service;
+pub mod sse;
 pub mod stream;
 pub mod transport;
+pub mod tunnel;
 pub mod upgrade;
 pub mod websocket;
 
 // Re-export protocol implementations
+pub use body::{BodyLimitExceeded, BodyStream, ReaderBody};
 pub use context::{HyperContext, HyperRequest, HyperResponse};
+pub use expect::BodyAdmission;
 pub use protocol::{HyperHttp1, HyperHttp2, HyperHttp3};
+pub use raw::{RawRecvStream, RawSendStream, RawStreamClosed};
+pub use reset::H2ErrorCode;
+pub use service::{ContentTypeRouter, StreamFuture, StreamService};
+pub use sse::{SSE_BUFFERED_EVENTS, SseClosed, SseEvent, SseSender};
 
 // Type aliases to distinguish from core HTTP implementation
 pub type HYPER1 = HyperHttp1;
@@ -11,7 +11,8 @@ pub use crate::{HYPER1, HYPER2, HYPER3};
 
 // Re-export context types
 pub use crate::context::{
-    Body, HttpVersion, HyperContext, HyperRequest, HyperResponse, switch_protocol_response,
+    Body, BodyError, HttpVersion, HyperContext, HyperRequest, HyperResponse, box_body, empty_body,
+    full_body, switch_protocol_response,
 };
 
 // Re-export request and response templates
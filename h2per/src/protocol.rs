@@ -1,23 +1,35 @@
 //! Hyper-based protocol implementations for HTTP/1, HTTP/2, and HTTP/3.
 
 use async_trait::async_trait;
+use hyper::Response;
+use hyper::header::{CONNECTION, HeaderValue};
+use hyper::http::Extensions;
 use hyper::server::conn::{http1, http2};
-use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper::service::Service;
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
 use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use hotaru_core::{
     app::application::App,
     connection::{Message, Protocol, ProtocolRole, Stream, TcpReader, TcpWriter, Transport},
+    protocol::Detection,
 };
 
 use crate::context::HyperContext;
+use crate::expect::BodyAdmission;
 use crate::io_compat::HyperIoCompat;
 use crate::message::{Http1Message, Http2Message, Http3Message};
-use crate::service::HotaruService;
+use crate::service::{ContentTypeRouter, HotaruService, StreamService};
 use crate::stream::{Http2Stream, Http3Stream};
 use crate::transport::{Http2Transport, Http3Transport, HyperTransport};
 
+/// HTTP/2 client connection preface.
+pub const HTTP2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
 // ============================================================================
 // HTTP/1.1 Protocol Implementation
 // ============================================================================
@@ -27,6 +39,9 @@ use crate::transport::{Http2Transport, Http3Transport, HyperTransport};
 pub struct HyperHttp1 {
     transport: HyperTransport,
     role: ProtocolRole,
+    admission: BodyAdmission,
+    keep_alive: bool,
+    max_requests_per_connection: Option<usize>,
 }
 
 impl HyperHttp1 {
@@ -34,8 +49,43 @@ impl HyperHttp1 {
         Self {
             transport: HyperTransport::new_http1(),
             role,
+            admission: BodyAdmission::default(),
+            keep_alive: true,
+            max_requests_per_connection: None,
         }
     }
+
+    /// Whether connections are reused for further requests (on by default).
+    ///
+    /// With keep-alive on, an HTTP/1.1 connection serves requests until
+    /// either side sends `Connection: close`, and an HTTP/1.0 one only if
+    /// the client asked for `Connection: keep-alive`. It is also closed
+    /// after an I/O or parse error, or a request whose body was rejected
+    /// unread. Turned off, every response ends its connection.
+    pub fn with_keep_alive(mut self, keep_alive: bool) -> Self {
+        self.keep_alive = keep_alive;
+        self
+    }
+
+    /// Most requests served on one kept-alive connection (unlimited by
+    /// default).
+    ///
+    /// The response to the last allowed request carries
+    /// `Connection: close` and the connection ends once it is written, so
+    /// a client cannot hold one connection open indefinitely. `0` is
+    /// treated as `1`.
+    pub fn with_max_requests_per_connection(mut self, max: usize) -> Self {
+        self.max_requests_per_connection = Some(max.max(1));
+        self
+    }
+
+    /// Method, content-type and size checks applied before a request body
+    /// is read. `Expect: 100-continue` requests failing them get the final
+    /// error status instead of `100 Continue`.
+    pub fn with_body_admission(mut self, admission: BodyAdmission) -> Self {
+        self.admission = admission;
+        self
+    }
 }
 
 #[async_trait]
@@ -45,19 +95,23 @@ impl Protocol for HyperHttp1 {
     type Message = Http1Message;
     type Context = HyperContext;
 
-    fn detect(initial_bytes: &[u8]) -> bool {
-        // Check for HTTP/1.x methods
-        initial_bytes.starts_with(b"GET ") ||
-        initial_bytes.starts_with(b"POST ") ||
-        initial_bytes.starts_with(b"PUT ") ||
-        initial_bytes.starts_with(b"DELETE ") ||
-        initial_bytes.starts_with(b"HEAD ") ||
-        initial_bytes.starts_with(b"OPTIONS ") ||
-        initial_bytes.starts_with(b"CONNECT ") ||
-        initial_bytes.starts_with(b"TRACE ") ||
-        initial_bytes.starts_with(b"PATCH ") ||
-        // Check for HTTP/1.x response
-        initial_bytes.starts_with(b"HTTP/1.")
+    fn detect(initial_bytes: &[u8]) -> Detection {
+        // Check for HTTP/1.x methods and HTTP/1.x responses
+        Detection::any_prefix(
+            initial_bytes,
+            &[
+                b"GET ",
+                b"POST ",
+                b"PUT ",
+                b"DELETE ",
+                b"HEAD ",
+                b"OPTIONS ",
+                b"CONNECT ",
+                b"TRACE ",
+                b"PATCH ",
+                b"HTTP/1.",
+            ],
+        )
     }
 
     fn role(&self) -> ProtocolRole {
@@ -76,10 +130,12 @@ impl Protocol for HyperHttp1 {
                 let io = TokioIo::new(HyperIoCompat::new_buffered(reader, writer));
 
                 // Create the service that will handle HTTP requests
-                let service = HotaruService::<HyperHttp1>::new(app, self.role);
+                let service = HotaruService::<HyperHttp1>::new(app, self.role)
+                    .with_body_admission(self.admission.clone());
+                let service = MaxRequests::new(service, self.max_requests_per_connection);
 
                 // Build the HTTP/1.1 connection handler
-                let conn = http1::Builder::new()
+                let conn = http1_server(self.keep_alive)
                     // Support HTTP upgrades (e.g., WebSocket)
                     .serve_connection(io, service)
                     .with_upgrades();
@@ -101,6 +157,65 @@ impl Protocol for HyperHttp1 {
     }
 }
 
+/// Server-side HTTP/1.1 connection settings.
+///
+/// The timer lets hyper bound how long an idle kept-alive connection may
+/// take to send the next request head, so reuse never pins a connection
+/// forever.
+pub(crate) fn http1_server(keep_alive: bool) -> http1::Builder {
+    let mut builder = http1::Builder::new();
+    builder.keep_alive(keep_alive).timer(TokioTimer::new());
+    builder
+}
+
+/// Ends an HTTP/1.1 connection after a number of requests.
+///
+/// Wraps the service of a single connection. The response to request
+/// number `max` gets `Connection: close`, which makes hyper close the
+/// connection after writing it.
+pub(crate) struct MaxRequests<S> {
+    inner: S,
+    max: Option<usize>,
+    served: AtomicUsize,
+}
+
+impl<S> MaxRequests<S> {
+    pub(crate) fn new(inner: S, max: Option<usize>) -> Self {
+        Self {
+            inner,
+            max,
+            served: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<S, R, B> Service<R> for MaxRequests<S>
+where
+    S: Service<R, Response = Response<B>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = Response<B>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response<B>, S::Error>> + Send>>;
+
+    fn call(&self, req: R) -> Self::Future {
+        let served = self.served.fetch_add(1, Ordering::Relaxed) + 1;
+        let last = self.max.is_some_and(|max| served >= max);
+        let response = self.inner.call(req);
+        Box::pin(async move {
+            let mut response = response.await?;
+            if last {
+                response
+                    .headers_mut()
+                    .insert(CONNECTION, HeaderValue::from_static("close"));
+            }
+            Ok(response)
+        })
+    }
+}
+
 // ============================================================================
 // HTTP/2 Protocol Implementation
 // ============================================================================
@@ -110,6 +225,8 @@ impl Protocol for HyperHttp1 {
 pub struct HyperHttp2 {
     transport: Http2Transport,
     role: ProtocolRole,
+    content_type_routes: Vec<(String, StreamService)>,
+    request_extensions: Extensions,
 }
 
 impl HyperHttp2 {
@@ -117,8 +234,38 @@ impl HyperHttp2 {
         Self {
             transport: Http2Transport::new(),
             role,
+            content_type_routes: Vec::new(),
+            request_extensions: Extensions::new(),
         }
     }
+
+    /// Hands streams whose `Content-Type` starts with `prefix` to `service`
+    ///
+    /// Decided per stream, so a client can mix these with requests for the
+    /// app's own HTTP/2 routes on one connection. See [`ContentTypeRouter`].
+    pub fn route_content_type(mut self, prefix: impl Into<String>, service: StreamService) -> Self {
+        self.content_type_routes.push((prefix.into(), service));
+        self
+    }
+
+    /// Puts `value` in the extensions of every request on this protocol
+    ///
+    /// For settings the layers above read per request, whichever route the
+    /// request takes. A value of the same type set earlier is replaced.
+    pub fn with_request_extension<T: Clone + Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.request_extensions.insert(value);
+        self
+    }
+
+    /// The service each stream of a connection goes through, routing by
+    /// content type and handing the rest to `fallback`
+    pub fn stream_router<S>(&self, fallback: S) -> ContentTypeRouter<S> {
+        self.content_type_routes.iter().fold(
+            ContentTypeRouter::new(fallback)
+                .with_request_extensions(self.request_extensions.clone()),
+            |router, (prefix, routed)| router.route(prefix.clone(), routed.clone()),
+        )
+    }
 }
 
 #[async_trait]
@@ -128,15 +275,25 @@ impl Protocol for HyperHttp2 {
     type Message = Http2Message;
     type Context = HyperContext;
 
-    fn detect(initial_bytes: &[u8]) -> bool {
+    fn detect(initial_bytes: &[u8]) -> Detection {
         // Check for HTTP/2 connection preface
         // "PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n"
-        initial_bytes.starts_with(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n") ||
+        let preface = Detection::prefix(initial_bytes, HTTP2_PREFACE);
+
         // Check for direct HTTP/2 over TLS (ALPN negotiated)
         // This would be handled by TLS layer, but we check for HTTP/2 frames
-        (initial_bytes.len() >= 9 && 
-         initial_bytes[0..3] == [0x00, 0x00, 0x00] && // Frame length
-         initial_bytes[3] <= 0x0A) // Valid frame type (0x00-0x0A)
+        // (9-byte header: zero length prefix, valid frame type 0x00-0x0A)
+        let header_ok = initial_bytes.iter().take(3).all(|b| *b == 0x00)
+            && initial_bytes.get(3).is_none_or(|t| *t <= 0x0A);
+        let frame = if initial_bytes.is_empty() || !header_ok {
+            Detection::NoMatch
+        } else if initial_bytes.len() < 9 {
+            Detection::NeedMoreData
+        } else {
+            Detection::Match
+        };
+
+        preface.or(frame)
     }
 
     fn role(&self) -> ProtocolRole {
@@ -155,7 +312,7 @@ impl Protocol for HyperHttp2 {
                 let io = TokioIo::new(HyperIoCompat::new_buffered(reader, writer));
 
                 // Create the service that will handle HTTP/2 requests
-                let service = HotaruService::<HyperHttp2>::new(app, self.role);
+                let service = self.stream_router(HotaruService::<HyperHttp2>::new(app, self.role));
 
                 // Build the HTTP/2 connection handler
                 let mut h2_builder = http2::Builder::new(TokioExecutor::new());
@@ -164,11 +321,9 @@ impl Protocol for HyperHttp2 {
                 h2_builder
                     .initial_stream_window_size(1024 * 1024)
                     .initial_connection_window_size(1024 * 1024)
-                    .max_concurrent_streams(100);
-
-                // Enable Extended CONNECT for WebSocket over HTTP/2
-                // Note: Extended CONNECT support in Hyper is still evolving
-                // This prepares for RFC 8441 support
+                    .max_concurrent_streams(100)
+                    // Extended CONNECT for WebSocket over HTTP/2 (RFC 8441)
+                    .enable_connect_protocol();
 
                 let conn = h2_builder.serve_connection(io, service);
 
@@ -216,11 +371,11 @@ impl Protocol for HyperHttp3 {
     type Message = Http3Message;
     type Context = HyperContext;
 
-    fn detect(initial_bytes: &[u8]) -> bool {
+    fn detect(_initial_bytes: &[u8]) -> Detection {
         // HTTP/3 runs over QUIC, not TCP
         // This would typically be detected at the transport layer
-        // For now, return false as HTTP/3 detection needs QUIC transport
-        false
+        // For now, never match as HTTP/3 detection needs QUIC transport
+        Detection::NoMatch
     }
 
     fn role(&self) -> ProtocolRole {
@@ -243,3 +398,121 @@ impl Protocol for HyperHttp3 {
         unimplemented!("HTTP/3 requires QUIC transport, not TCP - implementation pending")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use http_body_util::Full;
+    use hyper::Request;
+    use hyper::body::Incoming;
+    use hyper::service::service_fn;
+    use std::convert::Infallible;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+    /// Serves one connection, answering each request with its position on it
+    async fn serve(io: DuplexStream, keep_alive: bool) {
+        serve_limited(io, keep_alive, None).await;
+    }
+
+    async fn serve_limited(io: DuplexStream, keep_alive: bool, max_requests: Option<usize>) {
+        let served = Arc::new(AtomicUsize::new(0));
+        let service = service_fn(move |_req: Request<Incoming>| {
+            let n = served.fetch_add(1, Ordering::SeqCst) + 1;
+            async move { Ok::<_, Infallible>(Response::new(Full::new(Bytes::from(format!("#{n}"))))) }
+        });
+        let _ = http1_server(keep_alive)
+            .serve_connection(TokioIo::new(io), MaxRequests::new(service, max_requests))
+            .await;
+    }
+
+    /// Sends `request` and reads until its body `#n` has arrived
+    async fn exchange(client: &mut DuplexStream, request: &[u8], n: usize) -> String {
+        client.write_all(request).await.unwrap();
+        let expected = format!("#{n}");
+        let mut response = String::new();
+        let mut buf = [0u8; 1024];
+        while !response.ends_with(&expected) {
+            let read = client.read(&mut buf).await.unwrap();
+            assert!(read > 0, "connection closed early: {:?}", response);
+            response.push_str(&String::from_utf8_lossy(&buf[..read]));
+        }
+        response
+    }
+
+    async fn closed(client: &mut DuplexStream) -> bool {
+        let mut buf = [0u8; 16];
+        matches!(client.read(&mut buf).await, Ok(0))
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_serves_sequential_requests_on_one_connection() {
+        let (mut client, server) = tokio::io::duplex(4096);
+        tokio::spawn(serve(server, true));
+
+        let request = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: keep-alive\r\n\r\n";
+        let first = exchange(&mut client, request, 1).await;
+        assert!(first.starts_with("HTTP/1.1 200 OK\r\n"), "got {:?}", first);
+        assert!(!first.to_ascii_lowercase().contains("connection: close"));
+
+        // Same socket, next request on it
+        let second = exchange(&mut client, b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n", 2).await;
+        assert!(
+            second.starts_with("HTTP/1.1 200 OK\r\n"),
+            "got {:?}",
+            second
+        );
+
+        // The client asking to close ends it after the response
+        let last = b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n";
+        exchange(&mut client, last, 3).await;
+        assert!(closed(&mut client).await);
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_off_closes_after_each_response() {
+        let (mut client, server) = tokio::io::duplex(4096);
+        tokio::spawn(serve(server, false));
+
+        let request = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        exchange(&mut client, request, 1).await;
+        assert!(closed(&mut client).await);
+    }
+
+    #[tokio::test]
+    async fn test_http10_keep_alive_is_opt_in() {
+        let (mut client, server) = tokio::io::duplex(4096);
+        tokio::spawn(serve(server, true));
+
+        let request = b"GET / HTTP/1.0\r\nConnection: keep-alive\r\n\r\n";
+        let first = exchange(&mut client, request, 1).await;
+        assert!(
+            first
+                .to_ascii_lowercase()
+                .contains("connection: keep-alive")
+        );
+        exchange(&mut client, b"GET / HTTP/1.0\r\n\r\n", 2).await;
+        assert!(closed(&mut client).await);
+    }
+
+    #[tokio::test]
+    async fn test_max_requests_closes_connection() {
+        let (mut client, server) = tokio::io::duplex(4096);
+        tokio::spawn(serve_limited(server, true, Some(2)));
+
+        let request = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let first = exchange(&mut client, request, 1).await;
+        assert!(!first.to_ascii_lowercase().contains("connection: close"));
+
+        let second = exchange(&mut client, request, 2).await;
+        assert!(
+            second.to_ascii_lowercase().contains("connection: close"),
+            "got {:?}",
+            second
+        );
+
+        // A third request finds the connection already closed
+        let _ = client.write_all(request).await;
+        assert!(closed(&mut client).await);
+    }
+}
@@ -0,0 +1,319 @@
+//! Raw access to an HTTP/2 stream.
+//!
+//! For handlers the request/response API does not cover, such as custom
+//! framing or exact control over when each frame goes out.
+//! [`HyperContext::raw_h2_stream`](crate::HyperContext::raw_h2_stream)
+//! takes the stream out of the context as two handles shaped like `h2`'s:
+//!
+//! - [`RawSendStream`] writes the response body one frame at a time. Each
+//!   [`send_data`](RawSendStream::send_data) is handed to the connection as
+//!   its own DATA frame and only returns once the connection has taken it.
+//! - [`RawRecvStream`] reads the request body frame by frame and its
+//!   trailers.
+//!
+//! hyper drives the `h2` connection and keeps its `SendStream` and
+//! `RecvStream` to itself, so these handles sit on the body of the stream
+//! rather than on `h2` directly: flow control, frame size limits and
+//! `WINDOW_UPDATE`s are still hyper's, and a chunk larger than the peer's
+//! frame size or window is split by it. Response headers are not part of
+//! them; they go out from the context as usual.
+//!
+//! # Misuse
+//!
+//! These handles bypass the response body of the context, so they are easy
+//! to get wrong:
+//!
+//! - The response, headers included, is only sent once the handler
+//!   returns, and a send waits for the connection. Awaiting a send inside
+//!   the handler therefore never finishes. Move the [`RawSendStream`] into
+//!   a spawned task and return.
+//! - Setting a body on the response afterwards replaces the raw one, and
+//!   the sends fail with [`RawStreamClosed`].
+//! - Nothing checks what is sent: a `Content-Length` that does not match
+//!   the data sent makes the peer reset the stream.
+//!
+//! ```rust,ignore
+//! endpoint! {
+//!     APP.url("/ticks"),
+//!     pub ticks <HYPER2> {
+//!         let Some((mut send, _recv)) = req.raw_h2_stream() else {
+//!             return req;
+//!         };
+//!         tokio::spawn(async move {
+//!             for tick in 0..10u8 {
+//!                 send.send_data(Bytes::from(vec![tick]), false).await?;
+//!                 tokio::time::sleep(Duration::from_millis(100)).await;
+//!             }
+//!             send.send_data(Bytes::new(), true).await
+//!         });
+//!         req
+//!     }
+//! }
+//! ```
+
+use std::convert::Infallible;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use http_body::{Body as _, Frame};
+use http_body_util::BodyExt;
+use hyper::HeaderMap;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::context::{Body, box_body};
+
+/// The response body behind a [`RawSendStream`] is gone, or the stream was
+/// already ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawStreamClosed;
+
+impl fmt::Display for RawStreamClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HTTP/2 stream is closed")
+    }
+}
+
+impl std::error::Error for RawStreamClosed {}
+
+/// One frame on its way to the connection.
+struct RawFrame {
+    frame: Frame<Bytes>,
+    end_of_stream: bool,
+    taken: oneshot::Sender<()>,
+}
+
+/// Writing half of a raw HTTP/2 stream.
+///
+/// Dropping it ends the stream with an empty DATA frame if it was not
+/// ended yet.
+pub struct RawSendStream {
+    tx: mpsc::Sender<RawFrame>,
+    stream_id: Option<u32>,
+    ended: bool,
+}
+
+impl RawSendStream {
+    /// The stream's id, if the connection recorded one.
+    pub fn stream_id(&self) -> Option<u32> {
+        self.stream_id
+    }
+
+    /// Sends `data` as one DATA frame, ending the stream if
+    /// `end_of_stream` is set.
+    ///
+    /// Returns once the connection has taken the frame, so consecutive
+    /// sends never share a frame, and a send waits while the peer's
+    /// flow-control window is closed.
+    pub async fn send_data(
+        &mut self,
+        data: Bytes,
+        end_of_stream: bool,
+    ) -> Result<(), RawStreamClosed> {
+        self.send(Frame::data(data), end_of_stream).await
+    }
+
+    /// Sends `trailers` as the final HEADERS frame, ending the stream.
+    pub async fn send_trailers(mut self, trailers: HeaderMap) -> Result<(), RawStreamClosed> {
+        self.send(Frame::trailers(trailers), true).await
+    }
+
+    /// Whether the stream has been ended.
+    pub fn is_end_stream(&self) -> bool {
+        self.ended || self.tx.is_closed()
+    }
+
+    async fn send(
+        &mut self,
+        frame: Frame<Bytes>,
+        end_of_stream: bool,
+    ) -> Result<(), RawStreamClosed> {
+        if self.ended {
+            return Err(RawStreamClosed);
+        }
+        let (taken, on_taken) = oneshot::channel();
+        let frame = RawFrame {
+            frame,
+            end_of_stream,
+            taken,
+        };
+        self.tx.send(frame).await.map_err(|_| RawStreamClosed)?;
+        self.ended = end_of_stream;
+        on_taken.await.map_err(|_| RawStreamClosed)
+    }
+}
+
+/// Response body fed by a [`RawSendStream`].
+struct RawBody {
+    rx: mpsc::Receiver<RawFrame>,
+    done: bool,
+}
+
+impl http_body::Body for RawBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        match self.rx.poll_recv(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(None) => {
+                self.done = true;
+                Poll::Ready(None)
+            }
+            Poll::Ready(Some(raw)) => {
+                let _ = raw.taken.send(());
+                if raw.end_of_stream {
+                    self.done = true;
+                    self.rx.close();
+                }
+                Poll::Ready(Some(Ok(raw.frame)))
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.done
+    }
+}
+
+/// Reading half of a raw HTTP/2 stream.
+pub struct RawRecvStream {
+    body: Body,
+    trailers: Option<HeaderMap>,
+    stream_id: Option<u32>,
+}
+
+impl RawRecvStream {
+    /// The stream's id, if the connection recorded one.
+    pub fn stream_id(&self) -> Option<u32> {
+        self.stream_id
+    }
+
+    /// The next chunk of request data, or `None` once the body has ended or
+    /// failed.
+    ///
+    /// Chunks arrive as the peer sends them, unless the handler already
+    /// read the body with [`HyperRequest::body`](crate::HyperRequest::body).
+    pub async fn data(&mut self) -> Option<Bytes> {
+        while let Some(frame) = self.body.frame().await {
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(_) => return None,
+            };
+            match frame.into_data() {
+                Ok(data) => return Some(data),
+                Err(frame) => {
+                    if let Ok(trailers) = frame.into_trailers() {
+                        self.trailers = Some(trailers);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// The request trailers, if any, skipping the data not yet read.
+    pub async fn trailers(&mut self) -> Option<HeaderMap> {
+        while self.data().await.is_some() {}
+        self.trailers.take()
+    }
+
+    /// Whether the request body has ended.
+    pub fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+}
+
+/// Raw handles for a stream whose request body is `body`, and the response
+/// body the send half writes to.
+pub(crate) fn raw_stream(
+    body: Body,
+    trailers: Option<HeaderMap>,
+    stream_id: Option<u32>,
+) -> (RawSendStream, RawRecvStream, Body) {
+    let (tx, rx) = mpsc::channel(1);
+    let send = RawSendStream {
+        tx,
+        stream_id,
+        ended: false,
+    };
+    let recv = RawRecvStream {
+        body,
+        trailers,
+        stream_id,
+    };
+    let response = box_body(RawBody { rx, done: false });
+    (send, recv, response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{HyperContext, empty_body, full_body};
+    use hyper::body::Incoming;
+    use hyper::server::conn::http2;
+    use hyper::service::service_fn;
+    use hyper::{Request, Response};
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+
+    async fn handle(request: Request<Incoming>) -> Result<Response<Body>, Infallible> {
+        let (parts, body) = request.into_parts();
+        let body = body.collect().await.unwrap().to_bytes();
+        let request = Request::from_parts(parts, full_body(body));
+        let mut ctx = HyperContext::new_client(request);
+
+        let (mut send, mut recv) = ctx.raw_h2_stream().unwrap();
+        tokio::spawn(async move {
+            let ping = recv.data().await.unwrap();
+            send.send_data(ping, false).await.unwrap();
+            send.send_data(Bytes::from_static(b" pong"), false)
+                .await
+                .unwrap();
+            let mut trailers = HeaderMap::new();
+            trailers.insert("x-frames", "2".parse().unwrap());
+            send.send_trailers(trailers).await.unwrap();
+        });
+        Ok(ctx.response.into_inner())
+    }
+
+    #[tokio::test]
+    async fn test_raw_stream_writes_response_frames() {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(
+            http2::Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(server_io), service_fn(handle)),
+        );
+        let (client, connection) = h2::client::handshake(client_io).await.unwrap();
+        tokio::spawn(connection);
+        let mut client = client.ready().await.unwrap();
+
+        let request = Request::post("http://localhost/raw").body(()).unwrap();
+        let (response, mut request_body) = client.send_request(request, false).unwrap();
+        request_body
+            .send_data(Bytes::from_static(b"ping"), true)
+            .unwrap();
+
+        // Each send arrives as a DATA frame of its own, then the trailers
+        let mut body = response.await.unwrap().into_body();
+        let first = body.data().await.unwrap().unwrap();
+        assert_eq!(first, "ping");
+        let _ = body.flow_control().release_capacity(first.len());
+        let second = body.data().await.unwrap().unwrap();
+        assert_eq!(second, " pong");
+        let _ = body.flow_control().release_capacity(second.len());
+        assert!(body.data().await.is_none());
+        let trailers = body.trailers().await.unwrap().unwrap();
+        assert_eq!(trailers["x-frames"], "2");
+
+        // Only HTTP/2 requests have a stream to hand out
+        let request = Request::get("/raw").body(empty_body()).unwrap();
+        assert!(HyperContext::new_client(request).raw_h2_stream().is_none());
+    }
+}
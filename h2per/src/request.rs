@@ -1,9 +1,7 @@
 //! Request templates for Hyper - convenient request builders
 
 pub mod request_templates {
-    use crate::context::Body;
-    use bytes::Bytes;
-    use http_body_util::{BodyExt, Empty, Full};
+    use crate::context::{Body, empty_body, full_body};
     use hyper::{Method, Request};
     use std::collections::HashMap;
 
@@ -13,14 +11,14 @@ pub mod request_templates {
             .method(Method::GET)
             .uri(uri.into())
             .header("user-agent", "h2per/0.1.0")
-            .body(Empty::<Bytes>::new().boxed())
+            .body(empty_body())
             .unwrap()
     }
 
     /// Create a POST request with JSON body
     pub fn json_post<S: Into<String>, T: serde::Serialize>(uri: S, data: T) -> Request<Body> {
         let json = serde_json::to_vec(&data).unwrap();
-        let body = Full::new(Bytes::from(json)).boxed();
+        let body = full_body(json);
 
         Request::builder()
             .method(Method::POST)
@@ -34,7 +32,7 @@ pub mod request_templates {
     /// Create a POST request with form data
     pub fn form_post<S: Into<String>>(uri: S, form_data: HashMap<String, String>) -> Request<Body> {
         let encoded = url_encode_form(&form_data);
-        let body = Full::new(Bytes::from(encoded)).boxed();
+        let body = full_body(encoded);
 
         Request::builder()
             .method(Method::POST)
@@ -48,7 +46,7 @@ pub mod request_templates {
     /// Create a PUT request with JSON body
     pub fn json_put<S: Into<String>, T: serde::Serialize>(uri: S, data: T) -> Request<Body> {
         let json = serde_json::to_vec(&data).unwrap();
-        let body = Full::new(Bytes::from(json)).boxed();
+        let body = full_body(json);
 
         Request::builder()
             .method(Method::PUT)
@@ -65,14 +63,14 @@ pub mod request_templates {
             .method(Method::DELETE)
             .uri(uri.into())
             .header("user-agent", "h2per/0.1.0")
-            .body(Empty::<Bytes>::new().boxed())
+            .body(empty_body())
             .unwrap()
     }
 
     /// Create a PATCH request with JSON body
     pub fn json_patch<S: Into<String>, T: serde::Serialize>(uri: S, data: T) -> Request<Body> {
         let json = serde_json::to_vec(&data).unwrap();
-        let body = Full::new(Bytes::from(json)).boxed();
+        let body = full_body(json);
 
         Request::builder()
             .method(Method::PATCH)
@@ -89,7 +87,7 @@ pub mod request_templates {
             .method(Method::HEAD)
             .uri(uri.into())
             .header("user-agent", "h2per/0.1.0")
-            .body(Empty::<Bytes>::new().boxed())
+            .body(empty_body())
             .unwrap()
     }
 
@@ -101,9 +99,9 @@ pub mod request_templates {
     ) -> Request<Body> {
         let bytes = body.into();
         let body = if bytes.is_empty() {
-            Empty::<Bytes>::new().boxed()
+            empty_body()
         } else {
-            Full::new(Bytes::from(bytes)).boxed()
+            full_body(bytes)
         };
 
         Request::builder()
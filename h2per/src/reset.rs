@@ -0,0 +1,181 @@
+//! HTTP/2 `RST_STREAM` error codes.
+//!
+//! A peer that gives up on a stream resets it with an error code, and the
+//! request body read then fails. [`reset_reason`] digs the code out of that
+//! error so the service can log it and hand it to the handler through
+//! [`HyperRequest::reset_reason`](crate::HyperRequest::reset_reason).
+
+use std::error::Error;
+use std::fmt;
+
+/// Error code carried by an HTTP/2 `RST_STREAM` frame (RFC 9113, section 7)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum H2ErrorCode {
+    NoError,
+    ProtocolError,
+    InternalError,
+    FlowControlError,
+    SettingsTimeout,
+    StreamClosed,
+    FrameSizeError,
+    RefusedStream,
+    Cancel,
+    CompressionError,
+    ConnectError,
+    EnhanceYourCalm,
+    InadequateSecurity,
+    Http11Required,
+    /// A code this list does not know; peers must treat it as `INTERNAL_ERROR`
+    Unknown(u32),
+}
+
+impl H2ErrorCode {
+    pub fn from_u32(code: u32) -> Self {
+        match code {
+            0x0 => Self::NoError,
+            0x1 => Self::ProtocolError,
+            0x2 => Self::InternalError,
+            0x3 => Self::FlowControlError,
+            0x4 => Self::SettingsTimeout,
+            0x5 => Self::StreamClosed,
+            0x6 => Self::FrameSizeError,
+            0x7 => Self::RefusedStream,
+            0x8 => Self::Cancel,
+            0x9 => Self::CompressionError,
+            0xa => Self::ConnectError,
+            0xb => Self::EnhanceYourCalm,
+            0xc => Self::InadequateSecurity,
+            0xd => Self::Http11Required,
+            other => Self::Unknown(other),
+        }
+    }
+
+    /// The code as sent on the wire
+    pub fn as_u32(self) -> u32 {
+        match self {
+            Self::NoError => 0x0,
+            Self::ProtocolError => 0x1,
+            Self::InternalError => 0x2,
+            Self::FlowControlError => 0x3,
+            Self::SettingsTimeout => 0x4,
+            Self::StreamClosed => 0x5,
+            Self::FrameSizeError => 0x6,
+            Self::RefusedStream => 0x7,
+            Self::Cancel => 0x8,
+            Self::CompressionError => 0x9,
+            Self::ConnectError => 0xa,
+            Self::EnhanceYourCalm => 0xb,
+            Self::InadequateSecurity => 0xc,
+            Self::Http11Required => 0xd,
+            Self::Unknown(code) => code,
+        }
+    }
+}
+
+impl From<h2::Reason> for H2ErrorCode {
+    fn from(reason: h2::Reason) -> Self {
+        Self::from_u32(reason.into())
+    }
+}
+
+impl fmt::Display for H2ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::NoError => "NO_ERROR",
+            Self::ProtocolError => "PROTOCOL_ERROR",
+            Self::InternalError => "INTERNAL_ERROR",
+            Self::FlowControlError => "FLOW_CONTROL_ERROR",
+            Self::SettingsTimeout => "SETTINGS_TIMEOUT",
+            Self::StreamClosed => "STREAM_CLOSED",
+            Self::FrameSizeError => "FRAME_SIZE_ERROR",
+            Self::RefusedStream => "REFUSED_STREAM",
+            Self::Cancel => "CANCEL",
+            Self::CompressionError => "COMPRESSION_ERROR",
+            Self::ConnectError => "CONNECT_ERROR",
+            Self::EnhanceYourCalm => "ENHANCE_YOUR_CALM",
+            Self::InadequateSecurity => "INADEQUATE_SECURITY",
+            Self::Http11Required => "HTTP_1_1_REQUIRED",
+            Self::Unknown(code) => return write!(f, "UNKNOWN(0x{:x})", code),
+        };
+        f.write_str(name)
+    }
+}
+
+/// The `RST_STREAM` code behind `err`, if the stream was reset
+///
+/// Walks the source chain, so the `hyper::Error` of a failed body read
+/// works as well as a bare `h2::Error`. Connection-wide `GOAWAY` errors
+/// are not resets and give `None`.
+pub fn reset_reason(err: &(dyn Error + 'static)) -> Option<H2ErrorCode> {
+    let mut current = Some(err);
+    while let Some(err) = current {
+        if let Some(h2_err) = err.downcast_ref::<h2::Error>() {
+            return match h2_err.reason() {
+                Some(reason) if h2_err.is_reset() => Some(reason.into()),
+                _ => None,
+            };
+        }
+        current = err.source();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::buffer;
+    use crate::context::{HyperContext, full_body};
+    use bytes::Bytes;
+    use hyper::body::Incoming;
+    use hyper::server::conn::http2;
+    use hyper::service::service_fn;
+    use hyper::{Request, Response};
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use std::convert::Infallible;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_codes_round_trip() {
+        for code in 0..=0xe {
+            assert_eq!(H2ErrorCode::from_u32(code).as_u32(), code);
+        }
+        assert_eq!(H2ErrorCode::from(h2::Reason::CANCEL), H2ErrorCode::Cancel);
+        assert_eq!(H2ErrorCode::Cancel.to_string(), "CANCEL");
+    }
+
+    #[tokio::test]
+    async fn test_handler_reads_cancel_reason() {
+        let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+        let (seen_tx, mut seen_rx) = mpsc::unbounded_channel();
+
+        // Reads the body the way the service does, then reports what a
+        // handler would see
+        let service = service_fn(move |req: Request<Incoming>| {
+            let seen_tx = seen_tx.clone();
+            async move {
+                let (parts, body) = req.into_parts();
+                let buffered = buffer(body, None).await.unwrap();
+                let request = Request::from_parts(parts, full_body(buffered.bytes));
+                let mut ctx = HyperContext::new_client(request);
+                ctx.request.set_reset_reason(buffered.reset);
+                seen_tx.send(ctx.request.reset_reason()).unwrap();
+                Ok::<_, Infallible>(Response::new(full_body(Bytes::new())))
+            }
+        });
+        tokio::spawn(
+            http2::Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(server_io), service),
+        );
+
+        let (client, connection) = h2::client::handshake(client_io).await.unwrap();
+        tokio::spawn(connection);
+        let mut client = client.ready().await.unwrap();
+        let request = Request::post("http://localhost/upload").body(()).unwrap();
+        let (_response, mut body) = client.send_request(request, false).unwrap();
+        body.send_data(Bytes::from_static(b"partial"), false)
+            .unwrap();
+        body.send_reset(h2::Reason::CANCEL);
+
+        assert_eq!(seen_rx.recv().await, Some(Some(H2ErrorCode::Cancel)));
+    }
+}
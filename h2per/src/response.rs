@@ -2,15 +2,15 @@
 
 pub mod response_templates {
     use crate::HyperResponse;
-    use crate::context::Body;
-    use bytes::Bytes;
-    use http_body_util::{BodyExt, Empty, Full};
+    use crate::body::ReaderBody;
+    use crate::context::{box_body, empty_body, full_body};
     use hyper::{Response, StatusCode};
+    use tokio::io::AsyncRead;
 
     /// Create a text response
     pub fn text_response<S: Into<String>>(text: S) -> HyperResponse {
         let text = text.into();
-        let body = Full::new(Bytes::from(text)).boxed();
+        let body = full_body(text);
 
         HyperResponse {
             inner: Response::builder()
@@ -26,7 +26,7 @@ pub mod response_templates {
         let json = serde_json::to_vec(&data).unwrap_or_else(|e| {
             format!("{{\"error\": \"JSON serialization failed: {}\"}}", e).into_bytes()
         });
-        let body = Full::new(Bytes::from(json)).boxed();
+        let body = full_body(json);
 
         HyperResponse {
             inner: Response::builder()
@@ -40,7 +40,7 @@ pub mod response_templates {
     /// Create an HTML response
     pub fn html_response<S: Into<String>>(html: S) -> HyperResponse {
         let html = html.into();
-        let body = Full::new(Bytes::from(html)).boxed();
+        let body = full_body(html);
 
         HyperResponse {
             inner: Response::builder()
@@ -51,10 +51,28 @@ pub mod response_templates {
         }
     }
 
+    /// Create a response streaming everything `reader` produces
+    ///
+    /// The body is sent chunk by chunk as it is read; see [`ReaderBody`]
+    /// for back-pressure and what a failed read does. An invalid
+    /// `content_type` is left out.
+    pub fn reader_response<R>(reader: R, content_type: &str) -> HyperResponse
+    where
+        R: AsyncRead + Send + Sync + 'static,
+    {
+        HyperResponse {
+            inner: Response::builder()
+                .status(StatusCode::OK)
+                .body(box_body(ReaderBody::new(reader)))
+                .unwrap(),
+        }
+        .with_header("content-type", content_type)
+    }
+
     /// Create a response with custom status and body
     pub fn normal_response<B: Into<Vec<u8>>>(status: StatusCode, body: B) -> HyperResponse {
         let bytes = body.into();
-        let body = Full::new(Bytes::from(bytes)).boxed();
+        let body = full_body(bytes);
 
         HyperResponse {
             inner: Response::builder().status(status).body(body).unwrap(),
@@ -69,7 +87,7 @@ pub mod response_templates {
             inner: Response::builder()
                 .status(StatusCode::SEE_OTHER)
                 .header("location", location)
-                .body(Empty::<Bytes>::new().boxed())
+                .body(empty_body())
                 .unwrap(),
         }
     }
@@ -80,7 +98,7 @@ pub mod response_templates {
             inner: Response::builder()
                 .status(StatusCode::NOT_FOUND)
                 .header("content-type", "text/plain; charset=utf-8")
-                .body(Full::new(Bytes::from("404 Not Found")).boxed())
+                .body(full_body("404 Not Found"))
                 .unwrap(),
         }
     }
@@ -88,7 +106,7 @@ pub mod response_templates {
     /// Create a 500 Internal Server Error response
     pub fn server_error_response<S: Into<String>>(message: S) -> HyperResponse {
         let message = message.into();
-        let body = Full::new(Bytes::from(message)).boxed();
+        let body = full_body(message);
 
         HyperResponse {
             inner: Response::builder()
@@ -106,7 +124,7 @@ pub mod response_templates {
                 .status(StatusCode::UNAUTHORIZED)
                 .header("content-type", "text/plain; charset=utf-8")
                 .header("www-authenticate", "Basic realm=\"Restricted\"")
-                .body(Full::new(Bytes::from("401 Unauthorized")).boxed())
+                .body(full_body("401 Unauthorized"))
                 .unwrap(),
         }
     }
@@ -117,7 +135,7 @@ pub mod response_templates {
             inner: Response::builder()
                 .status(StatusCode::FORBIDDEN)
                 .header("content-type", "text/plain; charset=utf-8")
-                .body(Full::new(Bytes::from("403 Forbidden")).boxed())
+                .body(full_body("403 Forbidden"))
                 .unwrap(),
         }
     }
@@ -127,7 +145,7 @@ pub mod response_templates {
         HyperResponse {
             inner: Response::builder()
                 .status(StatusCode::OK)
-                .body(Empty::<Bytes>::new().boxed())
+                .body(empty_body())
                 .unwrap(),
         }
     }
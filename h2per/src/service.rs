@@ -6,40 +6,136 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-use bytes::Bytes;
-use http_body_util::{BodyExt, Empty, Full};
 use hyper::body::Incoming;
+use hyper::header::CONTENT_TYPE;
+use hyper::http::Extensions;
 use hyper::service::Service;
-use hyper::{Request, Response, StatusCode};
+use hyper::{HeaderMap, Request, Response, StatusCode};
 
-use hotaru_core::{app::application::App, connection::ProtocolRole};
+use hotaru_core::{app::application::App, connection::ProtocolRole, url::Url};
 
-use crate::context::{Body, HyperContext};
+use crate::context::{Body, HyperContext, HyperResponse, box_body, empty_body, full_body};
+use crate::expect::{BodyAdmission, admit_body};
 use crate::upgrade::manager::{UpgradeManager, UpgradeResult};
+use crate::websocket::{
+    WebSocketOnly, is_http2_websocket_upgrade_generic, is_websocket_upgrade_generic,
+    upgrade_required_response,
+};
+
+/// Runs a request's context through the handler it is routed to
+pub(crate) type Dispatch =
+    Arc<dyn Fn(HyperContext) -> Pin<Box<dyn Future<Output = HyperContext> + Send>> + Send + Sync>;
 
 /// Service that routes Hyper requests through Hotaru's handler system
 ///
 /// Generic over the protocol type to support HTTP/1, HTTP/2, HTTP/3, etc.
+/// Handlers get the request body unread; it is only buffered once they ask
+/// for it with [`HyperRequest::body`](crate::HyperRequest::body).
 pub struct HotaruService<P> {
-    app: Arc<App>,
+    app: Option<Arc<App>>,
+    dispatch: Dispatch,
     role: ProtocolRole,
     upgrade_manager: Arc<UpgradeManager>,
+    admission: Arc<BodyAdmission>,
     _protocol: std::marker::PhantomData<P>,
 }
 
-impl<P> HotaruService<P> {
+impl<P> HotaruService<P>
+where
+    P: Protocol<Context = HyperContext> + 'static,
+{
     pub fn new(app: Arc<App>, role: ProtocolRole) -> Self {
+        let router = app.clone();
+        let dispatch: Dispatch = Arc::new(move |ctx| Box::pin(route::<P>(router.clone(), ctx)));
+        Self::with_dispatch(Some(app), dispatch, role)
+    }
+}
+
+impl<P> HotaruService<P> {
+    /// A service handing every request to `dispatch` instead of walking
+    /// an app's URL tree
+    pub(crate) fn with_dispatch(
+        app: Option<Arc<App>>,
+        dispatch: Dispatch,
+        role: ProtocolRole,
+    ) -> Self {
         Self {
             app,
+            dispatch,
             role,
             upgrade_manager: Arc::new(UpgradeManager::new()),
+            admission: Arc::new(BodyAdmission::default()),
             _protocol: std::marker::PhantomData,
         }
     }
+
+    /// Checks run on the request head before the body is read
+    pub fn with_body_admission(mut self, admission: BodyAdmission) -> Self {
+        self.admission = Arc::new(admission);
+        self
+    }
+}
+
+#[cfg(test)]
+impl<P> HotaruService<P> {
+    /// A service running every request through `handler`, with no app
+    pub(crate) fn from_handler<F, Fut>(handler: F) -> Self
+    where
+        F: Fn(HyperContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HyperContext> + Send + 'static,
+    {
+        let dispatch: Dispatch = Arc::new(move |ctx| Box::pin(handler(ctx)));
+        Self::with_dispatch(None, dispatch, ProtocolRole::Server)
+    }
 }
 
 use hotaru_core::connection::Protocol;
 
+/// Walks the app's URL tree for `P` and runs the endpoint the path reaches
+async fn route<P>(app: Arc<App>, mut ctx: HyperContext) -> HyperContext
+where
+    P: Protocol<Context = HyperContext> + 'static,
+{
+    // Get the root handler for protocol P from the app's protocol registry
+    let Some(root_handler) = app.handler.url::<P>() else {
+        // Answer 500 if no handler is registered
+        let error_text = format!(
+            "No handler registered for protocol {}",
+            std::any::type_name::<P>()
+        );
+        eprintln!("Error: {}", error_text);
+
+        ctx.response.set_status(StatusCode::INTERNAL_SERVER_ERROR);
+        ctx.response.set_body(error_text.into_bytes());
+        return ctx;
+    };
+
+    walk_and_run(root_handler, ctx).await
+}
+
+/// Runs the endpoint `root` routes the request to, unless the endpoint's
+/// config turns the request away
+async fn walk_and_run(root: Arc<Url<HyperContext>>, mut ctx: HyperContext) -> HyperContext {
+    // Walk the URL tree to find the matching endpoint
+    let path = ctx.request.path().to_string();
+    let endpoint = root.walk_str(&path).await;
+    ctx.endpoint = Some(endpoint.clone());
+
+    // WebSocket-only endpoints answer anything but an upgrade with 426
+    if endpoint.get_params::<WebSocketOnly>().is_some()
+        && !is_websocket_upgrade_generic(&ctx.request.inner)
+        && !is_http2_websocket_upgrade_generic(&ctx.request.inner)
+    {
+        ctx.response = HyperResponse {
+            inner: upgrade_required_response("websocket"),
+        };
+        return ctx;
+    }
+
+    // Run the endpoint like in the TCP example
+    endpoint.run(ctx).await
+}
+
 // Implement hyper's Service trait for incoming requests
 impl<P> Service<Request<Incoming>> for HotaruService<P>
 where
@@ -50,9 +146,11 @@ where
     type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
 
     fn call(&self, mut req: Request<Incoming>) -> Self::Future {
-        let _app = self.app.clone();
+        let app = self.app.clone();
+        let dispatch = self.dispatch.clone();
         let role = self.role;
         let upgrade_manager = self.upgrade_manager.clone();
+        let admission = self.admission.clone();
 
         Box::pin(async move {
             let path = req.uri().path().to_string();
@@ -60,33 +158,18 @@ where
 
             // println!("HotaruService routing request: {} {}", method, path);
 
-            // Get the root handler for protocol P from the app's protocol registry
-            let root_handler = match _app.handler.url::<P>() {
-                Some(handler) => handler,
-                None => {
-                    // Return 500 if no handler is registered
-                    let error_text = format!(
-                        "No handler registered for protocol {}",
-                        std::any::type_name::<P>()
-                    );
-                    eprintln!("Error: {}", error_text);
-
-                    return Ok(Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .body(Full::new(Bytes::from(error_text)).boxed())
-                        .unwrap());
-                }
-            };
-
-            // Walk the URL tree to find the matching endpoint
-            let endpoint = root_handler.clone().walk_str(&path).await;
-
             // Check if this is a WebSocket upgrade request early
-            use crate::websocket::is_websocket_upgrade_generic;
             let is_ws_upgrade_request = is_websocket_upgrade_generic(&req);
 
+            // HTTP/2 Extended CONNECT (RFC 8441) switches only this stream
+            let is_h2_ws_request = is_http2_websocket_upgrade_generic(&req);
+
+            // Plain CONNECT can be turned into a TCP tunnel by the endpoint
+            use crate::tunnel::is_connect_request;
+            let is_connect = is_connect_request(&req);
+
             // Set up upgrade future before consuming the request
-            let pending_upgrade = if is_ws_upgrade_request {
+            let mut pending_upgrade = if is_ws_upgrade_request || is_h2_ws_request || is_connect {
                 Some(hyper::upgrade::on(&mut req))
             } else {
                 None
@@ -95,20 +178,22 @@ where
             // Extract request parts before consuming body
             let (parts, body) = req.into_parts();
 
-            // Read the entire body into memory
-            let body_bytes = body.collect().await.unwrap_or_default().to_bytes();
-            let body_vec = body_bytes.to_vec(); // Clone for storing in context
-
-            // Reconstruct request with the body for the context
-            let hyper_req = Request::from_parts(parts, Full::new(body_bytes).boxed());
+            // Reject before touching the body so `Expect: 100-continue`
+            // clients never get the interim response for a doomed request.
+            // Otherwise the handler's first read makes hyper send
+            // `100 Continue`.
+            if let Some(rejection) = admit_body(&parts, &admission) {
+                return Ok(rejection);
+            }
 
-            // Create the context with the endpoint
-            let mut ctx = HyperContext::new_server(hyper_req, _app.clone());
-            ctx.endpoint = Some(endpoint.clone());
-            ctx.set_body_bytes(body_vec); // Store body bytes for form/json parsing
+            // The body goes to the handler unread. Reading it, in full or
+            // as a stream, stops at the stream limit so a body without
+            // `Content-Length` cannot grow unbounded
+            let hyper_req = Request::from_parts(parts, box_body(body));
+            let mut ctx = HyperContext::server_context(hyper_req, app);
+            ctx.request.set_stream_limit(admission.max_stream_size());
 
-            // Run the endpoint like in the TCP example
-            let mut result_ctx = endpoint.run(ctx).await;
+            let mut result_ctx = dispatch(ctx).await;
 
             // Check if protocol switch was requested and validate the response
             let should_handle_upgrade =
@@ -120,9 +205,13 @@ where
                     // Check if this is a WebSocket upgrade
                     use crate::websocket::WebSocketProtocol;
                     if *target_protocol == std::any::TypeId::of::<WebSocketProtocol>() {
-                        // Check if the response is 101 Switching Protocols
+                        // HTTP/1.1 expects 101 Switching Protocols, Extended
+                        // CONNECT a 2xx on the stream
                         let response = result_ctx.response_mut();
-                        if response.inner.status() == StatusCode::SWITCHING_PROTOCOLS {
+                        let status = response.inner.status();
+                        if status == StatusCode::SWITCHING_PROTOCOLS
+                            || (is_h2_ws_request && status.is_success())
+                        {
                             println!("🚀 WebSocket upgrade validated");
                             true
                         } else {
@@ -135,19 +224,32 @@ where
                     false
                 };
 
-            // Check if the endpoint was found or if it's a 404 (dangling URL)
+            // An accepted CONNECT is bridged once its 2xx response is sent
+            let tunnel_upstream = match &result_ctx.connection_status {
+                hotaru_core::connection::ConnectionStatus::SwitchProtocol(target)
+                    if is_connect
+                        && *target == std::any::TypeId::of::<crate::tunnel::TcpTunnel>()
+                        && result_ctx.response.status().is_success() =>
+                {
+                    crate::tunnel::tunnel_target(&result_ctx)
+                }
+                _ => None,
+            };
+
+            let stream_id = result_ctx.stream_id.unwrap_or(0);
+
+            // Check if the endpoint was found or if it's a 404 (dangling URL).
+            // A body that failed to decode also answers 400; that one is kept
+            let body_failed = result_ctx.request.body_failure().is_some();
             let response = result_ctx.response_mut();
             let status = response.inner.status();
 
-            if status == StatusCode::BAD_REQUEST {
+            if status == StatusCode::BAD_REQUEST && !body_failed {
                 // This is a dangling URL (no handler found), return 404
                 // println!("❌ No endpoint found for: {} {} - returning 404", method, path);
                 Ok(Response::builder()
                     .status(StatusCode::NOT_FOUND)
-                    .body(
-                        Full::new(Bytes::from(format!("404 Not Found: {} {}", method, path)))
-                            .boxed(),
-                    )
+                    .body(full_body(format!("404 Not Found: {} {}", method, path)))
                     .unwrap())
             } else {
                 // Valid endpoint found - extract the actual response
@@ -163,11 +265,26 @@ where
 
                 // Move the body from the context response
                 // We need to swap out the body since we can't clone it
-                let body =
-                    std::mem::replace(response.inner.body_mut(), Empty::<Bytes>::new().boxed());
+                let body = std::mem::replace(response.inner.body_mut(), empty_body());
 
                 let final_response = final_response.body(body).unwrap();
 
+                if let (Some(upstream), Some(upgrade_future)) =
+                    (tunnel_upstream, pending_upgrade.take())
+                {
+                    tokio::spawn(async move {
+                        match upgrade_future.await {
+                            Ok(upgraded) => {
+                                use crate::tunnel::handle_tunnel_upgrade;
+                                handle_tunnel_upgrade(upgraded, upstream).await;
+                            }
+                            Err(e) => {
+                                eprintln!("❌ CONNECT upgrade failed: {:?}", e);
+                            }
+                        }
+                    });
+                }
+
                 // If we have a pending upgrade and it was validated, spawn the handler
                 if should_handle_upgrade {
                     if let Some(upgrade_future) = pending_upgrade {
@@ -182,7 +299,10 @@ where
                                     println!("✅ WebSocket upgrade successful!");
 
                                     // Use appropriate handler based on endpoint
-                                    if is_download_endpoint {
+                                    if is_h2_ws_request {
+                                        use crate::websocket::handle_http2_websocket_upgrade;
+                                        handle_http2_websocket_upgrade(upgraded, stream_id).await;
+                                    } else if is_download_endpoint {
                                         use crate::websocket::handle_download_websocket;
                                         handle_download_websocket(upgraded).await;
                                     } else {
@@ -209,9 +329,263 @@ impl<P> Clone for HotaruService<P> {
     fn clone(&self) -> Self {
         Self {
             app: self.app.clone(),
+            dispatch: self.dispatch.clone(),
             role: self.role,
             upgrade_manager: self.upgrade_manager.clone(),
+            admission: self.admission.clone(),
             _protocol: std::marker::PhantomData,
         }
     }
 }
+
+/// Future answering one request of a [`StreamService`]
+pub type StreamFuture = Pin<Box<dyn Future<Output = Result<Response<Body>, Infallible>> + Send>>;
+
+/// A type-erased handler for whole requests, as taken by
+/// [`ContentTypeRouter::route`]
+pub type StreamService = Arc<dyn Fn(Request<Incoming>) -> StreamFuture + Send + Sync>;
+
+/// Routes each request by its `Content-Type` before it reaches `fallback`
+///
+/// On HTTP/2 every stream is routed on its own, so one connection can
+/// carry gRPC calls and JSON requests side by side. A route takes the
+/// requests whose content type starts with its prefix, compared without
+/// regard to case; the first matching route wins and everything else goes
+/// to `fallback`.
+pub struct ContentTypeRouter<S> {
+    routes: Arc<Vec<(String, StreamService)>>,
+    extensions: Extensions,
+    fallback: S,
+}
+
+impl<S> ContentTypeRouter<S> {
+    pub fn new(fallback: S) -> Self {
+        Self {
+            routes: Arc::new(Vec::new()),
+            extensions: Extensions::new(),
+            fallback,
+        }
+    }
+
+    /// Sends requests whose content type starts with `prefix` to `service`
+    pub fn route(mut self, prefix: impl Into<String>, service: StreamService) -> Self {
+        let prefix = prefix.into().to_ascii_lowercase();
+        Arc::make_mut(&mut self.routes).push((prefix, service));
+        self
+    }
+
+    /// Adds `extensions` to every request before it is routed
+    ///
+    /// Routes and `fallback` alike find them in the request's extensions,
+    /// which is how settings made on the protocol reach each request.
+    pub fn with_request_extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions.extend(extensions);
+        self
+    }
+
+    /// The route taking a request with these headers, if any
+    fn find(&self, headers: &HeaderMap) -> Option<&StreamService> {
+        let content_type = headers.get(CONTENT_TYPE)?.to_str().ok()?;
+        let content_type = content_type.trim_start().to_ascii_lowercase();
+        self.routes
+            .iter()
+            .find(|(prefix, _)| content_type.starts_with(prefix.as_str()))
+            .map(|(_, service)| service)
+    }
+}
+
+impl<S> Service<Request<Incoming>> for ContentTypeRouter<S>
+where
+    S: Service<Request<Incoming>, Response = Response<Body>, Error = Infallible>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = StreamFuture;
+
+    fn call(&self, mut req: Request<Incoming>) -> Self::Future {
+        req.extensions_mut().extend(self.extensions.clone());
+        match self.find(req.headers()) {
+            Some(service) => service(req),
+            None => Box::pin(self.fallback.call(req)),
+        }
+    }
+}
+
+impl<S: Clone> Clone for ContentTypeRouter<S> {
+    fn clone(&self) -> Self {
+        Self {
+            routes: self.routes.clone(),
+            extensions: self.extensions.clone(),
+            fallback: self.fallback.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HyperHttp1;
+    use crate::body::BodyLimitExceeded;
+    use futures_util::StreamExt;
+    use hotaru_core::extensions::ParamsClone;
+    use http_body_util::BodyExt;
+    use hyper::client::conn::http1 as client_http1;
+    use hyper::header::HeaderValue;
+    use hyper::server::conn::http1;
+    use hyper_util::rt::TokioIo;
+
+    /// Serves `service` over an in-memory connection and sends `request`
+    async fn send(
+        service: HotaruService<HyperHttp1>,
+        request: Request<Body>,
+    ) -> (StatusCode, HeaderMap, String) {
+        let (client_io, server_io) = tokio::io::duplex(4096);
+        let conn = http1::Builder::new().serve_connection(TokioIo::new(server_io), service);
+        tokio::spawn(conn.with_upgrades());
+
+        let (mut sender, conn) = client_http1::handshake(TokioIo::new(client_io))
+            .await
+            .unwrap();
+        tokio::spawn(conn);
+        let response = sender.send_request(request).await.unwrap();
+        let (parts, body) = response.into_parts();
+        let bytes = body.collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+        (parts.status, parts.headers, text)
+    }
+
+    /// Sends one POST of `body` to `service`
+    async fn post(service: HotaruService<HyperHttp1>, body: &'static [u8]) -> (StatusCode, String) {
+        let request = Request::post("/upload")
+            .header("host", "localhost")
+            .body(full_body(body))
+            .unwrap();
+        let (status, _, text) = send(service, request).await;
+        (status, text)
+    }
+
+    fn limited(service: HotaruService<HyperHttp1>, limit: u64) -> HotaruService<HyperHttp1> {
+        service.with_body_admission(BodyAdmission::new().with_max_stream_size(limit))
+    }
+
+    #[tokio::test]
+    async fn test_stream_errors_at_limit() {
+        let service = HotaruService::from_handler(|mut ctx: HyperContext| async move {
+            let mut stream = ctx.request.body_stream();
+            let mut read = 0;
+            let mut outcome = String::from("ended");
+            while let Some(chunk) = stream.next().await {
+                match chunk {
+                    Ok(bytes) => read += bytes.len(),
+                    Err(e) => {
+                        let limit = e.downcast_ref::<BodyLimitExceeded>().unwrap().limit;
+                        outcome = format!("limit {}", limit);
+                        break;
+                    }
+                }
+            }
+            assert!(read <= 10);
+            ctx.response.set_body(outcome.into_bytes());
+            ctx
+        });
+
+        let (status, text) = post(limited(service, 10), b"0123456789abcdef").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(text, "limit 10");
+    }
+
+    #[tokio::test]
+    async fn test_unread_body_is_not_limited() {
+        let service = HotaruService::from_handler(|mut ctx: HyperContext| async move {
+            ctx.response.set_body(b"ignored".to_vec());
+            ctx
+        });
+
+        let (status, text) = post(limited(service, 10), b"0123456789abcdef").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(text, "ignored");
+    }
+
+    #[tokio::test]
+    async fn test_body_buffers_on_demand() {
+        let handler = |mut ctx: HyperContext| async move {
+            match ctx.request.body().await {
+                Ok(first) => {
+                    let first = first.to_vec();
+                    let again = ctx.request.body().await.unwrap().to_vec();
+                    assert_eq!(first, again);
+                    ctx.response.set_body(first);
+                }
+                Err(status) => ctx.response.set_status(status),
+            }
+            ctx
+        };
+
+        let service = limited(HotaruService::from_handler(handler), 32);
+        let (status, text) = post(service, b"0123456789abcdef").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(text, "0123456789abcdef");
+
+        let service = limited(HotaruService::from_handler(handler), 10);
+        let (status, _) = post(service, b"0123456789abcdef").await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_websocket_only_endpoint_answers_plain_requests_with_426() {
+        let room = |ctx: HyperContext| async move {
+            let mut ctx = ctx;
+            let name = ctx.pattern("room").unwrap_or_default();
+            ctx.response.set_body(name.into_bytes());
+            ctx
+        };
+        let root = Arc::new(Url::<HyperContext>::default());
+        let mut websocket_only = ParamsClone::default();
+        websocket_only.set(WebSocketOnly);
+        root.sub_url("/rooms/<room>", Some(Arc::new(room)), None, websocket_only)
+            .unwrap();
+        root.sub_url(
+            "/lobby/<room>",
+            Some(Arc::new(room)),
+            None,
+            ParamsClone::default(),
+        )
+        .unwrap();
+        let dispatch: Dispatch = Arc::new(move |ctx| Box::pin(walk_and_run(root.clone(), ctx)));
+        let service = HotaruService::with_dispatch(None, dispatch, ProtocolRole::Server);
+
+        let get = |path: &str| {
+            Request::get(path)
+                .header("host", "localhost")
+                .body(empty_body())
+                .unwrap()
+        };
+
+        // A plain request never reaches the handler
+        let (status, headers, text) = send(service.clone(), get("/rooms/tea")).await;
+        assert_eq!(status, StatusCode::UPGRADE_REQUIRED);
+        assert_eq!(headers["upgrade"], "websocket");
+        assert_eq!(headers["sec-websocket-version"], "13");
+        assert!(text.is_empty());
+
+        // An upgrade request for the same route does
+        let mut upgrade = get("/rooms/tea");
+        let upgrade_headers = upgrade.headers_mut();
+        upgrade_headers.insert("connection", HeaderValue::from_static("Upgrade"));
+        upgrade_headers.insert("upgrade", HeaderValue::from_static("websocket"));
+        upgrade_headers.insert("sec-websocket-version", HeaderValue::from_static("13"));
+        upgrade_headers.insert(
+            "sec-websocket-key",
+            HeaderValue::from_static("dGhlIHNhbXBsZSBub25jZQ=="),
+        );
+        let (status, _, text) = send(service.clone(), upgrade).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(text, "tea");
+
+        // Routes without the marker serve plain requests
+        let (status, _, text) = send(service, get("/lobby/tea")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(text, "tea");
+    }
+}
@@ -0,0 +1,339 @@
+//! Server-sent events.
+//!
+//! [`HyperContext::sse`](crate::HyperContext::sse) answers a request with a
+//! `text/event-stream` body and runs a producer that sends events to it
+//! through an [`SseSender`]:
+//!
+//! ```rust,ignore
+//! endpoint! {
+//!     APP.url("/ticks"),
+//!     pub ticks <HYPER1> {
+//!         req.sse(|events| async move {
+//!             for tick in 0u64.. {
+//!                 let event = SseEvent::new(tick.to_string()).event("tick");
+//!                 if events.send(event).await.is_err() {
+//!                     break;
+//!                 }
+//!                 tokio::time::sleep(Duration::from_secs(1)).await;
+//!             }
+//!         });
+//!         req
+//!     }
+//! }
+//! ```
+//!
+//! # Backpressure
+//!
+//! At most [`SSE_BUFFERED_EVENTS`] events wait for the connection. Once
+//! they do, [`send`](SseSender::send) waits for the client to take one, so
+//! a slow client slows the producer down instead of growing a queue.
+//!
+//! # Disconnects
+//!
+//! A client that goes away, by resetting the stream on HTTP/2 or closing
+//! the connection on HTTP/1, makes hyper drop the response body. The
+//! producer runs in a task that selects on that drop, so the producer
+//! future is dropped at its next await point instead of generating events
+//! no one reads. Whatever it holds is released then.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use http_body::Frame;
+use tokio::sync::mpsc;
+
+use crate::context::{Body, box_body};
+
+/// Events buffered between an [`SseSender`] and the connection.
+pub const SSE_BUFFERED_EVENTS: usize = 8;
+
+/// The client of an event stream disconnected, or the stream was
+/// otherwise dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SseClosed;
+
+impl fmt::Display for SseClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("event stream is closed")
+    }
+}
+
+impl std::error::Error for SseClosed {}
+
+/// One server-sent event.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    data: String,
+    event: Option<String>,
+    id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl SseEvent {
+    /// An event carrying `data`. Multi-line data is sent as one `data:`
+    /// field per line.
+    pub fn new(data: impl Into<String>) -> Self {
+        Self {
+            data: data.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the event type, the `event:` field.
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Sets the event id a reconnecting client sends back as
+    /// `Last-Event-ID`.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets how long the client waits before reconnecting.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// The event as it goes on the wire, blank line included.
+    ///
+    /// Line breaks in the event type and id would start new fields, so they
+    /// are dropped.
+    pub fn encode(&self) -> Bytes {
+        let mut out = BytesMut::new();
+        if let Some(event) = &self.event {
+            put_field(&mut out, "event", &single_line(event));
+        }
+        if let Some(id) = &self.id {
+            put_field(&mut out, "id", &single_line(id));
+        }
+        if let Some(retry) = self.retry {
+            put_field(&mut out, "retry", &retry.as_millis().to_string());
+        }
+        for line in self.data.split('\n') {
+            put_field(&mut out, "data", line.strip_suffix('\r').unwrap_or(line));
+        }
+        out.put_u8(b'\n');
+        out.freeze()
+    }
+}
+
+fn put_field(out: &mut BytesMut, name: &str, value: &str) {
+    out.put_slice(name.as_bytes());
+    out.put_slice(b": ");
+    out.put_slice(value.as_bytes());
+    out.put_u8(b'\n');
+}
+
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], "")
+}
+
+/// Sends events to the client of an event stream.
+#[derive(Clone)]
+pub struct SseSender {
+    tx: mpsc::Sender<Bytes>,
+}
+
+impl SseSender {
+    /// Sends `event`, waiting while the buffer is full.
+    pub async fn send(&self, event: SseEvent) -> Result<(), SseClosed> {
+        self.tx.send(event.encode()).await.map_err(|_| SseClosed)
+    }
+
+    /// Sends a comment line, which clients ignore. Useful as a keep-alive
+    /// through proxies that close idle connections.
+    pub async fn comment(&self, text: &str) -> Result<(), SseClosed> {
+        let comment = format!(": {}\n\n", single_line(text));
+        self.tx
+            .send(Bytes::from(comment))
+            .await
+            .map_err(|_| SseClosed)
+    }
+
+    /// Whether the client has disconnected.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Waits until the client has disconnected.
+    pub async fn closed(&self) {
+        self.tx.closed().await
+    }
+}
+
+/// Response body fed by an [`SseSender`].
+struct SseBody {
+    rx: mpsc::Receiver<Bytes>,
+}
+
+impl http_body::Body for SseBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+        self.rx
+            .poll_recv(cx)
+            .map(|event| event.map(|event| Ok(Frame::data(event))))
+    }
+}
+
+/// Starts `producer` on a new event stream and returns the stream's
+/// response body.
+///
+/// The producer is dropped as soon as the body is: the task races it
+/// against the sender's `closed`, which resolves once the receiver inside
+/// the body is gone.
+pub(crate) fn event_stream<F, Fut>(producer: F) -> Body
+where
+    F: FnOnce(SseSender) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(SSE_BUFFERED_EVENTS);
+    let disconnected = SseSender { tx: tx.clone() };
+    let producer = producer(SseSender { tx });
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = producer => {}
+            _ = disconnected.closed() => {}
+        }
+    });
+    box_body(SseBody { rx })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{HyperContext, empty_body};
+    use hyper::body::Incoming;
+    use hyper::server::conn::{http1, http2};
+    use hyper::service::service_fn;
+    use hyper::{Request, Response};
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_event_encoding() {
+        let event = SseEvent::new("first\nsecond\r\n")
+            .event("update\n")
+            .id("7")
+            .retry(Duration::from_secs(3));
+        assert_eq!(
+            event.encode(),
+            "event: update\nid: 7\nretry: 3000\ndata: first\ndata: second\ndata: \n\n"
+        );
+        assert_eq!(SseEvent::new("").encode(), "data: \n\n");
+    }
+
+    type Reply = std::future::Ready<Result<Response<Body>, Infallible>>;
+
+    /// Counts how often the producer runs, and whether it has been dropped
+    #[derive(Clone, Default)]
+    struct Probe {
+        polls: Arc<AtomicUsize>,
+        dropped: Arc<AtomicBool>,
+    }
+
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    impl Probe {
+        /// An endless producer, sending an event every few milliseconds
+        fn service(self) -> impl Fn(Request<Incoming>) -> Reply + Clone {
+            let probe = self;
+            move |request: Request<Incoming>| {
+                let (parts, _) = request.into_parts();
+                let request = Request::from_parts(parts, empty_body());
+                let mut ctx = HyperContext::new_client(request);
+                let probe = probe.clone();
+                ctx.sse(move |events| async move {
+                    let _flag = DropFlag(probe.dropped.clone());
+                    for tick in 0u64.. {
+                        probe.polls.fetch_add(1, Ordering::SeqCst);
+                        let _ = events.send(SseEvent::new(tick.to_string())).await;
+                        tokio::time::sleep(Duration::from_millis(5)).await;
+                    }
+                });
+                std::future::ready(Ok(ctx.response.into_inner()))
+            }
+        }
+
+        /// Waits for the producer to be dropped, then checks it is no
+        /// longer polled
+        async fn assert_stopped(&self) {
+            tokio::time::timeout(Duration::from_secs(1), async {
+                while !self.dropped.load(Ordering::SeqCst) {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            })
+            .await
+            .expect("producer still running after the client left");
+            let polls = self.polls.load(Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert_eq!(self.polls.load(Ordering::SeqCst), polls);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_producer_dropped_on_disconnect() {
+        // HTTP/1: the client closes the connection mid-stream
+        let probe = Probe::default();
+        let (mut client, server_io) = tokio::io::duplex(1024);
+        tokio::spawn(
+            http1::Builder::new()
+                .serve_connection(TokioIo::new(server_io), service_fn(probe.clone().service())),
+        );
+        client
+            .write_all(b"GET /events HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut received = Vec::new();
+        while !String::from_utf8_lossy(&received).contains("data: 2\n\n") {
+            let mut chunk = [0u8; 256];
+            let read = client.read(&mut chunk).await.unwrap();
+            assert!(read > 0, "stream ended early");
+            received.extend_from_slice(&chunk[..read]);
+        }
+        let head = String::from_utf8_lossy(&received).to_lowercase();
+        assert!(head.contains("content-type: text/event-stream"));
+        assert!(!probe.dropped.load(Ordering::SeqCst));
+        drop(client);
+        probe.assert_stopped().await;
+
+        // HTTP/2: the client resets the stream, the connection stays up
+        let probe = Probe::default();
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(
+            http2::Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(server_io), service_fn(probe.clone().service())),
+        );
+        let (client, connection) = h2::client::handshake(client_io).await.unwrap();
+        tokio::spawn(connection);
+        let mut client = client.ready().await.unwrap();
+        let request = Request::get("http://localhost/events").body(()).unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let mut body = response.await.unwrap().into_body();
+        let first = body.data().await.unwrap().unwrap();
+        assert_eq!(first, "data: 0\n\n");
+        drop(body);
+        probe.assert_stopped().await;
+    }
+}
@@ -0,0 +1,139 @@
+//! CONNECT tunneling (RFC 9110 §9.3.6, RFC 9113 §8.5)
+//!
+//! A plain CONNECT request (HTTP/1.1, or HTTP/2 without a `:protocol`
+//! pseudo-header) asks the server to open a TCP connection to the request
+//! authority and relay bytes both ways. An endpoint accepts it with
+//! [`HyperContext::accept_connect`]; once the 2xx response is sent, the
+//! service bridges the upgraded client stream to the upstream connection.
+//!
+//! ```rust,ignore
+//! endpoint! {
+//!     APP.url("/"),
+//!     pub proxy <HYPER1> {
+//!         if is_connect_request(&req.request.inner) {
+//!             req.accept_connect();
+//!         }
+//!         req
+//!     }
+//! }
+//! ```
+
+use std::io;
+
+use hyper::upgrade::Upgraded;
+use hyper::{Method, Request};
+use hyper_util::rt::TokioIo;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+
+use crate::context::HyperContext;
+
+/// Protocol marker used as the `SwitchProtocol` target of an accepted CONNECT
+pub struct TcpTunnel;
+
+/// Whether a request is a plain CONNECT, as opposed to the extended CONNECT
+/// used to bootstrap WebSockets over HTTP/2
+pub fn is_connect_request<T>(request: &Request<T>) -> bool {
+    request.method() == Method::CONNECT
+        && request.extensions().get::<hyper::ext::Protocol>().is_none()
+}
+
+/// `host:port` a CONNECT request asks to be tunneled to
+pub fn connect_authority<T>(request: &Request<T>) -> Option<String> {
+    request.uri().authority().map(|a| a.to_string())
+}
+
+/// Upstream address of an accepted CONNECT, if the context holds one
+pub fn tunnel_target(ctx: &HyperContext) -> Option<String> {
+    use crate::upgrade::HttpUpgradeType;
+
+    match &ctx.upgrade_context.as_ref()?.upgrade_type {
+        HttpUpgradeType::Connect { upstream } => Some(upstream.clone()),
+        _ => None,
+    }
+}
+
+/// Relays bytes between `client` and a fresh TCP connection to `upstream`
+/// until both directions are shut down.
+///
+/// Returns the bytes copied client→upstream and upstream→client.
+pub async fn bridge<C>(mut client: C, upstream: &str) -> io::Result<(u64, u64)>
+where
+    C: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut upstream = TcpStream::connect(upstream).await?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await
+}
+
+/// Handle an upgraded CONNECT stream by bridging it to `upstream`
+pub async fn handle_tunnel_upgrade(upgraded: Upgraded, upstream: String) {
+    if let Err(e) = bridge(TokioIo::new(upgraded), &upstream).await {
+        eprintln!("CONNECT tunnel to {} failed: {:?}", upstream, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{Body, empty_body};
+    use hyper::body::Incoming;
+    use hyper::service::service_fn;
+    use hyper::{Response, StatusCode};
+    use std::convert::Infallible;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    async fn proxy(mut req: Request<Incoming>) -> Result<Response<Body>, Infallible> {
+        assert!(is_connect_request(&req));
+        let upstream = connect_authority(&req).unwrap();
+        let upgrade = hyper::upgrade::on(&mut req);
+        tokio::spawn(async move {
+            handle_tunnel_upgrade(upgrade.await.unwrap(), upstream).await;
+        });
+        Ok(Response::builder()
+            .status(StatusCode::OK)
+            .body(empty_body())
+            .unwrap())
+    }
+
+    #[tokio::test]
+    async fn connect_tunnel_round_trips_bytes() {
+        // Upstream TCP echo server
+        let echo = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let echo_addr = echo.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = echo.accept().await.unwrap();
+            let (mut read, mut write) = socket.split();
+            tokio::io::copy(&mut read, &mut write).await.unwrap();
+        });
+
+        // Proxy accepting CONNECT over HTTP/1.1
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(socket), service_fn(proxy))
+                .with_upgrades()
+                .await
+                .unwrap();
+        });
+
+        let mut client = TcpStream::connect(proxy_addr).await.unwrap();
+        let request = format!("CONNECT {echo_addr} HTTP/1.1\r\nHost: {echo_addr}\r\n\r\n");
+        client.write_all(request.as_bytes()).await.unwrap();
+
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(client.read_u8().await.unwrap());
+        }
+        assert!(head.starts_with(b"HTTP/1.1 200 OK\r\n"));
+
+        for payload in [&b"hello through the tunnel"[..], &[0u8, 255, 13, 10]] {
+            client.write_all(payload).await.unwrap();
+            let mut echoed = vec![0; payload.len()];
+            client.read_exact(&mut echoed).await.unwrap();
+            assert_eq!(echoed, payload);
+        }
+    }
+}
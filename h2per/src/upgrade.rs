@@ -36,6 +36,8 @@ pub enum HttpProtocol {
     SSE,
     /// gRPC (over HTTP/2)
     Grpc,
+    /// Raw TCP relayed through a CONNECT tunnel
+    Tunnel,
     /// Custom protocol
     Custom(&'static str),
 }
@@ -50,6 +52,7 @@ impl HttpProtocol {
             HttpProtocol::WebSocket => "websocket",
             HttpProtocol::SSE => "text/event-stream",
             HttpProtocol::Grpc => "grpc",
+            HttpProtocol::Tunnel => "tcp",
             HttpProtocol::Custom(s) => s,
         }
     }
@@ -105,6 +108,12 @@ pub enum HttpUpgradeType {
         direct: bool,
     },
 
+    /// Plain CONNECT tunnel over HTTP/1.1 or HTTP/2
+    Connect {
+        /// `host:port` the tunnel is bridged to
+        upstream: String,
+    },
+
     /// HTTP/3 WebSocket over CONNECT
     Http3Connect {
         /// The QUIC stream ID
@@ -8,12 +8,15 @@ use std::any::Any;
 use std::error::Error;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio_tungstenite::{WebSocketStream, tungstenite};
 use tungstenite::protocol::Message as WsMessage;
+use tungstenite::protocol::frame::{CloseFrame, coding::CloseCode};
 
 use hotaru_core::{
     app::application::App,
     connection::{Message, Protocol, ProtocolRole, TcpReader, TcpWriter, Transport},
+    protocol::Detection,
 };
 
 use crate::context::HyperContext;
@@ -145,6 +148,89 @@ impl Message for WebSocketMessage {
     }
 }
 
+// ============================================================================
+// WebSocket Connection - Message-level API over an upgraded stream
+// ============================================================================
+
+/// An established WebSocket connection, read and written a message at a time
+///
+/// The same API serves every upgrade path: an HTTP/1.1 `Upgrade`, or an
+/// HTTP/2 Extended CONNECT stream (RFC 8441), where frames travel in the DATA
+/// frames of that one stream and the connection's other streams carry on
+/// unaffected.
+pub struct WebSocketConnection<S = TokioIo<Upgraded>> {
+    stream: WebSocketStream<S>,
+    transport: WebSocketTransport,
+}
+
+impl WebSocketConnection {
+    /// Server side of an HTTP/1.1 connection upgraded to WebSocket
+    pub async fn accept_http1(upgraded: Upgraded) -> Self {
+        let transport = WebSocketTransport::from_http1(generate_connection_id());
+        Self::accept(TokioIo::new(upgraded), transport).await
+    }
+
+    /// Server side of an HTTP/2 Extended CONNECT stream
+    pub async fn accept_http2(upgraded: Upgraded, stream_id: u32) -> Self {
+        let transport = WebSocketTransport::from_http2_stream(generate_connection_id(), stream_id);
+        Self::accept(TokioIo::new(upgraded), transport).await
+    }
+}
+
+impl<S> WebSocketConnection<S>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    /// Server side of a WebSocket over `io`, whose handshake is already done
+    pub async fn accept(io: S, transport: WebSocketTransport) -> Self {
+        let stream =
+            WebSocketStream::from_raw_socket(io, tungstenite::protocol::Role::Server, None).await;
+        Self::new(stream, transport)
+    }
+
+    pub fn new(stream: WebSocketStream<S>, transport: WebSocketTransport) -> Self {
+        Self { stream, transport }
+    }
+
+    pub fn transport(&self) -> &WebSocketTransport {
+        &self.transport
+    }
+
+    /// Sends one message
+    pub async fn send_message(
+        &mut self,
+        message: WebSocketMessage,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if matches!(message.0, WsMessage::Close(_)) {
+            self.transport.mark_closing();
+        }
+        self.stream.send(message.0).await?;
+        Ok(())
+    }
+
+    /// Receives the next message, or `None` once the peer has gone away
+    pub async fn recv_message(
+        &mut self,
+    ) -> Result<Option<WebSocketMessage>, Box<dyn Error + Send + Sync>> {
+        match self.stream.next().await {
+            Some(Ok(message)) => {
+                if matches!(message, WsMessage::Close(_)) {
+                    self.transport.mark_closing();
+                }
+                self.transport.increment_messages();
+                Ok(Some(WebSocketMessage(message)))
+            }
+            Some(Err(err)) => Err(err.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Gives back the underlying tungstenite stream
+    pub fn into_inner(self) -> WebSocketStream<S> {
+        self.stream
+    }
+}
+
 // ============================================================================
 // WebSocket Protocol Implementation
 // ============================================================================
@@ -154,6 +240,10 @@ impl Message for WebSocketMessage {
 pub struct WebSocketProtocol {
     role: ProtocolRole,
     transport: WebSocketTransport,
+
+    /// Close the connection after this long without a Text/Binary frame
+    /// from the peer. Control frames (ping/pong) do not reset it.
+    idle_timeout: Option<Duration>,
 }
 
 impl WebSocketProtocol {
@@ -161,6 +251,7 @@ impl WebSocketProtocol {
         Self {
             role,
             transport: WebSocketTransport::new_direct(),
+            idle_timeout: None,
         }
     }
 
@@ -169,6 +260,7 @@ impl WebSocketProtocol {
         Self {
             role: ProtocolRole::Server,
             transport: WebSocketTransport::from_http1(connection_id),
+            idle_timeout: None,
         }
     }
 
@@ -177,9 +269,26 @@ impl WebSocketProtocol {
         Self {
             role: ProtocolRole::Server,
             transport: WebSocketTransport::from_http2_stream(connection_id, stream_id),
+            idle_timeout: None,
         }
     }
 
+    /// Close the connection with `1000 Normal Closure` once no application
+    /// message (Text/Binary) has arrived for `timeout`.
+    ///
+    /// This is separate from ping/pong keepalive: a peer that only answers
+    /// pings is still considered idle. The timer restarts on every
+    /// application message.
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the configured idle timeout, if any.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
     /// Generate WebSocket accept key (for manual upgrade response)
     pub fn generate_accept_key(key: &str) -> String {
         use base64::{Engine, engine::general_purpose::STANDARD};
@@ -203,17 +312,21 @@ impl Protocol for WebSocketProtocol {
     type Message = WebSocketMessage;
     type Context = HyperContext;
 
-    fn detect(initial_bytes: &[u8]) -> bool {
+    fn detect(initial_bytes: &[u8]) -> Detection {
         // Check for WebSocket frame structure
         // This is called after upgrade, so we check for WebSocket frames
-        if initial_bytes.len() >= 2 {
-            let first_byte = initial_bytes[0];
-            let opcode = first_byte & 0x0F;
-
-            // Valid WebSocket opcodes
-            matches!(opcode, 0x0..=0x2 | 0x8..=0xA)
+        let Some(first_byte) = initial_bytes.first() else {
+            return Detection::NoMatch;
+        };
+        let opcode = first_byte & 0x0F;
+
+        // Valid WebSocket opcodes; the 2-byte header needs the length byte too
+        if !matches!(opcode, 0x0..=0x2 | 0x8..=0xA) {
+            Detection::NoMatch
+        } else if initial_bytes.len() < 2 {
+            Detection::NeedMoreData
         } else {
-            false
+            Detection::Match
         }
     }
 
@@ -257,9 +370,46 @@ impl WebSocketProtocol {
             .send(WsMessage::Text("Welcome to WebSocket server!".to_string()))
             .await?;
 
+        // Idle deadline, restarted on every application message.
+        let idle_deadline = |timeout: Duration| tokio::time::Instant::now() + timeout;
+        let mut deadline = self.idle_timeout.map(idle_deadline);
+
         // Echo server loop
-        while let Some(msg) = ws_stream.next().await {
-            match msg? {
+        loop {
+            let next = match deadline {
+                Some(at) => tokio::select! {
+                    msg = ws_stream.next() => msg,
+                    _ = tokio::time::sleep_until(at) => {
+                        println!("WebSocket idle timeout reached, closing");
+                        ws_stream
+                            .send(WsMessage::Close(Some(CloseFrame {
+                                code: CloseCode::Normal,
+                                reason: "idle timeout".into(),
+                            })))
+                            .await?;
+                        break;
+                    }
+                },
+                None => ws_stream.next().await,
+            };
+            let Some(msg) = next else {
+                break;
+            };
+
+            let msg = match msg {
+                Ok(msg) => msg,
+                Err(tungstenite::Error::Utf8) => {
+                    println!("WebSocket received invalid UTF-8 text, closing");
+                    ws_stream.send(invalid_utf8_close()).await?;
+                    break;
+                }
+                Err(err) => return Err(err.into()),
+            };
+            if matches!(msg, WsMessage::Text(_) | WsMessage::Binary(_)) {
+                deadline = self.idle_timeout.map(idle_deadline);
+            }
+
+            match msg {
                 WsMessage::Text(text) => {
                     println!("WebSocket received text: {}", text);
 
@@ -302,14 +452,25 @@ impl WebSocketProtocol {
     }
 }
 
+/// Close frame failing a connection whose Text message is not valid UTF-8.
+///
+/// tungstenite validates Text payloads incrementally, so a code point split
+/// across fragments is fine while an invalid sequence in any fragment yields
+/// `Error::Utf8`. RFC 6455 §8.1 requires answering that with
+/// `1007 Invalid frame payload data`. Binary frames are never validated.
+fn invalid_utf8_close() -> WsMessage {
+    WsMessage::Close(Some(CloseFrame {
+        code: CloseCode::Invalid,
+        reason: "invalid UTF-8 in text message".into(),
+    }))
+}
+
 // ============================================================================
 // Upgrade Helper Functions
 // ============================================================================
 
-use crate::context::Body;
-use bytes::Bytes;
-use http_body_util::{BodyExt, Empty};
-use hyper::header::{CONNECTION, UPGRADE};
+use crate::context::{Body, empty_body};
+use hyper::header::{CONNECTION, HeaderValue, UPGRADE};
 use hyper::{Request, Response, StatusCode};
 
 /// Check if a request is a WebSocket upgrade request (HTTP/1.1) - generic version
@@ -352,8 +513,8 @@ pub fn is_websocket_upgrade(request: &Request<Body>) -> bool {
     is_websocket_upgrade_generic(request)
 }
 
-/// Check if a request is an HTTP/2 Extended CONNECT for WebSocket
-pub fn is_http2_websocket_upgrade(request: &Request<Body>) -> bool {
+/// Check if a request is an HTTP/2 Extended CONNECT for WebSocket - generic version
+pub fn is_http2_websocket_upgrade_generic<T>(request: &Request<T>) -> bool {
     let headers = request.headers();
 
     // HTTP/2 uses Extended CONNECT method with :protocol pseudo-header
@@ -362,9 +523,12 @@ pub fn is_http2_websocket_upgrade(request: &Request<Body>) -> bool {
         return false;
     }
 
-    // Check for :protocol = websocket pseudo-header
-    // In HTTP/2, this would be a pseudo-header, but Hyper may expose it differently
-    // For now, check for protocol header (implementations vary)
+    // Hyper exposes the :protocol pseudo-header as a request extension
+    if let Some(protocol) = request.extensions().get::<hyper::ext::Protocol>() {
+        return protocol.as_str().eq_ignore_ascii_case("websocket");
+    }
+
+    // Fallback for requests built by hand with a :protocol header
     if let Some(protocol) = headers.get(":protocol") {
         if let Ok(value) = protocol.to_str() {
             return value.eq_ignore_ascii_case("websocket");
@@ -379,6 +543,11 @@ pub fn is_http2_websocket_upgrade(request: &Request<Body>) -> bool {
     false
 }
 
+/// Check if a request is an HTTP/2 Extended CONNECT for WebSocket - specific for Body type
+pub fn is_http2_websocket_upgrade(request: &Request<Body>) -> bool {
+    is_http2_websocket_upgrade_generic(request)
+}
+
 /// Build a WebSocket upgrade response for HTTP/1.1
 pub fn build_websocket_response(
     request: &Request<Body>,
@@ -399,7 +568,7 @@ pub fn build_websocket_response(
         .header(CONNECTION, "Upgrade")
         .header(UPGRADE, "websocket")
         .header("Sec-WebSocket-Accept", accept)
-        .body(Empty::<Bytes>::new().boxed())?;
+        .body(empty_body())?;
 
     Ok(response)
 }
@@ -424,11 +593,43 @@ pub fn build_http2_websocket_response(
     }
 
     // HTTP/2 doesn't use Connection: Upgrade, the stream is already established
-    let response = builder.body(Empty::<Bytes>::new().boxed())?;
+    let response = builder.body(empty_body())?;
 
     Ok(response)
 }
 
+/// Build a 426 Upgrade Required response asking the client to switch to
+/// `protocol`, e.g. `"websocket"`
+///
+/// For routes that only speak the upgraded protocol: a plain request gets
+/// this instead of a page, with `Upgrade` and `Connection` naming the
+/// protocol to retry with. For WebSocket it also carries the supported
+/// `Sec-WebSocket-Version`.
+pub fn upgrade_required_response(protocol: &str) -> Response<Body> {
+    let mut response = Response::new(empty_body());
+    *response.status_mut() = StatusCode::UPGRADE_REQUIRED;
+
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(protocol) {
+        headers.insert(UPGRADE, value);
+        headers.insert(CONNECTION, HeaderValue::from_static("Upgrade"));
+    }
+    if protocol.eq_ignore_ascii_case("websocket") {
+        headers.insert("Sec-WebSocket-Version", HeaderValue::from_static("13"));
+    }
+
+    response
+}
+
+/// Endpoint config marking a route as WebSocket-only
+///
+/// Listed in an endpoint's `config = [...]`, it makes the server answer
+/// requests routed there that are not WebSocket upgrades with
+/// [`upgrade_required_response`] instead of running the handler. Upgrades,
+/// over HTTP/1.1 or HTTP/2 Extended CONNECT, reach the handler as usual.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WebSocketOnly;
+
 // ============================================================================
 // Protocol Switching Support
 // ============================================================================
@@ -479,6 +680,20 @@ pub async fn handle_websocket_upgrade(upgraded: Upgraded) {
     println!("🔌 WebSocket connection closed");
 }
 
+/// Handle a WebSocket carried by an HTTP/2 Extended CONNECT stream
+pub async fn handle_http2_websocket_upgrade(upgraded: Upgraded, stream_id: u32) {
+    println!("🔌 HTTP/2 stream {} switched to WebSocket", stream_id);
+
+    let connection = WebSocketConnection::accept_http2(upgraded, stream_id).await;
+    let protocol = WebSocketProtocol::from_http2_upgrade(connection.transport().id(), stream_id);
+
+    if let Err(e) = protocol.handle_websocket(connection.into_inner()).await {
+        eprintln!("WebSocket error on HTTP/2 stream {}: {:?}", stream_id, e);
+    }
+
+    println!("🔌 HTTP/2 WebSocket stream {} closed", stream_id);
+}
+
 /// Handle WebSocket upgrade specifically for file downloads
 pub async fn handle_download_websocket(upgraded: Upgraded) {
     use serde_json::json;
@@ -573,6 +788,10 @@ pub async fn handle_download_websocket(upgraded: Upgraded) {
                 println!("📥 WebSocket download connection closed");
                 break;
             }
+            Err(tungstenite::Error::Utf8) => {
+                ws_stream.send(invalid_utf8_close()).await.ok();
+                break;
+            }
             Err(e) => {
                 eprintln!("WebSocket error: {:?}", e);
                 break;
@@ -662,3 +881,266 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::full_body;
+    use bytes::Bytes;
+    use http_body_util::BodyExt;
+    use tokio_tungstenite::tungstenite::protocol::Role;
+    use tokio_tungstenite::tungstenite::protocol::frame::Frame;
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::{Data, OpCode};
+
+    async fn expect_frame<S>(client: &mut WebSocketStream<S>) -> WsMessage
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        client
+            .next()
+            .await
+            .expect("stream ended")
+            .expect("frame error")
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_timeout_ignores_pings_and_closes_normally() {
+        let (server_io, client_io) = tokio::io::duplex(4096);
+        let protocol =
+            WebSocketProtocol::new(ProtocolRole::Server).with_idle_timeout(Duration::from_secs(10));
+
+        let server = tokio::spawn(async move {
+            let ws = WebSocketStream::from_raw_socket(server_io, Role::Server, None).await;
+            protocol.handle_websocket(ws).await.unwrap();
+        });
+        let mut client = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+        assert!(matches!(
+            expect_frame(&mut client).await,
+            WsMessage::Text(_)
+        ));
+
+        // An application message restarts the idle timer.
+        tokio::time::advance(Duration::from_secs(6)).await;
+        client.send(WsMessage::Text("hi".into())).await.unwrap();
+        let last_message = tokio::time::Instant::now();
+        assert!(matches!(expect_frame(&mut client).await, WsMessage::Text(t) if t == "Echo: hi"));
+
+        // Pings keep flowing but do not count as activity.
+        for _ in 0..3 {
+            tokio::time::advance(Duration::from_secs(3)).await;
+            client.send(WsMessage::Ping(vec![1])).await.unwrap();
+            assert!(matches!(
+                expect_frame(&mut client).await,
+                WsMessage::Pong(_)
+            ));
+        }
+
+        // The paused clock jumps straight to the next timer, so the close
+        // must land exactly one timeout after "hi", not after the last ping.
+        loop {
+            match expect_frame(&mut client).await {
+                WsMessage::Pong(_) => continue,
+                WsMessage::Close(Some(frame)) => {
+                    assert_eq!(frame.code, CloseCode::Normal);
+                    assert_eq!(last_message.elapsed(), Duration::from_secs(10));
+                    break;
+                }
+                other => panic!("unexpected frame: {:?}", other),
+            }
+        }
+        server.await.unwrap();
+    }
+
+    async fn connect_echo_server() -> (
+        WebSocketStream<tokio::io::DuplexStream>,
+        tokio::task::JoinHandle<()>,
+    ) {
+        let (server_io, client_io) = tokio::io::duplex(4096);
+        let server = tokio::spawn(async move {
+            let ws = WebSocketStream::from_raw_socket(server_io, Role::Server, None).await;
+            WebSocketProtocol::new(ProtocolRole::Server)
+                .handle_websocket(ws)
+                .await
+                .unwrap();
+        });
+        let mut client = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+        assert!(matches!(
+            expect_frame(&mut client).await,
+            WsMessage::Text(_)
+        ));
+        (client, server)
+    }
+
+    fn fragment(opcode: OpCode, is_final: bool, payload: &[u8]) -> WsMessage {
+        WsMessage::Frame(Frame::message(payload.to_vec(), opcode, is_final))
+    }
+
+    #[tokio::test]
+    async fn text_split_inside_a_code_point_is_accepted() {
+        let (mut client, server) = connect_echo_server().await;
+
+        // "héllo" with the two bytes of 'é' in different fragments.
+        let bytes = "héllo".as_bytes();
+        client
+            .send(fragment(OpCode::Data(Data::Text), false, &bytes[..2]))
+            .await
+            .unwrap();
+        client
+            .send(fragment(OpCode::Data(Data::Continue), true, &bytes[2..]))
+            .await
+            .unwrap();
+        assert!(
+            matches!(expect_frame(&mut client).await, WsMessage::Text(t) if t == "Echo: héllo")
+        );
+
+        // Binary payloads are passed through without validation.
+        client
+            .send(WsMessage::Binary(vec![0xff, 0xfe]))
+            .await
+            .unwrap();
+        assert!(
+            matches!(expect_frame(&mut client).await, WsMessage::Binary(b) if b == [0xff, 0xfe])
+        );
+
+        client.send(WsMessage::Text("close".into())).await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn invalid_utf8_text_closes_with_1007() {
+        let (mut client, server) = connect_echo_server().await;
+
+        client
+            .send(fragment(OpCode::Data(Data::Text), false, b"ok "))
+            .await
+            .unwrap();
+        client
+            .send(fragment(OpCode::Data(Data::Continue), true, &[0xc3, 0x28]))
+            .await
+            .unwrap();
+
+        match expect_frame(&mut client).await {
+            WsMessage::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Invalid),
+            other => panic!("unexpected frame: {:?}", other),
+        }
+        server.await.unwrap();
+    }
+
+    async fn ws2_or_plain(
+        mut req: Request<hyper::body::Incoming>,
+    ) -> Result<Response<Body>, std::convert::Infallible> {
+        if !is_http2_websocket_upgrade_generic(&req) {
+            return Ok(Response::new(full_body("plain")));
+        }
+        let upgrade = hyper::upgrade::on(&mut req);
+        tokio::spawn(async move {
+            handle_http2_websocket_upgrade(upgrade.await.unwrap(), 1).await;
+        });
+        let (parts, _) = req.into_parts();
+        let req = Request::from_parts(parts, empty_body());
+        Ok(build_http2_websocket_response(&req).unwrap())
+    }
+
+    #[tokio::test]
+    async fn http2_websocket_stream_echoes_beside_plain_streams() {
+        use hyper::client::conn::http2 as client_http2;
+        use hyper::server::conn::http2 as server_http2;
+        use hyper::service::service_fn;
+        use hyper_util::rt::TokioExecutor;
+        use tokio::net::{TcpListener, TcpStream};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            server_http2::Builder::new(TokioExecutor::new())
+                .enable_connect_protocol()
+                .serve_connection(TokioIo::new(socket), service_fn(ws2_or_plain))
+                .await
+                .unwrap();
+        });
+
+        let socket = TcpStream::connect(addr).await.unwrap();
+        let (mut sender, connection) =
+            client_http2::handshake(TokioExecutor::new(), TokioIo::new(socket))
+                .await
+                .unwrap();
+        tokio::spawn(connection);
+
+        async fn plain_get(sender: &mut client_http2::SendRequest<Body>) -> Bytes {
+            let request = Request::get("http://localhost/plain")
+                .body(empty_body())
+                .unwrap();
+            let response = sender.send_request(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            response.into_body().collect().await.unwrap().to_bytes()
+        }
+
+        // Also makes sure the server's SETTINGS, which allow Extended
+        // CONNECT, have arrived
+        assert_eq!(plain_get(&mut sender).await, "plain");
+
+        let mut connect = Request::connect("http://localhost/ws2")
+            .header("sec-websocket-version", "13")
+            .body(empty_body())
+            .unwrap();
+        connect
+            .extensions_mut()
+            .insert(hyper::ext::Protocol::from_static("websocket"));
+        let response = sender.send_request(connect).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let upgraded = hyper::upgrade::on(response).await.unwrap();
+        let mut client =
+            WebSocketStream::from_raw_socket(TokioIo::new(upgraded), Role::Client, None).await;
+        assert!(matches!(
+            expect_frame(&mut client).await,
+            WsMessage::Text(_)
+        ));
+
+        // Another stream is served normally while the WebSocket is open
+        assert_eq!(plain_get(&mut sender).await, "plain");
+
+        client.send(WsMessage::Text("hi".into())).await.unwrap();
+        assert!(matches!(expect_frame(&mut client).await, WsMessage::Text(t) if t == "Echo: hi"));
+
+        client.send(WsMessage::Text("close".into())).await.unwrap();
+        assert!(
+            matches!(expect_frame(&mut client).await, WsMessage::Text(t) if t == "Echo: close")
+        );
+        assert!(matches!(
+            expect_frame(&mut client).await,
+            WsMessage::Close(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn connection_sends_and_receives_messages() {
+        let (server_io, client_io) = tokio::io::duplex(4096);
+        let server = tokio::spawn(async move {
+            let mut connection =
+                WebSocketConnection::accept(server_io, WebSocketTransport::from_http2_stream(7, 3))
+                    .await;
+            while let Some(message) = connection.recv_message().await.unwrap() {
+                if let WsMessage::Text(text) = message.0 {
+                    let reply = WebSocketMessage(WsMessage::Text(text.to_uppercase()));
+                    connection.send_message(reply).await.unwrap();
+                }
+            }
+            connection.transport().clone()
+        });
+
+        let mut client = WebSocketStream::from_raw_socket(client_io, Role::Client, None).await;
+        client.send(WsMessage::Text("ping".into())).await.unwrap();
+        assert!(matches!(expect_frame(&mut client).await, WsMessage::Text(t) if t == "PING"));
+        client.close(None).await.unwrap();
+
+        let transport = server.await.unwrap();
+        assert_eq!(transport.id(), 7);
+        assert!(matches!(
+            transport.upgraded_from,
+            UpgradeSource::Http2Stream
+        ));
+        assert_eq!(transport.message_count, 2);
+        assert!(transport.is_closing);
+    }
+}
@@ -15,6 +15,10 @@ pub use hotaru_http::{
     TlsTransport,
 };
 
+// Request recording and replay (gated by the `http_record` feature).
+#[cfg(feature = "http_record")]
+pub use hotaru_http::{RequestRecorder, record};
+
 // Request / response / context
 pub use hotaru_http::context::HttpReqCtx;
 pub use hotaru_http::context::{Executable, HttpContext};
@@ -25,6 +29,7 @@ pub use hotaru_http::request::HttpRequest;
 pub use hotaru_http::response::HttpResponse;
 
 // HTTP types
+pub use hotaru_http::ConcurrencyLimit;
 pub use hotaru_http::body::*;
 pub use hotaru_http::cookie::*;
 pub use hotaru_http::encoding::*;
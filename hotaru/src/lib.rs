@@ -63,8 +63,8 @@ pub use hotaru_core::connection::ConnStream;
 pub use hotaru_core::connection::error::{ConnectionError, Result};
 pub use hotaru_core::connection::{Inbound, Outbound};
 pub use hotaru_core::protocol::{
-    BoxProtocolError, DefaultProtocolError, EmptyError, EndpointOutcome, Message, Protocol,
-    ProtocolError, ProtocolRole, RequestContext, Stream,
+    BoxProtocolError, DefaultProtocolError, EmptyError, EndpointOutcome, HeaderMultiMap, Message,
+    Protocol, ProtocolError, ProtocolRole, RequestContext, Stream,
 };
 // `hotaru_io_embedded` is not surfaced through the umbrella in 0.8.x (`hotaru`
 // is std-only — see Cargo.toml). For no_std, use `hotaru_core` +
@@ -78,6 +78,8 @@ pub use hotaru_io_tokio::{
     TcpAccepter, TcpConnector, TcpConnectorAddr, TcpInbound, TcpMeta, TcpOutbound, TcpStream,
     TcpTransport, TokioBackend, TokioIo,
 };
+#[cfg(all(feature = "io_tokio", unix))]
+pub use hotaru_io_tokio::{UnixInbound, UnixMeta, UnixOutbound, UnixStream, UnixTransport};
 
 pub use hotaru_core::extensions::*;
 
@@ -91,6 +93,7 @@ pub use hrt::call;
 pub use hrt::endpoint;
 pub use hrt::middleware;
 pub use hrt::outpoint;
+pub use hrt::resource;
 pub use hrt::run;
 pub use hrt::{run_server, run_server_no_block, run_server_no_block_until, run_server_until};
 pub use hrt::{LClient, LPattern, LServer, LUrl};
@@ -35,6 +35,7 @@ pub use crate::call;
 pub use crate::endpoint;
 pub use crate::middleware;
 pub use crate::outpoint;
+pub use crate::resource;
 pub use crate::run;
 pub use crate::{LClient, LPattern, LServer, LUrl};
 
@@ -0,0 +1,53 @@
+//! `endpoint!` with several URLs registers one handler under each of them.
+
+use std::sync::Arc;
+
+use hotaru::http::*;
+use hotaru::prelude::*;
+use hotaru_io_tokio::testing::http_get;
+
+pub static APP: SServer = Lazy::new(|| {
+    Server::new()
+        .binding("127.0.0.1:0")
+        .single_protocol(ProtocolBuilder::new(HTTP::server(HttpSafety::default())))
+        .build()
+});
+
+endpoint! {
+    APP.url("/greeting"),
+    APP.url("/hello"),
+    "/hi/<name>",
+
+    pub greeting <HTTP> {
+        text_response(format!("hello {}", req.pattern("name").unwrap_or_default()))
+    }
+}
+
+#[tokio::test]
+async fn aliases_share_one_handler() {
+    let root = APP.registry.url::<HTTP>().unwrap();
+    let mut handlers = Vec::new();
+    for path in ["/greeting", "/hello", "/hi/someone"] {
+        let node = root.walk_str(path).await.unwrap();
+        handlers.push(node.binding().handler().expect(path));
+    }
+    assert!(Arc::ptr_eq(&handlers[0], &handlers[1]));
+    assert!(Arc::ptr_eq(&handlers[0], &handlers[2]));
+}
+
+#[tokio::test]
+async fn every_url_is_served() {
+    APP.ensure_inbounds().await.unwrap();
+    tokio::spawn(APP.clone().run_until(std::future::pending()));
+    let addr = APP.local_addr().unwrap();
+
+    for (path, body) in [
+        ("/greeting", "hello "),
+        ("/hello", "hello "),
+        ("/hi/there", "hello there"),
+    ] {
+        let response = http_get(addr, path).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{path}: {response}");
+        assert!(response.ends_with(body), "{path}: {response}");
+    }
+}
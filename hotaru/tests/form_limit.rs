@@ -0,0 +1,55 @@
+//! A urlencoded body over `max_form_buffer_size` is refused with 413.
+
+use hotaru::http::*;
+use hotaru::prelude::*;
+use hotaru_io_tokio::testing::send_raw;
+
+pub static APP: SServer = Lazy::new(|| {
+    Server::new()
+        .binding("127.0.0.1:0")
+        .single_protocol(ProtocolBuilder::new(HTTP::server(
+            HttpSafety::new().with_max_form_buffer_size(64),
+        )))
+        .build()
+});
+
+endpoint! {
+    APP.url("/signup"),
+
+    pub signup <HTTP> {
+        let name = req
+            .form()
+            .await
+            .and_then(|form| form.get("name").cloned())
+            .unwrap_or_default();
+        text_response(format!("welcome {name}"))
+    }
+}
+
+async fn post_form(addr: std::net::SocketAddr, body: &str) -> String {
+    let request = format!(
+        "POST /signup HTTP/1.1\r\nHost: localhost\r\n\
+         Content-Type: application/x-www-form-urlencoded\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    send_raw(addr, request).await
+}
+
+#[tokio::test]
+async fn oversized_form_is_rejected() {
+    APP.ensure_inbounds().await.unwrap();
+    tokio::spawn(APP.clone().run_until(std::future::pending()));
+    let addr = APP.local_addr().unwrap();
+
+    let response = post_form(addr, "name=ayaka").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.ends_with("welcome ayaka"), "{response}");
+
+    let mut body = String::from("name=ayaka");
+    for i in 0..20 {
+        body.push_str(&format!("&field{i}=value{i}"));
+    }
+    let response = post_form(addr, &body).await;
+    assert!(response.starts_with("HTTP/1.1 413"), "{response}");
+}
@@ -0,0 +1,53 @@
+//! `endpoint!` in `raw` mode: the handler owns the context and the
+//! generated `__wrapper_*` returns it as is.
+
+use hotaru::http::*;
+use hotaru::prelude::*;
+
+pub static APP: SServer = Lazy::new(|| {
+    Server::new()
+        .binding("127.0.0.1:0")
+        .single_protocol(ProtocolBuilder::new(HTTP::server(HttpSafety::default())))
+        .build()
+});
+
+endpoint! {
+    APP.url("/raw"),
+    raw,
+
+    /// Expands to
+    /// `async fn raw_hello(mut req: <HTTP as Protocol>::Context) -> <HTTP as Protocol>::Context`
+    /// and a wrapper whose body is `Ok(raw_hello(req).await)`
+    pub raw_hello <HTTP> {
+        req.response = text_response("raw");
+        req
+    }
+}
+
+endpoint! {
+    APP.url("/plain"),
+
+    pub plain_hello <HTTP> {
+        text_response("plain")
+    }
+}
+
+fn context() -> HttpContext {
+    HttpContext::new_client(String::new(), HttpSafety::default())
+}
+
+#[tokio::test]
+async fn raw_wrapper_returns_the_handlers_context() {
+    let ctx = __wrapper_raw_hello(context()).await.unwrap();
+    assert!(matches!(&ctx.response.body, HttpBody::Text(text) if text == "raw"));
+}
+
+#[tokio::test]
+async fn raw_handler_is_callable_by_value() {
+    let ctx = raw_hello(context()).await;
+    assert!(matches!(&ctx.response.body, HttpBody::Text(text) if text == "raw"));
+
+    // The default wrapper still applies the returned outcome
+    let ctx = __wrapper_plain_hello(context()).await.unwrap();
+    assert!(matches!(&ctx.response.body, HttpBody::Text(text) if text == "plain"));
+}
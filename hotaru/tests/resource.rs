@@ -0,0 +1,63 @@
+//! `resource!` registers the collection and item routes of a REST resource
+//! and dispatches each request to the handler for its method.
+
+use hotaru::http::*;
+use hotaru::prelude::*;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+pub static APP: SServer = Lazy::new(|| {
+    Server::new()
+        .binding("127.0.0.1:0")
+        .single_protocol(ProtocolBuilder::new(HTTP::server(HttpSafety::default())))
+        .build()
+});
+
+resource! {
+    APP.url("/users"),
+
+    pub users <HTTP> {
+        GET { text_response("list") }
+        POST { text_response("create") }
+        GET <id> { text_response(format!("show {}", req.pattern("id").unwrap_or_default())) }
+        PUT <id> { text_response(format!("update {}", req.pattern("id").unwrap_or_default())) }
+        DELETE <id> { text_response(format!("delete {}", req.pattern("id").unwrap_or_default())) }
+    }
+}
+
+async fn send(addr: std::net::SocketAddr, method: &str, path: &str) -> String {
+    let mut client = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+    client.write_all(request.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    client.read_to_end(&mut response).await.unwrap();
+    String::from_utf8(response).unwrap()
+}
+
+#[tokio::test]
+async fn all_five_routes_register() {
+    APP.ensure_inbounds().await.unwrap();
+    tokio::spawn(APP.clone().run_until(std::future::pending()));
+    let addr = APP.local_addr().unwrap();
+
+    for (method, path, body) in [
+        ("GET", "/users", "list"),
+        ("POST", "/users", "create"),
+        ("GET", "/users/7", "show 7"),
+        ("PUT", "/users/7", "update 7"),
+        ("DELETE", "/users/7", "delete 7"),
+    ] {
+        let response = send(addr, method, path).await;
+        assert!(
+            response.starts_with("HTTP/1.1 200"),
+            "{method} {path}: {response}"
+        );
+        assert!(response.ends_with(body), "{method} {path}: {response}");
+    }
+
+    // A method the resource does not declare for that path
+    let response = send(addr, "DELETE", "/users").await;
+    assert!(response.starts_with("HTTP/1.1 405"), "{response}");
+}
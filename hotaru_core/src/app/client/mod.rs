@@ -12,8 +12,9 @@ use crate::{
     app::runtime::{Either, OnceCellCap, RuntimeSpec},
     connection::{Outbound, TransportSpec},
     executable::ExecutableBinding,
+    marker::MaybeSend,
     protocol::Protocol,
-    protocol::{Channel, RequestContext},
+    protocol::{Channel, ProtocolError, RequestContext},
     url::{PathPattern, UrlError, UrlNode, UrlRoot, node::StepName},
 };
 
@@ -150,6 +151,12 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> Client<TS, Rt> {
         self.config.max_frame_process_time()
     }
 
+    /// Returns the timeout applied to outpoint calls that don't set their
+    /// own.
+    pub fn get_default_timeout(self: &Arc<Self>) -> TimeoutSetting {
+        self.config.request_timeout()
+    }
+
     /// Returns the `TS::Outbound` instance, building on first use.
     pub async fn ensure_outbound(self: &Arc<Self>) -> Result<&Arc<TS::Outbound>, TS::IoError> {
         self.outbound
@@ -205,7 +212,9 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> Client<TS, Rt> {
         ctx: P::Context,
     ) -> Result<Result<P::Context, <P::Context as RequestContext>::Error>, UrlError> {
         let outpoint = self.resolve::<P>(path).await?;
-        Ok(outpoint.run(ctx).await)
+        Ok(self
+            .with_timeout::<P, _>(outpoint.run(ctx), TimeoutSetting::Inherit)
+            .await)
     }
 
     /// Executes one outbound route by path with an explicit depth limit.
@@ -216,7 +225,9 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> Client<TS, Rt> {
         ctx: P::Context,
     ) -> Result<Result<P::Context, <P::Context as RequestContext>::Error>, UrlError> {
         let outpoint = self.resolve_with_limit::<P>(path, max_depth).await?;
-        Ok(outpoint.run(ctx).await)
+        Ok(self
+            .with_timeout::<P, _>(outpoint.run(ctx), TimeoutSetting::Inherit)
+            .await)
     }
 
     /// Run a named outpoint: look up the access point, open a wire, build the
@@ -230,6 +241,25 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> Client<TS, Rt> {
         Result<<P::Context as RequestContext>::Response, <P::Context as RequestContext>::Error>,
         UrlError,
     >
+    where
+        P: Protocol<Wire = TS::Wire, TS = TS> + 'static,
+    {
+        self.request_fn_with_timeout::<P>(name, request, TimeoutSetting::Inherit)
+            .await
+    }
+
+    /// Same as `request_fn`, with a timeout for this call alone: `Inherit`
+    /// keeps the client's `default_timeout`, `Disabled` lets the call take
+    /// as long as it needs.
+    pub async fn request_fn_with_timeout<P>(
+        self: &Arc<Self>,
+        name: &str,
+        request: <P::Context as RequestContext>::Request,
+        timeout: TimeoutSetting,
+    ) -> Result<
+        Result<<P::Context as RequestContext>::Response, <P::Context as RequestContext>::Error>,
+        UrlError,
+    >
     where
         P: Protocol<Wire = TS::Wire, TS = TS> + 'static,
     {
@@ -245,24 +275,55 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> Client<TS, Rt> {
             .resolve()
             .ok_or_else(|| UrlError::InvalidPath(name.to_string()))?;
 
+        let mut ctx = P::Context::default();
+        ctx.inject_request(request);
+
         // Inner: connect-IO + chain errors land in CtxError<P>.
-        let inner: Result<_, <P::Context as RequestContext>::Error> = async {
+        let call = async {
             let outbound = self.ensure_outbound().await?.clone();
             let channel = entry
                 .protocol
                 .acquire_channel(&self.runtime, outbound)
                 .await?;
 
-            let mut ctx = P::Context::default();
             P::install_channel(&mut ctx, channel);
-            ctx.inject_request(request);
+            node.run(ctx).await
+        };
 
-            let ctx = node.run(ctx).await?;
-            Ok(ctx.into_response())
-        }
-        .await;
+        Ok(self
+            .with_timeout::<P, _>(call, timeout)
+            .await
+            .map(|ctx| ctx.into_response()))
+    }
 
-        Ok(inner)
+    /// Runs one outpoint call under `timeout`, resolving `Inherit` to the
+    /// client's default. Calls are unbounded when the protocol's error has
+    /// no timeout form (`ProtocolError::from_timeout` gives `None`).
+    async fn with_timeout<P, T>(
+        &self,
+        call: impl Future<Output = Result<T, <P::Context as RequestContext>::Error>> + MaybeSend,
+        timeout: TimeoutSetting,
+    ) -> Result<T, <P::Context as RequestContext>::Error>
+    where
+        P: Protocol<Wire = TS::Wire, TS = TS> + 'static,
+        T: MaybeSend,
+    {
+        let limit = match timeout {
+            TimeoutSetting::Inherit => match self.config.request_timeout() {
+                TimeoutSetting::Fixed(limit) => Some(limit),
+                _ => None,
+            },
+            TimeoutSetting::Disabled => None,
+            TimeoutSetting::Fixed(limit) => Some(limit),
+        };
+        let timed_out = limit.and_then(<P::Context as RequestContext>::Error::from_timeout);
+        match (limit, timed_out) {
+            (Some(limit), Some(timed_out)) => match Rt::select2(call, Rt::sleep(limit)).await {
+                Either::Left(result) => result,
+                Either::Right(()) => Err(timed_out),
+            },
+            _ => call.await,
+        }
     }
 
     /// Spawn a persistent call task: one outpoint, one channel, looped while
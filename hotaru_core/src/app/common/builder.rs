@@ -1,7 +1,9 @@
 #[cfg(not(feature = "std"))]
 use crate::prelude::*;
 use alloc::sync::Arc;
+use core::fmt;
 use core::marker::PhantomData;
+use core::time::Duration;
 
 use crate::{
     app::{client::Client, registry::ProtocolRegistryKind, runtime::RuntimeSpec, server::Server},
@@ -13,6 +15,30 @@ use crate::{
 
 use super::{OperationalConfig, RunMode, RuntimeConfig, TimeoutSetting};
 
+/// Error returned by [`AppBuilder::binding_from_env`] when the address it
+/// would listen on is not a socket address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidBinding {
+    /// The address that failed to parse.
+    pub address: String,
+    /// The environment variable it came from; `None` for the default.
+    pub var: Option<String>,
+    /// Why the address did not parse.
+    pub error: core::net::AddrParseError,
+}
+
+impl fmt::Display for InvalidBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.var {
+            Some(var) => write!(f, "invalid address {:?} from {}", self.address, var)?,
+            None => write!(f, "invalid default binding {:?}", self.address)?,
+        }
+        write!(f, ": {}", self.error)
+    }
+}
+
+impl core::error::Error for InvalidBinding {}
+
 pub struct ServerRole;
 pub struct ClientRole;
 
@@ -23,12 +49,18 @@ pub struct ClientRole;
 /// `AppBuilder<ClientRole, TS, Rt>` builds a [`Client`].
 pub struct AppBuilder<R, TS: TransportSpec, Rt: RuntimeSpec> {
     registry: Option<ProtocolEntryRegistry<TS>>,
-    binding: Option<<TS::Inbound as Inbound>::BindTarget>,
+    bindings: Vec<<TS::Inbound as Inbound>::BindTarget>,
+    binding_labels: Vec<Option<String>>,
     target: Option<<TS::Outbound as Outbound>::ConnectTarget>,
     mode: Option<RunMode>,
     worker: Option<usize>,
     max_connection_time: Option<TimeoutSetting>,
     max_frame_process_time: Option<usize>,
+    max_connections: Option<usize>,
+    accept_parallelism: Option<usize>,
+    catch_panics: Option<bool>,
+    case_insensitive_routes: Option<bool>,
+    default_timeout: Option<Duration>,
     config: Params,
     statics: Locals,
     _role: PhantomData<R>,
@@ -39,12 +71,18 @@ impl<R, TS: TransportSpec, Rt: RuntimeSpec> AppBuilder<R, TS, Rt> {
     pub fn new() -> Self {
         Self {
             registry: None,
-            binding: None,
+            bindings: Vec::new(),
+            binding_labels: Vec::new(),
             target: None,
             mode: None,
             worker: None,
             max_connection_time: None,
             max_frame_process_time: None,
+            max_connections: None,
+            accept_parallelism: None,
+            catch_panics: None,
+            case_insensitive_routes: None,
+            default_timeout: None,
             config: Params::new(),
             statics: Locals::new(),
             _role: PhantomData,
@@ -52,6 +90,20 @@ impl<R, TS: TransportSpec, Rt: RuntimeSpec> AppBuilder<R, TS, Rt> {
         }
     }
 
+    /// Adds an address for the server to listen on.
+    ///
+    /// Call it more than once to listen on several addresses (say an
+    /// internal and a public interface); `run` accepts on all of them and
+    /// they share the same routes.
+    ///
+    /// Each call adds a listener. Servers used to have a single binding,
+    /// and a second call replaced the first address; code relying on that
+    /// should call it once, or use
+    /// [`binding_from_env`](Self::binding_from_env), which still replaces.
+    ///
+    /// Every binding serves all the protocols in the registry; a binding
+    /// cannot be limited to some of them. Build a second server with its
+    /// own registry for that.
     pub fn binding<T: Into<String>>(self, binding: T) -> Self
     where
         <TS::Inbound as Inbound>::BindTarget: From<String>,
@@ -59,13 +111,80 @@ impl<R, TS: TransportSpec, Rt: RuntimeSpec> AppBuilder<R, TS, Rt> {
         self.with_binding(binding.into().into())
     }
 
+    /// Adds an address to listen on, tagged with `label`.
+    ///
+    /// Handlers read the tag of the binding a request came in on (e.g.
+    /// `HttpContext::binding_label`) to tell an internal interface from a
+    /// public one. Untagged bindings are labeled with their bound address.
+    pub fn labeled_binding<T: Into<String>, L: Into<String>>(self, binding: T, label: L) -> Self
+    where
+        <TS::Inbound as Inbound>::BindTarget: From<String>,
+    {
+        let mut builder = self.binding(binding);
+        if let Some(last) = builder.binding_labels.last_mut() {
+            *last = Some(label.into());
+        }
+        builder
+    }
+
+    /// Listens on the address in the environment variable `var`, or on
+    /// `default` if it is unset or empty.
+    ///
+    /// Lets a container pick the listen address without a rebuild:
+    ///
+    /// ```ignore
+    /// let app = App::new()
+    ///     .binding_from_env("BIND_ADDR", "127.0.0.1:3090")?
+    ///     .build();
+    /// ```
+    ///
+    /// Unlike [`binding`](Self::binding), this replaces the bindings added
+    /// so far, so the environment decides the one address served; add any
+    /// further bindings after it.
+    ///
+    /// Fails with [`InvalidBinding`] if the address used does not parse as
+    /// a `SocketAddr`, such as `0.0.0.0:8080` or `[::1]:8080`. Host names
+    /// are not resolved.
+    #[cfg(feature = "std")]
+    pub fn binding_from_env<K: AsRef<str>, D: Into<String>>(
+        mut self,
+        var: K,
+        default: D,
+    ) -> Result<Self, InvalidBinding>
+    where
+        <TS::Inbound as Inbound>::BindTarget: From<String>,
+    {
+        let var = var.as_ref();
+        let binding = env_binding(var, std::env::var(var).ok(), default.into())?;
+        self.bindings.clear();
+        self.binding_labels.clear();
+        Ok(self.binding(binding))
+    }
+
+    /// Binds the server to a Unix domain socket at `path`.
+    ///
+    /// Only available for transports whose bind target is a filesystem path,
+    /// such as `hotaru_io_tokio::UnixTransport`. The socket file is removed
+    /// when the server shuts down.
+    #[cfg(feature = "std")]
+    pub fn binding_uds<P: Into<std::path::PathBuf>>(self, path: P) -> Self
+    where
+        <TS::Inbound as Inbound>::BindTarget: From<std::path::PathBuf>,
+    {
+        self.with_binding(path.into().into())
+    }
+
     pub fn registry(mut self, protocol: ProtocolEntryRegistry<TS>) -> Self {
         self.registry = Some(protocol);
         self
     }
 
+    /// Adds a transport-specific bind target, e.g. a TLS target carrying
+    /// its own certificate. Like [`binding`](Self::binding), repeated calls
+    /// add further listeners.
     pub fn with_binding(mut self, binding: <TS::Inbound as Inbound>::BindTarget) -> Self {
-        self.binding = Some(binding);
+        self.bindings.push(binding);
+        self.binding_labels.push(None);
         self
     }
 
@@ -111,6 +230,32 @@ impl<R, TS: TransportSpec, Rt: RuntimeSpec> AppBuilder<R, TS, Rt> {
         self
     }
 
+    /// Caps how many connections the server serves at once. At the cap the
+    /// accept loop stops accepting until a connection closes; waiting
+    /// clients stay in the listener's backlog.
+    pub fn max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = Some(max_connections);
+        self
+    }
+
+    /// Runs `accept_parallelism` accept loops against each listener
+    /// instead of one, so a burst of new connections is taken off the
+    /// backlog by several loops at once. This shares one socket, unlike
+    /// `SO_REUSEPORT`. Defaults to 1.
+    pub fn accept_parallelism(mut self, accept_parallelism: usize) -> Self {
+        self.accept_parallelism = Some(accept_parallelism);
+        self
+    }
+
+    /// Whether a panic in a handler is caught and answered with the
+    /// protocol's default error response (HTTP: `500`), or left to crash the
+    /// connection's task without a response. On by default; turn it off to
+    /// fail fast during development.
+    pub fn catch_panics(mut self, catch_panics: bool) -> Self {
+        self.catch_panics = Some(catch_panics);
+        self
+    }
+
     pub fn statics(mut self, statics: Locals) -> Self {
         self.statics = statics;
         self
@@ -137,38 +282,65 @@ impl<R, TS: TransportSpec, Rt: RuntimeSpec> AppBuilder<R, TS, Rt> {
 }
 
 impl<TS: TransportSpec, Rt: RuntimeSpec> AppBuilder<ServerRole, TS, Rt> {
+    /// Whether literal route segments match ignoring ASCII case, so a
+    /// request for `/Users` reaches a `/users` route. Captured segments
+    /// (`<id>`, `<**path>`, regex) are passed to handlers as sent. Off by
+    /// default.
+    pub fn case_insensitive_routes(mut self, enabled: bool) -> Self {
+        self.case_insensitive_routes = Some(enabled);
+        self
+    }
+
     /// Builds a server runtime from the configured server-side builder state.
     ///
     /// Panics - server runtimes require a protocol registry, so this must be set
     /// via the builder methods before calling `build()`.
     pub fn build(self) -> Arc<Server<TS, Rt>> {
+        let case_insensitive_routes = self.case_insensitive_routes;
         let registry = self
             .registry
+            .inspect(|registry| {
+                if let Some(enabled) = case_insensitive_routes {
+                    registry.set_case_insensitive_routes(enabled);
+                }
+            })
             .map(ProtocolRegistryKind::from)
             .expect("AppBuilder::registry(...) must be set for App<TS>");
-        let binding = self
-            .binding
-            .or_else(TS::default_inbound)
-            .expect("AppBuilder::binding(...) must be set for Server<TS>");
+        let mut bindings = self.bindings;
+        let mut binding_labels = self.binding_labels;
+        if bindings.is_empty() {
+            bindings.extend(TS::default_inbound());
+            binding_labels.resize(bindings.len(), None);
+        }
+        assert!(
+            !bindings.is_empty(),
+            "AppBuilder::binding(...) must be set for Server<TS>"
+        );
 
         let mode = self.mode.unwrap_or(RunMode::Development);
         let worker = self.worker.unwrap_or_else(num_cpus);
         let max_connection_time = self.max_connection_time.unwrap_or(TimeoutSetting::Inherit);
         let max_frame_process_time = self.max_frame_process_time.unwrap_or(5);
-        let runtime = RuntimeConfig::from_parts(mode, self.config, self.statics);
-        let config = OperationalConfig::from_server_parts(
+        let mut runtime = RuntimeConfig::from_parts(mode, self.config, self.statics);
+        runtime.set_catch_panics(self.catch_panics.unwrap_or(true));
+        let mut config = OperationalConfig::from_server_parts(
             worker,
             max_connection_time,
             max_frame_process_time,
         );
+        config.set_max_connections(self.max_connections);
+        config.set_accept_parallelism(self.accept_parallelism.unwrap_or(1));
         let runtime = Arc::new(runtime);
 
         let app = Arc::new(Server {
             registry,
-            binding,
-            inbound: Default::default(),
+            bindings,
+            binding_labels,
+            inbounds: Default::default(),
             runtime,
             config,
+            connections: Default::default(),
+            closing: Default::default(),
             _rt: PhantomData,
         });
 
@@ -177,6 +349,16 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> AppBuilder<ServerRole, TS, Rt> {
 }
 
 impl<TS: TransportSpec, Rt: RuntimeSpec> AppBuilder<ClientRole, TS, Rt> {
+    /// Caps how long each outpoint call made through the client may take,
+    /// from connecting to the end of its chain. A call that runs longer
+    /// fails with the protocol's timeout error; `request_fn_with_timeout`
+    /// overrides it for a single call. Takes precedence over
+    /// `max_frame_process_time`.
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
     /// Builds a client runtime from the configured client-side builder state.
     ///
     /// Panics - client runtimes require a target and protocol registry, so these must be set
@@ -196,8 +378,9 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> AppBuilder<ClientRole, TS, Rt> {
             .max_connection_time
             .unwrap_or(TimeoutSetting::Seconds(30));
         let request_timeout = self
-            .max_frame_process_time
-            .map(|n| TimeoutSetting::Seconds(n))
+            .default_timeout
+            .map(TimeoutSetting::Fixed)
+            .or_else(|| self.max_frame_process_time.map(TimeoutSetting::Seconds))
             .unwrap_or(TimeoutSetting::Seconds(30));
         let runtime = Arc::new(RuntimeConfig::from_parts(mode, self.config, self.statics));
         let config = OperationalConfig::from_client_parts(connect_timeout, request_timeout);
@@ -228,3 +411,59 @@ fn num_cpus() -> usize {
 fn num_cpus() -> usize {
     1
 }
+
+/// The address [`AppBuilder::binding_from_env`] listens on, given the value
+/// of `var` (if set).
+#[cfg(feature = "std")]
+fn env_binding(
+    var: &str,
+    value: Option<String>,
+    default: String,
+) -> Result<String, InvalidBinding> {
+    let (address, var) = match value {
+        Some(value) if !value.trim().is_empty() => (value.trim().to_string(), Some(var)),
+        _ => (default, None),
+    };
+    match address.parse::<core::net::SocketAddr>() {
+        Ok(_) => Ok(address),
+        Err(error) => Err(InvalidBinding {
+            address,
+            var: var.map(str::to_string),
+            error,
+        }),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_binding_prefers_the_variable() {
+        let default = || "0.0.0.0:3090".to_string();
+        let set = env_binding("BIND", Some(" 127.0.0.1:8080 ".into()), default());
+        assert_eq!(set.unwrap(), "127.0.0.1:8080");
+        assert_eq!(
+            env_binding("BIND", None, default()).unwrap(),
+            "0.0.0.0:3090"
+        );
+        assert_eq!(
+            env_binding("BIND", Some("".into()), default()).unwrap(),
+            "0.0.0.0:3090"
+        );
+    }
+
+    #[test]
+    fn env_binding_rejects_host_names() {
+        let err = env_binding("BIND", Some("localhost:80".into()), String::new()).unwrap_err();
+        assert_eq!(err.address, "localhost:80");
+        assert_eq!(err.var.as_deref(), Some("BIND"));
+        assert_eq!(
+            err.to_string(),
+            format!("invalid address \"localhost:80\" from BIND: {}", err.error)
+        );
+
+        let err = env_binding("BIND", None, "localhost:3090".into()).unwrap_err();
+        assert_eq!(err.var, None);
+    }
+}
@@ -7,7 +7,7 @@ pub mod runmode;
 /// Shared runtime configuration and extension storage.
 pub mod runtime;
 
-pub use builder::AppBuilder;
+pub use builder::{AppBuilder, InvalidBinding};
 pub use operational_config::{OperationalConfig, TimeoutSetting};
 pub use runmode::RunMode;
 pub use runtime::RuntimeConfig;
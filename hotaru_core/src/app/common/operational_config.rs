@@ -32,6 +32,8 @@ pub struct OperationalConfig {
     max_frame_process_time: usize,
     connect_timeout: TimeoutSetting,
     request_timeout: TimeoutSetting,
+    max_connections: Option<usize>,
+    accept_parallelism: usize,
 }
 
 impl Default for OperationalConfig {
@@ -42,6 +44,8 @@ impl Default for OperationalConfig {
             max_frame_process_time: 5,
             connect_timeout: TimeoutSetting::Seconds(30),
             request_timeout: TimeoutSetting::Seconds(30),
+            max_connections: None,
+            accept_parallelism: 1,
         }
     }
 }
@@ -66,6 +70,8 @@ impl OperationalConfig {
             max_frame_process_time,
             connect_timeout,
             request_timeout,
+            max_connections: None,
+            accept_parallelism: 1,
         }
     }
 
@@ -131,6 +137,16 @@ impl OperationalConfig {
         self.request_timeout
     }
 
+    /// Returns the cap on simultaneously served connections, if any.
+    pub fn max_connections(&self) -> Option<usize> {
+        self.max_connections
+    }
+
+    /// Returns how many accept loops run against each listener.
+    pub fn accept_parallelism(&self) -> usize {
+        self.accept_parallelism
+    }
+
     /// Replaces the worker thread count.
     pub fn set_worker(&mut self, worker: usize) {
         self.worker = worker;
@@ -155,4 +171,16 @@ impl OperationalConfig {
     pub fn set_request_timeout(&mut self, request_timeout: TimeoutSetting) {
         self.request_timeout = request_timeout;
     }
+
+    /// Replaces the cap on simultaneously served connections. `None`
+    /// accepts without limit.
+    pub fn set_max_connections(&mut self, max_connections: Option<usize>) {
+        self.max_connections = max_connections;
+    }
+
+    /// Replaces how many accept loops run against each listener. Values
+    /// below 1 are treated as 1.
+    pub fn set_accept_parallelism(&mut self, accept_parallelism: usize) {
+        self.accept_parallelism = accept_parallelism.max(1);
+    }
 }
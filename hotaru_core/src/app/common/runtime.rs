@@ -1,4 +1,12 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use alloc::sync::Arc;
+use core::net::SocketAddr;
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::app::server::{ActiveConnections, ConnectionGuard, ShutdownPhase};
 use crate::extensions::{Locals, Params};
+use crate::marker::PRwLock;
 
 use super::RunMode;
 
@@ -8,6 +16,11 @@ pub struct RuntimeConfig {
     mode: RunMode,
     config: Params,
     statics: Locals,
+    draining: AtomicBool,
+    shutdown_phase: AtomicU8,
+    in_flight: Arc<ActiveConnections>,
+    binding_labels: PRwLock<Vec<(SocketAddr, Arc<str>)>>,
+    propagate_panics: bool,
 }
 
 impl RuntimeConfig {
@@ -22,6 +35,11 @@ impl RuntimeConfig {
             mode,
             config,
             statics,
+            draining: AtomicBool::new(false),
+            shutdown_phase: AtomicU8::new(0),
+            in_flight: Arc::default(),
+            binding_labels: PRwLock::new(Vec::new()),
+            propagate_panics: false,
         }
     }
 
@@ -45,6 +63,91 @@ impl RuntimeConfig {
         &self.statics
     }
 
+    /// Whether the server has started shutting down.
+    ///
+    /// Set once the stop condition fires, or earlier through
+    /// `Server::drain`, while in-flight connections are still being
+    /// served. Readiness checks use it to turn new traffic away.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Acquire)
+    }
+
+    /// Marks the runtime as draining. There is no way back.
+    pub fn start_draining(&self) {
+        self.draining.store(true, Ordering::Release);
+    }
+
+    /// The shutdown phase the server is in, or `None` while it runs.
+    ///
+    /// Only `Server::run_with_shutdown` goes through the phases; see
+    /// [`ShutdownPhase`].
+    pub fn shutdown_phase(&self) -> Option<ShutdownPhase> {
+        ShutdownPhase::from_u8(self.shutdown_phase.load(Ordering::Acquire))
+    }
+
+    /// Moves the runtime to `phase`.
+    pub(crate) fn set_shutdown_phase(&self, phase: ShutdownPhase) {
+        self.shutdown_phase.store(phase.to_u8(), Ordering::Release);
+    }
+
+    /// Counts a request as in flight until the guard is dropped.
+    ///
+    /// Protocols take one once a request has been read and hold it until
+    /// the response is written, so a shutdown can tell connections busy
+    /// with a request from idle ones. Requests of protocols that do not
+    /// are treated as idle.
+    pub fn enter_request(&self) -> ConnectionGuard {
+        self.in_flight.enter()
+    }
+
+    /// Number of requests currently in flight, see
+    /// [`enter_request`](Self::enter_request).
+    pub fn in_flight_requests(&self) -> usize {
+        self.in_flight.get()
+    }
+
+    pub(crate) fn in_flight(&self) -> &ActiveConnections {
+        &self.in_flight
+    }
+
+    /// Whether a panicking handler is answered with the protocol's error
+    /// response instead of taking its connection down. On by default.
+    pub fn catch_panics(&self) -> bool {
+        !self.propagate_panics
+    }
+
+    /// Turns panic catching on or off, see [`catch_panics`](Self::catch_panics).
+    pub fn set_catch_panics(&mut self, catch_panics: bool) {
+        self.propagate_panics = !catch_panics;
+    }
+
+    /// Names the binding listening on `addr`. Servers call this once their
+    /// inbounds are bound, with the user's tag or the address itself.
+    pub fn label_binding(&self, addr: SocketAddr, label: impl Into<Arc<str>>) {
+        let mut labels = self.binding_labels.write();
+        labels.retain(|(bound, _)| *bound != addr);
+        labels.push((addr, label.into()));
+    }
+
+    /// Label of the binding a connection came in on, given the connection's
+    /// local address.
+    ///
+    /// A binding on an unspecified address (`0.0.0.0:8080`) sees
+    /// connections on concrete ones, so those match by port. `None` when no
+    /// binding matches, e.g. on a client runtime.
+    pub fn binding_label(&self, local_addr: SocketAddr) -> Option<Arc<str>> {
+        let labels = self.binding_labels.read();
+        labels
+            .iter()
+            .find(|(bound, _)| *bound == local_addr)
+            .or_else(|| {
+                labels.iter().find(|(bound, _)| {
+                    bound.ip().is_unspecified() && bound.port() == local_addr.port()
+                })
+            })
+            .map(|(_, label)| label.clone())
+    }
+
     /// Returns a typed config value if present.
     pub fn get_config<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
         self.config.get::<T>().cloned()
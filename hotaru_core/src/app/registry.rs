@@ -192,6 +192,22 @@ impl<TS: TransportSpec> ProtocolRegistryKind<TS> {
         entry.register(name, path, step_names, executable, config)
     }
 
+    /// Registers the catch-all binding for protocol `P`. Routes to the
+    /// matching `ProtocolEntry<P>`'s `register_fallback`.
+    pub fn register_fallback<P, N>(
+        &self,
+        name: N,
+        executable: ExecutableBinding<P::Context>,
+        config: ParamsClone,
+    ) -> Result<UrlRegistration<P::Context, TS>, UrlError>
+    where
+        P: Protocol<Wire = TS::Wire, TS = TS> + 'static,
+        N: Into<String>,
+    {
+        let entry = self.entry::<P>().ok_or(UrlError::ProtocolNotFound)?;
+        Ok(entry.register_fallback(name, executable, config))
+    }
+
     #[av::ver(
         deprecated,
         since = "0.8.0",
@@ -0,0 +1,130 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use alloc::sync::Arc;
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::{Context, Poll, Waker};
+
+use crate::marker::PMutex;
+
+/// Count of connections a server is currently serving.
+///
+/// Each accepted wire holds a [`ConnectionGuard`] for as long as its protocol
+/// task runs. The accept loop waits on [`ActiveConnections::below`] before
+/// accepting when `max_connections` is set, so excess clients stay in the
+/// kernel backlog instead of being accepted and dropped. Graceful shutdown
+/// waits on the same count, so every pending [`Below`] is woken when a
+/// connection closes.
+#[derive(Default)]
+pub struct ActiveConnections {
+    count: AtomicUsize,
+    waiters: PMutex<Vec<Waker>>,
+}
+
+impl ActiveConnections {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of connections currently open.
+    pub fn get(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
+    /// Registers one more open connection until the guard is dropped.
+    pub fn enter(self: &Arc<Self>) -> ConnectionGuard {
+        self.count.fetch_add(1, Ordering::AcqRel);
+        ConnectionGuard {
+            connections: self.clone(),
+        }
+    }
+
+    /// Resolves once fewer than `limit` connections are open.
+    pub fn below(&self, limit: usize) -> Below<'_> {
+        Below {
+            connections: self,
+            limit,
+        }
+    }
+
+    fn leave(&self) {
+        self.count.fetch_sub(1, Ordering::AcqRel);
+        for waker in self.waiters.lock().drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// Keeps a connection counted in [`ActiveConnections`] while alive.
+pub struct ConnectionGuard {
+    connections: Arc<ActiveConnections>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.connections.leave();
+    }
+}
+
+/// Future returned by [`ActiveConnections::below`].
+pub struct Below<'a> {
+    connections: &'a ActiveConnections,
+    limit: usize,
+}
+
+impl Future for Below<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.connections.get() < self.limit {
+            return Poll::Ready(());
+        }
+        let mut waiters = self.connections.waiters.lock();
+        if !waiters.iter().any(|w| w.will_wake(cx.waker())) {
+            waiters.push(cx.waker().clone());
+        }
+        drop(waiters);
+        // A connection may have closed before the waker was stored.
+        if self.connections.get() < self.limit {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::task::Wake;
+    use core::sync::atomic::AtomicBool;
+
+    #[derive(Default)]
+    struct Flag(AtomicBool);
+
+    impl Wake for Flag {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::Release);
+        }
+    }
+
+    #[test]
+    fn test_closing_a_connection_wakes_every_waiter() {
+        let connections = Arc::new(ActiveConnections::new());
+        let guard = connections.enter();
+
+        // An accept loop and a draining shutdown waiting at once
+        let flags = [Arc::new(Flag::default()), Arc::new(Flag::default())];
+        for flag in &flags {
+            let waker = Waker::from(flag.clone());
+            let mut below = connections.below(1);
+            let poll = Pin::new(&mut below).poll(&mut Context::from_waker(&waker));
+            assert!(poll.is_pending());
+        }
+
+        drop(guard);
+        assert!(flags.iter().all(|flag| flag.0.load(Ordering::Acquire)));
+        assert!(connections.waiters.lock().is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use alloc::boxed::Box;
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll, Waker};
+
+use crate::marker::PMutex;
+
+/// Stop signal shared by the accept loops of every binding.
+///
+/// A server's `stop` future can only be awaited once, so it is raced
+/// against all loops together and, when it fires, relayed to each of them
+/// through [`Shutdown::wait`].
+#[derive(Default)]
+pub(crate) struct Shutdown {
+    fired: AtomicBool,
+    waiters: PMutex<Vec<Waker>>,
+}
+
+impl Shutdown {
+    /// Resolves every pending and future [`wait`](Self::wait).
+    pub(crate) fn fire(&self) {
+        self.fired.store(true, Ordering::Release);
+        for waker in self.waiters.lock().drain(..) {
+            waker.wake();
+        }
+    }
+
+    /// Resolves once [`fire`](Self::fire) has been called.
+    pub(crate) fn wait(&self) -> ShutdownWait<'_> {
+        ShutdownWait { shutdown: self }
+    }
+}
+
+/// Future returned by [`Shutdown::wait`].
+pub(crate) struct ShutdownWait<'a> {
+    shutdown: &'a Shutdown,
+}
+
+impl Future for ShutdownWait<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.shutdown.fired.load(Ordering::Acquire) {
+            return Poll::Ready(());
+        }
+        let mut waiters = self.shutdown.waiters.lock();
+        if !waiters.iter().any(|w| w.will_wake(cx.waker())) {
+            waiters.push(cx.waker().clone());
+        }
+        drop(waiters);
+        // The signal may have fired before the waker was stored.
+        if self.shutdown.fired.load(Ordering::Acquire) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Drives accept loops to completion: one per binding, and within a
+/// binding one per unit of `accept_parallelism`.
+///
+/// The first loop to fail fires the [`Shutdown`] so the others close their
+/// inbounds too; its error is the result once all of them have finished.
+pub(crate) struct AcceptLoops<'a, F: Future<Output = Result<(), E>>, E> {
+    loops: Vec<Option<Pin<Box<F>>>>,
+    error: Option<E>,
+    shutdown: &'a Shutdown,
+}
+
+impl<'a, F: Future<Output = Result<(), E>>, E> AcceptLoops<'a, F, E> {
+    pub(crate) fn new(loops: impl IntoIterator<Item = F>, shutdown: &'a Shutdown) -> Self {
+        Self {
+            loops: loops.into_iter().map(|f| Some(Box::pin(f))).collect(),
+            error: None,
+            shutdown,
+        }
+    }
+}
+
+impl<F: Future<Output = Result<(), E>>, E> Unpin for AcceptLoops<'_, F, E> {}
+
+impl<F: Future<Output = Result<(), E>>, E> Future for AcceptLoops<'_, F, E> {
+    type Output = Result<(), E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        for slot in this.loops.iter_mut() {
+            let Some(accept_loop) = slot else { continue };
+            if let Poll::Ready(result) = accept_loop.as_mut().poll(cx) {
+                *slot = None;
+                if let Err(e) = result
+                    && this.error.is_none()
+                {
+                    this.error = Some(e);
+                    this.shutdown.fire();
+                }
+            }
+        }
+        if this.loops.iter().any(Option::is_some) {
+            return Poll::Pending;
+        }
+        Poll::Ready(match this.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        })
+    }
+}
@@ -7,12 +7,20 @@ use core::marker::PhantomData;
 use core::panic;
 use core::time::Duration;
 
+mod connections;
+mod listeners;
+mod shutdown;
+
+pub use connections::{ActiveConnections, Below, ConnectionGuard};
+use listeners::{AcceptLoops, Shutdown};
+pub use shutdown::ShutdownPhase;
+
 use crate::app::runtime::{Either, OnceCellCap, RuntimeSpec};
 use crate::executable::ExecutableBinding;
 use crate::marker::MaybeSend;
 use crate::{debug_error, debug_log, debug_warn};
 
-use crate::connection::{Inbound, TransportSpec};
+use crate::connection::{AcceptErrorKind, Inbound, TransportSpec};
 use crate::protocol::{Protocol, RequestContext};
 use crate::url::{PathPattern, UrlError, node::StepName};
 
@@ -26,13 +34,32 @@ use super::common::{OperationalConfig, RunMode, RuntimeConfig, TimeoutSetting};
 
 // type Job = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
 
+/// First wait after a transient accept error; doubles on each further one.
+const ACCEPT_BACKOFF_MIN: Duration = Duration::from_millis(5);
+/// Longest wait between accept retries.
+const ACCEPT_BACKOFF_MAX: Duration = Duration::from_secs(1);
+
 /// Server runtime for inbound protocol traffic.
 pub struct Server<TS: TransportSpec, Rt: RuntimeSpec> {
     pub registry: ProtocolRegistryKind<TS>,
-    pub binding: <TS::Inbound as Inbound>::BindTarget,
-    pub inbound: <Rt as RuntimeSpec>::OnceCell<Arc<TS::Inbound>>,
+    /// Addresses to listen on, in the order they were added to the builder.
+    /// All of them serve every protocol in [`registry`](Self::registry).
+    ///
+    /// Replaces the single `binding` field servers had before they could
+    /// listen on several addresses.
+    pub bindings: Vec<<TS::Inbound as Inbound>::BindTarget>,
+    /// User tags for the bindings, in the same order; `None` labels a
+    /// binding with its bound address.
+    pub binding_labels: Vec<Option<String>>,
+    /// One inbound per binding, in the same order. Replaces the single
+    /// `inbound` field.
+    pub inbounds: <Rt as RuntimeSpec>::OnceCell<Vec<Arc<TS::Inbound>>>,
     pub runtime: Arc<RuntimeConfig>,
     pub config: OperationalConfig,
+    pub connections: Arc<ActiveConnections>,
+    /// Fired by [`run_with_shutdown`](Self::run_with_shutdown) to close the
+    /// connections still open.
+    pub(crate) closing: Arc<Shutdown>,
     pub(crate) _rt: PhantomData<fn() -> Rt>,
 }
 
@@ -68,6 +95,41 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> Server<TS, Rt> {
             .set_max_frame_process_time(max_frame_process_time);
     }
 
+    /// Cap on simultaneously served connections, if one is set.
+    pub fn get_max_connections(self: &Arc<Self>) -> Option<usize> {
+        self.config.max_connections()
+    }
+
+    /// Number of accept loops run against each binding.
+    pub fn get_accept_parallelism(self: &Arc<Self>) -> usize {
+        self.config.accept_parallelism()
+    }
+
+    /// Number of connections currently being served.
+    pub fn active_connections(self: &Arc<Self>) -> usize {
+        self.connections.get()
+    }
+
+    /// Starts draining without stopping: readiness endpoints report `503`
+    /// so a load balancer stops sending traffic, while the server keeps
+    /// accepting and serving until its stop condition fires. Stopping
+    /// drains too, so calling this first is only needed to get ahead of
+    /// the stop, e.g. from a pre-stop hook.
+    pub fn drain(self: &Arc<Self>) {
+        self.runtime.start_draining();
+    }
+
+    /// Whether the server is draining; see [`drain`](Self::drain).
+    pub fn is_draining(self: &Arc<Self>) -> bool {
+        self.runtime.is_draining()
+    }
+
+    /// The phase of a [`run_with_shutdown`](Self::run_with_shutdown)
+    /// shutdown the server is in, or `None` while it runs.
+    pub fn shutdown_phase(self: &Arc<Self>) -> Option<ShutdownPhase> {
+        self.runtime.shutdown_phase()
+    }
+
     pub fn config(self: &Arc<Self>) -> &crate::extensions::Params {
         self.runtime.config()
     }
@@ -163,6 +225,31 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> Server<TS, Rt> {
         Ok(())
     }
 
+    /// Register the catch-all handler for protocol `P`.
+    ///
+    /// The fallback has the lowest priority of all routes: it runs only when
+    /// no registered route with a handler matches the request path, so
+    /// `/api/x` keeps its own handler while `/random` (or `/api` itself)
+    /// falls through to it. Typical use is an SPA serving `index.html`.
+    /// Registering a second fallback replaces the first.
+    pub fn fallback<P, N>(
+        self: &Arc<Self>,
+        name: N,
+        mut executable: ExecutableBinding<P::Context>,
+        config: ParamsClone,
+    ) -> Result<(), UrlError>
+    where
+        P: Protocol<Wire = TS::Wire, TS = TS> + 'static,
+        N: Into<String>,
+    {
+        if executable.has_no_middlewares() {
+            executable.set_middlewares(self.registry.get_protocol_middlewares::<P>());
+        }
+        self.registry
+            .register_fallback::<P, _>(name, executable, config)?;
+        Ok(())
+    }
+
     // TODO: Implement register_from on Url or remove this method
     // pub fn reg_from<P: Protocol + 'static>(self: &Arc<Self>, segments: &[PathPattern]) -> Arc<Url<P::Context>> {
     //     match self.registry.reg_from::<P>(segments) {
@@ -183,24 +270,31 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> Server<TS, Rt> {
             TimeoutSetting::Fixed(d) => Some(d),
         };
         let app = self.clone();
+        let guard = self.connections.enter();
         Rt::spawn_detached(async move {
-            match timeout {
-                None => {
-                    self.registry.serve(app.runtime.clone(), conn).await;
-                }
-                Some(duration) => {
-                    match Rt::select2(
-                        self.registry.serve(app.runtime.clone(), conn),
-                        Rt::sleep(duration),
-                    )
-                    .await
-                    {
-                        Either::Left(_) => {}
-                        Either::Right(_) => {
-                            debug_warn!("⚠️ Connection timed out after {:?}", duration);
+            let _guard = guard;
+            let serve = async {
+                match timeout {
+                    None => {
+                        self.registry.serve(app.runtime.clone(), conn).await;
+                    }
+                    Some(duration) => {
+                        match Rt::select2(
+                            self.registry.serve(app.runtime.clone(), conn),
+                            Rt::sleep(duration),
+                        )
+                        .await
+                        {
+                            Either::Left(_) => {}
+                            Either::Right(_) => {
+                                debug_warn!("⚠️ Connection timed out after {:?}", duration);
+                            }
                         }
                     }
                 }
+            };
+            if let Either::Right(()) = Rt::select2(serve, self.closing.wait()).await {
+                debug_log!("Connection closed by shutdown");
             }
         });
     }
@@ -237,40 +331,211 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> Server<TS, Rt> {
     /// `Rt::sleep`, and `Rt::spawn_detached`; no Tokio APIs are referenced.
     /// Provide any stop source you want: OS signal, board interrupt, supervisor
     /// message, deadline, or `core::future::pending()` for never-stop service.
+    ///
+    /// Every binding gets its own accept loop, all serving the same routes
+    /// and all ended by the one `stop`. A fatal accept error on any of them
+    /// (see [`try_run_until`](Self::try_run_until)) is logged and shuts the
+    /// others down too.
     pub async fn run_until<S>(self: Arc<Self>, stop: S)
     where
         S: core::future::Future<Output = ()> + MaybeSend,
     {
-        let inbound = self
-            .ensure_inbound()
+        let inbounds = self
+            .ensure_inbounds()
             .await
             .unwrap_or_else(|_| panic!("Failed to bind inbound transport"))
             .clone();
 
+        if let Err(_e) = self.accept_all(inbounds, stop).await {
+            debug_error!("Accept loop stopped: {_e}");
+        }
+    }
+
+    /// Like [`run_until`](Self::run_until), but returns the error that
+    /// stopped it instead of logging it.
+    ///
+    /// Accept errors the inbound classifies as
+    /// [`Transient`](AcceptErrorKind::Transient) (out of file descriptors,
+    /// aborted connections, ...) are logged and retried after a backoff that
+    /// doubles from 5ms up to 1s and resets on the next accepted wire.
+    /// [`Fatal`](AcceptErrorKind::Fatal) ones, and a failure to bind any of
+    /// the bindings, end every loop and come back as `Err`.
+    pub async fn try_run_until<S>(self: Arc<Self>, stop: S) -> Result<(), TS::IoError>
+    where
+        S: core::future::Future<Output = ()> + MaybeSend,
+    {
+        let inbounds = self.ensure_inbounds().await?.clone();
+        self.accept_all(inbounds, stop).await
+    }
+
+    /// Like [`try_run_until`](Self::try_run_until), but once `stop` fires
+    /// the server shuts down in phases instead of leaving its connections
+    /// running, and returns when none is left.
+    ///
+    /// The phases are entered in order, calling `on_phase` on each
+    /// transition; see [`ShutdownPhase`]:
+    ///
+    /// 1. `StopAccept`: the accept loops close their inbounds.
+    /// 2. `DrainInFlight`: requests being handled finish, and each
+    ///    connection closes after its current request.
+    /// 3. `CloseIdle`: once no request is in flight, the idle connections
+    ///    left are closed.
+    /// 4. `ForceClose`: only if `grace`, counted from when `stop` fires,
+    ///    runs out before the phases above are done. Every remaining
+    ///    connection is closed at once.
+    ///
+    /// The current phase can also be read from
+    /// [`shutdown_phase`](Self::shutdown_phase). A fatal accept error
+    /// returns it as `Err` without going through the phases.
+    pub async fn run_with_shutdown<S, F>(
+        self: Arc<Self>,
+        stop: S,
+        grace: Duration,
+        mut on_phase: F,
+    ) -> Result<(), TS::IoError>
+    where
+        S: core::future::Future<Output = ()> + MaybeSend,
+        F: FnMut(ShutdownPhase) + MaybeSend,
+    {
+        let inbounds = self.ensure_inbounds().await?.clone();
+        let mut enter = |phase: ShutdownPhase| {
+            self.runtime.set_shutdown_phase(phase);
+            on_phase(phase);
+        };
+
+        // Stopping the accept loops counts against `grace` too.
+        let mut stopped_at = None;
+        let stop = async {
+            stop.await;
+            stopped_at = Some(Rt::now());
+            enter(ShutdownPhase::StopAccept);
+        };
+        self.clone().accept_all(inbounds, stop).await?;
+
+        let stopped_at = stopped_at.unwrap_or_else(Rt::now);
+        let mut deadline = core::pin::pin!(Rt::sleep_until(Rt::instant_plus(stopped_at, grace)));
+        enter(ShutdownPhase::DrainInFlight);
+        let drained = Rt::select2(self.runtime.in_flight().below(1), &mut deadline).await;
+        if let Either::Left(()) = drained {
+            enter(ShutdownPhase::CloseIdle);
+            self.closing.fire();
+            let closed = Rt::select2(self.connections.below(1), &mut deadline).await;
+            if let Either::Left(()) = closed {
+                return Ok(());
+            }
+        }
+
+        enter(ShutdownPhase::ForceClose);
+        self.closing.fire();
+        self.connections.below(1).await;
+        Ok(())
+    }
+
+    /// Serves every inbound until `stop` fires or one of its accept loops
+    /// fails.
+    async fn accept_all<S>(
+        self: Arc<Self>,
+        inbounds: Vec<Arc<TS::Inbound>>,
+        stop: S,
+    ) -> Result<(), TS::IoError>
+    where
+        S: core::future::Future<Output = ()> + MaybeSend,
+    {
+        let shutdown = Shutdown::default();
+        let listeners = inbounds
+            .into_iter()
+            .map(|inbound| self.clone().serve_inbound(inbound, &shutdown));
+        let mut loops = AcceptLoops::new(listeners, &shutdown);
+
+        match Rt::select2(&mut loops, stop).await {
+            Either::Left(result) => result,
+            Either::Right(()) => {
+                self.runtime.start_draining();
+                shutdown.fire();
+                loops.await
+            }
+        }
+    }
+
+    /// Runs the configured number of accept loops against one inbound,
+    /// then closes it.
+    ///
+    /// The loops share the listener, so up to `accept_parallelism`
+    /// connections are taken off its backlog at once. Each loop checks
+    /// `max_connections` before accepting, so with several loops the cap
+    /// can be passed by up to `accept_parallelism - 1` connections.
+    async fn serve_inbound(
+        self: Arc<Self>,
+        inbound: Arc<TS::Inbound>,
+        shutdown: &Shutdown,
+    ) -> Result<(), TS::IoError> {
         debug_log!("Inbound transport bound");
 
+        let loops = (0..self.config.accept_parallelism())
+            .map(|_| self.clone().accept_loop(inbound.clone(), shutdown.wait()));
+        let result = AcceptLoops::new(loops, shutdown).await;
+
+        inbound.close();
+        debug_log!("Server shutdown complete");
+        result
+    }
+
+    async fn accept_loop<S>(
+        self: Arc<Self>,
+        inbound: Arc<TS::Inbound>,
+        stop: S,
+    ) -> Result<(), TS::IoError>
+    where
+        S: core::future::Future<Output = ()> + MaybeSend,
+    {
         let mut stop = core::pin::pin!(stop);
+        let mut backoff = ACCEPT_BACKOFF_MIN;
 
         loop {
+            // At the cap, leave new clients in the backlog until one closes.
+            if let Some(limit) = self.config.max_connections()
+                && self.connections.get() >= limit
+            {
+                debug_log!("Connection limit {limit} reached, pausing accept");
+                if let Either::Right(()) =
+                    Rt::select2(self.connections.below(limit), &mut stop).await
+                {
+                    debug_log!("Shutting down server...");
+                    break Ok(());
+                }
+            }
+
             match Rt::select2(inbound.accept(), &mut stop).await {
                 Either::Left(Ok(conn)) => {
                     debug_log!("Accepted inbound wire");
+                    backoff = ACCEPT_BACKOFF_MIN;
                     Arc::clone(&self).handle_wire(conn);
                 }
-                Either::Left(Err(_e)) => {
-                    if self.get_mode() == RunMode::Build {
-                        debug_error!("Failed to accept connection: {_e}");
+                Either::Left(Err(e)) => match inbound.classify_accept_error(&e) {
+                    AcceptErrorKind::Fatal => {
+                        debug_error!("Fatal accept error, shutting down: {e}");
+                        break Err(e);
                     }
-                }
+                    AcceptErrorKind::Transient => {
+                        if self.get_mode() == RunMode::Build {
+                            debug_warn!(
+                                "Failed to accept connection, retrying in {backoff:?}: {e}"
+                            );
+                        }
+                        if let Either::Right(()) = Rt::select2(Rt::sleep(backoff), &mut stop).await
+                        {
+                            debug_log!("Shutting down server...");
+                            break Ok(());
+                        }
+                        backoff = (backoff * 2).min(ACCEPT_BACKOFF_MAX);
+                    }
+                },
                 Either::Right(()) => {
                     debug_log!("Shutting down server...");
-                    break;
+                    break Ok(());
                 }
             }
         }
-
-        Rt::sleep(Duration::from_secs(1)).await;
-        debug_log!("Server shutdown complete");
     }
 
     /// Synthetically invoke a registered endpoint by name. Builds a fresh
@@ -311,11 +576,61 @@ impl<TS: TransportSpec, Rt: RuntimeSpec> Server<TS, Rt> {
         Ok(inner)
     }
 
-    /// Returns the `TS::Inbound` instance, binding on first use.
+    /// Address the server is listening on, once its inbound is bound.
+    ///
+    /// With a `:0` binding this is where the OS-assigned port shows up, so
+    /// tests can connect without hardcoding one. `None` before
+    /// [`ensure_inbound`](Self::ensure_inbound) (or `run`) has bound, and
+    /// for transports without socket addresses. With several bindings this
+    /// is the first one's; see [`local_addrs`](Self::local_addrs).
+    pub fn local_addr(&self) -> Option<core::net::SocketAddr> {
+        self.inbounds
+            .get()
+            .and_then(|inbounds| inbounds.first())
+            .and_then(|inbound| inbound.local_addr())
+    }
+
+    /// Addresses of every bound inbound, in binding order.
+    ///
+    /// Empty before the server has bound; inbounds without a socket address
+    /// are skipped.
+    pub fn local_addrs(&self) -> Vec<core::net::SocketAddr> {
+        self.inbounds
+            .get()
+            .map(|inbounds| inbounds.iter().filter_map(|i| i.local_addr()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the first binding's `TS::Inbound`, binding all of them on
+    /// first use.
+    ///
+    /// Kept from when a server had a single binding; the other inbounds
+    /// are bound too but not returned.
+    #[deprecated(note = "returns only the first binding's inbound; use `ensure_inbounds`")]
     pub async fn ensure_inbound(&self) -> Result<&Arc<TS::Inbound>, TS::IoError> {
-        self.inbound
+        let inbounds = self.ensure_inbounds().await?;
+        Ok(&inbounds[0])
+    }
+
+    /// Returns one `TS::Inbound` per binding, binding them on first use.
+    ///
+    /// Binding also records each inbound's label in the runtime, so
+    /// requests can tell which binding they came in on.
+    pub async fn ensure_inbounds(&self) -> Result<&Vec<Arc<TS::Inbound>>, TS::IoError> {
+        self.inbounds
             .get_or_try_init(|| async {
-                Ok(Arc::new(TS::Inbound::bind(self.binding.clone()).await?))
+                let mut inbounds = Vec::with_capacity(self.bindings.len());
+                for (i, binding) in self.bindings.iter().enumerate() {
+                    let inbound = TS::Inbound::bind(binding.clone()).await?;
+                    if let Some(addr) = inbound.local_addr() {
+                        match self.binding_labels.get(i).cloned().flatten() {
+                            Some(label) => self.runtime.label_binding(addr, label),
+                            None => self.runtime.label_binding(addr, addr.to_string()),
+                        }
+                    }
+                    inbounds.push(Arc::new(inbound));
+                }
+                Ok(inbounds)
             })
             .await
     }
@@ -0,0 +1,45 @@
+/// Phases of a shutdown run by
+/// [`Server::run_with_shutdown`](super::Server::run_with_shutdown).
+///
+/// They are entered in declaration order. `ForceClose` is only entered when
+/// the grace period runs out, and can follow either of the two phases
+/// before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShutdownPhase {
+    /// The stop condition fired and the accept loops are closing their
+    /// inbounds. The server is draining from here on.
+    StopAccept,
+    /// No new connections are accepted. Requests already being handled run
+    /// to completion, and each connection closes once its current request
+    /// has been answered instead of waiting for another.
+    DrainInFlight,
+    /// No request is in flight, so the connections still open are idle
+    /// between requests. They are closed.
+    CloseIdle,
+    /// The grace period ran out before the server drained. Every connection
+    /// still open is closed, in-flight requests included.
+    ForceClose,
+}
+
+impl ShutdownPhase {
+    /// Encoding stored in `RuntimeConfig`, where `0` means running.
+    pub(crate) fn to_u8(self) -> u8 {
+        match self {
+            ShutdownPhase::StopAccept => 1,
+            ShutdownPhase::DrainInFlight => 2,
+            ShutdownPhase::CloseIdle => 3,
+            ShutdownPhase::ForceClose => 4,
+        }
+    }
+
+    /// Inverse of [`to_u8`](Self::to_u8).
+    pub(crate) fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(ShutdownPhase::StopAccept),
+            2 => Some(ShutdownPhase::DrainInFlight),
+            3 => Some(ShutdownPhase::CloseIdle),
+            4 => Some(ShutdownPhase::ForceClose),
+            _ => None,
+        }
+    }
+}
@@ -26,6 +26,8 @@ pub use self::io::{
     HotaruWrite, MaybeSend, MaybeSendBoxFuture,
 };
 pub use self::primitive::{Accepter, Connector};
-pub use self::runtime::{Inbound, Outbound};
+#[cfg(feature = "std")]
+pub use self::runtime::io_accept_error_kind;
+pub use self::runtime::{AcceptErrorKind, Inbound, Outbound};
 pub use self::stream::{ConnMeta, ConnStream};
 pub use self::transport_spec::TransportSpec;
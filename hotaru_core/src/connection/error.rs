@@ -19,6 +19,7 @@ pub enum ConnectionError {
     PoolExhausted,
 
     PayloadTooLarge,
+    UriTooLong,
     InvalidFrameFormat,
     MethodNotAllowed,
     BadRequest(String),
@@ -45,6 +46,7 @@ impl fmt::Display for ConnectionError {
             Self::PoolExhausted => write!(f, "Connection pool exhausted"),
 
             Self::PayloadTooLarge => write!(f, "Payload too large"),
+            Self::UriTooLong => write!(f, "URI too long"),
             Self::InvalidFrameFormat => write!(f, "Invalid frame format"),
             Self::MethodNotAllowed => write!(f, "Method not allowed"),
             Self::BadRequest(err) => write!(f, "Bad request: {}", err),
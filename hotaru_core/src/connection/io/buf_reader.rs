@@ -113,4 +113,20 @@ impl<R: HotaruRead + MaybeSend + Unpin + 'static> HotaruBufRead for HotaruBufRea
     fn consume(&mut self, amt: usize) {
         self.pos = (self.pos + amt).min(self.cap);
     }
+
+    async fn fill_more(&mut self) -> Result<&[u8], Self::Error> {
+        // Move unconsumed bytes to the front, grow if still full, append.
+        if self.pos > 0 {
+            self.buf.copy_within(self.pos..self.cap, 0);
+            self.cap -= self.pos;
+            self.pos = 0;
+        }
+        if self.cap == self.buf.len() {
+            let grow = self.buf.len().max(64);
+            self.buf.resize(self.buf.len() + grow, 0);
+        }
+        let n = self.inner.read(&mut self.buf[self.cap..]).await?;
+        self.cap += n;
+        Ok(&self.buf[self.pos..self.cap])
+    }
 }
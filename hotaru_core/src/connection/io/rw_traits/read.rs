@@ -47,6 +47,22 @@ pub trait HotaruBufRead: HotaruRead {
     /// the next `fill_buf` skips them.
     fn consume(&mut self, amt: usize);
 
+    /// Reads more bytes from the underlying reader and appends them to the
+    /// unconsumed buffer, returning the whole buffered slice. Used by
+    /// protocol detection when the first read was too short to decide.
+    ///
+    /// Backends that cannot grow their buffer keep this default, which
+    /// returns the current buffer unchanged; callers treat "no growth" as
+    /// "no more data available for detection".
+    fn fill_more<'a>(
+        &'a mut self,
+    ) -> impl Future<Output = Result<&'a [u8], Self::Error>> + MaybeSend + 'a
+    where
+        Self: MaybeSend,
+    {
+        self.fill_buf()
+    }
+
     /// Reads bytes into `buf` until the delimiter `byte` is encountered,
     /// inclusive. Stops at EOF without error. Returns `Self::Error` directly:
     /// EOF is `Ok`, and the only failure path is `fill_buf`, which already
@@ -1,6 +1,7 @@
 //! Server-side runtime that accepts inbound wire streams.
 
 use core::future::Future;
+use core::net::SocketAddr;
 
 use crate::connection::{ConnStream, MaybeSend};
 
@@ -26,4 +27,56 @@ pub trait Inbound: Send + Sync + 'static {
 
     /// Wait for one inbound wire.
     fn accept(&self) -> impl Future<Output = Result<Self::Wire, Self::Error>> + MaybeSend;
+
+    /// Address the runtime is actually listening on, once bound.
+    ///
+    /// Resolves a bind target with port 0 to the port the OS picked.
+    /// Transports without socket addresses keep the default, `None`.
+    fn local_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+
+    /// How the server's accept loop should treat an error from `accept`.
+    ///
+    /// The default sorts `std::io::Error`s with [`io_accept_error_kind`]
+    /// and calls anything else transient, so an error a transport does not
+    /// classify never takes the listener down.
+    fn classify_accept_error(&self, err: &Self::Error) -> AcceptErrorKind {
+        #[cfg(feature = "std")]
+        if let Some(err) = (err as &(dyn core::error::Error + 'static)).downcast_ref() {
+            return io_accept_error_kind(err);
+        }
+        let _ = err;
+        AcceptErrorKind::Transient
+    }
+
+    /// Release any resources held outside the process once the accept loop
+    /// has stopped (e.g. a Unix socket file). Defaults to a no-op.
+    fn close(&self) {}
+}
+
+/// What the accept loop does after [`Inbound::accept`] fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcceptErrorKind {
+    /// The listener still works (out of file descriptors, a client that
+    /// gave up mid-handshake, ...): log, back off briefly and accept again.
+    Transient,
+    /// The listener itself is unusable: stop accepting and return the error.
+    Fatal,
+}
+
+/// Classifies an `accept` error from a std socket listener.
+///
+/// Only `InvalidInput` (EINVAL: the socket is no longer listening) and
+/// `Unsupported` are fatal. Resource exhaustion (EMFILE, ENFILE, ENOBUFS),
+/// aborted or reset connections and failed TLS handshakes all leave the
+/// listener usable and are transient.
+#[cfg(feature = "std")]
+pub fn io_accept_error_kind(err: &std::io::Error) -> AcceptErrorKind {
+    match err.kind() {
+        std::io::ErrorKind::InvalidInput | std::io::ErrorKind::Unsupported => {
+            AcceptErrorKind::Fatal
+        }
+        _ => AcceptErrorKind::Transient,
+    }
 }
@@ -4,5 +4,7 @@
 pub mod inbound;
 pub mod outbound;
 
-pub use inbound::Inbound;
+#[cfg(feature = "std")]
+pub use inbound::io_accept_error_kind;
+pub use inbound::{AcceptErrorKind, Inbound};
 pub use outbound::Outbound;
@@ -22,6 +22,15 @@
 //! zero allocation cost. Wrapping in `let _ = ...;` marks the resulting
 //! value as intentionally discarded.
 //!
+//! # `tracing`
+//!
+//! The `tracing` feature (std only) adds a fourth arm that takes precedence
+//! over the table above, with or without `dev-log`: each macro emits a
+//! `tracing` event at its level (`debug_log!` → `debug`, `debug_error!` →
+//! `error`, `debug_warn!` → `warn`, `debug_trace!` → `trace`, `debug_value!`
+//! → `debug`). The installed subscriber decides what is kept, and events
+//! land in the connection and request spans described in [`crate::trace`].
+//!
 //! # Future: hooking a real embedded logger
 //!
 //! When HCR picks an embedded logger (defmt / semihosting / RTT / the
@@ -62,13 +71,22 @@
 /// debug_log!("Processing {} requests", count);
 /// ```
 #[macro_export]
-#[cfg(all(feature = "dev-log", feature = "std"))]
+#[cfg(all(not(feature = "tracing"), feature = "dev-log", feature = "std"))]
 macro_rules! debug_log {
     ($($arg:tt)*) => {
         ::std::println!("[DEBUG] {}", ::std::format!($($arg)*));
     };
 }
 
+/// `tracing` — a `debug`-level event.
+#[macro_export]
+#[cfg(feature = "tracing")]
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        $crate::__tracing::debug!($($arg)*);
+    };
+}
+
 /// `dev-log + embedded` — hollow expansion. See the module docs for
 /// why we use `core::format_args!` here instead of a full no-op.
 #[macro_export]
@@ -80,7 +98,7 @@ macro_rules! debug_log {
 }
 
 #[macro_export]
-#[cfg(not(feature = "dev-log"))]
+#[cfg(not(any(feature = "dev-log", feature = "tracing")))]
 macro_rules! debug_log {
     ($($arg:tt)*) => {};
 }
@@ -103,13 +121,22 @@ macro_rules! debug_log {
 /// debug_error!("Invalid configuration: {:?}", config);
 /// ```
 #[macro_export]
-#[cfg(all(feature = "dev-log", feature = "std"))]
+#[cfg(all(not(feature = "tracing"), feature = "dev-log", feature = "std"))]
 macro_rules! debug_error {
     ($($arg:tt)*) => {
         ::std::eprintln!("[ERROR] {}", ::std::format!($($arg)*));
     };
 }
 
+/// `tracing` — a `error`-level event.
+#[macro_export]
+#[cfg(feature = "tracing")]
+macro_rules! debug_error {
+    ($($arg:tt)*) => {
+        $crate::__tracing::error!($($arg)*);
+    };
+}
+
 /// `dev-log + embedded` — hollow expansion.
 #[macro_export]
 #[cfg(all(feature = "dev-log", feature = "embedded"))]
@@ -120,7 +147,7 @@ macro_rules! debug_error {
 }
 
 #[macro_export]
-#[cfg(not(feature = "dev-log"))]
+#[cfg(not(any(feature = "dev-log", feature = "tracing")))]
 macro_rules! debug_error {
     ($($arg:tt)*) => {};
 }
@@ -141,13 +168,22 @@ macro_rules! debug_error {
 /// debug_warn!("Using deprecated API");
 /// ```
 #[macro_export]
-#[cfg(all(feature = "dev-log", feature = "std"))]
+#[cfg(all(not(feature = "tracing"), feature = "dev-log", feature = "std"))]
 macro_rules! debug_warn {
     ($($arg:tt)*) => {
         ::std::eprintln!("[WARN] {}", ::std::format!($($arg)*));
     };
 }
 
+/// `tracing` — a `warn`-level event.
+#[macro_export]
+#[cfg(feature = "tracing")]
+macro_rules! debug_warn {
+    ($($arg:tt)*) => {
+        $crate::__tracing::warn!($($arg)*);
+    };
+}
+
 /// `dev-log + embedded` — hollow expansion.
 #[macro_export]
 #[cfg(all(feature = "dev-log", feature = "embedded"))]
@@ -158,7 +194,7 @@ macro_rules! debug_warn {
 }
 
 #[macro_export]
-#[cfg(not(feature = "dev-log"))]
+#[cfg(not(any(feature = "dev-log", feature = "tracing")))]
 macro_rules! debug_warn {
     ($($arg:tt)*) => {};
 }
@@ -180,13 +216,22 @@ macro_rules! debug_warn {
 /// debug_trace!("Loop iteration {}: state={:?}", i, state);
 /// ```
 #[macro_export]
-#[cfg(all(feature = "dev-log", feature = "std"))]
+#[cfg(all(not(feature = "tracing"), feature = "dev-log", feature = "std"))]
 macro_rules! debug_trace {
     ($($arg:tt)*) => {
         ::std::println!("[TRACE] {}", ::std::format!($($arg)*));
     };
 }
 
+/// `tracing` — a `trace`-level event.
+#[macro_export]
+#[cfg(feature = "tracing")]
+macro_rules! debug_trace {
+    ($($arg:tt)*) => {
+        $crate::__tracing::trace!($($arg)*);
+    };
+}
+
 /// `dev-log + embedded` — hollow expansion.
 #[macro_export]
 #[cfg(all(feature = "dev-log", feature = "embedded"))]
@@ -197,7 +242,7 @@ macro_rules! debug_trace {
 }
 
 #[macro_export]
-#[cfg(not(feature = "dev-log"))]
+#[cfg(not(any(feature = "dev-log", feature = "tracing")))]
 macro_rules! debug_trace {
     ($($arg:tt)*) => {};
 }
@@ -225,7 +270,7 @@ macro_rules! debug_trace {
 /// [src/main.rs:42] calculate() = 42
 /// ```
 #[macro_export]
-#[cfg(all(feature = "dev-log", feature = "std"))]
+#[cfg(all(not(feature = "tracing"), feature = "dev-log", feature = "std"))]
 macro_rules! debug_value {
     () => {
         ::std::eprintln!("[{}:{}]", file!(), line!())
@@ -244,6 +289,27 @@ macro_rules! debug_value {
     };
 }
 
+/// `tracing` — the same output as a `debug`-level event.
+#[macro_export]
+#[cfg(feature = "tracing")]
+macro_rules! debug_value {
+    () => {
+        $crate::__tracing::debug!("[{}:{}]", file!(), line!())
+    };
+    ($val:expr $(,)?) => {
+        match $val {
+            tmp => {
+                $crate::__tracing::debug!("[{}:{}] {} = {:#?}",
+                    file!(), line!(), stringify!($val), &tmp);
+                tmp
+            }
+        }
+    };
+    ($($val:expr),+ $(,)?) => {
+        ($($crate::debug_value!($val)),+,)
+    };
+}
+
 /// `dev-log + embedded` — identity on values, no-op on the marker form.
 /// No `Debug` bound is required so this compiles for any `T`.
 #[macro_export]
@@ -255,7 +321,7 @@ macro_rules! debug_value {
 }
 
 #[macro_export]
-#[cfg(not(feature = "dev-log"))]
+#[cfg(not(any(feature = "dev-log", feature = "tracing")))]
 macro_rules! debug_value {
     () => {};
     ($val:expr $(,)?) => { $val };
@@ -31,10 +31,12 @@ impl<C: RequestContext, TS: TransportSpec> AccessPoint<C, TS> {
     ///   rebind doesn't need any explicit notification — the next
     ///   `resolve()` call returns the new endpoint. Returns `None` only if
     ///   the root endpoint slot is empty (registration was never completed).
+    /// - **`Fallback` variant** — reads the live fallback slot the same way.
     pub fn resolve(&self) -> Option<Arc<UrlNode<C, TS>>> {
         match &self.target {
             UrlRegistration::Root(root) => root.endpoint(),
             UrlRegistration::Node(node) => Some(node.clone()),
+            UrlRegistration::Fallback(root) => root.fallback(),
         }
     }
 }
@@ -46,6 +48,7 @@ impl<C: RequestContext, TS: TransportSpec> Clone for AccessPoint<C, TS> {
             target: match &self.target {
                 UrlRegistration::Root(r) => UrlRegistration::Root(r.clone()),
                 UrlRegistration::Node(n) => UrlRegistration::Node(n.clone()),
+                UrlRegistration::Fallback(r) => UrlRegistration::Fallback(r.clone()),
             },
         }
     }
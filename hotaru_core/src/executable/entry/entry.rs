@@ -14,7 +14,8 @@ use akari::extensions::{Locals, Params, ParamsClone};
 use crate::{
     app::common::RuntimeConfig, connection::{ConnStream, TransportSpec}, executable::{ExecutableBinding, access::{access_point::AccessPoint, table::AccessPointTable}, entry::ProtocolEntryTrait, middleware::AsyncMiddlewareChain}, protocol::Protocol, url::{PathPattern, UrlError, UrlRegistration, UrlRoot, node::StepName}
 };
-use crate::protocol::{Channel, ProtocolFlow};
+use crate::protocol::{Channel, Detection, ProtocolFlow};
+use crate::trace::{ConnectionSpan, RequestSpan};
 
 /// Concrete handler for a specific protocol.
 pub struct ProtocolEntry<P, TS>
@@ -77,6 +78,22 @@ where
         Ok(reg)
     }
 
+    /// Register the catch-all binding under `name`. It runs only when no
+    /// route with a handler matches (see [`UrlRoot`]'s fallback docs).
+    pub fn register_fallback<N: Into<String>>(
+        &self,
+        name: N,
+        binding: ExecutableBinding<P::Context>,
+        config: ParamsClone,
+    ) -> UrlRegistration<P::Context, TS> {
+        let reg = self.root_handler.register_fallback(binding, config);
+        self.access_points.insert(
+            name,
+            AccessPoint { path: Vec::new(), target: reg.clone() },
+        );
+        reg
+    }
+
     /// Wrap an acquired wire in this protocol's channel handle.
     /// The caller (e.g. `Client::open_channel`) owns connection sourcing.
     pub fn create_channel(&self, wire: TS::Wire) -> P::Channel {
@@ -92,7 +109,7 @@ where
     P: Protocol<Wire = TS::Wire, TS = TS> + Clone + 'static,
     TS: TransportSpec,
 {
-    fn test(&self, buf: &[u8]) -> bool {
+    fn test(&self, buf: &[u8]) -> Detection {
         P::detect(buf)
     }
 
@@ -100,6 +117,10 @@ where
         self.protocol.default_connection_timeout()
     }
 
+    fn set_case_insensitive_routes(&self, enabled: bool) {
+        self.root_handler.set_case_insensitive(enabled);
+    }
+
     fn serve(
         &self,
         runtime: Arc<RuntimeConfig>,
@@ -109,11 +130,24 @@ where
     ) -> MaybeSendBoxFuture<'static, ()> {
         let protocol = self.protocol.clone();
         let root = self.root_handler.clone();
+        let span = ConnectionSpan::new(core::any::type_name::<P>());
 
-        Box::pin(async move {
+        Box::pin(span.instrument(async move {
             let channel = protocol.open_channel(reader, writer, meta);
             while channel.is_open() {
-                match P::handle(&channel, runtime.clone(), root.clone()).await {
+                let request = RequestSpan::new();
+                let result = request
+                    .instrument(P::handle(&channel, runtime.clone(), root.clone()))
+                    .await;
+                if let Ok(flow) = &result {
+                    request.handled(flow);
+                }
+                match result {
+                    // Past the stop of a phased shutdown, no further
+                    // request is read on the connection.
+                    Ok(ProtocolFlow::Continue) if runtime.shutdown_phase().is_some() => {
+                        channel.close();
+                    }
                     Ok(ProtocolFlow::Continue) => continue,
                     Ok(ProtocolFlow::Close) => {
                         channel.close();
@@ -124,7 +158,7 @@ where
                     }
                 }
             }
-        })
+        }))
     }
 
     fn serve_upgrade(
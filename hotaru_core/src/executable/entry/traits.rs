@@ -7,6 +7,7 @@ use crate::{
     connection::{
         BufferedReadHalf, BufferedWriteHalf, ConnStream, MaybeSendBoxFuture, TransportSpec,
     },
+    protocol::Detection,
 };
 use akari::extensions::{Locals, Params};
 
@@ -19,7 +20,7 @@ use akari::extensions::{Locals, Params};
 /// and `request`, not by duplicating the entry type itself.
 pub trait ProtocolEntryTrait<TS: TransportSpec>: Send + Sync {
     /// Test if this protocol can handle the connection.
-    fn test(&self, buf: &[u8]) -> bool;
+    fn test(&self, buf: &[u8]) -> Detection;
 
     /// Handle the connection.
     fn serve(
@@ -64,6 +65,10 @@ pub trait ProtocolEntryTrait<TS: TransportSpec>: Send + Sync {
     /// Used to resolve [`TimeoutSetting::Inherit`](crate::app::common::TimeoutSetting::Inherit) at connection time.
     fn default_connection_timeout(&self) -> Option<Duration>;
 
+    /// Sets whether the entry's URL tree matches literal segments ignoring
+    /// ASCII case. See [`UrlRoot::set_case_insensitive`](crate::url::UrlRoot::set_case_insensitive).
+    fn set_case_insensitive_routes(&self, enabled: bool);
+
     /// Allows downcasting.
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
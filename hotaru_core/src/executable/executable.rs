@@ -154,7 +154,7 @@ where
         final_handler: Arc<dyn AsyncFinalHandler<C>>,
     ) -> Self {
         let final_fn: Arc<dyn Fn(C) -> BoxFuture<C> + Send + Sync + 'static> =
-            Arc::new(move |ctx| final_handler.handle(ctx));
+            Arc::new(move |ctx| timed(final_handler.handle(ctx)));
 
         let chain = middlewares.into_iter().rev().fold(final_fn, |next, mw| {
             let next_clone = next.clone();
@@ -173,6 +173,26 @@ where
     } 
 }
 
+/// Records how long the final handler ran on the context it returns, so
+/// after-middleware can read it through `RequestContext::handler_duration`.
+#[cfg(feature = "std")]
+fn timed<C: RequestContext>(handler: BoxFuture<C>) -> BoxFuture<C> {
+    let start = std::time::Instant::now();
+    Box::pin(async move {
+        let mut result = handler.await;
+        if let Ok(ctx) = &mut result {
+            ctx.set_handler_duration(start.elapsed());
+        }
+        result
+    })
+}
+
+/// Without `std` there is no clock to read; the duration stays unrecorded.
+#[cfg(not(feature = "std"))]
+fn timed<C: RequestContext>(handler: BoxFuture<C>) -> BoxFuture<C> {
+    handler
+}
+
 impl<C> TryFrom<ExecutableBinding<C>> for ExecutionChain<C>
 where
     C: RequestContext + Send + 'static,
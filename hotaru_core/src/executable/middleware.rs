@@ -2,6 +2,7 @@
 use crate::prelude::*;
 use alloc::sync::Arc;
 use core::future::Future;
+use core::task::Poll;
 
 // use crate::debug_log;
 
@@ -52,6 +53,313 @@ where
     }
 }
 
+/// Middleware that runs several others as one, in order.
+///
+/// Built by [`chain`]. The first middleware in the list is the outermost;
+/// the last one hands on to whatever follows the chain. An empty chain
+/// passes the context straight through.
+pub struct Chain<C: RequestContext> {
+    middlewares: AsyncMiddlewareChain<C>,
+}
+
+/// Composes `middlewares` into a single middleware, so a library can ship a
+/// ready-made stack that is appended like any other middleware.
+pub fn chain<C: RequestContext>(middlewares: AsyncMiddlewareChain<C>) -> Chain<C> {
+    Chain { middlewares }
+}
+
+impl<C: RequestContext> AsyncMiddleware<C> for Chain<C> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn return_self() -> Self {
+        chain(Vec::new())
+    }
+
+    fn handle<'a>(
+        &self,
+        rc: C,
+        next: Box<dyn Fn(C) -> BoxFuture<C> + Send + Sync + 'static>,
+    ) -> BoxFuture<C> {
+        let next: Arc<dyn Fn(C) -> BoxFuture<C> + Send + Sync + 'static> = Arc::from(next);
+        let inner = self.middlewares.iter().rev().fold(next, |next, mw| {
+            let mw = mw.clone();
+            Arc::new(move |ctx: C| {
+                let next = next.clone();
+                mw.handle(ctx, Box::new(move |r| next(r)))
+            })
+        });
+        inner(rc)
+    }
+}
+
+/// Middleware that runs another only for contexts matching a predicate.
+///
+/// Built by [`conditional`]. When the predicate is false the wrapped
+/// middleware is skipped entirely and the context goes straight to `next`.
+pub struct Conditional<C: RequestContext> {
+    predicate: Arc<dyn Fn(&C) -> bool + Send + Sync + 'static>,
+    middleware: Arc<dyn AsyncMiddleware<C>>,
+}
+
+/// Runs `middleware` only when `predicate` holds for the incoming context.
+pub fn conditional<C, F>(predicate: F, middleware: Arc<dyn AsyncMiddleware<C>>) -> Conditional<C>
+where
+    C: RequestContext,
+    F: Fn(&C) -> bool + Send + Sync + 'static,
+{
+    Conditional {
+        predicate: Arc::new(predicate),
+        middleware,
+    }
+}
+
+impl<C: RequestContext> AsyncMiddleware<C> for Conditional<C> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    /// A conditional that never runs; there is no predicate to default to.
+    fn return_self() -> Self {
+        conditional(|_: &C| false, Arc::new(Chain::<C>::return_self()))
+    }
+
+    fn handle<'a>(
+        &self,
+        rc: C,
+        next: Box<dyn Fn(C) -> BoxFuture<C> + Send + Sync + 'static>,
+    ) -> BoxFuture<C> {
+        if (self.predicate)(&rc) {
+            self.middleware.handle(rc, next)
+        } else {
+            next(rc)
+        }
+    }
+}
+
+/// Outcome of [`race_deadline`].
+pub enum Deadline<C: RequestContext> {
+    /// The rest of the chain finished before the deadline.
+    Finished(Result<C, <C as RequestContext>::Error>),
+    /// The deadline fired first and the rest of the chain was dropped.
+    /// Carries the context's [`detached`](RequestContext::detached) copy,
+    /// if it has one.
+    Expired(Option<C>),
+}
+
+/// Runs `next(ctx)` against `deadline`, for middleware that bound how long
+/// the layers inside them may take.
+///
+/// When the deadline wins, the inner future (and the context it owns) is
+/// dropped; the middleware answers on the detached copy taken beforehand and
+/// returns it as `Ok`, so the middleware wrapping it still see that answer on
+/// their way out instead of the whole chain being aborted.
+pub async fn race_deadline<C, N, D>(ctx: C, next: N, deadline: D) -> Deadline<C>
+where
+    C: RequestContext,
+    N: FnOnce(C) -> BoxFuture<C>,
+    D: Future,
+{
+    let fallback = ctx.detached();
+    let mut inner = next(ctx);
+    let mut deadline = core::pin::pin!(deadline);
+    core::future::poll_fn(|cx| {
+        if let Poll::Ready(result) = inner.as_mut().poll(cx) {
+            return Poll::Ready(Some(result));
+        }
+        deadline.as_mut().poll(cx).map(|_| None)
+    })
+    .await
+    .map_or_else(|| Deadline::Expired(fallback), Deadline::Finished)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::executable::ExecutionChain;
+    use crate::protocol::{Channel, Extensions, ProtocolRole};
+
+    #[derive(Clone)]
+    struct TestChannel;
+
+    impl Channel for TestChannel {
+        fn is_open(&self) -> bool {
+            true
+        }
+        fn close(&self) {}
+    }
+
+    /// Records every step the chain takes, and whether the request is
+    /// authenticated (the predicate under test).
+    #[derive(Default)]
+    struct TestContext {
+        authenticated: bool,
+        trace: Vec<String>,
+        extensions: Extensions,
+    }
+
+    impl RequestContext for TestContext {
+        type Request = ();
+        type Response = ();
+        type Error = std::io::Error;
+        type Channel = TestChannel;
+
+        fn handle_error(&mut self) {}
+
+        fn role(&self) -> ProtocolRole {
+            ProtocolRole::Server
+        }
+
+        fn extensions(&self) -> &Extensions {
+            &self.extensions
+        }
+
+        fn extensions_mut(&mut self) -> &mut Extensions {
+            &mut self.extensions
+        }
+
+        fn inject_request(&mut self, _: Self::Request) {}
+        fn into_response(self) -> Self::Response {}
+    }
+
+    struct Tag(&'static str);
+
+    impl AsyncMiddleware<TestContext> for Tag {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn return_self() -> Self {
+            Tag("")
+        }
+
+        fn handle<'a>(
+            &self,
+            mut rc: TestContext,
+            next: Box<dyn Fn(TestContext) -> BoxFuture<TestContext> + Send + Sync + 'static>,
+        ) -> BoxFuture<TestContext> {
+            let name = self.0;
+            Box::pin(async move {
+                rc.trace.push(format!("{name}>"));
+                let mut rc = next(rc).await?;
+                rc.trace.push(format!("<{name}"));
+                Ok(rc)
+            })
+        }
+    }
+
+    fn tag(name: &'static str) -> Arc<dyn AsyncMiddleware<TestContext>> {
+        Arc::new(Tag(name))
+    }
+
+    fn handler() -> Arc<dyn AsyncFinalHandler<TestContext>> {
+        Arc::new(|mut ctx: TestContext| async move {
+            ctx.trace.push("handler".into());
+            Ok(ctx)
+        })
+    }
+
+    #[tokio::test]
+    async fn chain_runs_in_list_order_inside_the_outer_chain() {
+        let composed = chain(vec![tag("a"), tag("b"), tag("c")]);
+        let chain = ExecutionChain::new(vec![tag("outer"), Arc::new(composed)], handler());
+
+        let ctx = chain.run(TestContext::default()).await.unwrap();
+        assert_eq!(
+            ctx.trace,
+            [
+                "outer>", "a>", "b>", "c>", "handler", "<c", "<b", "<a", "<outer"
+            ]
+        );
+
+        // Reusing the composed middleware composes afresh every time
+        let ctx = chain.run(TestContext::default()).await.unwrap();
+        assert_eq!(ctx.trace.len(), 9);
+    }
+
+    #[tokio::test]
+    async fn empty_chain_passes_through() {
+        let chain = ExecutionChain::new(vec![Arc::new(chain(Vec::new()))], handler());
+
+        let ctx = chain.run(TestContext::default()).await.unwrap();
+        assert_eq!(ctx.trace, ["handler"]);
+    }
+
+    #[tokio::test]
+    async fn conditional_is_skipped_when_predicate_is_false() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_by_predicate = seen.clone();
+        let audit = conditional(
+            move |ctx: &TestContext| {
+                seen_by_predicate.lock().unwrap().push(ctx.authenticated);
+                ctx.authenticated
+            },
+            tag("audit"),
+        );
+        let chain = ExecutionChain::new(vec![Arc::new(audit), tag("log")], handler());
+
+        let ctx = chain.run(TestContext::default()).await.unwrap();
+        assert_eq!(ctx.trace, ["log>", "handler", "<log"]);
+
+        let authenticated = TestContext {
+            authenticated: true,
+            ..Default::default()
+        };
+        let ctx = chain.run(authenticated).await.unwrap();
+        assert_eq!(ctx.trace, ["audit>", "log>", "handler", "<log", "<audit"]);
+
+        // The predicate sees each request once, before anything runs
+        assert_eq!(*seen.lock().unwrap(), [false, true]);
+    }
+
+    #[tokio::test]
+    async fn extensions_carry_middleware_values_to_the_handler() {
+        #[derive(Debug, PartialEq)]
+        struct User(&'static str);
+
+        /// Resolves the user once so nothing downstream has to.
+        struct Authenticate;
+
+        impl AsyncMiddleware<TestContext> for Authenticate {
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+
+            fn return_self() -> Self {
+                Authenticate
+            }
+
+            fn handle<'a>(
+                &self,
+                mut rc: TestContext,
+                next: Box<dyn Fn(TestContext) -> BoxFuture<TestContext> + Send + Sync + 'static>,
+            ) -> BoxFuture<TestContext> {
+                Box::pin(async move {
+                    rc.extensions_mut().insert(User("ada"));
+                    next(rc).await
+                })
+            }
+        }
+
+        let handler: Arc<dyn AsyncFinalHandler<TestContext>> =
+            Arc::new(|mut ctx: TestContext| async move {
+                let user = ctx.extensions().get::<User>().expect("set by middleware");
+                ctx.trace.push(format!("hello {}", user.0));
+                Ok(ctx)
+            });
+        let chain = ExecutionChain::new(vec![Arc::new(Authenticate)], handler);
+
+        let mut ctx = chain.run(TestContext::default()).await.unwrap();
+        assert_eq!(ctx.trace, ["hello ada"]);
+        assert_eq!(ctx.extensions().get::<User>(), Some(&User("ada")));
+        assert_eq!(ctx.extensions_mut().remove::<User>(), Some(User("ada")));
+        assert!(ctx.extensions().is_empty());
+    }
+}
+
 // HTTP Implementation example (to be moved to hotaru_http crate later)
 // pub struct LoggingMiddleware;
 
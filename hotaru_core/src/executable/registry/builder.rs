@@ -5,9 +5,14 @@ use alloc::sync::Arc;
 use crate::{
     connection::TransportSpec,
     executable::{
-        ProtocolEntryBuilder, entry::ProtocolEntryTrait, registry::ProtocolEntryRegistry,
+        ExecutableBinding, ProtocolEntryBuilder,
+        entry::{ProtocolEntry, ProtocolEntryTrait},
+        middleware::{AsyncFinalHandler, AsyncMiddlewareChain},
+        registry::ProtocolEntryRegistry,
     },
+    extensions::ParamsClone,
     protocol::Protocol,
+    url::UrlError,
 };
 
 /// Builder for protocol registries assembled from neutral protocol entries.
@@ -30,6 +35,48 @@ impl<TS: TransportSpec> ProtocolRegistryBuilder<TS> {
         self
     }
 
+    /// Adds a prebuilt protocol entry, e.g. one wrapping a protocol that is
+    /// not registered through [`Protocol`]. Entries are detected in the
+    /// order they were added.
+    pub fn entry(mut self, entry: Arc<dyn ProtocolEntryTrait<TS>>) -> Self {
+        self.handlers.push(entry);
+        self
+    }
+
+    /// Register a route on protocol `P` without the `endpoint!` macro, e.g.
+    /// for routes generated from configuration.
+    ///
+    /// `pattern` uses the same syntax as `Server::url` and doubles as the
+    /// route's access-point name. An empty `middlewares` falls back to the
+    /// protocol-level middlewares. `P` must already have been added with
+    /// [`protocol`](Self::protocol); otherwise, or when the pattern does not
+    /// parse, the error is returned.
+    pub fn add_route<P: Protocol<Wire = TS::Wire, TS = TS> + Clone + 'static>(
+        self,
+        pattern: &str,
+        handler: Arc<dyn AsyncFinalHandler<P::Context>>,
+        middlewares: AsyncMiddlewareChain<P::Context>,
+        config: ParamsClone,
+    ) -> Result<Self, UrlError> {
+        let entry = self
+            .handlers
+            .iter()
+            .find_map(|h| h.as_any().downcast_ref::<ProtocolEntry<P, TS>>())
+            .ok_or(UrlError::ProtocolNotFound)?;
+        let middlewares = if middlewares.is_empty() {
+            entry.middlewares.clone()
+        } else {
+            middlewares
+        };
+        let tokens = P::tokenize_url(pattern)?;
+        let (path, step_names) = crate::url::tokens_to_patterns(&tokens)?;
+        let binding = ExecutableBinding::new()
+            .with_handler(handler)
+            .with_middlewares(middlewares);
+        entry.register(pattern, path, step_names.into(), binding, config)?;
+        Ok(self)
+    }
+
     pub fn build(self) -> ProtocolEntryRegistry<TS> {
         ProtocolEntryRegistry {
             handlers: self.handlers,
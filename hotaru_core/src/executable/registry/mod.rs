@@ -5,7 +5,9 @@ use alloc::sync::Arc;
 
 use crate::{
     app::common::RuntimeConfig,
-    connection::{ConnStream, HotaruBufRead, HotaruRead, HotaruWrite, TransportSpec},
+    connection::{
+        BufferedReadHalf, ConnStream, HotaruBufRead, HotaruRead, HotaruWrite, TransportSpec,
+    },
     debug_log,
     executable::{
         ExecutableBinding,
@@ -13,7 +15,7 @@ use crate::{
         middleware::{AsyncMiddleware, AsyncMiddlewareChain},
     },
     extensions::ParamsClone,
-    protocol::Protocol,
+    protocol::{Detection, Protocol},
     url::{UrlError, UrlRegistration, UrlRoot},
 };
 
@@ -22,6 +24,10 @@ pub mod builder;
 
 pub use builder::ProtocolRegistryBuilder;
 
+/// Upper bound on buffered bytes while protocols ask for more data during
+/// detection. Comfortably above every built-in preface.
+pub const MAX_DETECTION_BYTES: usize = 1024;
+
 /// Registry for multiple protocol entries.
 pub struct ProtocolEntryRegistry<TS: TransportSpec> {
     pub(crate) handlers: Vec<Arc<dyn ProtocolEntryTrait<TS>>>,
@@ -48,19 +54,64 @@ impl<TS: TransportSpec> ProtocolEntryRegistry<TS> {
         )));
     }
 
-    pub async fn serve(&self, runtime: Arc<RuntimeConfig>, conn: TS::Wire) {
-        let (read_half, mut writer, meta) = conn.split();
-        let mut reader = read_half.into_buf();
-        let selected = {
-            let buf = reader.fill_buf().await.unwrap_or(&[]);
+    /// Sets case-insensitive literal matching on every entry's URL tree.
+    pub(crate) fn set_case_insensitive_routes(&self, enabled: bool) {
+        for handler in &self.handlers {
+            handler.set_case_insensitive_routes(enabled);
+        }
+    }
+
+    /// Picks the first registered protocol whose `detect` matches the
+    /// initial bytes. While any protocol reports `NeedMoreData`, keeps
+    /// reading (up to [`MAX_DETECTION_BYTES`]) before giving up.
+    ///
+    /// Registration order is priority order: a `Match` only wins once every
+    /// protocol registered before it has ruled the bytes out. If the bytes
+    /// run out first, the earliest `Match` seen on them is used.
+    async fn select(
+        &self,
+        reader: &mut BufferedReadHalf<TS>,
+    ) -> Option<Arc<dyn ProtocolEntryTrait<TS>>> {
+        let mut seen = None;
+        loop {
+            let buf = match seen {
+                None => reader.fill_buf().await,
+                Some(_) => reader.fill_more().await,
+            }
+            .unwrap_or(&[]);
             let _n = buf.len();
             debug_log!(
                 "Protocol detection: {} bytes: {:?}",
                 _n,
                 String::from_utf8_lossy(&buf[.._n.min(50)])
             );
-            self.handlers.iter().find(|h| h.test(buf)).cloned()
-        };
+
+            let mut pending = false;
+            let mut matched = None;
+            for handler in &self.handlers {
+                match handler.test(buf) {
+                    Detection::Match if !pending => return Some(handler.clone()),
+                    Detection::Match => {
+                        matched.get_or_insert_with(|| handler.clone());
+                    }
+                    Detection::NeedMoreData => pending = true,
+                    Detection::NoMatch => {}
+                }
+            }
+
+            // Stop once nobody is waiting, the backend could not supply
+            // more bytes, or the detection window is exhausted.
+            if !pending || seen == Some(buf.len()) || buf.len() >= MAX_DETECTION_BYTES {
+                return matched;
+            }
+            seen = Some(buf.len());
+        }
+    }
+
+    pub async fn serve(&self, runtime: Arc<RuntimeConfig>, conn: TS::Wire) {
+        let (read_half, mut writer, meta) = conn.split();
+        let mut reader = read_half.into_buf();
+        let selected = self.select(&mut reader).await;
 
         if let Some(handler) = selected {
             handler.serve(runtime, reader, writer.into_buf_write(), meta).await;
@@ -72,16 +123,7 @@ impl<TS: TransportSpec> ProtocolEntryRegistry<TS> {
     pub async fn request(&self, runtime: Arc<RuntimeConfig>, conn: TS::Wire) {
         let (read_half, mut writer, meta) = conn.split();
         let mut reader = read_half.into_buf();
-        let selected = {
-            let buf = reader.fill_buf().await.unwrap_or(&[]);
-            let _n = buf.len();
-            debug_log!(
-                "Protocol detection: {} bytes: {:?}",
-                _n,
-                String::from_utf8_lossy(&buf[.._n.min(50)])
-            );
-            self.handlers.iter().find(|h| h.test(buf)).cloned()
-        };
+        let selected = self.select(&mut reader).await;
 
         if let Some(handler) = selected {
             handler.request(runtime, reader, writer.into_buf_write(), meta).await;
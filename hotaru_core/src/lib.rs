@@ -86,6 +86,8 @@ pub mod executable;
 pub mod connection;
 /// Debug logging helpers used by Hotaru internals.
 pub mod debug;
+/// Connection and request spans for the optional `tracing` integration.
+pub mod trace;
 /// Protocol traits, request contexts, messages, and protocol flow types.
 pub mod protocol;
 /// URL pattern parsing, routing trees, and path matching.
@@ -93,6 +95,11 @@ pub mod url;
 
 pub use akari::*;
 
+// Lets the exported `debug_*` macros reach `tracing` from downstream crates.
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub use tracing as __tracing;
+
 // Re-export commonly used marker aliases.
 pub use marker::{
     BoxFuture, MaybeSend, MaybeSendBoxFuture, MaybeSendFuture, PRwLock, PRwLockReadGuard,
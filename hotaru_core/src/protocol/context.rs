@@ -1,4 +1,6 @@
-use crate::protocol::{Channel, ProtocolError, ProtocolRole};
+use core::time::Duration;
+
+use crate::protocol::{Channel, Extensions, HeaderMultiMap, ProtocolError, ProtocolRole};
 
 // ----------------------------------------------------------------------------
 // RequestContext Trait
@@ -47,6 +49,46 @@ pub trait RequestContext: Default + Send + 'static {
     /// Called by `Client::request_fn` before running the outpoint chain.
     fn inject_request(&mut self, request: Self::Request);
 
+    /// Raw headers of the request, in wire order with duplicates kept.
+    ///
+    /// Protocols without headers keep the default, an empty map.
+    fn headers(&self) -> &HeaderMultiMap {
+        HeaderMultiMap::empty()
+    }
+
+    /// Typed values attached to this request by middleware.
+    ///
+    /// See [`Extensions`]; it starts empty for every request.
+    fn extensions(&self) -> &Extensions;
+
+    /// Mutable access to [`extensions`](Self::extensions), e.g.
+    /// `ctx.extensions_mut().insert(user)`.
+    fn extensions_mut(&mut self) -> &mut Extensions;
+
+    /// Time the endpoint's final handler took, as recorded by the
+    /// execution chain once it returns.
+    ///
+    /// Middleware running after `next` (and finalizers) can read it for
+    /// logging or `Server-Timing` without keeping their own `Instant`.
+    /// Contexts that don't store it keep the default, `Duration::ZERO`.
+    fn handler_duration(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// Store the final handler's running time. Called by the execution
+    /// chain; contexts without a slot for it keep the default no-op.
+    fn set_handler_duration(&mut self, _duration: Duration) {}
+
+    /// A new context for the same request with an untouched response.
+    ///
+    /// Middleware that may drop the rest of the chain (a timeout, say) takes
+    /// one before calling `next`, so it still has a context to answer with
+    /// once the one it handed on is gone. Contexts that can't be copied keep
+    /// the default, `None`.
+    fn detached(&self) -> Option<Self> {
+        None
+    }
+
     /// Consume the context and return its response. Called by
     /// `Client::request_fn` / `Server::request_fn` after the chain finishes.
     fn into_response(self) -> Self::Response;
@@ -0,0 +1,90 @@
+// ============================================================================
+// Protocol Detection
+// ============================================================================
+
+/// Outcome of [`crate::protocol::Protocol::detect`] on the initial bytes of
+/// a connection.
+///
+/// Detection runs on whatever the first read returned, which may be shorter
+/// than the prefix a protocol needs to decide. `NeedMoreData` lets the
+/// connection layer read further before picking a protocol instead of
+/// rejecting the connection early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Detection {
+    /// The bytes belong to this protocol.
+    Match,
+    /// The bytes can never belong to this protocol.
+    NoMatch,
+    /// The bytes are a valid start but too short to decide.
+    NeedMoreData,
+}
+
+impl Detection {
+    /// Compares `buf` against a fixed `prefix`.
+    ///
+    /// Returns `Match` when `buf` starts with `prefix`, `NeedMoreData` when
+    /// `buf` is a non-empty strict prefix of it, and `NoMatch` otherwise. An
+    /// empty buffer means the peer sent nothing (EOF), so it never matches.
+    pub fn prefix(buf: &[u8], prefix: &[u8]) -> Self {
+        if buf.starts_with(prefix) {
+            Self::Match
+        } else if !buf.is_empty() && prefix.starts_with(buf) {
+            Self::NeedMoreData
+        } else {
+            Self::NoMatch
+        }
+    }
+
+    /// [`Detection::prefix`] over several candidates. Any `Match` wins,
+    /// then any `NeedMoreData`.
+    pub fn any_prefix(buf: &[u8], prefixes: &[&[u8]]) -> Self {
+        prefixes
+            .iter()
+            .map(|p| Self::prefix(buf, p))
+            .fold(Self::NoMatch, Self::or)
+    }
+
+    /// Combines two outcomes for "either of these shapes": `Match` beats
+    /// `NeedMoreData`, which beats `NoMatch`.
+    pub fn or(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Match, _) | (_, Self::Match) => Self::Match,
+            (Self::NeedMoreData, _) | (_, Self::NeedMoreData) => Self::NeedMoreData,
+            _ => Self::NoMatch,
+        }
+    }
+
+    /// Returns `true` for `Match`.
+    pub fn is_match(self) -> bool {
+        self == Self::Match
+    }
+}
+
+impl From<bool> for Detection {
+    fn from(matched: bool) -> Self {
+        if matched { Self::Match } else { Self::NoMatch }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+    #[test]
+    fn test_prefix_partial_needs_more_data() {
+        assert_eq!(Detection::prefix(b"PRI", PREFACE), Detection::NeedMoreData);
+        assert_eq!(Detection::prefix(PREFACE, PREFACE), Detection::Match);
+        assert_eq!(Detection::prefix(b"PRX", PREFACE), Detection::NoMatch);
+        assert_eq!(Detection::prefix(b"", PREFACE), Detection::NoMatch);
+    }
+
+    #[test]
+    fn test_any_prefix_prefers_match() {
+        let methods: &[&[u8]] = &[b"GET ", b"POST "];
+        assert_eq!(Detection::any_prefix(b"GET /", methods), Detection::Match);
+        assert_eq!(Detection::any_prefix(b"PO", methods), Detection::NeedMoreData);
+        assert_eq!(Detection::any_prefix(b"XYZ", methods), Detection::NoMatch);
+    }
+}
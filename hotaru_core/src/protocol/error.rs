@@ -22,6 +22,29 @@ pub trait ProtocolError: core::error::Error + Send + Sync + 'static {
     {
         Box::new(self)
     }
+
+    /// The error a panicking handler turns into while the server catches
+    /// panics (see `AppBuilder::catch_panics`), so the protocol answers it
+    /// like any other failed request.
+    ///
+    /// The default, `None`, lets the panic propagate even then.
+    fn from_panic(_message: &str) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// The error an outpoint call turns into when it outlives its timeout
+    /// (see `AppBuilder::default_timeout`).
+    ///
+    /// The default, `None`, leaves the call unbounded.
+    fn from_timeout(_after: core::time::Duration) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 // Blanket helper so plain `core::error::Error` types can be wrapped trivially
@@ -0,0 +1,86 @@
+// ============================================================================
+// Request Extensions
+// ============================================================================
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+use core::any::{Any, TypeId};
+use core::fmt;
+
+use akari::hash::IdHashMapTypeId;
+
+/// Per-request values keyed by their type.
+///
+/// Every context exposes one through
+/// [`RequestContext::extensions`](crate::protocol::RequestContext::extensions),
+/// so middleware can hand typed data down the chain: an auth middleware
+/// inserts the `User` it resolved and the handler reads it back. Each type
+/// holds at most one value; wrap values in a newtype to keep two of the same
+/// type apart. The store starts empty for every request.
+#[derive(Default)]
+pub struct Extensions {
+    map: IdHashMapTypeId<Box<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `value`, returning the one of the same type it replaces.
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.map
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|old| old.downcast().ok().map(|old| *old))
+    }
+
+    /// The stored value of type `T`.
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    /// The stored value of type `T`, mutably.
+    pub fn get_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.map
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut())
+    }
+
+    /// Takes the stored value of type `T` out of the store.
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.map
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok().map(|value| *value))
+    }
+
+    /// Whether a value of type `T` is stored.
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Number of stored values.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Whether nothing is stored.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Drops every stored value.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.map.len())
+            .finish()
+    }
+}
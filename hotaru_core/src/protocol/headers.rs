@@ -0,0 +1,106 @@
+// ============================================================================
+// Header Multimap
+// ============================================================================
+
+#[cfg(not(feature = "std"))]
+use crate::prelude::*;
+
+/// Request headers in wire order, duplicates included.
+///
+/// Protocol contexts expose their raw headers through
+/// [`RequestContext::headers`](crate::protocol::RequestContext::headers) as
+/// this type, so code such as `Forwarded` parsing or proxying works the same
+/// over every protocol. Names keep the case they arrived in; lookups compare
+/// them ASCII case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderMultiMap {
+    entries: Vec<(String, String)>,
+}
+
+static EMPTY: HeaderMultiMap = HeaderMultiMap::new();
+
+impl HeaderMultiMap {
+    /// Creates an empty map.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Shared empty map, for contexts that carry no headers.
+    pub fn empty() -> &'static Self {
+        &EMPTY
+    }
+
+    /// Adds a header after all existing ones, keeping any earlier value
+    /// with the same name.
+    pub fn append(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.entries.push((name.into(), value.into()));
+    }
+
+    /// Replaces every header named `name` with a single line holding
+    /// `value`, added after the others.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        self.remove(&name);
+        self.entries.push((name, value.into()));
+    }
+
+    /// Drops every header named `name`, returning whether there was one.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.entries.len() != before
+    }
+
+    /// First value for `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every value for `name`, in the order received.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.entries
+            .iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Whether at least one header is named `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// All `(name, value)` pairs, in the order received.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    /// Number of header lines, counting duplicates.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<N: Into<String>, V: Into<String>> FromIterator<(N, V)> for HeaderMultiMap {
+    fn from_iter<I: IntoIterator<Item = (N, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<N: Into<String>, V: Into<String>> Extend<(N, V)> for HeaderMultiMap {
+    fn extend<I: IntoIterator<Item = (N, V)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.append(name, value);
+        }
+    }
+}
@@ -2,8 +2,14 @@
 pub mod channel;
 /// Request context and endpoint outcome traits.
 pub mod context;
+/// Tri-state protocol detection result.
+pub mod detect;
 /// Protocol error traits and default error types.
 pub mod error;
+/// Type-keyed per-request store shared by middleware and handlers.
+pub mod extensions;
+/// Ordered header multimap shared by header-carrying protocols.
+pub mod headers;
 /// Message buffer abstraction used by protocols.
 pub mod message;
 /// Main protocol trait.
@@ -14,7 +20,10 @@ pub mod stream;
 pub mod types;
 
 pub use context::{EndpointOutcome, RequestContext};
+pub use detect::Detection;
 pub use error::{BoxProtocolError, DefaultProtocolError, EmptyError, ProtocolError};
+pub use extensions::Extensions;
+pub use headers::HeaderMultiMap;
 pub use message::Message;
 pub use protocol::{Protocol, CtxError};
 pub use stream::Stream;
@@ -10,7 +10,7 @@ use crate::protocol::ProtocolRole;
 use crate::url::UrlRoot;
 use crate::{app::common::RuntimeConfig, protocol::ProtocolFlow};
 
-use super::{Channel, Message, RequestContext, Stream as ProtocolStream};
+use super::{Channel, Detection, Message, RequestContext, Stream as ProtocolStream};
 
 // ----------------------------------------------------------------------------
 // Protocol Trait
@@ -105,7 +105,11 @@ where
     }
 
     /// Detects if this protocol can handle the connection.
-    fn detect(initial_bytes: &[u8]) -> bool
+    ///
+    /// `initial_bytes` may be shorter than the prefix the protocol needs;
+    /// return [`Detection::NeedMoreData`] rather than `NoMatch` when the
+    /// bytes so far are a valid start. Must not panic on short input.
+    fn detect(initial_bytes: &[u8]) -> Detection
     where
         Self: Sized;
 
@@ -0,0 +1,124 @@
+//! Optional integration with the `tracing` crate.
+//!
+//! With the `tracing` feature on, the `debug_*` macros emit `tracing`
+//! events instead of printing (see [`crate::debug`]), and the dispatch path
+//! in [`ProtocolEntry`](crate::executable::entry::ProtocolEntry) wraps:
+//!
+//! - every served connection in a `connection` span carrying
+//!   `connection_id` and `protocol`;
+//! - every request on it in a nested `request` span carrying `request_id`
+//!   and `route`, and emits a `request handled` event (target
+//!   `hotaru::request`) when the protocol finishes it.
+//!
+//! IDs are process-wide counters starting at 1. `route` is filled in by the
+//! protocol through [`record_route`] once it has matched the request, with
+//! the endpoint's [`RoutePattern`](crate::url::RoutePattern) rather than the
+//! request path.
+//!
+//! Without the feature the spans are zero-sized and every function here is
+//! a no-op, so protocols call them unconditionally.
+
+use core::future::Future;
+
+use crate::protocol::ProtocolFlow;
+
+#[cfg(feature = "tracing")]
+pub use self::enabled::*;
+
+#[cfg(not(feature = "tracing"))]
+pub use self::disabled::*;
+
+#[cfg(feature = "tracing")]
+mod enabled {
+    use super::*;
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use tracing::Instrument;
+
+    static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+    static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+    /// Records the route of the request being handled on its `request` span.
+    pub fn record_route(route: &str) {
+        tracing::Span::current().record("route", route);
+    }
+
+    /// Span covering one served connection.
+    pub(crate) struct ConnectionSpan(tracing::Span);
+
+    impl ConnectionSpan {
+        pub fn new(protocol: &'static str) -> Self {
+            let connection_id = NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed);
+            Self(tracing::info_span!("connection", connection_id, protocol))
+        }
+
+        pub fn instrument<F: Future>(self, fut: F) -> impl Future<Output = F::Output> {
+            fut.instrument(self.0)
+        }
+    }
+
+    /// Span covering one request, nested in the current connection span.
+    pub(crate) struct RequestSpan(tracing::Span);
+
+    impl RequestSpan {
+        pub fn new() -> Self {
+            let request_id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+            Self(tracing::info_span!(
+                "request",
+                request_id,
+                route = tracing::field::Empty
+            ))
+        }
+
+        pub fn instrument<F: Future>(&self, fut: F) -> impl Future<Output = F::Output> {
+            fut.instrument(self.0.clone())
+        }
+
+        /// Emits the `request handled` event inside this span.
+        pub fn handled(&self, flow: &ProtocolFlow) {
+            self.0.in_scope(|| {
+                tracing::info!(
+                    target: "hotaru::request",
+                    keep_alive = matches!(flow, ProtocolFlow::Continue),
+                    "request handled"
+                );
+            });
+        }
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+mod disabled {
+    use super::*;
+
+    /// Records the route of the request being handled on its `request` span.
+    pub fn record_route(_route: &str) {}
+
+    /// Span covering one served connection.
+    pub(crate) struct ConnectionSpan;
+
+    impl ConnectionSpan {
+        pub fn new(_protocol: &'static str) -> Self {
+            Self
+        }
+
+        pub fn instrument<F: Future>(self, fut: F) -> F {
+            fut
+        }
+    }
+
+    /// Span covering one request, nested in the current connection span.
+    pub(crate) struct RequestSpan;
+
+    impl RequestSpan {
+        pub fn new() -> Self {
+            Self
+        }
+
+        pub fn instrument<F: Future>(&self, fut: F) -> F {
+            fut
+        }
+
+        /// Emits the `request handled` event inside this span.
+        pub fn handled(&self, _flow: &ProtocolFlow) {}
+    }
+}
@@ -13,10 +13,12 @@ pub mod root;
 // pub use self::segments::{Url, dangling_url};
 pub use self::error::UrlError;
 pub use self::node::{
-    Children, ChildrenInner, FrameNode, LiteralChild, RegexChild, StepName, UrlNode, WalkCursor,
-    WalkFrame,
+    Children, ChildrenInner, FrameNode, LiteralChild, RegexChild, RoutePattern, StepName, UrlNode,
+    WalkCursor, WalkFrame,
 };
-pub use self::parser::{PatternError, RawToken, TypeKind, tokenize, tokens_to_patterns};
-pub use self::pattern::{PathPattern, RegexSegment, path_pattern_creator::*};
+pub use self::parser::{
+    PatternError, RawToken, TypeKind, UrlParseError, tokenize, tokens_to_patterns,
+};
+pub use self::pattern::{PathPattern, RegexSegment, decode_segment, path_pattern_creator::*};
 pub use self::root::UrlRegistration;
 pub use self::root::UrlRoot;
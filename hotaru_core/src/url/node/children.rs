@@ -42,8 +42,13 @@ pub struct ChildrenInner<C: RequestContext, TS: TransportSpec> {
 }
 
 /// Exact-match child cache for literal path segments.
+///
+/// A second map keyed by the ASCII-lowercased segment serves
+/// case-insensitive lookups. When several literals fold to the same key the
+/// one registered first answers until it is removed.
 pub struct LiteralChild<C: RequestContext, TS: TransportSpec> {
     inner: HashMap<String, Arc<UrlNode<C, TS>>>,
+    folded: HashMap<String, Arc<UrlNode<C, TS>>>,
     _ts: PhantomData<TS>,
 }
 
@@ -102,6 +107,11 @@ impl<C: RequestContext, TS: TransportSpec> Children<C, TS> {
         self.inner.read().match_literal(segment)
     }
 
+    /// Matches a literal child by exact segment, then ignoring ASCII case.
+    pub fn match_literal_ignore_case(&self, segment: &str) -> Option<Arc<UrlNode<C, TS>>> {
+        self.inner.read().match_literal_ignore_case(segment)
+    }
+
     /// Matches the first regex child that accepts the segment.
     pub fn match_regex(&self, segment: &str) -> Option<Arc<UrlNode<C, TS>>> {
         self.inner.read().match_regex(segment)
@@ -145,6 +155,22 @@ impl<C: RequestContext, TS: TransportSpec> Children<C, TS> {
         self.inner.read().match_step(segment, state)
     }
 
+    /// Like [`Children::match_step`], with literal segments optionally
+    /// compared ignoring ASCII case.
+    ///
+    /// Only the literal lookup folds case; regex and wildcard children
+    /// still see the segment exactly as it was sent.
+    pub fn match_step_with(
+        &self,
+        segment: &str,
+        state: PartialState,
+        ignore_case: bool,
+    ) -> (Option<Arc<UrlNode<C, TS>>>, PartialState) {
+        self.inner
+            .read()
+            .match_step_with(segment, state, ignore_case)
+    }
+
     /// Returns all child nodes for traversal or debug use.
     pub fn all_nodes(&self) -> Vec<Arc<UrlNode<C, TS>>> {
         self.inner.read().all_nodes()
@@ -242,6 +268,13 @@ impl<C: RequestContext, TS: TransportSpec> ChildrenInner<C, TS> {
         self.literals.get(segment)
     }
 
+    /// Matches a literal child by exact segment, then ignoring ASCII case.
+    pub fn match_literal_ignore_case(&self, segment: &str) -> Option<Arc<UrlNode<C, TS>>> {
+        self.literals
+            .get(segment)
+            .or_else(|| self.literals.get_ignore_case(segment))
+    }
+
     /// Matches the first regex child that accepts the segment.
     pub fn match_regex(&self, segment: &str) -> Option<Arc<UrlNode<C, TS>>> {
         self.regex
@@ -286,10 +319,26 @@ impl<C: RequestContext, TS: TransportSpec> ChildrenInner<C, TS> {
         &self,
         segment: &str,
         state: PartialState,
+    ) -> (Option<Arc<UrlNode<C, TS>>>, PartialState) {
+        self.match_step_with(segment, state, false)
+    }
+
+    /// Matches one step, comparing literal segments ignoring ASCII case when
+    /// `ignore_case` is set.
+    pub fn match_step_with(
+        &self,
+        segment: &str,
+        state: PartialState,
+        ignore_case: bool,
     ) -> (Option<Arc<UrlNode<C, TS>>>, PartialState) {
         match state {
             PartialState::NotStart => {
-                if let Some(node) = self.match_literal(segment) {
+                let literal = if ignore_case {
+                    self.match_literal_ignore_case(segment)
+                } else {
+                    self.match_literal(segment)
+                };
+                if let Some(node) = literal {
                     return (Some(node), PartialState::Lit);
                 }
                 if let Some((node, idx)) = self.match_regex_with_idx(segment, 0) {
@@ -382,6 +431,7 @@ impl<C: RequestContext, TS: TransportSpec> LiteralChild<C, TS> {
     pub fn new() -> Self {
         Self {
             inner: HashMap::default(),
+            folded: HashMap::default(),
             _ts: PhantomData,
         }
     }
@@ -398,12 +448,45 @@ impl<C: RequestContext, TS: TransportSpec> LiteralChild<C, TS> {
 
     /// Inserts or replaces a literal child by exact segment.
     pub fn insert<T: Into<String>>(&mut self, segment: T, node: Arc<UrlNode<C, TS>>) {
-        self.inner.insert(segment.into(), node);
+        let segment = segment.into();
+        let key = segment.to_ascii_lowercase();
+        let replaced = self.inner.insert(segment, node.clone());
+        // An earlier spelling keeps the folded key unless it was just replaced
+        let taken = self.folded.get(&key).is_some_and(|current| {
+            !replaced
+                .as_ref()
+                .is_some_and(|old| Arc::ptr_eq(current, old))
+        });
+        if !taken {
+            self.folded.insert(key, node);
+        }
     }
 
     /// Removes a literal child by exact segment.
     pub fn remove(&mut self, segment: &str) -> Option<Arc<UrlNode<C, TS>>> {
-        self.inner.remove(segment)
+        let removed = self.inner.remove(segment)?;
+        let key = segment.to_ascii_lowercase();
+        if self
+            .folded
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &removed))
+        {
+            self.folded.remove(&key);
+            // Hand the folded key to another literal spelled the same way
+            let mut others: Vec<_> = self
+                .inner
+                .keys()
+                .filter(|other| other.eq_ignore_ascii_case(segment))
+                .cloned()
+                .collect();
+            others.sort();
+            if let Some(other) = others.first()
+                && let Some(node) = self.inner.get(other)
+            {
+                self.folded.insert(key, node.clone());
+            }
+        }
+        Some(removed)
     }
 
     /// Finds a literal child by exact segment.
@@ -411,6 +494,12 @@ impl<C: RequestContext, TS: TransportSpec> LiteralChild<C, TS> {
         self.inner.get(segment).cloned()
     }
 
+    /// Finds a literal child whose segment equals `segment` ignoring ASCII
+    /// case.
+    pub fn get_ignore_case(&self, segment: &str) -> Option<Arc<UrlNode<C, TS>>> {
+        self.folded.get(&segment.to_ascii_lowercase()).cloned()
+    }
+
     /// Returns every literal child node.
     pub fn all_nodes(&self) -> Vec<Arc<UrlNode<C, TS>>> {
         let mut keys: Vec<_> = self.inner.keys().cloned().collect();
@@ -425,6 +514,7 @@ impl<C: RequestContext, TS: TransportSpec> Clone for LiteralChild<C, TS> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            folded: self.folded.clone(),
             _ts: PhantomData,
         }
     }
@@ -476,7 +566,7 @@ mod tests {
         connection::test_support::TestTransport,
         executable::ExecutableBinding,
         extensions::ParamsClone,
-        protocol::{Channel, ProtocolRole, RequestContext},
+        protocol::{Channel, Extensions, ProtocolRole, RequestContext},
         url::{PathPattern, StepName},
     };
 
@@ -493,7 +583,9 @@ mod tests {
     }
 
     #[derive(Default)]
-    struct TestContext;
+    struct TestContext {
+        extensions: Extensions,
+    }
 
     impl RequestContext for TestContext {
         type Request = ();
@@ -507,6 +599,14 @@ mod tests {
             ProtocolRole::Server
         }
 
+        fn extensions(&self) -> &Extensions {
+            &self.extensions
+        }
+
+        fn extensions_mut(&mut self) -> &mut Extensions {
+            &mut self.extensions
+        }
+
         fn inject_request(&mut self, _: Self::Request) {}
         fn into_response(self) -> Self::Response {}
     }
@@ -47,6 +47,7 @@ impl<C: RequestContext, TS: TransportSpec> FrameNode<C, TS> {
 #[av::ver(unstable, since = "0.8.1", note = "Resumable URL traversal — surface may change", date = "2026-05-25")]
 pub struct WalkCursor<C: RequestContext, TS: TransportSpec> {
     frames: Vec<WalkFrame<C, TS>>,
+    ignore_case: bool,
 }
 
 impl<C, TS> WalkCursor<C, TS>
@@ -56,12 +57,16 @@ where
 {
     /// Yields `None` on every call. Used by `walk_cursor("")`.
     pub fn empty() -> Self {
-        Self { frames: Vec::new() }
+        Self {
+            frames: Vec::new(),
+            ignore_case: false,
+        }
     }
 
-    /// Walk from a root's children.
+    /// Walk from a root's children, matching literals as the root is set to.
     pub fn from_root(root: Arc<RootNode<C, TS>>) -> Self {
         Self {
+            ignore_case: root.is_case_insensitive(),
             frames: vec![WalkFrame {
                 node: FrameNode::Root(root),
                 state: PartialState::NotStart,
@@ -76,6 +81,7 @@ where
                 node: FrameNode::Node(node),
                 state: PartialState::NotStart,
             }],
+            ignore_case: false,
         }
     }
 
@@ -95,7 +101,10 @@ where
             let state = self.frames[idx].state;
             let segment = segments[depth];
             let (matched, next_state) =
-                self.frames[idx].node.children().match_step(segment, state);
+                self.frames[idx]
+                    .node
+                    .children()
+                    .match_step_with(segment, state, self.ignore_case);
             self.frames[idx].state = next_state;
 
             let Some(child) = matched else {
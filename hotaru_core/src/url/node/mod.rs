@@ -4,6 +4,7 @@ use alloc::sync::Arc;
 use core::slice::Iter;
 
 use crate::{
+    app::common::RuntimeConfig,
     connection::TransportSpec,
     executable::{ExecutableBinding, ExecutionChain},
     extensions::{ParamValue, ParamsClone},
@@ -22,6 +23,41 @@ pub use self::cursor::{FrameNode, WalkCursor, WalkFrame};
 pub use self::partial::PartialState;
 pub use self::stepname::StepName;
 
+/// Pattern a node was registered under, e.g. `/users/<id>`.
+///
+/// [`UrlRoot::register`](crate::url::UrlRoot) stores it in the node's
+/// params, so it reads back through [`UrlNode::route`]. Named captures show
+/// as `<name>`; unnamed ones as `*`, `**` or their regex in angle brackets.
+/// Unlike the request path it has one value per route, which keeps it
+/// usable as a label in logs and metrics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutePattern(pub String);
+
+impl RoutePattern {
+    /// Renders parsed segments, naming captures from `names`.
+    pub fn render(path: &[PathPattern], names: &StepName) -> Self {
+        let segments: Vec<String> = path
+            .iter()
+            .enumerate()
+            .map(|(index, pattern)| {
+                let name = names
+                    .inner
+                    .iter()
+                    .find(|(_, i)| **i == index)
+                    .map(|(name, _)| name);
+                match (pattern, name) {
+                    (PathPattern::Literal(text), _) => text.clone(),
+                    (_, Some(name)) => format!("<{name}>"),
+                    (PathPattern::Any, None) => "*".to_string(),
+                    (PathPattern::AnyPath, None) => "**".to_string(),
+                    (PathPattern::Regex(segment), None) => format!("<{}>", segment.src()),
+                }
+            })
+            .collect();
+        Self(segments.join("/"))
+    }
+}
+
 /// Represents a URL in the application.
 /// This struct holds the various components of a URL, including its path, query parameters, and more.
 pub struct UrlNode<C: RequestContext, TS: TransportSpec> {
@@ -182,9 +218,20 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> UrlNode<C, TS> {
     /// If a future design introduces dynamic route creation or cyclic node graphs,
     /// depth validation should be revisited at that layer.
     pub fn walk<'a>(
+        self: Arc<Self>,
+        path: Iter<'a, &str>,
+        state: PartialState,
+    ) -> MaybeSendBoxFuture<'a, Option<Arc<Self>>> {
+        self.walk_with(path, state, false)
+    }
+
+    /// Like [`UrlNode::walk`], with literal segments compared ignoring ASCII
+    /// case when `ignore_case` is set.
+    pub fn walk_with<'a>(
         self: Arc<Self>,
         mut path: Iter<'a, &str>,
         mut state: PartialState,
+        ignore_case: bool,
     ) -> MaybeSendBoxFuture<'a, Option<Arc<Self>>> {
         let this_segment = match path.next() {
             Some(segment) => *segment,
@@ -193,7 +240,9 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> UrlNode<C, TS> {
 
         Box::pin(async move {
             while !state.is_end() {
-                let (matched_child, next_state) = self.children.match_step(this_segment, state);
+                let (matched_child, next_state) =
+                    self.children
+                        .match_step_with(this_segment, state, ignore_case);
                 state = next_state;
 
                 let Some(child) = matched_child else {
@@ -203,7 +252,7 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> UrlNode<C, TS> {
                 if path.len() >= 1 && !child.path().is_any_path() {
                     if let Some(result) = child
                         .clone()
-                        .walk(path.clone(), PartialState::NotStart)
+                        .walk_with(path.clone(), PartialState::NotStart, ignore_case)
                         .await
                     {
                         return Some(result);
@@ -241,6 +290,14 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> UrlNode<C, TS> {
         self.walk(segments.iter(), PartialState::NotStart).await
     }
 
+    /// The pattern this node was registered under, if it was registered
+    /// rather than created as an intermediate segment.
+    pub fn route(&self) -> Option<&str> {
+        self.params
+            .get::<RoutePattern>()
+            .map(|route| route.0.as_str())
+    }
+
     /// Retrieves a cloned value of type `T` from the URL's parameter storage.
     /// Returns `Some(T)` if the parameter exists and matches the type, `None` otherwise.
     pub fn get_params<T: ParamValue + Clone + 'static>(&self) -> Option<T> {
@@ -314,6 +371,65 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> UrlNode<C, TS> {
             Ok(ctx)
         }
     }
+
+    /// Runs the endpoint for a request a server received.
+    ///
+    /// Like [`run`](Self::run), but while `runtime.catch_panics()` is on a
+    /// panic in the chain becomes the error `ProtocolError::from_panic`
+    /// gives for it, so the protocol sends its usual error response and the
+    /// connection survives. Otherwise, or if the error type has no panic
+    /// error, the panic propagates.
+    #[cfg(feature = "std")]
+    pub async fn run_guarded(
+        &self,
+        ctx: C,
+        runtime: &RuntimeConfig,
+    ) -> Result<C, <C as RequestContext>::Error> {
+        use core::task::Poll;
+        use std::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
+
+        use crate::protocol::ProtocolError;
+
+        if !runtime.catch_panics() {
+            return self.run(ctx).await;
+        }
+        let mut run = core::pin::pin!(self.run(ctx));
+        let caught = core::future::poll_fn(|cx| {
+            match catch_unwind(AssertUnwindSafe(|| run.as_mut().poll(cx))) {
+                Ok(Poll::Ready(result)) => Poll::Ready(Ok(result)),
+                Ok(Poll::Pending) => Poll::Pending,
+                Err(payload) => Poll::Ready(Err(payload)),
+            }
+        })
+        .await;
+        let payload = match caught {
+            Ok(result) => return result,
+            Err(payload) => payload,
+        };
+
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("non-string panic payload");
+        match <C as RequestContext>::Error::from_panic(message) {
+            Some(err) => {
+                crate::debug_error!("Handler panicked: {}", message);
+                Err(err)
+            }
+            None => resume_unwind(payload),
+        }
+    }
+
+    /// Without `std` panics cannot be caught; this is [`run`](Self::run).
+    #[cfg(not(feature = "std"))]
+    pub async fn run_guarded(
+        &self,
+        ctx: C,
+        _runtime: &RuntimeConfig,
+    ) -> Result<C, <C as RequestContext>::Error> {
+        self.run(ctx).await
+    }
 }
 
 #[cfg(test)]
@@ -322,7 +438,7 @@ mod tests {
 
     use crate::{
         connection::test_support::TestTransport,
-        protocol::{Channel, ProtocolRole},
+        protocol::{Channel, Extensions, ProtocolRole},
         url::PathPattern,
     };
 
@@ -339,7 +455,9 @@ mod tests {
     }
 
     #[derive(Default)]
-    struct TestContext;
+    struct TestContext {
+        extensions: Extensions,
+    }
 
     impl RequestContext for TestContext {
         type Request = ();
@@ -353,6 +471,14 @@ mod tests {
             ProtocolRole::Server
         }
 
+        fn extensions(&self) -> &Extensions {
+            &self.extensions
+        }
+
+        fn extensions_mut(&mut self) -> &mut Extensions {
+            &mut self.extensions
+        }
+
         fn inject_request(&mut self, _: Self::Request) {}
         fn into_response(self) -> Self::Response {}
     }
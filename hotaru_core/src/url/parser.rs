@@ -1,6 +1,9 @@
 #[cfg(not(feature = "std"))]
 use crate::prelude::*;
-pub use crate::url::{PathPattern, parser::parser::PatternError};
+pub use crate::url::{
+    PathPattern,
+    parser::parser::{PatternError, UrlParseError},
+};
 
 /// Tokenizer for URL pattern strings.
 pub mod lexer;
@@ -103,6 +106,8 @@ pub use parser::tokens_to_patterns;
 // - "/<int" -> Err("ExpectedAngleClose at index …")
 // - "/<int:>" -> Err("ExpectedIdentAfterColon at index …")
 // - "/files-<**path>" -> Err("AnyPathMixedWithOtherContent at index …")
+// - "/<integer:id>" -> Err("UnknownType at index …")
+// - "/<>" -> Err("EmptyAngle at index …")
 
 // Complexity:
 // - Tokenization and parsing both run in O(n) over the input size; memory scales with the number of segments and dynamic groups.
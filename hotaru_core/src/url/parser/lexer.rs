@@ -28,6 +28,7 @@ pub enum RawToken {
 // - <int> => Type(Int) without a name
 // - <id>  => Ident("id") without a type
 // - <**path> => Type(Path) special (no regex expansion)
+// - <path:rest> => Type(Path) too; a bare <path> stays a name-only Any
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TypeKind {
@@ -128,7 +129,7 @@ fn count_pipes(chars: &[char], mut i: usize) -> usize {
 //     by exactly N consecutive pipes on each side: <|||content with | inside|||:name>.
 //     Inside the block, single pipes do not become Pipe tokens; they are part of the Literal.
 //     We emit: AngleStart, N*Pipe, Literal(content), N*Pipe, Colon, Ident(name), AngleClose.
-//   - Else if it starts with "**path", we emit Type(Path).
+//   - Else if it starts with "**path", or is "path" followed by ":", we emit Type(Path).
 //   - Else if it starts with an identifier and we have not yet seen ":", we emit Type(..)
 //     when the ident is one of {int, uint, decimal, str, uuid}; otherwise Ident(ident).
 //   - Else, we treat content as free-form regex until ":" or ">" (pipes and '/' inside are literal).
@@ -308,6 +309,8 @@ pub fn tokenize(input: &str) -> Result<Vec<RawToken>, PatternError> {
                 let ident: String = chars[start..i].iter().collect();
                 if let Some(kind) = TypeKind::from_ident(&ident) {
                     out.push(RawToken::Type(kind));
+                } else if ident == "path" && chars.get(i) == Some(&':') {
+                    out.push(RawToken::Type(TypeKind::Path));
                 } else {
                     out.push(RawToken::Ident(ident));
                 }
@@ -511,6 +514,28 @@ mod tests {
         assert_eq!(tokens, expected);
     }
 
+    #[test]
+    fn named_path_is_any_path() {
+        let tokens = tokenize("<path:rest>").unwrap();
+        let expected = vec![
+            AngleStart,
+            super::RawToken::Type(TypeKind::Path),
+            Colon,
+            super::RawToken::Ident("rest".into()),
+            AngleClose,
+        ];
+        assert_eq!(tokens, expected);
+        // Without a name `path` is an ordinary parameter name
+        assert_eq!(
+            tokenize("<path>").unwrap(),
+            vec![
+                AngleStart,
+                super::RawToken::Ident("path".into()),
+                AngleClose
+            ]
+        );
+    }
+
     #[test]
     fn outside_literals_and_angle_with_slash_separators() {
         let input = "/users/<id>";
@@ -33,6 +33,15 @@ pub enum PatternError {
     AnyPathMixedWithOtherContent {
         at: usize,
     },
+    // <name:id> where `name` is not a known type (int, uint, decimal, str, uuid, path).
+    UnknownType {
+        at: usize,
+        name: String,
+    },
+    // An angle group with nothing inside, `<>`.
+    EmptyAngle {
+        at: usize,
+    },
 }
 
 impl core::fmt::Display for PatternError {
@@ -72,6 +81,65 @@ impl core::fmt::Display for PatternError {
                     at
                 )
             }
+            PatternError::UnknownType { at, name } => {
+                write!(f, "Unknown parameter type '{}' at index {}", name, at)
+            }
+            PatternError::EmptyAngle { at } => {
+                write!(f, "Empty '<>' at index {}", at)
+            }
+        }
+    }
+}
+
+/// Why a single-segment pattern given to [`PathPattern::try_new`] is invalid.
+///
+/// Groups the parser's [`PatternError`]s into the mistakes a caller
+/// registering routes programmatically can act on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlParseError {
+    /// `<type:name>` names a type other than `int`, `uint`, `decimal`,
+    /// `str`, `uuid` or `path`.
+    UnknownParamType(String),
+    /// A `<` or a `<||` regex block is never closed.
+    UnbalancedBrackets,
+    /// The pattern, or an angle group in it (`<>`), is empty.
+    EmptySegment,
+    /// The pattern contains a `/`; it must describe exactly one segment.
+    MultipleSegments,
+    /// A regex segment that does not compile.
+    InvalidRegex(String),
+    /// Any other syntax error.
+    Syntax(PatternError),
+}
+
+impl core::fmt::Display for UrlParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            UrlParseError::UnknownParamType(name) => {
+                write!(f, "unknown parameter type '{}'", name)
+            }
+            UrlParseError::UnbalancedBrackets => write!(f, "unbalanced brackets"),
+            UrlParseError::EmptySegment => write!(f, "empty segment"),
+            UrlParseError::MultipleSegments => {
+                write!(f, "pattern spans more than one segment")
+            }
+            UrlParseError::InvalidRegex(src) => write!(f, "invalid regex {:?}", src),
+            UrlParseError::Syntax(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl core::error::Error for UrlParseError {}
+
+impl From<PatternError> for UrlParseError {
+    fn from(error: PatternError) -> Self {
+        match error {
+            PatternError::UnknownType { name, .. } => UrlParseError::UnknownParamType(name),
+            PatternError::ExpectedAngleClose { .. } | PatternError::MissingClosingPipes { .. } => {
+                UrlParseError::UnbalancedBrackets
+            }
+            PatternError::EmptyAngle { .. } => UrlParseError::EmptySegment,
+            error => UrlParseError::Syntax(error),
         }
     }
 }
@@ -91,7 +159,7 @@ impl core::fmt::Display for PatternError {
 ///   - Regex: produced by typed patterns (`<int>`, `<uuid>`, custom regex blocks), or any
 ///     mixture of literal + dynamic parts inside a single segment. Literal parts are
 ///     regex-escaped when embedded.
-///   - AnyPath: produced by `<**path>`. It must be the only content of the segment.
+///   - AnyPath: produced by `<**path>` or `<path:name>`. It must be the only content of the segment.
 /// - Names: If a segment defines a name via `<..:name>` or `<name>`, it's captured as Some(name).
 ///   If multiple names are specified in a single segment, the first one wins.
 pub fn tokens_to_patterns(
@@ -402,6 +470,13 @@ fn parse_angle(
     if let Some(RawToken::Ident(s)) = tokens.get(i) {
         let name = Some(s.clone());
         i += 1;
+        if matches!(tokens.get(i), Some(RawToken::Colon)) {
+            // <ident:name> reads as a typed parameter of an unknown type
+            return Err(PatternError::UnknownType {
+                at: start_i,
+                name: s.clone(),
+            });
+        }
         if matches!(tokens.get(i), Some(RawToken::AngleClose)) {
             i += 1;
             return Ok((AngleKind::Any, name, i));
@@ -410,7 +485,12 @@ fn parse_angle(
         }
     }
 
-    // 6) Unexpected token after '<'
+    // 6) Nothing between '<' and '>'
+    if matches!(tokens.get(i), Some(RawToken::AngleClose)) {
+        return Err(PatternError::EmptyAngle { at: start_i });
+    }
+
+    // 7) Unexpected token after '<'
     Err(PatternError::UnexpectedToken {
         at: start_i,
         token: tokens
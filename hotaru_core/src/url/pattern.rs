@@ -1,8 +1,10 @@
 #[cfg(not(feature = "std"))]
 use crate::prelude::*;
+use alloc::borrow::Cow;
 use alloc::sync::Arc;
 
 use crate::debug_warn;
+use crate::url::parser::{UrlParseError, parse};
 
 // `regex` crate under all flavours. Under `full` the `regex/unicode`
 // feature adds Unicode tables (for `\p{...}` classes etc.); under
@@ -71,6 +73,16 @@ impl RegexSegment {
 }
 
 /// A single URL path-segment matcher.
+///
+/// When several registered patterns match the same segment, the router
+/// takes the most specific one, whatever the registration order:
+/// `Literal > Regex > Any > AnyPath`, i.e. literal segments, then typed
+/// parameters (`<int:id>`), then plain parameters (`<name>`), then the
+/// catch-all (`<**path>` / `<path:rest>`). So with both `/files/special` and
+/// `/files/<path:rest>` registered, `/files/special` reaches the literal route
+/// and `/files/a/b` the catch-all. A weaker candidate is only tried when the
+/// stronger one cannot complete the rest of the path. Overlapping regex
+/// segments are tried in registration order.
 #[derive(Clone, Debug)]
 pub enum PathPattern {
     /// A literal path segment, such as `users`.
@@ -84,6 +96,33 @@ pub enum PathPattern {
 }
 
 impl PathPattern {
+    /// Parses one segment of route syntax, such as `users`, `<int:id>` or
+    /// `<path:rest>`, reporting why it is invalid.
+    ///
+    /// Use this when building routes programmatically; unlike
+    /// [`PathPattern::regex_path`], a regex that does not compile is an
+    /// error here rather than a segment that never matches.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UrlParseError`] if the segment is empty, contains a `/`,
+    /// names an unknown parameter type, leaves a bracket open or holds an
+    /// invalid regex.
+    pub fn try_new(pattern: &str) -> Result<Self, UrlParseError> {
+        let (mut patterns, _names) = parse(pattern)?;
+        let pattern = match patterns.len() {
+            0 => return Err(UrlParseError::EmptySegment),
+            1 => patterns.remove(0),
+            _ => return Err(UrlParseError::MultipleSegments),
+        };
+        if let PathPattern::Regex(seg) = &pattern
+            && !seg.is_compiled()
+        {
+            return Err(UrlParseError::InvalidRegex(seg.src().to_string()));
+        }
+        Ok(pattern)
+    }
+
     /// Creates a literal path pattern.
     pub fn literal_path<T: Into<String>>(path: T) -> Self {
         Self::Literal(path.into())
@@ -118,7 +157,8 @@ impl PathPattern {
         }
     }
 
-    /// Get the priority of this pattern for ordering (lower = higher priority)
+    /// Get the priority of this pattern for ordering (lower = higher priority).
+    /// This is the order matching tries candidates in.
     pub fn priority(&self) -> u8 {
         match self {
             PathPattern::Literal(_) => 0,
@@ -192,6 +232,47 @@ impl core::fmt::Display for PathPattern {
     }
 }
 
+/// Percent-decodes a captured path segment.
+///
+/// Routing always matches the raw segment; this is only applied to values
+/// handed out by name (e.g. `req.pattern("name")`), so `John%20Doe` reads
+/// as `John Doe`. A `%` not followed by two hex digits is kept literally,
+/// and if the decoded bytes are not valid UTF-8 the raw segment is returned
+/// unchanged. `+` is not a space in a path and stays as is.
+///
+/// Escapes for `/`, `\` and NUL stay encoded: a decoded value is often
+/// joined onto a file path, and `..%2F..%2Fetc` must not turn into
+/// `../../etc` there. Read the raw segment when the exact bytes matter.
+pub fn decode_segment(raw: &str) -> Cow<'_, str> {
+    if !raw.contains('%') {
+        return Cow::Borrowed(raw);
+    }
+
+    let bytes = raw.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let (Some(hi), Some(lo)) = (
+                bytes.get(i + 1).and_then(|b| (*b as char).to_digit(16)),
+                bytes.get(i + 2).and_then(|b| (*b as char).to_digit(16)),
+            )
+            && !matches!((hi * 16 + lo) as u8, b'/' | b'\\' | 0)
+        {
+            decoded.push((hi * 16 + lo) as u8);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    match String::from_utf8(decoded) {
+        Ok(decoded) => Cow::Owned(decoded),
+        Err(_) => Cow::Borrowed(raw),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     //! `PartialEq` and matching tests for `PathPattern`.
@@ -542,3 +623,76 @@ mod tests {
         assert!(!s.is_match("a/b"));
     }
 }
+
+#[cfg(test)]
+mod decode_tests {
+    use super::decode_segment;
+
+    #[test]
+    fn decodes_escapes_and_keeps_malformed_ones() {
+        assert_eq!(decode_segment("John%20Doe"), "John Doe");
+        assert_eq!(decode_segment("caf%C3%A9"), "café");
+        assert_eq!(decode_segment("a+b"), "a+b");
+        assert_eq!(decode_segment("100%"), "100%");
+        assert_eq!(decode_segment("50%zz%2"), "50%zz%2");
+        // Not UTF-8 once decoded
+        assert_eq!(decode_segment("%FF%20"), "%FF%20");
+    }
+
+    #[test]
+    fn keeps_separators_and_nul_encoded() {
+        assert_eq!(decode_segment("..%2F..%2Fetc%2f"), "..%2F..%2Fetc%2f");
+        assert_eq!(decode_segment("..%5C..%5Cwin.ini"), "..%5C..%5Cwin.ini");
+        assert_eq!(decode_segment("name%00.txt"), "name%00.txt");
+        assert_eq!(decode_segment("a%20b%2Fc"), "a b%2Fc");
+    }
+}
+
+#[cfg(test)]
+mod try_new_tests {
+    use super::PathPattern;
+    use crate::url::parser::UrlParseError;
+
+    #[test]
+    fn parses_one_segment() {
+        assert_eq!(
+            PathPattern::try_new("users"),
+            Ok(PathPattern::literal_path("users"))
+        );
+        assert_eq!(PathPattern::try_new("<id>"), Ok(PathPattern::Any));
+        assert_eq!(
+            PathPattern::try_new("<path:rest>"),
+            Ok(PathPattern::AnyPath)
+        );
+        assert!(matches!(
+            PathPattern::try_new("<int:id>"),
+            Ok(PathPattern::Regex(_))
+        ));
+    }
+
+    #[test]
+    fn malformed_patterns_report_why() {
+        assert_eq!(
+            PathPattern::try_new("<integer:id>"),
+            Err(UrlParseError::UnknownParamType("integer".into()))
+        );
+        assert_eq!(
+            PathPattern::try_new("<int:id"),
+            Err(UrlParseError::UnbalancedBrackets)
+        );
+        assert_eq!(
+            PathPattern::try_new("<||a|b"),
+            Err(UrlParseError::UnbalancedBrackets)
+        );
+        assert_eq!(PathPattern::try_new(""), Err(UrlParseError::EmptySegment));
+        assert_eq!(PathPattern::try_new("<>"), Err(UrlParseError::EmptySegment));
+        assert_eq!(
+            PathPattern::try_new("users/<id>"),
+            Err(UrlParseError::MultipleSegments)
+        );
+        assert_eq!(
+            PathPattern::try_new("<||[a-||>"),
+            Err(UrlParseError::InvalidRegex("[a-".into()))
+        );
+    }
+}
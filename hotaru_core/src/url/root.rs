@@ -2,6 +2,7 @@
 use crate::prelude::*;
 use alloc::sync::Arc;
 use core::slice::Iter;
+use core::sync::atomic::{AtomicBool, Ordering};
 
 use akari::extensions::ParamsClone;
 
@@ -16,7 +17,7 @@ use crate::{
 };
 
 use super::{
-    node::{PartialState, StepName, UrlNode},
+    node::{PartialState, RoutePattern, StepName, UrlNode},
     parser::parse,
 };
 
@@ -30,9 +31,14 @@ use super::{
 /// HTTP `"/"` is NOT the root endpoint. It is registered as a two-level
 /// `[Literal(""), Literal("")]` tree node so that `"/"` and `""` remain
 /// distinct routes.
+///
+/// A second `fallback` slot holds the catch-all handler used when no route
+/// with a handler matches (see [`UrlRoot::walk_str`]).
 pub struct RootNode<C: RequestContext, TS: TransportSpec> {
     children: Children<C, TS>,
     endpoint: PRwLock<Option<Arc<UrlNode<C, TS>>>>,
+    fallback: PRwLock<Option<Arc<UrlNode<C, TS>>>>,
+    case_insensitive: AtomicBool,
 }
 
 impl<C: RequestContext + Send + 'static, TS: TransportSpec> RootNode<C, TS> {
@@ -40,6 +46,8 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> RootNode<C, TS> {
         Self {
             children: Children::new(),
             endpoint: PRwLock::new(None),
+            fallback: PRwLock::new(None),
+            case_insensitive: AtomicBool::new(false),
         }
     }
 
@@ -65,6 +73,29 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> RootNode<C, TS> {
         self.endpoint.read().clone()
     }
 
+    /// Returns a cloned `Arc` of the current fallback node, if one is
+    /// registered. Read live on every call, like [`RootNode::endpoint`].
+    pub fn fallback(&self) -> Option<Arc<UrlNode<C, TS>>> {
+        self.fallback.read().clone()
+    }
+
+    /// Returns `true` if literal segments are matched ignoring ASCII case.
+    pub fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive.load(Ordering::Relaxed)
+    }
+
+    /// Substitutes the fallback when the walk found nothing to run.
+    ///
+    /// Intermediate nodes without a handler also fall back, so a route at
+    /// `/api/x` does not swallow `/api`. Without a fallback the walk result
+    /// is returned unchanged.
+    fn or_fallback(&self, found: Option<Arc<UrlNode<C, TS>>>) -> Option<Arc<UrlNode<C, TS>>> {
+        match found {
+            Some(node) if node.has_handler() => Some(node),
+            other => self.fallback().or(other),
+        }
+    }
+
     /// Walks from the root's children using a segment iterator.
     ///
     /// If the iterator is exhausted on entry the root endpoint is returned.
@@ -81,10 +112,13 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> RootNode<C, TS> {
         };
 
         Box::pin(async move {
+            let ignore_case = self.is_case_insensitive();
             let mut state = PartialState::NotStart;
 
             while !state.is_end() {
-                let (matched_child, next_state) = self.children.match_step(this_segment, state);
+                let (matched_child, next_state) =
+                    self.children
+                        .match_step_with(this_segment, state, ignore_case);
                 state = next_state;
 
                 let Some(child) = matched_child else {
@@ -94,7 +128,7 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> RootNode<C, TS> {
                 if path.len() >= 1 && !child.path().is_any_path() {
                     if let Some(result) = child
                         .clone()
-                        .walk(path.clone(), PartialState::NotStart)
+                        .walk_with(path.clone(), PartialState::NotStart, ignore_case)
                         .await
                     {
                         return Some(result);
@@ -115,9 +149,11 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> RootNode<C, TS> {
 ///   segmentless root endpoint slot.  Only protocols that have a meaningful
 ///   empty-path concept (e.g. MQTT) use this variant.
 /// - `Node` — the path resolved to a tree node (includes HTTP `"/"`).
+/// - `Fallback` — the binding was stored in the root's catch-all slot.
 pub enum UrlRegistration<C: RequestContext, TS: TransportSpec> {
     Root(Arc<RootNode<C, TS>>),
     Node(Arc<UrlNode<C, TS>>),
+    Fallback(Arc<RootNode<C, TS>>),
 }
 
 impl<C: RequestContext + Send + 'static, TS: TransportSpec> Clone for UrlRegistration<C, TS> {
@@ -125,6 +161,7 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> Clone for UrlRegistr
         match self {
             UrlRegistration::Root(root) => UrlRegistration::Root(root.clone()),
             UrlRegistration::Node(node) => UrlRegistration::Node(node.clone()),
+            UrlRegistration::Fallback(root) => UrlRegistration::Fallback(root.clone()),
         }
     }
 }
@@ -140,6 +177,21 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> Clone for UrlRegistr
 /// `walk_str("")` returns the root endpoint (registered via `literal_url("")`).
 /// `walk_str("/")` walks the tree as `["", ""]` — it is a distinct node, never
 /// the root endpoint. Only the literal empty-string path maps to the root.
+///
+/// # Fallback
+///
+/// A fallback registered with `register_fallback` has the lowest priority
+/// of all routes: it runs only when the walk finds no node with a handler,
+/// after every literal, wildcard and `<**path>` candidate has been tried.
+/// Paths rejected by a depth limit never reach the fallback.
+///
+/// # Case sensitivity
+///
+/// Literal segments match exactly by default. After
+/// `set_case_insensitive(true)` a literal that has no exact match is looked
+/// up again ignoring ASCII case, so `/Users` reaches a `/users` route.
+/// Regex and wildcard segments are unaffected and capture the segment as
+/// sent.
 pub struct UrlRoot<C: RequestContext, TS: TransportSpec> {
     root: Arc<RootNode<C, TS>>,
 }
@@ -152,12 +204,29 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> UrlRoot<C, TS> {
         }
     }
 
+    /// Sets whether literal segments are matched ignoring ASCII case.
+    ///
+    /// Takes effect for every walk started afterwards, including walks
+    /// through routes registered earlier.
+    pub fn set_case_insensitive(&self, enabled: bool) {
+        self.root.case_insensitive.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if literal segments are matched ignoring ASCII case.
+    pub fn is_case_insensitive(&self) -> bool {
+        self.root.is_case_insensitive()
+    }
+
     /// Walks the URL tree using a segment iterator.
     pub fn walk<'a>(
         &self,
         path: Iter<'a, &str>,
     ) -> MaybeSendBoxFuture<'a, Option<Arc<UrlNode<C, TS>>>> {
-        self.root.clone().walk(path)
+        let root = self.root.clone();
+        Box::pin(async move {
+            let found = root.clone().walk(path).await;
+            root.or_fallback(found)
+        })
     }
 
     /// Walks the URL tree using a segment iterator, rejecting paths deeper than `max_depth`.
@@ -180,10 +249,10 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> UrlRoot<C, TS> {
     /// sequence of segments (empty segments are preserved).
     pub async fn walk_str(&self, path: &str) -> Option<Arc<UrlNode<C, TS>>> {
         if path.is_empty() {
-            return self.root.endpoint.read().clone();
+            return self.root.or_fallback(self.root.endpoint());
         }
         let segments: Vec<&str> = path.split('/').collect();
-        self.root.clone().walk(segments.iter()).await
+        self.walk(segments.iter()).await
     }
 
     /// Resumable cursor over every node matching `path`, in priority
@@ -216,13 +285,13 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> UrlRoot<C, TS> {
         max_depth: u32,
     ) -> Option<Arc<UrlNode<C, TS>>> {
         if path.is_empty() {
-            return self.root.endpoint.read().clone();
+            return self.root.or_fallback(self.root.endpoint());
         }
         let segments: Vec<&str> = path.split('/').collect();
         if segments.len() > max_depth as usize {
             return None;
         }
-        self.root.clone().walk(segments.iter()).await
+        self.walk(segments.iter()).await
     }
 
     /// Registers `path` under the root, returning a [`UrlRegistration`] on success.
@@ -231,14 +300,17 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> UrlRoot<C, TS> {
     ///   endpoint slot.
     /// - `Ok(Node(node))` — a child node was created or rebound.
     /// - `Err(e)` — the path pattern was invalid.
+    ///
+    /// The registered node's params also get the path's [`RoutePattern`].
     pub(crate) fn register(
         &self,
         path: Vec<PathPattern>,
         binding: ExecutableBinding<C>,
-        params: ParamsClone,
+        mut params: ParamsClone,
         names: StepName,
     ) -> Result<UrlRegistration<C, TS>, UrlError> {
         debug_log!("Registering URL: {:?}", path);
+        params.set(RoutePattern::render(&path, &names));
 
         if path.is_empty() {
             // The path was empty — store in the root endpoint slot.
@@ -292,6 +364,31 @@ impl<C: RequestContext + Send + 'static, TS: TransportSpec> UrlRoot<C, TS> {
             .map(UrlRegistration::Node)
     }
 
+    /// Registers the catch-all handler used when no route matches.
+    ///
+    /// Registering again rebinds the existing fallback node in place.
+    pub(crate) fn register_fallback(
+        &self,
+        binding: ExecutableBinding<C>,
+        mut params: ParamsClone,
+    ) -> UrlRegistration<C, TS> {
+        debug_log!("Registering fallback URL");
+        params.set(RoutePattern("**".to_string()));
+        let existing = self.root.fallback.read().clone();
+        let node = match existing {
+            Some(existing) => existing.rebind(binding, params, StepName::default()),
+            None => Arc::new(UrlNode::new(
+                PathPattern::AnyPath,
+                Children::new(),
+                binding,
+                params,
+                StepName::default(),
+            )),
+        };
+        *self.root.fallback.write() = Some(node);
+        UrlRegistration::Fallback(self.root.clone())
+    }
+
     #[av::ver(
         deprecated,
         since = "0.8.0",
@@ -365,7 +462,7 @@ mod tests {
     use crate::{
         connection::test_support::TestTransport,
         executable::{ExecutableBinding, middleware::AsyncFinalHandler},
-        protocol::{Channel, ProtocolRole},
+        protocol::{Channel, Extensions, ProtocolRole},
         url::PathPattern,
     };
 
@@ -382,7 +479,9 @@ mod tests {
     }
 
     #[derive(Default)]
-    struct TestContext;
+    struct TestContext {
+        extensions: Extensions,
+    }
 
     impl RequestContext for TestContext {
         type Request = ();
@@ -396,6 +495,14 @@ mod tests {
             ProtocolRole::Server
         }
 
+        fn extensions(&self) -> &Extensions {
+            &self.extensions
+        }
+
+        fn extensions_mut(&mut self) -> &mut Extensions {
+            &mut self.extensions
+        }
+
         fn inject_request(&mut self, _: Self::Request) {}
         fn into_response(self) -> Self::Response {}
     }
@@ -555,6 +662,95 @@ mod tests {
         assert!(root.walk_str("/users/alice").await.is_some());
     }
 
+    #[tokio::test]
+    async fn registered_nodes_keep_their_route_pattern() {
+        let root = Arc::new(TestUrlRoot::new());
+        for pattern in [
+            "/users/<id>/posts/<int:post>",
+            "/files/<**path:rest>",
+            "/about",
+        ] {
+            root.sub_url(pattern, binding_with_handler(), ParamsClone::default())
+                .unwrap();
+        }
+
+        let route = |path| {
+            let root = root.clone();
+            async move { root.walk_str(path).await.unwrap().route().map(String::from) }
+        };
+        assert_eq!(
+            route("/users/ada/posts/7").await.as_deref(),
+            Some("/users/<id>/posts/<post>")
+        );
+        assert_eq!(route("/files/a/b").await.as_deref(), Some("/files/<rest>"));
+        assert_eq!(route("/about").await.as_deref(), Some("/about"));
+        // Created on the way to a route, never registered itself
+        assert_eq!(route("/users").await, None);
+    }
+
+    #[tokio::test]
+    async fn fallback_runs_only_when_no_route_matches() {
+        let root = Arc::new(TestUrlRoot::new());
+        root.literal_url("/api/x", binding_with_handler(), ParamsClone::default())
+            .unwrap();
+        assert!(root.walk_str("/random").await.is_none());
+
+        let reg = root.register_fallback(binding_with_handler(), ParamsClone::default());
+        assert!(matches!(reg, UrlRegistration::Fallback(_)));
+        let fallback = root.root.fallback().unwrap();
+
+        // The specific route keeps its handler.
+        let api = root.walk_str("/api/x").await.unwrap();
+        assert_eq!(api.path(), &PathPattern::literal_path("x"));
+
+        // Unmatched paths and handler-less intermediate nodes fall back.
+        for path in ["/random", "/api", "/api/x/y", ""] {
+            let node = root.walk_str(path).await.unwrap();
+            assert!(Arc::ptr_eq(&node, &fallback), "{path:?} should fall back");
+        }
+
+        // A `<**path>` route still outranks the fallback.
+        root.sub_url("/static/<**path>", binding_with_handler(), ParamsClone::default())
+            .unwrap();
+        let asset = root.walk_str("/static/app.js").await.unwrap();
+        assert!(!Arc::ptr_eq(&asset, &fallback));
+    }
+
+    #[tokio::test]
+    async fn most_specific_overlapping_route_wins() {
+        let root = Arc::new(TestUrlRoot::new());
+        // Registered least specific first, so order cannot be what decides.
+        for pattern in [
+            "/files/<path:rest>",
+            "/files/<name>",
+            "/files/<int:id>",
+            "/files/special",
+        ] {
+            let (path, names) = parse(pattern).unwrap();
+            root.register(
+                path,
+                binding_with_handler(),
+                ParamsClone::default(),
+                names.into(),
+            )
+            .unwrap();
+        }
+
+        let hit = |path: &'static str| {
+            let root = root.clone();
+            async move { root.walk_str(path).await.unwrap().path().clone() }
+        };
+        assert_eq!(
+            hit("/files/special").await,
+            PathPattern::literal_path("special")
+        );
+        assert!(matches!(hit("/files/42").await, PathPattern::Regex(_)));
+        assert_eq!(hit("/files/readme").await, PathPattern::Any);
+        assert_eq!(hit("/files/a/b").await, PathPattern::AnyPath);
+        // A literal that cannot complete the path gives way to the catch-all.
+        assert_eq!(hit("/files/special/x").await, PathPattern::AnyPath);
+    }
+
     #[tokio::test]
     async fn walk_cursor_yields_priority_ordered_matches() {
         let root = Arc::new(TestUrlRoot::new());
@@ -576,4 +772,42 @@ mod tests {
         assert_eq!(hits.len(), 3);
         assert_eq!(hits[0], PathPattern::literal_path("literal"));
     }
+
+    #[tokio::test]
+    async fn case_insensitive_matching_folds_literal_segments_only() {
+        let root = Arc::new(TestUrlRoot::new());
+        for pattern in ["/users", "/users/<name>", "/users/Admin"] {
+            let (path, names) = parse(pattern).unwrap();
+            root.register(
+                path,
+                binding_with_handler(),
+                ParamsClone::default(),
+                names.into(),
+            )
+            .unwrap();
+        }
+
+        assert!(!root.is_case_insensitive());
+        assert!(root.walk_str("/Users").await.is_none());
+
+        root.set_case_insensitive(true);
+        let users = root.walk_str("/Users").await.unwrap();
+        assert_eq!(users.path(), &PathPattern::literal_path("users"));
+        assert_eq!(
+            root.walk_str("/USERS/admin").await.unwrap().path(),
+            &PathPattern::literal_path("Admin")
+        );
+        // A segment no literal folds to still reaches the capture.
+        assert_eq!(
+            root.walk_str("/Users/Alice").await.unwrap().path(),
+            &PathPattern::Any
+        );
+
+        let segments: Vec<&str> = "/Users".split('/').collect();
+        let mut cursor = root.walk_cursor("/Users");
+        assert!(cursor.find_next(&segments).is_some());
+
+        root.set_case_insensitive(false);
+        assert!(root.walk_str("/Users").await.is_none());
+    }
 }
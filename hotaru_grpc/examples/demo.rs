@@ -74,15 +74,15 @@ fn demo_protocol_detection() {
     let invalid_data = b"invalid protocol data";
 
     println!(
-        "HTTP/2 preface detection: {}",
+        "HTTP/2 preface detection: {:?}",
         GrpcProtocol::detect(http2_preface)
     );
     println!(
-        "HTTP/1.1 request detection: {}",
+        "HTTP/1.1 request detection: {:?}",
         GrpcProtocol::detect(http1_request)
     );
     println!(
-        "Invalid data detection: {}",
+        "Invalid data detection: {:?}",
         GrpcProtocol::detect(invalid_data)
     );
 
@@ -57,33 +57,36 @@ endpoint! {
     }
 }
 
-// Server streaming gRPC endpoint
+// Server streaming gRPC endpoint. `raw` hands the context to the handler
+// and takes it back as is, so the response stream set up here is not
+// replaced by the wrapper.
 endpoint! {
     APP.url("/helloworld.Greeter/SayHelloStream"),
+    raw,
 
     pub say_hello_stream<GrpcProtocol> {
-        // Decode the request
-        let request: HelloRequest = req.decode_request()
-            .map_err(|e| {
-                req.set_status_code(GrpcCode::InvalidArgument, &format!("Decode error: {}", e));
-                e
-            })?;
-
-        println!("Received streaming gRPC request: name = {}", request.name);
-
-        // For streaming, we would need to implement additional streaming support
-        // For now, just return a single response
-        let response = HelloReply {
-            message: format!("Hello stream, {}!", request.name),
+        let request: HelloRequest = match req.decode_request() {
+            Ok(request) => request,
+            Err(status) => {
+                req.set_status(status);
+                return req;
+            }
         };
 
-        req.encode_response(response)
-            .map_err(|e| {
-                req.set_status_code(GrpcCode::Internal, &format!("Encode error: {}", e));
-                e
-            })?;
+        println!("Received streaming gRPC request: name = {}", request.name);
 
-        req.set_status_code(GrpcCode::Ok, "");
+        let mut tx = req.server_stream(hotaru_grpc::DEFAULT_MAX_SEND_MESSAGE_SIZE);
+        tokio::spawn(async move {
+            for greeting in ["Hello", "Hi", "Hey"] {
+                let reply = HelloReply {
+                    message: format!("{}, {}!", greeting, request.name),
+                };
+                if tx.send(&reply).await.is_err() {
+                    return;
+                }
+            }
+            tx.finish(GrpcStatus::new(GrpcCode::Ok, "")).await;
+        });
 
         req
     }
@@ -51,7 +51,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Test protocol detection
     let http2_preface = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
     println!(
-        "HTTP/2 detection test: {}",
+        "HTTP/2 detection test: {:?}",
         GrpcProtocol::detect(http2_preface)
     );
 
@@ -0,0 +1,115 @@
+//! Telling gRPC calls apart from other traffic on a gRPC route
+//!
+//! gRPC only runs over HTTP/2 with an `application/grpc*` content type.
+//! Anything else reaching a gRPC route gets a plain HTTP error instead of a
+//! framing error the client cannot make sense of:
+//!
+//! - HTTP/2 with a gRPC content type is dispatched
+//! - HTTP/2 with any other content type gets `415 Unsupported Media Type`
+//! - HTTP/1.x gets `505 HTTP Version Not Supported`, except gRPC-Web text
+//!   calls, which browsers send over any version and are dispatched
+//!
+//! A gRPC-only listener never sees HTTP/1.x requests through
+//! [`GrpcProtocol`](crate::GrpcProtocol), which only detects the HTTP/2
+//! preface; register [`GrpcHttp1Rejection`](crate::GrpcHttp1Rejection) after
+//! it to answer those connections the same way.
+
+use bytes::Bytes;
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{HeaderValue, Request, Response, StatusCode, Version};
+
+use crate::web::is_grpc_web_text;
+
+/// Content type prefix every gRPC request carries
+pub const GRPC_CONTENT_TYPE: &str = "application/grpc";
+
+/// How a request on a gRPC route should be handled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admission {
+    /// HTTP/2 with a gRPC content type
+    Dispatch,
+    /// HTTP/2 whose content type is missing or not gRPC
+    UnsupportedMediaType(Option<String>),
+    /// Any HTTP version other than HTTP/2
+    Http2Required(Version),
+}
+
+impl Admission {
+    /// Classifies `request` by HTTP version and content type
+    pub fn of<B>(request: &Request<B>) -> Self {
+        let content_type = request
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok());
+        if content_type.is_some_and(is_grpc_web_text) {
+            return Admission::Dispatch;
+        }
+        if request.version() != Version::HTTP_2 {
+            return Admission::Http2Required(request.version());
+        }
+        match content_type {
+            Some(ct) if ct.starts_with(GRPC_CONTENT_TYPE) => Admission::Dispatch,
+            other => Admission::UnsupportedMediaType(other.map(str::to_string)),
+        }
+    }
+
+    /// Whether the request may be dispatched to the service
+    pub fn is_dispatch(&self) -> bool {
+        matches!(self, Admission::Dispatch)
+    }
+
+    /// HTTP error answering a request that is not a gRPC call
+    ///
+    /// Returns `None` for [`Admission::Dispatch`].
+    pub fn rejection(&self) -> Option<Response<Bytes>> {
+        let (status, message) = match self {
+            Admission::Dispatch => return None,
+            Admission::UnsupportedMediaType(Some(content_type)) => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                format!("gRPC requires content-type {GRPC_CONTENT_TYPE}, got {content_type}\n"),
+            ),
+            Admission::UnsupportedMediaType(None) => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                format!("gRPC requires content-type {GRPC_CONTENT_TYPE}\n"),
+            ),
+            Admission::Http2Required(version) => (
+                StatusCode::HTTP_VERSION_NOT_SUPPORTED,
+                format!("gRPC requires HTTP/2, got {version:?}\n"),
+            ),
+        };
+        Some(text_response(status, message))
+    }
+}
+
+fn text_response(status: StatusCode, message: String) -> Response<Bytes> {
+    let length = message.len();
+    let mut response = Response::new(Bytes::from(message));
+    *response.status_mut() = status;
+    let headers = response.headers_mut();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    headers.insert(CONTENT_LENGTH, HeaderValue::from(length));
+    response
+}
+
+/// Serializes `response` as an HTTP/1.1 message that closes the connection
+pub(crate) fn http1_bytes(response: &Response<Bytes>) -> Vec<u8> {
+    let status = response.status();
+    let mut out = format!(
+        "HTTP/1.1 {} {}\r\n",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("")
+    )
+    .into_bytes();
+    for (name, value) in response.headers() {
+        out.extend_from_slice(name.as_str().as_bytes());
+        out.extend_from_slice(b": ");
+        out.extend_from_slice(value.as_bytes());
+        out.extend_from_slice(b"\r\n");
+    }
+    out.extend_from_slice(b"connection: close\r\n\r\n");
+    out.extend_from_slice(response.body());
+    out
+}
@@ -0,0 +1,366 @@
+//! Channel-level load balancing for gRPC client calls
+//!
+//! A [`ConnectionTarget`] holds one connection per upstream address (for
+//! example a `tonic::transport::Channel` each) and picks one of them per RPC
+//! according to its [`LoadBalancer`] policy. Endpoints failing with
+//! `Unavailable` too many times in a row are ejected for a cool-down period
+//! and skipped until it ends. If every endpoint is ejected, all of them are
+//! tried again rather than failing the call outright.
+//!
+//! Given a [`RetryPolicy`] with [`ConnectionTarget::with_retry_policy`],
+//! [`ConnectionTarget::call_with_retry`] retries the call under it, and each
+//! retry picks its endpoint afresh:
+//!
+//! ```rust,ignore
+//! let target = target.with_retry_policy(RetryPolicy::new());
+//! target
+//!     .call_with_retry(deadline, |channel, attempt| async move { ... })
+//!     .await
+//! ```
+//!
+//! A target built with [`ConnectionTarget::lazy`] connects each endpoint on
+//! its first call instead of up front. Connecting is bounded by its own
+//! [connect timeout](ConnectionTarget::with_connect_timeout), apart from the
+//! RPC deadline. A connection that fails or times out fails the call with
+//! `Unavailable` and is tried again on the next call:
+//!
+//! ```rust,ignore
+//! let target = ConnectionTarget::lazy("10.0.0.1:50051", |address| async move {
+//!     Endpoint::from_shared(format!("http://{address}"))?.connect().await
+//! })
+//! .with_connect_timeout(Duration::from_secs(2));
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::OnceCell;
+use tokio::time::Instant;
+use tonic::{Code, Status};
+
+use crate::retry::{CallAttempt, RetryPolicy};
+
+/// Connect timeout of lazy targets unless set otherwise
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Policy choosing the endpoint of each RPC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadBalancer {
+    /// Always use the first healthy endpoint, in configuration order
+    #[default]
+    PickFirst,
+    /// Rotate through the healthy endpoints, one per call
+    RoundRobin,
+}
+
+/// When failing endpoints are taken out of rotation
+#[derive(Debug, Clone)]
+pub struct EjectionPolicy {
+    /// Consecutive failures that eject an endpoint
+    pub max_failures: u32,
+    /// How long an ejected endpoint is skipped
+    pub cooldown: Duration,
+    /// Codes counted as endpoint failures
+    pub failure_codes: Vec<Code>,
+}
+
+impl Default for EjectionPolicy {
+    fn default() -> Self {
+        Self {
+            max_failures: 3,
+            cooldown: Duration::from_secs(10),
+            failure_codes: vec![Code::Unavailable],
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    ejected_until: Option<Instant>,
+}
+
+#[derive(Debug)]
+struct Endpoint<C> {
+    address: String,
+    connection: OnceCell<C>,
+    health: Mutex<Health>,
+}
+
+type ConnectFuture<C> = Pin<Box<dyn Future<Output = Result<C, String>> + Send>>;
+type ConnectFn<C> = dyn Fn(&str) -> ConnectFuture<C> + Send + Sync;
+
+/// Makes the connection of a lazy endpoint from its address
+struct Connector<C>(Arc<ConnectFn<C>>);
+
+impl<C> fmt::Debug for Connector<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Connector")
+    }
+}
+
+impl<C> Endpoint<C> {
+    fn is_ejected(&self, now: Instant) -> bool {
+        let health = self.health.lock().unwrap();
+        health.ejected_until.is_some_and(|until| now < until)
+    }
+}
+
+/// Set of upstream endpoints a client spreads its calls over
+#[derive(Debug)]
+pub struct ConnectionTarget<C> {
+    endpoints: Vec<Endpoint<C>>,
+    balancer: LoadBalancer,
+    ejection: EjectionPolicy,
+    next: AtomicUsize,
+    connector: Option<Connector<C>>,
+    connect_timeout: Duration,
+    retry: Option<RetryPolicy>,
+}
+
+impl<C: Clone> ConnectionTarget<C> {
+    /// Creates a target with a single endpoint
+    pub fn new(address: impl Into<String>, connection: C) -> Self {
+        Self {
+            endpoints: Vec::new(),
+            balancer: LoadBalancer::default(),
+            ejection: EjectionPolicy::default(),
+            next: AtomicUsize::new(0),
+            connector: None,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            retry: None,
+        }
+        .with_endpoint(address, connection)
+    }
+
+    /// Creates a target with a single endpoint connected on its first call
+    ///
+    /// `connect` makes the connection from the endpoint's address. It is
+    /// also used for endpoints added with
+    /// [`with_lazy_endpoint`](Self::with_lazy_endpoint).
+    pub fn lazy<F, Fut, E>(address: impl Into<String>, connect: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<C, E>> + Send + 'static,
+        E: fmt::Display,
+        C: 'static,
+    {
+        let connect = move |address: &str| -> ConnectFuture<C> {
+            let connecting = connect(address.to_string());
+            Box::pin(async move { connecting.await.map_err(|err| err.to_string()) })
+        };
+        Self {
+            endpoints: Vec::new(),
+            balancer: LoadBalancer::default(),
+            ejection: EjectionPolicy::default(),
+            next: AtomicUsize::new(0),
+            connector: Some(Connector(Arc::new(connect))),
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            retry: None,
+        }
+        .with_lazy_endpoint(address)
+    }
+
+    /// Adds another upstream endpoint
+    pub fn with_endpoint(mut self, address: impl Into<String>, connection: C) -> Self {
+        self.endpoints.push(Endpoint {
+            address: address.into(),
+            connection: OnceCell::new_with(Some(connection)),
+            health: Mutex::new(Health::default()),
+        });
+        self
+    }
+
+    /// Adds another upstream endpoint, connected on its first call by the
+    /// connector given to [`lazy`](Self::lazy)
+    pub fn with_lazy_endpoint(mut self, address: impl Into<String>) -> Self {
+        self.endpoints.push(Endpoint {
+            address: address.into(),
+            connection: OnceCell::new(),
+            health: Mutex::new(Health::default()),
+        });
+        self
+    }
+
+    /// Sets how long connecting a lazy endpoint may take
+    ///
+    /// This bounds the connection alone; the RPC deadline still bounds the
+    /// whole call, connecting included.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Sets the policy choosing an endpoint per call
+    pub fn with_load_balancer(mut self, balancer: LoadBalancer) -> Self {
+        self.balancer = balancer;
+        self
+    }
+
+    /// Sets when failing endpoints are ejected
+    pub fn with_ejection(mut self, ejection: EjectionPolicy) -> Self {
+        self.ejection = ejection;
+        self
+    }
+
+    /// Sets the policy [`call_with_retry`](Self::call_with_retry) retries
+    /// calls under
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Configured load-balancing policy
+    pub fn load_balancer(&self) -> LoadBalancer {
+        self.balancer
+    }
+
+    /// Addresses of all endpoints, in configuration order
+    pub fn addresses(&self) -> impl Iterator<Item = &str> {
+        self.endpoints.iter().map(|e| e.address.as_str())
+    }
+
+    /// Configured retry policy, if any
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry.as_ref()
+    }
+
+    /// Configured connect timeout of lazy endpoints
+    pub fn connect_timeout(&self) -> Duration {
+        self.connect_timeout
+    }
+
+    /// Addresses of the endpoints with a connection, made or given
+    pub fn connected_addresses(&self) -> Vec<&str> {
+        self.endpoints
+            .iter()
+            .filter(|e| e.connection.initialized())
+            .map(|e| e.address.as_str())
+            .collect()
+    }
+
+    /// Addresses of the endpoints currently in rotation
+    pub fn healthy_addresses(&self) -> Vec<&str> {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .filter(|e| !e.is_ejected(now))
+            .map(|e| e.address.as_str())
+            .collect()
+    }
+
+    fn pick(&self) -> usize {
+        let now = Instant::now();
+        let mut healthy: Vec<usize> = (0..self.endpoints.len())
+            .filter(|&i| !self.endpoints[i].is_ejected(now))
+            .collect();
+        if healthy.is_empty() {
+            healthy = (0..self.endpoints.len()).collect();
+        }
+        match self.balancer {
+            LoadBalancer::PickFirst => healthy[0],
+            LoadBalancer::RoundRobin => {
+                healthy[self.next.fetch_add(1, Ordering::Relaxed) % healthy.len()]
+            }
+        }
+    }
+
+    fn record(&self, index: usize, result: Result<(), Code>) {
+        let mut health = self.endpoints[index].health.lock().unwrap();
+        match result {
+            Err(code) if self.ejection.failure_codes.contains(&code) => {
+                health.consecutive_failures += 1;
+                if health.consecutive_failures >= self.ejection.max_failures {
+                    health.consecutive_failures = 0;
+                    health.ejected_until = Some(Instant::now() + self.ejection.cooldown);
+                }
+            }
+            // Application-level errors say nothing about the endpoint.
+            Err(_) => {}
+            Ok(()) => {
+                health.consecutive_failures = 0;
+                health.ejected_until = None;
+            }
+        }
+    }
+
+    /// Connection of endpoint `index`, connecting it first if it is lazy
+    /// and not connected yet
+    async fn connection(&self, index: usize) -> Result<C, Status> {
+        let endpoint = &self.endpoints[index];
+        let connect = || async {
+            let Some(connector) = &self.connector else {
+                return Err(Status::unavailable(format!(
+                    "no connection to {} and no connector to make one",
+                    endpoint.address
+                )));
+            };
+            let connecting = (connector.0)(&endpoint.address);
+            match tokio::time::timeout(self.connect_timeout, connecting).await {
+                Ok(Ok(connection)) => Ok(connection),
+                Ok(Err(err)) => Err(Status::unavailable(format!(
+                    "failed to connect to {}: {err}",
+                    endpoint.address
+                ))),
+                Err(_) => Err(Status::unavailable(format!(
+                    "connecting to {} timed out after {:?}",
+                    endpoint.address, self.connect_timeout
+                ))),
+            }
+        };
+        endpoint.connection.get_or_try_init(connect).await.cloned()
+    }
+
+    /// Runs one RPC on the endpoint chosen by the load balancer.
+    ///
+    /// `rpc` receives a clone of that endpoint's connection, made first if
+    /// the endpoint is lazy. The outcome, a failed connection included, is
+    /// recorded against the endpoint for ejection.
+    pub async fn call<T, F, Fut>(&self, rpc: F) -> Result<T, Status>
+    where
+        F: FnOnce(C) -> Fut,
+        Fut: Future<Output = Result<T, Status>>,
+    {
+        let index = self.pick();
+        let result = match self.connection(index).await {
+            Ok(connection) => rpc(connection).await,
+            Err(status) => Err(status),
+        };
+        self.record(index, result.as_ref().map(|_| ()).map_err(Status::code));
+        result
+    }
+    /// Runs a unary or server-streaming RPC under the target's retry policy.
+    ///
+    /// Every attempt goes through [`call`](Self::call), so a retry may go to
+    /// another endpoint. `deadline` bounds the whole call, retries included;
+    /// `rpc` should forward [`CallAttempt::remaining`] as its
+    /// `grpc-timeout`. Without a retry policy the RPC is attempted once.
+    pub async fn call_with_retry<T, F, Fut>(
+        &self,
+        deadline: Option<Duration>,
+        rpc: F,
+    ) -> Result<T, Status>
+    where
+        F: Fn(C, CallAttempt) -> Fut,
+        Fut: Future<Output = Result<T, Status>>,
+    {
+        let once;
+        let policy = match &self.retry {
+            Some(policy) => policy,
+            None => {
+                once = RetryPolicy::new().with_max_attempts(1);
+                &once
+            }
+        };
+        let rpc = &rpc;
+        policy
+            .call(deadline, |attempt| {
+                self.call(move |connection| rpc(connection, attempt))
+            })
+            .await
+    }
+}
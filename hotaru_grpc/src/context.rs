@@ -2,13 +2,39 @@
 //!
 //! Provides GrpcContext that wraps tonic functionality for use with Hotaru endpoints
 
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
 use bytes::Bytes;
-use http::HeaderMap;
+use http::{HeaderMap, HeaderValue};
 use prost::Message;
+use tokio::time::Instant;
 use tonic::{metadata::MetadataMap, Code, Status};
 
+use h2per::context::{box_body, header_multimap, Body};
 use h2per::HyperContext;
 use hotaru_core::connection::{ProtocolRole, RequestContext};
+use hotaru_core::protocol::{Extensions, HeaderMultiMap};
+use hotaru_tls::PeerIdentity;
+
+use crate::metrics::{MessageSizeInterceptor, MessageSizeRecorder, SizeRecorder};
+use crate::streaming::{
+    message_too_large, server_stream, RequestStream, ResponseStream, StreamInterceptor,
+    StreamSender, DEFAULT_MAX_SEND_MESSAGE_SIZE,
+};
+use crate::timeout::{decode_grpc_timeout, encode_grpc_timeout, split_budget, with_timeout};
+use crate::tonic_service::from_http02_headers;
+use crate::validate::MessageValidators;
+use crate::web::{
+    decode_web_text, encode_web_text, is_grpc_web_text, GRPC_WEB_TEXT_PROTO_CONTENT_TYPE,
+};
+
+/// Tracing metadata an inbound call forwards to the outbound calls made
+/// while handling it: W3C Trace Context (`traceparent`, `tracestate`) and
+/// OpenCensus binary context (`grpc-trace-bin`)
+pub const TRACE_HEADERS: [&str; 3] = ["traceparent", "tracestate", "grpc-trace-bin"];
 
 /// gRPC-specific context for use with Hotaru endpoints
 pub struct GrpcContext {
@@ -24,14 +50,62 @@ pub struct GrpcContext {
     /// gRPC metadata (headers)
     pub metadata: MetadataMap,
 
+    /// Request headers in wire order, including reserved ones
+    raw_headers: HeaderMultiMap,
+
     /// gRPC status (for responses)
     pub status: Status,
 
     /// Request body bytes (protobuf message)
     request_body: Option<Bytes>,
 
+    /// Request message with the gRPC frame header stripped
+    request_payload: Option<Bytes>,
+
     /// Response body bytes (protobuf message)  
     response_body: Option<Bytes>,
+
+    /// Whether initial metadata was already given to `send_headers`
+    headers_sent: bool,
+
+    /// Set once the response stream has queued its first message
+    stream_started: Option<Arc<AtomicBool>>,
+
+    /// Message size metrics for this call, if a recorder is attached
+    size_interceptor: Option<Arc<MessageSizeInterceptor>>,
+
+    /// Per-message hook for the streams of this call, if one is attached
+    stream_interceptor: Option<Arc<dyn StreamInterceptor>>,
+
+    /// Idle timeout for the server stream of this call, if set
+    stream_idle_timeout: Option<Duration>,
+
+    /// Checks run on the decoded request message, if attached
+    validators: Option<Arc<MessageValidators>>,
+
+    /// Call timeout, sent or received as `grpc-timeout`
+    timeout: Option<Duration>,
+
+    /// The request's [`TRACE_HEADERS`]
+    trace_headers: HeaderMap,
+
+    /// When `timeout` runs out, counted from when it was received or set
+    deadline: Option<Instant>,
+
+    /// Client-configured limit on an outgoing request message
+    max_send_message_size: usize,
+
+    /// Server-advertised limit on the request message, if known
+    server_max_receive_message_size: Option<usize>,
+
+    /// Identity from the verified client certificate, on mTLS connections
+    peer_identity: Option<PeerIdentity>,
+
+    /// Whether the call is gRPC-Web text, with base64 bodies both ways
+    web_text: bool,
+
+    /// Typed values attached by middleware
+    extensions: Extensions,
 }
 
 impl GrpcContext {
@@ -52,42 +126,337 @@ impl GrpcContext {
         if !content_type.starts_with("application/grpc") {
             return Err(Status::new(Code::InvalidArgument, "Not a gRPC request"));
         }
+        let web_text = is_grpc_web_text(content_type);
 
         // Extract metadata from headers
         let metadata = Self::extract_metadata(inner.request().headers());
+        let raw_headers = header_multimap(inner.request().headers());
 
         // Get request body from HyperRequest
-        let request_body = inner
+        let mut request_body = inner
             .request()
             .body_bytes
             .as_ref()
             .map(|bytes| Bytes::from(bytes.clone()));
+        if let (true, Some(text)) = (web_text, &request_body) {
+            request_body = Some(decode_web_text(text)?);
+        }
+        let request_payload = request_body.as_ref().and_then(Self::deframe);
+        let timeout = inner
+            .request()
+            .headers()
+            .get("grpc-timeout")
+            .and_then(|v| v.to_str().ok())
+            .and_then(decode_grpc_timeout);
+        let peer_identity = inner
+            .request()
+            .as_inner()
+            .extensions()
+            .get::<PeerIdentity>()
+            .cloned();
+        let size_recorder = inner
+            .request()
+            .as_inner()
+            .extensions()
+            .get::<SizeRecorder>()
+            .cloned();
+        let mut trace_headers = HeaderMap::new();
+        for name in TRACE_HEADERS {
+            if let Some(value) = inner.request().headers().get(name) {
+                trace_headers.insert(name, value.clone());
+            }
+        }
 
-        Ok(Self {
+        let context = Self {
             inner,
             method,
             service,
             metadata,
+            raw_headers,
             status: Status::ok(""),
             request_body,
+            request_payload,
             response_body: None,
+            headers_sent: false,
+            stream_started: None,
+            size_interceptor: None,
+            stream_interceptor: None,
+            stream_idle_timeout: None,
+            validators: None,
+            timeout,
+            trace_headers,
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+            max_send_message_size: DEFAULT_MAX_SEND_MESSAGE_SIZE,
+            server_max_receive_message_size: None,
+            peer_identity,
+            web_text,
+            extensions: Extensions::new(),
+        };
+        Ok(match size_recorder {
+            Some(SizeRecorder(recorder)) => context.with_size_recorder(recorder),
+            None => context,
         })
     }
 
+    /// Attaches a message size recorder to this call
+    ///
+    /// Request and response sizes are accumulated across all messages and
+    /// reported under the method path when the response is finalized.
+    /// Calls served by a `GrpcProtocol` with a recorder of its own
+    /// ([`GrpcProtocol::with_size_recorder`](crate::GrpcProtocol::with_size_recorder))
+    /// already report to it; this replaces it for the call.
+    pub fn with_size_recorder(mut self, recorder: Arc<dyn MessageSizeRecorder>) -> Self {
+        self.size_interceptor = Some(Arc::new(MessageSizeInterceptor::new(
+            recorder,
+            self.method_path(),
+        )));
+        self
+    }
+
+    /// Attaches a per-message interceptor to this call's streams
+    ///
+    /// It sees each message of [`request_stream`](Self::request_stream) and
+    /// of the sender returned by [`server_stream`](Self::server_stream).
+    pub fn with_stream_interceptor(mut self, interceptor: Arc<dyn StreamInterceptor>) -> Self {
+        self.stream_interceptor = Some(interceptor);
+        self
+    }
+
+    /// Resets this call's server stream with `Cancelled` once the client
+    /// takes no message for `timeout`
+    ///
+    /// Applies to the sender returned by
+    /// [`server_stream`](Self::server_stream); see
+    /// [`StreamSender::with_idle_timeout`].
+    pub fn with_stream_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.stream_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Attaches request message validation to this call
+    ///
+    /// [`decode_request`](Self::decode_request) runs the check registered
+    /// for the decoded type and fails with `INVALID_ARGUMENT` if it does
+    /// not pass.
+    pub fn with_validators(mut self, validators: Arc<MessageValidators>) -> Self {
+        self.validators = Some(validators);
+        self
+    }
+
+    /// Sets the client-side timeout of this call
+    ///
+    /// Also sets (or removes) the `grpc-timeout` request header so the
+    /// server stops working on the call once it expires. Enforce it locally
+    /// with [`run_with_timeout`](Self::run_with_timeout).
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        let headers = self.inner.request.headers_mut();
+        match timeout {
+            Some(timeout) => {
+                let value = HeaderValue::from_str(&encode_grpc_timeout(timeout))
+                    .expect("grpc-timeout is ASCII");
+                headers.insert("grpc-timeout", value);
+            }
+            None => {
+                headers.remove("grpc-timeout");
+            }
+        }
+        self.timeout = timeout;
+        self.deadline = timeout.map(|timeout| Instant::now() + timeout);
+    }
+
+    /// Returns the call timeout
+    ///
+    /// On the server this is the client's `grpc-timeout`, if it sent one.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Time left before the call's deadline, zero once it has passed
+    ///
+    /// `None` if the call has no timeout.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Timeout for each of `calls` outbound calls made under this call's
+    /// deadline
+    ///
+    /// The time left is split evenly, so the calls finish before this one
+    /// runs out even if they end up running one after another. Pass the
+    /// result to each outbound context's [`set_timeout`](Self::set_timeout).
+    /// `None` if this call has no deadline.
+    pub fn deadline_budget(&self, calls: usize) -> Option<Duration> {
+        split_budget(self.remaining(), calls)
+    }
+
+    /// Runs an outbound call bounded by this context's timeout
+    ///
+    /// Fails with `DeadlineExceeded` when the timeout fires; `call` is
+    /// dropped, which resets its HTTP/2 stream.
+    pub async fn run_with_timeout<T, Fut>(&self, call: Fut) -> Result<T, Status>
+    where
+        Fut: Future<Output = Result<T, Status>>,
+    {
+        with_timeout(self.timeout, call).await
+    }
+
+    /// Tracing metadata of this call, the [`TRACE_HEADERS`] it carries
+    pub fn trace_headers(&self) -> &HeaderMap {
+        &self.trace_headers
+    }
+
+    /// The call's W3C `traceparent`, if it carries one
+    pub fn traceparent(&self) -> Option<&str> {
+        self.trace_headers
+            .get("traceparent")
+            .and_then(|value| value.to_str().ok())
+    }
+
+    /// Creates the context of an outbound call made while handling this one
+    ///
+    /// This call's [`trace_headers`](Self::trace_headers) are copied onto
+    /// `request`, so the upstream call joins the same trace. A trace header
+    /// `request` already sets is kept as is, so a handler that starts its
+    /// own span can pass its child `traceparent` instead. A context built
+    /// directly with [`from_hyper_context`](Self::from_hyper_context)
+    /// forwards nothing.
+    pub fn outbound_call(&self, mut request: http::Request<Body>) -> Result<GrpcContext, Status> {
+        let headers = request.headers_mut();
+        for (name, value) in &self.trace_headers {
+            if !headers.contains_key(name) {
+                headers.insert(name.clone(), value.clone());
+            }
+        }
+        Self::from_hyper_context(HyperContext::new_client(request))
+    }
+
+    /// Sets the client's limit on a single request message, before framing
+    pub fn set_max_send_message_size(&mut self, max_send_message_size: usize) {
+        self.max_send_message_size = max_send_message_size;
+    }
+
+    /// Records the largest request message the server accepts
+    ///
+    /// Known from the service config (`maxRequestMessageBytes`) or an
+    /// earlier `ResourceExhausted` reply. `None` forgets it.
+    pub fn set_server_max_receive_message_size(&mut self, max: Option<usize>) {
+        self.server_max_receive_message_size = max;
+    }
+
+    /// Limit request messages are checked against before sending
+    ///
+    /// The client's own limit, lowered to the server's if that is known
+    /// and smaller.
+    pub fn max_send_message_size(&self) -> usize {
+        match self.server_max_receive_message_size {
+            Some(server_max) => self.max_send_message_size.min(server_max),
+            None => self.max_send_message_size,
+        }
+    }
+
+    /// Identity of the client, read from its verified TLS certificate
+    ///
+    /// `None` unless the call came over a TLS listener that asked for a
+    /// client certificate and the client presented one.
+    pub fn peer_identity(&self) -> Option<&PeerIdentity> {
+        self.peer_identity.as_ref()
+    }
+
+    /// Whether this is a gRPC-Web text call (`application/grpc-web-text`)
+    ///
+    /// Its request body has already been base64-decoded, and
+    /// [`finalize_response`](Self::finalize_response) encodes the response
+    /// and trailer frame back. Streamed responses are not encoded.
+    pub fn is_web_text(&self) -> bool {
+        self.web_text
+    }
+
+    /// Fully qualified service name, e.g. "helloworld.Greeter"
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    /// Method name, e.g. "SayHello"
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Returns the full method path, e.g. "/helloworld.Greeter/SayHello"
+    pub fn method_path(&self) -> String {
+        format!("/{}/{}", self.service, self.method)
+    }
+
     /// Parses gRPC path into service and method
     /// Path format: "/package.Service/Method"
-    fn parse_grpc_path(path: &str) -> Result<(String, String), Status> {
-        let path = path.trim_start_matches('/');
-        let parts: Vec<&str> = path.split('/').collect();
+    pub(crate) fn parse_grpc_path(path: &str) -> Result<(String, String), Status> {
+        match path.trim_start_matches('/').split_once('/') {
+            Some((service, method))
+                if !service.is_empty() && !method.is_empty() && !method.contains('/') =>
+            {
+                Ok((service.to_string(), method.to_string()))
+            }
+            // Not a method this server could implement
+            _ => Err(Status::new(
+                Code::Unimplemented,
+                format!("Malformed gRPC method path: {}", path),
+            )),
+        }
+    }
 
-        if parts.len() != 2 {
-            return Err(Status::new(
-                Code::InvalidArgument,
-                "Invalid gRPC path format",
-            ));
+    /// Removes a mount prefix from the request path, so a call to
+    /// `/v2/package.Service/Method` behind the prefix `/v2` is parsed as
+    /// `/package.Service/Method`
+    ///
+    /// The prefix is matched on whole segments, with or without its
+    /// leading and trailing slashes; the query is kept. Returns whether the
+    /// path had the prefix. Paths without it are left as they are.
+    pub fn strip_path_prefix<B>(request: &mut http::Request<B>, prefix: &str) -> bool {
+        let prefix = prefix.trim_matches('/');
+        if prefix.is_empty() {
+            return false;
+        }
+        let uri = request.uri();
+        let rest = match uri
+            .path()
+            .strip_prefix('/')
+            .and_then(|path| path.strip_prefix(prefix))
+        {
+            Some(rest) if rest.starts_with('/') => rest,
+            _ => return false,
+        };
+        let path_and_query = match uri.query() {
+            Some(query) => format!("{}?{}", rest, query),
+            None => rest.to_string(),
+        };
+        let mut parts = uri.clone().into_parts();
+        parts.path_and_query = path_and_query.parse().ok();
+        match http::Uri::from_parts(parts) {
+            Ok(uri) => {
+                *request.uri_mut() = uri;
+                true
+            }
+            Err(_) => false,
         }
+    }
 
-        Ok((parts[0].to_string(), parts[1].to_string()))
+    /// Strips the 5-byte gRPC frame header from an uncompressed message
+    pub(crate) fn deframe(body: &Bytes) -> Option<Bytes> {
+        if body.len() < 5 || body[0] != 0 {
+            return None;
+        }
+        let length = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+        let end = 5usize.checked_add(length)?;
+        (end <= body.len()).then(|| body.slice(5..end))
+    }
+
+    /// Prefixes a message with the gRPC frame header (uncompressed)
+    pub(crate) fn frame(message: &[u8]) -> Bytes {
+        let mut framed = Vec::with_capacity(5 + message.len());
+        framed.push(0); // No compression
+        framed.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        framed.extend_from_slice(message);
+        Bytes::from(framed)
     }
 
     /// Extracts gRPC metadata from HTTP headers
@@ -115,9 +484,12 @@ impl GrpcContext {
     }
 
     /// Decodes the request body as a protobuf message
+    ///
+    /// With [validators](Self::with_validators) attached, the message must
+    /// also pass the check registered for `T`.
     pub fn decode_request<T>(&self) -> Result<T, Status>
     where
-        T: Message + Default,
+        T: Message + Default + 'static,
     {
         let body_bytes = self
             .request_body
@@ -131,8 +503,84 @@ impl GrpcContext {
             return Err(Status::new(Code::InvalidArgument, "Invalid gRPC frame"));
         };
 
-        T::decode(message_bytes)
-            .map_err(|e| Status::new(Code::InvalidArgument, format!("Decode error: {}", e)))
+        let message = T::decode(message_bytes)
+            .map_err(|e| Status::new(Code::InvalidArgument, format!("Decode error: {}", e)))?;
+
+        if let Some(interceptor) = &self.size_interceptor {
+            interceptor.on_request_message(message_bytes.len());
+        }
+        if let Some(validators) = &self.validators {
+            validators.validate(&message)?;
+        }
+
+        Ok(message)
+    }
+
+    /// Decodes the request body as a stream of protobuf messages
+    ///
+    /// For client-streaming calls: yields one message per gRPC frame and
+    /// ends after the last complete one. A body without messages is an empty
+    /// stream. See [`RequestStream`] for how malformed frames are reported.
+    pub fn request_stream<T>(&self) -> RequestStream<T>
+    where
+        T: Message + Default,
+    {
+        RequestStream::new(
+            self.request_body.clone().unwrap_or_default(),
+            self.size_interceptor.clone(),
+            self.stream_interceptor.clone(),
+        )
+    }
+
+    /// Starts a server stream as the response of this call
+    ///
+    /// Like [`server_stream`], with the returned sender already passing its
+    /// messages through this call's stream interceptor and using its
+    /// stream idle timeout.
+    pub fn server_stream(&mut self, max_send_message_size: usize) -> StreamSender {
+        let (mut sender, body) = server_stream(max_send_message_size);
+        if let Some(interceptor) = &self.stream_interceptor {
+            sender = sender.with_interceptor(interceptor.clone());
+        }
+        if let Some(timeout) = self.stream_idle_timeout {
+            sender = sender.with_idle_timeout(timeout);
+        }
+        self.set_response_stream(body);
+        sender
+    }
+
+    /// Encodes a request message as protobuf and sets it as the request body
+    ///
+    /// For client calls. See [`set_request_bytes`](Self::set_request_bytes)
+    /// for the size check.
+    pub fn encode_request<T>(&mut self, message: T) -> Result<(), Status>
+    where
+        T: Message,
+    {
+        self.set_request_bytes(Bytes::from(message.encode_to_vec()))
+    }
+
+    /// Sets an already serialized request message as the request body
+    ///
+    /// A message over [`max_send_message_size`](Self::max_send_message_size)
+    /// fails here with `ResourceExhausted` and leaves the request untouched,
+    /// so the call is never sent rather than reset by the server. The gRPC
+    /// frame header is added here; `message` must not carry one.
+    pub fn set_request_bytes(&mut self, message: Bytes) -> Result<(), Status> {
+        let max_send_message_size = self.max_send_message_size();
+        if message.len() > max_send_message_size {
+            return Err(message_too_large(message.len(), max_send_message_size));
+        }
+
+        if let Some(interceptor) = &self.size_interceptor {
+            interceptor.on_request_message(message.len());
+        }
+
+        let framed = Self::frame(&message);
+        self.inner.set_body_bytes(framed.to_vec());
+        self.request_payload = Some(message);
+        self.request_body = Some(framed);
+        Ok(())
     }
 
     /// Encodes a response message as protobuf and sets it in the context
@@ -145,16 +593,79 @@ impl GrpcContext {
             .encode(&mut buf)
             .map_err(|e| Status::new(Code::Internal, format!("Encode error: {}", e)))?;
 
-        // Add gRPC framing: 1 byte compression flag (0) + 4 bytes length + message
-        let mut framed = Vec::with_capacity(5 + buf.len());
-        framed.push(0); // No compression
-        framed.extend_from_slice(&(buf.len() as u32).to_be_bytes());
-        framed.extend_from_slice(&buf);
+        self.set_response_bytes(Bytes::from(buf));
+        Ok(())
+    }
+
+    /// Returns the raw request message without its gRPC frame header
+    ///
+    /// Lets proxies and loggers handle a message without knowing its prost
+    /// type. `None` if there is no body, the frame is truncated, or the
+    /// message is compressed (compressed messages are not supported yet).
+    pub fn request_bytes(&self) -> Option<&Bytes> {
+        self.request_payload.as_ref()
+    }
+
+    /// Sets an already serialized response message, bypassing `encode_response`
+    ///
+    /// The gRPC frame header is added here; `message` must not carry one.
+    pub fn set_response_bytes(&mut self, message: Bytes) {
+        if let Some(interceptor) = &self.size_interceptor {
+            interceptor.on_response_message(message.len());
+        }
+
+        self.response_body = Some(Self::frame(&message));
+    }
+
+    /// Streams the response from a [`server_stream`](crate::streaming::server_stream)
+    ///
+    /// The stream's body replaces any single response message, and its
+    /// trailers carry the final status.
+    pub fn set_response_stream(&mut self, stream: ResponseStream) {
+        self.response_body = None;
+        self.stream_started = Some(stream.started());
+        self.inner.response.set_body_stream(box_body(stream));
+    }
+
+    /// Sends `metadata` as the initial metadata of the response
+    ///
+    /// It goes into the HEADERS frame that opens the response, which HTTP/2
+    /// always sends ahead of the first DATA frame. With a
+    /// [server stream](Self::server_stream), the frame goes out as soon as
+    /// the handler returns, while the first message may still be in the
+    /// making, so clients waiting on early metadata, e.g. an auth
+    /// acknowledgment, get it without waiting for a message. Reserved names,
+    /// `content-type` and `grpc-*`, are skipped.
+    ///
+    /// Fails with `FailedPrecondition` once a response message has been set
+    /// or streamed, or when the headers were already sent.
+    pub fn send_headers(&mut self, metadata: MetadataMap) -> Result<(), Status> {
+        let streamed = self
+            .stream_started
+            .as_ref()
+            .is_some_and(|started| started.load(Ordering::Relaxed));
+        if self.headers_sent || self.response_body.is_some() || streamed {
+            return Err(Status::new(
+                Code::FailedPrecondition,
+                "initial metadata must be sent once, before the first response message",
+            ));
+        }
+        self.headers_sent = true;
 
-        self.response_body = Some(Bytes::from(framed));
+        let headers = self.inner.response_mut().headers_mut();
+        for (name, value) in &from_http02_headers(&metadata.into_headers()) {
+            if name != http::header::CONTENT_TYPE && !name.as_str().starts_with("grpc-") {
+                headers.append(name, value.clone());
+            }
+        }
         Ok(())
     }
 
+    /// Returns the framed response body, as it will be sent
+    pub fn response_body(&self) -> Option<&Bytes> {
+        self.response_body.as_ref()
+    }
+
     /// Sets the gRPC status
     pub fn set_status(&mut self, status: Status) {
         self.status = status;
@@ -180,6 +691,22 @@ impl GrpcContext {
 
         // Add gRPC trailers (these go at the end of the HTTP/2 stream)
         // h2per will need to support trailers for this to work properly
+
+        // gRPC-Web text carries the trailers in the body, so the whole
+        // response can be written now
+        if self.web_text {
+            let frames = self.response_body.clone().unwrap_or_default();
+            let response = self.inner.response_mut();
+            response.set_body_bytes(encode_web_text(&frames, &self.status));
+            response.headers_mut().insert(
+                http::header::CONTENT_TYPE,
+                HeaderValue::from_static(GRPC_WEB_TEXT_PROTO_CONTENT_TYPE),
+            );
+        }
+
+        if let Some(interceptor) = &self.size_interceptor {
+            interceptor.finish();
+        }
     }
 
     /// Gets the underlying HyperContext (for compatibility)
@@ -219,4 +746,16 @@ impl RequestContext for GrpcContext {
         // gRPC contexts are always server-side for now
         ProtocolRole::Server
     }
+
+    fn headers(&self) -> &HeaderMultiMap {
+        &self.raw_headers
+    }
+
+    fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
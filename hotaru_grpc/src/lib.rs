@@ -37,15 +37,42 @@
 //! }
 //! ```
 
+pub mod admission;
+pub mod balance;
 pub mod context;
+pub mod metrics;
 pub mod protocol;
+pub mod retry;
 pub mod service;
+pub mod status;
+pub mod streaming;
+pub mod timeout;
+pub mod tonic_service;
+#[cfg(feature = "transcoding")]
+pub mod transcode;
 pub mod transport;
+pub mod validate;
+pub mod web;
 
 // Re-export key types
-pub use context::GrpcContext;
-pub use protocol::GrpcProtocol;
-pub use service::GrpcService;
+pub use admission::{Admission, GRPC_CONTENT_TYPE};
+pub use balance::{ConnectionTarget, EjectionPolicy, LoadBalancer};
+pub use context::{GrpcContext, TRACE_HEADERS};
+pub use metrics::{MessageSizeHistogram, MessageSizeInterceptor, MessageSizeRecorder};
+pub use protocol::{GrpcHttp1Rejection, GrpcProtocol};
+pub use retry::{CallAttempt, HedgingPolicy, RetryPolicy};
+pub use service::{GrpcRegistry, GrpcService};
+pub use status::{grpc_code_to_http_status, http_status_to_grpc_code};
+pub use streaming::{
+    server_stream, status_from_trailers, status_trailers, MessageDumpInterceptor, RequestStream,
+    ResponseStream, StreamInterceptor, StreamSender, DEFAULT_MAX_SEND_MESSAGE_SIZE,
+};
+pub use timeout::{decode_grpc_timeout, encode_grpc_timeout, split_budget, with_timeout};
+pub use tonic_service::TonicService;
+#[cfg(feature = "transcoding")]
+pub use transcode::{JsonTranscoder, JSON_CONTENT_TYPE};
+pub use validate::{FieldViolation, MessageValidators};
+pub use web::{is_grpc_web_text, WebTextDecoder, GRPC_WEB_TEXT_CONTENT_TYPE};
 
 // Re-export tonic types for convenience
 pub use prost::Message;
@@ -56,10 +83,17 @@ pub use tonic::{
 // Re-export h2per types we build on
 pub use h2per::{HyperContext, HyperHttp2};
 
+// Re-export the mTLS identity exposed by `GrpcContext::peer_identity`
+pub use hotaru_tls::PeerIdentity;
+
 pub mod prelude {
     //! Common imports for gRPC development
 
-    pub use crate::{GrpcCode, GrpcContext, GrpcProtocol, GrpcService, GrpcStatus, Message};
+    pub use crate::{
+        ConnectionTarget, FieldViolation, GrpcCode, GrpcContext, GrpcProtocol, GrpcRegistry,
+        GrpcService, GrpcStatus, LoadBalancer, Message, MessageValidators, RetryPolicy,
+        TonicService,
+    };
 
     // Re-export hotaru core types
     pub use hotaru_core::connection::*;
@@ -71,34 +105,348 @@ mod tests {
     use crate::context::{GrpcRequest, GrpcResponse};
     use crate::transport::GrpcMessage;
     use bytes::{Bytes, BytesMut};
+    use h2per::context::{empty_body, full_body};
     use hotaru_core::connection::{
         Message as MessageTrait, Protocol, ProtocolRole, RequestContext,
     };
+    use hotaru_core::protocol::Detection;
     use tonic::{Code, Status};
 
     #[test]
     fn test_grpc_protocol_detection() {
         // Test HTTP/2 preface detection
         let http2_preface = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
-        assert!(
+        assert_eq!(
             GrpcProtocol::detect(http2_preface),
+            Detection::Match,
             "Should detect HTTP/2 preface"
         );
 
         // Test non-HTTP/2 data
         let http1_request = b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n";
-        assert!(
-            !GrpcProtocol::detect(http1_request),
+        assert_eq!(
+            GrpcProtocol::detect(http1_request),
+            Detection::NoMatch,
             "Should not detect HTTP/1.1"
         );
 
         let random_data = b"random data that is not HTTP/2";
-        assert!(
-            !GrpcProtocol::detect(random_data),
+        assert_eq!(
+            GrpcProtocol::detect(random_data),
+            Detection::NoMatch,
             "Should not detect random data"
         );
     }
 
+    #[test]
+    fn test_grpc_protocol_detection_partial_preface() {
+        // Only the first 3 bytes of the HTTP/2 preface have arrived
+        assert_eq!(GrpcProtocol::detect(b"PRI"), Detection::NeedMoreData);
+        assert_eq!(HyperHttp2::detect(b"PRI"), Detection::NeedMoreData);
+
+        // Diverging early is a definite miss, and nothing must panic
+        assert_eq!(GrpcProtocol::detect(b"PRX"), Detection::NoMatch);
+        assert_eq!(GrpcProtocol::detect(b""), Detection::NoMatch);
+        assert_eq!(GrpcProtocol::detect(&[0x00]), Detection::NeedMoreData);
+    }
+
+    fn routed_request(version: http::Version, content_type: Option<&str>) -> HyperContext {
+        use h2per::context::Body;
+
+        let mut builder = http::Request::builder()
+            .version(version)
+            .uri("/helloworld.Greeter/SayHello");
+        if let Some(content_type) = content_type {
+            builder = builder.header("content-type", content_type);
+        }
+        let request = builder.body::<Body>(empty_body()).unwrap();
+        HyperContext::new_client(request)
+    }
+
+    #[test]
+    fn test_grpc_call_over_http2_is_dispatched() {
+        let service = GrpcService::new("helloworld.Greeter");
+        for content_type in ["application/grpc", "application/grpc+proto"] {
+            let ctx = routed_request(http::Version::HTTP_2, Some(content_type));
+            assert_eq!(Admission::of(ctx.request().as_inner()), Admission::Dispatch);
+            assert!(service.admit(ctx).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_http2_without_grpc_content_type_gets_415() {
+        let service = GrpcService::new("helloworld.Greeter");
+        for content_type in [Some("application/json"), None] {
+            let ctx = routed_request(http::Version::HTTP_2, content_type);
+            let rejected = service.admit(ctx).err().expect("not a gRPC call");
+            let response = rejected.response();
+            assert_eq!(response.status(), http::StatusCode::UNSUPPORTED_MEDIA_TYPE);
+            assert_eq!(
+                response.headers()["content-type"],
+                "text/plain; charset=utf-8"
+            );
+        }
+    }
+
+    #[test]
+    fn test_http1_on_grpc_route_gets_505() {
+        let service = GrpcService::new("helloworld.Greeter");
+        let ctx = routed_request(http::Version::HTTP_11, Some("application/grpc"));
+        assert_eq!(
+            Admission::of(ctx.request().as_inner()),
+            Admission::Http2Required(http::Version::HTTP_11)
+        );
+        let rejected = service.admit(ctx).err().expect("gRPC needs HTTP/2");
+        assert_eq!(
+            rejected.response().status(),
+            http::StatusCode::HTTP_VERSION_NOT_SUPPORTED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_call_is_trailers_only() {
+        use hyper_util::rt::{TokioExecutor, TokioIo};
+
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+
+        // An auth check refuses every call before any handler runs
+        let svc = hyper::service::service_fn(|request: http::Request<hyper::body::Incoming>| {
+            let (parts, _) = request.into_parts();
+            let request = http::Request::from_parts(parts, empty_body());
+            let ctx = GrpcService::new("helloworld.Greeter").reject(
+                HyperContext::new_client(request),
+                &Status::new(Code::Unauthenticated, "missing token"),
+            );
+            async move { Ok::<_, std::convert::Infallible>(ctx.response.into_inner()) }
+        });
+        tokio::spawn(
+            hyper::server::conn::http2::Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(server_io), svc),
+        );
+
+        let (mut client, connection) = h2::client::handshake(client_io).await.unwrap();
+        tokio::spawn(connection);
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("http://localhost/helloworld.Greeter/SayHello")
+            .header("content-type", "application/grpc")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(request, true).unwrap();
+        let response = response.await.unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/grpc");
+        assert_eq!(response.headers()["grpc-status"], "16");
+        assert_eq!(response.headers()["grpc-message"], "missing token");
+        // END_STREAM came with the HEADERS frame: no DATA, no separate trailers
+        assert!(response.body().is_end_stream());
+    }
+
+    #[tokio::test]
+    async fn test_shared_listener_routes_streams_by_content_type() {
+        use h2per::{ContentTypeRouter, StreamFuture, StreamService};
+        use hyper::body::Incoming;
+        use hyper_util::rt::{TokioExecutor, TokioIo};
+        use std::sync::Arc;
+
+        fn answer(handler: &'static str) -> http::Response<h2per::context::Body> {
+            http::Response::builder()
+                .header("x-handler", handler)
+                .body(full_body(Bytes::from_static(b"")))
+                .unwrap()
+        }
+
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let grpc: StreamService = Arc::new(|_request: http::Request<Incoming>| -> StreamFuture {
+            Box::pin(async { Ok(answer("grpc")) })
+        });
+        let http = hyper::service::service_fn(|_request| async {
+            Ok::<_, std::convert::Infallible>(answer("http"))
+        });
+        let router = ContentTypeRouter::new(http).route(GRPC_CONTENT_TYPE, grpc);
+        tokio::spawn(
+            hyper::server::conn::http2::Builder::new(TokioExecutor::new())
+                .serve_connection(TokioIo::new(server_io), router),
+        );
+
+        // Both streams share one connection
+        let (client, connection) = h2::client::handshake(client_io).await.unwrap();
+        tokio::spawn(connection);
+        let mut client = client.ready().await.unwrap();
+        let call = http::Request::builder()
+            .method("POST")
+            .uri("http://localhost/helloworld.Greeter/SayHello")
+            .header("content-type", "application/grpc+proto")
+            .body(())
+            .unwrap();
+        let (grpc_response, _) = client.send_request(call, true).unwrap();
+        let mut client = client.ready().await.unwrap();
+        let json = http::Request::builder()
+            .method("POST")
+            .uri("http://localhost/api/orders")
+            .header("content-type", "application/json")
+            .body(())
+            .unwrap();
+        let (json_response, _) = client.send_request(json, true).unwrap();
+
+        let grpc_response = grpc_response.await.unwrap();
+        assert_eq!(grpc_response.headers()["x-handler"], "grpc");
+        let json_response = json_response.await.unwrap();
+        assert_eq!(json_response.headers()["x-handler"], "http");
+    }
+
+    #[tokio::test]
+    async fn test_initial_metadata_is_sent_before_the_first_message() {
+        use h2per::{StreamFuture, StreamService};
+        use hyper::body::Incoming;
+        use hyper_util::rt::{TokioExecutor, TokioIo};
+        use std::sync::Arc;
+        use tokio::sync::Notify;
+        use tonic::metadata::MetadataMap;
+
+        // The first message is only produced once the client has seen the
+        // response headers
+        let release = Arc::new(Notify::new());
+        let gate = release.clone();
+        let service: StreamService =
+            Arc::new(move |request: http::Request<Incoming>| -> StreamFuture {
+                let gate = gate.clone();
+                Box::pin(async move {
+                    let request = request.map(|_| empty_body());
+                    let mut ctx =
+                        GrpcContext::from_hyper_context(HyperContext::new_client(request)).unwrap();
+                    let mut tx = ctx.server_stream(64);
+                    let mut metadata = MetadataMap::new();
+                    metadata.insert("x-auth-ack", "granted".parse().unwrap());
+                    ctx.send_headers(metadata).unwrap();
+                    tokio::spawn(async move {
+                        gate.notified().await;
+                        tx.send_bytes(Bytes::from_static(b"first")).await.unwrap();
+                        tx.finish(Status::new(Code::Ok, "")).await;
+                    });
+                    let empty = http::Response::new(empty_body());
+                    Ok(std::mem::replace(
+                        &mut ctx.inner.response_mut().inner,
+                        empty,
+                    ))
+                })
+            });
+
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(
+            hyper::server::conn::http2::Builder::new(TokioExecutor::new()).serve_connection(
+                TokioIo::new(server_io),
+                hyper::service::service_fn(move |request| service(request)),
+            ),
+        );
+        let (client, connection) = h2::client::handshake(client_io).await.unwrap();
+        tokio::spawn(connection);
+        let mut client = client.ready().await.unwrap();
+        let call = http::Request::builder()
+            .method("POST")
+            .uri("http://localhost/pkg.Svc/M")
+            .header("content-type", "application/grpc")
+            .body(())
+            .unwrap();
+        let (response, _) = client.send_request(call, true).unwrap();
+
+        // HEADERS carry the metadata while no DATA exists yet
+        let response = response.await.unwrap();
+        assert_eq!(response.headers()["x-auth-ack"], "granted");
+        release.notify_one();
+        let mut body = response.into_body();
+        let data = body.data().await.unwrap().unwrap();
+        assert_eq!(data, GrpcContext::frame(b"first"));
+        let _ = body.flow_control().release_capacity(data.len());
+        let trailers = body.trailers().await.unwrap().unwrap();
+        assert_eq!(trailers["grpc-status"], "0");
+
+        // Too late once a message is on its way
+        let mut ctx = client_stream_request(Vec::new());
+        let mut tx = ctx.server_stream(64);
+        tx.send_bytes(Bytes::from_static(b"first")).await.unwrap();
+        let err = ctx.send_headers(MetadataMap::new()).unwrap_err();
+        assert_eq!(err.code(), Code::FailedPrecondition);
+    }
+
+    #[test]
+    fn test_peer_identity_from_client_certificate() {
+        use h2per::context::Body;
+        use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair};
+
+        // A test CA and a client certificate it issued
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca_cert = ca_params.self_signed(&ca_key).unwrap();
+        let client_key = KeyPair::generate().unwrap();
+        let mut client_params =
+            CertificateParams::new(vec!["orders.internal".to_string()]).unwrap();
+        client_params
+            .distinguished_name
+            .push(DnType::CommonName, "orders");
+        let client_cert = client_params
+            .signed_by(&client_key, &ca_cert, &ca_key)
+            .unwrap();
+        let identity = PeerIdentity::from_der(client_cert.der()).unwrap();
+
+        // The TLS layer attaches the identity to each request of the connection
+        let service = GrpcService::new("helloworld.Greeter").require_client_auth();
+        let request = http::Request::builder()
+            .version(http::Version::HTTP_2)
+            .uri("/helloworld.Greeter/SayHello")
+            .header("content-type", "application/grpc")
+            .extension(identity)
+            .body::<Body>(empty_body())
+            .unwrap();
+        let admitted = service
+            .admit(HyperContext::new_client(request))
+            .ok()
+            .expect("client certificate present");
+        let ctx = GrpcContext::from_hyper_context(admitted).unwrap();
+        let peer = ctx.peer_identity().expect("peer identity");
+        assert_eq!(peer.common_name(), Some("orders"));
+        assert_eq!(peer.sans(), ["orders.internal"]);
+
+        // Without a certificate the call is refused before dispatch
+        let ctx = routed_request(http::Version::HTTP_2, Some("application/grpc"));
+        let rejected = service.admit(ctx).err().expect("no client certificate");
+        let response = rejected.response();
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert_eq!(response.headers()["grpc-status"], "16");
+        assert_eq!(
+            response.headers()["grpc-message"],
+            "client certificate required"
+        );
+
+        // Services that do not ask for client auth let it through
+        let ctx = routed_request(http::Version::HTTP_2, Some("application/grpc"));
+        let admitted = GrpcService::new("helloworld.Greeter").admit(ctx).ok();
+        let ctx = GrpcContext::from_hyper_context(admitted.unwrap()).unwrap();
+        assert!(ctx.peer_identity().is_none());
+    }
+
+    #[test]
+    fn test_http1_on_grpc_only_listener_gets_clear_error() {
+        // GrpcProtocol leaves HTTP/1.x alone; the rejection protocol claims it
+        let http1_request = b"POST /helloworld.Greeter/SayHello HTTP/1.1\r\n";
+        assert_eq!(GrpcProtocol::detect(http1_request), Detection::NoMatch);
+        assert_eq!(GrpcHttp1Rejection::detect(http1_request), Detection::Match);
+        assert_eq!(
+            GrpcHttp1Rejection::detect(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n"),
+            Detection::NoMatch
+        );
+
+        let response = Admission::Http2Required(http::Version::HTTP_11)
+            .rejection()
+            .unwrap();
+        let wire = String::from_utf8(admission::http1_bytes(&response)).unwrap();
+        assert!(wire.starts_with("HTTP/1.1 505 HTTP Version Not Supported\r\n"));
+        assert!(wire.contains("connection: close\r\n\r\n"));
+        assert!(wire.ends_with("gRPC requires HTTP/2, got HTTP/1.1\n"));
+    }
+
     #[test]
     fn test_grpc_protocol_role() {
         let server_protocol = GrpcProtocol::new(ProtocolRole::Server);
@@ -159,6 +507,38 @@ mod tests {
         assert!(decoded.is_none(), "Should return None for incomplete body");
     }
 
+    #[test]
+    fn test_grpc_empty_message_encoding() {
+        // e.g. google.protobuf.Empty
+        let message = GrpcMessage::new(Bytes::new());
+
+        let mut buf = BytesMut::new();
+        message.encode(&mut buf).unwrap();
+
+        assert_eq!(&buf[..], &[0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_grpc_empty_message_decoding() {
+        // A zero-length frame followed by the start of the next one
+        let mut buf = BytesMut::from(&[0, 0, 0, 0, 0, 0, 0][..]);
+
+        let decoded = GrpcMessage::decode(&mut buf).unwrap();
+        let message = decoded.expect("Zero-length frame is a complete message");
+        assert_eq!(message.body(), Some(&Bytes::new()));
+        assert_eq!(buf.len(), 2, "Only the 5-byte header should be consumed");
+    }
+
+    #[test]
+    fn test_grpc_empty_message_round_trip() {
+        let mut buf = BytesMut::new();
+        GrpcMessage::new(Bytes::new()).encode(&mut buf).unwrap();
+
+        let message = GrpcMessage::decode(&mut buf).unwrap().unwrap();
+        assert_eq!(message.body(), Some(&Bytes::new()));
+        assert!(buf.is_empty());
+    }
+
     #[test]
     fn test_grpc_message_with_status() {
         let mut message = GrpcMessage::error(3, "Invalid argument");
@@ -170,6 +550,35 @@ mod tests {
         assert_eq!(message.grpc_message, Some("Not found".to_string()));
     }
 
+    #[test]
+    fn test_grpc_message_debug_dump() {
+        // A compressed frame as it arrives on the wire
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(&[1]);
+        buf.extend_from_slice(&3u32.to_be_bytes());
+        buf.extend_from_slice(&[0x0a, 0x01, 0xff]);
+        let message = GrpcMessage::decode(&mut buf).unwrap().unwrap();
+        assert_eq!(message.debug_dump(), "compressed=1 length=3 body=0a 01 ff");
+
+        // Long bodies are cut to a preview, the length stays exact
+        let dump = GrpcMessage::new(Bytes::from(vec![0xab; 100])).debug_dump();
+        assert!(
+            dump.starts_with("compressed=0 length=100 body=ab ab"),
+            "{dump}"
+        );
+        assert!(dump.ends_with(" ..."), "{dump}");
+        assert_eq!(
+            dump.matches("ab").count(),
+            crate::transport::DEBUG_DUMP_PREVIEW
+        );
+
+        let dump = GrpcMessage::error(5, "Not found").debug_dump();
+        assert_eq!(
+            dump,
+            "compressed=0 length=0 body=none grpc-status=5 grpc-message=\"Not found\""
+        );
+    }
+
     #[test]
     fn test_grpc_context_path_parsing() {
         // Test valid gRPC paths
@@ -197,6 +606,75 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_grpc_context_exposes_service_and_method() {
+        use h2per::context::Body;
+
+        fn context_for(path: &str) -> Result<GrpcContext, Status> {
+            let request = http::Request::builder()
+                .uri(path)
+                .header("content-type", "application/grpc")
+                .body::<Body>(empty_body())
+                .unwrap();
+            GrpcContext::from_hyper_context(HyperContext::new_client(request))
+        }
+
+        let ctx = context_for("/com.example.UserService/GetUser").unwrap();
+        assert_eq!(ctx.service(), "com.example.UserService");
+        assert_eq!(ctx.method(), "GetUser");
+        assert_eq!(ctx.method_path(), "/com.example.UserService/GetUser");
+
+        // Paths that do not name a method never reach a handler
+        for path in [
+            "/helloworld.Greeter",
+            "/helloworld.Greeter/",
+            "//SayHello",
+            "/helloworld.Greeter/SayHello/extra",
+        ] {
+            let status = context_for(path).err().expect(path);
+            assert_eq!(status.code(), Code::Unimplemented, "{path}");
+        }
+    }
+
+    #[test]
+    fn test_path_prefix_is_stripped_before_routing() {
+        use h2per::context::Body;
+
+        fn request(path: &str) -> http::Request<Body> {
+            http::Request::builder()
+                .uri(path)
+                .header("content-type", "application/grpc")
+                .body::<Body>(empty_body())
+                .unwrap()
+        }
+
+        let registry = GrpcRegistry::new().service(GrpcService::new("pkg.Svc").method("M"));
+
+        let mut prefixed = request("/v2/pkg.Svc/M?trace=1");
+        assert!(GrpcContext::strip_path_prefix(&mut prefixed, "/v2"));
+        assert_eq!(prefixed.uri().path(), "/pkg.Svc/M");
+        assert_eq!(prefixed.uri().query(), Some("trace=1"));
+        assert_eq!(
+            registry.resolve(prefixed.uri().path()).unwrap().name,
+            "pkg.Svc"
+        );
+        let ctx = GrpcContext::from_hyper_context(HyperContext::new_client(prefixed)).unwrap();
+        assert_eq!(ctx.method_path(), "/pkg.Svc/M");
+
+        // The prefix is matched on whole segments, with or without slashes
+        let mut prefixed = request("/v2/pkg.Svc/M");
+        assert!(GrpcContext::strip_path_prefix(&mut prefixed, "v2/"));
+        assert_eq!(prefixed.uri().path(), "/pkg.Svc/M");
+        for path in ["/pkg.Svc/M", "/v20/pkg.Svc/M", "/v2"] {
+            let mut unprefixed = request(path);
+            assert!(
+                !GrpcContext::strip_path_prefix(&mut unprefixed, "/v2"),
+                "{path}"
+            );
+            assert_eq!(unprefixed.uri().path(), path);
+        }
+    }
+
     #[test]
     fn test_grpc_context_request_context_trait() {
         // Test that GrpcContext implements RequestContext correctly
@@ -243,6 +721,1498 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_grpc_request_size_metrics() {
+        use h2per::context::Body;
+        use std::sync::Arc;
+
+        let message = prost_types::Duration {
+            seconds: 42,
+            nanos: 7,
+        };
+        let payload = message.encode_to_vec();
+        let mut framed = vec![0];
+        framed.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        framed.extend_from_slice(&payload);
+
+        let request = http::Request::builder()
+            .uri("/helloworld.Greeter/SayHello")
+            .header("content-type", "application/grpc")
+            .body::<Body>(empty_body())
+            .unwrap();
+        let mut hyper_context = HyperContext::new_client(request);
+        hyper_context.request.body_bytes = Some(framed);
+
+        let histogram = Arc::new(MessageSizeHistogram::new());
+        let mut ctx = GrpcContext::from_hyper_context(hyper_context)
+            .unwrap()
+            .with_size_recorder(histogram.clone());
+
+        let decoded: prost_types::Duration = ctx.decode_request().unwrap();
+        assert_eq!(decoded, message);
+        ctx.encode_response(decoded.clone()).unwrap();
+        ctx.finalize_response();
+
+        let method = "/helloworld.Greeter/SayHello";
+        assert_eq!(histogram.request_sizes(method), vec![payload.len()]);
+        assert_eq!(histogram.response_sizes(method), vec![payload.len()]);
+
+        // A second finalize must not double-report
+        ctx.finalize_response();
+        assert_eq!(histogram.request_sizes(method).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_size_recorder_reaches_every_call() {
+        use h2per::{StreamFuture, StreamService};
+        use http_body_util::BodyExt;
+        use hyper::body::Incoming;
+        use hyper_util::rt::{TokioExecutor, TokioIo};
+        use std::sync::Arc;
+
+        // Echoes the request message without attaching a recorder itself
+        async fn echo(
+            request: http::Request<Incoming>,
+        ) -> Result<http::Response<h2per::context::Body>, std::convert::Infallible> {
+            let (parts, incoming) = request.into_parts();
+            let received = incoming.collect().await.unwrap().to_bytes();
+            let request = http::Request::from_parts(parts, empty_body());
+            let mut hyper_context = HyperContext::new_client(request);
+            hyper_context.request.body_bytes = Some(received.to_vec());
+            let mut ctx = GrpcContext::from_hyper_context(hyper_context).unwrap();
+            let number: Number = ctx.decode_request().unwrap();
+            ctx.encode_response(number).unwrap();
+            ctx.finalize_response();
+            Ok(http::Response::new(empty_body()))
+        }
+
+        let message = GrpcContext::frame(&Number { value: 300 }.encode_to_vec());
+        let method = "/pkg.Svc/Echo";
+        // Calls to a gRPC service, then calls left to the app's routes
+        for routed in [true, false] {
+            let histogram = Arc::new(MessageSizeHistogram::new());
+            let mut protocol =
+                GrpcProtocol::new(ProtocolRole::Server).with_size_recorder(histogram.clone());
+            if routed {
+                let service: StreamService =
+                    Arc::new(|request: http::Request<Incoming>| -> StreamFuture {
+                        Box::pin(echo(request))
+                    });
+                protocol = protocol.with_grpc_service(service);
+            }
+            let router = protocol.stream_router(hyper::service::service_fn(echo));
+
+            let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+            tokio::spawn(
+                hyper::server::conn::http2::Builder::new(TokioExecutor::new())
+                    .serve_connection(TokioIo::new(server_io), router),
+            );
+            let (client, connection) = h2::client::handshake(client_io).await.unwrap();
+            tokio::spawn(connection);
+            let mut client = client.ready().await.unwrap();
+            let call = http::Request::builder()
+                .method("POST")
+                .uri(format!("http://localhost{method}"))
+                .header("content-type", "application/grpc")
+                .body(())
+                .unwrap();
+            let (response, mut request_body) = client.send_request(call, false).unwrap();
+            request_body.send_data(message.clone(), true).unwrap();
+            assert_eq!(response.await.unwrap().status(), http::StatusCode::OK);
+
+            let size = message.len() - 5;
+            assert_eq!(histogram.request_sizes(method), vec![size], "{routed}");
+            assert_eq!(histogram.response_sizes(method), vec![size], "{routed}");
+        }
+    }
+
+    #[test]
+    fn test_grpc_raw_bytes_passthrough() {
+        use h2per::context::Body;
+
+        // An opaque payload the gateway never decodes
+        let payload = b"\x0a\x05hello\xff".to_vec();
+        let mut framed = vec![0];
+        framed.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        framed.extend_from_slice(&payload);
+
+        let request = http::Request::builder()
+            .uri("/gateway.Proxy/Forward")
+            .header("content-type", "application/grpc")
+            .body::<Body>(empty_body())
+            .unwrap();
+        let mut hyper_context = HyperContext::new_client(request);
+        hyper_context.request.body_bytes = Some(framed.clone());
+
+        let mut ctx = GrpcContext::from_hyper_context(hyper_context).unwrap();
+        let raw = ctx.request_bytes().cloned().unwrap();
+        assert_eq!(raw.as_ref(), payload.as_slice());
+
+        ctx.set_response_bytes(raw);
+        assert_eq!(ctx.response_body().unwrap().as_ref(), framed.as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_client_timeout_fires_before_slow_upstream() {
+        use h2per::context::Body;
+        use std::time::Duration;
+        use tokio::time::Instant;
+
+        let request = http::Request::builder()
+            .uri("/helloworld.Greeter/SayHello")
+            .header("content-type", "application/grpc")
+            .body::<Body>(empty_body())
+            .unwrap();
+        let mut ctx = GrpcContext::from_hyper_context(HyperContext::new_client(request)).unwrap();
+        ctx.set_timeout(Some(Duration::from_millis(100)));
+
+        // The server is told how long the client waits
+        let header = ctx.inner().request.headers()["grpc-timeout"].clone();
+        assert_eq!(header, "100000u");
+        assert_eq!(
+            decode_grpc_timeout(header.to_str().unwrap()),
+            Some(Duration::from_millis(100))
+        );
+
+        // Upstream answers only after a second
+        let started = Instant::now();
+        let result = ctx
+            .run_with_timeout(async {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Ok::<_, Status>("late reply")
+            })
+            .await;
+        let elapsed = started.elapsed();
+
+        assert_eq!(result.unwrap_err().code(), Code::DeadlineExceeded);
+        assert!(elapsed >= Duration::from_millis(100), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(500), "{elapsed:?}");
+
+        ctx.set_timeout(None);
+        assert!(!ctx.inner().request.headers().contains_key("grpc-timeout"));
+    }
+
+    #[tokio::test]
+    async fn test_deadline_budget_shrinks_as_deadline_approaches() {
+        use h2per::context::Body;
+        use std::time::Duration;
+
+        let request = http::Request::builder()
+            .uri("/shop.Checkout/PlaceOrder")
+            .header("content-type", "application/grpc")
+            .header("grpc-timeout", "300m")
+            .body::<Body>(empty_body())
+            .unwrap();
+        let req = GrpcContext::from_hyper_context(HyperContext::new_client(request)).unwrap();
+
+        // Three upstream calls share the 300ms the client allowed
+        let first = req.deadline_budget(3).unwrap();
+        assert!(first <= Duration::from_millis(100), "{first:?}");
+        assert!(first > Duration::from_millis(50), "{first:?}");
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let later = req.deadline_budget(3).unwrap();
+        assert!(later < first, "{later:?} vs {first:?}");
+        assert!(later <= Duration::from_millis(50), "{later:?}");
+
+        // The slice goes out as each outbound call's grpc-timeout
+        let upstream = http::Request::builder()
+            .uri("/shop.Inventory/Reserve")
+            .header("content-type", "application/grpc")
+            .body::<Body>(empty_body())
+            .unwrap();
+        let mut call = GrpcContext::from_hyper_context(HyperContext::new_client(upstream)).unwrap();
+        call.set_timeout(Some(later));
+        assert_eq!(call.timeout(), Some(later));
+        assert!(call.inner().request.headers().contains_key("grpc-timeout"));
+
+        // Once the deadline has passed nothing is left to hand out
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(req.remaining(), Some(Duration::ZERO));
+        assert_eq!(req.deadline_budget(3), Some(Duration::ZERO));
+        assert_eq!(split_budget(None, 3), None);
+    }
+
+    #[test]
+    fn test_trace_headers_are_forwarded_on_outbound_calls() {
+        use h2per::context::Body;
+
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let request = http::Request::builder()
+            .uri("/shop.Checkout/PlaceOrder")
+            .header("content-type", "application/grpc")
+            .header("traceparent", traceparent)
+            .header("tracestate", "vendor=value")
+            .header("x-request-id", "42")
+            .body::<Body>(empty_body())
+            .unwrap();
+        let req = GrpcContext::from_hyper_context(HyperContext::new_client(request)).unwrap();
+        assert_eq!(req.traceparent(), Some(traceparent));
+
+        // Only the trace headers travel upstream
+        let upstream = http::Request::builder()
+            .uri("/shop.Inventory/Reserve")
+            .header("content-type", "application/grpc")
+            .body::<Body>(empty_body())
+            .unwrap();
+        let call = req.outbound_call(upstream).unwrap();
+        let headers = call.inner().request.headers();
+        assert_eq!(headers["traceparent"], traceparent);
+        assert_eq!(headers["tracestate"], "vendor=value");
+        assert!(!headers.contains_key("x-request-id"));
+        assert_eq!(call.traceparent(), Some(traceparent));
+
+        // A traceparent the handler set itself wins
+        let child = "00-4bf92f3577b34da6a3ce929d0e0e4736-b7ad6b7169203331-01";
+        let upstream = http::Request::builder()
+            .uri("/shop.Inventory/Reserve")
+            .header("content-type", "application/grpc")
+            .header("traceparent", child)
+            .body::<Body>(empty_body())
+            .unwrap();
+        let call = req.outbound_call(upstream).unwrap();
+        assert_eq!(call.traceparent(), Some(child));
+    }
+
+    #[test]
+    fn test_client_rejects_oversized_request_locally() {
+        use h2per::context::Body;
+
+        let request = http::Request::builder()
+            .uri("/helloworld.Greeter/SayHello")
+            .header("content-type", "application/grpc")
+            .body::<Body>(empty_body())
+            .unwrap();
+        let mut ctx = GrpcContext::from_hyper_context(HyperContext::new_client(request)).unwrap();
+        assert_eq!(ctx.max_send_message_size(), DEFAULT_MAX_SEND_MESSAGE_SIZE);
+        ctx.set_max_send_message_size(64);
+
+        // Over the client's own limit: fails before anything is framed
+        let status = ctx
+            .set_request_bytes(Bytes::from(vec![7u8; 65]))
+            .unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(
+            status.message(),
+            "message of 65 bytes exceeds max_send_message_size of 64 bytes"
+        );
+        assert!(ctx.inner().request.body_bytes.is_none());
+
+        // A smaller server-advertised limit wins
+        ctx.set_server_max_receive_message_size(Some(8));
+        assert_eq!(ctx.max_send_message_size(), 8);
+        let message = prost_types::Duration {
+            seconds: i64::MAX,
+            nanos: 999_999_999,
+        };
+        assert!(message.encoded_len() > 8);
+        let status = ctx.encode_request(message).unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert!(ctx.inner().request.body_bytes.is_none());
+
+        // Within both limits the message is framed into the request body
+        let small = prost_types::Duration {
+            seconds: 1,
+            nanos: 0,
+        };
+        ctx.encode_request(small.clone()).unwrap();
+        let body = ctx.inner().request.body_bytes.clone().unwrap();
+        assert_eq!(body[0], 0);
+        assert_eq!(&body[5..], small.encode_to_vec().as_slice());
+        assert_eq!(
+            ctx.decode_request::<prost_types::Duration>().unwrap(),
+            small
+        );
+    }
+
+    #[test]
+    fn test_grpc_size_interceptor_accumulates_stream() {
+        use std::sync::Arc;
+
+        let histogram = Arc::new(MessageSizeHistogram::new());
+        let interceptor = MessageSizeInterceptor::new(histogram.clone(), "/svc.Chat/Stream");
+        for len in [3, 5, 8] {
+            interceptor.on_request_message(len);
+        }
+        interceptor.on_response_message(10);
+        interceptor.on_response_message(2);
+        interceptor.finish();
+
+        assert_eq!(histogram.request_sizes("/svc.Chat/Stream"), vec![16]);
+        assert_eq!(histogram.response_sizes("/svc.Chat/Stream"), vec![12]);
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_retries_unavailable() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::time::Duration;
+
+        let attempts = AtomicU32::new(0);
+        let policy = RetryPolicy::new()
+            .with_max_attempts(5)
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(5));
+
+        // Upstream answers Unavailable twice, then succeeds
+        let reply = policy
+            .call(Some(Duration::from_secs(5)), |attempt| {
+                let n = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                assert_eq!(attempt.number(), n);
+                async move {
+                    if n <= 2 {
+                        Err(Status::unavailable("upstream down"))
+                    } else {
+                        Ok("pong")
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(reply.unwrap(), "pong");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_skips_committed_stream_and_fatal_codes() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::time::Duration;
+
+        let policy = RetryPolicy::new()
+            .with_max_attempts(5)
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(5));
+
+        // A client stream that already sent a message is not replayed
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), Status> = policy
+            .call_streaming(None, |attempt| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    attempt.record_message_sent();
+                    Err(Status::unavailable("reset mid-stream"))
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap_err().code(), Code::Unavailable);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        // Codes outside the retryable set fail immediately
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), Status> = policy
+            .call(None, |_| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { Err(Status::invalid_argument("bad request")) }
+            })
+            .await;
+        assert_eq!(result.unwrap_err().code(), Code::InvalidArgument);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_hedges_slow_attempt() {
+        use std::time::Duration;
+
+        let policy = RetryPolicy::new()
+            .with_max_attempts(3)
+            .with_hedging(Duration::from_millis(10));
+
+        // The first attempt hangs; the hedged second one answers
+        let reply = policy
+            .call(Some(Duration::from_secs(5)), |attempt| async move {
+                if attempt.number() == 1 {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                }
+                Ok::<_, Status>(attempt.number())
+            })
+            .await;
+        assert_eq!(reply.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_hedges_again_right_after_a_retryable_failure() {
+        use std::time::Duration;
+
+        // Set after hedging is enabled, and still non-fatal for the hedge
+        let policy = RetryPolicy::new()
+            .with_max_attempts(3)
+            .with_hedging(Duration::from_secs(60))
+            .with_retryable_codes(vec![Code::Aborted]);
+
+        // The next attempt starts without waiting out the hedging delay
+        let reply = tokio::time::timeout(
+            Duration::from_secs(5),
+            policy.call(None, |attempt| async move {
+                match attempt.number() {
+                    1 => Err(Status::aborted("conflict")),
+                    n => Ok(n),
+                }
+            }),
+        )
+        .await
+        .expect("waited for the hedging delay");
+        assert_eq!(reply.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_spreads_calls_across_upstreams() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+
+        // Each mock upstream counts the calls it serves
+        let upstream_a = Arc::new(AtomicU32::new(0));
+        let upstream_b = Arc::new(AtomicU32::new(0));
+        let target = ConnectionTarget::new("10.0.0.1:50051", upstream_a.clone())
+            .with_endpoint("10.0.0.2:50051", upstream_b.clone())
+            .with_load_balancer(LoadBalancer::RoundRobin);
+
+        for _ in 0..6 {
+            target
+                .call(|upstream| async move {
+                    upstream.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, Status>(())
+                })
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(upstream_a.load(Ordering::SeqCst), 3);
+        assert_eq!(upstream_b.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_failing_upstream_is_ejected() {
+        use std::time::Duration;
+
+        let target = ConnectionTarget::new("pick-first", "down")
+            .with_endpoint("backup", "up")
+            .with_ejection(EjectionPolicy {
+                max_failures: 2,
+                cooldown: Duration::from_secs(60),
+                failure_codes: vec![Code::Unavailable],
+            });
+        let rpc = |upstream: &'static str| async move {
+            match upstream {
+                "down" => Err(Status::unavailable("connection refused")),
+                _ => Ok(upstream),
+            }
+        };
+
+        // Pick-first sticks to the first endpoint until it is ejected
+        assert!(target.call(rpc).await.is_err());
+        assert!(target.call(rpc).await.is_err());
+        assert_eq!(target.healthy_addresses(), vec!["backup"]);
+        assert_eq!(target.call(rpc).await.unwrap(), "up");
+
+        // Application errors don't count against an endpoint
+        let target = ConnectionTarget::new("only", ()).with_ejection(EjectionPolicy {
+            max_failures: 1,
+            ..Default::default()
+        });
+        let _ = target
+            .call(|_| async { Err::<(), _>(Status::not_found("no such user")) })
+            .await;
+        assert_eq!(target.healthy_addresses(), vec!["only"]);
+    }
+
+    #[tokio::test]
+    async fn test_lazy_target_fails_unavailable_within_connect_timeout() {
+        use std::time::{Duration, Instant};
+        use tonic::transport::Endpoint;
+
+        // Nothing listens on a port once its listener is dropped
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        let target = ConnectionTarget::lazy(address.clone(), |address| async move {
+            let endpoint = Endpoint::from_shared(format!("http://{address}"))
+                .map_err(|err| err.to_string())?;
+            endpoint.connect().await.map_err(|err| err.to_string())
+        })
+        .with_connect_timeout(Duration::from_secs(2));
+        assert!(target.connected_addresses().is_empty());
+
+        let started = Instant::now();
+        let status = target
+            .call(|_channel| async { Ok::<_, Status>(()) })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+        assert!(status.message().contains(&address));
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(target.connected_addresses().is_empty());
+
+        // A connection that never comes up is cut off at the connect timeout
+        let target = ConnectionTarget::lazy("blackhole:50051", |_| async {
+            std::future::pending::<Result<(), String>>().await
+        })
+        .with_connect_timeout(Duration::from_millis(50));
+        let started = Instant::now();
+        let status = target.call(|_| async { Ok(()) }).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+        assert!(status.message().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retry_backoff_stays_capped() {
+        use std::time::Duration;
+
+        let policy = RetryPolicy::new().with_max_attempts(100);
+        for retry in [68, 99, u32::MAX] {
+            assert_eq!(policy.backoff(retry), Duration::from_secs(1), "{retry}");
+        }
+        let policy = RetryPolicy::new().with_backoff_multiplier(f64::NAN);
+        assert_eq!(policy.backoff(5), Duration::from_millis(100));
+    }
+
+    /// Answers `Unavailable` to its first `failures` calls, then doubles
+    #[derive(Clone)]
+    struct FlakyDoubler {
+        calls: std::sync::Arc<std::sync::atomic::AtomicU32>,
+        failures: u32,
+    }
+
+    impl tonic::server::NamedService for FlakyDoubler {
+        const NAME: &'static str = "calc.Doubler";
+    }
+
+    impl tower::Service<http02::Request<tonic::transport::Body>> for FlakyDoubler {
+        type Response = http02::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>> + Send>,
+        >;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http02::Request<tonic::transport::Body>) -> Self::Future {
+            use http_body04::Body as _;
+
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            if call <= self.failures {
+                let unavailable = Status::unavailable("warming up").to_http();
+                return Box::pin(async move { Ok(unavailable) });
+            }
+            let request = request.map(|body| {
+                body.map_err(|err| Status::from_error(Box::new(err)))
+                    .boxed_unsync()
+            });
+            tower::Service::call(&mut DoublerServer, request)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_target_retries_unavailable_call_over_the_network() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        use std::sync::Arc;
+        use std::time::Duration;
+        use tonic::transport::{Endpoint, Server};
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let incoming = futures_util::stream::unfold(listener, |listener| async move {
+            let accepted = listener.accept().await.map(|(stream, _)| stream);
+            Some((accepted, listener))
+        });
+        let upstream = FlakyDoubler {
+            calls: calls.clone(),
+            failures: 2,
+        };
+        tokio::spawn(
+            Server::builder()
+                .add_service(upstream)
+                .serve_with_incoming(incoming),
+        );
+
+        let target = ConnectionTarget::lazy(address.to_string(), |address| async move {
+            let endpoint = Endpoint::from_shared(format!("http://{address}"))
+                .map_err(|err| err.to_string())?;
+            endpoint.connect().await.map_err(|err| err.to_string())
+        })
+        .with_retry_policy(
+            RetryPolicy::new().with_backoff(Duration::from_millis(1), Duration::from_millis(5)),
+        );
+
+        // Unavailable twice, then answered on the third and last attempt
+        let reply = target
+            .call_with_retry(
+                Some(Duration::from_secs(5)),
+                |channel, _attempt| async move {
+                    let mut client = tonic::client::Grpc::new(channel);
+                    client
+                        .ready()
+                        .await
+                        .map_err(|err| Status::unavailable(err.to_string()))?;
+                    let path = http02::uri::PathAndQuery::from_static("/calc.Doubler/Double");
+                    let codec = tonic::codec::ProstCodec::<Number, Number>::default();
+                    let response = client
+                        .unary(tonic::Request::new(Number { value: 21 }), path, codec)
+                        .await?;
+                    Ok(response.into_inner().value)
+                },
+            )
+            .await;
+        assert_eq!(reply.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Without a policy the call is attempted once
+        let target = ConnectionTarget::new(address.to_string(), ());
+        let attempts = AtomicU32::new(0);
+        let result = target
+            .call_with_retry(None, |_, _| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { Err::<(), _>(Status::unavailable("down")) }
+            })
+            .await;
+        assert_eq!(result.unwrap_err().code(), Code::Unavailable);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_stream_message_fails_only_its_stream() {
+        use http_body_util::BodyExt;
+
+        async fn collect(body: ResponseStream) -> (usize, String) {
+            let collected = body.collect().await.unwrap();
+            let trailers = collected.trailers().cloned().unwrap();
+            let data = collected.to_bytes();
+            let status = trailers["grpc-status"].to_str().unwrap().to_string();
+            (data.len(), status)
+        }
+
+        let (mut healthy, healthy_body) = server_stream(64);
+        let (mut oversized, oversized_body) = server_stream(64);
+
+        // Both streams are produced and consumed concurrently
+        let healthy_task = tokio::spawn(async move {
+            for i in 0..3u8 {
+                healthy.send_bytes(Bytes::from(vec![i; 32])).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+            healthy.finish(Status::new(Code::Ok, "")).await;
+        });
+        let oversized_task = tokio::spawn(async move {
+            oversized
+                .send_bytes(Bytes::from(vec![0; 16]))
+                .await
+                .unwrap();
+            let err = oversized.send_bytes(Bytes::from(vec![0; 65])).await;
+            assert_eq!(err.unwrap_err().code(), Code::ResourceExhausted);
+            // The stream stays failed
+            let again = oversized.send_bytes(Bytes::from_static(b"late")).await;
+            assert_eq!(again.unwrap_err().code(), Code::ResourceExhausted);
+        });
+
+        let (healthy_result, oversized_result) =
+            tokio::join!(collect(healthy_body), collect(oversized_body));
+        healthy_task.await.unwrap();
+        oversized_task.await.unwrap();
+
+        // Three framed messages, then OK
+        assert_eq!(healthy_result, (3 * (5 + 32), "0".to_string()));
+        // Only the message sent before the limit was hit, then RESOURCE_EXHAUSTED
+        assert_eq!(oversized_result, (5 + 16, "8".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_is_reset_after_idle_timeout() {
+        use http_body_util::BodyExt;
+        use std::time::Duration;
+        use tokio::time::Instant;
+
+        let idle = Duration::from_millis(50);
+        let (tx, body) = server_stream(64);
+        let mut tx = tx.with_idle_timeout(idle);
+
+        // Nothing reads the body, so once its buffer is full a send waits
+        let started = Instant::now();
+        let mut sent = 0;
+        let status = loop {
+            match tx.send_bytes(Bytes::from_static(b"tick")).await {
+                Ok(()) => sent += 1,
+                Err(status) => break status,
+            }
+        };
+        let elapsed = started.elapsed();
+        assert!(sent > 0);
+        assert_eq!(status.code(), Code::Cancelled);
+        assert!(elapsed >= idle, "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(500), "{elapsed:?}");
+        let again = tx.send_bytes(Bytes::from_static(b"late")).await;
+        assert_eq!(again.unwrap_err().code(), Code::Cancelled);
+
+        // The body ends with CANCELLED rather than the stale buffered messages
+        let collected = body.collect().await.unwrap();
+        let trailers = collected.trailers().cloned().unwrap();
+        assert_eq!(trailers["grpc-status"], "1");
+        assert!(collected.to_bytes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_send_capacity_drops_until_the_client_reads() {
+        use http_body_util::BodyExt;
+
+        let (mut tx, mut body) = server_stream(64);
+        let initial = tx.available_send_capacity();
+        assert!(initial > 2);
+
+        // Nothing has polled the body, so no window update has freed room
+        tx.send_bytes(Bytes::from_static(b"one")).await.unwrap();
+        tx.send_bytes(Bytes::from_static(b"two")).await.unwrap();
+        assert_eq!(tx.available_send_capacity(), initial - 2);
+
+        // The connection taking a message gives its credit back
+        let frame = body.frame().await.unwrap().unwrap();
+        assert!(frame.is_data());
+        assert_eq!(tx.available_send_capacity(), initial - 1);
+
+        // An ended stream has none
+        let too_large = Bytes::from(vec![0; 65]);
+        assert!(tx.send_bytes(too_large).await.is_err());
+        assert_eq!(tx.available_send_capacity(), 0);
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    #[cfg_attr(feature = "transcoding", derive(serde::Serialize, serde::Deserialize))]
+    struct Number {
+        #[prost(int64, tag = "1")]
+        value: i64,
+    }
+
+    fn client_stream_request(body: Vec<u8>) -> GrpcContext {
+        use h2per::context::Body;
+
+        let request = http::Request::builder()
+            .uri("/calc.Calculator/Sum")
+            .header("content-type", "application/grpc")
+            .body::<Body>(empty_body())
+            .unwrap();
+        let mut hyper_context = HyperContext::new_client(request);
+        hyper_context.request.body_bytes = Some(body);
+        GrpcContext::from_hyper_context(hyper_context).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_stream_sums_client_stream() {
+        use futures_util::StreamExt;
+
+        let mut body = Vec::new();
+        for value in [3, 4, 5] {
+            body.extend_from_slice(&GrpcContext::frame(&Number { value }.encode_to_vec()));
+        }
+        let req = client_stream_request(body);
+
+        let mut numbers = req.request_stream::<Number>();
+        let mut sum = 0;
+        while let Some(number) = numbers.next().await {
+            sum += number.unwrap().value;
+        }
+        assert_eq!(sum, 12);
+        assert!(numbers.next().await.is_none());
+
+        // A client that sends no messages yields an empty stream
+        let empty = client_stream_request(Vec::new());
+        assert_eq!(empty.request_stream::<Number>().count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_request_stream_errors_end_the_stream() {
+        use futures_util::StreamExt;
+
+        let whole = GrpcContext::frame(&Number { value: 7 }.encode_to_vec());
+        let cases = [
+            // Body cut off in the second frame's header
+            ([&whole[..], &whole[..3]].concat(), Code::Internal),
+            // Body cut off in the second frame's message
+            (
+                [&whole[..], &whole[..whole.len() - 1]].concat(),
+                Code::Internal,
+            ),
+            // Compressed message without compression support
+            ([&whole[..], &[1, 0, 0, 0, 0]].concat(), Code::Unimplemented),
+            // A frame that is not a Number
+            (
+                [&whole[..], &[0, 0, 0, 0, 1, 0xff]].concat(),
+                Code::InvalidArgument,
+            ),
+        ];
+
+        for (body, code) in cases {
+            let req = client_stream_request(body);
+            let results: Vec<_> = req.request_stream::<Number>().collect().await;
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0].as_ref().unwrap().value, 7);
+            assert_eq!(results[1].as_ref().unwrap_err().code(), code);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batched_stream_coalesces_small_messages() {
+        use futures_util::StreamExt;
+        use http_body_util::BodyExt;
+        use std::time::Duration;
+        use tokio::time::Instant;
+
+        async fn next_data(body: &mut ResponseStream) -> Bytes {
+            let frame = body.frame().await.unwrap().unwrap();
+            frame.into_data().unwrap()
+        }
+
+        let (mut tx, body) = server_stream(64);
+        let interval = Duration::from_millis(20);
+        let mut body = body.with_batching(64, interval);
+        let mut received = Vec::new();
+        // Each message is 7 bytes framed
+        for value in 1..=5 {
+            tx.send(&Number { value }).await.unwrap();
+        }
+        tx.flush().await.unwrap();
+        let data = next_data(&mut body).await;
+        assert_eq!(data.len(), 5 * 7);
+        received.extend_from_slice(&data);
+
+        // The tenth message takes the frame past the threshold
+        for value in 6..=15 {
+            tx.send(&Number { value }).await.unwrap();
+        }
+        let data = next_data(&mut body).await;
+        assert_eq!(data.len(), 10 * 7);
+        received.extend_from_slice(&data);
+
+        // A lone message goes out once the flush interval has passed
+        tx.send(&Number { value: 16 }).await.unwrap();
+        let started = Instant::now();
+        let data = next_data(&mut body).await;
+        assert_eq!(data.len(), 7);
+        let elapsed = started.elapsed();
+        assert!(elapsed >= interval, "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(500), "{elapsed:?}");
+        received.extend_from_slice(&data);
+
+        tx.finish(Status::new(Code::Ok, "")).await;
+        let frame = body.frame().await.unwrap().unwrap();
+        let trailers = frame.into_trailers().unwrap();
+        assert_eq!(trailers["grpc-status"], "0");
+
+        // Sixteen messages in three frames, each still decoded on its own
+        let req = client_stream_request(received);
+        let values: Vec<i64> = req
+            .request_stream::<Number>()
+            .map(|number| number.unwrap().value)
+            .collect()
+            .await;
+        assert_eq!(values, (1..=16).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_echo_stream_keeps_order_under_back_pressure() {
+        use futures_util::StreamExt;
+        use h2per::{StreamFuture, StreamService};
+        use http_body_util::BodyExt;
+        use hyper::body::Incoming;
+        use hyper_util::rt::{TokioExecutor, TokioIo};
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        const MESSAGES: i64 = 2000;
+        // Room for a few messages per stream at a time, in both directions
+        const WINDOW: u32 = 64;
+
+        for batching in [false, true] {
+            // Echoes each request message back as soon as it is decoded
+            let service: StreamService =
+                Arc::new(move |request: http::Request<Incoming>| -> StreamFuture {
+                    Box::pin(async move {
+                        let (parts, incoming) = request.into_parts();
+                        let received = incoming.collect().await.unwrap().to_bytes();
+                        let request = http::Request::from_parts(parts, empty_body());
+                        let mut hyper_context = HyperContext::new_client(request);
+                        hyper_context.request.body_bytes = Some(received.to_vec());
+                        let mut ctx = GrpcContext::from_hyper_context(hyper_context).unwrap();
+
+                        let (mut tx, body) = server_stream(64);
+                        let body = match batching {
+                            true => body.with_batching(48, Duration::from_millis(1)),
+                            false => body,
+                        };
+                        ctx.set_response_stream(body);
+                        let mut numbers = ctx.request_stream::<Number>();
+                        tokio::spawn(async move {
+                            while let Some(number) = numbers.next().await {
+                                tx.send(&number.unwrap()).await.unwrap();
+                            }
+                            tx.finish(Status::new(Code::Ok, "")).await;
+                        });
+                        let empty = http::Response::new(empty_body());
+                        Ok(std::mem::replace(
+                            &mut ctx.inner.response_mut().inner,
+                            empty,
+                        ))
+                    })
+                });
+
+            let (client_io, server_io) = tokio::io::duplex(1024);
+            let mut server = hyper::server::conn::http2::Builder::new(TokioExecutor::new());
+            server.initial_stream_window_size(WINDOW);
+            tokio::spawn(server.serve_connection(
+                TokioIo::new(server_io),
+                hyper::service::service_fn(move |request| service(request)),
+            ));
+            let (client, connection) = h2::client::Builder::new()
+                .initial_window_size(WINDOW)
+                .handshake(client_io)
+                .await
+                .unwrap();
+            tokio::spawn(connection);
+            let mut client = client.ready().await.unwrap();
+            let call = http::Request::builder()
+                .method("POST")
+                .uri("http://localhost/pkg.Svc/Echo")
+                .header("content-type", "application/grpc")
+                .body(())
+                .unwrap();
+            let (response, mut request_body) = client.send_request(call, false).unwrap();
+
+            // One DATA frame per message, sent as fast as h2 takes them
+            for value in 1..=MESSAGES {
+                let framed = GrpcContext::frame(&Number { value }.encode_to_vec());
+                request_body.send_data(framed, value == MESSAGES).unwrap();
+            }
+
+            // Read slowly, so the server keeps running out of window
+            let mut body = response.await.unwrap().into_body();
+            let mut received = Vec::new();
+            while let Some(data) = body.data().await {
+                let data = data.unwrap();
+                received.extend_from_slice(&data);
+                tokio::task::yield_now().await;
+                let _ = body.flow_control().release_capacity(data.len());
+            }
+            let trailers = body.trailers().await.unwrap().unwrap();
+            assert_eq!(trailers["grpc-status"], "0");
+
+            let values: Vec<i64> = client_stream_request(received)
+                .request_stream::<Number>()
+                .map(|number| number.unwrap().value)
+                .collect()
+                .await;
+            assert_eq!(values, (1..=MESSAGES).collect::<Vec<_>>(), "{batching}");
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Signup {
+        #[prost(string, tag = "1")]
+        email: String,
+        #[prost(int64, tag = "2")]
+        age: i64,
+    }
+
+    #[test]
+    fn test_validator_rejects_empty_required_field() {
+        use std::sync::Arc;
+
+        let validators = Arc::new(MessageValidators::new().register(|signup: &Signup| {
+            if signup.email.is_empty() {
+                return Err(vec![FieldViolation::new("email", "is required")]);
+            }
+            Ok(())
+        }));
+
+        let missing = Signup {
+            email: String::new(),
+            age: 30,
+        };
+        let req = client_stream_request(GrpcContext::frame(&missing.encode_to_vec()).to_vec())
+            .with_validators(validators.clone());
+        let status = req.decode_request::<Signup>().unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.message(), "invalid request: email: is required");
+
+        let valid = Signup {
+            email: "ada@example.com".to_string(),
+            age: 30,
+        };
+        let req = client_stream_request(GrpcContext::frame(&valid.encode_to_vec()).to_vec())
+            .with_validators(validators.clone());
+        assert_eq!(req.decode_request::<Signup>().unwrap(), valid);
+
+        // Types without a registered check pass through
+        let req = client_stream_request(
+            GrpcContext::frame(&Number { value: 1 }.encode_to_vec()).to_vec(),
+        )
+        .with_validators(validators);
+        assert_eq!(req.decode_request::<Number>().unwrap().value, 1);
+    }
+
+    #[tokio::test]
+    async fn test_stream_interceptor_sees_each_message() {
+        use futures_util::StreamExt;
+        use http_body_util::BodyExt;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        #[derive(Default)]
+        struct Counter {
+            inbound: AtomicUsize,
+            outbound: AtomicUsize,
+        }
+
+        impl StreamInterceptor for Counter {
+            fn on_inbound(&self, message: GrpcMessage) -> Option<GrpcMessage> {
+                self.inbound.fetch_add(1, Ordering::SeqCst);
+                Some(message)
+            }
+
+            fn on_outbound(&self, message: GrpcMessage) -> Option<GrpcMessage> {
+                self.outbound.fetch_add(1, Ordering::SeqCst);
+                Some(message)
+            }
+        }
+
+        let mut body = Vec::new();
+        for value in [3, 4, 5] {
+            body.extend_from_slice(&GrpcContext::frame(&Number { value }.encode_to_vec()));
+        }
+        let counter = Arc::new(Counter::default());
+        let mut req = client_stream_request(body).with_stream_interceptor(counter.clone());
+
+        // Three messages in
+        let numbers: Vec<i64> = req
+            .request_stream::<Number>()
+            .map(|number| number.unwrap().value)
+            .collect()
+            .await;
+        assert_eq!(numbers, [3, 4, 5]);
+        assert_eq!(counter.inbound.load(Ordering::SeqCst), 3);
+        assert_eq!(counter.outbound.load(Ordering::SeqCst), 0);
+
+        // Three messages out
+        let mut tx = req.server_stream(DEFAULT_MAX_SEND_MESSAGE_SIZE);
+        for value in numbers {
+            tx.send(&Number { value: value * 10 }).await.unwrap();
+        }
+        tx.finish(Status::new(Code::Ok, "")).await;
+        let body = req.inner.response.into_inner().into_body();
+        let sent = body.collect().await.unwrap().to_bytes();
+        assert_eq!(
+            RequestStream::<Number>::new(sent, None, None)
+                .map(|number| number.unwrap().value)
+                .collect::<Vec<_>>()
+                .await,
+            [30, 40, 50]
+        );
+        assert_eq!(counter.outbound.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stream_interceptor_can_rewrite_and_drop_messages() {
+        use futures_util::StreamExt;
+        use std::sync::Arc;
+
+        /// Drops odd numbers and zeroes the rest
+        struct Redact;
+
+        impl StreamInterceptor for Redact {
+            fn on_inbound(&self, message: GrpcMessage) -> Option<GrpcMessage> {
+                let number = Number::decode(message.body()?.clone()).ok()?;
+                (number.value % 2 == 0)
+                    .then(|| GrpcMessage::new(Number { value: 0 }.encode_to_vec().into()))
+            }
+        }
+
+        let mut body = Vec::new();
+        for value in [1, 2, 3, 4] {
+            body.extend_from_slice(&GrpcContext::frame(&Number { value }.encode_to_vec()));
+        }
+        let req = client_stream_request(body).with_stream_interceptor(Arc::new(Redact));
+        let numbers: Vec<i64> = req
+            .request_stream::<Number>()
+            .map(|number| number.unwrap().value)
+            .collect()
+            .await;
+        assert_eq!(numbers, [0, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_web_text_unary_round_trip() {
+        use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+        use h2per::context::Body;
+        use http_body_util::BodyExt;
+
+        // The browser base64-encodes the framed request
+        let framed = GrpcContext::frame(&Number { value: 21 }.encode_to_vec());
+        let text = BASE64.encode(&framed);
+
+        // Split inside a quantum, as across two DATA frames
+        let (head, tail) = text.as_bytes().split_at(6);
+        let mut decoder = WebTextDecoder::new();
+        let mut frames = decoder.decode(head).unwrap().to_vec();
+        frames.extend_from_slice(&decoder.decode(tail).unwrap());
+        decoder.finish().unwrap();
+        assert_eq!(frames, framed);
+
+        // Or padded and restarted between chunks
+        let restarted = BASE64.encode(&framed[..4]) + &BASE64.encode(&framed[4..]);
+        assert_eq!(web::decode_web_text(restarted.as_bytes()).unwrap(), framed);
+
+        let request = http::Request::builder()
+            .version(http::Version::HTTP_11)
+            .uri("/calc.Calculator/Double")
+            .header("content-type", "application/grpc-web-text+proto")
+            .body::<Body>(empty_body())
+            .unwrap();
+        assert_eq!(Admission::of(&request), Admission::Dispatch);
+        let mut hyper_context = HyperContext::new_client(request);
+        hyper_context.request.body_bytes = Some(text.into_bytes());
+
+        let mut req = GrpcContext::from_hyper_context(hyper_context).unwrap();
+        assert!(req.is_web_text());
+        let number: Number = req.decode_request().unwrap();
+        req.encode_response(Number {
+            value: number.value * 2,
+        })
+        .unwrap();
+        req.finalize_response();
+
+        let response = req.inner.response.into_inner();
+        assert_eq!(
+            response.headers()["content-type"],
+            "application/grpc-web-text+proto"
+        );
+        let text = response.into_body().collect().await.unwrap().to_bytes();
+        let body = BASE64.decode(&text).unwrap();
+
+        // One message frame, then the trailer frame
+        let message_len = u32::from_be_bytes(body[1..5].try_into().unwrap()) as usize;
+        assert_eq!(body[0], 0);
+        let reply = Number::decode(&body[5..5 + message_len]).unwrap();
+        assert_eq!(reply.value, 42);
+        let trailer = &body[5 + message_len..];
+        assert_eq!(trailer[0], 0x80);
+        assert_eq!(&trailer[5..], b"grpc-status:0\r\n");
+    }
+
+    #[test]
+    fn test_client_decodes_status_trailers() {
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert("grpc-status", "3".parse().unwrap());
+        trailers.insert(
+            "grpc-message",
+            "caf%C3%A9 %E2%98%95 100%25".parse().unwrap(),
+        );
+        trailers.insert("grpc-status-details-bin", "AQID".parse().unwrap());
+        let status = status_from_trailers(&trailers);
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.message(), "caf\u{e9} \u{2615} 100%");
+        assert_eq!(status.details(), &[1, 2, 3]);
+
+        // What the server sends decodes back to the same status
+        let sent = Status::with_details(Code::NotFound, "n\u{e4}me", Bytes::from_static(b"\xff"));
+        let received = status_from_trailers(&status_trailers(&sent));
+        assert_eq!(received.code(), Code::NotFound);
+        assert_eq!(received.message(), "n\u{e4}me");
+        assert_eq!(received.details(), b"\xff");
+
+        // Broken escapes are reported locally
+        trailers.insert("grpc-message", "bad %E".parse().unwrap());
+        let status = status_from_trailers(&trailers);
+        assert_eq!(status.code(), Code::Internal);
+        assert!(status.message().starts_with("Malformed grpc-message"));
+    }
+
+    /// Stands in for a `tonic-build` generated server
+    #[derive(Clone)]
+    struct DoublerServer;
+
+    impl tonic::server::NamedService for DoublerServer {
+        const NAME: &'static str = "calc.Doubler";
+    }
+
+    impl tower::Service<http02::Request<tonic::body::BoxBody>> for DoublerServer {
+        type Response = http02::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>> + Send>,
+        >;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http02::Request<tonic::body::BoxBody>) -> Self::Future {
+            Box::pin(async move {
+                if request.uri().path() != "/calc.Doubler/Double" {
+                    return Ok(Status::unimplemented("").to_http());
+                }
+                let double = tower::service_fn(|request: tonic::Request<Number>| async move {
+                    let value = request.into_inner().value * 2;
+                    Ok::<_, Status>(tonic::Response::new(Number { value }))
+                });
+                let mut grpc = tonic::server::Grpc::new(tonic::codec::ProstCodec::default());
+                Ok(grpc.unary(double, request).await)
+            })
+        }
+    }
+
+    fn http2_call(path: &str, message: &Number) -> HyperContext {
+        use h2per::context::Body;
+
+        let request = http::Request::builder()
+            .version(http::Version::HTTP_2)
+            .method("POST")
+            .uri(path)
+            .header("content-type", "application/grpc")
+            .body::<Body>(empty_body())
+            .unwrap();
+        let mut hyper_context = HyperContext::new_client(request);
+        hyper_context.request.body_bytes =
+            Some(GrpcContext::frame(&message.encode_to_vec()).to_vec());
+        hyper_context
+    }
+
+    #[tokio::test]
+    async fn test_tonic_service_beside_endpoint_handler() {
+        use http_body_util::BodyExt;
+
+        assert_eq!(
+            TonicService::<DoublerServer>::route(),
+            "/calc.Doubler/<**path:method>"
+        );
+        let tonic_service = TonicService::new(DoublerServer);
+
+        // The tonic service answers its own method
+        let ctx = tonic_service
+            .call(http2_call("/calc.Doubler/Double", &Number { value: 21 }))
+            .await;
+        let response = ctx.response.into_inner();
+        assert_eq!(response.status(), http::StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "application/grpc");
+        let collected = response.into_body().collect().await.unwrap();
+        let trailers = collected.trailers().cloned().unwrap();
+        assert_eq!(trailers["grpc-status"], "0");
+        let body = collected.to_bytes();
+        assert_eq!(Number::decode(&body[5..]).unwrap().value, 42);
+
+        // and keeps its own dispatch for the rest of its prefix
+        let ctx = tonic_service
+            .call(http2_call("/calc.Doubler/Halve", &Number { value: 21 }))
+            .await;
+        let headers = ctx.response.into_inner().headers().clone();
+        assert_eq!(
+            headers["grpc-status"],
+            (Code::Unimplemented as i32).to_string().as_str()
+        );
+
+        // An endpoint handler on another service's path is untouched
+        let endpoint = |hyper_context: HyperContext| async move {
+            let mut req = GrpcContext::from_hyper_context(hyper_context).unwrap();
+            let number: Number = req.decode_request().unwrap();
+            req.encode_response(Number {
+                value: number.value + 1,
+            })
+            .unwrap();
+            req
+        };
+        let req = endpoint(http2_call(
+            "/calc.Calculator/Increment",
+            &Number { value: 41 },
+        ))
+        .await;
+        assert_eq!(req.service(), "calc.Calculator");
+        let reply = req.response_body().unwrap();
+        assert_eq!(Number::decode(&reply[5..]).unwrap().value, 42);
+    }
+
+    #[cfg(feature = "transcoding")]
+    #[tokio::test]
+    async fn test_json_transcoding_calls_grpc_method() {
+        use h2per::context::Body;
+        use http_body_util::BodyExt;
+
+        fn json_post(body: &str) -> HyperContext {
+            let request = http::Request::builder()
+                .method("POST")
+                .uri("/v1/double")
+                .header("content-type", "application/json")
+                .body::<Body>(empty_body())
+                .unwrap();
+            let mut hyper_context = HyperContext::new_client(request);
+            hyper_context.set_body_bytes(body.as_bytes().to_vec());
+            hyper_context
+        }
+
+        async fn json_reply(ctx: HyperContext) -> (http::StatusCode, serde_json::Value) {
+            let response = ctx.response.into_inner();
+            let status = response.status();
+            assert_eq!(response.headers()["content-type"], "application/json");
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            (status, serde_json::from_slice(&body).unwrap())
+        }
+
+        let tonic_service = TonicService::new(DoublerServer);
+        let transcoder = |grpc_path: &str| {
+            let tonic_service = tonic_service.clone();
+            JsonTranscoder::<Number, Number>::new(grpc_path, move |ctx| {
+                let tonic_service = tonic_service.clone();
+                async move { tonic_service.call(ctx).await }
+            })
+        };
+
+        // The JSON body reaches the method as a message, and its reply
+        // comes back as JSON
+        let double = transcoder("/calc.Doubler/Double");
+        let (status, reply) = json_reply(double.call(json_post(r#"{"value": 21}"#)).await).await;
+        assert_eq!(status, http::StatusCode::OK);
+        assert_eq!(reply, serde_json::json!({ "value": 42 }));
+
+        // An empty body is the default message
+        let (status, reply) = json_reply(double.call(json_post("")).await).await;
+        assert_eq!(status, http::StatusCode::OK);
+        assert_eq!(reply, serde_json::json!({ "value": 0 }));
+
+        // JSON that does not decode never reaches the method
+        let (status, reply) = json_reply(double.call(json_post(r#"{"value": "#)).await).await;
+        assert_eq!(status, http::StatusCode::BAD_REQUEST);
+        assert_eq!(reply["code"], Code::InvalidArgument as i32);
+        assert!(reply["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Invalid JSON request"));
+
+        // A failed call answers its code with the mapped HTTP status
+        let halve = transcoder("/calc.Doubler/Halve");
+        let (status, reply) = json_reply(halve.call(json_post(r#"{"value": 21}"#)).await).await;
+        assert_eq!(status, http::StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(reply["code"], Code::Unimplemented as i32);
+    }
+
+    #[test]
+    fn test_unknown_method_and_unknown_service_get_distinct_messages() {
+        let registry = GrpcRegistry::new()
+            .service(
+                GrpcService::new("calc.Calculator")
+                    .method("Double")
+                    .method("Sum"),
+            )
+            .list_methods(true);
+        assert_eq!(
+            registry.resolve("/calc.Calculator/Double").unwrap().name,
+            "calc.Calculator"
+        );
+
+        let unknown_method = registry.resolve("/calc.Calculator/Halve").unwrap_err();
+        assert_eq!(unknown_method.code(), Code::Unimplemented);
+        assert_eq!(
+            unknown_method.message(),
+            "Unknown method Halve for service calc.Calculator; available methods: Double, Sum"
+        );
+
+        let unknown_service = registry.resolve("/calc.Abacus/Double").unwrap_err();
+        assert_eq!(unknown_service.code(), Code::Unimplemented);
+        assert_eq!(unknown_service.message(), "Unknown service calc.Abacus");
+
+        // Without method listing the service's methods are not revealed
+        let quiet =
+            GrpcRegistry::new().service(GrpcService::new("calc.Calculator").method("Double"));
+        assert_eq!(
+            quiet
+                .resolve("/calc.Calculator/Halve")
+                .unwrap_err()
+                .message(),
+            "Unknown method Halve for service calc.Calculator"
+        );
+
+        // As the fallback, the registry answers Trailers-Only
+        let ctx = registry.unimplemented(http2_call("/calc.Abacus/Double", &Number { value: 1 }));
+        let headers = ctx.response.into_inner().headers().clone();
+        assert_eq!(headers["grpc-status"], "12");
+        assert_eq!(headers["grpc-message"], "Unknown service calc.Abacus");
+    }
+
+    #[test]
+    fn test_grpc_code_http_status_mapping() {
+        use http::StatusCode;
+
+        let table = [
+            (Code::Ok, 200),
+            (Code::Cancelled, 499),
+            (Code::Unknown, 500),
+            (Code::InvalidArgument, 400),
+            (Code::DeadlineExceeded, 504),
+            (Code::NotFound, 404),
+            (Code::AlreadyExists, 409),
+            (Code::PermissionDenied, 403),
+            (Code::ResourceExhausted, 429),
+            (Code::FailedPrecondition, 400),
+            (Code::Aborted, 409),
+            (Code::OutOfRange, 400),
+            (Code::Unimplemented, 501),
+            (Code::Internal, 500),
+            (Code::Unavailable, 503),
+            (Code::DataLoss, 500),
+            (Code::Unauthenticated, 401),
+        ];
+        for (code, status) in table {
+            assert_eq!(grpc_code_to_http_status(code).as_u16(), status, "{code:?}");
+        }
+
+        // Back from HTTP, shared statuses pick the most general code and
+        // the rest fall back by class
+        let table = [
+            (200, Code::Ok),
+            (204, Code::Ok),
+            (400, Code::InvalidArgument),
+            (401, Code::Unauthenticated),
+            (403, Code::PermissionDenied),
+            (404, Code::NotFound),
+            (409, Code::Aborted),
+            (416, Code::OutOfRange),
+            (429, Code::ResourceExhausted),
+            (499, Code::Cancelled),
+            (500, Code::Internal),
+            (501, Code::Unimplemented),
+            (503, Code::Unavailable),
+            (504, Code::DeadlineExceeded),
+            (412, Code::FailedPrecondition),
+            (502, Code::Internal),
+            (302, Code::Unknown),
+        ];
+        for (status, code) in table {
+            let status = StatusCode::from_u16(status).unwrap();
+            assert_eq!(http_status_to_grpc_code(status), code, "{status}");
+        }
+
+        // Codes with a status of their own survive the round trip
+        for code in [
+            Code::Ok,
+            Code::Cancelled,
+            Code::InvalidArgument,
+            Code::DeadlineExceeded,
+            Code::NotFound,
+            Code::PermissionDenied,
+            Code::ResourceExhausted,
+            Code::Aborted,
+            Code::Unimplemented,
+            Code::Internal,
+            Code::Unavailable,
+            Code::Unauthenticated,
+        ] {
+            assert_eq!(
+                http_status_to_grpc_code(grpc_code_to_http_status(code)),
+                code
+            );
+        }
+    }
+
     #[test]
     fn test_transport_ids() {
         use crate::transport::{GrpcStream, GrpcTransport};
@@ -0,0 +1,137 @@
+//! Message size metrics for gRPC calls
+//!
+//! Reports the decoded request size and encoded response size of every call,
+//! keyed by method path (e.g. "/helloworld.Greeter/SayHello"). Sizes exclude
+//! the 5-byte gRPC frame header. For streaming RPCs the sizes accumulate over
+//! all messages of the call and are reported once when the call finishes.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Sink for per-method message size observations
+pub trait MessageSizeRecorder: Send + Sync {
+    /// Records the total decoded request size of one call
+    fn record_request_size(&self, method: &str, bytes: usize);
+
+    /// Records the total encoded response size of one call
+    fn record_response_size(&self, method: &str, bytes: usize);
+}
+
+/// A recorder set with `GrpcProtocol::with_size_recorder`, carried in the
+/// extensions of each request so every call's context picks it up
+#[derive(Clone)]
+pub(crate) struct SizeRecorder(pub(crate) Arc<dyn MessageSizeRecorder>);
+
+/// In-memory recorder keeping every observation per method path.
+///
+/// Useful as a histogram source for exporters and in tests.
+#[derive(Default)]
+pub struct MessageSizeHistogram {
+    requests: Mutex<HashMap<String, Vec<usize>>>,
+    responses: Mutex<HashMap<String, Vec<usize>>>,
+}
+
+impl MessageSizeHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recorded request sizes for a method path, in call order
+    pub fn request_sizes(&self, method: &str) -> Vec<usize> {
+        self.requests
+            .lock()
+            .unwrap()
+            .get(method)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Recorded response sizes for a method path, in call order
+    pub fn response_sizes(&self, method: &str) -> Vec<usize> {
+        self.responses
+            .lock()
+            .unwrap()
+            .get(method)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl MessageSizeRecorder for MessageSizeHistogram {
+    fn record_request_size(&self, method: &str, bytes: usize) {
+        self.requests
+            .lock()
+            .unwrap()
+            .entry(method.to_string())
+            .or_default()
+            .push(bytes);
+    }
+
+    fn record_response_size(&self, method: &str, bytes: usize) {
+        self.responses
+            .lock()
+            .unwrap()
+            .entry(method.to_string())
+            .or_default()
+            .push(bytes);
+    }
+}
+
+/// Per-call interceptor accumulating message sizes until the call finishes
+pub struct MessageSizeInterceptor {
+    recorder: Arc<dyn MessageSizeRecorder>,
+    method: String,
+    request_bytes: AtomicUsize,
+    response_bytes: AtomicUsize,
+    finished: AtomicBool,
+}
+
+impl MessageSizeInterceptor {
+    /// Creates an interceptor for one call to `method` (the full method path)
+    pub fn new(recorder: Arc<dyn MessageSizeRecorder>, method: impl Into<String>) -> Self {
+        Self {
+            recorder,
+            method: method.into(),
+            request_bytes: AtomicUsize::new(0),
+            response_bytes: AtomicUsize::new(0),
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    /// Method path the sizes are reported under
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Adds one decoded request message to the running total
+    pub fn on_request_message(&self, len: usize) {
+        self.request_bytes.fetch_add(len, Ordering::Relaxed);
+    }
+
+    /// Adds one encoded response message to the running total
+    pub fn on_response_message(&self, len: usize) {
+        self.response_bytes.fetch_add(len, Ordering::Relaxed);
+    }
+
+    /// Request bytes seen so far
+    pub fn request_bytes(&self) -> usize {
+        self.request_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Response bytes seen so far
+    pub fn response_bytes(&self) -> usize {
+        self.response_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Reports the accumulated totals. Only the first call has any effect.
+    pub fn finish(&self) {
+        if self.finished.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.recorder
+            .record_request_size(&self.method, self.request_bytes());
+        self.recorder
+            .record_response_size(&self.method, self.response_bytes());
+    }
+}
@@ -7,32 +7,105 @@ use async_trait::async_trait;
 use http::HeaderMap;
 use std::error::Error;
 use std::sync::Arc;
-use tokio::io::{BufReader, BufWriter, ReadHalf, WriteHalf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter, ReadHalf, WriteHalf};
 
 use hotaru_core::{
     app::application::App,
     connection::{Protocol, ProtocolRole, TcpConnectionStream},
+    protocol::Detection,
 };
 
+use crate::admission::{http1_bytes, Admission, GRPC_CONTENT_TYPE};
 use crate::context::GrpcContext;
-use h2per::HyperHttp2;
+use crate::metrics::{MessageSizeRecorder, SizeRecorder};
+use h2per::{HyperHttp1, HyperHttp2, StreamFuture, StreamService};
+use hyper::body::Incoming;
+
+/// Most request header lines read before answering an HTTP/1.x client
+const MAX_HTTP1_HEADER_LINES: usize = 100;
 
 /// gRPC protocol implementation that wraps tonic functionality
 #[derive(Clone)]
 pub struct GrpcProtocol {
     inner: HyperHttp2,
+    /// `inner` before any gRPC service was routed
+    base: HyperHttp2,
+    grpc_services: Vec<StreamService>,
+    path_prefix: Option<Arc<str>>,
     role: ProtocolRole,
 }
 
 impl GrpcProtocol {
     /// Creates a new gRPC protocol instance
     pub fn new(role: ProtocolRole) -> Self {
+        let base = HyperHttp2::new(role);
         Self {
-            inner: HyperHttp2::new(role),
+            inner: base.clone(),
+            base,
+            grpc_services: Vec::new(),
+            path_prefix: None,
             role,
         }
     }
 
+    /// Serves the `application/grpc*` streams of each connection with `service`
+    ///
+    /// Routing is per stream: one HTTP/2 connection can carry gRPC calls to
+    /// `service` and, say, JSON requests to the app's HTTP/2 routes. Without
+    /// it every stream goes to those routes.
+    pub fn with_grpc_service(mut self, service: StreamService) -> Self {
+        self.grpc_services.push(service);
+        self.route_grpc_services();
+        self
+    }
+
+    /// Strips `prefix` from the path of gRPC calls before they reach the
+    /// services of [`with_grpc_service`](Self::with_grpc_service)
+    ///
+    /// For services mounted behind a gateway or a version segment: with the
+    /// prefix `/v2`, a call to `/v2/package.Service/Method` is served as
+    /// `/package.Service/Method`. Calls without the prefix are served as
+    /// they are.
+    pub fn with_path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.path_prefix = Some(Arc::from(prefix.into()));
+        self.route_grpc_services();
+        self
+    }
+
+    /// Reports the message sizes of every call served to `recorder`
+    ///
+    /// Each call's [`GrpcContext`] starts out with the recorder attached,
+    /// whether the call goes to a service of
+    /// [`with_grpc_service`](Self::with_grpc_service) or to the app's
+    /// routes, so handlers need not call
+    /// [`GrpcContext::with_size_recorder`] themselves.
+    pub fn with_size_recorder(mut self, recorder: Arc<dyn MessageSizeRecorder>) -> Self {
+        self.base = self.base.with_request_extension(SizeRecorder(recorder));
+        self.route_grpc_services();
+        self
+    }
+
+    /// The service the streams of each connection go through, in front of
+    /// `fallback`
+    #[cfg(test)]
+    pub(crate) fn stream_router<S>(&self, fallback: S) -> h2per::ContentTypeRouter<S> {
+        self.inner.stream_router(fallback)
+    }
+
+    /// Rebuilds `inner` from `base`, so the prefix applies to services
+    /// added before it was set
+    fn route_grpc_services(&mut self) {
+        let mut inner = self.base.clone();
+        for service in &self.grpc_services {
+            let service = match &self.path_prefix {
+                Some(prefix) => strip_path_prefix(prefix.clone(), service.clone()),
+                None => service.clone(),
+            };
+            inner = inner.route_content_type(GRPC_CONTENT_TYPE, service);
+        }
+        self.inner = inner;
+    }
+
     /// Checks if the request headers indicate gRPC
     fn is_grpc_request(headers: &HeaderMap) -> bool {
         headers
@@ -43,6 +116,16 @@ impl GrpcProtocol {
     }
 }
 
+/// Wraps `service` so it sees request paths without `prefix`
+fn strip_path_prefix(prefix: Arc<str>, service: StreamService) -> StreamService {
+    Arc::new(
+        move |mut request: http::Request<Incoming>| -> StreamFuture {
+            GrpcContext::strip_path_prefix(&mut request, &prefix);
+            service(request)
+        },
+    )
+}
+
 #[async_trait]
 impl Protocol for GrpcProtocol {
     type Transport = ();
@@ -50,15 +133,11 @@ impl Protocol for GrpcProtocol {
     type Message = crate::transport::GrpcMessage;
     type Context = GrpcContext;
 
-    fn detect(initial_bytes: &[u8]) -> bool {
-        // First check if this looks like HTTP/2
-        if !HyperHttp2::detect(initial_bytes) {
-            return false;
-        }
-
+    fn detect(initial_bytes: &[u8]) -> Detection {
+        // gRPC is HTTP/2 on the wire; a partial preface stays NeedMoreData.
         // We'll do final gRPC detection based on headers in the service layer
         // since we need the full HTTP/2 request to check content-type
-        true
+        HyperHttp2::detect(initial_bytes)
     }
 
     fn role(&self) -> ProtocolRole {
@@ -76,3 +155,59 @@ impl Protocol for GrpcProtocol {
         self.inner.handle(reader, writer, app).await
     }
 }
+
+/// Answers HTTP/1.x connections on a gRPC-only listener
+///
+/// Register it after [`GrpcProtocol`] so clients that do not speak HTTP/2
+/// get `505 HTTP Version Not Supported` instead of a closed connection.
+#[derive(Clone)]
+pub struct GrpcHttp1Rejection {
+    role: ProtocolRole,
+}
+
+impl GrpcHttp1Rejection {
+    pub fn new(role: ProtocolRole) -> Self {
+        Self { role }
+    }
+}
+
+#[async_trait]
+impl Protocol for GrpcHttp1Rejection {
+    type Transport = ();
+    type Stream = ();
+    type Message = crate::transport::GrpcMessage;
+    type Context = GrpcContext;
+
+    fn detect(initial_bytes: &[u8]) -> Detection {
+        HyperHttp1::detect(initial_bytes)
+    }
+
+    fn role(&self) -> ProtocolRole {
+        self.role
+    }
+
+    async fn handle(
+        &mut self,
+        mut reader: BufReader<ReadHalf<TcpConnectionStream>>,
+        mut writer: BufWriter<WriteHalf<TcpConnectionStream>>,
+        _app: Arc<App>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // Read the request head so closing does not reset the connection
+        // before the client sees the response
+        let mut line = String::new();
+        for _ in 0..MAX_HTTP1_HEADER_LINES {
+            line.clear();
+            if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+                break;
+            }
+        }
+
+        let response = Admission::Http2Required(http::Version::HTTP_11)
+            .rejection()
+            .expect("HTTP/1.1 is always rejected");
+        writer.write_all(&http1_bytes(&response)).await?;
+        writer.flush().await?;
+        writer.shutdown().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+//! Retry and hedging policy for gRPC client calls
+//!
+//! Follows the gRPC retry model: an attempt failing with one of the
+//! configured retryable codes is retried with exponential backoff until
+//! `max_attempts` is reached or the call deadline would be exceeded. A
+//! client-streaming attempt is only retried if it failed before sending any
+//! message. With hedging enabled, further attempts are started every
+//! `hedging_delay` without waiting for earlier ones, or as soon as one fails
+//! with a retryable code, and the first successful response wins.
+//!
+//! A [`ConnectionTarget`](crate::ConnectionTarget) given a policy with
+//! [`with_retry_policy`](crate::ConnectionTarget::with_retry_policy) runs
+//! its [`call_with_retry`](crate::ConnectionTarget::call_with_retry) calls
+//! under it.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use tokio::time::Instant;
+use tonic::{Code, Status};
+
+/// Parallel attempts raced against each other instead of sequential retries
+///
+/// The policy's retryable codes are the non-fatal ones: they do not stop
+/// other in-flight attempts.
+#[derive(Debug, Clone)]
+pub struct HedgingPolicy {
+    /// Delay before starting each further attempt
+    pub hedging_delay: Duration,
+}
+
+/// gRPC retry policy for client calls
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    backoff_multiplier: f64,
+    retryable_codes: Vec<Code>,
+    hedging: Option<HedgingPolicy>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            backoff_multiplier: 2.0,
+            retryable_codes: vec![Code::Unavailable],
+            hedging: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy retrying `Unavailable` up to 3 attempts in total
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the total number of attempts, including the first one
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the backoff before the first retry and its upper bound
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets the factor the backoff grows by after every retry
+    ///
+    /// Factors below `1.0`, and NaN, are taken as `1.0`.
+    pub fn with_backoff_multiplier(mut self, multiplier: f64) -> Self {
+        self.backoff_multiplier = if multiplier.is_nan() {
+            1.0
+        } else {
+            multiplier.max(1.0)
+        };
+        self
+    }
+
+    /// Sets the status codes that trigger a retry
+    pub fn with_retryable_codes(mut self, codes: Vec<Code>) -> Self {
+        self.retryable_codes = codes;
+        self
+    }
+
+    /// Races attempts every `delay` instead of retrying sequentially.
+    ///
+    /// The retryable codes are used as the non-fatal codes of the hedge,
+    /// whether they are set before or after this call.
+    pub fn with_hedging(mut self, delay: Duration) -> Self {
+        self.hedging = Some(HedgingPolicy {
+            hedging_delay: delay,
+        });
+        self
+    }
+
+    /// Total number of attempts allowed
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Hedging configuration, if enabled
+    pub fn hedging(&self) -> Option<&HedgingPolicy> {
+        self.hedging.as_ref()
+    }
+
+    /// Whether a status code is retryable under this policy
+    pub fn is_retryable(&self, code: Code) -> bool {
+        self.retryable_codes.contains(&code)
+    }
+
+    /// Backoff before retry number `retry` (1-based)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = i32::try_from(retry.saturating_sub(1)).unwrap_or(i32::MAX);
+        let factor = self.backoff_multiplier.powi(exponent);
+        // Past what a `Duration` holds the cap applies anyway
+        Duration::try_from_secs_f64(self.initial_backoff.as_secs_f64() * factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+
+    /// Runs a unary or server-streaming call under this policy.
+    ///
+    /// `attempt` is invoked once per attempt. `deadline` is the remaining
+    /// time budget of the whole call; once it runs out the call fails with
+    /// `DeadlineExceeded`.
+    pub async fn call<T, F, Fut>(&self, deadline: Option<Duration>, attempt: F) -> Result<T, Status>
+    where
+        F: FnMut(CallAttempt) -> Fut,
+        Fut: Future<Output = Result<T, Status>>,
+    {
+        self.execute(false, deadline, attempt).await
+    }
+
+    /// Runs a client-streaming call under this policy.
+    ///
+    /// The attempt must call [`CallAttempt::record_message_sent`] for every
+    /// request message it sends; an attempt that failed after sending one
+    /// is not retried.
+    pub async fn call_streaming<T, F, Fut>(
+        &self,
+        deadline: Option<Duration>,
+        attempt: F,
+    ) -> Result<T, Status>
+    where
+        F: FnMut(CallAttempt) -> Fut,
+        Fut: Future<Output = Result<T, Status>>,
+    {
+        self.execute(true, deadline, attempt).await
+    }
+
+    async fn execute<T, F, Fut>(
+        &self,
+        client_streaming: bool,
+        deadline: Option<Duration>,
+        attempt: F,
+    ) -> Result<T, Status>
+    where
+        F: FnMut(CallAttempt) -> Fut,
+        Fut: Future<Output = Result<T, Status>>,
+    {
+        let deadline = deadline.map(|d| Instant::now() + d);
+        let run = async {
+            match &self.hedging {
+                Some(hedging) => self.run_hedged(hedging, deadline, attempt).await,
+                None => {
+                    self.run_sequential(client_streaming, deadline, attempt)
+                        .await
+                }
+            }
+        };
+        match deadline {
+            Some(at) => tokio::time::timeout_at(at, run)
+                .await
+                .unwrap_or_else(|_| Err(Status::deadline_exceeded("retry deadline exceeded"))),
+            None => run.await,
+        }
+    }
+
+    async fn run_sequential<T, F, Fut>(
+        &self,
+        client_streaming: bool,
+        deadline: Option<Instant>,
+        mut attempt: F,
+    ) -> Result<T, Status>
+    where
+        F: FnMut(CallAttempt) -> Fut,
+        Fut: Future<Output = Result<T, Status>>,
+    {
+        let mut number = 1;
+        loop {
+            let call = CallAttempt::new(number, deadline);
+            let status = match attempt(call.clone()).await {
+                Ok(value) => return Ok(value),
+                Err(status) => status,
+            };
+
+            let committed = client_streaming && call.messages_sent() > 0;
+            if committed || number >= self.max_attempts || !self.is_retryable(status.code()) {
+                return Err(status);
+            }
+
+            // Don't sleep past the deadline just to be cut off by it.
+            let backoff = self.backoff(number);
+            if deadline.is_some_and(|at| Instant::now() + backoff >= at) {
+                return Err(status);
+            }
+            tokio::time::sleep(backoff).await;
+            number += 1;
+        }
+    }
+
+    async fn run_hedged<T, F, Fut>(
+        &self,
+        hedging: &HedgingPolicy,
+        deadline: Option<Instant>,
+        mut attempt: F,
+    ) -> Result<T, Status>
+    where
+        F: FnMut(CallAttempt) -> Fut,
+        Fut: Future<Output = Result<T, Status>>,
+    {
+        let mut in_flight = FuturesUnordered::new();
+        in_flight.push(attempt(CallAttempt::new(1, deadline)));
+        let mut started = 1;
+        let mut last_error = None;
+        let next_hedge = tokio::time::sleep(hedging.hedging_delay);
+        tokio::pin!(next_hedge);
+
+        loop {
+            let can_hedge = started < self.max_attempts;
+
+            let start_next = tokio::select! {
+                result = in_flight.next(), if !in_flight.is_empty() => match result {
+                    Some(Ok(value)) => return Ok(value),
+                    Some(Err(status)) => {
+                        if !self.is_retryable(status.code()) {
+                            return Err(status);
+                        }
+                        last_error = Some(status);
+                        // A non-fatal failure doesn't wait for the delay
+                        true
+                    }
+                    None => false,
+                },
+                _ = &mut next_hedge, if can_hedge => true,
+            };
+
+            if start_next && can_hedge {
+                started += 1;
+                in_flight.push(attempt(CallAttempt::new(started, deadline)));
+                next_hedge
+                    .as_mut()
+                    .reset(Instant::now() + hedging.hedging_delay);
+            }
+
+            if in_flight.is_empty() && started >= self.max_attempts {
+                return Err(
+                    last_error.unwrap_or_else(|| Status::unavailable("all hedged attempts failed"))
+                );
+            }
+        }
+    }
+}
+
+/// Handle passed to each attempt of a call
+#[derive(Debug, Clone)]
+pub struct CallAttempt {
+    number: u32,
+    deadline: Option<Instant>,
+    messages_sent: Arc<AtomicUsize>,
+}
+
+impl CallAttempt {
+    fn new(number: u32, deadline: Option<Instant>) -> Self {
+        Self {
+            number,
+            deadline,
+            messages_sent: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Attempt number, starting at 1
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// Time left until the call deadline, if one is set
+    ///
+    /// Should be forwarded upstream as the `grpc-timeout` of the attempt.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|at| at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Marks one request message as sent on this attempt
+    pub fn record_message_sent(&self) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Request messages sent on this attempt so far
+    pub fn messages_sent(&self) -> usize {
+        self.messages_sent.load(Ordering::Relaxed)
+    }
+}
@@ -2,31 +2,131 @@
 //!
 //! This module provides the bridge between tonic services and Hotaru's endpoint system.
 
+use h2per::context::empty_body;
 use h2per::HyperContext;
+use http::header::CONTENT_TYPE;
+use http::{HeaderValue, StatusCode};
 use std::sync::Arc;
 use tonic::{Code, Status};
 
+use crate::admission::{Admission, GRPC_CONTENT_TYPE};
 use crate::context::GrpcContext;
+use crate::streaming::status_trailers;
 use hotaru_core::app::application::App;
+use hotaru_tls::PeerIdentity;
 
 /// gRPC service wrapper that integrates with Hotaru's service system
+#[derive(Debug)]
 pub struct GrpcService {
     /// Service name (e.g., "helloworld.Greeter")
     pub name: String,
+
+    /// Methods the service implements, e.g. "SayHello"
+    methods: Vec<String>,
+
+    /// Whether calls must carry a verified client certificate
+    require_client_auth: bool,
 }
 
 impl GrpcService {
     /// Creates a new gRPC service wrapper
     pub fn new(name: impl Into<String>) -> Self {
-        Self { name: name.into() }
+        Self {
+            name: name.into(),
+            methods: Vec::new(),
+            require_client_auth: false,
+        }
+    }
+
+    /// Declares a method the service implements
+    pub fn method(mut self, method: impl Into<String>) -> Self {
+        self.methods.push(method.into());
+        self
+    }
+
+    /// Methods declared with [`method`](Self::method)
+    pub fn methods(&self) -> &[String] {
+        &self.methods
+    }
+
+    /// Requires every call to come from a client with a verified certificate
+    ///
+    /// Pair it with a listener built from
+    /// `TlsConfig::builder().require_client_auth(ca)`, which refuses
+    /// clients without a certificate during the handshake. This check
+    /// catches calls that reach the service some other way (plain TCP, or
+    /// a listener with optional client auth) and answers them
+    /// `UNAUTHENTICATED`.
+    pub fn require_client_auth(mut self) -> Self {
+        self.require_client_auth = true;
+        self
+    }
+
+    /// Checks that the request is a gRPC call before it is dispatched
+    ///
+    /// Non-gRPC requests come back as `Err` with the HTTP error (415 or 505)
+    /// already set as the response, ready to be sent as is. With
+    /// [`require_client_auth`](Self::require_client_auth), calls without a
+    /// peer identity come back rejected as `UNAUTHENTICATED`.
+    pub fn admit(&self, mut hyper_context: HyperContext) -> Result<HyperContext, HyperContext> {
+        let request = hyper_context.request().as_inner();
+        let Some(rejection) = Admission::of(request).rejection() else {
+            if self.require_client_auth && request.extensions().get::<PeerIdentity>().is_none() {
+                let status = Status::new(Code::Unauthenticated, "client certificate required");
+                return Err(self.reject(hyper_context, &status));
+            }
+            return Ok(hyper_context);
+        };
+        let (parts, body) = rejection.into_parts();
+        let response = hyper_context.response_mut();
+        response.set_status(parts.status);
+        *response.headers_mut() = parts.headers;
+        response.set_body_bytes(body);
+        Err(hyper_context)
+    }
+
+    /// Reads the call's request body into memory so it can be decoded
+    ///
+    /// The server hands handlers the body unread. A body past the
+    /// listener's stream limit is refused as `RESOURCE_EXHAUSTED` and one
+    /// that cannot be read as `INTERNAL`, both Trailers-Only.
+    pub async fn read_body(
+        &self,
+        mut hyper_context: HyperContext,
+    ) -> Result<HyperContext, HyperContext> {
+        match hyper_context.request.body().await {
+            Ok(_) => Ok(hyper_context),
+            Err(status) => Err(self.reject(hyper_context, &body_status(status))),
+        }
+    }
+
+    /// Answers a call that is refused before its handler runs
+    ///
+    /// For interceptors and auth checks. The response is Trailers-Only, as
+    /// gRPC prescribes: `200` with `grpc-status` and `grpc-message` among
+    /// the headers and an empty body, so HTTP/2 sends it as a single
+    /// HEADERS frame that ends the stream, with no DATA frame.
+    pub fn reject(&self, mut hyper_context: HyperContext, status: &Status) -> HyperContext {
+        let response = hyper_context.response_mut();
+        response.set_status(StatusCode::OK);
+        let headers = response.headers_mut();
+        headers.clear();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(GRPC_CONTENT_TYPE));
+        headers.extend(status_trailers(status));
+        response.set_body_stream(empty_body());
+        hyper_context
     }
 
     /// Handles incoming gRPC requests by converting them to GrpcContext
     pub async fn handle_request(
         &self,
-        hyper_context: HyperContext,
+        mut hyper_context: HyperContext,
         _app: Arc<App>,
     ) -> Result<GrpcContext, Status> {
+        if let Err(status) = hyper_context.request.body().await {
+            return Err(body_status(status));
+        }
+
         // Convert HyperContext to GrpcContext
         let grpc_context = GrpcContext::from_hyper_context(hyper_context)?;
 
@@ -52,4 +152,86 @@ impl GrpcService {
     }
 }
 
-// Service trait implementation removed - will be handled by endpoint! macro integration
+/// The gRPC services an app serves
+///
+/// Register it as the fallback for gRPC routes: a call that reached no
+/// handler is answered `UNIMPLEMENTED`, with a message telling an unknown
+/// service apart from an unknown method of a known one. With
+/// [`list_methods`](Self::list_methods) the latter also names the methods
+/// the service does have, which helps while developing but tells callers
+/// more than they need in production.
+#[derive(Debug, Default)]
+pub struct GrpcRegistry {
+    services: Vec<GrpcService>,
+    list_methods: bool,
+}
+
+impl GrpcRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a service with its declared methods
+    pub fn service(mut self, service: GrpcService) -> Self {
+        self.services.push(service);
+        self
+    }
+
+    /// Names the available methods when a call asks for an unknown one
+    pub fn list_methods(mut self, enabled: bool) -> Self {
+        self.list_methods = enabled;
+        self
+    }
+
+    /// Checks that `path` names a registered method
+    ///
+    /// Unknown services, unknown methods and malformed paths all come back
+    /// as `UNIMPLEMENTED`, each with its own message.
+    pub fn resolve(&self, path: &str) -> Result<&GrpcService, Status> {
+        let (service, method) = GrpcContext::parse_grpc_path(path)?;
+        let Some(known) = self.services.iter().find(|s| s.name == service) else {
+            return Err(Status::new(
+                Code::Unimplemented,
+                format!("Unknown service {}", service),
+            ));
+        };
+        if known.methods.contains(&method) {
+            return Ok(known);
+        }
+
+        let mut message = format!("Unknown method {} for service {}", method, service);
+        if self.list_methods {
+            message.push_str("; available methods: ");
+            message.push_str(&known.methods.join(", "));
+        }
+        Err(Status::new(Code::Unimplemented, message))
+    }
+
+    /// Answers a gRPC call no handler took
+    ///
+    /// Non-gRPC requests get the HTTP error [`GrpcService::admit`] gives
+    /// them; calls get a Trailers-Only `UNIMPLEMENTED` from
+    /// [`resolve`](Self::resolve).
+    pub fn unimplemented(&self, hyper_context: HyperContext) -> HyperContext {
+        let gate = GrpcService::new("");
+        let hyper_context = match gate.admit(hyper_context) {
+            Ok(hyper_context) => hyper_context,
+            Err(rejected) => return rejected,
+        };
+        let status = match self.resolve(hyper_context.request().uri().path()) {
+            Err(status) => status,
+            // Registered, but nothing was mounted to serve it
+            Ok(_) => Status::new(Code::Unimplemented, "Method not implemented"),
+        };
+        gate.reject(hyper_context, &status)
+    }
+}
+
+/// The gRPC status for a request body that could not be buffered
+pub(crate) fn body_status(status: StatusCode) -> Status {
+    if status == StatusCode::PAYLOAD_TOO_LARGE {
+        Status::new(Code::ResourceExhausted, "request body too large")
+    } else {
+        Status::new(Code::Internal, "request body could not be read")
+    }
+}
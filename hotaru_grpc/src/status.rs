@@ -0,0 +1,92 @@
+//! Mapping between gRPC status codes and HTTP status codes
+//!
+//! A gateway exposing gRPC methods over HTTP/JSON answers with the HTTP
+//! status of the call's gRPC code, and a client of such a gateway reads the
+//! code back from the HTTP status. Both directions follow the mapping of
+//! `google.rpc.Code`, the one grpc-gateway and Google's HTTP APIs use:
+//!
+//! | gRPC code            | HTTP status |
+//! |----------------------|-------------|
+//! | `Ok`                 | 200         |
+//! | `Cancelled`          | 499         |
+//! | `Unknown`            | 500         |
+//! | `InvalidArgument`    | 400         |
+//! | `DeadlineExceeded`   | 504         |
+//! | `NotFound`           | 404         |
+//! | `AlreadyExists`      | 409         |
+//! | `PermissionDenied`   | 403         |
+//! | `ResourceExhausted`  | 429         |
+//! | `FailedPrecondition` | 400         |
+//! | `Aborted`            | 409         |
+//! | `OutOfRange`         | 400         |
+//! | `Unimplemented`      | 501         |
+//! | `Internal`           | 500         |
+//! | `Unavailable`        | 503         |
+//! | `DataLoss`           | 500         |
+//! | `Unauthenticated`    | 401         |
+//!
+//! Several codes share a status, so going back picks one code per status;
+//! see [`http_status_to_grpc_code`].
+//!
+//! This is not the mapping a gRPC client applies when a server answers a
+//! gRPC call with a non-200 HTTP status. That one is defined by the gRPC
+//! spec and handled by tonic.
+
+use http::StatusCode;
+use tonic::Code;
+
+/// `499 Client Closed Request`, which `http` has no constant for
+fn client_closed_request() -> StatusCode {
+    StatusCode::from_u16(499).expect("499 is a valid status code")
+}
+
+/// The HTTP status a gateway answers a call ending in `code` with
+pub fn grpc_code_to_http_status(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::Cancelled => client_closed_request(),
+        Code::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
+        Code::InvalidArgument => StatusCode::BAD_REQUEST,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists => StatusCode::CONFLICT,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::FailedPrecondition => StatusCode::BAD_REQUEST,
+        Code::Aborted => StatusCode::CONFLICT,
+        Code::OutOfRange => StatusCode::BAD_REQUEST,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+    }
+}
+
+/// The gRPC code of a gateway response with HTTP status `status`
+///
+/// The inverse of [`grpc_code_to_http_status`] where a status has a single
+/// code. Shared ones go to the most general code: 400 to
+/// `InvalidArgument`, 409 to `Aborted` and 500 to `Internal`. `416 Range
+/// Not Satisfiable` reads as `OutOfRange`. Other statuses fall back by
+/// class: 2xx is `Ok`, 4xx `FailedPrecondition`, 5xx `Internal`, and
+/// anything else `Unknown`.
+pub fn http_status_to_grpc_code(status: StatusCode) -> Code {
+    match status.as_u16() {
+        400 => Code::InvalidArgument,
+        401 => Code::Unauthenticated,
+        403 => Code::PermissionDenied,
+        404 => Code::NotFound,
+        409 => Code::Aborted,
+        416 => Code::OutOfRange,
+        429 => Code::ResourceExhausted,
+        499 => Code::Cancelled,
+        501 => Code::Unimplemented,
+        503 => Code::Unavailable,
+        504 => Code::DeadlineExceeded,
+        _ if status.is_success() => Code::Ok,
+        _ if status.is_client_error() => Code::FailedPrecondition,
+        _ if status.is_server_error() => Code::Internal,
+        _ => Code::Unknown,
+    }
+}
@@ -0,0 +1,681 @@
+//! Streaming requests and responses
+//!
+//! [`RequestStream`] decodes the messages of a client-streaming request.
+//! [`server_stream`] pairs a [`StreamSender`], which the endpoint writes
+//! messages to, with a [`ResponseStream`] body that hands them to HTTP/2 as
+//! gRPC frames and ends with the `grpc-status` trailers.
+//!
+//! Every stream enforces its own `max_send_message_size`. A message over the
+//! limit is not sent; the stream ends with `ResourceExhausted` instead, and
+//! only that stream. Other RPCs on the same HTTP/2 connection are unaffected.
+//!
+//! A [`StreamInterceptor`] sees each streamed message, inbound and outbound,
+//! and may rewrite or drop it. [`MessageDumpInterceptor`] logs each one.
+//!
+//! A server stream whose client stops reading without resetting it would
+//! leave its sender waiting forever. With an
+//! [idle timeout](StreamSender::with_idle_timeout), a send that makes no
+//! progress for that long ends the stream with `Cancelled` instead.
+//!
+//! Many small messages cost one DATA frame each. With
+//! [batching](ResponseStream::with_batching), the body coalesces the
+//! messages queued while the connection waits to send, e.g. on the client's
+//! flow-control window, into larger frames. The receiver still decodes them
+//! one by one, since every message keeps its own gRPC frame header.
+//!
+//! Messages keep their order in both directions. A [`RequestStream`] decodes
+//! the buffered request body front to back. A server stream passes its
+//! messages through one FIFO buffer, so flow control and batching only
+//! delay them: a send that finds the buffer full waits rather than letting
+//! a later message ahead. The only messages not delivered are those an
+//! interceptor drops and those still buffered when the stream is reset.
+//!
+//! ```rust,ignore
+//! let (mut tx, body) = server_stream(DEFAULT_MAX_SEND_MESSAGE_SIZE);
+//! req.set_response_stream(body);
+//! tokio::spawn(async move {
+//!     for item in items {
+//!         tx.send(&item).await?;
+//!     }
+//!     tx.finish(Status::ok("")).await;
+//!     Ok::<_, Status>(())
+//! });
+//! ```
+
+use std::convert::Infallible;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig, STANDARD_NO_PAD};
+use base64::engine::{DecodePaddingMode, Engine as _};
+use bytes::{Buf, Bytes, BytesMut};
+use futures_core::Stream;
+use http::{HeaderMap, HeaderValue};
+use http_body::{Body, Frame};
+use prost::Message;
+use tokio::sync::mpsc;
+use tokio::time::Sleep;
+use tonic::{Code, Status};
+
+use crate::context::GrpcContext;
+use crate::metrics::MessageSizeInterceptor;
+use crate::transport::GrpcMessage;
+
+/// Length of the gRPC frame header: compression flag and message length
+const FRAME_HEADER_LEN: usize = 5;
+
+/// Default limit on a single outgoing message (4 MiB), as in tonic
+pub const DEFAULT_MAX_SEND_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Messages buffered between the sender and the connection
+const STREAM_BUFFER: usize = 16;
+
+/// Decoder for binary trailers such as `grpc-status-details-bin`, which
+/// may arrive with or without padding
+const BASE64_INDIFFERENT: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Per-message hook for streaming calls
+///
+/// Unlike call-level checks such as [`GrpcService::reject`], it runs once
+/// per message: for each message of a [`RequestStream`] before it is
+/// decoded, and for each message given to a [`StreamSender`] before the size
+/// limit is checked and the message is framed. Returning `None` drops the
+/// message; a message whose body was taken is sent empty.
+///
+/// Both hooks pass messages through unchanged by default.
+///
+/// [`GrpcService::reject`]: crate::service::GrpcService::reject
+pub trait StreamInterceptor: Send + Sync {
+    /// Called for each request message received on the stream
+    fn on_inbound(&self, message: GrpcMessage) -> Option<GrpcMessage> {
+        Some(message)
+    }
+
+    /// Called for each response message sent on the stream
+    fn on_outbound(&self, message: GrpcMessage) -> Option<GrpcMessage> {
+        Some(message)
+    }
+}
+
+/// Stream interceptor logging [`GrpcMessage::debug_dump`] for each message
+///
+/// Messages pass through unchanged. The dumps go through hotaru_core's
+/// `debug_log!`, so they are only printed when hotaru_core is built with
+/// its `dev-log` (or `tracing`) feature; otherwise the interceptor does
+/// nothing and the dumps are never formatted.
+///
+/// ```rust,ignore
+/// let req = req.with_stream_interceptor(Arc::new(MessageDumpInterceptor));
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MessageDumpInterceptor;
+
+impl StreamInterceptor for MessageDumpInterceptor {
+    fn on_inbound(&self, message: GrpcMessage) -> Option<GrpcMessage> {
+        hotaru_core::debug_log!("gRPC inbound message: {}", message.debug_dump());
+        Some(message)
+    }
+
+    fn on_outbound(&self, message: GrpcMessage) -> Option<GrpcMessage> {
+        hotaru_core::debug_log!("gRPC outbound message: {}", message.debug_dump());
+        Some(message)
+    }
+}
+
+enum StreamItem {
+    Message(Bytes),
+    /// Asks a batching body to send what it holds
+    Flush,
+    End(Status),
+}
+
+/// Messages of a client-streaming request, decoded as `T`
+///
+/// Returned by [`GrpcContext::request_stream`]. The stream ends after the
+/// last complete message. A truncated frame ends it with `Internal`, a
+/// compressed message with `Unimplemented` and a message that does not
+/// decode as `T` with `InvalidArgument`, as [`GrpcContext::decode_request`]
+/// does; nothing is yielded after an error.
+///
+/// ```rust,ignore
+/// let mut numbers = req.request_stream::<Number>();
+/// let mut sum = 0;
+/// while let Some(number) = numbers.next().await {
+///     sum += number?.value;
+/// }
+/// ```
+pub struct RequestStream<T> {
+    body: Bytes,
+    size_interceptor: Option<Arc<MessageSizeInterceptor>>,
+    stream_interceptor: Option<Arc<dyn StreamInterceptor>>,
+    failed: bool,
+    _message: PhantomData<fn() -> T>,
+}
+
+impl<T: Message + Default> RequestStream<T> {
+    pub(crate) fn new(
+        body: Bytes,
+        size_interceptor: Option<Arc<MessageSizeInterceptor>>,
+        stream_interceptor: Option<Arc<dyn StreamInterceptor>>,
+    ) -> Self {
+        Self {
+            body,
+            size_interceptor,
+            stream_interceptor,
+            failed: false,
+            _message: PhantomData,
+        }
+    }
+
+    /// Decodes the next message, or `None` once the body is exhausted
+    ///
+    /// Messages dropped by the stream interceptor are skipped.
+    pub fn next_message(&mut self) -> Option<Result<T, Status>> {
+        loop {
+            if self.failed || self.body.is_empty() {
+                return None;
+            }
+            let message = match self.next_frame() {
+                Ok(message) => message,
+                Err(status) => {
+                    self.failed = true;
+                    return Some(Err(status));
+                }
+            };
+            let message = match &self.stream_interceptor {
+                Some(interceptor) => match interceptor.on_inbound(GrpcMessage::new(message)) {
+                    Some(message) => message.body.unwrap_or_default(),
+                    None => continue,
+                },
+                None => message,
+            };
+            let result = T::decode(message)
+                .map_err(|e| Status::new(Code::InvalidArgument, format!("Decode error: {}", e)));
+            self.failed = result.is_err();
+            return Some(result);
+        }
+    }
+
+    /// Splits the next message off the body, without its frame header
+    fn next_frame(&mut self) -> Result<Bytes, Status> {
+        if self.body.len() < FRAME_HEADER_LEN {
+            return Err(Status::new(
+                Code::Internal,
+                format!(
+                    "truncated gRPC frame header: {} of {FRAME_HEADER_LEN} bytes",
+                    self.body.len()
+                ),
+            ));
+        }
+        let compressed = self.body[0];
+        let length =
+            u32::from_be_bytes([self.body[1], self.body[2], self.body[3], self.body[4]]) as usize;
+        match compressed {
+            0 => {}
+            1 => {
+                return Err(Status::new(
+                    Code::Unimplemented,
+                    "compressed request messages are not supported",
+                ))
+            }
+            flag => {
+                return Err(Status::new(
+                    Code::Internal,
+                    format!("invalid gRPC compression flag {flag}"),
+                ))
+            }
+        }
+        let available = self.body.len() - FRAME_HEADER_LEN;
+        if available < length {
+            return Err(Status::new(
+                Code::Internal,
+                format!("truncated gRPC message: expected {length} bytes, got {available}"),
+            ));
+        }
+
+        self.body.advance(FRAME_HEADER_LEN);
+        let message = self.body.split_to(length);
+        if let Some(interceptor) = &self.size_interceptor {
+            interceptor.on_request_message(message.len());
+        }
+        Ok(message)
+    }
+}
+
+impl<T: Message + Default> Stream for RequestStream<T> {
+    type Item = Result<T, Status>;
+
+    fn poll_next(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // The body is already buffered, so decoding never waits
+        Poll::Ready(self.get_mut().next_message())
+    }
+}
+
+/// `ResourceExhausted` status for a message over `max_send_message_size`
+pub(crate) fn message_too_large(len: usize, max_send_message_size: usize) -> Status {
+    Status::new(
+        Code::ResourceExhausted,
+        format!(
+            "message of {} bytes exceeds max_send_message_size of {} bytes",
+            len, max_send_message_size
+        ),
+    )
+}
+
+/// Creates a server stream whose messages may be at most
+/// `max_send_message_size` bytes, before framing
+pub fn server_stream(max_send_message_size: usize) -> (StreamSender, ResponseStream) {
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+    let reset = Arc::new(OnceLock::new());
+    let started = Arc::new(AtomicBool::new(false));
+    let sender = StreamSender {
+        tx,
+        max_send_message_size,
+        interceptor: None,
+        idle_timeout: None,
+        reset: reset.clone(),
+        started: started.clone(),
+        closed: None,
+    };
+    let body = ResponseStream {
+        rx,
+        reset,
+        started,
+        batching: None,
+        pending: BytesMut::new(),
+        flush_timer: None,
+        ending: None,
+        finished: false,
+    };
+    (sender, body)
+}
+
+/// Writing half of a server stream
+///
+/// Dropping the sender without calling [`finish`](Self::finish) ends the
+/// stream with `OK`.
+pub struct StreamSender {
+    tx: mpsc::Sender<StreamItem>,
+    max_send_message_size: usize,
+    interceptor: Option<Arc<dyn StreamInterceptor>>,
+    /// Longest a send may wait for the client to take messages
+    idle_timeout: Option<Duration>,
+    /// Set when the sender gives up on a stalled client; the body ends with
+    /// it ahead of any messages still buffered
+    reset: Arc<OnceLock<Status>>,
+    /// Set once the first message is queued
+    started: Arc<AtomicBool>,
+    /// Status the stream already ended with, if any
+    closed: Option<Status>,
+}
+
+impl StreamSender {
+    /// Passes every message sent on this stream through `interceptor`
+    pub fn with_interceptor(mut self, interceptor: Arc<dyn StreamInterceptor>) -> Self {
+        self.interceptor = Some(interceptor);
+        self
+    }
+
+    /// Resets the stream with `Cancelled` when a send waits `timeout`
+    /// without the client taking any message
+    ///
+    /// Sends only wait once the stream's buffer is full, i.e. once the
+    /// client has stopped reading or its flow-control window is exhausted,
+    /// so a producer that is merely slow is never cut off. The timed-out
+    /// send returns the `Cancelled` status, as do later ones, and the body
+    /// ends with it the next time the connection polls it, dropping the
+    /// messages still buffered.
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Encodes and sends one message
+    pub async fn send<T: Message>(&mut self, message: &T) -> Result<(), Status> {
+        self.send_bytes(Bytes::from(message.encode_to_vec())).await
+    }
+
+    /// Sends one already serialized message
+    ///
+    /// A message over the size limit ends this stream with
+    /// `ResourceExhausted`, which is also returned; later sends fail with the
+    /// same status. A message the interceptor drops is not sent, and the
+    /// send still succeeds.
+    pub async fn send_bytes(&mut self, message: Bytes) -> Result<(), Status> {
+        if let Some(status) = &self.closed {
+            return Err(status.clone());
+        }
+
+        let message = match &self.interceptor {
+            Some(interceptor) => match interceptor.on_outbound(GrpcMessage::new(message)) {
+                Some(message) => message.body.unwrap_or_default(),
+                None => return Ok(()),
+            },
+            None => message,
+        };
+
+        if message.len() > self.max_send_message_size {
+            let status = message_too_large(message.len(), self.max_send_message_size);
+            let _ = self.tx.send(StreamItem::End(status.clone())).await;
+            self.closed = Some(status.clone());
+            return Err(status);
+        }
+
+        self.started.store(true, Ordering::Relaxed);
+        self.push(StreamItem::Message(GrpcContext::frame(&message)))
+            .await
+    }
+
+    /// Makes the body send the messages it holds for
+    /// [batching](ResponseStream::with_batching) right away
+    ///
+    /// Waits for room in the stream's buffer like a send, and fails the same
+    /// way once the stream has ended. Without batching no message is held,
+    /// so there is nothing to force out.
+    pub async fn flush(&mut self) -> Result<(), Status> {
+        if let Some(status) = &self.closed {
+            return Err(status.clone());
+        }
+        self.push(StreamItem::Flush).await
+    }
+
+    /// Queues `item` for the body, giving up after the idle timeout
+    async fn push(&mut self, item: StreamItem) -> Result<(), Status> {
+        let send = self.tx.send(item);
+        let sent = match self.idle_timeout {
+            None => send.await,
+            Some(idle) => match tokio::time::timeout(idle, send).await {
+                Ok(sent) => sent,
+                Err(_) => {
+                    let status = Status::new(
+                        Code::Cancelled,
+                        format!("client made no progress on the stream for {idle:?}"),
+                    );
+                    let _ = self.reset.set(status.clone());
+                    self.closed = Some(status.clone());
+                    return Err(status);
+                }
+            },
+        };
+        if sent.is_err() {
+            let status = Status::new(Code::Cancelled, "client closed the stream");
+            self.closed = Some(status.clone());
+            return Err(status);
+        }
+        Ok(())
+    }
+
+    /// Ends the stream with `status`
+    pub async fn finish(self, status: Status) {
+        if self.closed.is_none() {
+            let _ = self.tx.send(StreamItem::End(status)).await;
+        }
+    }
+
+    /// Number of messages that can be sent right now without waiting
+    ///
+    /// The connection only takes messages off this stream's buffer while
+    /// the client's HTTP/2 flow-control window has room, so this is the send
+    /// credit a producer sees: it drops with every send the client has not
+    /// yet made room for and recovers as the window opens. A producer can
+    /// check it before building an expensive message. Zero once the stream
+    /// has ended.
+    pub fn available_send_capacity(&self) -> usize {
+        if self.closed.is_some() {
+            return 0;
+        }
+        self.tx.capacity()
+    }
+
+    /// Configured limit on a single message
+    pub fn max_send_message_size(&self) -> usize {
+        self.max_send_message_size
+    }
+
+    /// Whether the stream has already ended with an error status
+    pub fn is_closed(&self) -> bool {
+        self.closed.is_some()
+    }
+}
+
+/// HTTP/2 response body of a server stream
+pub struct ResponseStream {
+    rx: mpsc::Receiver<StreamItem>,
+    reset: Arc<OnceLock<Status>>,
+    /// Shared with the sender; set once the first message is queued
+    started: Arc<AtomicBool>,
+    batching: Option<Batching>,
+    /// Framed messages held for the next DATA frame
+    pending: BytesMut,
+    /// Fires when the oldest pending message has waited the flush interval
+    flush_timer: Option<Pin<Box<Sleep>>>,
+    /// Status to end with once `pending` has gone out
+    ending: Option<Status>,
+    finished: bool,
+}
+
+/// Limits of [`ResponseStream::with_batching`]
+#[derive(Debug, Clone, Copy)]
+struct Batching {
+    max_bytes: usize,
+    flush_interval: Duration,
+}
+
+impl ResponseStream {
+    /// Coalesces queued messages into DATA frames of about `max_bytes`
+    ///
+    /// Each time the connection polls the body, it gets every message
+    /// queued since the last frame instead of only the first. A frame goes
+    /// out once it holds at least `max_bytes`, once its first message has
+    /// waited `flush_interval`, on [`StreamSender::flush`] and when the
+    /// stream ends. Messages are never split, so one over `max_bytes` is
+    /// sent whole.
+    pub fn with_batching(mut self, max_bytes: usize, flush_interval: Duration) -> Self {
+        self.batching = Some(Batching {
+            max_bytes,
+            flush_interval,
+        });
+        self
+    }
+
+    /// Flag the sender sets once it has queued the first message
+    pub(crate) fn started(&self) -> Arc<AtomicBool> {
+        self.started.clone()
+    }
+
+    /// A DATA frame of the pending messages
+    fn take_pending(&mut self) -> Frame<Bytes> {
+        self.flush_timer = None;
+        Frame::data(self.pending.split().freeze())
+    }
+}
+
+impl Body for ResponseStream {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+        if self.finished {
+            return Poll::Ready(None);
+        }
+
+        let status = loop {
+            if let Some(status) = self.reset.get() {
+                break status.clone();
+            }
+            if let Some(status) = self.ending.take() {
+                break status;
+            }
+
+            let item = match self.rx.poll_recv(cx) {
+                Poll::Pending => {
+                    let flush_due = match &mut self.flush_timer {
+                        Some(timer) => timer.as_mut().poll(cx).is_ready(),
+                        None => false,
+                    };
+                    if flush_due {
+                        return Poll::Ready(Some(Ok(self.take_pending())));
+                    }
+                    return Poll::Pending;
+                }
+                Poll::Ready(item) => item,
+            };
+            let status = match item {
+                Some(StreamItem::Message(framed)) => {
+                    let Some(batching) = self.batching else {
+                        return Poll::Ready(Some(Ok(Frame::data(framed))));
+                    };
+                    self.pending.extend_from_slice(&framed);
+                    if self.pending.len() >= batching.max_bytes {
+                        return Poll::Ready(Some(Ok(self.take_pending())));
+                    }
+                    if self.flush_timer.is_none() {
+                        self.flush_timer =
+                            Some(Box::pin(tokio::time::sleep(batching.flush_interval)));
+                    }
+                    continue;
+                }
+                Some(StreamItem::Flush) => {
+                    if !self.pending.is_empty() {
+                        return Poll::Ready(Some(Ok(self.take_pending())));
+                    }
+                    continue;
+                }
+                Some(StreamItem::End(status)) => status,
+                None => Status::new(Code::Ok, ""),
+            };
+
+            // Messages still held go out ahead of the trailers
+            if self.pending.is_empty() {
+                break status;
+            }
+            self.ending = Some(status);
+            return Poll::Ready(Some(Ok(self.take_pending())));
+        };
+
+        self.finished = true;
+        self.rx.close();
+        Poll::Ready(Some(Ok(Frame::trailers(status_trailers(&status)))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.finished
+    }
+}
+
+/// `grpc-status` / `grpc-message` trailers for `status`
+///
+/// The message is percent-encoded and the details, if any, are sent as
+/// unpadded base64 in `grpc-status-details-bin`, as the gRPC spec asks.
+pub fn status_trailers(status: &Status) -> HeaderMap {
+    let mut trailers = HeaderMap::new();
+    trailers.insert("grpc-status", HeaderValue::from(status.code() as i32));
+    if !status.message().is_empty() {
+        let mut message = String::new();
+        percent_encode(status.message(), &mut message);
+        if let Ok(message) = HeaderValue::from_str(&message) {
+            trailers.insert("grpc-message", message);
+        }
+    }
+    if !status.details().is_empty() {
+        let details = STANDARD_NO_PAD.encode(status.details());
+        if let Ok(details) = HeaderValue::from_str(&details) {
+            trailers.insert("grpc-status-details-bin", details);
+        }
+    }
+    trailers
+}
+
+/// The status a server reported in its trailers
+///
+/// For clients: the reverse of [`status_trailers`]. `grpc-message` is
+/// percent-decoded and `grpc-status-details-bin` base64-decoded, padded or
+/// not. Trailers without a valid `grpc-status`, or with an encoding that
+/// does not decode, give `INTERNAL` naming what was wrong.
+pub fn status_from_trailers(trailers: &HeaderMap) -> Status {
+    let Some(code) = trailers.get("grpc-status") else {
+        return Status::new(Code::Internal, "Missing grpc-status trailer");
+    };
+    let Some(code) = code.to_str().ok().and_then(|code| code.parse::<i32>().ok()) else {
+        return Status::new(
+            Code::Internal,
+            format!("Malformed grpc-status trailer: {:?}", code),
+        );
+    };
+
+    let message = match trailers.get("grpc-message") {
+        Some(message) => match percent_decode(message.as_bytes()) {
+            Ok(message) => message,
+            Err(e) => {
+                return Status::new(
+                    Code::Internal,
+                    format!("Malformed grpc-message trailer: {}", e),
+                )
+            }
+        },
+        None => String::new(),
+    };
+
+    let details = match trailers.get("grpc-status-details-bin") {
+        Some(details) => match BASE64_INDIFFERENT.decode(details.as_bytes()) {
+            Ok(details) => Bytes::from(details),
+            Err(e) => {
+                return Status::new(
+                    Code::Internal,
+                    format!("Malformed grpc-status-details-bin trailer: {}", e),
+                )
+            }
+        },
+        None => Bytes::new(),
+    };
+
+    Status::with_details(Code::from_i32(code), message, details)
+}
+
+/// Percent-encodes `grpc-message` the way the gRPC spec asks: printable
+/// ASCII other than `%` is kept, every other byte is escaped
+pub(crate) fn percent_encode(message: &str, out: &mut String) {
+    for &b in message.as_bytes() {
+        if (0x20..=0x7e).contains(&b) && b != b'%' {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+}
+
+/// Reverses [`percent_encode`]; the decoded bytes must be UTF-8
+fn percent_decode(encoded: &[u8]) -> Result<String, String> {
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut bytes = encoded.iter();
+    while let Some(&b) = bytes.next() {
+        if b != b'%' {
+            decoded.push(b);
+            continue;
+        }
+        let hex = [bytes.next(), bytes.next()];
+        let byte = match hex {
+            [Some(&hi), Some(&lo)] => std::str::from_utf8(&[hi, lo])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match byte {
+            Some(byte) => decoded.push(byte),
+            None => return Err("truncated or invalid percent escape".to_string()),
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| "not UTF-8 once decoded".to_string())
+}
@@ -0,0 +1,105 @@
+//! Client-side call timeouts
+//!
+//! A timeout bounds how long the client waits for a response, whatever
+//! deadline the server applies. It is sent to the server as the
+//! `grpc-timeout` header so the server can give up too, and enforced locally
+//! by [`with_timeout`]: when it fires, the in-flight call future is dropped,
+//! which resets its HTTP/2 stream, and the call fails with
+//! `DeadlineExceeded`.
+//!
+//! ```rust,ignore
+//! req.set_timeout(Some(Duration::from_millis(250)));
+//! let reply = req.run_with_timeout(client.say_hello(request)).await?;
+//! ```
+//!
+//! A server handler fanning out to several upstreams under its own
+//! deadline hands each call a slice of what is left with
+//! [`GrpcContext::deadline_budget`](crate::GrpcContext::deadline_budget):
+//!
+//! ```rust,ignore
+//! let budget = req.deadline_budget(3);
+//! for call in &mut calls {
+//!     call.set_timeout(budget);
+//! }
+//! ```
+
+use std::future::Future;
+use std::time::Duration;
+
+use tonic::Status;
+
+/// Longest value `grpc-timeout` can carry in one unit (8 ASCII digits)
+const MAX_TIMEOUT_VALUE: u128 = 99_999_999;
+
+/// Formats `timeout` as a `grpc-timeout` header value
+///
+/// Picks the finest unit that fits in 8 digits, so precision is only lost
+/// for long timeouts. Rounds up: the server must not give up earlier than
+/// the client.
+pub fn encode_grpc_timeout(timeout: Duration) -> String {
+    let nanos = timeout.as_nanos();
+    let units: [(u128, char); 6] = [
+        (1, 'n'),
+        (1_000, 'u'),
+        (1_000_000, 'm'),
+        (1_000_000_000, 'S'),
+        (60_000_000_000, 'M'),
+        (3_600_000_000_000, 'H'),
+    ];
+    for (scale, unit) in units {
+        let value = nanos.div_ceil(scale);
+        if value <= MAX_TIMEOUT_VALUE {
+            return format!("{value}{unit}");
+        }
+    }
+    format!("{MAX_TIMEOUT_VALUE}H")
+}
+
+/// Parses a `grpc-timeout` header value
+pub fn decode_grpc_timeout(value: &str) -> Option<Duration> {
+    if value.len() < 2 || value.len() > 9 {
+        return None;
+    }
+    let (digits, unit) = value.split_at(value.len() - 1);
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let amount: u64 = digits.parse().ok()?;
+    match unit {
+        "n" => Some(Duration::from_nanos(amount)),
+        "u" => Some(Duration::from_micros(amount)),
+        "m" => Some(Duration::from_millis(amount)),
+        "S" => Some(Duration::from_secs(amount)),
+        "M" => Some(Duration::from_secs(amount * 60)),
+        "H" => Some(Duration::from_secs(amount * 3600)),
+        _ => None,
+    }
+}
+
+/// Splits `remaining` evenly between `calls` outbound calls
+///
+/// `None` (no deadline) stays `None`; zero calls get the whole budget.
+pub fn split_budget(remaining: Option<Duration>, calls: usize) -> Option<Duration> {
+    let calls = u32::try_from(calls.max(1)).unwrap_or(u32::MAX);
+    remaining.map(|remaining| remaining / calls)
+}
+
+/// Runs `call`, failing with `DeadlineExceeded` if it takes longer than
+/// `timeout`
+///
+/// On timeout `call` is dropped, cancelling the request. `None` waits
+/// indefinitely.
+pub async fn with_timeout<T, Fut>(timeout: Option<Duration>, call: Fut) -> Result<T, Status>
+where
+    Fut: Future<Output = Result<T, Status>>,
+{
+    match timeout {
+        None => call.await,
+        Some(timeout) => match tokio::time::timeout(timeout, call).await {
+            Ok(result) => result,
+            Err(_) => Err(Status::deadline_exceeded(format!(
+                "call timed out after {timeout:?}"
+            ))),
+        },
+    }
+}
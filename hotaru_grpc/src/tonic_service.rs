@@ -0,0 +1,225 @@
+//! Serving tonic-generated services next to `endpoint!` handlers
+//!
+//! A service generated by `tonic-build` (e.g. `GreeterServer<MyGreeter>`)
+//! answers every method under `/{package.Service}/`. [`TonicService`]
+//! registers that prefix as one route on the app's HTTP/2 URL tree, so the
+//! service keeps its own method dispatch while every other path still goes
+//! to `endpoint!` handlers:
+//!
+//! ```rust,ignore
+//! TonicService::new(GreeterServer::new(MyGreeter::default())).mount(&APP)?;
+//!
+//! endpoint! {
+//!     APP.url("/shop.Checkout/PlaceOrder"),
+//!
+//!     pub place_order <GrpcProtocol> { /* ... */ }
+//! }
+//! ```
+//!
+//! tonic still speaks `http` 0.2, so requests and responses are converted
+//! at the boundary. Both bodies are buffered: h2per reads the whole request
+//! before dispatch, and a streamed response reaches the client once the
+//! tonic service has finished it.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use bytes::{Bytes, BytesMut};
+use futures_util::stream;
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use http_body::Frame;
+use http_body04::Body as _;
+use http_body_util::{BodyExt, StreamBody};
+use hyper::body::Incoming;
+use tonic::body::BoxBody as TonicBody;
+use tonic::server::NamedService;
+use tonic::{Code, Status};
+use tower::{Service, ServiceExt};
+
+use h2per::context::{box_body, full_body};
+use h2per::{HyperContext, HyperHttp2, StreamFuture, StreamService};
+use hotaru_core::app::application::App;
+use hotaru_core::extensions::ParamsClone;
+
+use crate::service::GrpcService;
+use crate::streaming::status_trailers;
+
+/// A tonic server mounted on a Hotaru app
+///
+/// `S` is what `tonic-build` generates for a service: a `tower` service
+/// over `http` 0.2 requests that never fails at the transport level and
+/// reports errors as gRPC statuses.
+#[derive(Clone)]
+pub struct TonicService<S> {
+    inner: S,
+}
+
+impl<S> TonicService<S>
+where
+    S: NamedService
+        + Service<
+            http02::Request<TonicBody>,
+            Response = http02::Response<TonicBody>,
+            Error = Infallible,
+        > + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send,
+{
+    pub fn new(service: S) -> Self {
+        Self { inner: service }
+    }
+
+    /// URL pattern covering every method of the service,
+    /// e.g. `/helloworld.Greeter/<**path:method>`
+    pub fn route() -> String {
+        format!("/{}/<**path:method>", S::NAME)
+    }
+
+    /// Registers [`route`](Self::route) on `app`'s HTTP/2 URL tree
+    ///
+    /// Paths of other services are left to the handlers registered for
+    /// them. Fails if the app has no HTTP/2 protocol or the route is
+    /// already taken.
+    pub fn mount(self, app: &App) -> Result<(), String> {
+        let root = app
+            .handler
+            .url::<HyperHttp2>()
+            .ok_or_else(|| format!("cannot mount {}: no HTTP/2 protocol registered", S::NAME))?;
+        let service = Arc::new(self);
+        let handler = move |ctx: HyperContext| {
+            let service = service.clone();
+            async move { service.call(ctx).await }
+        };
+        root.sub_url(
+            Self::route(),
+            Some(Arc::new(handler)),
+            None,
+            ParamsClone::default(),
+        )?;
+        Ok(())
+    }
+
+    /// The service as a [`StreamService`], for a listener that routes by
+    /// content type
+    ///
+    /// Handed to [`GrpcProtocol::with_grpc_service`](crate::GrpcProtocol::with_grpc_service),
+    /// it takes the `application/grpc*` streams of each HTTP/2 connection
+    /// while the app's `endpoint!` handlers keep the rest. The request body
+    /// is read in full first, as for a mounted service.
+    pub fn stream_service(self) -> StreamService {
+        let service = Arc::new(self);
+        Arc::new(move |request: http::Request<Incoming>| -> StreamFuture {
+            let service = service.clone();
+            Box::pin(async move {
+                let (parts, body) = request.into_parts();
+                let body = match body.collect().await {
+                    Ok(collected) => collected.to_bytes(),
+                    Err(_) => Bytes::new(),
+                };
+                let request = http::Request::from_parts(parts, full_body(body.clone()));
+                let mut hyper_context = HyperContext::new_client(request);
+                hyper_context.set_body_bytes(body.to_vec());
+                let hyper_context = service.call(hyper_context).await;
+                Ok(hyper_context.response.into_inner())
+            })
+        })
+    }
+
+    /// Runs one call through the tonic service
+    ///
+    /// The call is admitted like any gRPC route first; a request tonic
+    /// cannot be given ends as `INTERNAL`.
+    pub async fn call(&self, hyper_context: HyperContext) -> HyperContext {
+        let gate = GrpcService::new(S::NAME);
+        let hyper_context = match gate.admit(hyper_context) {
+            Ok(hyper_context) => hyper_context,
+            Err(rejected) => return rejected,
+        };
+        let mut hyper_context = match gate.read_body(hyper_context).await {
+            Ok(hyper_context) => hyper_context,
+            Err(rejected) => return rejected,
+        };
+
+        let request = match to_tonic_request(&hyper_context) {
+            Ok(request) => request,
+            Err(status) => return gate.reject(hyper_context, &status),
+        };
+        let response = match self.inner.clone().oneshot(request).await {
+            Ok(response) => response,
+            Err(never) => match never {},
+        };
+
+        let (parts, mut body) = response.into_parts();
+        let mut data = BytesMut::new();
+        let mut trailers = None;
+        while let Some(chunk) = body.data().await {
+            match chunk {
+                Ok(chunk) => data.extend_from_slice(&chunk),
+                Err(status) => {
+                    trailers = Some(status_trailers(&status));
+                    break;
+                }
+            }
+        }
+        if trailers.is_none() {
+            trailers = match body.trailers().await {
+                Ok(trailers) => trailers.as_ref().map(from_http02_headers),
+                Err(status) => Some(status_trailers(&status)),
+            };
+        }
+
+        let mut frames = Vec::new();
+        if !data.is_empty() {
+            frames.push(Ok::<_, Infallible>(Frame::data(data.freeze())));
+        }
+        if let Some(trailers) = trailers {
+            frames.push(Ok(Frame::trailers(trailers)));
+        }
+
+        let response = hyper_context.response_mut();
+        response.set_status(StatusCode::from_u16(parts.status.as_u16()).unwrap_or(StatusCode::OK));
+        *response.headers_mut() = from_http02_headers(&parts.headers);
+        response.set_body_stream(box_body(StreamBody::new(stream::iter(frames))));
+        hyper_context
+    }
+}
+
+/// Rebuilds the buffered request as the `http` 0.2 request tonic expects
+fn to_tonic_request(hyper_context: &HyperContext) -> Result<http02::Request<TonicBody>, Status> {
+    let request = hyper_context.request().as_inner();
+    let body = hyper_context
+        .request()
+        .body_bytes
+        .clone()
+        .unwrap_or_default();
+
+    let mut builder = http02::Request::builder()
+        .method(request.method().as_str())
+        .uri(request.uri().to_string())
+        .version(http02::Version::HTTP_2);
+    for (name, value) in request.headers() {
+        builder = builder.header(name.as_str(), value.as_bytes());
+    }
+    builder
+        .body(
+            http_body04::Full::new(Bytes::from(body))
+                .map_err(|never| match never {})
+                .boxed_unsync(),
+        )
+        .map_err(|e| Status::new(Code::Internal, format!("Cannot pass call to tonic: {}", e)))
+}
+
+pub(crate) fn from_http02_headers(headers: &http02::HeaderMap) -> HeaderMap {
+    let mut converted = HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_str().as_bytes()),
+            HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            converted.append(name, value);
+        }
+    }
+    converted
+}
@@ -0,0 +1,265 @@
+//! HTTP/JSON transcoding of gRPC methods
+//!
+//! Exposes a gRPC method on a plain HTTP route for clients that speak JSON.
+//! A [`JsonTranscoder`] is built for one method path and mounted on the HTTP
+//! path it should answer. Each request then goes through these steps:
+//!
+//! 1. The JSON body is decoded into the method's request message. An empty
+//!    body is the default message.
+//! 2. The message is framed and the gRPC handler is called with it, as if
+//!    it had arrived over HTTP/2.
+//! 3. The reply is decoded and sent back as JSON, with the HTTP status of
+//!    the call's gRPC code (see [`crate::status`]).
+//!
+//! ```rust,ignore
+//! let greeter = TonicService::new(GreeterServer::new(MyGreeter::default()));
+//! JsonTranscoder::<HelloRequest, HelloReply>::new(
+//!     "/helloworld.Greeter/SayHello",
+//!     move |ctx| {
+//!         let greeter = greeter.clone();
+//!         async move { greeter.call(ctx).await }
+//!     },
+//! )
+//! .mount(&APP, "/v1/hello")?;
+//! ```
+//!
+//! The messages need serde's `Serialize`/`Deserialize` next to their prost
+//! derive, e.g. through `tonic-build`'s `type_attribute`. Failed calls answer
+//! `{"code": <gRPC code>, "message": "..."}`. Request headers are passed on
+//! as metadata, and response metadata comes back as headers.
+//!
+//! Only unary methods are transcoded. A reply carrying more than one
+//! message is answered with the first.
+//!
+//! Needs the `transcoding` feature.
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use http::{HeaderMap, HeaderValue, Method, StatusCode, Version};
+use http_body_util::{BodyExt, Full};
+use prost::Message;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tonic::{Code, Status};
+
+use h2per::context::full_body;
+use h2per::{HyperContext, HyperHttp1, HyperHttp2};
+use hotaru_core::app::application::App;
+use hotaru_core::extensions::ParamsClone;
+
+use crate::admission::GRPC_CONTENT_TYPE;
+use crate::context::GrpcContext;
+use crate::service::body_status;
+use crate::status::grpc_code_to_http_status;
+use crate::streaming::status_from_trailers;
+
+/// Content type of transcoded requests and replies
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+
+type GrpcCall =
+    Arc<dyn Fn(HyperContext) -> Pin<Box<dyn Future<Output = HyperContext> + Send>> + Send + Sync>;
+
+/// A gRPC method served as HTTP/JSON
+///
+/// `Req` and `Resp` are the method's request and reply messages. The
+/// handler is called the way the HTTP/2 URL tree calls one, with a
+/// `HyperContext` holding the framed request, and answers with the gRPC
+/// response in the context, as [`TonicService::call`](crate::TonicService::call)
+/// does.
+pub struct JsonTranscoder<Req, Resp> {
+    grpc_path: Arc<str>,
+    call: GrpcCall,
+    messages: PhantomData<fn(Req) -> Resp>,
+}
+
+impl<Req, Resp> Clone for JsonTranscoder<Req, Resp> {
+    fn clone(&self) -> Self {
+        Self {
+            grpc_path: self.grpc_path.clone(),
+            call: self.call.clone(),
+            messages: PhantomData,
+        }
+    }
+}
+
+impl<Req, Resp> JsonTranscoder<Req, Resp>
+where
+    Req: Message + Default + DeserializeOwned + 'static,
+    Resp: Message + Default + Serialize + 'static,
+{
+    /// Transcodes calls to the method at `grpc_path`, e.g.
+    /// `/helloworld.Greeter/SayHello`, handled by `handler`
+    pub fn new<F, Fut>(grpc_path: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(HyperContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HyperContext> + Send + 'static,
+    {
+        Self {
+            grpc_path: Arc::from(grpc_path.into()),
+            call: Arc::new(move |ctx| Box::pin(handler(ctx))),
+            messages: PhantomData,
+        }
+    }
+
+    /// The gRPC method path calls are transcoded to
+    pub fn grpc_path(&self) -> &str {
+        &self.grpc_path
+    }
+
+    /// Registers `http_path` on `app`'s HTTP/1 and HTTP/2 URL trees
+    ///
+    /// Fails if the app has neither protocol or the path is already taken.
+    pub fn mount(self, app: &App, http_path: impl Into<String>) -> Result<(), String> {
+        let http_path = http_path.into();
+        let transcoder = Arc::new(self);
+        let handler = move |ctx: HyperContext| {
+            let transcoder = transcoder.clone();
+            async move { transcoder.call(ctx).await }
+        };
+
+        let mut mounted = false;
+        if let Some(root) = app.handler.url::<HyperHttp1>() {
+            root.sub_url(
+                http_path.clone(),
+                Some(Arc::new(handler.clone())),
+                None,
+                ParamsClone::default(),
+            )?;
+            mounted = true;
+        }
+        if let Some(root) = app.handler.url::<HyperHttp2>() {
+            root.sub_url(
+                http_path.clone(),
+                Some(Arc::new(handler)),
+                None,
+                ParamsClone::default(),
+            )?;
+            mounted = true;
+        }
+        if !mounted {
+            return Err(format!(
+                "cannot mount {}: no HTTP protocol registered",
+                http_path
+            ));
+        }
+        Ok(())
+    }
+
+    /// Runs one HTTP/JSON request through the gRPC method
+    pub async fn call(&self, mut ctx: HyperContext) -> HyperContext {
+        let body = match ctx.request.body().await {
+            Ok(body) => body.to_vec(),
+            Err(status) => return json_error(ctx, &body_status(status)),
+        };
+        let message = if body.iter().all(u8::is_ascii_whitespace) {
+            Ok(Req::default())
+        } else {
+            serde_json::from_slice::<Req>(&body).map_err(|e| {
+                Status::new(
+                    Code::InvalidArgument,
+                    format!("Invalid JSON request: {}", e),
+                )
+            })
+        };
+        let message = match message {
+            Ok(message) => message,
+            Err(status) => return json_error(ctx, &status),
+        };
+
+        let grpc = match self.grpc_request(&mut ctx, &message) {
+            Ok(grpc) => grpc,
+            Err(status) => return json_error(ctx, &status),
+        };
+        let response = (self.call)(grpc).await.response.into_inner();
+
+        let (parts, body) = response.into_parts();
+        let collected = match body.collect().await {
+            Ok(collected) => collected,
+            Err(never) => match never {},
+        };
+        // A call that fails before its first message answers Trailers-Only
+        let status = if parts.headers.contains_key("grpc-status") {
+            status_from_trailers(&parts.headers)
+        } else {
+            status_from_trailers(collected.trailers().unwrap_or(&HeaderMap::new()))
+        };
+        if status.code() != Code::Ok {
+            return json_error(ctx, &status);
+        }
+
+        let data = collected.to_bytes();
+        let reply = match GrpcContext::deframe(&data) {
+            Some(payload) => Resp::decode(payload)
+                .map_err(|e| Status::new(Code::Internal, format!("Decode error: {}", e))),
+            None => Err(Status::new(Code::Internal, "Missing response message")),
+        };
+        let json = match reply.and_then(|reply| {
+            serde_json::to_vec(&reply)
+                .map_err(|e| Status::new(Code::Internal, format!("Encode error: {}", e)))
+        }) {
+            Ok(json) => json,
+            Err(status) => return json_error(ctx, &status),
+        };
+
+        let response = ctx.response_mut();
+        for (name, value) in &parts.headers {
+            if name != CONTENT_TYPE && !name.as_str().starts_with("grpc-") {
+                response.headers_mut().append(name, value.clone());
+            }
+        }
+        response.set_status(StatusCode::OK);
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(JSON_CONTENT_TYPE));
+        response.set_body(json);
+        ctx
+    }
+
+    /// Builds the HTTP/2 gRPC request the handler sees for `message`
+    ///
+    /// The request's headers become metadata, and its extensions, such as
+    /// the peer's identity, move over with it.
+    fn grpc_request(&self, ctx: &mut HyperContext, message: &Req) -> Result<HyperContext, Status> {
+        let framed = GrpcContext::frame(&message.encode_to_vec());
+        let mut builder = http::Request::builder()
+            .version(Version::HTTP_2)
+            .method(Method::POST)
+            .uri(&*self.grpc_path);
+        for (name, value) in ctx.request().headers() {
+            if name != CONTENT_TYPE && name != CONTENT_LENGTH {
+                builder = builder.header(name, value.clone());
+            }
+        }
+        let mut request = builder
+            .header(CONTENT_TYPE, GRPC_CONTENT_TYPE)
+            .header("te", "trailers")
+            .body(full_body(framed.clone()))
+            .map_err(|e| Status::new(Code::Internal, format!("Cannot build gRPC call: {}", e)))?;
+        *request.extensions_mut() = std::mem::take(ctx.request.as_inner_mut().extensions_mut());
+
+        let mut grpc = HyperContext::new_client(request);
+        grpc.set_body_bytes(framed.to_vec());
+        grpc.app = ctx.app.clone();
+        Ok(grpc)
+    }
+}
+
+/// Answers `status` as a JSON error with its mapped HTTP status
+fn json_error(mut ctx: HyperContext, status: &Status) -> HyperContext {
+    let body = serde_json::json!({
+        "code": status.code() as i32,
+        "message": status.message(),
+    });
+    let response = ctx.response_mut();
+    response.set_status(grpc_code_to_http_status(status.code()));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(JSON_CONTENT_TYPE));
+    response.set_body_bytes(Bytes::from(body.to_string()));
+    ctx
+}
@@ -5,6 +5,7 @@
 use bytes::{Bytes, BytesMut};
 use std::any::Any;
 use std::error::Error;
+use std::fmt::Write as _;
 
 use h2per::stream::Http2Stream;
 use h2per::transport::Http2Transport;
@@ -16,6 +17,9 @@ pub struct GrpcMessage {
     /// The protobuf message body
     pub body: Option<Bytes>,
 
+    /// Compression flag of the message's frame
+    pub compressed: bool,
+
     /// gRPC specific metadata
     pub grpc_status: Option<u32>,
     pub grpc_message: Option<String>,
@@ -26,6 +30,7 @@ impl GrpcMessage {
     pub fn new(body: Bytes) -> Self {
         Self {
             body: Some(body),
+            compressed: false,
             grpc_status: None,
             grpc_message: None,
         }
@@ -35,6 +40,7 @@ impl GrpcMessage {
     pub fn error(code: u32, message: impl Into<String>) -> Self {
         Self {
             body: None,
+            compressed: false,
             grpc_status: Some(code),
             grpc_message: Some(message.into()),
         }
@@ -56,14 +62,58 @@ impl GrpcMessage {
     pub fn set_body(&mut self, body: Bytes) {
         self.body = Some(body);
     }
+
+    /// Marks the message body as compressed
+    pub fn with_compressed(mut self, compressed: bool) -> Self {
+        self.compressed = compressed;
+        self
+    }
+
+    /// One-line description of the message for debugging framing issues
+    ///
+    /// Gives the compression flag and length of the frame header and a hex
+    /// preview of the first [`DEBUG_DUMP_PREVIEW`] bytes of the body,
+    /// followed by the status if one is set:
+    ///
+    /// ```text
+    /// compressed=0 length=5 body=0a 03 66 6f 6f
+    /// ```
+    pub fn debug_dump(&self) -> String {
+        let mut dump = format!("compressed={}", u8::from(self.compressed));
+        match self.body() {
+            Some(body) => {
+                let _ = write!(dump, " length={} body=", body.len());
+                for (i, byte) in body.iter().take(DEBUG_DUMP_PREVIEW).enumerate() {
+                    if i > 0 {
+                        dump.push(' ');
+                    }
+                    let _ = write!(dump, "{byte:02x}");
+                }
+                if body.len() > DEBUG_DUMP_PREVIEW {
+                    dump.push_str(" ...");
+                }
+            }
+            None => dump.push_str(" length=0 body=none"),
+        }
+        if let Some(code) = self.grpc_status {
+            let _ = write!(dump, " grpc-status={code}");
+        }
+        if let Some(message) = &self.grpc_message {
+            let _ = write!(dump, " grpc-message={message:?}");
+        }
+        dump
+    }
 }
 
+/// Body bytes shown by [`GrpcMessage::debug_dump`]
+pub const DEBUG_DUMP_PREVIEW: usize = 32;
+
 impl Message for GrpcMessage {
     fn encode(&self, buf: &mut BytesMut) -> Result<(), Box<dyn Error + Send + Sync>> {
         // Encode gRPC message with framing
         if let Some(body) = self.body() {
             // gRPC framing: 1 byte compression flag + 4 bytes length + message
-            buf.extend_from_slice(&[0]); // No compression
+            buf.extend_from_slice(&[u8::from(self.compressed)]);
             buf.extend_from_slice(&(body.len() as u32).to_be_bytes());
             buf.extend_from_slice(body);
         }
@@ -87,10 +137,11 @@ impl Message for GrpcMessage {
         }
 
         // Parse gRPC frame header
-        let _compression_flag = buf[0];
+        let compressed = buf[0] != 0;
         let length = u32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]) as usize;
 
-        // Check if we have the complete message
+        // Check if we have the complete message. A zero-length frame (e.g.
+        // google.protobuf.Empty) is complete once the header is in.
         if buf.len() < 5 + length {
             return Ok(None); // Need more data
         }
@@ -99,7 +150,7 @@ impl Message for GrpcMessage {
         let _ = buf.split_to(5); // Skip header
         let body = buf.split_to(length).freeze();
 
-        Ok(Some(GrpcMessage::new(body)))
+        Ok(Some(GrpcMessage::new(body).with_compressed(compressed)))
     }
 }
 
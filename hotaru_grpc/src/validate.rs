@@ -0,0 +1,108 @@
+//! Request message validation
+//!
+//! A [`MessageValidators`] registry holds at most one check per message
+//! type. Attached to a call with
+//! [`GrpcContext::with_validators`](crate::GrpcContext::with_validators), it
+//! runs on every message [`decode_request`](crate::GrpcContext::decode_request)
+//! returns, so a handler only ever sees requests that passed. A failed check
+//! answers the call with `INVALID_ARGUMENT`, naming each offending field.
+//!
+//! ```rust,ignore
+//! let validators = MessageValidators::new().register(|req: &HelloRequest| {
+//!     if req.name.is_empty() {
+//!         return Err(vec![FieldViolation::new("name", "is required")]);
+//!     }
+//!     Ok(())
+//! });
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+
+use prost::Message;
+use tonic::{Code, Status};
+
+/// One field that failed validation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldViolation {
+    /// Field path, e.g. "name" or "address.zip"
+    pub field: String,
+
+    /// What is wrong with the field
+    pub description: String,
+}
+
+impl FieldViolation {
+    pub fn new(field: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            description: description.into(),
+        }
+    }
+}
+
+impl fmt::Display for FieldViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.description)
+    }
+}
+
+type Validator = dyn Fn(&dyn Any) -> Result<(), Vec<FieldViolation>> + Send + Sync;
+
+/// Validation checks for request messages, keyed by message type
+#[derive(Default)]
+pub struct MessageValidators {
+    validators: HashMap<TypeId, Box<Validator>>,
+}
+
+impl MessageValidators {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the check for messages of type `T`
+    ///
+    /// Replaces any check registered for `T` before. Returning an empty
+    /// list of violations counts as a pass.
+    pub fn register<T, F>(mut self, validator: F) -> Self
+    where
+        T: Message + 'static,
+        F: Fn(&T) -> Result<(), Vec<FieldViolation>> + Send + Sync + 'static,
+    {
+        let validator = move |message: &dyn Any| match message.downcast_ref::<T>() {
+            Some(message) => validator(message),
+            None => Ok(()),
+        };
+        self.validators
+            .insert(TypeId::of::<T>(), Box::new(validator));
+        self
+    }
+
+    /// Runs the check registered for `T`, if any
+    pub fn validate<T>(&self, message: &T) -> Result<(), Status>
+    where
+        T: Message + 'static,
+    {
+        let Some(validator) = self.validators.get(&TypeId::of::<T>()) else {
+            return Ok(());
+        };
+        match validator(message) {
+            Ok(()) => Ok(()),
+            Err(violations) if violations.is_empty() => Ok(()),
+            Err(violations) => Err(invalid_argument(&violations)),
+        }
+    }
+}
+
+/// The `INVALID_ARGUMENT` status reporting `violations`
+///
+/// The message lists every field, e.g.
+/// `invalid request: name: is required; age: must be positive`.
+pub fn invalid_argument(violations: &[FieldViolation]) -> Status {
+    let fields: Vec<String> = violations.iter().map(ToString::to_string).collect();
+    Status::new(
+        Code::InvalidArgument,
+        format!("invalid request: {}", fields.join("; ")),
+    )
+}
@@ -0,0 +1,122 @@
+//! gRPC-Web text mode (`application/grpc-web-text`)
+//!
+//! Browsers that cannot read binary bodies send gRPC-Web with the whole
+//! body base64-encoded: request frames on the way in, message frames plus
+//! the trailer frame on the way out. The base64 text may arrive split at
+//! any byte across DATA frames, and a sender may pad and restart the
+//! encoding between chunks, so [`WebTextDecoder`] keeps the unfinished
+//! quantum until the next chunk completes it.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use bytes::Bytes;
+use tonic::{Code, Status};
+
+use crate::streaming::percent_encode;
+
+/// Content type prefix of gRPC-Web text calls
+pub const GRPC_WEB_TEXT_CONTENT_TYPE: &str = "application/grpc-web-text";
+
+/// Content type of gRPC-Web text responses
+pub const GRPC_WEB_TEXT_PROTO_CONTENT_TYPE: &str = "application/grpc-web-text+proto";
+
+/// Flag bit marking a gRPC-Web frame as the trailer frame
+const TRAILER_FLAG: u8 = 0x80;
+
+/// Whether `content_type` is `application/grpc-web-text` or one of its
+/// `+format` variants
+pub fn is_grpc_web_text(content_type: &str) -> bool {
+    match content_type.strip_prefix(GRPC_WEB_TEXT_CONTENT_TYPE) {
+        Some(rest) => rest.is_empty() || rest.starts_with('+') || rest.starts_with(';'),
+        None => false,
+    }
+}
+
+/// Incremental base64 decoder for a gRPC-Web text body
+///
+/// Feed it the body chunk by chunk as it arrives; each call returns the
+/// bytes of every complete 4-character quantum seen so far. Whitespace is
+/// skipped.
+#[derive(Debug, Default)]
+pub struct WebTextDecoder {
+    pending: Vec<u8>,
+}
+
+impl WebTextDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes what `chunk` completes, keeping a trailing partial quantum
+    pub fn decode(&mut self, chunk: &[u8]) -> Result<Bytes, Status> {
+        self.pending
+            .extend(chunk.iter().filter(|b| !b.is_ascii_whitespace()));
+        let usable = self.pending.len() / 4 * 4;
+
+        let mut out = Vec::with_capacity(usable / 4 * 3);
+        let mut start = 0;
+        for end in (4..=usable).step_by(4) {
+            // Padding ends one base64 run; the next quantum starts another
+            if end == usable || self.pending[end - 1] == b'=' {
+                BASE64
+                    .decode_vec(&self.pending[start..end], &mut out)
+                    .map_err(|e| invalid_text(format!("invalid base64: {}", e)))?;
+                start = end;
+            }
+        }
+        self.pending.drain(..usable);
+        Ok(Bytes::from(out))
+    }
+
+    /// Checks that the body did not end in the middle of a quantum
+    pub fn finish(self) -> Result<(), Status> {
+        if self.pending.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid_text(format!(
+                "body ends with {} stray base64 characters",
+                self.pending.len()
+            )))
+        }
+    }
+}
+
+/// Decodes a complete gRPC-Web text body into its binary frames
+pub fn decode_web_text(body: &[u8]) -> Result<Bytes, Status> {
+    let mut decoder = WebTextDecoder::new();
+    let frames = decoder.decode(body)?;
+    decoder.finish()?;
+    Ok(frames)
+}
+
+/// Encodes a gRPC-Web text response body
+///
+/// `frames` are the already framed response messages; the trailer frame
+/// carrying `status` is appended before the whole body is base64-encoded.
+pub fn encode_web_text(frames: &[u8], status: &Status) -> Bytes {
+    let mut body = frames.to_vec();
+    body.extend_from_slice(&trailer_frame(status));
+    Bytes::from(BASE64.encode(body))
+}
+
+/// gRPC-Web trailer frame: the status as HTTP/1 style header lines
+pub fn trailer_frame(status: &Status) -> Bytes {
+    let mut trailers = format!("grpc-status:{}\r\n", status.code() as i32);
+    if !status.message().is_empty() {
+        trailers.push_str("grpc-message:");
+        percent_encode(status.message(), &mut trailers);
+        trailers.push_str("\r\n");
+    }
+
+    let mut frame = Vec::with_capacity(5 + trailers.len());
+    frame.push(TRAILER_FLAG);
+    frame.extend_from_slice(&(trailers.len() as u32).to_be_bytes());
+    frame.extend_from_slice(trailers.as_bytes());
+    Bytes::from(frame)
+}
+
+fn invalid_text(message: String) -> Status {
+    Status::new(
+        Code::InvalidArgument,
+        format!("Malformed grpc-web-text body: {}", message),
+    )
+}
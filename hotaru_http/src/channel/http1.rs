@@ -12,6 +12,7 @@ use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use hotaru_core::connection::error::ConnectionError;
 use hotaru_core::connection::{ConnMeta, ConnStream, HotaruRead, HotaruWrite};
 use hotaru_core::protocol::Channel;
 use tokio::sync::Mutex;
@@ -67,7 +68,22 @@ where
 {
     async fn parse_request(&self, safety: &HttpSafety) -> Result<HttpRequest, HttpError> {
         let mut reader = self.reader.lock().await;
-        let request = HttpRequest::parse_lazy(&mut *reader, safety, false).await;
+        let request = match HttpRequest::try_parse_lazy(&mut *reader, safety, false).await {
+            Ok(request) => request,
+            // The rest of the request line is still unread, so the caller
+            // answers 414 and closes instead of continuing the connection.
+            Err(ConnectionError::UriTooLong) => {
+                self.open.store(false, Ordering::Release);
+                return Err(HttpError::UriTooLong);
+            }
+            // Ambiguous framing: where the body ends is unknown, so nothing
+            // after this request can be trusted either.
+            Err(ConnectionError::ProtocolError(reason)) => {
+                self.open.store(false, Ordering::Release);
+                return Err(HttpError::ProtocolViolation(reason));
+            }
+            Err(_) => HttpRequest::default(),
+        };
 
         // EOF / malformed: flip the channel closed and signal Io.
         if request.meta.path().is_empty() && request.meta.header.is_empty() {
@@ -0,0 +1,172 @@
+//! Liveness and readiness routes.
+//!
+//! [`HealthRoutes`] adds both to a [`ProtocolRegistryBuilder`] next to the
+//! app's own routes, instead of hand-writing them as endpoints:
+//!
+//! ```ignore
+//! let builder = ProtocolRegistryBuilder::<DefaultHttpTransport>::new()
+//!     .protocol(ProtocolEntryBuilder::new(HTTP::server(HttpSafety::default())))
+//!     .with_health::<HTTP>("/healthz")?
+//!     .with_ready::<HTTP, _>("/readyz", || CACHE.is_warm())?;
+//! ```
+//!
+//! The health route answers `200` whenever the process can serve a request
+//! at all. The ready route answers `503` while its check fails and from the
+//! moment the server starts draining (see `Server::drain`), so a load
+//! balancer stops routing to an instance before it goes away.
+
+use std::sync::Arc;
+
+use hotaru_core::connection::TransportSpec;
+use hotaru_core::executable::ProtocolRegistryBuilder;
+use hotaru_core::executable::middleware::AsyncFinalHandler;
+use hotaru_core::extensions::ParamsClone;
+use hotaru_core::protocol::Protocol;
+use hotaru_core::url::UrlError;
+
+use crate::context::HttpContext;
+use crate::message::http_value::StatusCode;
+use crate::message::response::response_templates::text_response;
+use crate::protocol::HttpError;
+
+/// Registers the standard probe routes on an HTTP protocol.
+pub trait HealthRoutes<TS: TransportSpec>: Sized
+where
+    HttpError: From<<TS as TransportSpec>::IoError>,
+{
+    /// Answers `GET path` with `200 ok` for as long as the server runs.
+    fn with_health<P>(self, path: &str) -> Result<Self, UrlError>
+    where
+        P: Protocol<Wire = TS::Wire, TS = TS, Context = HttpContext<TS>> + Clone + 'static;
+
+    /// Answers `GET path` with `200 ready` once `check` passes, and with
+    /// `503` while it does not or while the server is draining.
+    ///
+    /// `check` runs on every probe, so it should only read state kept up to
+    /// date elsewhere. Pass `|| true` to track draining alone.
+    fn with_ready<P, F>(self, path: &str, check: F) -> Result<Self, UrlError>
+    where
+        P: Protocol<Wire = TS::Wire, TS = TS, Context = HttpContext<TS>> + Clone + 'static,
+        F: Fn() -> bool + Send + Sync + 'static;
+}
+
+impl<TS: TransportSpec> HealthRoutes<TS> for ProtocolRegistryBuilder<TS>
+where
+    HttpError: From<<TS as TransportSpec>::IoError>,
+{
+    fn with_health<P>(self, path: &str) -> Result<Self, UrlError>
+    where
+        P: Protocol<Wire = TS::Wire, TS = TS, Context = HttpContext<TS>> + Clone + 'static,
+    {
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext<TS>>> =
+            Arc::new(|mut ctx: HttpContext<TS>| async move {
+                ctx.response = text_response("ok");
+                Ok(ctx)
+            });
+        self.add_route::<P>(path, handler, vec![], ParamsClone::default())
+    }
+
+    fn with_ready<P, F>(self, path: &str, check: F) -> Result<Self, UrlError>
+    where
+        P: Protocol<Wire = TS::Wire, TS = TS, Context = HttpContext<TS>> + Clone + 'static,
+        F: Fn() -> bool + Send + Sync + 'static,
+    {
+        let check = Arc::new(check);
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext<TS>>> =
+            Arc::new(move |mut ctx: HttpContext<TS>| {
+                let check = check.clone();
+                async move {
+                    let draining = ctx.runtime().is_some_and(|runtime| runtime.is_draining());
+                    ctx.response = if draining {
+                        text_response("draining").status(StatusCode::SERVICE_UNAVAILABLE)
+                    } else if !check() {
+                        text_response("not ready").status(StatusCode::SERVICE_UNAVAILABLE)
+                    } else {
+                        text_response("ready")
+                    };
+                    Ok(ctx)
+                }
+            });
+        self.add_route::<P>(path, handler, vec![], ParamsClone::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{DefaultHttpTransport, HTTP};
+    use crate::security::safety::HttpSafety;
+    use hotaru_core::app::server::Server;
+    use hotaru_core::executable::ProtocolEntryBuilder;
+    use hotaru_io_tokio::testing::http_get;
+    use hotaru_rt_tokio::TokioRuntime;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test]
+    async fn readiness_follows_check_and_drain_while_health_stays_up() {
+        let warm = Arc::new(AtomicBool::new(false));
+        let check = warm.clone();
+        let builder = ProtocolRegistryBuilder::<DefaultHttpTransport>::new()
+            .protocol(ProtocolEntryBuilder::new(HTTP::server(
+                HttpSafety::default(),
+            )))
+            .with_health::<HTTP>("/healthz")
+            .unwrap()
+            .with_ready::<HTTP, _>("/readyz", move || check.load(Ordering::SeqCst))
+            .unwrap();
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let server = Server::<DefaultHttpTransport, TokioRuntime>::new()
+            .binding(addr.to_string())
+            .handle(builder)
+            .build();
+        server.ensure_inbounds().await.unwrap();
+        let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+        let running = tokio::spawn(server.clone().run_until(async {
+            let _ = stop_rx.await;
+        }));
+
+        // Up, but the check has not passed yet
+        assert!(http_get(addr, "/healthz").await.starts_with("HTTP/1.1 200"));
+        let response = http_get(addr, "/readyz").await;
+        assert!(response.starts_with("HTTP/1.1 503"), "{response}");
+        assert!(response.ends_with("not ready"), "{response}");
+
+        warm.store(true, Ordering::SeqCst);
+        let response = http_get(addr, "/readyz").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+        // Draining turns readiness off; the process is still healthy
+        server.drain();
+        let response = http_get(addr, "/readyz").await;
+        assert!(response.starts_with("HTTP/1.1 503"), "{response}");
+        assert!(response.ends_with("draining"), "{response}");
+        let response = http_get(addr, "/healthz").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("ok"), "{response}");
+
+        stop_tx.send(()).unwrap();
+        running.await.unwrap();
+        assert!(server.is_draining());
+    }
+
+    #[tokio::test]
+    async fn stopping_the_server_starts_draining() {
+        let builder = ProtocolRegistryBuilder::<DefaultHttpTransport>::new()
+            .protocol(ProtocolEntryBuilder::new(HTTP::server(
+                HttpSafety::default(),
+            )))
+            .with_health::<HTTP>("/healthz")
+            .unwrap();
+        let server = Server::<DefaultHttpTransport, TokioRuntime>::new()
+            .binding("127.0.0.1:0")
+            .handle(builder)
+            .build();
+        assert!(!server.is_draining());
+        server.clone().run_until(async {}).await;
+        assert!(server.is_draining());
+    }
+}
@@ -20,9 +20,19 @@ pub mod message;
 /// [One-shot request] `send_request(&outbound, request, safety)` over any `Outbound`
 pub mod send_request;
 
-/// [Security] HttpSafety
+/// [Client retries] RetryPolicy, `Retry-After` parsing
+pub mod retry;
+
+/// [Probes] HealthRoutes: `/healthz` and drain-aware `/readyz` routes
+pub mod health;
+
+/// [Security] HttpSafety, ConcurrencyLimit
 pub mod security;
 
+/// [Debugging] RequestRecorder, `replay` of recorded requests
+#[cfg(feature = "record")]
+pub mod record;
+
 /// [Utilities] Cookie, encoding, form, security tests
 pub mod util;
 
@@ -39,8 +49,13 @@ pub use protocol::{HTTPS, Http1TlsProtocol};
 #[cfg(feature = "tls")]
 pub use hotaru_tls::{TlsClientConfig, TlsConfig, TlsOutbound, TlsOutboundTarget, TlsTransport};
 
-pub use protocol::HttpError;
-pub use send_request::send_request;
+pub use health::HealthRoutes;
+pub use protocol::{BodyError, ExtractError, ExtractSource, FieldError, HttpError};
+#[cfg(feature = "record")]
+pub use record::RequestRecorder;
+pub use retry::RetryPolicy;
+pub use security::concurrency::ConcurrencyLimit;
+pub use send_request::{send_request, send_request_with_retry};
 
 // ============================================================================
 // Backward-compatible re-exports for external crates (e.g. hotaru, htmstd, h2per)
@@ -46,6 +46,47 @@ impl HttpBody {
 
     /// Parse a Buffer variant into a more specific type based on content_type
     pub fn parse_buffer(self, safety: &HttpSafety) -> Self {
+        match self.decode_buffer(safety) {
+            Self::Buffer { data, content_type, .. } => match content_type {
+                HttpContentType::Application { ref subtype, .. }
+                    if subtype == "x-www-form-urlencoded"
+                        && !safety.check_form_buffer_size(data.len()) =>
+                {
+                    // Too large to collect into a map: left unparsed, like an oversized body
+                    Self::Unparsed
+                }
+                HttpContentType::Application { subtype, .. } if subtype == "json" => {
+                    Self::parse_json(data)
+                }
+                HttpContentType::Text { subtype, .. }
+                    if subtype == "html" || subtype == "plain" =>
+                {
+                    Self::parse_text(data)
+                }
+                HttpContentType::Application { subtype, .. }
+                    if subtype == "x-www-form-urlencoded" =>
+                {
+                    Self::parse_form(data)
+                }
+                HttpContentType::Multipart { subtype, boundary } if subtype == "form-data" => {
+                    // Over the multipart limits: left unparsed, like an oversized body
+                    let boundary = boundary.unwrap_or_default();
+                    match MultiForm::try_parse(&data, &boundary, safety) {
+                        Ok(files) => Self::Files(files),
+                        Err(_) => Self::Unparsed,
+                    }
+                }
+                _ => Self::parse_binary(data),
+            },
+            // If already parsed or empty, just return as is
+            other => other,
+        }
+    }
+
+    /// Checks a `Buffer` against the body size limit and undoes its content
+    /// coding, leaving it a `Buffer` with no coding. Anything else is
+    /// returned as is.
+    pub fn decode_buffer(self, safety: &HttpSafety) -> Self {
         match self {
             Self::Buffer {
                 data,
@@ -56,31 +97,23 @@ impl HttpBody {
                 if !safety.check_body_size(data.len()) {
                     return Self::Unparsed; // Return Unparsed if size exceeds limits
                 }
-                // Decode the content based on content coding
-                let data = content_coding
-                    .decode_compressed(data)
-                    .unwrap_or_else(|_| vec![]);
-                match content_type {
-                    HttpContentType::Application { subtype, .. } if subtype == "json" => {
-                        Self::parse_json(data)
-                    }
-                    HttpContentType::Text { subtype, .. }
-                        if subtype == "html" || subtype == "plain" =>
-                    {
-                        Self::parse_text(data)
-                    }
-                    HttpContentType::Application { subtype, .. }
-                        if subtype == "x-www-form-urlencoded" =>
-                    {
-                        Self::parse_form(data)
-                    }
-                    HttpContentType::Multipart { subtype, boundary } if subtype == "form-data" => {
-                        Self::parse_files(data, boundary.unwrap_or("".to_string()))
+                // Decode the content based on content coding, stopping at the
+                // size limit so a compressed body cannot expand past it
+                let data = match content_coding
+                    .decode_compressed_limited(data, safety.effective_body_size())
+                {
+                    Ok(data) => data,
+                    Err(err) if err.kind() == std::io::ErrorKind::FileTooLarge => {
+                        return Self::Unparsed;
                     }
-                    _ => Self::parse_binary(data),
+                    Err(_) => vec![],
+                };
+                Self::Buffer {
+                    data,
+                    content_type,
+                    content_coding: ContentCodings::new(),
                 }
             }
-            // If already parsed or empty, just return as is
             _ => self,
         }
     }
@@ -305,7 +338,12 @@ impl HttpBody {
                 bin
             }
             _ => {
-                if let None = meta.get_content_length() {
+                // 1xx and 204 responses must not carry Content-Length.
+                let bodiless = meta
+                    .start_line
+                    .try_status_code()
+                    .is_some_and(|code| code.is_informational() || code.is_no_content());
+                if !bodiless && meta.get_content_length().is_none() {
                     meta.set_content_length(0);
                 }
                 EMPTY.to_vec()
@@ -316,9 +354,15 @@ impl HttpBody {
             .map(|e| e.content().clone())
             .unwrap_or(ContentCodings::new());
         // If the content coding is not identity, we need to encode the binary data
-        content_coding
+        if content_coding.is_identity() {
+            return bin;
+        }
+        let encoded = content_coding
             .encode_compressed(bin)
-            .unwrap_or_else(|_| vec![])
+            .unwrap_or_else(|_| vec![]);
+        // Content-Length describes the encoded body that goes on the wire
+        meta.set_content_length(encoded.len());
+        encoded
     }
 
     pub fn parse_json(body: Vec<u8>) -> Self {
@@ -89,6 +89,15 @@ impl HttpMethod {
         }
     }
 
+    /// Whether the method is safe (RFC 9110 §9.2.1): read-only, so a
+    /// replayed request does no harm.
+    pub fn is_safe(&self) -> bool {
+        matches!(
+            self,
+            HttpMethod::GET | HttpMethod::HEAD | HttpMethod::OPTIONS | HttpMethod::TRACE
+        )
+    }
+
     pub fn get_full_list() -> Vec<HttpMethod> {
         vec![
             HttpMethod::GET,
@@ -1885,6 +1894,85 @@ impl Default for RequestPath {
     }
 }
 
+/// A request-target split into its parts, borrowed from the request line.
+///
+/// Requests usually carry the origin form, `/search?q=rust`, which has no
+/// scheme or authority; the host is then in the `Host` header. Requests to
+/// a proxy carry the absolute form, `http://example.com/search?q=rust`,
+/// and `CONNECT` the authority form, `example.com:443`.
+/// [`as_str`](Self::as_str) gives the target exactly as received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Uri<'a> {
+    target: &'a str,
+}
+
+impl<'a> Uri<'a> {
+    pub fn new(target: &'a str) -> Self {
+        Self { target }
+    }
+
+    /// The whole request-target, query string included.
+    pub fn as_str(&self) -> &'a str {
+        self.target
+    }
+
+    /// The scheme of an absolute-form target, e.g. `https`.
+    pub fn scheme(&self) -> Option<&'a str> {
+        self.parts().0
+    }
+
+    /// The `host[:port]` of an absolute-form or authority-form target.
+    pub fn authority(&self) -> Option<&'a str> {
+        self.parts().1
+    }
+
+    /// The path, without the query string. `/` for an absolute-form target
+    /// without one, empty for the authority form.
+    pub fn path(&self) -> &'a str {
+        let (scheme, _, path_and_query) = self.parts();
+        let path = match path_and_query.split_once('?') {
+            Some((path, _)) => path,
+            None => path_and_query,
+        };
+        if path.is_empty() && scheme.is_some() {
+            "/"
+        } else {
+            path
+        }
+    }
+
+    /// The raw query string, without the `?`.
+    pub fn query(&self) -> Option<&'a str> {
+        self.parts().2.split_once('?').map(|(_, query)| query)
+    }
+
+    /// The path and query string, as they would appear in origin form.
+    pub fn path_and_query(&self) -> &'a str {
+        self.parts().2
+    }
+
+    /// Scheme, authority and path-and-query of the target.
+    fn parts(&self) -> (Option<&'a str>, Option<&'a str>, &'a str) {
+        let target = self.target;
+        if target.starts_with('/') || target == "*" {
+            return (None, None, target);
+        }
+        match target.split_once("://") {
+            Some((scheme, rest)) if !scheme.is_empty() && !scheme.contains(['/', '?']) => {
+                let end = rest.find(['/', '?']).unwrap_or(rest.len());
+                (Some(scheme), Some(&rest[..end]), &rest[end..])
+            }
+            _ => (None, Some(target), ""),
+        }
+    }
+}
+
+impl std::fmt::Display for Uri<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.target)
+    }
+}
+
 /// Represents HTTP `Accept-Language` header for client language preferences.
 ///
 /// Stores language tags with quality weights (q-values) for content negotiation.
@@ -5,10 +5,11 @@ use crate::security::safety::HttpSafety;
 use crate::util::cookie::{Cookie, CookieMap};
 
 use crate::message::http_value::*;
-use crate::message::start_line::HttpStartLine;
+use crate::message::start_line::{HttpStartLine, RequestStartLine};
 use std::collections::{HashMap, HashSet};
 use std::str;
 use hotaru_core::connection::HotaruBufRead;
+use hotaru_core::protocol::HeaderMultiMap;
 
 /// RequestHeader is a struct that represents the headers of an HTTP request.
 #[derive(Debug, Clone)]
@@ -16,6 +17,9 @@ pub struct HttpMeta {
     pub start_line: HttpStartLine,
     pub header: HashMap<String, HeaderValue>,
 
+    // Header lines as received, in order and with original name casing
+    raw_header: HeaderMultiMap,
+
     // Content-type header, overrides the content type from the hashmap if present
     content_type: Option<HttpContentType>,
 
@@ -529,6 +533,7 @@ impl HttpMeta {
     pub fn new(start_line: HttpStartLine, headers: HashMap<String, HeaderValue>) -> Self {
         Self {
             start_line,
+            raw_header: Self::raw_from(&headers),
             header: headers,
             content_type: None,
             content_length: None,
@@ -547,10 +552,27 @@ impl HttpMeta {
         print_raw: bool,
         is_request: bool,
     ) -> Result<HttpMeta, ConnectionError> {
+        // Requests read their start line separately so an oversized target
+        // is rejected before the whole line is buffered.
+        let request_line = if is_request {
+            let line = Self::request_line_from_stream(buf_reader, config).await?;
+            if line.is_empty() {
+                return Err(ConnectionError::BadRequest("Empty request".to_string()));
+            }
+            Some(line)
+        } else {
+            None
+        };
+
         let mut headers = Self::header_lines_raw_from_stream(buf_reader, config, print_raw)
             .await
             .map_err(|_| ConnectionError::BadRequest(format!("Failed to read headers")))?;
 
+        if let Some(line) = request_line {
+            Self::check_request_framing(&headers)?;
+            headers.insert(0, line);
+        }
+
         if headers.is_empty() {
             return Err(ConnectionError::BadRequest(format!(
                 "Empty {}",
@@ -562,14 +584,63 @@ impl HttpMeta {
         let start_line = Self::parse_start_line(&headers.remove(0), is_request);
 
         // Parse headers with special handling for specific header names
-        let header = Self::parse_headers(headers, is_request);
+        let (header, raw_header) = Self::parse_headers(headers, is_request);
 
         if print_raw {
             println!("Parsed headers: {:?}", header);
             println!("Parsed start line: {:?}", start_line);
         }
 
-        Ok(HttpMeta::new(start_line, header))
+        let mut meta = HttpMeta::new(start_line, header);
+        meta.raw_header = raw_header;
+        Ok(meta)
+    }
+
+    /// Reads the request line chunk by chunk, enforcing `max_uri_length`
+    /// on the request-target as soon as enough of it has arrived.
+    ///
+    /// Returns `ConnectionError::UriTooLong` without reading the rest of the
+    /// line, and an empty string at EOF or on a blank first line.
+    async fn request_line_from_stream<R: HotaruBufRead<Error = std::io::Error> + Unpin + Send>(
+        buf_reader: &mut R,
+        config: &HttpSafety,
+    ) -> Result<String, ConnectionError> {
+        let mut line = Vec::new();
+        loop {
+            let (done, used) = {
+                let available = buf_reader.fill_buf().await.map_err(|_| {
+                    ConnectionError::InternalServerError("Failed to fill buffer".to_string())
+                })?;
+                if available.is_empty() {
+                    break;
+                }
+                match available.iter().position(|b| *b == b'\n') {
+                    Some(i) => {
+                        line.extend_from_slice(&available[..=i]);
+                        (true, i + 1)
+                    }
+                    None => {
+                        line.extend_from_slice(available);
+                        (false, available.len())
+                    }
+                }
+            };
+            buf_reader.consume(used);
+
+            if !config.check_uri_length(RequestStartLine::target_length(&line)) {
+                return Err(ConnectionError::UriTooLong);
+            }
+            if !config.check_line_length(line.len()) {
+                return Err(ConnectionError::PayloadTooLarge);
+            }
+            if done {
+                break;
+            }
+        }
+
+        let line = String::from_utf8(line)
+            .map_err(|_| ConnectionError::BadRequest("Invalid request line".to_string()))?;
+        Ok(line.trim_end().replace("\r", ""))
     }
 
     async fn header_lines_raw_from_stream<R: HotaruBufRead<Error = std::io::Error> + Unpin + Send>(
@@ -668,6 +739,50 @@ impl HttpMeta {
         Ok(headers)
     }
 
+    /// Rejects request framing that two parsers could read differently.
+    ///
+    /// With both `Content-Length` and `Transfer-Encoding`, disagreeing
+    /// `Content-Length` values, or an obsolete folded header line, a proxy
+    /// in front of us may end the request somewhere else than we do and
+    /// smuggle the rest in as a second request. RFC 9112 has servers answer
+    /// all of these with 400 and close the connection; they come back as
+    /// `ConnectionError::ProtocolError`.
+    fn check_request_framing(header_lines: &[String]) -> Result<(), ConnectionError> {
+        let smuggling = |reason: &str| Err(ConnectionError::ProtocolError(reason.to_string()));
+        let mut content_length = None;
+        let mut transfer_encoding = false;
+
+        for line in header_lines {
+            if line.starts_with([' ', '\t']) {
+                return smuggling("obsolete line folding in header section");
+            }
+            let Some((name, value)) = line.split_once(':') else {
+                continue;
+            };
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("transfer-encoding") {
+                transfer_encoding = true;
+            } else if name.eq_ignore_ascii_case("content-length") {
+                // `Content-Length: 5, 5` repeats one length, which is allowed
+                for digits in value.split(',').map(str::trim) {
+                    let length = match digits.parse::<u64>() {
+                        Ok(length) if digits.bytes().all(|b| b.is_ascii_digit()) => length,
+                        _ => return smuggling("invalid Content-Length value"),
+                    };
+                    if content_length.is_some_and(|seen| seen != length) {
+                        return smuggling("conflicting Content-Length values");
+                    }
+                    content_length = Some(length);
+                }
+            }
+        }
+
+        if transfer_encoding && content_length.is_some() {
+            return smuggling("both Content-Length and Transfer-Encoding");
+        }
+        Ok(())
+    }
+
     // Helper function to parse the start line
     fn parse_start_line(line: &str, is_request: bool) -> HttpStartLine {
         if is_request {
@@ -681,8 +796,9 @@ impl HttpMeta {
     fn parse_headers(
         header_lines: Vec<String>,
         _is_response: bool,
-    ) -> HashMap<String, HeaderValue> {
+    ) -> (HashMap<String, HeaderValue>, HeaderMultiMap) {
         let mut headers: HashMap<String, HeaderValue> = HashMap::new();
+        let mut raw_headers = HeaderMultiMap::new();
 
         // // List of headers that should not be combined (kept as separate values)
         // // This is especially important for responses with multiple Set-Cookie headers
@@ -700,6 +816,7 @@ impl HttpMeta {
 
                 // Remove the colon and trim whitespace from the value
                 let header_value = value[1..].trim().to_string();
+                raw_headers.append(key.trim(), header_value.as_str());
 
                 // Check if this is a special header that should not be combined
                 // let is_non_combinable = is_response && non_combinable_headers.contains(header_name.as_str());
@@ -723,7 +840,7 @@ impl HttpMeta {
             }
         }
 
-        headers
+        (headers, raw_headers)
     }
 
     // Expose the specific methods that call the shared implementation
@@ -751,7 +868,7 @@ impl HttpMeta {
         let start_line = Self::parse_start_line(&headers.remove(0), true);
 
         // Parse headers
-        let header = Self::parse_headers(headers, true);
+        let (header, raw_header) = Self::parse_headers(headers, true);
 
         if print_raw {
             println!("Parsed request headers: {:?}", header);
@@ -760,6 +877,7 @@ impl HttpMeta {
 
         self.start_line = start_line;
         self.header.extend(header);
+        self.raw_header.extend(raw_header.iter());
 
         Ok(())
     }
@@ -777,6 +895,11 @@ impl HttpMeta {
         buffer: &'a [u8],
         config: &HttpSafety,
     ) -> Option<(Vec<&'a str>, usize)> {
+        // A leading CRLF ends a header section with no fields
+        if buffer.starts_with(b"\r\n") {
+            return Some((Vec::new(), 2));
+        }
+
         // Look for the end of headers marker (double CRLF)
         let mut i = 0;
         while i + 3 < buffer.len() {
@@ -807,9 +930,28 @@ impl HttpMeta {
     }
 
     pub fn set_header_hashmap(&mut self, header: HashMap<String, HeaderValue>) {
+        self.raw_header = Self::raw_from(&header);
         self.header = header;
     }
 
+    /// Lines for a header map that was not parsed from the wire, sorted by
+    /// name since the map has no order of its own.
+    fn raw_from(header: &HashMap<String, HeaderValue>) -> HeaderMultiMap {
+        let mut names: Vec<_> = header.keys().collect();
+        names.sort();
+        let mut raw = HeaderMultiMap::new();
+        for name in names {
+            raw.extend(header[name].clone().into_iter().map(|value| (name.as_str(), value)));
+        }
+        raw
+    }
+
+    /// Drops a header from both the merged map and the raw lines.
+    fn remove_header(&mut self, name: &str) {
+        self.header.remove(name);
+        self.raw_header.remove(name);
+    }
+
     /// Returns the hashed, unparsed header.
     /// Note this reference is not intended for you to mutate.
     /// If yo do want to mutate, please use .set_attribute() method
@@ -817,6 +959,16 @@ impl HttpMeta {
         &self.header
     }
 
+    /// Returns the header lines as they were received, in order and with
+    /// duplicates kept.
+    ///
+    /// Headers set through this type's setters are kept in step, and a
+    /// meta built with [`new`](Self::new) starts with the lines of its map.
+    /// Edits made directly to the public `header` field are not reflected.
+    pub fn raw_headers(&self) -> &HeaderMultiMap {
+        &self.raw_header
+    }
+
     pub fn get_header<T: Into<String>>(&self, key: T) -> Option<String> {
         self.header
             .get(&key.into().trim().to_lowercase())
@@ -825,8 +977,12 @@ impl HttpMeta {
 
     ///
     pub fn set_attribute<T: Into<String>, S: Into<HeaderValue>>(&mut self, key: T, value: S) {
-        self.header
-            .insert(key.into().trim().to_lowercase(), value.into());
+        let key = key.into().trim().to_lowercase();
+        let value = value.into();
+        self.raw_header.remove(&key);
+        self.raw_header
+            .extend(value.clone().into_iter().map(|line| (key.as_str(), line)));
+        self.header.insert(key, value);
     }
 
     pub fn get_path(&mut self, part: usize) -> String {
@@ -974,7 +1130,7 @@ impl HttpMeta {
     /// ```
     pub fn delete_content_length(&mut self) {
         self.content_length = None;
-        self.header.remove("content-length");
+        self.remove_header("content-length");
     }
 
     /// Gets the content type from the HTTP meta data.
@@ -1123,7 +1279,7 @@ impl HttpMeta {
     /// ```
     pub fn delete_content_type(&mut self) {
         self.content_type = None;
-        self.header.remove("content-type");
+        self.remove_header("content-type");
     }
 
     /// Gets the Content-Disposition header value from the HTTP metadata.
@@ -1291,7 +1447,7 @@ impl HttpMeta {
     /// ```
     pub fn delete_content_disposition(&mut self) {
         self.content_disposition = None;
-        self.header.remove("content-disposition");
+        self.remove_header("content-disposition");
     }
 
     /// Gets the cookies from the HTTP meta data.
@@ -1583,8 +1739,8 @@ impl HttpMeta {
     /// ```
     pub fn delete_cookies(&mut self) {
         self.cookies = None;
-        self.header.remove("cookie");
-        self.header.remove("set-cookie");
+        self.remove_header("cookie");
+        self.remove_header("set-cookie");
     }
 
     /// Gets the host from the HTTP meta data.
@@ -1827,9 +1983,9 @@ impl HttpMeta {
     pub fn delete_lang(&mut self) {
         self.lang = None;
         if self.start_line.is_request() {
-            self.header.remove("accept-language");
+            self.remove_header("accept-language");
         } else {
-            self.header.remove("content-language");
+            self.remove_header("content-language");
         }
     }
 
@@ -1862,7 +2018,7 @@ impl HttpMeta {
     /// ```
     pub fn delete_host(&mut self) {
         self.host = None;
-        self.header.remove("host");
+        self.remove_header("host");
     }
 
     /// Gets the location header from the HTTP meta data.
@@ -2005,7 +2161,7 @@ impl HttpMeta {
     /// ```
     pub fn delete_location(&mut self) {
         self.location = None;
-        self.header.remove("location");
+        self.remove_header("location");
     }
 
     /// Gets the HTTP encoding (both transfer and content encoding) from the HTTP meta data.
@@ -2165,8 +2321,8 @@ impl HttpMeta {
     /// ```
     pub fn delete_encoding(&mut self) {
         self.encoding = None;
-        self.header.remove("transfer-encoding");
-        self.header.remove("content-encoding");
+        self.remove_header("transfer-encoding");
+        self.remove_header("content-encoding");
     }
 
     /// Serializes the HTTP meta data to a string representation.
@@ -2317,6 +2473,7 @@ impl Default for HttpMeta {
                 "/".to_string(),
             ),
             header: HashMap::new(),
+            raw_header: HeaderMultiMap::new(),
             content_type: None,
             content_length: None,
             content_disposition: None,
@@ -2328,3 +2485,257 @@ impl Default for HttpMeta {
         }
     }
 }
+
+/// Hop-by-hop headers, which describe one connection and must not be
+/// forwarded by a proxy (RFC 9110 §7.6.1). `proxy-connection` is the
+/// pre-standard spelling some clients still send.
+pub const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Whether `name` is one of the [`HOP_BY_HOP_HEADERS`].
+pub fn is_hop_by_hop(name: &str) -> bool {
+    HOP_BY_HOP_HEADERS
+        .iter()
+        .any(|hop| hop.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Header names listed in the `Connection` header of `headers`, which are
+/// hop-by-hop for that message only.
+fn connection_options(headers: &HashMap<String, HeaderValue>) -> Vec<String> {
+    headers
+        .get("connection")
+        .map(|value| {
+            value
+                .values()
+                .iter()
+                .flat_map(|value| value.split(','))
+                .map(|name| name.trim().to_lowercase())
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Removes the hop-by-hop headers from `headers`: the standard ones and any
+/// named in its `Connection` header.
+pub fn strip_hop_by_hop(headers: &mut HashMap<String, HeaderValue>) {
+    let options = connection_options(headers);
+    headers.retain(|name, _| !is_hop_by_hop(name) && !options.contains(name));
+}
+
+/// How [`HeaderMerge`] combines a header present in both maps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderMergeMode {
+    /// The incoming value takes the place of the existing one.
+    Replace,
+    /// The incoming values are added after the existing ones, each kept as
+    /// a value of its own.
+    Append,
+}
+
+/// Rules for combining header maps, as a proxy does when it forwards a
+/// message with headers of its own.
+///
+/// By default an incoming header replaces an existing one of the same name,
+/// except `Set-Cookie`, whose values are appended so every cookie survives.
+/// Values are never joined into one string, so a header sent on several
+/// lines is still sent on several lines. Hop-by-hop headers are dropped from
+/// the result unless [`keep_hop_by_hop`](Self::keep_hop_by_hop) is set.
+///
+/// ```rust,ignore
+/// let merge = HeaderMerge::new()
+///     .mode("x-forwarded-for", HeaderMergeMode::Append)
+///     .via("1.1 gateway");
+/// merge.merge(&mut upstream.meta.header, &overrides);
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeaderMerge {
+    default: HeaderMergeMode,
+    modes: HashMap<String, HeaderMergeMode>,
+    keep_hop_by_hop: bool,
+    via: Option<String>,
+}
+
+impl HeaderMerge {
+    /// The default rules described on [`HeaderMerge`].
+    pub fn new() -> Self {
+        let mut modes = HashMap::new();
+        modes.insert("set-cookie".to_string(), HeaderMergeMode::Append);
+        Self {
+            default: HeaderMergeMode::Replace,
+            modes,
+            keep_hop_by_hop: false,
+            via: None,
+        }
+    }
+
+    /// Mode for headers without a mode of their own.
+    pub fn default_mode(mut self, mode: HeaderMergeMode) -> Self {
+        self.default = mode;
+        self
+    }
+
+    /// Mode for the header `name`.
+    pub fn mode<T: Into<String>>(mut self, name: T, mode: HeaderMergeMode) -> Self {
+        self.modes.insert(name.into().trim().to_lowercase(), mode);
+        self
+    }
+
+    /// Keeps hop-by-hop headers in the result.
+    pub fn keep_hop_by_hop(mut self) -> Self {
+        self.keep_hop_by_hop = true;
+        self
+    }
+
+    /// Appends `received_by` (e.g. `"1.1 gateway"`) to the `Via` header of
+    /// the result.
+    pub fn via<T: Into<String>>(mut self, received_by: T) -> Self {
+        self.via = Some(received_by.into());
+        self
+    }
+
+    /// The mode used for the header `name`.
+    pub fn mode_for(&self, name: &str) -> HeaderMergeMode {
+        self.modes
+            .get(&name.trim().to_lowercase())
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// Merges `incoming` into `headers`.
+    ///
+    /// Header names listed in the `Connection` header of either map count
+    /// as hop-by-hop.
+    pub fn merge(
+        &self,
+        headers: &mut HashMap<String, HeaderValue>,
+        incoming: &HashMap<String, HeaderValue>,
+    ) {
+        let mut options = connection_options(headers);
+        options.extend(connection_options(incoming));
+
+        for (name, value) in incoming {
+            let name = name.trim().to_lowercase();
+            match (self.mode_for(&name), headers.get_mut(&name)) {
+                (HeaderMergeMode::Append, Some(existing)) => {
+                    for value in value.values() {
+                        existing.add_without_combining(value.clone());
+                    }
+                }
+                _ => {
+                    headers.insert(name, value.clone());
+                }
+            }
+        }
+
+        if !self.keep_hop_by_hop {
+            headers.retain(|name, _| !is_hop_by_hop(name) && !options.contains(name));
+        }
+        if let Some(via) = &self.via {
+            match headers.get_mut("via") {
+                Some(existing) => existing.add_without_combining(via.clone()),
+                None => {
+                    headers.insert("via".to_string(), HeaderValue::new(via.clone()));
+                }
+            }
+        }
+    }
+}
+
+impl Default for HeaderMerge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(lines: &[(&str, &str)]) -> HashMap<String, HeaderValue> {
+        let mut headers: HashMap<String, HeaderValue> = HashMap::new();
+        for (name, value) in lines {
+            match headers.get_mut(*name) {
+                Some(existing) => existing.add_without_combining(*value),
+                None => {
+                    headers.insert(name.to_string(), HeaderValue::new(*value));
+                }
+            }
+        }
+        headers
+    }
+
+    #[test]
+    fn merge_drops_hop_by_hop_headers() {
+        let mut upstream = headers(&[
+            ("content-type", "text/html"),
+            ("connection", "keep-alive, x-upstream-debug"),
+            ("keep-alive", "timeout=5"),
+            ("x-upstream-debug", "1"),
+        ]);
+        let incoming = headers(&[
+            ("transfer-encoding", "chunked"),
+            ("upgrade", "websocket"),
+            ("cache-control", "no-store"),
+        ]);
+
+        HeaderMerge::new().merge(&mut upstream, &incoming);
+
+        let mut names: Vec<_> = upstream.keys().cloned().collect();
+        names.sort();
+        assert_eq!(names, ["cache-control", "content-type"]);
+
+        // Kept when asked for
+        let mut upstream = headers(&[("connection", "close")]);
+        HeaderMerge::new()
+            .keep_hop_by_hop()
+            .merge(&mut upstream, &incoming);
+        assert!(upstream.contains_key("connection"));
+        assert!(upstream.contains_key("transfer-encoding"));
+    }
+
+    #[test]
+    fn merge_preserves_every_set_cookie() {
+        let mut upstream = headers(&[
+            ("set-cookie", "session=abc; Path=/"),
+            ("set-cookie", "theme=dark; Path=/"),
+            ("cache-control", "public"),
+        ]);
+        let incoming = headers(&[
+            ("set-cookie", "region=eu; Path=/; Secure"),
+            ("cache-control", "no-store"),
+        ]);
+
+        HeaderMerge::new()
+            .via("1.1 gateway")
+            .merge(&mut upstream, &incoming);
+
+        let cookies = upstream["set-cookie"].values();
+        assert_eq!(
+            cookies,
+            [
+                "session=abc; Path=/",
+                "theme=dark; Path=/",
+                "region=eu; Path=/; Secure",
+            ]
+        );
+        assert_eq!(
+            upstream["set-cookie"].into_header_string("set-cookie"),
+            "set-cookie: session=abc; Path=/\r\n\
+             set-cookie: theme=dark; Path=/\r\n\
+             set-cookie: region=eu; Path=/; Secure\r\n"
+        );
+        // Other headers are replaced by default
+        assert_eq!(upstream["cache-control"].as_str(), "no-store");
+        assert_eq!(upstream["via"].as_str(), "1.1 gateway");
+    }
+}
@@ -8,6 +8,7 @@ use crate::message::http_value::*;
 use crate::context::io;
 use std::collections::HashMap;
 use hotaru_core::connection::{HotaruBufRead, HotaruWrite};
+use hotaru_core::connection::error::ConnectionError;
 
 /// Represents an HTTP request with metadata and body.
 ///
@@ -36,10 +37,20 @@ impl HttpRequest {
         config: &HttpSafety,
         print_raw: bool,
     ) -> Self {
-        match io::parse_lazy(stream, config, true, print_raw).await {
-            Ok((meta, body)) => Self::new(meta, body),
-            Err(_) => Self::default(),
-        }
+        Self::try_parse_lazy(stream, config, print_raw)
+            .await
+            .unwrap_or_default()
+    }
+
+    /// Like [`HttpRequest::parse_lazy`], but reports why parsing failed
+    /// instead of falling back to an empty request.
+    pub async fn try_parse_lazy<R: HotaruBufRead<Error = std::io::Error> + Unpin + Send>(
+        stream: &mut R,
+        config: &HttpSafety,
+        print_raw: bool,
+    ) -> Result<Self, ConnectionError> {
+        let (meta, body) = io::parse_lazy(stream, config, true, print_raw).await?;
+        Ok(Self::new(meta, body))
     }
 
     /// Parses the HTTP Body from buffer
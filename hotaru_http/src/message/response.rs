@@ -8,6 +8,7 @@ use crate::message::meta::HttpMeta;
 use crate::context::io;
 use crate::message::start_line::{HttpStartLine, ResponseStartLine};
 use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
 use hotaru_core::connection::{HotaruBufRead, HotaruWrite};
 
 #[derive(Debug, Clone)]
@@ -75,6 +76,14 @@ impl HttpResponse {
         self
     }
 
+    /// Send a custom reason phrase on the HTTP/1 status line.
+    /// Set the status first, since changing it restores the standard phrase.
+    /// Phrases with control characters are ignored.
+    pub fn with_reason<T: Into<String>>(mut self, phrase: T) -> Self {
+        self.meta.start_line.as_response_mut().set_reason_phrase(phrase);
+        self
+    }
+
     /// Send the response
     /// When this method is changed, please also check Request::send()
     pub async fn send<W: HotaruWrite<Error = std::io::Error> + Unpin + Send>(self, writer: &mut W) -> std::io::Result<()> {
@@ -104,6 +113,159 @@ impl Default for HttpResponse {
     }
 }
 
+/// One part of a [`MultipartResponse`]: its own headers and a body.
+#[derive(Debug, Clone, Default)]
+pub struct MultipartPart {
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl MultipartPart {
+    /// A part with no headers and an empty body.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a header to this part. Headers are written in the order added.
+    pub fn header<T: Into<String>, U: Into<String>>(mut self, key: T, value: U) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set the `Content-Type` of this part.
+    pub fn content_type(self, content_type: HttpContentType) -> Self {
+        self.header("Content-Type", content_type.to_string())
+    }
+
+    /// Set the body of this part.
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Headers of this part, in the order they are written.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Body of this part.
+    pub fn get_body(&self) -> &[u8] {
+        &self.body
+    }
+}
+
+/// Builds a `multipart/*` response (`multipart/mixed` unless told
+/// otherwise) out of parts added one by one.
+///
+/// The boundary is chosen when the response is built so that it occurs in
+/// none of the parts, unless one is set explicitly with
+/// [`boundary`](Self::boundary).
+///
+/// # Examples
+///
+/// ```rust
+/// use hotaru_http::message::http_value::HttpContentType;
+/// use hotaru_http::message::response::{MultipartPart, MultipartResponse};
+///
+/// let response = MultipartResponse::new()
+///     .part(MultipartPart::new().content_type(HttpContentType::TextPlain()).body("hello"))
+///     .part(MultipartPart::new().content_type(HttpContentType::ApplicationJson()).body("{}"))
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultipartResponse {
+    subtype: String,
+    boundary: Option<String>,
+    parts: Vec<MultipartPart>,
+}
+
+impl Default for MultipartResponse {
+    fn default() -> Self {
+        Self {
+            subtype: "mixed".to_string(),
+            boundary: None,
+            parts: Vec::new(),
+        }
+    }
+}
+
+impl MultipartResponse {
+    /// An empty `multipart/mixed` response.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use another multipart subtype, e.g. `"related"` or `"alternative"`.
+    pub fn subtype(mut self, subtype: impl Into<String>) -> Self {
+        self.subtype = subtype.into();
+        self
+    }
+
+    /// Use this boundary instead of generating one. The caller must make
+    /// sure it does not occur in any part.
+    pub fn boundary(mut self, boundary: impl Into<String>) -> Self {
+        self.boundary = Some(boundary.into());
+        self
+    }
+
+    /// Append a part.
+    pub fn part(mut self, part: MultipartPart) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    /// Encode the parts into a 200 response with the matching
+    /// `Content-Type: multipart/<subtype>; boundary=...` header.
+    pub fn build(self) -> HttpResponse {
+        let boundary = self
+            .boundary
+            .unwrap_or_else(|| multipart_boundary(&self.parts));
+        let mut body = Vec::new();
+        for part in &self.parts {
+            body.extend_from_slice(format!("--{boundary}\r\n").as_bytes());
+            for (key, value) in &part.headers {
+                body.extend_from_slice(format!("{key}: {value}\r\n").as_bytes());
+            }
+            body.extend_from_slice(b"\r\n");
+            body.extend_from_slice(&part.body);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+        response_templates::normal_response(StatusCode::OK, body).content_type(
+            HttpContentType::Multipart {
+                subtype: self.subtype,
+                boundary: Some(boundary),
+            },
+        )
+    }
+}
+
+/// Boundary that occurs in neither the headers nor the body of any part.
+fn multipart_boundary(parts: &[MultipartPart]) -> String {
+    let mut seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    loop {
+        let boundary = format!("hotaru-multipart-{seed:x}");
+        let occurs = |data: &[u8]| {
+            data.windows(boundary.len())
+                .any(|w| w == boundary.as_bytes())
+        };
+        if !parts.iter().any(|part| {
+            occurs(&part.body)
+                || part
+                    .headers
+                    .iter()
+                    .any(|(k, v)| occurs(k.as_bytes()) || occurs(v.as_bytes()))
+        }) {
+            return boundary;
+        }
+        seed = seed.wrapping_add(1);
+    }
+}
+
 /// Collection of helper functions to easily create common HTTP responses.
 ///
 /// This module provides convenient functions to create standardized HTTP responses
@@ -112,6 +274,7 @@ impl Default for HttpResponse {
 pub mod response_templates {
     use std::collections::HashMap;
     use std::path::Path;
+    use std::time::SystemTime;
 
     use akari::TemplateManager;
     use akari::Value;
@@ -121,6 +284,11 @@ pub mod response_templates {
     use crate::message::http_value::{HttpContentType, HttpVersion, StatusCode};
     use crate::message::meta::HttpMeta;
     use crate::message::start_line::HttpStartLine;
+    use crate::protocol::error::HttpError;
+    use crate::util::http_date::{fails_unmodified_since, format_http_date, is_not_modified};
+    use crate::util::range::{
+        RangeRequest, byteranges_boundary, multipart_byteranges, parse_range,
+    };
 
     /// Creates a plain text HTTP response with status 200 OK.
     ///
@@ -130,7 +298,7 @@ pub mod response_templates {
     ///
     /// # Returns
     ///
-    /// An `HttpResponse` with Content-Type set to text/plain.
+    /// An `HttpResponse` with Content-Type set to `text/plain; charset=UTF-8`.
     ///
     /// # Examples
     ///
@@ -154,7 +322,7 @@ pub mod response_templates {
     ///
     /// # Returns
     ///
-    /// An `HttpResponse` with Content-Type set to text/html.
+    /// An `HttpResponse` with Content-Type set to `text/html; charset=UTF-8`.
     ///
     /// # Examples
     ///
@@ -171,6 +339,72 @@ pub mod response_templates {
         HttpResponse::new(meta, HttpBody::Binary(body.into()))
     }
 
+    /// Creates a plain text HTTP response with status 200 OK in another charset.
+    ///
+    /// [`text_response`] always declares UTF-8; use this when the body is
+    /// encoded differently.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The text content, already encoded in `charset`.
+    /// * `charset` - The charset named in the Content-Type header.
+    ///
+    /// # Returns
+    ///
+    /// An `HttpResponse` with Content-Type set to `text/plain; charset=<charset>`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crate::response::response_templates;
+    ///
+    /// let response = response_templates::text_response_with_charset(b"caf\xe9".to_vec(), "ISO-8859-1");
+    /// ```
+    pub fn text_response_with_charset(
+        body: impl Into<Vec<u8>>,
+        charset: impl Into<String>,
+    ) -> HttpResponse {
+        text_with_charset("plain", body.into(), charset.into())
+    }
+
+    /// Creates an HTML HTTP response with status 200 OK in another charset.
+    ///
+    /// [`html_response`] always declares UTF-8; use this when the body is
+    /// encoded differently.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The HTML content, already encoded in `charset`.
+    /// * `charset` - The charset named in the Content-Type header.
+    ///
+    /// # Returns
+    ///
+    /// An `HttpResponse` with Content-Type set to `text/html; charset=<charset>`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crate::response::response_templates;
+    ///
+    /// let response = response_templates::html_response_with_charset("<p>Hi</p>", "Shift_JIS");
+    /// ```
+    pub fn html_response_with_charset(
+        body: impl Into<Vec<u8>>,
+        charset: impl Into<String>,
+    ) -> HttpResponse {
+        text_with_charset("html", body.into(), charset.into())
+    }
+
+    fn text_with_charset(subtype: &str, body: Vec<u8>, charset: String) -> HttpResponse {
+        let start_line = HttpStartLine::new_response(HttpVersion::Http11, StatusCode::OK);
+        let mut meta = HttpMeta::new(start_line, HashMap::new());
+        meta.set_content_type(HttpContentType::Text {
+            subtype: subtype.to_string(),
+            charset: Some(charset),
+        });
+        HttpResponse::new(meta, HttpBody::Binary(body))
+    }
+
     /// Creates a redirect response (302 Found).
     ///
     /// # Arguments
@@ -180,6 +414,8 @@ pub mod response_templates {
     /// # Returns
     ///
     /// An `HttpResponse` with the Location header set and an empty body.
+    /// Control characters in `url` are percent-encoded so they cannot end
+    /// the header early.
     ///
     /// # Examples
     ///
@@ -191,7 +427,228 @@ pub mod response_templates {
     pub fn redirect_response(url: &str) -> HttpResponse {
         let start_line = HttpStartLine::new_response(HttpVersion::Http11, StatusCode::FOUND);
         let mut meta = HttpMeta::new(start_line, HashMap::new());
-        meta.set_location(Some(url.to_string()));
+        meta.set_location(Some(sanitize_location(url)));
+        HttpResponse::new(meta, HttpBody::Empty)
+    }
+
+    /// Creates a redirect response with any 3xx status.
+    ///
+    /// Use 301/308 for permanent moves and 302/307 for temporary ones; 307
+    /// and 308 keep the request method and body.
+    ///
+    /// # Arguments
+    ///
+    /// * `status_code` - The redirect status, which must be a 3xx code.
+    /// * `location` - The URL to redirect to.
+    ///
+    /// # Returns
+    ///
+    /// An `HttpResponse` with the Location header set and an empty body,
+    /// `HttpError::Status` carrying the rejected code if it is not a 3xx, or
+    /// `HttpError::InvalidHeader` if `location` contains CR, LF or another
+    /// control character.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crate::response::response_templates;
+    /// use crate::message::http_value::StatusCode;
+    ///
+    /// let response = response_templates::redirect(StatusCode::PERMANENT_REDIRECT, "/new").unwrap();
+    /// ```
+    pub fn redirect<S: Into<StatusCode>>(
+        status_code: S,
+        location: impl Into<String>,
+    ) -> Result<HttpResponse, HttpError> {
+        let status_code = status_code.into();
+        if !status_code.is_redirection() {
+            return Err(HttpError::Status(status_code));
+        }
+        let location = location.into();
+        if location.chars().any(char::is_control) {
+            return Err(HttpError::InvalidHeader(format!(
+                "control character in redirect location {location:?}"
+            )));
+        }
+        let start_line = HttpStartLine::new_response(HttpVersion::Http11, status_code);
+        let mut meta = HttpMeta::new(start_line, HashMap::new());
+        meta.set_location(Some(location));
+        Ok(HttpResponse::new(meta, HttpBody::Empty))
+    }
+
+    /// Creates a redirect response that cannot send the client off-site.
+    ///
+    /// Use this when the location comes from the request, e.g. a `?next=`
+    /// parameter. Relative locations (`/account`, `../up`, `?page=2`) always
+    /// pass. Absolute ones, and scheme-relative ones like `//host/path`,
+    /// pass only when they use `http` or `https` and their host is in
+    /// `allowed_hosts`; an empty list allows same-origin locations only.
+    ///
+    /// # Arguments
+    ///
+    /// * `status_code` - The redirect status, which must be a 3xx code.
+    /// * `location` - The URL to redirect to.
+    /// * `allowed_hosts` - Hosts absolute locations may point at, without
+    ///   port, compared case-insensitively.
+    ///
+    /// # Returns
+    ///
+    /// The response from [`redirect`], or `HttpError::InvalidHeader` if the
+    /// location leaves the allowed hosts.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crate::response::response_templates;
+    /// use crate::message::http_value::StatusCode;
+    ///
+    /// let response =
+    ///     response_templates::redirect_within(StatusCode::FOUND, "https://example.com/", &["example.com"]);
+    /// assert!(response.is_ok());
+    /// ```
+    pub fn redirect_within<S: Into<StatusCode>>(
+        status_code: S,
+        location: impl Into<String>,
+        allowed_hosts: &[&str],
+    ) -> Result<HttpResponse, HttpError> {
+        let location = location.into();
+        if let Some(host) = redirect_target_host(&location) {
+            let allowed = host.is_some_and(|host| {
+                allowed_hosts
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(host))
+            });
+            if !allowed {
+                return Err(HttpError::InvalidHeader(format!(
+                    "redirect location {location:?} leaves the allowed hosts"
+                )));
+            }
+        }
+        redirect(status_code, location)
+    }
+
+    /// `None` for a location that stays on the current origin; otherwise
+    /// `Some` with the host it points at, or `Some(None)` when that is not
+    /// an `http(s)` host at all (`javascript:`, `data:`, ...).
+    fn redirect_target_host(location: &str) -> Option<Option<&str>> {
+        // Browsers read `\` as `/`, so `/\host` is scheme-relative too
+        fn strip_two_slashes(s: &str) -> Option<&str> {
+            let bytes = s.as_bytes();
+            (bytes.len() >= 2 && bytes[..2].iter().all(|b| matches!(b, b'/' | b'\\')))
+                .then(|| &s[2..])
+        }
+
+        let trimmed = location.trim_start();
+        let authority = match strip_two_slashes(trimmed) {
+            Some(authority) => authority,
+            None => {
+                let scheme_end = trimmed.find([':', '/', '\\', '?', '#'])?;
+                let scheme = &trimmed[..scheme_end];
+                if !trimmed[scheme_end..].starts_with(':')
+                    || !scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                {
+                    return None;
+                }
+                if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+                    return Some(None);
+                }
+                match strip_two_slashes(&trimmed[scheme_end + 1..]) {
+                    Some(authority) => authority,
+                    None => return Some(None),
+                }
+            }
+        };
+        let authority = authority
+            .split(['/', '\\', '?', '#'])
+            .next()
+            .unwrap_or_default();
+        // Drop userinfo, then the port
+        let host = authority.rsplit('@').next().unwrap_or_default();
+        let host = match host.strip_prefix('[') {
+            Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+            None => host.split(':').next().unwrap_or_default(),
+        };
+        Some((!host.is_empty()).then_some(host))
+    }
+
+    /// Percent-encodes the control characters of a location.
+    fn sanitize_location(location: &str) -> String {
+        let mut sanitized = String::with_capacity(location.len());
+        for c in location.chars() {
+            if c.is_control() {
+                let mut buf = [0; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    sanitized.push_str(&format!("%{byte:02X}"));
+                }
+            } else {
+                sanitized.push(c);
+            }
+        }
+        sanitized
+    }
+
+    /// Creates a 201 Created response pointing at the new resource.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - The URL of the created resource.
+    /// * `body` - The content to be sent in the response.
+    ///
+    /// # Returns
+    ///
+    /// An `HttpResponse` with the Location header set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crate::response::response_templates;
+    ///
+    /// let response = response_templates::created("/users/42", "User created");
+    /// ```
+    pub fn created(location: impl Into<String>, body: impl Into<Vec<u8>>) -> HttpResponse {
+        let mut response = normal_response(StatusCode::CREATED, body);
+        response.meta.set_location(Some(location.into()));
+        response
+    }
+
+    /// Creates a 202 Accepted response for work that completes later.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The content to be sent in the response, e.g. a job id.
+    ///
+    /// # Returns
+    ///
+    /// An `HttpResponse` with status 202.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crate::response::response_templates;
+    ///
+    /// let response = response_templates::accepted("job queued");
+    /// ```
+    pub fn accepted(body: impl Into<Vec<u8>>) -> HttpResponse {
+        normal_response(StatusCode::ACCEPTED, body)
+    }
+
+    /// Creates a 204 No Content response.
+    ///
+    /// # Returns
+    ///
+    /// An `HttpResponse` with status 204, no body and no Content-Type or
+    /// Content-Length header.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crate::response::response_templates;
+    ///
+    /// let response = response_templates::no_content();
+    /// ```
+    pub fn no_content() -> HttpResponse {
+        let start_line = HttpStartLine::new_response(HttpVersion::Http11, StatusCode::NO_CONTENT);
+        let meta = HttpMeta::new(start_line, HashMap::new());
         HttpResponse::new(meta, HttpBody::Empty)
     }
 
@@ -227,9 +684,12 @@ pub mod response_templates {
     }
 
     pub fn serve_static_file(file: &str) -> HttpResponse {
+        static_file_response(&Path::new("templates").join(file))
+    }
+
+    fn static_file_response(file_path: &Path) -> HttpResponse {
         let start_line = HttpStartLine::new_response(HttpVersion::Http11, StatusCode::OK);
         let mut meta = HttpMeta::new(start_line, HashMap::new());
-        let file_path = Path::new("templates").join(file);
 
         // Set the response content type based on the file extension
         meta.set_content_type(HttpContentType::from_file_name(
@@ -244,9 +704,197 @@ pub mod response_templates {
             Ok(content) => content,
             Err(_) => return return_status(StatusCode::NOT_FOUND),
         };
+        meta.set_attribute("Accept-Ranges", "bytes");
+        if let Some(modified) = file_modified(file_path) {
+            meta.set_attribute("Last-Modified", format_http_date(modified));
+        }
         HttpResponse::new(meta, HttpBody::Binary(body))
     }
 
+    /// Like [`serve_static_file`], answering a conditional GET.
+    ///
+    /// Pass the request's `If-Modified-Since` header value, if any. When the
+    /// file has not changed since that date the response is a bodiless
+    /// [`not_modified`]; otherwise, or when the header is missing or
+    /// malformed, the file is served as usual. The file's modification time
+    /// is compared at whole seconds, the precision of `Last-Modified`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let since = req.header("if-modified-since").map(|v| v.as_str().to_string());
+    /// response_templates::serve_static_file_conditional("app.js", since.as_deref())
+    /// ```
+    pub fn serve_static_file_conditional(
+        file: &str,
+        if_modified_since: Option<&str>,
+    ) -> HttpResponse {
+        conditional_file_response(&Path::new("templates").join(file), if_modified_since)
+    }
+
+    pub(super) fn conditional_file_response(
+        file_path: &Path,
+        if_modified_since: Option<&str>,
+    ) -> HttpResponse {
+        if let Some(modified) = file_modified(file_path)
+            && is_not_modified(modified, if_modified_since)
+        {
+            return not_modified(modified);
+        }
+        static_file_response(file_path)
+    }
+
+    /// Checks a write to a static file against the request's
+    /// `If-Unmodified-Since` header value.
+    ///
+    /// Returns a 412 Precondition Failed response when the file changed
+    /// after that date, so the client's copy is stale and the write should
+    /// not be applied. Returns `None` when the write may go ahead: the
+    /// header is missing or malformed, the file is unchanged, or it does not
+    /// exist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let since = req.header("if-unmodified-since").map(|v| v.as_str().to_string());
+    /// if let Some(rejected) = response_templates::check_unmodified_since("notes.txt", since.as_deref()) {
+    ///     return rejected;
+    /// }
+    /// ```
+    pub fn check_unmodified_since(
+        file: &str,
+        if_unmodified_since: Option<&str>,
+    ) -> Option<HttpResponse> {
+        unmodified_since_precondition(&Path::new("templates").join(file), if_unmodified_since)
+    }
+
+    pub(super) fn unmodified_since_precondition(
+        file_path: &Path,
+        if_unmodified_since: Option<&str>,
+    ) -> Option<HttpResponse> {
+        let modified = file_modified(file_path)?;
+        fails_unmodified_since(modified, if_unmodified_since)
+            .then(|| return_status(StatusCode::PRECONDITION_FAILED))
+    }
+
+    /// Creates a 304 Not Modified response.
+    ///
+    /// # Arguments
+    ///
+    /// * `last_modified` - When the resource last changed, sent as
+    ///   `Last-Modified`.
+    ///
+    /// # Returns
+    ///
+    /// An `HttpResponse` with status 304 and no body, Content-Type or
+    /// Content-Length header.
+    pub fn not_modified(last_modified: SystemTime) -> HttpResponse {
+        let start_line = HttpStartLine::new_response(HttpVersion::Http11, StatusCode::NOT_MODIFIED);
+        let mut meta = HttpMeta::new(start_line, HashMap::new());
+        meta.set_attribute("Last-Modified", format_http_date(last_modified));
+        HttpResponse::new(meta, HttpBody::Empty)
+    }
+
+    fn file_modified(file_path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(file_path)
+            .and_then(|meta| meta.modified())
+            .ok()
+    }
+
+    /// Like [`serve_static_file`], honouring the request's `Range` header.
+    ///
+    /// Pass the request's `Range` header value, if any; see
+    /// [`ranged_response`] for how it is answered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let range = req.header("range").map(|v| v.as_str().to_string());
+    /// response_templates::serve_static_file_ranged("video.mp4", range.as_deref())
+    /// ```
+    pub fn serve_static_file_ranged(file: &str, range: Option<&str>) -> HttpResponse {
+        let file_path = Path::new("templates").join(file);
+        let content_type = HttpContentType::from_file_name(
+            file_path
+                .file_name()
+                .unwrap_or_default()
+                .to_str()
+                .unwrap_or(""),
+        );
+        let mut response = match std::fs::read(&file_path) {
+            Ok(content) => ranged_response(content, content_type, range),
+            Err(_) => return return_status(StatusCode::NOT_FOUND),
+        };
+        if let Some(modified) = file_modified(&file_path) {
+            response
+                .meta
+                .set_attribute("Last-Modified", format_http_date(modified));
+        }
+        response
+    }
+
+    /// Serves `content` according to a `Range` header.
+    ///
+    /// * No (usable) range: 200 with the whole content.
+    /// * One range: 206 with that slice and its `Content-Range`.
+    /// * Several ranges: 206 `multipart/byteranges`, one part per range,
+    ///   each with its own `Content-Type` and `Content-Range`.
+    /// * Unsatisfiable or overlapping ranges: 416 with
+    ///   `Content-Range: bytes */<total>`.
+    ///
+    /// Every response advertises `Accept-Ranges: bytes`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hotaru_http::message::http_value::{HttpContentType, StatusCode};
+    /// use hotaru_http::message::response::response_templates;
+    ///
+    /// let mut response = response_templates::ranged_response(
+    ///     b"0123456789".to_vec(),
+    ///     HttpContentType::TextPlain(),
+    ///     Some("bytes=2-4"),
+    /// );
+    /// assert_eq!(response.meta.start_line.status_code(), StatusCode::PARTIAL_CONTENT);
+    /// assert_eq!(response.body.raw(), b"234".to_vec());
+    /// ```
+    pub fn ranged_response(
+        content: Vec<u8>,
+        content_type: HttpContentType,
+        range: Option<&str>,
+    ) -> HttpResponse {
+        let total = content.len();
+        let mut response = match parse_range(range, total) {
+            RangeRequest::Full => {
+                normal_response(StatusCode::OK, content).content_type(content_type)
+            }
+            RangeRequest::Ranges(ranges) if ranges.len() == 1 => {
+                let range = ranges[0];
+                normal_response(
+                    StatusCode::PARTIAL_CONTENT,
+                    &content[range.start..=range.end],
+                )
+                .content_type(content_type)
+                .add_header("Content-Range", range.content_range(total))
+            }
+            RangeRequest::Ranges(ranges) => {
+                let boundary = byteranges_boundary(&content);
+                let body =
+                    multipart_byteranges(&content, &ranges, &content_type.to_string(), &boundary);
+                normal_response(StatusCode::PARTIAL_CONTENT, body).content_type(
+                    HttpContentType::Multipart {
+                        subtype: "byteranges".to_string(),
+                        boundary: Some(boundary),
+                    },
+                )
+            }
+            RangeRequest::Unsatisfiable => return_status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .add_header("Content-Range", format!("bytes */{total}")),
+        };
+        response.meta.set_attribute("Accept-Ranges", "bytes");
+        response
+    }
+
     /// Creates an HTTP response with a specified status code and binary body.
     ///
     /// # Arguments
@@ -414,3 +1062,418 @@ pub mod response_templates {
 //         }};
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::response_templates::*;
+    use crate::message::http_value::{HttpVersion, StatusCode};
+    use crate::protocol::error::HttpError;
+
+    #[test]
+    fn created_sets_status_and_location() {
+        let mut response = created("/users/42", "made");
+        assert_eq!(response.meta.start_line.status_code(), StatusCode::CREATED);
+        assert_eq!(response.meta.get_location(), Some("/users/42".to_string()));
+        assert_eq!(response.body.raw(), b"made".to_vec());
+    }
+
+    #[test]
+    fn accepted_sets_status() {
+        let response = accepted("queued");
+        assert_eq!(response.meta.start_line.status_code(), StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn no_content_has_no_body_headers() {
+        let response = no_content();
+        assert_eq!(response.meta.start_line.status_code(), StatusCode::NO_CONTENT);
+
+        let mut meta = response.meta;
+        let body = response.body.into_static(&mut meta).await;
+        assert!(body.is_empty());
+        let head = meta.represent().to_lowercase();
+        assert!(!head.contains("content-length"), "{head}");
+        assert!(!head.contains("content-type"), "{head}");
+    }
+
+    #[tokio::test]
+    async fn custom_reason_phrase_on_status_line() {
+        let response = normal_response(StatusCode::OK, "done").with_reason("All Good Here");
+        let mut meta = response.meta;
+        response.body.into_static(&mut meta).await;
+        assert!(
+            meta.represent().starts_with("HTTP/1.1 200 All Good Here\r\n"),
+            "{}",
+            meta.represent()
+        );
+
+        // A phrase that would break the head is refused
+        let response = normal_response(StatusCode::OK, "")
+            .with_reason("OK\r\nSet-Cookie: x=1");
+        assert_eq!(response.meta.start_line.to_string(), "HTTP/1.1 200 OK");
+
+        // HTTP/2 has no reason phrase
+        let mut response = normal_response(StatusCode::OK, "").with_reason("Fine");
+        response.meta.start_line.set_http_version(HttpVersion::Http20);
+        assert!(!response.meta.start_line.to_string().contains("Fine"));
+    }
+
+    async fn content_type_header(response: super::HttpResponse) -> String {
+        let mut meta = response.meta;
+        response.body.into_static(&mut meta).await;
+        let head = meta.represent();
+        head.lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-type")
+                    .then(|| value.trim().to_string())
+            })
+            .unwrap_or_else(|| panic!("no Content-Type in {head}"))
+    }
+
+    #[tokio::test]
+    async fn text_helpers_declare_utf8_by_default() {
+        assert_eq!(
+            content_type_header(text_response("hi")).await,
+            "text/plain; charset=UTF-8"
+        );
+        assert_eq!(
+            content_type_header(html_response("<p>hi</p>")).await,
+            "text/html; charset=UTF-8"
+        );
+    }
+
+    #[tokio::test]
+    async fn text_helpers_take_another_charset() {
+        let latin1 = text_response_with_charset(b"caf\xe9".to_vec(), "ISO-8859-1");
+        assert_eq!(
+            content_type_header(latin1).await,
+            "text/plain; charset=ISO-8859-1"
+        );
+        assert_eq!(
+            content_type_header(html_response_with_charset("<p>hi</p>", "Shift_JIS")).await,
+            "text/html; charset=Shift_JIS"
+        );
+    }
+
+    #[test]
+    fn redirect_accepts_only_3xx() {
+        for status in [
+            StatusCode::MOVED_PERMANENTLY,
+            StatusCode::FOUND,
+            StatusCode::TEMPORARY_REDIRECT,
+            StatusCode::PERMANENT_REDIRECT,
+        ] {
+            let mut response = redirect(status.clone(), "/next").unwrap();
+            assert_eq!(response.meta.start_line.status_code(), status);
+            assert_eq!(response.meta.get_location(), Some("/next".to_string()));
+        }
+
+        assert!(matches!(
+            redirect(StatusCode::OK, "/next"),
+            Err(HttpError::Status(StatusCode::OK))
+        ));
+        assert!(redirect(404u16, "/next").is_err());
+    }
+
+    #[test]
+    fn redirect_rejects_crlf_in_location() {
+        for location in [
+            "/next\r\nSet-Cookie: session=stolen",
+            "/next\nX-Injected: 1",
+            "/next\rX-Injected: 1",
+            "/next\0",
+            "/next\x7f",
+        ] {
+            assert!(
+                matches!(
+                    redirect(StatusCode::FOUND, location),
+                    Err(HttpError::InvalidHeader(_))
+                ),
+                "{location:?}"
+            );
+        }
+
+        // The infallible helper encodes them instead
+        let mut response = redirect_response("/next\r\nSet-Cookie: x=1");
+        assert_eq!(
+            response.meta.get_location(),
+            Some("/next%0D%0ASet-Cookie: x=1".to_string())
+        );
+    }
+
+    #[test]
+    fn redirect_within_keeps_clients_on_allowed_hosts() {
+        for location in [
+            "/account",
+            "account?tab=2",
+            "../up",
+            "?page=2",
+            "#top",
+            "https://example.com/welcome",
+            "HTTP://Example.COM:8080/",
+            "//example.com/path",
+            "https://user@example.com",
+        ] {
+            let mut response = redirect_within(StatusCode::SEE_OTHER, location, &["example.com"])
+                .unwrap_or_else(|err| panic!("{location:?}: {err}"));
+            assert_eq!(response.meta.get_location(), Some(location.to_string()));
+        }
+
+        for location in [
+            "https://evil.com/",
+            "//evil.com",
+            "/\\evil.com",
+            "\\\\evil.com",
+            "https://example.com@evil.com/",
+            "https://example.com.evil.com/",
+            "javascript:alert(1)",
+            "data:text/html,hi",
+            "https:evil.com",
+            "https:\\\\evil.com",
+        ] {
+            assert!(
+                matches!(
+                    redirect_within(StatusCode::FOUND, location, &["example.com"]),
+                    Err(HttpError::InvalidHeader(_))
+                ),
+                "{location:?}"
+            );
+        }
+
+        // Same-origin only
+        assert!(redirect_within(StatusCode::FOUND, "/home", &[]).is_ok());
+        assert!(redirect_within(StatusCode::FOUND, "https://example.com/", &[]).is_err());
+    }
+
+    fn header(response: &super::HttpResponse, name: &str) -> Option<String> {
+        response.meta.get_header(name)
+    }
+
+    #[test]
+    fn two_ranges_give_multipart_byteranges() {
+        use crate::message::http_value::HttpContentType;
+
+        let content: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let mut response = ranged_response(
+            content.clone(),
+            HttpContentType::TextPlain(),
+            Some("bytes=0-99,200-299"),
+        );
+
+        assert_eq!(
+            response.meta.start_line.status_code(),
+            StatusCode::PARTIAL_CONTENT
+        );
+        assert_eq!(header(&response, "accept-ranges").as_deref(), Some("bytes"));
+        let Some(HttpContentType::Multipart {
+            subtype,
+            boundary: Some(boundary),
+        }) = response.meta.get_content_type()
+        else {
+            panic!("not multipart: {:?}", response.meta.get_content_type());
+        };
+        assert_eq!(subtype, "byteranges");
+
+        let body = response.body.raw();
+        let delimiter = format!("--{boundary}");
+        let mut parts = Vec::new();
+        let mut rest = &body[..];
+        // Split on the delimiter lines; the first chunk is the empty preamble.
+        while let Some(pos) = rest
+            .windows(delimiter.len())
+            .position(|w| w == delimiter.as_bytes())
+        {
+            parts.push(&rest[..pos]);
+            rest = &rest[pos + delimiter.len()..];
+        }
+        assert_eq!(rest, b"--\r\n");
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].is_empty());
+
+        for (part, (start, end)) in parts[1..].iter().zip([(0, 99), (200, 299)]) {
+            let head_end = part.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            let head = String::from_utf8_lossy(&part[..head_end]);
+            assert!(head.contains("Content-Type: text/plain"), "{head}");
+            assert!(
+                head.contains(&format!("Content-Range: bytes {start}-{end}/1000")),
+                "{head}"
+            );
+            let data = &part[head_end + 4..part.len() - 2];
+            assert_eq!(data, &content[start..=end]);
+        }
+    }
+
+    #[test]
+    fn multipart_response_parses_back_into_its_parts() {
+        use super::{MultipartPart, MultipartResponse};
+        use crate::message::http_value::HttpContentType;
+
+        let parts = [
+            MultipartPart::new()
+                .content_type(HttpContentType::TextPlain())
+                .header("Content-ID", "<summary>")
+                .body("two parts follow\r\n"),
+            MultipartPart::new()
+                .content_type(HttpContentType::ApplicationOctetStream())
+                .body((0..=255u8).collect::<Vec<u8>>()),
+        ];
+        let mut response = MultipartResponse::new()
+            .part(parts[0].clone())
+            .part(parts[1].clone())
+            .build();
+
+        assert_eq!(response.meta.start_line.status_code(), StatusCode::OK);
+        let content_type = response.meta.get_content_type().unwrap().to_string();
+        let boundary = content_type
+            .strip_prefix("multipart/mixed; boundary=")
+            .unwrap_or_else(|| panic!("{content_type}"));
+
+        let body = response.body.raw();
+        let delimiter = format!("--{boundary}");
+        let mut chunks = Vec::new();
+        let mut rest = &body[..];
+        while let Some(pos) = rest
+            .windows(delimiter.len())
+            .position(|w| w == delimiter.as_bytes())
+        {
+            chunks.push(&rest[..pos]);
+            rest = &rest[pos + delimiter.len()..];
+        }
+        // Empty preamble, one chunk per part, then the closing delimiter
+        assert_eq!(rest, b"--\r\n");
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[0].is_empty());
+
+        for (chunk, part) in chunks[1..].iter().zip(&parts) {
+            let chunk = chunk.strip_prefix(b"\r\n").unwrap();
+            let head_end = chunk.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            let headers: Vec<(String, String)> = String::from_utf8_lossy(&chunk[..head_end])
+                .split("\r\n")
+                .map(|line| {
+                    let (key, value) = line.split_once(": ").unwrap();
+                    (key.to_string(), value.to_string())
+                })
+                .collect();
+            assert_eq!(headers, part.headers());
+            assert_eq!(&chunk[head_end + 4..chunk.len() - 2], part.get_body());
+        }
+    }
+
+    #[test]
+    fn single_range_gets_content_range() {
+        use crate::message::http_value::HttpContentType;
+
+        let response = ranged_response(
+            b"0123456789".to_vec(),
+            HttpContentType::TextPlain(),
+            Some("bytes=-3"),
+        );
+        assert_eq!(
+            response.meta.start_line.status_code(),
+            StatusCode::PARTIAL_CONTENT
+        );
+        assert_eq!(
+            header(&response, "content-range").as_deref(),
+            Some("bytes 7-9/10")
+        );
+        assert_eq!(response.body.raw(), b"789".to_vec());
+    }
+
+    #[test]
+    fn unsatisfiable_range_gets_416() {
+        use crate::message::http_value::HttpContentType;
+
+        for range in ["bytes=10-20", "bytes=0-5,3-8"] {
+            let response = ranged_response(
+                b"0123456789".to_vec(),
+                HttpContentType::TextPlain(),
+                Some(range),
+            );
+            assert_eq!(
+                response.meta.start_line.status_code(),
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                "{range}"
+            );
+            assert_eq!(
+                header(&response, "content-range").as_deref(),
+                Some("bytes */10")
+            );
+            assert_eq!(header(&response, "accept-ranges").as_deref(), Some("bytes"));
+        }
+
+        // No Range header: the whole file, still advertising range support
+        let response = ranged_response(b"0123456789".to_vec(), HttpContentType::TextPlain(), None);
+        assert_eq!(response.meta.start_line.status_code(), StatusCode::OK);
+        assert_eq!(header(&response, "accept-ranges").as_deref(), Some("bytes"));
+    }
+
+    /// A file in the temp dir whose mtime has a sub-second part
+    fn file_modified_at(name: &str, modified: std::time::SystemTime) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("hotaru-{}-{name}", std::process::id()));
+        std::fs::write(&path, "body").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn conditional_get_answers_304_until_the_file_changes() {
+        use crate::util::http_date::format_http_date;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let modified = UNIX_EPOCH + Duration::from_millis(1_700_000_000_750);
+        let path = file_modified_at("conditional.txt", modified);
+
+        // The client echoes the Last-Modified it was given
+        let response = conditional_file_response(&path, None);
+        assert_eq!(response.meta.start_line.status_code(), StatusCode::OK);
+        let last_modified = header(&response, "last-modified").unwrap();
+        assert_eq!(last_modified, "Tue, 14 Nov 2023 22:13:20 GMT");
+
+        let response = conditional_file_response(&path, Some(&last_modified));
+        assert_eq!(
+            response.meta.start_line.status_code(),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            header(&response, "last-modified").as_deref(),
+            Some(last_modified.as_str())
+        );
+        assert!(response.body.raw().is_empty());
+
+        // A date before the last change gets the file again
+        let stale = format_http_date(modified - Duration::from_secs(3600));
+        let response = conditional_file_response(&path, Some(&stale));
+        assert_eq!(response.meta.start_line.status_code(), StatusCode::OK);
+        assert_eq!(response.body.raw(), b"body".to_vec());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn write_with_stale_if_unmodified_since_gets_412() {
+        use crate::util::http_date::format_http_date;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let modified = UNIX_EPOCH + Duration::from_millis(1_700_000_000_750);
+        let path = file_modified_at("precondition.txt", modified);
+
+        let current = format_http_date(modified);
+        assert!(unmodified_since_precondition(&path, Some(&current)).is_none());
+        assert!(unmodified_since_precondition(&path, None).is_none());
+
+        let stale = format_http_date(modified - Duration::from_secs(1));
+        let rejected = unmodified_since_precondition(&path, Some(&stale)).unwrap();
+        assert_eq!(
+            rejected.meta.start_line.status_code(),
+            StatusCode::PRECONDITION_FAILED
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -83,6 +83,22 @@ impl RequestStartLine {
         Ok(Self::new(http_version, method, path))
     }
 
+    /// Measures the request-target of a possibly incomplete request line.
+    ///
+    /// Counts the bytes after the first space up to the next space or line
+    /// ending, so the length can be checked while the line is still being
+    /// read. Returns 0 if the method has not been terminated yet.
+    pub fn target_length(line: &[u8]) -> usize {
+        let Some(start) = line.iter().position(|b| *b == b' ') else {
+            return 0;
+        };
+        let target = &line[start + 1..];
+        target
+            .iter()
+            .position(|b| matches!(b, b' ' | b'\r' | b'\n'))
+            .unwrap_or(target.len())
+    }
+
     /// Gets the parsed URL, parsing it if not already present.
     ///
     /// # Returns
@@ -132,6 +148,8 @@ impl std::fmt::Display for RequestStartLine {
 pub struct ResponseStartLine {
     pub http_version: HttpVersion,
     pub status_code: StatusCode,
+    /// Custom reason phrase; `None` writes the status code's standard one.
+    pub reason_phrase: Option<String>,
 }
 
 impl ResponseStartLine {
@@ -149,7 +167,28 @@ impl ResponseStartLine {
         Self {
             http_version,
             status_code,
+            reason_phrase: None,
+        }
+    }
+
+    /// Replaces the standard reason phrase written after the status code.
+    ///
+    /// Only HTTP/1.x status lines carry a reason phrase; HTTP/2 and HTTP/3
+    /// responses ignore it. A phrase with control characters such as CR or
+    /// LF is refused and the standard phrase kept.
+    ///
+    /// # Returns
+    ///
+    /// Whether the phrase was accepted.
+    pub fn set_reason_phrase<T: Into<String>>(&mut self, phrase: T) -> bool {
+        let phrase = phrase.into();
+        let valid = phrase
+            .bytes()
+            .all(|b| b == b'\t' || b == b' ' || (b >= 0x21 && b != 0x7f));
+        if valid {
+            self.reason_phrase = Some(phrase);
         }
+        valid
     }
 
     /// Parses a string into a response start line.
@@ -201,22 +240,31 @@ impl ResponseStartLine {
     ///
     /// A string representation of the ResponseStartLine.
     pub fn represent(&self) -> String {
-        format!(
-            "{} {}",
-            self.http_version.to_string(),
-            self.status_code.to_string()
-        )
+        self.to_string()
     }
 }
 
 impl std::fmt::Display for ResponseStartLine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{} {}",
-            self.http_version.to_string(),
-            self.status_code.to_string()
-        )
+        match &self.reason_phrase {
+            Some(reason)
+                if !matches!(self.http_version, HttpVersion::Http20 | HttpVersion::Http30) =>
+            {
+                write!(
+                    f,
+                    "{} {} {}",
+                    self.http_version.to_string(),
+                    self.status_code.as_u16(),
+                    reason
+                )
+            }
+            _ => write!(
+                f,
+                "{} {}",
+                self.http_version.to_string(),
+                self.status_code.to_string()
+            ),
+        }
     }
 }
 
@@ -820,6 +868,7 @@ impl HttpStartLine {
     ///
     /// If this start line represents a request, it will be converted to a response
     /// with the specified status code and default HTTP version (HTTP/1.1).
+    /// A custom reason phrase is dropped along with the old status code.
     ///
     /// # Arguments
     ///
@@ -841,6 +890,7 @@ impl HttpStartLine {
     pub fn set_status_code<T: Into<StatusCode>>(&mut self, status: T) {
         if let Self::Response(res) = self {
             res.status_code = status.into();
+            res.reason_phrase = None;
         } else {
             *self = Self::Response(ResponseStartLine::new(HttpVersion::Http11, status.into()));
         }
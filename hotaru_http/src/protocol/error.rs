@@ -1,5 +1,8 @@
+use std::collections::HashMap;
 use std::fmt;
 
+use akari::Value;
+
 use hotaru_core::connection::error::ConnectionError;
 use hotaru_core::protocol::ProtocolError;
 
@@ -26,6 +29,8 @@ pub enum HttpError {
     InvalidHeader(String),
     /// Invalid or malformed URI in the request line.
     InvalidUri(String),
+    /// Request-target exceeds the configured length (414 URI Too Long).
+    UriTooLong,
     /// Error in chunked transfer encoding parsing.
     ChunkError(String),
 
@@ -47,6 +52,11 @@ pub enum HttpError {
     /// Wraps a specific HTTP status code (for user-facing error responses).
     Status(StatusCode),
 
+    // ── Extraction ────────────────────────────────────────────────────
+    /// A typed extractor could not build its value from the request
+    /// (400 Bad Request when malformed, 422 Unprocessable Entity otherwise).
+    Extract(ExtractError),
+
     // ── Routing ───────────────────────────────────────────────────────
     /// No route matched the request path.
     NoRoute(String),
@@ -74,6 +84,7 @@ impl fmt::Display for HttpError {
             HttpError::ParseError(msg) => write!(f, "Parse error: {}", msg),
             HttpError::InvalidHeader(msg) => write!(f, "Invalid header: {}", msg),
             HttpError::InvalidUri(uri) => write!(f, "Invalid URI: {}", uri),
+            HttpError::UriTooLong => write!(f, "URI too long"),
             HttpError::ChunkError(msg) => write!(f, "Chunked transfer error: {}", msg),
             HttpError::PayloadTooLarge => write!(f, "Payload too large"),
             HttpError::MethodNotAllowed => write!(f, "Method not allowed"),
@@ -82,6 +93,7 @@ impl fmt::Display for HttpError {
             HttpError::TooManyHeaders => write!(f, "Too many headers"),
             HttpError::HeaderLineTooLong => write!(f, "Header line too long"),
             HttpError::Status(code) => write!(f, "HTTP status error: {:?}", code),
+            HttpError::Extract(err) => write!(f, "Extraction error: {}", err),
             HttpError::NoRoute(path) => write!(f, "No route matched path: {}", path),
             HttpError::Timeout => write!(f, "Request timed out"),
             HttpError::VersionNotSupported => write!(f, "HTTP version not supported"),
@@ -98,6 +110,7 @@ impl ProtocolError for HttpError {
     ///
     /// Recoverable errors (where a response can still be sent) return `true`:
     /// - `Status` — user-defined status response
+    /// - `Extract` — the request was read in full, only its content was rejected
     /// - `NoRoute` — 404, can send response and continue
     /// - `PayloadTooLarge`, `MethodNotAllowed`, `UnsupportedMediaType` — security checks
     /// - `HeaderTooLarge`, `TooManyHeaders`, `HeaderLineTooLong` — malformed request
@@ -109,10 +122,13 @@ impl ProtocolError for HttpError {
     /// Non-recoverable errors return `false`:
     /// - `Io` — I/O errors usually mean the connection is broken
     /// - `Connection` — connection-level failures
+    /// - `UriTooLong` — the rest of the request line is left unread, so a
+    ///   414 is sent and the connection closed
     fn can_continue(&self) -> bool {
         matches!(
             self,
             HttpError::Status(_)
+                | HttpError::Extract(_)
                 | HttpError::NoRoute(_)
                 | HttpError::PayloadTooLarge
                 | HttpError::MethodNotAllowed
@@ -130,6 +146,16 @@ impl ProtocolError for HttpError {
                 | HttpError::Other(_)
         )
     }
+
+    /// A caught handler panic is answered with `500 Internal Server Error`.
+    fn from_panic(message: &str) -> Option<Self> {
+        Some(HttpError::Other(format!("handler panicked: {message}")))
+    }
+
+    /// An outpoint call past its timeout fails with [`HttpError::Timeout`].
+    fn from_timeout(_after: std::time::Duration) -> Option<Self> {
+        Some(HttpError::Timeout)
+    }
 }
 
 // ── From impls ────────────────────────────────────────────────────────
@@ -146,6 +172,26 @@ impl From<ConnectionError> for HttpError {
     }
 }
 
+impl From<ExtractError> for HttpError {
+    fn from(err: ExtractError) -> Self {
+        HttpError::Extract(err)
+    }
+}
+
+impl From<BodyError> for HttpError {
+    fn from(err: BodyError) -> Self {
+        match err {
+            BodyError::TooLarge { .. } | BodyError::TooManyParts { .. } => {
+                HttpError::PayloadTooLarge
+            }
+            err @ BodyError::PartHeadersTooLarge { .. } => HttpError::ParseError(err.to_string()),
+            BodyError::Decode(msg) => HttpError::ParseError(format!("cannot decode body: {}", msg)),
+            BodyError::Consumed => HttpError::Other("body already consumed".to_string()),
+            BodyError::Io(err) => HttpError::Io(err),
+        }
+    }
+}
+
 impl From<StatusCode> for HttpError {
     fn from(code: StatusCode) -> Self {
         HttpError::Status(code)
@@ -164,6 +210,7 @@ impl From<&HttpError> for StatusCode {
             HttpError::ParseError(_) => StatusCode::BAD_REQUEST,
             HttpError::InvalidHeader(_) => StatusCode::BAD_REQUEST,
             HttpError::InvalidUri(_) => StatusCode::BAD_REQUEST,
+            HttpError::UriTooLong => StatusCode::URI_TOO_LONG,
             HttpError::ChunkError(_) => StatusCode::BAD_REQUEST,
             HttpError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
             HttpError::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
@@ -172,6 +219,7 @@ impl From<&HttpError> for StatusCode {
             HttpError::TooManyHeaders => StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
             HttpError::HeaderLineTooLong => StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
             HttpError::Status(code) => code.clone(),
+            HttpError::Extract(err) => err.status(),
             HttpError::NoRoute(_) => StatusCode::NOT_FOUND,
             HttpError::Timeout => StatusCode::REQUEST_TIMEOUT,
             HttpError::VersionNotSupported => StatusCode::HTTP_VERSION_NOT_SUPPORTED,
@@ -180,3 +228,220 @@ impl From<&HttpError> for StatusCode {
         }
     }
 }
+
+// ── Extraction errors ─────────────────────────────────────────────────
+
+/// Part of the request a typed extractor reads from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtractSource {
+    Json,
+    Form,
+    Query,
+}
+
+impl ExtractSource {
+    /// Lowercase name used in error bodies.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExtractSource::Json => "json",
+            ExtractSource::Form => "form",
+            ExtractSource::Query => "query",
+        }
+    }
+}
+
+/// A single offending field reported by an extractor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// Error returned by typed extractors when the request cannot be turned into
+/// the value a handler asked for: `HttpContext::json_result`,
+/// `HttpContext::form_result` and `HttpContext::query_as`.
+///
+/// Converts into [`HttpError::Extract`] with `?`, so a `Result`-returning
+/// endpoint answers with a JSON error body without further handling:
+///
+/// ```json
+/// {"error": "Unprocessable Entity", "source": "json",
+///  "fields": [{"field": "name", "message": "missing field"}]}
+/// ```
+///
+/// Handlers wanting a different shape can `map_err` the error themselves
+/// before returning it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractError {
+    /// The body or query string could not be parsed at all (400).
+    Malformed {
+        source: ExtractSource,
+        message: String,
+    },
+    /// It parsed, but fields are missing or have the wrong type (422).
+    Fields {
+        source: ExtractSource,
+        fields: Vec<FieldError>,
+    },
+}
+
+impl ExtractError {
+    pub fn malformed(source: ExtractSource, message: impl Into<String>) -> Self {
+        ExtractError::Malformed {
+            source,
+            message: message.into(),
+        }
+    }
+
+    pub fn missing_field(source: ExtractSource, field: impl Into<String>) -> Self {
+        Self::invalid_field(source, field, "missing field")
+    }
+
+    pub fn invalid_field(
+        source: ExtractSource,
+        field: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        ExtractError::Fields {
+            source,
+            fields: vec![FieldError {
+                field: field.into(),
+                message: message.into(),
+            }],
+        }
+    }
+
+    /// Adds another offending field. A `Malformed` error is left unchanged.
+    pub fn with_field(mut self, field: impl Into<String>, message: impl Into<String>) -> Self {
+        if let ExtractError::Fields { fields, .. } = &mut self {
+            fields.push(FieldError {
+                field: field.into(),
+                message: message.into(),
+            });
+        }
+        self
+    }
+
+    pub fn source(&self) -> ExtractSource {
+        match self {
+            ExtractError::Malformed { source, .. } | ExtractError::Fields { source, .. } => *source,
+        }
+    }
+
+    /// Offending fields; empty for `Malformed`.
+    pub fn fields(&self) -> &[FieldError] {
+        match self {
+            ExtractError::Malformed { .. } => &[],
+            ExtractError::Fields { fields, .. } => fields,
+        }
+    }
+
+    /// 400 Bad Request for `Malformed`, 422 Unprocessable Entity for `Fields`.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ExtractError::Malformed { .. } => StatusCode::BAD_REQUEST,
+            ExtractError::Fields { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+
+    /// JSON body sent for this error by the default error response.
+    pub fn to_json(&self) -> Value {
+        let mut body = HashMap::new();
+        body.insert(
+            "error".to_string(),
+            Value::Str(self.status().reason_phrase().to_string()),
+        );
+        body.insert(
+            "source".to_string(),
+            Value::Str(self.source().as_str().to_string()),
+        );
+        match self {
+            ExtractError::Malformed { message, .. } => {
+                body.insert("message".to_string(), Value::Str(message.clone()));
+            }
+            ExtractError::Fields { fields, .. } => {
+                let fields = fields
+                    .iter()
+                    .map(|f| {
+                        let mut entry = HashMap::new();
+                        entry.insert("field".to_string(), Value::Str(f.field.clone()));
+                        entry.insert("message".to_string(), Value::Str(f.message.clone()));
+                        Value::Dict(entry)
+                    })
+                    .collect();
+                body.insert("fields".to_string(), Value::List(fields));
+            }
+        }
+        Value::Dict(body)
+    }
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::Malformed { source, message } => {
+                write!(f, "malformed {} input: {}", source.as_str(), message)
+            }
+            ExtractError::Fields { source, fields } => {
+                write!(f, "invalid {} fields:", source.as_str())?;
+                for field in fields {
+                    write!(f, " {} ({})", field.field, field.message)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+// ── Body errors ───────────────────────────────────────────────────────
+
+/// Error returned when a request body is copied out of the context, as by
+/// `HttpContext::buffered_body_to_writer`, or parsed as multipart by
+/// `HttpContext::multipart`.
+///
+/// Converts into [`HttpError`] with `?`: an oversized body answers 413 and
+/// a failed write is an I/O error.
+#[derive(Debug)]
+pub enum BodyError {
+    /// The body is over the endpoint's `max_body_size` (413).
+    TooLarge { limit: usize },
+    /// The multipart body has more parts than `max_parts` allows (413).
+    TooManyParts { limit: usize },
+    /// A multipart part's header block is over `max_part_header_bytes`
+    /// (400).
+    PartHeadersTooLarge { limit: usize },
+    /// The body's content coding could not be undone (400).
+    Decode(String),
+    /// The body was already parsed or copied out, so there is nothing left
+    /// to copy.
+    Consumed,
+    /// Writing to the destination failed.
+    Io(std::io::Error),
+}
+
+impl fmt::Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyError::TooLarge { limit } => write!(f, "body exceeds {} bytes", limit),
+            BodyError::TooManyParts { limit } => {
+                write!(f, "multipart body has more than {} parts", limit)
+            }
+            BodyError::PartHeadersTooLarge { limit } => {
+                write!(f, "multipart part headers exceed {} bytes", limit)
+            }
+            BodyError::Decode(msg) => write!(f, "cannot decode body: {}", msg),
+            BodyError::Consumed => write!(f, "body already consumed"),
+            BodyError::Io(err) => write!(f, "I/O error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for BodyError {}
+
+impl From<std::io::Error> for BodyError {
+    fn from(err: std::io::Error) -> Self {
+        BodyError::Io(err)
+    }
+}
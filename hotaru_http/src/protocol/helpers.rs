@@ -67,6 +67,7 @@ pub fn not_found_response() -> HttpResponse {
 /// | `ParseError` | 400 Bad Request |
 /// | `InvalidHeader` | 400 Bad Request |
 /// | `InvalidUri` | 400 Bad Request |
+/// | `UriTooLong` | 414 URI Too Long |
 /// | `ChunkError` | 400 Bad Request |
 /// | `PayloadTooLarge` | 413 Payload Too Large |
 /// | `MethodNotAllowed` | 405 Method Not Allowed |
@@ -75,11 +76,15 @@ pub fn not_found_response() -> HttpResponse {
 /// | `TooManyHeaders` | 431 Request Header Fields Too Large |
 /// | `HeaderLineTooLong` | 431 Request Header Fields Too Large |
 /// | `Status(code)` | The wrapped status code |
+/// | `Extract` | 400 Bad Request (malformed) / 422 Unprocessable Entity |
 /// | `NoRoute` | 404 Not Found |
 /// | `Timeout` | 408 Request Timeout |
 /// | `VersionNotSupported` | 505 HTTP Version Not Supported |
 /// | `ProtocolViolation` | 400 Bad Request |
 /// | `Other` | 500 Internal Server Error |
+///
+/// `Extract` errors get a JSON body from [`ExtractError::to_json`](crate::protocol::error::ExtractError::to_json)
+/// instead of the HTML page, so API clients can see which fields were rejected.
 pub fn error_response_from(err: &dyn ProtocolError) -> HttpResponse {
     // Try to downcast to HttpError for fine-grained status mapping.
     // ProtocolError: std::error::Error + Send + Sync + 'static, so we can
@@ -87,6 +92,9 @@ pub fn error_response_from(err: &dyn ProtocolError) -> HttpResponse {
     let status = if let Some(http_err) =
         (err as &dyn std::error::Error).downcast_ref::<HttpError>()
     {
+        if let HttpError::Extract(extract) = http_err {
+            return response_templates::json_response(extract.to_json()).status(extract.status());
+        }
         http_err.into()
     } else {
         // Fallback: generic 500 for non-HttpError protocol errors.
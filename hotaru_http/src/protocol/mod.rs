@@ -3,7 +3,7 @@ pub mod error;
 pub mod helpers;
 pub mod protocol_impl;
 
-pub use error::HttpError;
+pub use error::{BodyError, ExtractError, ExtractSource, FieldError, HttpError};
 pub use traits::{DefaultHttpTransport, HTTP, Http1Protocol, Http1TcpProtocol};
 #[cfg(feature = "tls")]
 pub use traits::{HTTPS, Http1TlsProtocol};
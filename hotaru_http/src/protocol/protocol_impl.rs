@@ -11,7 +11,8 @@ use hotaru_core::{
     app::common::RuntimeConfig,
     connection::{ConnStream, HotaruRead, HotaruWrite, Outbound, TransportSpec},
     protocol::{
-        Channel, CtxError, Protocol, ProtocolError, ProtocolFlow, ProtocolRole, RequestContext,
+        Channel, CtxError, Detection, Protocol, ProtocolError, ProtocolFlow, ProtocolRole,
+        RequestContext,
     },
     url::UrlRoot,
 };
@@ -20,11 +21,12 @@ use hotaru_io_tokio::TcpStream;
 use crate::{
     channel::{Http1Channel, HttpChannel},
     context::HttpContext,
+    message::http_value::StatusCode,
     protocol::{
         error::HttpError,
         helpers::{error_response_from, is_keep_alive, is_response_keep_alive, not_found_response},
     },
-    security::safety::HttpSafety,
+    security::{concurrency::ConcurrencyLimit, safety::HttpSafety},
 };
 
 // ============================================================================
@@ -56,6 +58,19 @@ pub type HTTPS = Http1TlsProtocol;
 // Http1Protocol
 // ============================================================================
 
+/// Request-line prefixes recognised by HTTP/1.1 detection.
+const HTTP1_METHOD_PREFIXES: &[&[u8]] = &[
+    b"GET ",
+    b"POST ",
+    b"PUT ",
+    b"DELETE ",
+    b"HEAD ",
+    b"OPTIONS ",
+    b"PATCH ",
+    b"CONNECT ",
+    b"TRACE ",
+];
+
 /// HTTP/1.1 protocol handler.
 ///
 /// Generic over the concrete wire stream type so the same logic serves TCP
@@ -147,16 +162,8 @@ where
         }
     }
 
-    fn detect(initial_bytes: &[u8]) -> bool {
-        initial_bytes.starts_with(b"GET ")
-            || initial_bytes.starts_with(b"POST ")
-            || initial_bytes.starts_with(b"PUT ")
-            || initial_bytes.starts_with(b"DELETE ")
-            || initial_bytes.starts_with(b"HEAD ")
-            || initial_bytes.starts_with(b"OPTIONS ")
-            || initial_bytes.starts_with(b"PATCH ")
-            || initial_bytes.starts_with(b"CONNECT ")
-            || initial_bytes.starts_with(b"TRACE ")
+    fn detect(initial_bytes: &[u8]) -> Detection {
+        Detection::any_prefix(initial_bytes, HTTP1_METHOD_PREFIXES)
     }
 
     fn open_channel(
@@ -176,10 +183,31 @@ where
     ) -> Result<ProtocolFlow, <Self::Context as RequestContext>::Error> {
         // 1. Parse one request using the channel-stored safety baseline
         //    (no per-request HashMap lookup against RuntimeConfig).
-        let request = channel.parse_request(channel.safety()).await?;
+        let request = match channel.parse_request(channel.safety()).await {
+            Ok(request) => request,
+            Err(err @ (HttpError::UriTooLong | HttpError::ProtocolViolation(_))) => {
+                channel.send_response(error_response_from(&err)).await?;
+                return Ok(ProtocolFlow::Close);
+            }
+            Err(err) => return Err(err),
+        };
+        // In flight until the response is written, for phased shutdown.
+        let _in_flight = runtime.enter_request();
         let keep_alive = is_keep_alive(&request);
 
-        // 2. Walk URL tree.
+        // 2. Methods denied on the baseline get 405 whatever the route.
+        if channel.safety().is_method_denied(&request.meta.method()) {
+            channel
+                .send_response(error_response_from(&HttpError::MethodNotAllowed))
+                .await?;
+            return Ok(if keep_alive {
+                ProtocolFlow::Continue
+            } else {
+                ProtocolFlow::Close
+            });
+        }
+
+        // 3. Walk URL tree.
         let path = request.meta.path();
         let endpoint = match root.walk_str(&path).await {
             Some(node) => node,
@@ -194,7 +222,22 @@ where
             }
         };
 
-        // 3. Build context, run chain. Addresses come from the channel's meta.
+        if let Some(route) = endpoint.route() {
+            hotaru_core::trace::record_route(route);
+        }
+
+        // Routes with a RequestRecorder, from the endpoint or the runtime
+        // config, write the request out before anything can answer it.
+        #[cfg(feature = "record")]
+        if let Some(recorder) = endpoint
+            .get_params::<crate::record::RequestRecorder>()
+            .or_else(|| runtime.get_config::<crate::record::RequestRecorder>())
+            && let Err(_err) = recorder.record(&request).await
+        {
+            hotaru_core::debug_warn!("Failed to record request to {}: {}", path, _err);
+        }
+
+        // 4. Build context, run chain. Addresses come from the channel's meta.
         //    Seed ctx.safety from the protocol baseline so endpoint overrides
         //    overlay on top of it instead of falling back to defaults.
         let mut ctx = HttpContext::new_server(
@@ -207,8 +250,61 @@ where
         );
         ctx.install_channel(channel.clone());
 
-        match endpoint.run(ctx).await {
-            Ok(ctx) => {
+        // 5. The endpoint's own limits, overlaid on the baseline: body and
+        //    form size (413), methods (405) and content types (415).
+        if let Err(err) = ctx.request_check(&endpoint) {
+            channel.send_response(error_response_from(&err)).await?;
+            return Ok(if keep_alive {
+                ProtocolFlow::Continue
+            } else {
+                ProtocolFlow::Close
+            });
+        }
+
+        // 6. Unsafe methods replayable from TLS early data get 425 unless
+        //    the endpoint allows them; the client retries after the handshake.
+        if ctx.is_too_early() {
+            channel
+                .send_response(error_response_from(&HttpError::Status(
+                    StatusCode::TOO_EARLY,
+                )))
+                .await?;
+            return Ok(if keep_alive {
+                ProtocolFlow::Continue
+            } else {
+                ProtocolFlow::Close
+            });
+        }
+
+        // 7. Endpoints with a ConcurrencyLimit run that many requests at a
+        //    time; past it, a request waits for a slot if the limit queues
+        //    and otherwise gets 503. The slot is held until the response is
+        //    built.
+        let _permit = match endpoint.get_params::<ConcurrencyLimit>() {
+            Some(limit) => match limit.acquire().await {
+                Some(permit) => Some(permit),
+                None => {
+                    channel
+                        .send_response(error_response_from(&HttpError::Status(
+                            StatusCode::SERVICE_UNAVAILABLE,
+                        )))
+                        .await?;
+                    return Ok(if keep_alive {
+                        ProtocolFlow::Continue
+                    } else {
+                        ProtocolFlow::Close
+                    });
+                }
+            },
+            None => None,
+        };
+
+        // Shared with the context, so spans recorded before a handler error
+        // still reach the error response.
+        let timing = ctx.server_timing().clone();
+        match endpoint.run_guarded(ctx, &runtime).await {
+            Ok(mut ctx) => {
+                ctx.finalize_server_timing();
                 channel.send_response(ctx.response).await?;
                 Ok(if keep_alive {
                     ProtocolFlow::Continue
@@ -218,7 +314,11 @@ where
             }
             Err(err) if err.can_continue() => {
                 // Recoverable: map error to a response and keep going.
-                channel.send_response(error_response_from(&err)).await?;
+                let mut response = error_response_from(&err);
+                if let Some(value) = timing.header_value() {
+                    response.meta.set_attribute("Server-Timing", value);
+                }
+                channel.send_response(response).await?;
                 Ok(if keep_alive {
                     ProtocolFlow::Continue
                 } else {
@@ -280,14 +380,70 @@ mod tests {
     use crate::message::http_value::StatusCode;
     use crate::message::meta::HeaderValue;
     use crate::message::request::HttpRequest;
+    use crate::message::response::response_templates;
+    use hotaru_core::app::server::{Server, ShutdownPhase};
+    use hotaru_core::executable::middleware::AsyncFinalHandler;
+    use hotaru_core::executable::{ProtocolEntryBuilder, ProtocolRegistryBuilder};
+    use hotaru_core::extensions::ParamsClone;
+    use hotaru_io_tokio::testing::{http_get, send_raw};
+    use hotaru_rt_tokio::TokioRuntime;
+    use std::net::SocketAddr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream as TokioTcpStream;
+
+    type TestServer = Arc<Server<DefaultHttpTransport, TokioRuntime>>;
+
+    /// Registry with an HTTP/1 server protocol, for `add_route`.
+    fn routes() -> ProtocolRegistryBuilder<DefaultHttpTransport> {
+        ProtocolRegistryBuilder::new().protocol(ProtocolEntryBuilder::new(HTTP::server(
+            HttpSafety::default(),
+        )))
+    }
+
+    /// Handler answering every request with `body`.
+    fn reply(body: &'static str) -> Arc<dyn AsyncFinalHandler<HttpContext>> {
+        Arc::new(move |mut ctx: HttpContext| async move {
+            ctx.response = response_templates::text_response(body);
+            Ok(ctx)
+        })
+    }
+
+    /// Server on an OS-assigned loopback port, serving `builder`'s routes.
+    fn test_server(builder: ProtocolRegistryBuilder<DefaultHttpTransport>) -> TestServer {
+        Server::new().binding("127.0.0.1:0").handle(builder).build()
+    }
+
+    /// Binds `server` and runs it in the background. Returns the address of
+    /// its first binding, read back after binding so the port can't be
+    /// taken in between.
+    async fn serve(server: &TestServer) -> SocketAddr {
+        server.ensure_inbounds().await.unwrap();
+        tokio::spawn(server.clone().run_until(std::future::pending()));
+        server.local_addr().unwrap()
+    }
+
+    /// [`serve`]s `builder`'s routes on a fresh [`test_server`].
+    async fn spawn_server(builder: ProtocolRegistryBuilder<DefaultHttpTransport>) -> SocketAddr {
+        serve(&test_server(builder)).await
+    }
 
     #[test]
     fn test_http1_detection() {
-        assert!(HTTP::detect(b"GET / HTTP/1.1\r\n"));
-        assert!(HTTP::detect(b"POST /api HTTP/1.1\r\n"));
-        assert!(HTTP::detect(b"PUT /resource HTTP/1.1\r\n"));
-        assert!(!HTTP::detect(b"INVALID REQUEST\r\n"));
-        assert!(!HTTP::detect(b""));
+        assert_eq!(HTTP::detect(b"GET / HTTP/1.1\r\n"), Detection::Match);
+        assert_eq!(HTTP::detect(b"POST /api HTTP/1.1\r\n"), Detection::Match);
+        assert_eq!(HTTP::detect(b"PUT /resource HTTP/1.1\r\n"), Detection::Match);
+        assert_eq!(HTTP::detect(b"INVALID REQUEST\r\n"), Detection::NoMatch);
+        assert_eq!(HTTP::detect(b""), Detection::NoMatch);
+    }
+
+    #[test]
+    fn test_http1_detection_short_buffer() {
+        assert_eq!(HTTP::detect(b"G"), Detection::NeedMoreData);
+        assert_eq!(HTTP::detect(b"OPTIO"), Detection::NeedMoreData);
+        assert_eq!(HTTP::detect(b"GET"), Detection::NeedMoreData);
+        assert_eq!(HTTP::detect(b"GEX"), Detection::NoMatch);
+        // HTTP/2 preface start is not an HTTP/1 method.
+        assert_eq!(HTTP::detect(b"PRI"), Detection::NoMatch);
     }
 
     #[test]
@@ -311,12 +467,984 @@ mod tests {
         assert!(is_keep_alive(&request));
     }
 
+    #[tokio::test]
+    async fn test_overlong_uri_gets_414_before_line_ends() {
+        use hotaru_core::connection::ConnStream;
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // The request line never ends: parsing must stop at the limit
+        // rather than wait for the full line.
+        let client = tokio::spawn(async move {
+            let mut stream = TokioTcpStream::connect(addr).await.unwrap();
+            let mut line = b"GET /".to_vec();
+            line.extend(std::iter::repeat_n(b'a', 16 * 1024));
+            stream.write_all(&line).await.unwrap();
+            let mut response = vec![0u8; 64];
+            let n = stream.read(&mut response).await.unwrap();
+            response.truncate(n);
+            response
+        });
+
+        let (socket, _) = listener.accept().await.unwrap();
+        let (read, write, meta) = TcpStream::new(socket).split();
+        let safety = Arc::new(HttpSafety::new().with_max_uri_length(1024));
+        let channel = Http1Channel::<TcpStream>::new(
+            read.into_buf(),
+            write.into_buf_write(),
+            meta,
+            safety.clone(),
+        );
+
+        let err = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            channel.parse_request(&safety),
+        )
+        .await
+        .expect("parser waited for the full request line")
+        .unwrap_err();
+        assert!(matches!(err, HttpError::UriTooLong));
+        assert!(!err.can_continue());
+        assert!(!channel.is_open());
+
+        channel.send_response(error_response_from(&err)).await.unwrap();
+
+        let response = client.await.unwrap();
+        assert!(response.starts_with(b"HTTP/1.1 414 URI Too Long\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_accept_pauses_at_max_connections() {
+        use std::time::Duration;
+
+        let server: TestServer = Server::new()
+            .binding("127.0.0.1:0")
+            .handle(routes())
+            .max_connections(1)
+            .build();
+        let addr = serve(&server).await;
+
+        // Keeps the connection open, unlike `http_get`
+        async fn get_kept(stream: &mut TokioTcpStream) -> Vec<u8> {
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+                .await
+                .unwrap();
+            let mut response = vec![0u8; 64];
+            let n = stream.read(&mut response).await.unwrap();
+            response.truncate(n);
+            response
+        }
+
+        // The first client is served and keeps its connection open
+        let mut first = TokioTcpStream::connect(addr).await.unwrap();
+        assert!(get_kept(&mut first).await.starts_with(b"HTTP/1.1 404"));
+        assert_eq!(server.active_connections(), 1);
+
+        // The second one completes the TCP handshake in the backlog but
+        // is not accepted, so its request goes unanswered
+        let mut second = TokioTcpStream::connect(addr).await.unwrap();
+        let pending = tokio::time::timeout(Duration::from_millis(300), get_kept(&mut second)).await;
+        assert!(pending.is_err(), "second connection was accepted");
+        assert_eq!(server.active_connections(), 1);
+
+        // Once the first closes, the second is accepted and answered
+        drop(first);
+        let mut response = vec![0u8; 64];
+        let n = tokio::time::timeout(Duration::from_secs(5), second.read(&mut response))
+            .await
+            .expect("second connection was never accepted")
+            .unwrap();
+        assert!(response[..n].starts_with(b"HTTP/1.1 404"));
+    }
+
+    #[tokio::test]
+    async fn test_port_zero_binding_reports_assigned_port() {
+        let server = test_server(routes());
+        assert_eq!(server.local_addr(), None);
+
+        let addr = serve(&server).await;
+        assert_ne!(addr.port(), 0);
+        assert!(addr.ip().is_loopback());
+
+        // The reported port is the one the server accepts on
+        assert!(http_get(addr, "/").await.starts_with("HTTP/1.1 404"));
+    }
+
+    #[tokio::test]
+    async fn test_binding_from_env_replaces_earlier_bindings() {
+        // Unset, so the default applies in place of the first binding
+        let server: TestServer = Server::new()
+            .binding("127.0.0.1:0")
+            .binding_from_env("HOTARU_TEST_BIND_ADDR_UNSET", "127.0.0.1:0")
+            .unwrap()
+            .handle(routes())
+            .build();
+        server.ensure_inbounds().await.unwrap();
+        let addrs = server.local_addrs();
+        assert_eq!(addrs.len(), 1);
+        assert!(addrs[0].ip().is_loopback());
+    }
+
+    #[tokio::test]
+    async fn test_binding_from_env_listens_on_the_variable() {
+        // Only this test reads the variable, so setting it races nothing
+        unsafe { std::env::set_var("HOTARU_TEST_BIND_ADDR_SET", "127.0.0.1:0") };
+        let server: TestServer = Server::new()
+            .binding_from_env("HOTARU_TEST_BIND_ADDR_SET", "[::1]:3090")
+            .unwrap()
+            .handle(routes())
+            .build();
+        let addr = serve(&server).await;
+        assert_eq!(addr.ip(), core::net::Ipv4Addr::LOCALHOST);
+        assert_ne!(addr.port(), 0);
+        assert!(http_get(addr, "/").await.starts_with("HTTP/1.1 404"));
+    }
+
+    #[test]
+    fn test_binding_from_env_rejects_what_is_not_an_address() {
+        let err = Server::<DefaultHttpTransport, TokioRuntime>::new()
+            .binding_from_env("HOTARU_TEST_BIND_ADDR_UNSET", "localhost:3090")
+            .err()
+            .unwrap();
+        assert_eq!(err.address, "localhost:3090");
+        assert_eq!(err.var, None);
+
+        // Set in every test environment, and never a socket address
+        let err = Server::<DefaultHttpTransport, TokioRuntime>::new()
+            .binding_from_env("PATH", "127.0.0.1:0")
+            .err()
+            .unwrap();
+        assert_eq!(err.var.as_deref(), Some("PATH"));
+    }
+
+    #[tokio::test]
+    async fn test_smuggling_vectors_get_400_and_close() {
+        use std::time::Duration;
+
+        let builder = routes()
+            .add_route::<HTTP>("/ping", reply("pong"), vec![], ParamsClone::default())
+            .unwrap();
+        let addr = spawn_server(builder).await;
+
+        let vectors: [&[u8]; 3] = [
+            b"POST /ping HTTP/1.1\r\nHost: a\r\nContent-Length: 5\r\n\
+              Transfer-Encoding: chunked\r\n\r\n0\r\n\r\n",
+            b"POST /ping HTTP/1.1\r\nHost: a\r\nContent-Length: 0\r\n\
+              Content-Length: 44\r\n\r\n",
+            b"GET /ping HTTP/1.1\r\nHost: a\r\nX-Pad: a\r\n Transfer-Encoding: chunked\r\n\r\n",
+        ];
+        for raw in vectors {
+            // A pipelined request the server must not read as a second one
+            let mut bytes = raw.to_vec();
+            bytes.extend_from_slice(b"GET /ping HTTP/1.1\r\nHost: a\r\n\r\n");
+
+            let response = tokio::time::timeout(Duration::from_secs(5), send_raw(addr, bytes))
+                .await
+                .expect("connection closed after the 400");
+            assert!(response.starts_with("HTTP/1.1 400"), "{response}");
+            assert!(!response.contains("pong"), "{response}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multiple_bindings_serve_the_same_routes() {
+        let builder = routes()
+            .add_route::<HTTP>("/ping", reply("pong"), vec![], ParamsClone::default())
+            .unwrap();
+        let server: TestServer = Server::new()
+            .binding("127.0.0.1:0")
+            .binding("127.0.0.1:0")
+            .handle(builder)
+            .build();
+        let first = serve(&server).await;
+        let addrs = server.local_addrs();
+        assert_eq!(addrs.len(), 2);
+        assert_ne!(addrs[0], addrs[1]);
+        assert_eq!(first, addrs[0]);
+
+        for addr in addrs {
+            let response = http_get(addr, "/ping").await;
+            assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+            assert!(response.ends_with("pong"), "{response}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_globally_denied_method_gets_405_before_routing() {
+        use crate::message::http_value::HttpMethod;
+
+        let safety = HttpSafety::new().with_denied_method(HttpMethod::TRACE);
+        // No method restrictions on the route itself
+        let builder = ProtocolRegistryBuilder::new()
+            .protocol(ProtocolEntryBuilder::new(HTTP::server(safety)))
+            .add_route::<HTTP>("/ping", reply("pong"), vec![], ParamsClone::default())
+            .unwrap();
+        let addr = spawn_server(builder).await;
+
+        let mut client = TokioTcpStream::connect(addr).await.unwrap();
+        client
+            .write_all(b"TRACE /ping HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = vec![0u8; 256];
+        let n = client.read(&mut response).await.unwrap();
+        let response = String::from_utf8_lossy(&response[..n]);
+        assert!(
+            response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"),
+            "{response}"
+        );
+        assert!(!response.contains("pong"), "{response}");
+
+        // Other methods are still served, on the same connection
+        client
+            .write_all(b"GET /ping HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        let response = String::from_utf8(response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("pong"), "{response}");
+    }
+
+    use hotaru_core::connection::Inbound;
+
+    /// TCP transport whose inbound fails its first `accept` calls with an
+    /// injected error before accepting for real.
+    struct FlakyTransport;
+
+    struct FlakyInbound {
+        inner: hotaru_io_tokio::TcpInbound,
+        failures: std::sync::atomic::AtomicUsize,
+        error: fn() -> std::io::Error,
+    }
+
+    impl Inbound for FlakyInbound {
+        type Wire = TcpStream;
+        /// Address, number of failing accepts, and the error they return
+        type BindTarget = (String, usize, fn() -> std::io::Error);
+        type Error = std::io::Error;
+
+        async fn bind((addr, failures, error): Self::BindTarget) -> std::io::Result<Self> {
+            Ok(Self {
+                inner: hotaru_io_tokio::TcpInbound::bind(addr).await?,
+                failures: failures.into(),
+                error,
+            })
+        }
+
+        async fn accept(&self) -> std::io::Result<TcpStream> {
+            use std::sync::atomic::Ordering;
+
+            let left = self.failures.load(Ordering::SeqCst);
+            if left > 0 {
+                self.failures.store(left - 1, Ordering::SeqCst);
+                return Err((self.error)());
+            }
+            self.inner.accept().await
+        }
+
+        fn local_addr(&self) -> Option<std::net::SocketAddr> {
+            self.inner.local_addr()
+        }
+    }
+
+    impl TransportSpec for FlakyTransport {
+        type Wire = TcpStream;
+        type IoError = std::io::Error;
+        type Inbound = FlakyInbound;
+        type Outbound = hotaru_io_tokio::TcpOutbound;
+
+        fn default_inbound() -> Option<<Self::Inbound as Inbound>::BindTarget> {
+            None
+        }
+
+        fn default_outbound() -> Option<<Self::Outbound as Outbound>::ConnectTarget> {
+            None
+        }
+    }
+
+    fn flaky_server(
+        failures: usize,
+        error: fn() -> std::io::Error,
+    ) -> Arc<Server<FlakyTransport, TokioRuntime>> {
+        let http = Http1Protocol::<TcpStream, FlakyTransport>::server(HttpSafety::default());
+        Server::<FlakyTransport, TokioRuntime>::new()
+            .with_binding(("127.0.0.1:0".to_string(), failures, error))
+            .single_protocol(ProtocolEntryBuilder::new(http))
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_accept_loop_survives_transient_errors() {
+        // EMFILE twice in a row, as when the process is out of descriptors
+        let server = flaky_server(2, || std::io::Error::from_raw_os_error(24));
+        server.ensure_inbounds().await.unwrap();
+        let addr = server.local_addr().unwrap();
+        let loop_task = tokio::spawn(server.clone().try_run_until(std::future::pending()));
+
+        // Still accepting once the errors are behind it
+        assert!(http_get(addr, "/").await.starts_with("HTTP/1.1 404"));
+        assert!(!loop_task.is_finished());
+        loop_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_accept_loop_returns_fatal_errors() {
+        // EINVAL: the socket is not listening, retrying cannot help
+        let server = flaky_server(1, || std::io::ErrorKind::InvalidInput.into());
+        let err = server
+            .try_run_until(std::future::pending())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    /// TCP transport whose accepts each wait, after taking a connection,
+    /// until `parallelism` of them have one, and which records how many
+    /// accepts were in progress at once.
+    struct GatedTransport;
+
+    struct GatedInbound {
+        inner: hotaru_io_tokio::TcpInbound,
+        gate: tokio::sync::Barrier,
+        in_flight: std::sync::atomic::AtomicUsize,
+        peak: std::sync::atomic::AtomicUsize,
+    }
+
+    impl Inbound for GatedInbound {
+        type Wire = TcpStream;
+        /// Address and number of accepts the gate waits for
+        type BindTarget = (String, usize);
+        type Error = std::io::Error;
+
+        async fn bind((addr, parallelism): Self::BindTarget) -> std::io::Result<Self> {
+            Ok(Self {
+                inner: hotaru_io_tokio::TcpInbound::bind(addr).await?,
+                gate: tokio::sync::Barrier::new(parallelism),
+                in_flight: 0.into(),
+                peak: 0.into(),
+            })
+        }
+
+        async fn accept(&self) -> std::io::Result<TcpStream> {
+            use std::sync::atomic::Ordering;
+
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            let conn = self.inner.accept().await;
+            self.gate.wait().await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            conn
+        }
+
+        fn local_addr(&self) -> Option<std::net::SocketAddr> {
+            self.inner.local_addr()
+        }
+    }
+
+    impl TransportSpec for GatedTransport {
+        type Wire = TcpStream;
+        type IoError = std::io::Error;
+        type Inbound = GatedInbound;
+        type Outbound = hotaru_io_tokio::TcpOutbound;
+
+        fn default_inbound() -> Option<<Self::Inbound as Inbound>::BindTarget> {
+            None
+        }
+
+        fn default_outbound() -> Option<<Self::Outbound as Outbound>::ConnectTarget> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_accept_parallelism_accepts_concurrently() {
+        use std::sync::atomic::Ordering;
+        use std::time::Duration;
+
+        let http = Http1Protocol::<TcpStream, GatedTransport>::server(HttpSafety::default());
+        let server = Server::<GatedTransport, TokioRuntime>::new()
+            .with_binding(("127.0.0.1:0".to_string(), 3))
+            .single_protocol(ProtocolEntryBuilder::new(http))
+            .accept_parallelism(3)
+            .build();
+        assert_eq!(server.get_accept_parallelism(), 3);
+        let inbound = server.ensure_inbounds().await.unwrap()[0].clone();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(server.clone().run_until(std::future::pending()));
+
+        // No accept returns until three hold a connection, so the clients
+        // are only answered if three accepts run at once
+        let clients = (0..3).map(|_| http_get(addr, "/"));
+        let responses =
+            tokio::time::timeout(Duration::from_secs(5), futures::future::join_all(clients))
+                .await
+                .expect("connections were not accepted concurrently");
+        for response in responses {
+            assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+        }
+
+        // Never more accepts in progress than configured
+        assert_eq!(inbound.peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_request_over_unix_socket() {
+        use hotaru_io_tokio::{UnixStream, UnixTransport};
+        use tokio::net::UnixStream as TokioUnixStream;
+
+        type UnixHttp = Http1Protocol<UnixStream, UnixTransport>;
+
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext<UnixTransport>>> =
+            Arc::new(|mut ctx: HttpContext<UnixTransport>| async move {
+                ctx.response = response_templates::text_response(ctx.path());
+                Ok(ctx)
+            });
+        let builder = ProtocolRegistryBuilder::<UnixTransport>::new()
+            .protocol(ProtocolEntryBuilder::new(UnixHttp::server(
+                HttpSafety::default(),
+            )))
+            .add_route::<UnixHttp>("/over/uds", handler, vec![], ParamsClone::default())
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("hotaru-http-{}.sock", std::process::id()));
+        let server = Server::<UnixTransport, TokioRuntime>::new()
+            .binding_uds(&path)
+            .handle(builder)
+            .build();
+        server.ensure_inbounds().await.unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let run = tokio::spawn(server.clone().run_until(async {
+            let _ = stopped.await;
+        }));
+
+        let mut client = TokioUnixStream::connect(&path).await.unwrap();
+        client
+            .write_all(b"GET /over/uds HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("/over/uds"), "{response}");
+
+        // The socket file goes away with the server
+        stop.send(()).unwrap();
+        run.await.unwrap();
+        assert!(!path.exists());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn test_request_span_and_event_are_emitted() {
+        use std::sync::Mutex;
+
+        #[derive(Clone, Default)]
+        struct Captured(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Captured {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        // The test runtime is single-threaded, so the server tasks log to
+        // this thread's default subscriber
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_max_level(tracing::Level::INFO)
+            .finish();
+        let _default = tracing::subscriber::set_default(subscriber);
+
+        let builder = routes()
+            .add_route::<HTTP>("/traced/<id>", reply(""), vec![], ParamsClone::default())
+            .unwrap();
+        let addr = spawn_server(builder).await;
+        let response = http_get(addr, "/traced/7").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+        let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let event = logs
+            .lines()
+            .find(|line| line.contains("request handled"))
+            .unwrap_or_else(|| panic!("no request event in {logs:?}"));
+        assert!(event.contains("connection{connection_id="), "{event}");
+        assert!(event.contains("request{request_id="), "{event}");
+        // The pattern, not the path, so every id shares one label
+        assert!(event.contains("route=\"/traced/<id>\""), "{event}");
+        assert!(event.contains("keep_alive=false"), "{event}");
+    }
+
+    #[tokio::test]
+    async fn test_routes_added_programmatically_are_served() {
+        // Routes as they might come out of a config file
+        let config = vec![("/greet/<name>", "hello"), ("/farewell/<name>", "bye")];
+
+        let mut builder = routes();
+        for (pattern, greeting) in config {
+            let handler: Arc<dyn AsyncFinalHandler<HttpContext>> =
+                Arc::new(move |mut ctx: HttpContext| async move {
+                    let name = ctx.pattern("name").unwrap_or_default();
+                    ctx.response = response_templates::text_response(format!("{greeting} {name}"));
+                    Ok(ctx)
+                });
+            builder = builder
+                .add_route::<HTTP>(pattern, handler, vec![], ParamsClone::default())
+                .unwrap();
+        }
+
+        let addr = spawn_server(builder).await;
+
+        let response = http_get(addr, "/greet/ada").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("hello ada"), "{response}");
+        let response = http_get(addr, "/farewell/bob").await;
+        assert!(response.ends_with("bye bob"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn test_pattern_decodes_percent_encoded_segment() {
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext>> =
+            Arc::new(|mut ctx: HttpContext| async move {
+                let name = ctx.pattern("name").unwrap_or_default();
+                let raw = ctx.param_raw("name").unwrap_or_default();
+                ctx.response = response_templates::text_response(format!("{raw} [{name}]"));
+                Ok(ctx)
+            });
+        let builder = routes()
+            .add_route::<HTTP>("/user/<name>", handler, vec![], ParamsClone::default())
+            .unwrap();
+
+        let addr = spawn_server(builder).await;
+
+        let response = http_get(addr, "/user/John%20Doe").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("John%20Doe [John Doe]"), "{response}");
+        // A malformed escape is handed over as it came
+        let response = http_get(addr, "/user/50%zz").await;
+        assert!(response.ends_with("[50%zz]"), "{response}");
+        // An encoded separator stays encoded, so it can't climb directories
+        let response = http_get(addr, "/user/..%2F..%2Fetc%2Fpasswd").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("[..%2F..%2Fetc%2Fpasswd]"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn test_binding_label_tells_bindings_apart() {
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext>> =
+            Arc::new(|mut ctx: HttpContext| async move {
+                let label = ctx.binding_label().unwrap_or_else(|| "none".into());
+                ctx.response = response_templates::text_response(format!("[{label}]"));
+                Ok(ctx)
+            });
+        let builder = routes()
+            .add_route::<HTTP>("/whoami", handler, vec![], ParamsClone::default())
+            .unwrap();
+
+        let server: TestServer = Server::new()
+            .labeled_binding("127.0.0.1:0", "internal")
+            .binding("127.0.0.1:0")
+            .handle(builder)
+            .build();
+        serve(&server).await;
+        let addrs = server.local_addrs();
+        assert_eq!(addrs.len(), 2);
+
+        let response = http_get(addrs[0], "/whoami").await;
+        assert!(response.ends_with("[internal]"), "{response}");
+        // Untagged bindings are labeled with their bound address
+        let response = http_get(addrs[1], "/whoami").await;
+        assert!(response.ends_with(&format!("[{}]", addrs[1])), "{response}");
+    }
+
+    #[tokio::test]
+    async fn test_unsafe_early_data_request_gets_425() {
+        let handler = reply("served");
+        let mut replay_safe = ParamsClone::default();
+        replay_safe.set(HttpSafety::new().with_early_data_allowed(true));
+        let builder = routes()
+            .add_route::<HTTP>("/orders", handler.clone(), vec![], ParamsClone::default())
+            .unwrap()
+            .add_route::<HTTP>("/ping", handler, vec![], replay_safe)
+            .unwrap();
+        let addr = spawn_server(builder).await;
+
+        async fn send(addr: SocketAddr, method: &str, path: &str, early: bool) -> String {
+            let early = if early { "Early-Data: 1\r\n" } else { "" };
+            let raw = format!(
+                "{method} {path} HTTP/1.1\r\nHost: localhost\r\n{early}\
+                 Content-Length: 0\r\nConnection: close\r\n\r\n"
+            );
+            send_raw(addr, raw).await
+        }
+
+        // A replayable POST is refused before the handler runs
+        let response = send(addr, "POST", "/orders", true).await;
+        assert!(response.starts_with("HTTP/1.1 425"), "{response}");
+        // A GET in early data is served, as is the POST once the
+        // handshake is done
+        let response = send(addr, "GET", "/orders", true).await;
+        assert!(response.ends_with("served"), "{response}");
+        let response = send(addr, "POST", "/orders", false).await;
+        assert!(response.ends_with("served"), "{response}");
+        // An endpoint that opts in serves it from early data
+        let response = send(addr, "POST", "/ping", true).await;
+        assert!(response.ends_with("served"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit_sheds_or_queues_past_the_limit() {
+        use std::time::Duration;
+        use tokio::sync::Semaphore;
+
+        // Handlers hold their slot until the gate opens
+        let gate = Arc::new(Semaphore::new(0));
+        let handler_gate = gate.clone();
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext>> =
+            Arc::new(move |mut ctx: HttpContext| {
+                let gate = handler_gate.clone();
+                async move {
+                    let _ = gate.acquire().await;
+                    ctx.response = response_templates::text_response("done");
+                    Ok(ctx)
+                }
+            });
+        let report = ConcurrencyLimit(4);
+        let export = ConcurrencyLimit(1).with_queue(Duration::from_secs(5));
+        let mut report_config = ParamsClone::default();
+        report_config.set(report.clone());
+        let mut export_config = ParamsClone::default();
+        export_config.set(export.clone());
+        let builder = routes()
+            .add_route::<HTTP>("/report", handler.clone(), vec![], report_config)
+            .unwrap()
+            .add_route::<HTTP>("/export", handler, vec![], export_config)
+            .unwrap();
+        let addr = spawn_server(builder).await;
+
+        async fn wait_for(limit: &ConcurrencyLimit, in_flight: usize) {
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while limit.in_flight() < in_flight {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            })
+            .await
+            .expect("requests never reached the handler");
+        }
+
+        let running: Vec<_> = (0..4)
+            .map(|_| tokio::spawn(http_get(addr, "/report")))
+            .collect();
+        wait_for(&report, 4).await;
+        // The fifth is shed while the other four run
+        let response = http_get(addr, "/report").await;
+        assert!(response.starts_with("HTTP/1.1 503"), "{response}");
+
+        // A queueing limit holds the second request until the first is done
+        let first = tokio::spawn(http_get(addr, "/export"));
+        wait_for(&export, 1).await;
+        let second = tokio::spawn(http_get(addr, "/export"));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!second.is_finished());
+
+        gate.add_permits(1);
+        for response in running {
+            assert!(response.await.unwrap().ends_with("done"));
+        }
+        assert!(first.await.unwrap().ends_with("done"));
+        assert!(second.await.unwrap().ends_with("done"));
+        assert_eq!(report.in_flight(), 0);
+    }
+
+    /// A server whose `/slow` handler holds each request until `gate` gets
+    /// a permit, with one request already in flight and one idle
+    /// connection open. Returns the server and the two clients.
+    async fn serve_gated(
+        gate: Arc<tokio::sync::Semaphore>,
+    ) -> (TestServer, tokio::task::JoinHandle<String>, TokioTcpStream) {
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext>> =
+            Arc::new(move |mut ctx: HttpContext| {
+                let gate = gate.clone();
+                async move {
+                    let _ = gate.acquire().await;
+                    ctx.response = response_templates::text_response("done");
+                    Ok(ctx)
+                }
+            });
+        let builder = routes()
+            .add_route::<HTTP>("/slow", handler, vec![], ParamsClone::default())
+            .unwrap();
+        let server = test_server(builder);
+        server.ensure_inbounds().await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // Sent without `Connection: close`, so only the shutdown ends it
+        let busy = tokio::spawn(send_raw(
+            addr,
+            "GET /slow HTTP/1.1\r\nHost: localhost\r\n\r\n",
+        ));
+        let idle = TokioTcpStream::connect(addr).await.unwrap();
+        (server, busy, idle)
+    }
+
+    async fn run_gated_shutdown(
+        grace: std::time::Duration,
+        release_on_drain: bool,
+    ) -> (
+        Vec<(ShutdownPhase, usize)>,
+        String,
+        usize,
+        std::time::Duration,
+    ) {
+        use std::sync::Mutex;
+        use std::time::Duration;
+        use tokio::sync::{Semaphore, oneshot};
+
+        let gate = Arc::new(Semaphore::new(0));
+        let (server, busy, mut idle) = serve_gated(gate.clone()).await;
+        let (stop, stopped) = oneshot::channel::<()>();
+        let phases = Arc::new(Mutex::new(Vec::new()));
+        let runtime = server.runtime.clone();
+        let seen = phases.clone();
+        let run = tokio::spawn(server.clone().run_with_shutdown(
+            async {
+                let _ = stopped.await;
+            },
+            grace,
+            move |phase| {
+                // Which phase each request was still in flight in
+                seen.lock()
+                    .unwrap()
+                    .push((phase, runtime.in_flight_requests()));
+                if phase == ShutdownPhase::DrainInFlight && release_on_drain {
+                    gate.add_permits(1);
+                }
+            },
+        ));
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while server.runtime.in_flight_requests() < 1 || server.active_connections() < 2 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("clients never reached the server");
+        stop.send(()).unwrap();
+        let stopped_at = tokio::time::Instant::now();
+
+        tokio::time::timeout(Duration::from_secs(10), run)
+            .await
+            .expect("shutdown never finished")
+            .unwrap()
+            .unwrap();
+        let took = stopped_at.elapsed();
+        let mut buf = [0u8; 16];
+        let idle_read = idle.read(&mut buf).await.unwrap_or(0);
+        let phases = phases.lock().unwrap().clone();
+        assert_eq!(
+            server.shutdown_phase(),
+            phases.last().map(|(phase, _)| *phase)
+        );
+        assert_eq!(server.active_connections(), 0);
+        (phases, busy.await.unwrap(), idle_read, took)
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_phases_run_in_order_and_drain_requests() {
+        use std::time::Duration;
+
+        let (phases, response, idle_read, _) =
+            run_gated_shutdown(Duration::from_secs(5), true).await;
+
+        // The request was still running into DrainInFlight and done by CloseIdle
+        assert_eq!(
+            phases,
+            [
+                (ShutdownPhase::StopAccept, 1),
+                (ShutdownPhase::DrainInFlight, 1),
+                (ShutdownPhase::CloseIdle, 0),
+            ]
+        );
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.ends_with("done"), "{response}");
+        assert_eq!(idle_read, 0);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_force_closes_after_grace() {
+        use std::time::Duration;
+
+        let (phases, response, idle_read, _) =
+            run_gated_shutdown(Duration::from_millis(100), false).await;
+
+        assert_eq!(
+            phases,
+            [
+                (ShutdownPhase::StopAccept, 1),
+                (ShutdownPhase::DrainInFlight, 1),
+                (ShutdownPhase::ForceClose, 1),
+            ]
+        );
+        assert!(response.is_empty(), "{response}");
+        assert_eq!(idle_read, 0);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_grace_counts_from_the_stop_signal() {
+        use std::time::Duration;
+
+        let grace = Duration::from_millis(300);
+        let (phases, _, _, took) = run_gated_shutdown(grace, false).await;
+
+        assert_eq!(phases.last().unwrap().0, ShutdownPhase::ForceClose);
+        assert!(took >= grace, "{took:?}");
+        assert!(took < grace + Duration::from_millis(700), "{took:?}");
+    }
+
+    #[tokio::test]
+    async fn test_short_grace_closes_idle_connections_without_forcing() {
+        use std::time::Duration;
+
+        let grace = Duration::from_millis(200);
+        let (phases, response, idle_read, took) = run_gated_shutdown(grace, true).await;
+
+        assert_eq!(
+            phases,
+            [
+                (ShutdownPhase::StopAccept, 1),
+                (ShutdownPhase::DrainInFlight, 1),
+                (ShutdownPhase::CloseIdle, 0),
+            ]
+        );
+        assert!(response.ends_with("done"), "{response}");
+        assert_eq!(idle_read, 0);
+        assert!(took < grace, "{took:?}");
+    }
+
+    async fn serve_panicking_handler(catch_panics: bool) -> SocketAddr {
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext>> =
+            Arc::new(|ctx: HttpContext| async move {
+                if ctx.request.meta.path() == "/panic" {
+                    panic!("handler failed");
+                }
+                Ok(ctx)
+            });
+        let builder = routes()
+            .add_route::<HTTP>("/panic", handler, vec![], ParamsClone::default())
+            .unwrap();
+        let server: TestServer = Server::new()
+            .binding("127.0.0.1:0")
+            .catch_panics(catch_panics)
+            .handle(builder)
+            .build();
+        serve(&server).await
+    }
+
+    #[tokio::test]
+    async fn test_caught_panic_is_answered_with_500() {
+        let addr = serve_panicking_handler(true).await;
+        let response = http_get(addr, "/panic").await;
+        assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+        // The server keeps serving
+        let response = http_get(addr, "/panic").await;
+        assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn test_uncaught_panic_propagates_without_a_response() {
+        let addr = serve_panicking_handler(false).await;
+        assert_eq!(http_get(addr, "/panic").await, "");
+        // Only that connection's task went down
+        assert_eq!(http_get(addr, "/panic").await, "");
+    }
+
+    #[test]
+    fn test_add_route_needs_a_registered_protocol() {
+        use hotaru_core::url::UrlError;
+
+        let handler = |ctx: HttpContext| async move { Ok(ctx) };
+        let result = ProtocolRegistryBuilder::<DefaultHttpTransport>::new().add_route::<HTTP>(
+            "/orphan",
+            Arc::new(handler),
+            vec![],
+            ParamsClone::default(),
+        );
+        assert!(matches!(result, Err(UrlError::ProtocolNotFound)));
+    }
+
     #[test]
     fn test_not_found_response() {
         let resp = not_found_response();
         assert_eq!(resp.meta.start_line.status_code(), StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn test_extractor_errors_get_400_or_422() {
+        async fn search(mut ctx: HttpContext) -> Result<HttpContext, HttpError> {
+            let limit: u32 = ctx.query_as("limit")?;
+            let filter = ctx.json_result().await?.into_json();
+            ctx.response = response_templates::text_response(format!("{limit} {filter}"));
+            Ok(ctx)
+        }
+
+        let builder = routes()
+            .add_route::<HTTP>("/search", Arc::new(search), vec![], ParamsClone::default())
+            .unwrap();
+        let addr = spawn_server(builder).await;
+        let post = |target: &str, body: &str| {
+            format!(
+                "POST {target} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+        };
+
+        let ok = send_raw(addr, post("/search?limit=5", "[1]")).await;
+        assert!(ok.starts_with("HTTP/1.1 200"), "{ok}");
+        assert!(ok.ends_with("5 [1]"), "{ok}");
+
+        // Missing and unparsable fields name the offending parameter
+        let missing = send_raw(addr, post("/search", "[1]")).await;
+        assert!(missing.starts_with("HTTP/1.1 422"), "{missing}");
+        assert!(missing.contains("\"limit\""), "{missing}");
+        assert!(missing.contains("missing field"), "{missing}");
+        let invalid = send_raw(addr, post("/search?limit=many", "[1]")).await;
+        assert!(invalid.starts_with("HTTP/1.1 422"), "{invalid}");
+        assert!(invalid.contains("\"limit\""), "{invalid}");
+
+        // A body that is not JSON at all
+        let malformed = send_raw(addr, post("/search?limit=5", "{nope")).await;
+        assert!(malformed.starts_with("HTTP/1.1 400"), "{malformed}");
+        assert!(malformed.contains("\"json\""), "{malformed}");
+    }
+
+    #[tokio::test]
+    async fn test_error_response_keeps_server_timing() {
+        async fn failing(ctx: HttpContext) -> Result<HttpContext, HttpError> {
+            ctx.time_span("db").finish();
+            Err(HttpError::Status(StatusCode::SERVICE_UNAVAILABLE))
+        }
+
+        let builder = routes()
+            .add_route::<HTTP>("/fail", Arc::new(failing), vec![], ParamsClone::default())
+            .unwrap();
+        let addr = spawn_server(builder).await;
+
+        let response = http_get(addr, "/fail").await;
+        assert!(response.starts_with("HTTP/1.1 503"), "{response}");
+        assert!(response.contains("server-timing: db;dur="), "{response}");
+    }
+
     #[test]
     fn pattern_and_literal_sides_align() {
         use hotaru_core::url::tokens_to_patterns;
@@ -353,4 +1481,171 @@ mod tests {
             assert!(pat.matches(seg));
         }
     }
+
+    #[tokio::test]
+    async fn test_request_through_mock_transport() {
+        use hotaru_io_tokio::testing::{MockNetwork, MockStream, MockTransport};
+
+        type MockHttp = Http1Protocol<MockStream, MockTransport>;
+
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext<MockTransport>>> =
+            Arc::new(|mut ctx: HttpContext<MockTransport>| async move {
+                ctx.response = response_templates::text_response("hello");
+                Ok(ctx)
+            });
+        let builder = ProtocolRegistryBuilder::<MockTransport>::new()
+            .protocol(ProtocolEntryBuilder::new(MockHttp::server(
+                HttpSafety::default(),
+            )))
+            .add_route::<MockHttp>("/hello", handler, vec![], ParamsClone::default())
+            .unwrap();
+        let network = MockNetwork::new();
+        let server = Server::<MockTransport, TokioRuntime>::new()
+            .with_binding(network.clone())
+            .handle(builder)
+            .build();
+        tokio::spawn(server.run_until(std::future::pending()));
+
+        // The client's bytes arrive split mid-header
+        let client = network.connect();
+        client.send("GET /hello HTTP/1.1\r\nHost: local");
+        client.send("host\r\nConnection: close\r\n\r\n");
+        let response = client.read_to_end().await;
+        assert_eq!(
+            String::from_utf8(response).unwrap(),
+            "HTTP/1.1 200 OK\r\n\
+             content-type: text/plain; charset=UTF-8\r\n\
+             content-length: 5\r\n\
+             \r\n\
+             hello"
+        );
+    }
+
+    #[cfg(feature = "record")]
+    #[tokio::test]
+    async fn test_recorded_request_replays_to_the_same_response() {
+        use crate::message::body::HttpBody;
+        use crate::record::{RequestRecorder, replay};
+        use hotaru_io_tokio::TcpOutbound;
+
+        let dir = std::env::temp_dir().join(format!("hotaru-record-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Echoes what it was sent, minus the credentials
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext>> =
+            Arc::new(|mut ctx: HttpContext| async move {
+                let body = match &ctx.request.body {
+                    HttpBody::Buffer { data, .. } => String::from_utf8_lossy(data).into_owned(),
+                    _ => String::new(),
+                };
+                let reply = format!(
+                    "{} {} {} {}",
+                    ctx.request.meta.method(),
+                    ctx.request.meta.path(),
+                    ctx.request.meta.get_header("x-trace").unwrap_or_default(),
+                    body
+                );
+                ctx.response = response_templates::text_response(reply);
+                Ok(ctx)
+            });
+        let mut config = ParamsClone::default();
+        config.set(RequestRecorder::new(&dir));
+        let builder = routes()
+            .add_route::<HTTP>("/orders", handler, vec![], config)
+            .unwrap();
+        let addr = spawn_server(builder).await;
+
+        let original = send_raw(
+            addr,
+            b"POST /orders?id=7 HTTP/1.1\r\nHost: localhost\r\n\
+              Authorization: Bearer secret-token\r\nCookie: session=secret-id\r\n\
+              X-Trace: abc\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+              5\r\nhello\r\n0\r\n\r\n",
+        )
+        .await;
+        assert!(original.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(original.ends_with("\r\n\r\nPOST /orders abc hello"));
+
+        let recordings: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(recordings.len(), 1);
+        let recorded = std::fs::read_to_string(&recordings[0]).unwrap();
+        assert!(recorded.starts_with("POST /orders?id=7 HTTP/1.1\r\n"));
+        assert!(recorded.contains("Authorization: [redacted]\r\n"));
+        assert!(recorded.contains("Cookie: [redacted]\r\n"));
+        assert!(!recorded.contains("secret"));
+        assert!(recorded.contains("X-Trace: abc\r\n"));
+        // Stored de-chunked
+        assert!(!recorded.contains("Transfer-Encoding"));
+        assert!(recorded.ends_with("Content-Length: 5\r\n\r\nhello"));
+
+        let outbound = TcpOutbound::build(addr.to_string()).await.unwrap();
+        let replayed = replay(&recordings[0], &outbound, HttpSafety::default())
+            .await
+            .unwrap();
+        assert_eq!(replayed.meta.start_line.status_code(), StatusCode::OK);
+        let body = match replayed.body {
+            HttpBody::Buffer { data, .. } => data,
+            other => panic!("unexpected body {other:?}"),
+        };
+        assert_eq!(body, b"POST /orders abc hello");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_client_default_timeout_and_per_call_override() {
+        use hotaru_core::app::client::Client;
+        use hotaru_core::app::common::TimeoutSetting;
+        use hotaru_core::executable::ExecutableBinding;
+        use hotaru_io_tokio::testing::{MockNetwork, MockStream, MockTransport};
+        use std::time::Duration;
+
+        type MockHttp = Http1Protocol<MockStream, MockTransport>;
+
+        // The outpoint takes 200ms, as a slow upstream would
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext<MockTransport>>> =
+            Arc::new(|ctx: HttpContext<MockTransport>| async move {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                Ok(ctx)
+            });
+        let client = Client::<MockTransport, TokioRuntime>::new()
+            .target(MockNetwork::new())
+            .single_protocol(ProtocolEntryBuilder::new(MockHttp::client(
+                HttpSafety::default(),
+            )))
+            .default_timeout(Duration::from_millis(50))
+            .build();
+        client
+            .lit_url::<MockHttp, _, _>(
+                "slow",
+                "slow",
+                ExecutableBinding::new().with_handler(handler),
+                ParamsClone::default(),
+            )
+            .unwrap();
+
+        let result = client
+            .request_fn::<MockHttp>("slow", HttpRequest::default())
+            .await
+            .unwrap();
+        assert!(matches!(result, Err(HttpError::Timeout)));
+
+        // A longer timeout for one call lets it finish
+        let result = client
+            .request_fn_with_timeout::<MockHttp>(
+                "slow",
+                HttpRequest::default(),
+                TimeoutSetting::Fixed(Duration::from_secs(5)),
+            )
+            .await
+            .unwrap();
+        assert!(result.is_ok());
+        assert!(matches!(
+            client.get_default_timeout(),
+            TimeoutSetting::Fixed(limit) if limit == Duration::from_millis(50)
+        ));
+    }
 }
@@ -0,0 +1,200 @@
+//! Recording requests to replay them later.
+//!
+//! Enabled by the `record` feature. Put a [`RequestRecorder`] in an
+//! endpoint's config, or in the runtime config for every route, and each
+//! request routed there is written to its own file before it is handled:
+//!
+//! ```rust,ignore
+//! endpoint! {
+//!     APP.url("/checkout"),
+//!     config = [RequestRecorder::new("/var/tmp/recorded")],
+//!
+//!     pub checkout <HTTP> { ... }
+//! }
+//! ```
+//!
+//! A recording holds the request as it came off the wire: the request line,
+//! the headers in their original order and casing, and the body. A chunked
+//! body is stored de-chunked with a `Content-Length`, so the file is one
+//! plain HTTP/1.1 request. `Authorization` and `Cookie` values are replaced
+//! with [`REDACTED`] unless [`RequestRecorder::unredact`] says otherwise.
+//!
+//! [`replay`] sends a recording again through any `Outbound`, such as a
+//! `TcpOutbound` to a local copy of the app, so a production request can be
+//! debugged offline:
+//!
+//! ```rust,ignore
+//! let outbound = TcpOutbound::build("127.0.0.1:3000".into()).await?;
+//! let response = replay(path, &outbound, HttpSafety::default()).await?;
+//! ```
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hotaru_core::connection::{ConnStream, HotaruRead, HotaruWrite, Outbound};
+use hotaru_io_tokio::TokioIo;
+use tokio::io::BufReader;
+
+use crate::message::body::HttpBody;
+use crate::message::request::HttpRequest;
+use crate::message::response::HttpResponse;
+use crate::protocol::error::HttpError;
+use crate::security::safety::HttpSafety;
+use crate::send_request::send_request;
+
+/// Headers redacted unless set otherwise.
+pub const DEFAULT_REDACTED: &[&str] = &["authorization", "cookie"];
+
+/// Value written in place of a redacted header.
+pub const REDACTED: &str = "[redacted]";
+
+/// Writes each request it is given to a file of its own.
+///
+/// Clones share their file counter, so one recorder placed on several
+/// routes numbers their recordings together.
+#[derive(Debug, Clone)]
+pub struct RequestRecorder {
+    dir: PathBuf,
+    redacted: Vec<String>,
+    next: Arc<AtomicU64>,
+}
+
+impl RequestRecorder {
+    /// Records into `dir`, created on the first recording if missing.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            redacted: DEFAULT_REDACTED
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            next: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Also redacts the header `name`.
+    pub fn redact(mut self, name: impl AsRef<str>) -> Self {
+        let name = name.as_ref().to_ascii_lowercase();
+        if !self.redacted.contains(&name) {
+            self.redacted.push(name);
+        }
+        self
+    }
+
+    /// Records the header `name` as sent, even if it is redacted by default.
+    pub fn unredact(mut self, name: impl AsRef<str>) -> Self {
+        let name = name.as_ref();
+        self.redacted
+            .retain(|redacted| !redacted.eq_ignore_ascii_case(name));
+        self
+    }
+
+    /// The directory recordings are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether the value of the header `name` is left out of recordings.
+    pub fn is_redacted(&self, name: &str) -> bool {
+        self.redacted
+            .iter()
+            .any(|redacted| redacted.eq_ignore_ascii_case(name))
+    }
+
+    /// The bytes a recording of `request` holds.
+    pub fn to_bytes(&self, request: &HttpRequest) -> Vec<u8> {
+        let body: &[u8] = match &request.body {
+            HttpBody::Buffer { data, .. } | HttpBody::Binary(data) => data,
+            HttpBody::Text(text) => text.as_bytes(),
+            _ => &[],
+        };
+
+        let mut head = format!("{}\r\n", request.meta.start_line);
+        let mut had_length = false;
+        for (name, value) in request.meta.raw_headers().iter() {
+            // The framing is rewritten below to match the stored body
+            if name.eq_ignore_ascii_case("content-length") {
+                had_length = true;
+                continue;
+            }
+            if name.eq_ignore_ascii_case("transfer-encoding") {
+                continue;
+            }
+            let value = if self.is_redacted(name) {
+                REDACTED
+            } else {
+                value
+            };
+            head.push_str(&format!("{name}: {value}\r\n"));
+        }
+        if had_length || !body.is_empty() {
+            head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+        }
+        head.push_str("\r\n");
+
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Writes `request` to a new file in the recording directory and
+    /// returns its path.
+    ///
+    /// Files are named `<unix millis>-<counter>.http`, so listing the
+    /// directory sorts them in the order they were recorded.
+    pub async fn record(&self, request: &HttpRequest) -> std::io::Result<PathBuf> {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis());
+        let n = self.next.fetch_add(1, Ordering::Relaxed);
+        let path = self.dir.join(format!("{millis:013}-{n:06}.http"));
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(&path, self.to_bytes(request)).await?;
+        Ok(path)
+    }
+}
+
+/// Reads a recording back into a request, ready to be sent.
+///
+/// The body comes back decoded from any content coding; sending the request
+/// encodes it again as its `Content-Encoding` says.
+pub async fn load(path: impl AsRef<Path>) -> std::io::Result<HttpRequest> {
+    let bytes = tokio::fs::read(path).await?;
+    let mut reader = TokioIo::new(BufReader::new(Cursor::new(bytes)));
+    let mut request = HttpRequest::try_parse_lazy(&mut reader, &HttpSafety::default(), false)
+        .await
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string()))?;
+
+    // Sending writes out parsed bodies only, not the raw buffer
+    if let HttpBody::Buffer {
+        data,
+        content_coding,
+        ..
+    } = &mut request.body
+    {
+        let data = content_coding.decode_compressed(std::mem::take(data))?;
+        request.body = HttpBody::Binary(data);
+    }
+    Ok(request)
+}
+
+/// Sends the recording at `path` over a fresh wire from `outbound` and
+/// returns the response, as [`send_request`] does.
+pub async fn replay<O>(
+    path: impl AsRef<Path>,
+    outbound: &O,
+    safety: HttpSafety,
+) -> Result<HttpResponse, HttpError>
+where
+    O: Outbound,
+    HttpError: From<O::Error>,
+    <O::Wire as ConnStream>::ReadHalf: HotaruRead<Error = std::io::Error>,
+    <O::Wire as ConnStream>::WriteHalf: HotaruWrite<Error = std::io::Error>,
+{
+    let request = load(path).await?;
+    send_request(outbound, request, safety).await
+}
@@ -0,0 +1,209 @@
+//! Retry policy for outbound HTTP requests.
+//!
+//! A `429 Too Many Requests` or `503 Service Unavailable` usually says when
+//! to come back in `Retry-After`. [`RetryPolicy`] waits that long before
+//! sending the request again, capped at
+//! [`with_max_retry_after`](RetryPolicy::with_max_retry_after), and falls
+//! back to its own exponential backoff when the header is missing or
+//! malformed. [`send_request_with_retry`](crate::send_request::send_request_with_retry)
+//! runs a request under a policy.
+
+use std::time::{Duration, SystemTime};
+
+use crate::message::http_value::StatusCode;
+use crate::message::response::HttpResponse;
+use crate::util::http_date::parse_http_date;
+
+/// When and how often a request is sent again.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    backoff_multiplier: f64,
+    max_retry_after: Duration,
+    retryable_statuses: Vec<StatusCode>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            backoff_multiplier: 2.0,
+            max_retry_after: Duration::from_secs(60),
+            retryable_statuses: vec![
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::SERVICE_UNAVAILABLE,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Retries `429` and `503` up to 3 attempts in total.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the total number of attempts, including the first one.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the backoff before the first retry and its upper bound.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets the factor the backoff grows by after every retry.
+    ///
+    /// Factors below `1.0` are raised to `1.0`, so the backoff never
+    /// shrinks; NaN counts as `1.0` too.
+    pub fn with_backoff_multiplier(mut self, multiplier: f64) -> Self {
+        self.backoff_multiplier = if multiplier.is_nan() {
+            1.0
+        } else {
+            multiplier.max(1.0)
+        };
+        self
+    }
+
+    /// Sets the longest `Retry-After` delay honored; longer ones are cut
+    /// down to it.
+    pub fn with_max_retry_after(mut self, max: Duration) -> Self {
+        self.max_retry_after = max;
+        self
+    }
+
+    /// Sets the response statuses that trigger a retry.
+    pub fn with_retryable_statuses(mut self, statuses: Vec<StatusCode>) -> Self {
+        self.retryable_statuses = statuses;
+        self
+    }
+
+    /// Total number of attempts allowed.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Whether a response status is retried under this policy.
+    pub fn is_retryable(&self, status: &StatusCode) -> bool {
+        self.retryable_statuses.contains(status)
+    }
+
+    /// Backoff before retry number `retry` (1-based) when the response
+    /// names no delay.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = i32::try_from(retry.saturating_sub(1)).unwrap_or(i32::MAX);
+        let factor = self.backoff_multiplier.powi(exponent);
+        // Past what a `Duration` holds the cap applies anyway
+        Duration::try_from_secs_f64(self.initial_backoff.as_secs_f64() * factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+
+    /// Delay before retry number `retry` (1-based) after `response`.
+    ///
+    /// `None` means the response is final: its status is not retryable or
+    /// the attempts are used up. `now` dates a `Retry-After` given as an
+    /// HTTP-date.
+    pub fn retry_delay(
+        &self,
+        retry: u32,
+        response: &HttpResponse,
+        now: SystemTime,
+    ) -> Option<Duration> {
+        if retry >= self.max_attempts || !self.is_retryable(&response.meta.start_line.status_code())
+        {
+            return None;
+        }
+        let retry_after = response
+            .meta
+            .get_header("retry-after")
+            .and_then(|value| parse_retry_after(&value, now));
+        Some(match retry_after {
+            Some(delay) => delay.min(self.max_retry_after),
+            None => self.backoff(retry),
+        })
+    }
+}
+
+/// The delay a `Retry-After` value asks for, as of `now`.
+///
+/// Takes delay-seconds (`120`) and HTTP-dates in the three formats of
+/// RFC 9110 §5.6.7. A date already past gives zero; anything else is
+/// `None`.
+pub fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        // Too many digits for a u64 is still "a very long time"
+        return Some(Duration::from_secs(value.parse().unwrap_or(u64::MAX)));
+    }
+    let at = parse_http_date(value)?;
+    Some(at.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    #[test]
+    fn retry_after_accepts_seconds_and_all_date_formats() {
+        // Sun, 06 Nov 1994 08:49:37 GMT
+        let date = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        let now = date - Duration::from_secs(90);
+
+        assert_eq!(parse_retry_after("2", now), Some(Duration::from_secs(2)));
+        assert_eq!(
+            parse_retry_after(" 120 ", now),
+            Some(Duration::from_secs(120))
+        );
+        for value in [
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994",
+        ] {
+            assert_eq!(
+                parse_retry_after(value, now),
+                Some(Duration::from_secs(90)),
+                "{value}"
+            );
+        }
+        // Already past
+        let later = date + Duration::from_secs(5);
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", later),
+            Some(Duration::ZERO)
+        );
+        for value in ["", "-1", "1.5", "soon", "Sun, 06 Foo 1994 08:49:37 GMT"] {
+            assert_eq!(parse_retry_after(value, now), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn backoff_stays_capped_for_any_retry_count() {
+        let policy = RetryPolicy::new().with_max_attempts(100);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        for retry in [68, 99, u32::MAX] {
+            assert_eq!(policy.backoff(retry), Duration::from_secs(1), "{retry}");
+        }
+    }
+
+    #[test]
+    fn backoff_multiplier_below_one_is_raised() {
+        for multiplier in [f64::NAN, -2.0, 0.5] {
+            let policy = RetryPolicy::new().with_backoff_multiplier(multiplier);
+            assert_eq!(policy.backoff(1), Duration::from_millis(100));
+            assert_eq!(policy.backoff(5), Duration::from_millis(100));
+        }
+        let policy = RetryPolicy::new().with_backoff_multiplier(f64::INFINITY);
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,88 @@
+//! Per-endpoint concurrency limits.
+//!
+//! Some endpoints are expensive enough (report generation, exports) that
+//! only a few may run at once, whatever the server's overall capacity.
+//! Add a [`ConcurrencyLimit`] to the endpoint's config:
+//!
+//! ```rust,ignore
+//! endpoint! {
+//!     APP.url("/report"),
+//!     config = [ConcurrencyLimit(4)],
+//!
+//!     pub report <HTTP> { ... }
+//! }
+//! ```
+//!
+//! The HTTP dispatcher takes a slot before running the endpoint and gives it
+//! back once the response is built. A request past the limit is answered with
+//! 503 Service Unavailable, or waits for a slot first if the limit queues (see
+//! [`ConcurrencyLimit::with_queue`]).
+//!
+//! Clones share their slots, so one limit placed in the config of several
+//! routes (say through `resource!`) caps them together.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Caps how many requests an endpoint runs at once.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimit {
+    max: usize,
+    queue_timeout: Option<Duration>,
+    permits: Arc<Semaphore>,
+}
+
+/// Shorthand for [`ConcurrencyLimit::new`], so an endpoint config reads
+/// `config = [ConcurrencyLimit(4)]`.
+#[allow(non_snake_case)]
+pub fn ConcurrencyLimit(max: usize) -> ConcurrencyLimit {
+    ConcurrencyLimit::new(max)
+}
+
+impl ConcurrencyLimit {
+    /// Runs at most `max` requests at once and sheds the rest.
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            queue_timeout: None,
+            permits: Arc::new(Semaphore::new(max)),
+        }
+    }
+
+    /// Lets a request past the limit wait up to `timeout` for a slot
+    /// before it is shed.
+    pub fn with_queue(mut self, timeout: Duration) -> Self {
+        self.queue_timeout = Some(timeout);
+        self
+    }
+
+    /// The most requests run at once.
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// How long a request waits for a slot, if the limit queues.
+    pub fn queue_timeout(&self) -> Option<Duration> {
+        self.queue_timeout
+    }
+
+    /// Requests running right now.
+    pub fn in_flight(&self) -> usize {
+        self.max - self.permits.available_permits()
+    }
+
+    /// Takes a slot, waiting for one if the limit queues. `None` means the
+    /// request should be shed. The slot is free again once the permit is
+    /// dropped.
+    pub(crate) async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        match self.queue_timeout {
+            None => self.permits.clone().try_acquire_owned().ok(),
+            Some(timeout) => tokio::time::timeout(timeout, self.permits.clone().acquire_owned())
+                .await
+                .ok()?
+                .ok(),
+        }
+    }
+}
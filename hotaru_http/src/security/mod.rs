@@ -1 +1,2 @@
-﻿pub mod safety;
\ No newline at end of file
+﻿pub mod concurrency;
+pub mod safety;
@@ -23,10 +23,22 @@
 /// - max_body_size: 10MB (prevents memory exhaustion attacks)
 /// - max_header_size: 1MB (prevents header bomb attacks)
 /// - max_line_length: 64KB (prevents single-line DoS)
+/// - max_uri_length: 8KB (rejects oversized request targets with 414)
 /// - max_headers: 100 (prevents header count DoS)
+/// - max_form_buffer_size: 1MB (largest urlencoded body accepted; more is 413)
+/// - max_parts: 100 (parts in one multipart body; more is 413)
+/// - max_part_header_bytes: 8KB (header block of one multipart part; more is 400)
 ///
 /// Method and content-type filtering are intentionally permissive by default, as these
-/// are application-level concerns, not framework security concerns.
+/// are application-level concerns, not framework security concerns. A deployment that
+/// must never serve some methods, such as `TRACE`, can deny them on the protocol's
+/// baseline; those requests get 405 before routing.
+///
+/// ## Early Data
+/// A request marked `Early-Data: 1` by a TLS-terminating proxy (RFC 8470) was
+/// sent in TLS 0-RTT and can be replayed by an attacker. Unless the endpoint
+/// allows early data, such a request with an unsafe method is answered with
+/// 425 Too Early, and the client retries it after the handshake.
 #[derive(Debug, Clone)]
 pub struct HttpSafety {
     /// Maximum request body size (None = use default)
@@ -35,6 +47,9 @@ pub struct HttpSafety {
     /// Allowed HTTP methods (None = allow all methods)
     allowed_methods: Option<Vec<HttpMethod>>,
 
+    /// Denied HTTP methods (None = deny no methods)
+    denied_methods: Option<Vec<HttpMethod>>,
+
     /// Allowed content types (None = allow all content types)
     allowed_content_types: Option<Vec<HttpContentType>>,
 
@@ -46,6 +61,21 @@ pub struct HttpSafety {
 
     /// Maximum number of headers (None = use default)
     max_headers: Option<usize>,
+
+    /// Maximum request-target length in the request line (None = use default)
+    max_uri_length: Option<usize>,
+
+    /// Largest urlencoded body parsed into a map (None = use default)
+    max_form_buffer_size: Option<usize>,
+
+    /// Maximum number of parts in a multipart body (None = use default)
+    max_parts: Option<usize>,
+
+    /// Maximum header block size of one multipart part (None = use default)
+    max_part_header_bytes: Option<usize>,
+
+    /// Serve unsafe methods sent in TLS early data (None = reject them)
+    allow_early_data: Option<bool>,
 }
 
 // Default constants for safety parameters
@@ -53,6 +83,10 @@ const DEFAULT_MAX_BODY_SIZE: usize = 10 * 1024 * 1024; // 10 MB
 const DEFAULT_MAX_HEADER_SIZE: usize = 1024 * 1024; // 1 MB
 const DEFAULT_MAX_LINE_LENGTH: usize = 1024 * 64; // 64 KB
 const DEFAULT_MAX_HEADERS: usize = 100; // 100 headers
+const DEFAULT_MAX_URI_LENGTH: usize = 8 * 1024; // 8 KB
+const DEFAULT_MAX_FORM_BUFFER_SIZE: usize = 1024 * 1024; // 1 MB
+const DEFAULT_MAX_PARTS: usize = 100; // 100 parts
+const DEFAULT_MAX_PART_HEADER_BYTES: usize = 8 * 1024; // 8 KB
 
 impl HttpSafety {
     // --------------------------------------------------
@@ -71,10 +105,16 @@ impl HttpSafety {
         Self {
             max_body_size: None,
             allowed_methods: None,
+            denied_methods: None,
             allowed_content_types: None,
             max_header_size: None,
             max_line_length: None,
             max_headers: None,
+            max_uri_length: None,
+            max_form_buffer_size: None,
+            max_parts: None,
+            max_part_header_bytes: None,
+            allow_early_data: None,
         }
     }
 
@@ -98,6 +138,28 @@ impl HttpSafety {
         self.max_headers.unwrap_or(DEFAULT_MAX_HEADERS)
     }
 
+    /// Returns the effective URI length limit (set value or default)
+    fn effective_max_uri_length(&self) -> usize {
+        self.max_uri_length.unwrap_or(DEFAULT_MAX_URI_LENGTH)
+    }
+
+    /// Returns the effective form buffer limit (set value or default)
+    fn effective_max_form_buffer_size(&self) -> usize {
+        self.max_form_buffer_size
+            .unwrap_or(DEFAULT_MAX_FORM_BUFFER_SIZE)
+    }
+
+    /// Returns the effective multipart part count limit (set value or default)
+    fn effective_max_parts(&self) -> usize {
+        self.max_parts.unwrap_or(DEFAULT_MAX_PARTS)
+    }
+
+    /// Returns the effective part header limit (set value or default)
+    fn effective_max_part_header_bytes(&self) -> usize {
+        self.max_part_header_bytes
+            .unwrap_or(DEFAULT_MAX_PART_HEADER_BYTES)
+    }
+
     // --------------------------------------------------
     // Body Size Configuration
     // --------------------------------------------------
@@ -159,13 +221,51 @@ impl HttpSafety {
     ///
     /// Applications should explicitly set allowed_methods only when business logic requires
     /// restricting operations (e.g., read-only API allowing only GET), not for security.
+    ///
+    /// A method on the deny list is never allowed, whatever the allow list says.
     pub fn check_method(&self, method: &HttpMethod) -> bool {
+        if self.is_method_denied(method) {
+            return false;
+        }
         match &self.allowed_methods {
             Some(methods) => methods.contains(method),
             None => true, // No restrictions - allow all methods (see security note above)
         }
     }
 
+    // --------------------------------------------------
+    // Method Deny List Configuration
+    // --------------------------------------------------
+
+    /// Gets the denied methods list (None if unset = deny none)
+    pub fn denied_methods(&self) -> Option<&[HttpMethod]> {
+        self.denied_methods.as_deref()
+    }
+
+    /// Sets the denied methods list
+    pub fn set_denied_methods(&mut self, methods: Option<Vec<HttpMethod>>) {
+        self.denied_methods = methods;
+    }
+
+    /// Adds a method to the deny list
+    pub fn deny_method(&mut self, method: HttpMethod) {
+        let methods = self.denied_methods.get_or_insert_with(Vec::new);
+        if !methods.contains(&method) {
+            methods.push(method);
+        }
+    }
+
+    /// Checks if a method is on the deny list
+    ///
+    /// On the protocol's baseline safety this is checked before routing, so a
+    /// denied method gets 405 Method Not Allowed on every path, including
+    /// paths with no endpoint and endpoints that allow all methods.
+    pub fn is_method_denied(&self, method: &HttpMethod) -> bool {
+        self.denied_methods
+            .as_ref()
+            .is_some_and(|methods| methods.contains(method))
+    }
+
     // --------------------------------------------------
     // Content Type Allow List Configuration
     // --------------------------------------------------
@@ -278,6 +378,128 @@ impl HttpSafety {
         count <= self.effective_max_headers()
     }
 
+    // --------------------------------------------------
+    // URI Length Configuration
+    // --------------------------------------------------
+
+    /// Gets the request-target length limit (None if unset)
+    pub fn max_uri_length(&self) -> Option<usize> {
+        self.max_uri_length
+    }
+
+    /// Sets the request-target length limit explicitly
+    pub fn set_max_uri_length(&mut self, size: Option<usize>) {
+        self.max_uri_length = size;
+    }
+
+    /// Gets the effective request-target length limit (always returns a value)
+    pub fn effective_uri_length(&self) -> usize {
+        self.effective_max_uri_length()
+    }
+
+    /// Checks if a request-target length is within effective limits
+    pub fn check_uri_length(&self, size: usize) -> bool {
+        size <= self.effective_max_uri_length()
+    }
+
+    // --------------------------------------------------
+    // Form Buffer Configuration
+    // --------------------------------------------------
+
+    /// Gets the form buffer limit (None if unset)
+    ///
+    /// A request with a urlencoded body above this size is rejected with
+    /// 413, and `form()` gives `None` for one.
+    pub fn max_form_buffer_size(&self) -> Option<usize> {
+        self.max_form_buffer_size
+    }
+
+    /// Sets the form buffer limit explicitly
+    pub fn set_max_form_buffer_size(&mut self, size: Option<usize>) {
+        self.max_form_buffer_size = size;
+    }
+
+    /// Gets the effective form buffer limit (always returns a value)
+    pub fn effective_form_buffer_size(&self) -> usize {
+        self.effective_max_form_buffer_size()
+    }
+
+    /// Checks if a urlencoded body may be collected into a map
+    pub fn check_form_buffer_size(&self, size: usize) -> bool {
+        size <= self.effective_max_form_buffer_size()
+    }
+
+    // --------------------------------------------------
+    // Multipart Configuration
+    // --------------------------------------------------
+
+    /// Gets the multipart part count limit (None if unset)
+    ///
+    /// A multipart body with more parts than this is rejected with 413.
+    pub fn max_parts(&self) -> Option<usize> {
+        self.max_parts
+    }
+
+    /// Sets the multipart part count limit explicitly
+    pub fn set_max_parts(&mut self, count: Option<usize>) {
+        self.max_parts = count;
+    }
+
+    /// Gets the effective multipart part count limit (always returns a value)
+    pub fn effective_parts(&self) -> usize {
+        self.effective_max_parts()
+    }
+
+    /// Checks if a multipart body may have this many parts
+    pub fn check_parts(&self, count: usize) -> bool {
+        count <= self.effective_max_parts()
+    }
+
+    /// Gets the part header limit (None if unset)
+    ///
+    /// A multipart part whose header block is larger than this is rejected
+    /// with 400.
+    pub fn max_part_header_bytes(&self) -> Option<usize> {
+        self.max_part_header_bytes
+    }
+
+    /// Sets the part header limit explicitly
+    pub fn set_max_part_header_bytes(&mut self, size: Option<usize>) {
+        self.max_part_header_bytes = size;
+    }
+
+    /// Gets the effective part header limit (always returns a value)
+    pub fn effective_part_header_bytes(&self) -> usize {
+        self.effective_max_part_header_bytes()
+    }
+
+    /// Checks if a multipart part header block is within the limit
+    pub fn check_part_header_bytes(&self, size: usize) -> bool {
+        size <= self.effective_max_part_header_bytes()
+    }
+
+    // --------------------------------------------------
+    // Early Data Configuration
+    // --------------------------------------------------
+
+    /// Gets whether unsafe methods are served from early data (None if unset)
+    pub fn allow_early_data(&self) -> Option<bool> {
+        self.allow_early_data
+    }
+
+    /// Sets whether unsafe methods are served from early data
+    pub fn set_allow_early_data(&mut self, allow: Option<bool>) {
+        self.allow_early_data = allow;
+    }
+
+    /// Checks if a request sent in early data may be served
+    ///
+    /// Safe methods always may; others only where early data is allowed,
+    /// since the handler must then cope with the request being replayed.
+    pub fn check_early_data(&self, method: &HttpMethod) -> bool {
+        method.is_safe() || self.allow_early_data.unwrap_or(false)
+    }
+
     // --------------------------------------------------
     // Configuration Merging
     // --------------------------------------------------
@@ -306,6 +528,9 @@ impl HttpSafety {
         if source.allowed_methods.is_some() {
             self.allowed_methods = source.allowed_methods.clone();
         }
+        if source.denied_methods.is_some() {
+            self.denied_methods = source.denied_methods.clone();
+        }
         if source.allowed_content_types.is_some() {
             self.allowed_content_types = source.allowed_content_types.clone();
         }
@@ -318,6 +543,21 @@ impl HttpSafety {
         if source.max_headers.is_some() {
             self.max_headers = source.max_headers;
         }
+        if source.max_uri_length.is_some() {
+            self.max_uri_length = source.max_uri_length;
+        }
+        if source.max_form_buffer_size.is_some() {
+            self.max_form_buffer_size = source.max_form_buffer_size;
+        }
+        if source.max_parts.is_some() {
+            self.max_parts = source.max_parts;
+        }
+        if source.max_part_header_bytes.is_some() {
+            self.max_part_header_bytes = source.max_part_header_bytes;
+        }
+        if source.allow_early_data.is_some() {
+            self.allow_early_data = source.allow_early_data;
+        }
     }
 
     /// Merges another configuration using "most restrictive wins" policy
@@ -325,6 +565,8 @@ impl HttpSafety {
     /// # Merge Logic
     /// - **Size Limits**: Takes the minimum value (more restrictive)
     /// - **Allow Lists**: Takes the intersection of allowed values
+    /// - **Deny Lists**: Takes the union of denied values
+    /// - **Early Data**: Allowed only if both allow it
     /// - **Unset Parameters**: Treated as using default values during merge
     ///
     /// # Examples
@@ -369,6 +611,23 @@ impl HttpSafety {
                 .min(other.effective_max_headers()),
         );
 
+        self.max_uri_length = Some(
+            self.effective_max_uri_length()
+                .min(other.effective_max_uri_length()),
+        );
+
+        self.max_form_buffer_size = Some(
+            self.effective_max_form_buffer_size()
+                .min(other.effective_max_form_buffer_size()),
+        );
+
+        self.max_parts = Some(self.effective_max_parts().min(other.effective_max_parts()));
+
+        self.max_part_header_bytes = Some(
+            self.effective_max_part_header_bytes()
+                .min(other.effective_max_part_header_bytes()),
+        );
+
         // Merge method allow lists
         self.allowed_methods = match (&self.allowed_methods, &other.allowed_methods) {
             (Some(a), Some(b)) => Some(a.iter().filter(|m| b.contains(m)).cloned().collect()),
@@ -377,6 +636,19 @@ impl HttpSafety {
             (None, None) => None,
         };
 
+        // Merge method deny lists
+        self.denied_methods = match (&self.denied_methods, &other.denied_methods) {
+            (Some(a), Some(b)) => Some(
+                a.iter()
+                    .chain(b.iter().filter(|m| !a.contains(m)))
+                    .cloned()
+                    .collect(),
+            ),
+            (Some(_), None) => self.denied_methods.clone(),
+            (None, Some(_)) => other.denied_methods.clone(),
+            (None, None) => None,
+        };
+
         // Merge content type allow lists
         self.allowed_content_types =
             match (&self.allowed_content_types, &other.allowed_content_types) {
@@ -385,6 +657,12 @@ impl HttpSafety {
                 (None, Some(_)) => other.allowed_content_types.clone(),
                 (None, None) => None,
             };
+
+        // Merge early data: allowed only if both sides allow it
+        self.allow_early_data = match (self.allow_early_data, other.allow_early_data) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(false) && b.unwrap_or(false)),
+        };
     }
 
     // --------------------------------------------------
@@ -409,6 +687,18 @@ impl HttpSafety {
         self
     }
 
+    /// Builder method to add a single denied method
+    pub fn with_denied_method(mut self, method: HttpMethod) -> Self {
+        self.deny_method(method);
+        self
+    }
+
+    /// Builder method to set method deny list
+    pub fn with_denied_methods(mut self, methods: Vec<HttpMethod>) -> Self {
+        self.set_denied_methods(Some(methods));
+        self
+    }
+
     /// Builder method to add a single allowed content type
     pub fn with_allowed_content_type(mut self, content_type: HttpContentType) -> Self {
         self.add_content_type(content_type);
@@ -438,6 +728,36 @@ impl HttpSafety {
         self.set_max_headers(Some(size));
         self
     }
+
+    /// Builder method to set request-target length
+    pub fn with_max_uri_length(mut self, size: usize) -> Self {
+        self.set_max_uri_length(Some(size));
+        self
+    }
+
+    /// Builder method to set the form buffer limit
+    pub fn with_max_form_buffer_size(mut self, size: usize) -> Self {
+        self.set_max_form_buffer_size(Some(size));
+        self
+    }
+
+    /// Builder method to set the multipart part count limit
+    pub fn with_max_parts(mut self, count: usize) -> Self {
+        self.set_max_parts(Some(count));
+        self
+    }
+
+    /// Builder method to set the part header limit
+    pub fn with_max_part_header_bytes(mut self, size: usize) -> Self {
+        self.set_max_part_header_bytes(Some(size));
+        self
+    }
+
+    /// Builder method to serve unsafe methods from early data
+    pub fn with_early_data_allowed(mut self, allow: bool) -> Self {
+        self.set_allow_early_data(Some(allow));
+        self
+    }
 }
 
 impl Default for HttpSafety {
@@ -451,10 +771,16 @@ impl Default for &HttpSafety {
         static DEFAULT_SAFETY: HttpSafety = HttpSafety {
             max_body_size: None,
             allowed_methods: None,
+            denied_methods: None,
             allowed_content_types: None,
             max_header_size: None,
             max_line_length: None,
             max_headers: None,
+            max_uri_length: None,
+            max_form_buffer_size: None,
+            max_parts: None,
+            max_part_header_bytes: None,
+            allow_early_data: None,
         };
         &DEFAULT_SAFETY
     }
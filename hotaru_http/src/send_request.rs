@@ -22,8 +22,12 @@
 //!
 //! The caller is responsible for setting the `Host` header on `request` —
 //! the helper does not know the hostname (only the `Outbound` does).
+//!
+//! `send_request_with_retry` does the same under a [`RetryPolicy`], sending
+//! the request again while the upstream answers `429`/`503`.
 
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use hotaru_core::connection::{ConnStream, HotaruRead, HotaruWrite, Outbound};
 use hotaru_core::protocol::Channel;
@@ -33,6 +37,7 @@ use crate::channel::HttpChannel;
 use crate::message::request::HttpRequest;
 use crate::message::response::HttpResponse;
 use crate::protocol::error::HttpError;
+use crate::retry::RetryPolicy;
 use crate::security::safety::HttpSafety;
 
 /// Send one HTTP/1.1 request over a fresh wire from `outbound` and return
@@ -72,6 +77,36 @@ where
     result
 }
 
+/// [`send_request`] under `policy`: each retryable response is followed by
+/// a fresh attempt once the delay from its `Retry-After`, or the policy's
+/// backoff, has passed.
+///
+/// Returns the first response the policy does not retry, which is the last
+/// retryable one once the attempts run out. Connection and parse errors
+/// are returned right away, not retried.
+pub async fn send_request_with_retry<O>(
+    outbound: &O,
+    request: HttpRequest,
+    safety: HttpSafety,
+    policy: &RetryPolicy,
+) -> Result<HttpResponse, HttpError>
+where
+    O: Outbound,
+    HttpError: From<O::Error>,
+    <O::Wire as ConnStream>::ReadHalf: HotaruRead<Error = std::io::Error>,
+    <O::Wire as ConnStream>::WriteHalf: HotaruWrite<Error = std::io::Error>,
+{
+    let mut retry = 0;
+    loop {
+        let response = send_request(outbound, request.clone(), safety.clone()).await?;
+        retry += 1;
+        match policy.retry_delay(retry, &response, SystemTime::now()) {
+            Some(delay) => tokio::time::sleep(delay).await,
+            None => return Ok(response),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,6 +116,7 @@ mod tests {
 
     use crate::message::http_value::{HttpMethod, HttpVersion, StatusCode};
     use crate::message::start_line::HttpStartLine;
+    use std::time::Duration;
 
     async fn spawn_stub_http_server(body: &'static [u8]) -> std::net::SocketAddr {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -129,4 +165,50 @@ mod tests {
         };
         assert_eq!(body_bytes, b"pong-tcp");
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_waits_for_retry_after_then_succeeds() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // The first request gets a 503 asking for a 2 second pause
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let served = Arc::new(AtomicUsize::new(0));
+        let counter = served.clone();
+        tokio::spawn(async move {
+            while let Ok((mut sock, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = sock.read(&mut buf).await;
+                let response: &[u8] = if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    b"HTTP/1.1 503 Service Unavailable\r\nRetry-After: 2\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                } else {
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok"
+                };
+                let _ = sock.write_all(response).await;
+                let _ = sock.shutdown().await;
+            }
+        });
+
+        let outbound = TcpOutbound::build(addr.to_string()).await.unwrap();
+        let mut request = HttpRequest::default();
+        request.meta.start_line =
+            HttpStartLine::new_request(HttpVersion::Http11, HttpMethod::GET, "/flaky".to_string());
+        request.meta.set_host(Some(addr.to_string()));
+        // Without Retry-After the policy would only back off for 10ms
+        let policy =
+            RetryPolicy::new().with_backoff(Duration::from_millis(10), Duration::from_millis(10));
+
+        let started = tokio::time::Instant::now();
+        let response = send_request_with_retry(&outbound, request, HttpSafety::default(), &policy)
+            .await
+            .expect("send_request_with_retry");
+        let waited = started.elapsed();
+
+        assert_eq!(response.meta.start_line.status_code(), StatusCode::OK);
+        assert_eq!(served.load(Ordering::SeqCst), 2);
+        assert!(
+            waited >= Duration::from_secs(2) && waited < Duration::from_millis(2100),
+            "waited {waited:?}"
+        );
+    }
 }
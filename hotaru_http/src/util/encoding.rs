@@ -1,4 +1,4 @@
-﻿//! # HTTP Encoding
+//! # HTTP Encoding
 //!
 //! This module provides types and functionality for working with HTTP encoding mechanisms,
 //! specifically Transfer-Encoding and Content-Encoding as defined in HTTP standards.
@@ -197,15 +197,26 @@ impl ContentCoding {
     }
 
     pub fn decode_compressed(encoding: &ContentCoding, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        Self::decode_compressed_limited(encoding, data, usize::MAX)
+    }
+
+    /// Like [`decode_compressed`](Self::decode_compressed), but stops as
+    /// soon as the decoded data passes `limit` bytes, so a small body cannot
+    /// expand without bound. That fails with `io::ErrorKind::FileTooLarge`.
+    pub fn decode_compressed_limited(
+        encoding: &ContentCoding,
+        data: &[u8],
+        limit: usize,
+    ) -> std::io::Result<Vec<u8>> {
         match encoding {
             #[cfg(feature = "compression")]
-            ContentCoding::Gzip => compression::decompress_gzip(data),
+            ContentCoding::Gzip => compression::decompress_gzip_limited(data, limit),
             #[cfg(feature = "compression")]
-            ContentCoding::Deflate => compression::decompress_deflate(data),
+            ContentCoding::Deflate => compression::decompress_deflate_limited(data, limit),
             #[cfg(feature = "compression")]
-            ContentCoding::Brotli => compression::decompress_brotli(data),
+            ContentCoding::Brotli => compression::decompress_brotli_limited(data, limit),
             #[cfg(feature = "compression")]
-            ContentCoding::Zstd => compression::decompress_zstd(data),
+            ContentCoding::Zstd => compression::decompress_zstd_limited(data, limit),
             #[cfg(not(feature = "compression"))]
             ContentCoding::Gzip
             | ContentCoding::Deflate
@@ -218,6 +229,10 @@ impl ContentCoding {
                 std::io::ErrorKind::Unsupported,
                 "compress encoding not supported",
             )),
+            _ if data.len() > limit => Err(std::io::Error::new(
+                std::io::ErrorKind::FileTooLarge,
+                format!("decoded data exceeds {} bytes", limit),
+            )),
             _ => Ok(data.to_vec()), // Identity or unsupported
         }
     }
@@ -515,6 +530,17 @@ impl ContentCodings {
     /// assert_eq!(result, data);
     /// ```
     pub fn decode_compressed(&self, data: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        self.decode_compressed_limited(data, usize::MAX)
+    }
+
+    /// Decodes like [`decode_compressed`](Self::decode_compressed), failing
+    /// with `io::ErrorKind::FileTooLarge` as soon as any stage of decoding
+    /// would produce more than `limit` bytes.
+    pub fn decode_compressed_limited(
+        &self,
+        data: Vec<u8>,
+        limit: usize,
+    ) -> std::io::Result<Vec<u8>> {
         if self.is_identity() {
             return Ok(data);
         }
@@ -522,7 +548,7 @@ impl ContentCodings {
         let mut result = data;
         // Decompress in REVERSE order (last applied first)
         for coding in self.codings.iter().rev() {
-            result = ContentCoding::decode_compressed(coding, &result)?;
+            result = ContentCoding::decode_compressed_limited(coding, &result, limit)?;
         }
         Ok(result)
     }
@@ -3,6 +3,8 @@ use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
 use crate::message::http_value::ContentDisposition;
+use crate::protocol::error::BodyError;
+use crate::security::safety::HttpSafety;
 
 #[derive(Debug, Clone)]
 pub struct UrlEncodedForm {
@@ -18,20 +20,16 @@ impl UrlEncodedForm {
     }
 
     pub fn parse(body: Vec<u8>) -> Self {
-        let form_data = String::from_utf8_lossy(&body).to_string();
-        let mut form_map = HashMap::new();
-        for pair in form_data.split('&') {
-            let parts: Vec<&str> = pair.split('=').collect();
-            if parts.len() == 2 {
-                form_map.insert(
-                    decode_form_url_owned(parts[0]),
-                    decode_form_url_owned(parts[1]),
-                );
-            }
-        }
+        let form_map = Self::pairs(&body).collect();
         return UrlEncodedForm { data: form_map };
     }
 
+    /// Decodes the key/value pairs of a urlencoded body one at a time, in the
+    /// order they were sent, without collecting them into a map.
+    pub fn pairs(body: &[u8]) -> UrlEncodedPairs<'_> {
+        UrlEncodedPairs { rest: Some(body) }
+    }
+
     pub fn to_string(&self) -> String {
         let mut form_data = String::new();
         for (key, value) in &self.data {
@@ -71,6 +69,40 @@ impl UrlEncodedForm {
     }
 }
 
+/// Iterator over the pairs of a urlencoded body, see [`UrlEncodedForm::pairs`].
+///
+/// Like [`UrlEncodedForm::parse`], it skips segments that are not exactly
+/// one `key=value`. Repeated keys are all yielded.
+pub struct UrlEncodedPairs<'a> {
+    /// The body after the last segment read; `None` once it is used up
+    rest: Option<&'a [u8]>,
+}
+
+impl Iterator for UrlEncodedPairs<'_> {
+    type Item = (String, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(rest) = self.rest {
+            let part = match rest.iter().position(|&byte| byte == b'&') {
+                Some(end) => {
+                    self.rest = Some(&rest[end + 1..]);
+                    &rest[..end]
+                }
+                None => {
+                    self.rest = None;
+                    rest
+                }
+            };
+            let pair = String::from_utf8_lossy(part);
+            let mut parts = pair.split('=');
+            if let (Some(key), Some(value), None) = (parts.next(), parts.next(), parts.next()) {
+                return Some((decode_form_url_owned(key), decode_form_url_owned(value)));
+            }
+        }
+        None
+    }
+}
+
 impl From<HashMap<String, String>> for UrlEncodedForm {
     fn from(data: HashMap<String, String>) -> Self {
         Self { data }
@@ -147,7 +179,35 @@ impl MultiForm {
     /// // Test the file content and filename
     /// assert_eq!(form.get_first_file("file1").unwrap().filename(), Some("example.txt".to_string()));
     /// ```
+    ///
+    /// No limits are applied here; bodies from a client should go through
+    /// [`MultiForm::try_parse`].
     pub fn parse(body: Vec<u8>, boundary: String) -> Self {
+        Self::parse_limited(&body, &boundary, usize::MAX, usize::MAX)
+            .unwrap_or_else(|_| Self::new())
+    }
+
+    /// Parses a multipart form data body, enforcing the multipart limits of
+    /// `safety`.
+    ///
+    /// Fails with [`BodyError::TooManyParts`] once the body has more than
+    /// `max_parts` parts, and with [`BodyError::PartHeadersTooLarge`] when a
+    /// part's header block is over `max_part_header_bytes`.
+    pub fn try_parse(body: &[u8], boundary: &str, safety: &HttpSafety) -> Result<Self, BodyError> {
+        Self::parse_limited(
+            body,
+            boundary,
+            safety.effective_parts(),
+            safety.effective_part_header_bytes(),
+        )
+    }
+
+    fn parse_limited(
+        body: &[u8],
+        boundary: &str,
+        max_parts: usize,
+        max_part_header_bytes: usize,
+    ) -> Result<Self, BodyError> {
         /// Finds a subsequence within a larger sequence of bytes.
         fn find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
             haystack
@@ -197,6 +257,9 @@ impl MultiForm {
         while let Some(idx) = find_subsequence(&body[start_idx..], boundary_bytes) {
             // Skip the first boundary or add the part if not the first
             if start_idx > 0 {
+                if parts.len() == max_parts {
+                    return Err(BodyError::TooManyParts { limit: max_parts });
+                }
                 parts.push(&body[start_idx..start_idx + idx - 2]); // -2 to remove trailing CRLF
             }
 
@@ -220,7 +283,13 @@ impl MultiForm {
             }
 
             // Find headers and content separation (double CRLF)
-            if let Some(header_end) = find_subsequence(part, b"\r\n\r\n") {
+            let header_end = find_subsequence(part, b"\r\n\r\n");
+            if header_end.unwrap_or(part.len()) > max_part_header_bytes {
+                return Err(BodyError::PartHeadersTooLarge {
+                    limit: max_part_header_bytes,
+                });
+            }
+            if let Some(header_end) = header_end {
                 let headers = &part[..header_end];
                 let content = &part[header_end + 4..]; // +4 to skip the double CRLF
 
@@ -278,7 +347,7 @@ impl MultiForm {
             }
         }
 
-        form_map.into()
+        Ok(form_map.into())
     }
 
     /// Change a MultiForm into a string.
@@ -558,6 +627,19 @@ mod tests {
         assert_eq!(form.get("c").map(String::as_str), Some("1+2"));
     }
 
+    #[test]
+    fn pairs_are_yielded_in_order() {
+        let pairs: Vec<_> = UrlEncodedForm::pairs(b"b=2&a=1&&skip&x=y=z&b=Hello+world").collect();
+        assert_eq!(
+            pairs,
+            [
+                ("b".to_string(), "2".to_string()),
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "Hello world".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn parse_non_ascii_percent_encoded() {
         // UTF-8 "héllo" — é is 0xC3 0xA9
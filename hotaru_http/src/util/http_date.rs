@@ -0,0 +1,210 @@
+//! HTTP-dates and the conditional request headers that carry them.
+//!
+//! [`parse_http_date`] reads all three formats of RFC 9110 §5.6.7 and
+//! [`format_http_date`] writes the preferred IMF-fixdate, as used by
+//! `Last-Modified`. [`is_not_modified`] and [`fails_unmodified_since`]
+//! evaluate `If-Modified-Since` and `If-Unmodified-Since` (RFC 9110 §13.1.3,
+//! §13.1.4) against a resource's modification time.
+//!
+//! An HTTP-date has one-second resolution while filesystem times usually
+//! have finer, so modification times are truncated to whole seconds before
+//! any comparison. Otherwise a client echoing back the `Last-Modified` it was
+//! given would look stale and get a full 200 every time.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// Parses an IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`), an RFC 850
+/// date (`Sunday, 06-Nov-94 08:49:37 GMT`) or an asctime date
+/// (`Sun Nov  6 08:49:37 1994`).
+pub fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let (day, month, year, time) = match value.split_once(',') {
+        Some((_, rest)) => match rest.split_whitespace().collect::<Vec<_>>()[..] {
+            [day, month, year, time, "GMT"] => (day, month, year.parse().ok()?, time),
+            [date, time, "GMT"] => {
+                let mut date = date.split('-');
+                let (day, month, year) = (date.next()?, date.next()?, date.next()?);
+                if date.next().is_some() || year.len() != 2 {
+                    return None;
+                }
+                // Two-digit years pivot at 1970
+                let year: i64 = year.parse().ok()?;
+                let year = if year < 70 { 2000 + year } else { 1900 + year };
+                (day, month, year, time)
+            }
+            _ => return None,
+        },
+        None => match value.split_whitespace().collect::<Vec<_>>()[..] {
+            [_, month, day, time, year] => (day, month, year.parse().ok()?, time),
+            _ => return None,
+        },
+    };
+
+    let day: i64 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as i64 + 1;
+    let mut clock = time.split(':').map(|part| part.parse::<i64>().ok());
+    let (hour, minute, second) = (clock.next()??, clock.next()??, clock.next()??);
+    // The header is client-controlled: an unbounded year would overflow
+    // the arithmetic below
+    if clock.next().is_some()
+        || !(1..=9999).contains(&year)
+        || !(1..=31).contains(&day)
+        || !(0..24).contains(&hour)
+        || !(0..60).contains(&minute)
+        || !(0..=60).contains(&second)
+    {
+        return None;
+    }
+
+    let seconds = days_from_civil(year, month, day)
+        .checked_mul(86_400)?
+        .checked_add(hour * 3_600 + minute * 60 + second)?;
+    // Dates before the epoch are long past either way
+    Some(UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64))
+}
+
+/// Formats `time` as an IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// Sub-second precision is dropped; times before the epoch format as the
+/// epoch.
+pub fn format_http_date(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs()) as i64;
+    let (days, seconds) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let (year, month, day) = civil_from_days(days);
+    // 1970-01-01 was a Thursday
+    let weekday = WEEKDAYS[(days + 4).rem_euclid(7) as usize];
+    format!(
+        "{weekday}, {day:02} {} {year:04} {:02}:{:02}:{:02} GMT",
+        MONTHS[month as usize - 1],
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
+/// `time` at HTTP-date resolution, with any sub-second part dropped.
+pub fn truncate_to_seconds(time: SystemTime) -> SystemTime {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => UNIX_EPOCH + Duration::from_secs(since.as_secs()),
+        Err(_) => time,
+    }
+}
+
+/// Whether a GET for a resource last modified at `last_modified` can be
+/// answered with 304 Not Modified, given the request's `If-Modified-Since`.
+///
+/// True when the resource has not changed since that date. A missing or
+/// malformed header is ignored, so the result is `false`.
+pub fn is_not_modified(last_modified: SystemTime, if_modified_since: Option<&str>) -> bool {
+    match if_modified_since.and_then(|value| parse_http_date(value.trim())) {
+        Some(since) => truncate_to_seconds(last_modified) <= since,
+        None => false,
+    }
+}
+
+/// Whether a request's `If-Unmodified-Since` fails for a resource last
+/// modified at `last_modified`, which should be answered with 412
+/// Precondition Failed instead of applying the change.
+///
+/// True when the resource changed after that date. A missing or malformed
+/// header is ignored, so the result is `false`.
+pub fn fails_unmodified_since(
+    last_modified: SystemTime,
+    if_unmodified_since: Option<&str>,
+) -> bool {
+    match if_unmodified_since.and_then(|value| parse_http_date(value.trim())) {
+        Some(since) => truncate_to_seconds(last_modified) > since,
+        None => false,
+    }
+}
+
+/// Days from 1970-01-01 to the given proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The proleptic Gregorian date `days` after 1970-01-01, as
+/// `(year, month, day)`; the inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_imf_fixdate_and_parses_it_back() {
+        // Sun, 06 Nov 1994 08:49:37 GMT
+        let date = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        assert_eq!(format_http_date(date), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(parse_http_date(&format_http_date(date)), Some(date));
+
+        // Leap day, and sub-second precision dropped
+        let leap = UNIX_EPOCH + Duration::from_millis(1_709_210_096_750);
+        assert_eq!(format_http_date(leap), "Thu, 29 Feb 2024 12:34:56 GMT");
+        assert_eq!(
+            format_http_date(UNIX_EPOCH),
+            "Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+    }
+
+    #[test]
+    fn out_of_range_years_are_rejected() {
+        let huge = "Tue, 14 Nov 9999999999999999 22:13:20 GMT";
+        assert_eq!(parse_http_date(huge), None);
+        assert!(!is_not_modified(UNIX_EPOCH, Some(huge)));
+        assert_eq!(parse_http_date("Tue Nov 14 22:13:20 10000"), None);
+        assert_eq!(parse_http_date("Sat, 01 Jan 0000 00:00:00 GMT"), None);
+        assert!(parse_http_date("Fri, 31 Dec 9999 23:59:59 GMT").is_some());
+    }
+
+    #[test]
+    fn conditions_compare_at_whole_seconds() {
+        let date = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        let modified = date + Duration::from_millis(900);
+        let header = format_http_date(modified);
+
+        assert!(is_not_modified(modified, Some(&header)));
+        assert!(!is_not_modified(
+            modified + Duration::from_secs(1),
+            Some(&header)
+        ));
+        assert!(!is_not_modified(modified, None));
+        assert!(!is_not_modified(modified, Some("yesterday")));
+
+        assert!(!fails_unmodified_since(modified, Some(&header)));
+        assert!(fails_unmodified_since(
+            modified + Duration::from_secs(1),
+            Some(&header)
+        ));
+        assert!(!fails_unmodified_since(modified, Some("yesterday")));
+    }
+}
@@ -1,5 +1,9 @@
 ﻿pub mod cookie;
 pub mod encoding;
 pub mod form;
+pub mod http_date;
+pub mod range;
+pub mod server_timing;
+pub mod typed_header;
 #[cfg(test)]
 pub mod test;
@@ -0,0 +1,141 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Inclusive byte range of a representation, already resolved against its
+/// length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ByteRange {
+    /// `Content-Range` value for this range, e.g. `bytes 0-99/1000`.
+    pub fn content_range(&self, total: usize) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end, total)
+    }
+}
+
+/// What a `Range` header asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// No usable `Range` header: send the whole representation (200).
+    Full,
+    /// One or more satisfiable, non-overlapping ranges (206).
+    Ranges(Vec<ByteRange>),
+    /// No range can be served, or the ranges overlap (416).
+    Unsatisfiable,
+}
+
+/// Resolves a `Range` header against a representation of `total` bytes.
+///
+/// Headers with another unit or invalid syntax are ignored, as RFC 9110
+/// allows, and yield [`RangeRequest::Full`]. Individual ranges starting past
+/// the end are dropped; if none is left, or two ranges overlap, the request
+/// is unsatisfiable.
+///
+/// ```rust
+/// use hotaru_http::util::range::{parse_range, ByteRange, RangeRequest};
+///
+/// assert_eq!(
+///     parse_range(Some("bytes=0-99,-100"), 1000),
+///     RangeRequest::Ranges(vec![
+///         ByteRange { start: 0, end: 99 },
+///         ByteRange { start: 900, end: 999 },
+///     ])
+/// );
+/// ```
+pub fn parse_range(header: Option<&str>, total: usize) -> RangeRequest {
+    let Some(specs) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return RangeRequest::Full;
+    };
+
+    let mut ranges = Vec::new();
+    for spec in specs.split(',') {
+        let Some((first, last)) = spec.trim().split_once('-') else {
+            return RangeRequest::Full;
+        };
+        let range = match (first.trim(), last.trim()) {
+            ("", "") => return RangeRequest::Full,
+            // Suffix range: the last `n` bytes
+            ("", suffix) => match suffix.parse::<usize>() {
+                Ok(0) => None,
+                Ok(n) if total > 0 => Some(ByteRange {
+                    start: total.saturating_sub(n),
+                    end: total - 1,
+                }),
+                Ok(_) => None,
+                Err(_) => return RangeRequest::Full,
+            },
+            (start, end) => {
+                let Ok(start) = start.parse::<usize>() else {
+                    return RangeRequest::Full;
+                };
+                let end = match end {
+                    "" => usize::MAX,
+                    end => match end.parse::<usize>() {
+                        Ok(end) if end >= start => end,
+                        _ => return RangeRequest::Full,
+                    },
+                };
+                (start < total).then(|| ByteRange {
+                    start,
+                    end: end.min(total - 1),
+                })
+            }
+        };
+        ranges.extend(range);
+    }
+
+    if ranges.is_empty() {
+        return RangeRequest::Unsatisfiable;
+    }
+    let mut sorted = ranges.clone();
+    sorted.sort_by_key(|r| r.start);
+    if sorted.windows(2).any(|w| w[1].start <= w[0].end) {
+        return RangeRequest::Unsatisfiable;
+    }
+    RangeRequest::Ranges(ranges)
+}
+
+/// Boundary for a `multipart/byteranges` body that does not occur in
+/// `content`.
+pub fn byteranges_boundary(content: &[u8]) -> String {
+    let mut seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    loop {
+        let boundary = format!("hotaru-byteranges-{seed:x}");
+        if !content
+            .windows(boundary.len())
+            .any(|w| w == boundary.as_bytes())
+        {
+            return boundary;
+        }
+        seed = seed.wrapping_add(1);
+    }
+}
+
+/// Builds a `multipart/byteranges` body with one part per range, each
+/// carrying its own `Content-Type` and `Content-Range`.
+pub fn multipart_byteranges(
+    content: &[u8],
+    ranges: &[ByteRange],
+    content_type: &str,
+    boundary: &str,
+) -> Vec<u8> {
+    let mut body = Vec::new();
+    for range in ranges {
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Type: {content_type}\r\nContent-Range: {}\r\n\r\n",
+                range.content_range(content.len())
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(&content[range.start..=range.end]);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+    body
+}
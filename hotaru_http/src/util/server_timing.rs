@@ -0,0 +1,108 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// One `Server-Timing` metric: a name and how long it took.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingSpan {
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// Spans collected while handling one request.
+///
+/// Clones share the same list, so a [`SpanGuard`] can record into it without
+/// borrowing the context. Span names go on the wire as `Server-Timing` metric
+/// names, so any character not allowed in an HTTP token (spaces, commas,
+/// semicolons, control characters such as CR and LF) is replaced with `_`.
+#[derive(Debug, Clone, Default)]
+pub struct ServerTiming {
+    spans: Arc<Mutex<Vec<TimingSpan>>>,
+}
+
+impl ServerTiming {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a span that is recorded when the returned guard is dropped.
+    pub fn span<T: Into<String>>(&self, name: T) -> SpanGuard {
+        SpanGuard {
+            timing: self.clone(),
+            name: name.into(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Records a span measured elsewhere.
+    pub fn record<T: Into<String>>(&self, name: T, duration: Duration) {
+        self.spans.lock().unwrap().push(TimingSpan {
+            name: token(name.into()),
+            duration,
+        });
+    }
+
+    /// Recorded spans, in the order they finished.
+    pub fn spans(&self) -> Vec<TimingSpan> {
+        self.spans.lock().unwrap().clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.lock().unwrap().is_empty()
+    }
+
+    /// Serializes the spans as a `Server-Timing` header value, e.g.
+    /// `db;dur=12.5, cache;dur=0.3`. Durations are in milliseconds.
+    ///
+    /// Returns `None` when nothing was recorded.
+    pub fn header_value(&self) -> Option<String> {
+        let spans = self.spans.lock().unwrap();
+        if spans.is_empty() {
+            return None;
+        }
+        let metrics: Vec<String> = spans
+            .iter()
+            .map(|span| {
+                format!(
+                    "{};dur={:.1}",
+                    span.name,
+                    span.duration.as_secs_f64() * 1000.0
+                )
+            })
+            .collect();
+        Some(metrics.join(", "))
+    }
+}
+
+/// `name` with every non-token character replaced by `_`; `_` if empty.
+fn token(name: String) -> String {
+    let is_tchar = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+    if name.is_empty() {
+        "_".to_string()
+    } else if name.chars().all(is_tchar) {
+        name
+    } else {
+        name.chars()
+            .map(|c| if is_tchar(c) { c } else { '_' })
+            .collect()
+    }
+}
+
+/// Guard returned by [`ServerTiming::span`]; records the elapsed time on drop.
+#[must_use = "the span is recorded when the guard is dropped"]
+pub struct SpanGuard {
+    timing: ServerTiming,
+    name: String,
+    start: Instant,
+}
+
+impl SpanGuard {
+    /// Ends the span now instead of at the end of the scope.
+    pub fn finish(self) {}
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        let name = std::mem::take(&mut self.name);
+        self.timing.record(name, self.start.elapsed());
+    }
+}
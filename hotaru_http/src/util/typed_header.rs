@@ -0,0 +1,76 @@
+//! Typed request headers.
+//!
+//! [`TypedHeader`] turns a raw header value into a Rust type; read one with
+//! [`HttpContext::typed_header`](crate::context::HttpContext::typed_header).
+//! Parsing never panics: a missing or malformed header is `None`.
+//!
+//! Two `Authorization` schemes come built in, [`BearerToken`] and
+//! [`BasicAuth`]. Scheme names match case-insensitively (RFC 9110 §11.1).
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+
+/// A request header that parses into `Self`.
+pub trait TypedHeader: Sized {
+    /// Lowercase header name, e.g. `"authorization"`.
+    const NAME: &'static str;
+
+    /// Parses the header value; `None` if it is malformed.
+    fn parse(value: &str) -> Option<Self>;
+}
+
+/// `Authorization: Bearer <token>` (RFC 6750).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BearerToken(pub String);
+
+impl TypedHeader for BearerToken {
+    const NAME: &'static str = "authorization";
+
+    fn parse(value: &str) -> Option<Self> {
+        bearer_token(value).map(|token| Self(token.to_string()))
+    }
+}
+
+/// `Authorization: Basic <base64(user:password)>` (RFC 7617).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicAuth {
+    pub username: String,
+    pub password: String,
+}
+
+impl TypedHeader for BasicAuth {
+    const NAME: &'static str = "authorization";
+
+    fn parse(value: &str) -> Option<Self> {
+        let credentials = credentials(value, "basic")?;
+        let decoded = String::from_utf8(BASE64.decode(credentials).ok()?).ok()?;
+        let (username, password) = decoded.split_once(':')?;
+        Some(Self {
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+}
+
+/// Token of a `Bearer` authorization value, borrowed from it.
+///
+/// `None` for another scheme, an empty token, or one with characters
+/// outside the `token68` syntax.
+pub fn bearer_token(value: &str) -> Option<&str> {
+    let token = credentials(value, "bearer")?;
+    let body = token.trim_end_matches('=');
+    let valid = !body.is_empty()
+        && body.bytes().all(|b| {
+            b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~' | b'+' | b'/')
+        });
+    valid.then_some(token)
+}
+
+/// Credentials following `scheme` in an authorization value.
+fn credentials<'a>(value: &'a str, scheme: &str) -> Option<&'a str> {
+    let (name, rest) = value.trim().split_once(' ')?;
+    if !name.eq_ignore_ascii_case(scheme) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    (!rest.is_empty() && !rest.contains(char::is_whitespace)).then_some(rest)
+}
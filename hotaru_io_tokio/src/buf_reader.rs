@@ -0,0 +1,215 @@
+//! Growable buffered reader behind [`TokioIo`](crate::TokioIo)'s buffered form.
+
+use core::pin::Pin;
+use core::task::{Context, Poll, ready};
+use std::io;
+
+use hotaru_core::connection::{HotaruBufRead, HotaruRead};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, ReadBuf};
+
+/// Buffered reader like `tokio::io::BufReader`, except that the buffer can
+/// also grow to hold more than one read.
+///
+/// Protocol detection needs this: when the first read is too short to tell
+/// protocols apart, [`fill_more`](Self::fill_more) appends the next read to
+/// the unconsumed bytes, where `BufReader` would only refill once they have
+/// been consumed.
+pub struct TokioBufReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+    pos: usize,
+    cap: usize,
+}
+
+impl<R> TokioBufReader<R> {
+    pub const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY, inner)
+    }
+
+    pub fn with_capacity(capacity: usize, inner: R) -> Self {
+        Self {
+            inner,
+            buf: vec![0; capacity],
+            pos: 0,
+            cap: 0,
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Currently-buffered unconsumed bytes.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.pos..self.cap]
+    }
+}
+
+impl<R: AsyncRead + Unpin> TokioBufReader<R> {
+    /// Reads more bytes and appends them to the unconsumed buffer, growing
+    /// it when full. Returns the whole buffered slice, which is unchanged
+    /// at end of stream.
+    pub async fn fill_more(&mut self) -> io::Result<&[u8]> {
+        // Move unconsumed bytes to the front, grow if still full, append.
+        if self.pos > 0 {
+            self.buf.copy_within(self.pos..self.cap, 0);
+            self.cap -= self.pos;
+            self.pos = 0;
+        }
+        if self.cap == self.buf.len() {
+            let grow = self.buf.len().max(64);
+            self.buf.resize(self.buf.len() + grow, 0);
+        }
+        let n = self.inner.read(&mut self.buf[self.cap..]).await?;
+        self.cap += n;
+        Ok(&self.buf[self.pos..self.cap])
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for TokioBufReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        out: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        // Large read with nothing buffered: bypass the buffer.
+        if this.pos == this.cap && out.remaining() >= this.buf.len() {
+            return Pin::new(&mut this.inner).poll_read(cx, out);
+        }
+        let available = ready!(Pin::new(&mut *this).poll_fill_buf(cx))?;
+        let n = available.len().min(out.remaining());
+        out.put_slice(&available[..n]);
+        this.pos += n;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncBufRead for TokioBufReader<R> {
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+        if this.pos >= this.cap {
+            let mut read = ReadBuf::new(&mut this.buf);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut read))?;
+            this.cap = read.filled().len();
+            this.pos = 0;
+        }
+        Poll::Ready(Ok(&this.buf[this.pos..this.cap]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+        this.pos = (this.pos + amt).min(this.cap);
+    }
+}
+
+/// What [`TokioIo::into_buf`](crate::TokioIo) returns: a
+/// [`TokioBufReader`] whose `fill_more` grows the buffer.
+///
+/// `TokioIo<B>` for any `B: AsyncBufRead` is also a `HotaruBufRead`, but
+/// only with the default `fill_more` that reports no further growth, since
+/// a generic buffered reader can only refill once drained.
+pub struct TokioBufIo<R> {
+    inner: TokioBufReader<R>,
+}
+
+impl<R> TokioBufIo<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner: TokioBufReader::new(inner),
+        }
+    }
+
+    pub fn into_inner(self) -> TokioBufReader<R> {
+        self.inner
+    }
+
+    pub fn inner(&self) -> &TokioBufReader<R> {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut TokioBufReader<R> {
+        &mut self.inner
+    }
+}
+
+impl<R> From<TokioBufReader<R>> for TokioBufIo<R> {
+    fn from(inner: TokioBufReader<R>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<R> HotaruRead for TokioBufIo<R>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    type Error = io::Error;
+    type Buffered = Self;
+
+    fn into_buf(self) -> Self::Buffered {
+        self
+    }
+
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        AsyncReadExt::read(&mut self.inner, buf).await
+    }
+
+    async fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        AsyncReadExt::read_exact(&mut self.inner, buf)
+            .await
+            .map(|_| ())
+    }
+}
+
+impl<R> HotaruBufRead for TokioBufIo<R>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    async fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        AsyncBufReadExt::fill_buf(&mut self.inner).await
+    }
+
+    fn consume(&mut self, amt: usize) {
+        AsyncBufRead::consume(Pin::new(&mut self.inner), amt)
+    }
+
+    async fn fill_more(&mut self) -> Result<&[u8], Self::Error> {
+        self.inner.fill_more().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_fill_more_appends_to_unconsumed_bytes() {
+        let (mut client, server) = tokio::io::duplex(64);
+        let mut reader = TokioBufReader::with_capacity(4, server);
+
+        client.write_all(b"PRI").await.unwrap();
+        assert_eq!(reader.fill_buf().await.unwrap(), b"PRI");
+        client.write_all(b" * HTTP/2.0").await.unwrap();
+        assert_eq!(reader.fill_more().await.unwrap(), b"PRI ");
+        // Full: grows past the initial capacity
+        assert_eq!(reader.fill_more().await.unwrap(), b"PRI * HTTP/2.0");
+
+        reader.consume(4);
+        drop(client);
+        assert_eq!(reader.fill_more().await.unwrap(), b"* HTTP/2.0");
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).await.unwrap();
+        assert_eq!(rest, "* HTTP/2.0");
+    }
+}
@@ -1,4 +1,4 @@
-//! Tokio IO and TCP backend for Hotaru.
+//! Tokio IO, TCP and Unix domain socket backend for Hotaru.
 
 use core::pin::Pin;
 
@@ -7,12 +7,22 @@ use tokio::io::{
     AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt,
 };
 
+pub mod buf_reader;
 pub mod tcp;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(unix)]
+pub mod uds;
 
+pub use buf_reader::{TokioBufIo, TokioBufReader};
 pub use tcp::{
     TcpAccepter, TcpConnector, TcpConnectorAddr, TcpInbound, TcpMeta, TcpOutbound, TcpStream,
     TcpTransport,
 };
+#[cfg(unix)]
+pub use uds::{
+    UnixAccepter, UnixConnector, UnixInbound, UnixMeta, UnixOutbound, UnixStream, UnixTransport,
+};
 
 /// Backend tag for Tokio IO values.
 pub enum TokioBackend {}
@@ -55,10 +65,10 @@ where
     T: AsyncRead + Unpin + Send + 'static,
 {
     type Error = std::io::Error;
-    type Buffered = TokioIo<tokio::io::BufReader<T>>;
+    type Buffered = TokioBufIo<T>;
 
     fn into_buf(self) -> Self::Buffered {
-        TokioIo::new(tokio::io::BufReader::new(self.inner))
+        TokioBufIo::new(self.inner)
     }
 
     async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
@@ -1,5 +1,7 @@
 //! TCP inbound and outbound runtime objects.
 
+use std::net::SocketAddr;
+
 use hotaru_core::connection::{Accepter, Inbound, Outbound};
 use tokio::net::TcpListener;
 
@@ -32,6 +34,10 @@ impl Inbound for TcpInbound {
             Err(never) => match never {},
         }
     }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.local_addr().ok()
+    }
 }
 
 /// TCP outbound runtime using normal `TcpStream::connect`.
@@ -0,0 +1,659 @@
+//! In-memory transport and fake clock for driving protocols in tests.
+//!
+//! [`MockStream`] is a wire stream with a [`MockPeer`] on the other end:
+//! the test scripts the bytes the protocol reads through the peer and
+//! inspects everything the protocol wrote, with no sockets involved.
+//! [`MockTransport`] plugs that wire into a server or client through a
+//! shared [`MockNetwork`], and [`MockClock`] pauses Tokio's clock so
+//! timeouts fire exactly when the test advances it. For servers bound to a
+//! real socket, [`send_raw`] and [`http_get`] act as a bare TCP client.
+//!
+//! Enabled by the `testing` feature.
+
+use core::net::SocketAddr;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use hotaru_core::connection::{
+    ConnMeta, ConnStream, HotaruRead, HotaruWrite, Inbound, Outbound, TransportSpec,
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use crate::TokioIo;
+
+// ============================================================================
+// Pipes
+// ============================================================================
+
+/// One direction of a mock connection.
+#[derive(Default)]
+struct Pipe {
+    state: Mutex<PipeState>,
+}
+
+#[derive(Default)]
+struct PipeState {
+    buf: VecDeque<u8>,
+    /// The writing side is done: once drained, reads see end of stream.
+    closed: bool,
+    reader: Option<Waker>,
+}
+
+impl Pipe {
+    fn push(&self, bytes: &[u8]) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return false;
+        }
+        state.buf.extend(bytes);
+        if let Some(waker) = state.reader.take() {
+            waker.wake();
+        }
+        true
+    }
+
+    fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        if let Some(waker) = state.reader.take() {
+            waker.wake();
+        }
+    }
+
+    /// Takes up to `max` bytes, `Ready(empty)` at end of stream.
+    fn poll_take(&self, cx: &mut Context<'_>, max: usize) -> Poll<Vec<u8>> {
+        let mut state = self.state.lock().unwrap();
+        if !state.buf.is_empty() {
+            let n = max.min(state.buf.len());
+            return Poll::Ready(state.buf.drain(..n).collect());
+        }
+        if state.closed {
+            return Poll::Ready(Vec::new());
+        }
+        state.reader = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn take_all(&self) -> Vec<u8> {
+        self.state.lock().unwrap().buf.drain(..).collect()
+    }
+
+    fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+}
+
+// ============================================================================
+// Stream and peer
+// ============================================================================
+
+/// In-memory wire stream; the protocol's end of a mock connection.
+///
+/// Reads return what the [`MockPeer`] sent, in the chunks it sent them,
+/// and end once the peer closed its side. Writes are captured for the peer.
+/// Dropping the stream (both halves, once split) closes the connection as
+/// seen from the peer.
+pub struct MockStream {
+    inbound: Arc<Pipe>,
+    outbound: Arc<Pipe>,
+    local: Option<SocketAddr>,
+    remote: Option<SocketAddr>,
+}
+
+/// The test's end of a mock connection.
+///
+/// Scripts the bytes the [`MockStream`] reads and collects what it writes.
+/// Cloning gives another handle to the same connection.
+#[derive(Clone)]
+pub struct MockPeer {
+    inbound: Arc<Pipe>,
+    outbound: Arc<Pipe>,
+}
+
+impl MockStream {
+    /// A connected stream and the peer scripting it.
+    pub fn pair() -> (MockStream, MockPeer) {
+        let inbound = Arc::new(Pipe::default());
+        let outbound = Arc::new(Pipe::default());
+        let stream = MockStream {
+            inbound: inbound.clone(),
+            outbound: outbound.clone(),
+            local: None,
+            remote: None,
+        };
+        (stream, MockPeer { inbound, outbound })
+    }
+
+    /// Addresses reported by `local_addr`/`peer_addr` and the split
+    /// metadata (`None` for both by default).
+    pub fn with_addrs(mut self, local: SocketAddr, remote: SocketAddr) -> Self {
+        self.local = Some(local);
+        self.remote = Some(remote);
+        self
+    }
+}
+
+impl Drop for MockStream {
+    fn drop(&mut self) {
+        self.outbound.close();
+        // Nobody reads any more: further sends from the peer fail.
+        self.inbound.close();
+    }
+}
+
+impl MockPeer {
+    /// Queues `bytes` for the stream to read.
+    ///
+    /// Returns `false` once the stream is gone or the peer closed its side.
+    pub fn send(&self, bytes: impl AsRef<[u8]>) -> bool {
+        self.inbound.push(bytes.as_ref())
+    }
+
+    /// Ends the peer's side: the stream reads end of stream once it has
+    /// drained what was sent.
+    pub fn close(&self) {
+        self.inbound.close();
+    }
+
+    /// Everything written by the stream since the last call, without
+    /// waiting.
+    pub fn take_written(&self) -> Vec<u8> {
+        self.outbound.take_all()
+    }
+
+    /// Waits for the stream to write something and returns it; empty once
+    /// the stream has shut down or been dropped with nothing left to read.
+    pub async fn read(&self) -> Vec<u8> {
+        core::future::poll_fn(|cx| self.outbound.poll_take(cx, usize::MAX)).await
+    }
+
+    /// Waits until the stream shuts down or is dropped and returns
+    /// everything it wrote.
+    pub async fn read_to_end(&self) -> Vec<u8> {
+        let mut written = Vec::new();
+        loop {
+            let chunk = self.read().await;
+            if chunk.is_empty() {
+                return written;
+            }
+            written.extend(chunk);
+        }
+    }
+
+    /// Whether the stream has shut down its writing side or been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.outbound.is_closed()
+    }
+}
+
+impl AsyncRead for MockStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let bytes = core::task::ready!(self.inbound.poll_take(cx, buf.remaining()));
+        buf.put_slice(&bytes);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for MockStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        if self.outbound.push(buf) {
+            Poll::Ready(Ok(buf.len()))
+        } else {
+            Poll::Ready(Err(std::io::ErrorKind::BrokenPipe.into()))
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.outbound.close();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl HotaruRead for MockStream {
+    type Error = std::io::Error;
+    type Buffered = <TokioIo<MockStream> as HotaruRead>::Buffered;
+
+    fn into_buf(self) -> Self::Buffered {
+        TokioIo::new(self).into_buf()
+    }
+
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        AsyncReadExt::read(self, buf).await
+    }
+
+    async fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        AsyncReadExt::read_exact(self, buf).await.map(|_| ())
+    }
+}
+
+impl HotaruWrite for MockStream {
+    type Error = std::io::Error;
+    type Buffered = <TokioIo<MockStream> as HotaruWrite>::Buffered;
+
+    fn into_buf_write(self) -> Self::Buffered {
+        TokioIo::new(self).into_buf_write()
+    }
+
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        AsyncWriteExt::write(self, buf).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        AsyncWriteExt::flush(self).await
+    }
+
+    async fn shutdown(&mut self) -> Result<(), Self::Error> {
+        AsyncWriteExt::shutdown(self).await
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        AsyncWriteExt::write_all(self, buf).await
+    }
+}
+
+/// Connection metadata for a mock stream.
+pub struct MockMeta {
+    local: Option<SocketAddr>,
+    remote: Option<SocketAddr>,
+}
+
+impl ConnMeta for MockMeta {
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.local
+    }
+
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote
+    }
+}
+
+impl ConnStream for MockStream {
+    type ReadHalf = TokioIo<tokio::io::ReadHalf<MockStream>>;
+    type WriteHalf = TokioIo<tokio::io::WriteHalf<MockStream>>;
+    type Meta = MockMeta;
+
+    fn split(self) -> (Self::ReadHalf, Self::WriteHalf, Self::Meta) {
+        let meta = MockMeta {
+            local: self.local,
+            remote: self.remote,
+        };
+        let (read, write) = tokio::io::split(self);
+        (TokioIo::new(read), TokioIo::new(write), meta)
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        self.remote
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.local
+    }
+}
+
+// ============================================================================
+// Transport
+// ============================================================================
+
+/// Connections between the test and mock inbound/outbound runtimes.
+///
+/// Serves as both the bind target of [`MockInbound`] and the connect
+/// target of [`MockOutbound`]; clones share the same connections.
+#[derive(Clone)]
+pub struct MockNetwork {
+    inbound_tx: UnboundedSender<MockStream>,
+    inbound_rx: Arc<tokio::sync::Mutex<UnboundedReceiver<MockStream>>>,
+    outbound_tx: UnboundedSender<MockPeer>,
+    outbound_rx: Arc<tokio::sync::Mutex<UnboundedReceiver<MockPeer>>>,
+}
+
+impl MockNetwork {
+    pub fn new() -> Self {
+        let (inbound_tx, inbound_rx) = unbounded_channel();
+        let (outbound_tx, outbound_rx) = unbounded_channel();
+        Self {
+            inbound_tx,
+            inbound_rx: Arc::new(tokio::sync::Mutex::new(inbound_rx)),
+            outbound_tx,
+            outbound_rx: Arc::new(tokio::sync::Mutex::new(outbound_rx)),
+        }
+    }
+
+    /// Opens a connection to the inbound bound on this network, returning
+    /// the client's end.
+    pub fn connect(&self) -> MockPeer {
+        let (stream, peer) = MockStream::pair();
+        // The receiver lives as long as `self`.
+        let _ = self.inbound_tx.send(stream);
+        peer
+    }
+
+    /// The remote end of the next connection opened through a
+    /// [`MockOutbound`] on this network, for the test to answer.
+    pub async fn next_outbound(&self) -> MockPeer {
+        let mut rx = self.outbound_rx.lock().await;
+        rx.recv().await.expect("MockNetwork holds its own sender")
+    }
+}
+
+impl Default for MockNetwork {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Inbound runtime accepting the connections of [`MockNetwork::connect`].
+pub struct MockInbound {
+    network: MockNetwork,
+}
+
+impl Inbound for MockInbound {
+    type Wire = MockStream;
+    type BindTarget = MockNetwork;
+    type Error = std::io::Error;
+
+    async fn bind(target: Self::BindTarget) -> Result<Self, Self::Error> {
+        Ok(Self { network: target })
+    }
+
+    async fn accept(&self) -> Result<Self::Wire, Self::Error> {
+        let mut rx = self.network.inbound_rx.lock().await;
+        Ok(rx.recv().await.expect("MockNetwork holds its own sender"))
+    }
+}
+
+/// Outbound runtime whose connections surface at
+/// [`MockNetwork::next_outbound`].
+pub struct MockOutbound {
+    network: MockNetwork,
+}
+
+impl Outbound for MockOutbound {
+    type Wire = MockStream;
+    type ConnectTarget = MockNetwork;
+    type Error = std::io::Error;
+
+    async fn build(target: Self::ConnectTarget) -> Result<Self, Self::Error> {
+        Ok(Self { network: target })
+    }
+
+    async fn connect(&self) -> Result<Self::Wire, Self::Error> {
+        let (stream, peer) = MockStream::pair();
+        let _ = self.network.outbound_tx.send(peer);
+        Ok(stream)
+    }
+}
+
+/// In-memory transport for tests.
+pub struct MockTransport;
+
+impl TransportSpec for MockTransport {
+    type Wire = MockStream;
+    type IoError = std::io::Error;
+    type Inbound = MockInbound;
+    type Outbound = MockOutbound;
+}
+
+// ============================================================================
+// Clock
+// ============================================================================
+
+/// Fake clock: Tokio's paused time, moved only by the test.
+///
+/// Everything timed through Tokio (`TokioRuntime`'s sleeps and timeouts,
+/// hence connection and request timeouts) follows it, with Tokio's
+/// millisecond timer resolution: a deadline fires once the clock is
+/// strictly past it, rounded up to the next millisecond. Requires the
+/// current-thread runtime, the default of `#[tokio::test]`. While paused,
+/// Tokio still jumps straight to the next timer once every task is idle,
+/// so a test waiting on a peer that never sends sees its timeouts fire
+/// immediately rather than hang.
+pub struct MockClock {
+    start: tokio::time::Instant,
+}
+
+impl MockClock {
+    /// Pauses Tokio's clock for the current runtime.
+    pub fn pause() -> Self {
+        tokio::time::pause();
+        Self {
+            start: tokio::time::Instant::now(),
+        }
+    }
+
+    /// Moves the clock forward by `by`, firing every timer due by then.
+    pub async fn advance(&self, by: Duration) {
+        tokio::time::advance(by).await;
+    }
+
+    /// Time the clock has moved since it was paused.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+impl Drop for MockClock {
+    fn drop(&mut self) {
+        tokio::time::resume();
+    }
+}
+
+// ============================================================================
+// TCP clients
+// ============================================================================
+
+/// Sends `raw` to `addr` on a new TCP connection and reads until the server
+/// closes it.
+///
+/// For tests against a server bound to a real socket. A connection the
+/// server drops may be reset rather than closed; whatever arrived before
+/// that is returned.
+pub async fn send_raw(addr: SocketAddr, raw: impl AsRef<[u8]>) -> String {
+    let mut client = tokio::net::TcpStream::connect(addr).await.unwrap();
+    client.write_all(raw.as_ref()).await.unwrap();
+    let mut response = Vec::new();
+    let _ = client.read_to_end(&mut response).await;
+    String::from_utf8_lossy(&response).into_owned()
+}
+
+/// [`send_raw`] for an HTTP/1.1 `GET path` that closes the connection after
+/// the response.
+pub async fn http_get(addr: SocketAddr, path: &str) -> String {
+    send_raw(
+        addr,
+        format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::any::Any;
+    use hotaru_core::PRwLock;
+    use hotaru_core::app::common::RuntimeConfig;
+    use hotaru_core::connection::{BufferedReadHalf, BufferedWriteHalf, MaybeSendBoxFuture};
+    use hotaru_core::executable::ProtocolRegistryBuilder;
+    use hotaru_core::executable::entry::ProtocolEntryTrait;
+    use hotaru_core::extensions::{Locals, Params};
+    use hotaru_core::protocol::Detection;
+
+    #[tokio::test]
+    async fn test_stream_reads_script_and_captures_writes() {
+        let (stream, peer) = MockStream::pair();
+        let (mut read, mut write, _meta) = stream.split();
+
+        peer.send(b"ping");
+        peer.close();
+        let mut buf = Vec::new();
+        tokio::io::AsyncReadExt::read_to_end(read.inner_mut(), &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(buf, b"ping");
+
+        HotaruWrite::write_all(&mut write, b"pong").await.unwrap();
+        assert_eq!(peer.take_written(), b"pong");
+        assert!(!peer.is_closed());
+
+        HotaruWrite::write_all(&mut write, b"bye").await.unwrap();
+        drop((read, write));
+        assert!(peer.is_closed());
+        assert_eq!(peer.read_to_end().await, b"bye");
+        assert!(!peer.send(b"late"));
+    }
+
+    #[tokio::test]
+    async fn test_network_connects_inbound_and_outbound() {
+        let network = MockNetwork::new();
+
+        let inbound = MockInbound::bind(network.clone()).await.unwrap();
+        let client = network.connect();
+        let mut server = inbound.accept().await.unwrap();
+        client.send(b"hello");
+        let mut buf = [0u8; 5];
+        HotaruRead::read_exact(&mut server, &mut buf).await.unwrap();
+        assert_eq!(&buf, b"hello");
+
+        let outbound = MockOutbound::build(network.clone()).await.unwrap();
+        let mut wire = outbound.connect().await.unwrap();
+        let remote = network.next_outbound().await;
+        HotaruWrite::write_all(&mut wire, b"request").await.unwrap();
+        assert_eq!(remote.read().await, b"request");
+    }
+
+    #[tokio::test]
+    async fn test_clock_fires_timers_when_advanced() {
+        let clock = MockClock::pause();
+        let timer = tokio::spawn(tokio::time::sleep(Duration::from_secs(30)));
+        tokio::task::yield_now().await;
+
+        clock.advance(Duration::from_secs(29)).await;
+        assert!(!timer.is_finished());
+        // Past the deadline: the timer fired during the advance, so awaiting
+        // it takes no more time
+        clock.advance(Duration::from_secs(2)).await;
+        timer.await.unwrap();
+        assert_eq!(clock.elapsed(), Duration::from_secs(31));
+    }
+
+    const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+    /// Protocol entry recording the bytes detection handed it
+    struct Probe {
+        name: &'static str,
+        detect: fn(&[u8]) -> Detection,
+        served: Arc<Mutex<Option<(&'static str, Vec<u8>)>>>,
+    }
+
+    impl ProtocolEntryTrait<MockTransport> for Probe {
+        fn test(&self, buf: &[u8]) -> Detection {
+            (self.detect)(buf)
+        }
+
+        fn serve(
+            &self,
+            _runtime: Arc<RuntimeConfig>,
+            reader: BufferedReadHalf<MockTransport>,
+            _writer: BufferedWriteHalf<MockTransport>,
+            _meta: MockMeta,
+        ) -> MaybeSendBoxFuture<'static, ()> {
+            *self.served.lock().unwrap() = Some((self.name, reader.inner().buffer().to_vec()));
+            Box::pin(async {})
+        }
+
+        fn serve_upgrade(
+            &self,
+            _runtime: Arc<RuntimeConfig>,
+            _reader: BufferedReadHalf<MockTransport>,
+            _writer: BufferedWriteHalf<MockTransport>,
+            _meta: MockMeta,
+            _params: PRwLock<Params>,
+            _locals: PRwLock<Locals>,
+        ) -> MaybeSendBoxFuture<'static, ()> {
+            Box::pin(async {})
+        }
+
+        fn request(
+            &self,
+            _runtime: Arc<RuntimeConfig>,
+            _reader: BufferedReadHalf<MockTransport>,
+            _writer: BufferedWriteHalf<MockTransport>,
+            _meta: MockMeta,
+        ) -> MaybeSendBoxFuture<'static, ()> {
+            Box::pin(async {})
+        }
+
+        fn request_upgrade(
+            &self,
+            _runtime: Arc<RuntimeConfig>,
+            _reader: BufferedReadHalf<MockTransport>,
+            _writer: BufferedWriteHalf<MockTransport>,
+            _meta: MockMeta,
+            _params: PRwLock<Params>,
+            _locals: PRwLock<Locals>,
+        ) -> MaybeSendBoxFuture<'static, ()> {
+            Box::pin(async {})
+        }
+
+        fn default_connection_timeout(&self) -> Option<Duration> {
+            None
+        }
+
+        fn set_case_insensitive_routes(&self, _enabled: bool) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_detection_reads_a_split_preface_before_choosing() {
+        let served = Arc::new(Mutex::new(None));
+        // HTTP/2 is registered first; the catch-all after it matches any bytes
+        let registry = ProtocolRegistryBuilder::<MockTransport>::new()
+            .entry(Arc::new(Probe {
+                name: "h2",
+                detect: |buf| Detection::prefix(buf, PREFACE),
+                served: served.clone(),
+            }))
+            .entry(Arc::new(Probe {
+                name: "any",
+                detect: |buf| Detection::from(!buf.is_empty()),
+                served: served.clone(),
+            }))
+            .build();
+
+        let (stream, peer) = MockStream::pair();
+        peer.send(&PREFACE[..16]);
+        let runtime = Arc::new(RuntimeConfig::new());
+        let serving = tokio::spawn(async move { registry.serve(runtime, stream).await });
+        // Detection reads the first half and waits for more
+        tokio::task::yield_now().await;
+        assert!(served.lock().unwrap().is_none());
+
+        peer.send(&PREFACE[16..]);
+        serving.await.unwrap();
+        let (name, buffered) = served.lock().unwrap().take().unwrap();
+        assert_eq!(name, "h2");
+        assert_eq!(buffered, PREFACE);
+    }
+}
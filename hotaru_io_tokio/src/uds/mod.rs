@@ -0,0 +1,81 @@
+//! Tokio Unix domain socket transport implementation.
+//!
+//! Intended for sidecar and local IPC deployments. Protocol detection and
+//! dispatch are identical to TCP; only the listener differs.
+
+mod primitive;
+mod runtime;
+mod stream;
+mod transport;
+
+pub use primitive::{UnixAccepter, UnixConnector};
+pub use runtime::{UnixInbound, UnixOutbound};
+pub use stream::{UnixMeta, UnixStream};
+pub use transport::UnixTransport;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hotaru_core::connection::{ConnMeta, ConnStream, HotaruRead, HotaruWrite, Inbound};
+    use std::path::PathBuf;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixStream as TokioUnixStream;
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("hotaru-{}-{}.sock", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn test_uds_serves_one_request() {
+        let path = socket_path("serve");
+        let inbound = UnixInbound::bind(path.clone()).await.unwrap();
+        assert!(path.exists());
+
+        let client = tokio::spawn({
+            let path = path.clone();
+            async move {
+                let mut client = TokioUnixStream::connect(path).await.unwrap();
+                client
+                    .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+                    .await
+                    .unwrap();
+                let mut response = Vec::new();
+                client.read_to_end(&mut response).await.unwrap();
+                response
+            }
+        });
+
+        let wire = inbound.accept().await.unwrap();
+        assert_eq!(ConnStream::peer_addr(&wire), None);
+        let (mut read, mut write, meta) = ConnStream::split(wire);
+        assert_eq!(meta.remote_addr(), None);
+        assert_eq!(meta.local_path(), Some(path.as_path()));
+
+        let mut buf = [0u8; 64];
+        let n = HotaruRead::read(&mut read, &mut buf).await.unwrap();
+        assert!(buf[..n].starts_with(b"GET / HTTP/1.1"));
+        HotaruWrite::write_all(&mut write, b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+            .await
+            .unwrap();
+        HotaruWrite::shutdown(&mut write).await.unwrap();
+
+        let response = client.await.unwrap();
+        assert!(response.ends_with(b"\r\n\r\nok"));
+
+        inbound.close();
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_uds_replaces_stale_socket_and_cleans_up_on_drop() {
+        let path = socket_path("stale");
+        let stale = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        drop(stale);
+        assert!(path.exists());
+
+        let inbound = UnixInbound::bind(path.clone()).await.unwrap();
+        assert_eq!(inbound.path(), path.as_path());
+        drop(inbound);
+        assert!(!path.exists());
+    }
+}
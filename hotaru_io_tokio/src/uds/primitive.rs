@@ -0,0 +1,37 @@
+//! Unix domain socket primitive accepter and connector implementations.
+
+use core::convert::Infallible;
+use std::path::PathBuf;
+
+use hotaru_core::connection::{Accepter, Connector};
+use tokio::net::UnixStream as TokioUnixStream;
+
+use super::stream::UnixStream;
+
+/// Unix socket accepter that wraps accepted Tokio streams.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnixAccepter;
+
+impl Accepter for UnixAccepter {
+    type Raw = TokioUnixStream;
+    type Stream = UnixStream;
+    type Error = Infallible;
+
+    async fn upgrade(&self, raw: Self::Raw) -> Result<Self::Stream, Self::Error> {
+        Ok(UnixStream::new(raw))
+    }
+}
+
+/// Unix socket outbound connector.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnixConnector;
+
+impl Connector for UnixConnector {
+    type Stream = UnixStream;
+    type Target = PathBuf;
+    type Error = std::io::Error;
+
+    async fn connect(&self, target: Self::Target) -> Result<Self::Stream, Self::Error> {
+        UnixStream::connect(target).await
+    }
+}
@@ -0,0 +1,97 @@
+//! Unix domain socket inbound and outbound runtime objects.
+
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+
+use hotaru_core::connection::{Accepter, Inbound, Outbound};
+use tokio::net::UnixListener;
+
+use super::{primitive::UnixAccepter, stream::UnixStream};
+
+/// Bound Unix domain socket inbound runtime.
+///
+/// The socket file is created by `bind` and removed again on `close` (called
+/// by the server on shutdown) or when the inbound is dropped.
+pub struct UnixInbound {
+    listener: UnixListener,
+    accepter: UnixAccepter,
+    path: PathBuf,
+}
+
+impl UnixInbound {
+    /// Returns the filesystem path this inbound is listening on.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn remove_socket_file(&self) {
+        // Already gone (or never created) is fine; nothing else to report.
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl Inbound for UnixInbound {
+    type Wire = UnixStream;
+    type BindTarget = PathBuf;
+    type Error = std::io::Error;
+
+    async fn bind(target: Self::BindTarget) -> Result<Self, Self::Error> {
+        // A socket file left behind by a crashed process makes `bind` fail
+        // with `AddrInUse`; clear it, but never touch a non-socket file.
+        if let Ok(meta) = std::fs::symlink_metadata(&target)
+            && meta.file_type().is_socket()
+        {
+            std::fs::remove_file(&target)?;
+        }
+
+        Ok(Self {
+            listener: UnixListener::bind(&target)?,
+            accepter: UnixAccepter,
+            path: target,
+        })
+    }
+
+    async fn accept(&self) -> Result<Self::Wire, Self::Error> {
+        let (stream, _) = self.listener.accept().await?;
+        match self.accepter.upgrade(stream).await {
+            Ok(wire) => Ok(wire),
+            Err(never) => match never {},
+        }
+    }
+
+    fn close(&self) {
+        self.remove_socket_file();
+    }
+}
+
+impl Drop for UnixInbound {
+    fn drop(&mut self) {
+        self.remove_socket_file();
+    }
+}
+
+/// Unix domain socket outbound runtime.
+pub struct UnixOutbound {
+    target: PathBuf,
+}
+
+impl UnixOutbound {
+    /// Returns the socket path this outbound is bound to.
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+}
+
+impl Outbound for UnixOutbound {
+    type Wire = UnixStream;
+    type ConnectTarget = PathBuf;
+    type Error = std::io::Error;
+
+    async fn build(target: Self::ConnectTarget) -> Result<Self, Self::Error> {
+        Ok(Self { target })
+    }
+
+    async fn connect(&self) -> Result<Self::Wire, Self::Error> {
+        UnixStream::connect(&self.target).await
+    }
+}
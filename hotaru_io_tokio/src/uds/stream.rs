@@ -0,0 +1,149 @@
+//! Unix domain socket wire stream and metadata.
+
+use core::net::SocketAddr;
+use std::path::PathBuf;
+
+use hotaru_core::connection::{ConnMeta, ConnStream, HotaruRead, HotaruWrite};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::UnixStream as TokioUnixStream,
+};
+
+use crate::TokioIo;
+
+/// Tokio Unix stream wrapper owned by `hotaru_io_tokio`.
+pub struct UnixStream {
+    inner: TokioUnixStream,
+}
+
+impl UnixStream {
+    pub fn new(inner: TokioUnixStream) -> Self {
+        Self { inner }
+    }
+
+    pub fn into_inner(self) -> TokioUnixStream {
+        self.inner
+    }
+
+    pub fn inner(&self) -> &TokioUnixStream {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut TokioUnixStream {
+        &mut self.inner
+    }
+
+    pub async fn connect<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
+        TokioUnixStream::connect(path).await.map(Self::new)
+    }
+
+    /// Returns the filesystem path of the peer socket, if it is bound to one.
+    ///
+    /// Clients connecting to a server socket are usually unnamed, so this is
+    /// `None` on the accepting side in the common case.
+    pub fn peer_path(&self) -> Option<PathBuf> {
+        self.inner
+            .peer_addr()
+            .ok()
+            .and_then(|addr| addr.as_pathname().map(PathBuf::from))
+    }
+
+    /// Returns the filesystem path of the local socket, if it is bound to one.
+    pub fn local_path(&self) -> Option<PathBuf> {
+        self.inner
+            .local_addr()
+            .ok()
+            .and_then(|addr| addr.as_pathname().map(PathBuf::from))
+    }
+}
+
+impl HotaruRead for UnixStream {
+    type Error = std::io::Error;
+    type Buffered = <TokioIo<TokioUnixStream> as HotaruRead>::Buffered;
+
+    fn into_buf(self) -> Self::Buffered {
+        TokioIo::new(self.inner).into_buf()
+    }
+
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        AsyncReadExt::read(&mut self.inner, buf).await
+    }
+
+    async fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        AsyncReadExt::read_exact(&mut self.inner, buf)
+            .await
+            .map(|_| ())
+    }
+}
+
+impl HotaruWrite for UnixStream {
+    type Error = std::io::Error;
+    type Buffered = <TokioIo<TokioUnixStream> as HotaruWrite>::Buffered;
+
+    fn into_buf_write(self) -> Self::Buffered {
+        TokioIo::new(self.inner).into_buf_write()
+    }
+
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        AsyncWriteExt::write(&mut self.inner, buf).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        AsyncWriteExt::flush(&mut self.inner).await
+    }
+
+    async fn shutdown(&mut self) -> Result<(), Self::Error> {
+        AsyncWriteExt::shutdown(&mut self.inner).await
+    }
+
+    async fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        AsyncWriteExt::write_all(&mut self.inner, buf).await
+    }
+}
+
+/// Connection metadata for Unix domain sockets.
+///
+/// UDS peers have no IP address, so the `ConnMeta` socket-address accessors
+/// always return `None`. The filesystem paths are kept for callers that know
+/// they are on a Unix transport.
+pub struct UnixMeta {
+    local: Option<PathBuf>,
+    remote: Option<PathBuf>,
+}
+
+impl UnixMeta {
+    /// Returns the local socket path, if bound to one.
+    pub fn local_path(&self) -> Option<&std::path::Path> {
+        self.local.as_deref()
+    }
+
+    /// Returns the peer socket path, if bound to one.
+    pub fn remote_path(&self) -> Option<&std::path::Path> {
+        self.remote.as_deref()
+    }
+}
+
+impl ConnMeta for UnixMeta {}
+
+impl ConnStream for UnixStream {
+    type ReadHalf = TokioIo<tokio::io::ReadHalf<TokioUnixStream>>;
+    type WriteHalf = TokioIo<tokio::io::WriteHalf<TokioUnixStream>>;
+    type Meta = UnixMeta;
+
+    fn split(self) -> (Self::ReadHalf, Self::WriteHalf, Self::Meta) {
+        let meta = UnixMeta {
+            local: self.local_path(),
+            remote: self.peer_path(),
+        };
+        let (read, write) = tokio::io::split(self.inner);
+        (TokioIo::new(read), TokioIo::new(write), meta)
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+}
@@ -0,0 +1,21 @@
+//! Unix domain socket transport policy.
+
+use hotaru_core::connection::TransportSpec;
+
+use super::{
+    runtime::{UnixInbound, UnixOutbound},
+    stream::UnixStream,
+};
+
+/// Tokio Unix domain socket transport.
+///
+/// There is no sensible default socket path, so a binding must be supplied
+/// via `binding_uds(...)` (or `binding(...)`) on the builder.
+pub struct UnixTransport;
+
+impl TransportSpec for UnixTransport {
+    type Wire = UnixStream;
+    type IoError = std::io::Error;
+    type Inbound = UnixInbound;
+    type Outbound = UnixOutbound;
+}
@@ -40,6 +40,39 @@ use zstd::stream::{read::Decoder as ZstdDecoder, write::Encoder as ZstdEncoder};
 
 static CHUNK_SIZE: usize = 4096;
 
+/// Error inside the `std::io::Error` of a `*_limited` decoder when the
+/// decoded data would be larger than the limit it was given. The
+/// `std::io::Error` has kind `FileTooLarge`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedTooLarge {
+    /// The limit, in bytes
+    pub limit: usize,
+}
+
+impl std::fmt::Display for DecodedTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "decoded data exceeds {} bytes", self.limit)
+    }
+}
+
+impl std::error::Error for DecodedTooLarge {}
+
+/// Reads `reader` to the end, stopping with [`DecodedTooLarge`] as soon as
+/// it gives more than `limit` bytes
+fn read_limited<R: Read>(reader: R, limit: usize) -> std::io::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    reader
+        .take((limit as u64).saturating_add(1))
+        .read_to_end(&mut decompressed)?;
+    if decompressed.len() > limit {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::FileTooLarge,
+            DecodedTooLarge { limit },
+        ));
+    }
+    Ok(decompressed)
+}
+
 /// Decompresses GZIP-encoded data
 ///
 /// # Arguments
@@ -67,6 +100,12 @@ pub fn decompress_gzip(data: &[u8]) -> std::io::Result<Vec<u8>> {
     Ok(decompressed)
 }
 
+/// Decompresses GZIP-encoded data, failing with [`DecodedTooLarge`] once
+/// the output would pass `limit` bytes
+pub fn decompress_gzip_limited(data: &[u8], limit: usize) -> std::io::Result<Vec<u8>> {
+    read_limited(bufread::GzDecoder::new(data), limit)
+}
+
 /// Compresses data using GZIP encoding
 ///
 /// # Arguments
@@ -114,6 +153,12 @@ pub fn decompress_deflate(data: &[u8]) -> std::io::Result<Vec<u8>> {
     Ok(decompressed)
 }
 
+/// Decompresses DEFLATE-encoded data, failing with [`DecodedTooLarge`] once
+/// the output would pass `limit` bytes
+pub fn decompress_deflate_limited(data: &[u8], limit: usize) -> std::io::Result<Vec<u8>> {
+    read_limited(bufread::DeflateDecoder::new(data), limit)
+}
+
 /// Compresses data using DEFLATE encoding
 ///
 /// # Arguments
@@ -155,6 +200,12 @@ pub fn decompress_brotli(data: &[u8]) -> std::io::Result<Vec<u8>> {
     Ok(decompressed)
 }
 
+/// Decompresses Brotli-encoded data, failing with [`DecodedTooLarge`] once
+/// the output would pass `limit` bytes
+pub fn decompress_brotli_limited(data: &[u8], limit: usize) -> std::io::Result<Vec<u8>> {
+    read_limited(BrotliDecompressor::new(data, CHUNK_SIZE), limit)
+}
+
 /// Compresses data using Brotli encoding
 ///
 /// # Arguments
@@ -196,6 +247,12 @@ pub fn decompress_zstd(data: &[u8]) -> std::io::Result<Vec<u8>> {
     Ok(decompressed)
 }
 
+/// Decompresses Zstandard-encoded data, failing with [`DecodedTooLarge`]
+/// once the output would pass `limit` bytes
+pub fn decompress_zstd_limited(data: &[u8], limit: usize) -> std::io::Result<Vec<u8>> {
+    read_limited(ZstdDecoder::new(data)?, limit)
+}
+
 /// Compresses data using Zstandard encoding
 ///
 /// # Arguments
@@ -211,3 +268,33 @@ pub fn compress_zstd(data: &[u8], level: i32) -> std::io::Result<Vec<u8>> {
     encoder.write_all(data)?;
     encoder.finish()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limited_decoders_stop_at_the_limit() {
+        let data = vec![0u8; 1 << 20];
+        let limit = 4096;
+        let encoded = [
+            compress_gzip(&data).unwrap(),
+            compress_deflate(&data).unwrap(),
+            compress_brotli(&data).unwrap(),
+            compress_zstd(&data, 1).unwrap(),
+        ];
+        let decoders: [fn(&[u8], usize) -> std::io::Result<Vec<u8>>; 4] = [
+            decompress_gzip_limited,
+            decompress_deflate_limited,
+            decompress_brotli_limited,
+            decompress_zstd_limited,
+        ];
+        for (encoded, decode) in encoded.iter().zip(decoders) {
+            let err = decode(encoded, limit).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::FileTooLarge);
+            let inner = err.get_ref().unwrap().downcast_ref::<DecodedTooLarge>();
+            assert_eq!(inner, Some(&DecodedTooLarge { limit }));
+            assert_eq!(decode(encoded, data.len()).unwrap(), data);
+        }
+    }
+}
@@ -13,13 +13,13 @@ middleware! {
     /// Logs all incoming requests
     pub LogRequest <HTTP> {
         println!("[LOG] Request: {} {}", req.method(), req.path());
-        let start = std::time::Instant::now();
 
         // Continue to next middleware/endpoint
-        let result = next(req).await;
+        let req = next(req).await?;
 
-        println!("[LOG] Response time: {:?}", start.elapsed());
-        result
+        // The chain records how long the endpoint ran
+        println!("[LOG] Handler time: {:?}", req.handler_duration());
+        Ok(req)
     }
 }
 
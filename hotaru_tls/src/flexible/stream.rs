@@ -15,7 +15,7 @@ use tokio::net::TcpStream;
 use tokio_rustls::client::TlsStream;
 
 use hotaru_core::connection::{ConnMeta, ConnStream, HotaruRead, HotaruWrite};
-use hotaru_io_tokio::TokioIo;
+use hotaru_io_tokio::{TokioBufIo, TokioIo};
 
 /// Connection metadata for flexible TCP/TLS streams.
 pub struct FlexMeta {
@@ -127,10 +127,10 @@ impl AsyncWrite for TcpOrTlsStream {
 
 impl HotaruRead for TcpOrTlsStream {
     type Error = std::io::Error;
-    type Buffered = TokioIo<BufReader<Self>>;
+    type Buffered = TokioBufIo<Self>;
 
     fn into_buf(self) -> Self::Buffered {
-        TokioIo::new(BufReader::new(self))
+        TokioBufIo::new(self)
     }
 
     async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
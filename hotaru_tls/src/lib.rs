@@ -6,7 +6,7 @@
 //!
 //! # Module layout
 //!
-//! - `tls/` — `TlsStream`, `TlsAccepter`, `TlsConnector`, `TlsTransport` (TLS-only)
+//! - `tls/` — `TlsStream`, `TlsAccepter`, `TlsConnector`, `TlsTransport`, `PeerIdentity` (TLS-only)
 //! - `config/` — `TlsConfig` (server) and `TlsClientConfig` (client) builders
 //! - `flexible/` — `TcpOrTlsStream`, `ConnectionBuilder` (runtime TCP-or-TLS choice)
 
@@ -16,8 +16,8 @@ pub mod tls;
 
 // ── TLS stream layer ──────────────────────────────────────────────────────────
 pub use tls::{
-    TlsAccepter, TlsAccepterError, TlsConnector, TlsConnectorError, TlsInbound, TlsInboundTarget,
-    TlsMeta, TlsOutbound, TlsOutboundTarget, TlsStream, TlsTransport,
+    PeerIdentity, TlsAccepter, TlsAccepterError, TlsConnector, TlsConnectorError, TlsInbound,
+    TlsInboundTarget, TlsMeta, TlsOutbound, TlsOutboundTarget, TlsStream, TlsTransport,
 };
 
 // ── Configuration builders ────────────────────────────────────────────────────
@@ -0,0 +1,75 @@
+//! PeerIdentity — who the verified client certificate says the peer is.
+
+use rustls::pki_types::CertificateDer;
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+/// Identity read from a peer's leaf certificate.
+///
+/// Only built from chains the handshake already verified, so the fields can
+/// be trusted for authorization decisions (service-to-service mTLS, SPIFFE
+/// IDs in a URI SAN, and so on).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerIdentity {
+    subject: String,
+    common_name: Option<String>,
+    sans: Vec<String>,
+}
+
+impl PeerIdentity {
+    /// Parses the identity out of a DER certificate; `None` if it is not
+    /// valid X.509.
+    pub fn from_der(cert: &CertificateDer<'_>) -> Option<Self> {
+        let (_, cert) = X509Certificate::from_der(cert.as_ref()).ok()?;
+        let subject = cert.subject();
+        let common_name = subject
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(str::to_string);
+        let sans = match cert.subject_alternative_name() {
+            Ok(Some(ext)) => ext
+                .value
+                .general_names
+                .iter()
+                .filter_map(san_to_string)
+                .collect(),
+            _ => Vec::new(),
+        };
+        Some(Self {
+            subject: subject.to_string(),
+            common_name,
+            sans,
+        })
+    }
+
+    /// Subject distinguished name, RFC 4514 style (`CN=orders, O=Example`).
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// First common name of the subject, if any.
+    pub fn common_name(&self) -> Option<&str> {
+        self.common_name.as_deref()
+    }
+
+    /// Subject alternative names in certificate order. DNS names, URIs and
+    /// emails are kept as written; IP addresses are formatted.
+    pub fn sans(&self) -> &[String] {
+        &self.sans
+    }
+}
+
+fn san_to_string(name: &GeneralName<'_>) -> Option<String> {
+    match name {
+        GeneralName::DNSName(s) | GeneralName::URI(s) | GeneralName::RFC822Name(s) => {
+            Some(s.to_string())
+        }
+        GeneralName::IPAddress(bytes) => match bytes.len() {
+            4 => Some(std::net::Ipv4Addr::from(<[u8; 4]>::try_from(*bytes).ok()?).to_string()),
+            16 => Some(std::net::Ipv6Addr::from(<[u8; 16]>::try_from(*bytes).ok()?).to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -1,11 +1,13 @@
 pub mod accepter;
 pub mod connector;
+pub mod identity;
 pub mod runtime;
 pub mod stream;
 pub mod transport;
 
 pub use accepter::{TlsAccepter, TlsAccepterError};
 pub use connector::{TlsConnector, TlsConnectorError};
+pub use identity::PeerIdentity;
 pub use runtime::{TlsInbound, TlsInboundTarget, TlsOutbound, TlsOutboundTarget};
 pub use stream::{TlsMeta, TlsStream};
 pub use transport::TlsTransport;
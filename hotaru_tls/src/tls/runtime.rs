@@ -1,5 +1,7 @@
 //! TLS inbound and outbound runtime objects.
 
+use std::net::SocketAddr;
+
 use hotaru_core::connection::{Accepter, Connector, Inbound, Outbound};
 use tokio::net::TcpListener;
 
@@ -49,6 +51,10 @@ impl Inbound for TlsInbound {
         // `TlsUpgradeError` -> `io::Error` via `From` (see accepter.rs).
         Ok(self.accepter.upgrade(tcp).await?)
     }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.local_addr().ok()
+    }
 }
 
 /// Client-side TLS connect target.
@@ -11,7 +11,9 @@ use tokio_rustls::client::TlsStream as ClientTlsStream;
 use tokio_rustls::server::TlsStream as ServerTlsStream;
 
 use hotaru_core::connection::{ConnMeta, ConnStream, HotaruRead, HotaruWrite};
-use hotaru_io_tokio::TokioIo;
+use hotaru_io_tokio::{TokioBufIo, TokioIo};
+
+use super::PeerIdentity;
 
 /// Connection metadata captured at split-time for TLS streams.
 pub struct TlsMeta {
@@ -35,6 +37,12 @@ impl TlsMeta {
     pub fn peer_certificates(&self) -> Option<&[CertificateDer<'static>]> {
         self.peer_certificates.as_deref()
     }
+
+    /// Identity from the verified client leaf certificate. `None` when the
+    /// peer presented no certificate (or on client-side streams).
+    pub fn peer_identity(&self) -> Option<PeerIdentity> {
+        PeerIdentity::from_der(self.peer_certificates()?.first()?)
+    }
 }
 
 impl ConnMeta for TlsMeta {
@@ -102,10 +110,10 @@ impl AsyncWrite for TlsStream {
 
 impl HotaruRead for TlsStream {
     type Error = std::io::Error;
-    type Buffered = TokioIo<tokio::io::BufReader<Self>>;
+    type Buffered = TokioBufIo<Self>;
 
     fn into_buf(self) -> Self::Buffered {
-        TokioIo::new(tokio::io::BufReader::new(self))
+        TokioBufIo::new(self)
     }
 
     async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
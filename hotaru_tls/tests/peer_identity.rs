@@ -0,0 +1,135 @@
+//! mTLS peer identity: a client certificate issued by the trusted CA yields
+//! a `PeerIdentity`, and a client without one is refused at the handshake.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use hotaru_core::connection::{ConnStream, Inbound, Outbound};
+use hotaru_tls::{
+    TlsClientConfig, TlsConfig, TlsInbound, TlsInboundTarget, TlsOutbound, TlsOutboundTarget,
+};
+use rcgen::{
+    BasicConstraints, CertificateParams, DistinguishedName, DnType, IsCa, KeyPair, SanType,
+};
+
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!(
+        "hotaru_tls_identity_{}_{}",
+        std::process::id(),
+        name
+    ));
+    let mut f = std::fs::File::create(&path).unwrap();
+    f.write_all(contents.as_bytes()).unwrap();
+    path
+}
+
+struct TestPki {
+    server: TlsConfig,
+    client_cert: PathBuf,
+    client_key: PathBuf,
+}
+
+/// A test CA, a client cert it signs, and a server requiring client auth
+/// against that CA.
+fn test_pki() -> TestPki {
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca_cert = ca_params.self_signed(&ca_key).unwrap();
+
+    let client_key = KeyPair::generate().unwrap();
+    let mut client_params = CertificateParams::new(vec!["orders.internal".to_string()]).unwrap();
+    client_params.subject_alt_names.push(SanType::URI(
+        "spiffe://example.org/orders".try_into().unwrap(),
+    ));
+    let mut name = DistinguishedName::new();
+    name.push(DnType::CommonName, "orders");
+    name.push(DnType::OrganizationName, "Example");
+    client_params.distinguished_name = name;
+    let client_cert = client_params
+        .signed_by(&client_key, &ca_cert, &ca_key)
+        .unwrap();
+
+    let server_key = KeyPair::generate().unwrap();
+    let server_params = CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+    let server_cert = server_params.self_signed(&server_key).unwrap();
+
+    let ca_file = temp_file("ca.pem", &ca_cert.pem());
+    let server = TlsConfig::builder()
+        .cert_chain_pem(server_cert.pem().as_bytes())
+        .unwrap()
+        .private_key_pem(server_key.serialize_pem().as_bytes())
+        .unwrap()
+        .require_client_auth(&ca_file)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    TestPki {
+        server,
+        client_cert: temp_file("client.pem", &client_cert.pem()),
+        client_key: temp_file("client.key", &client_key.serialize_pem()),
+    }
+}
+
+async fn bind(config: TlsConfig) -> (TlsInbound, u16) {
+    let server = TlsInbound::bind(TlsInboundTarget::new("127.0.0.1:0", config))
+        .await
+        .unwrap();
+    let port = server.local_addr().unwrap().port();
+    (server, port)
+}
+
+#[tokio::test]
+async fn valid_client_cert_yields_peer_identity() {
+    let pki = test_pki();
+    let (server, port) = bind(pki.server).await;
+
+    let server_task = tokio::spawn(async move {
+        let wire = server.accept().await.unwrap();
+        let (_r, _w, meta) = wire.split();
+        meta.peer_identity()
+    });
+
+    let client_config = TlsClientConfig::builder()
+        .client_auth(&pki.client_cert, &pki.client_key)
+        .unwrap()
+        .danger_disable_verification()
+        .build()
+        .unwrap();
+    let client = TlsOutbound::build(TlsOutboundTarget::new("localhost", port, client_config))
+        .await
+        .unwrap();
+    let _client_wire = client.connect().await.unwrap();
+
+    let identity = server_task.await.unwrap().expect("peer identity");
+    assert_eq!(identity.common_name(), Some("orders"));
+    assert!(identity.subject().contains("CN=orders"));
+    assert!(identity.subject().contains("O=Example"));
+    assert_eq!(
+        identity.sans(),
+        ["orders.internal", "spiffe://example.org/orders"]
+    );
+}
+
+#[tokio::test]
+async fn missing_client_cert_is_rejected_during_handshake() {
+    let pki = test_pki();
+    let (server, port) = bind(pki.server).await;
+
+    let server_task = tokio::spawn(async move { server.accept().await.map(|_| ()) });
+
+    let client_config = TlsClientConfig::builder()
+        .danger_disable_verification()
+        .build()
+        .unwrap();
+    let client = TlsOutbound::build(TlsOutboundTarget::new("localhost", port, client_config))
+        .await
+        .unwrap();
+    // Under TLS 1.3 the client may finish its side before the server
+    // checks for a certificate, so only the server's verdict is asserted.
+    let _ = client.connect().await;
+
+    assert!(server_task.await.unwrap().is_err());
+}
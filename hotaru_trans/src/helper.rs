@@ -214,6 +214,6 @@ pub fn match_any_literal_consume(
     }
 }
 
-pub fn into_peekable_iter(tokens: TokenStream) -> Peekable<impl Iterator<Item = TokenTree>> {
+pub fn into_peekable_iter(tokens: TokenStream) -> Peekable<impl Iterator<Item = TokenTree> + Clone> {
     tokens.into_iter().peekable()
 }
@@ -80,6 +80,21 @@ pub fn run(input: TokenStream) -> TokenStream {
     }
 }
 
+/// REST-style routes for one resource, HTTP only:
+///
+///   resource!{ APP.url("/users"), pub users<HTTP> {
+///       GET { .. } POST { .. } GET <id> { .. } PUT <id> { .. } DELETE <id> { .. }
+///   } }
+///
+/// Each body becomes a handler (`users_list`, `users_create`, `users_show`,
+/// `users_update`, `users_delete`); `/users` and `/users/<id>` are
+/// registered as endpoints that pick the handler by method and answer 405
+/// otherwise.
+#[proc_macro]
+pub fn resource(input: TokenStream) -> TokenStream {
+    url::resource_trans(input)
+}
+
 /// `run_server!(APP)` — blocking entry, for sync `fn main()`.
 #[proc_macro]
 pub fn run_server(input: TokenStream) -> TokenStream {
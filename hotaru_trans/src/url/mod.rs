@@ -7,6 +7,8 @@ pub(crate) mod parse;
 
 pub(crate) mod endpoint;
 pub(crate) mod outpoint;
+pub(crate) mod resource;
 
 pub(crate) use endpoint::{endpoint_trans, endpoint_attr, endpoint_semi_trans};
 pub(crate) use outpoint::{outpoint_trans, outpoint_attr, outpoint_semi_trans};
+pub(crate) use resource::resource_trans;
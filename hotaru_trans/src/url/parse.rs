@@ -11,8 +11,10 @@ use crate::helper::*;
 /// Parse the attribute input into UrlAttr
 /// endpoint/outpoint! {
 ///   <url-expr>,
+///   <url-expr>, // Optional, any number; the same handler serves each URL
 ///   middleware = [ ... ],  // Optional
 ///   config = [ ... ], // Optional
+///   raw, // Optional, endpoints only
 ///   endpoint_name<Protocol> {
 ///     ...
 ///  }
@@ -23,15 +25,17 @@ pub fn parse_trans(args: TokenStream) -> Result<UrlArgs, TokenStream> {
     /// Parse the function definition into UrlFunc
     fn parse_inner(
         tokens: &mut Peekable<impl Iterator<Item = TokenTree>>,
+        read_name: Option<Ident>,
     ) -> Result<UrlFunc, TokenStream> {
         let attrs = parse_outer_attrs(tokens)?;
         let is_pub = match_ident_consume(tokens, "pub");
-        let fn_name = match match_ident_consume(tokens, "_") {
-            true => {
+        let fn_name = match read_name {
+            Some(name) => name,
+            None if match_ident_consume(tokens, "_") => {
                 let random_name = format!("auto_generated_{}", random_alpha_string(8));
                 Ident::new(&random_name, Span::call_site())
             }
-            false => expect_any_ident(tokens, "Expected function name, or anonymous function annotation '_'")?,
+            None => expect_any_ident(tokens, "Expected function name, or anonymous function annotation '_'")?,
         };
         let _ = expect_punct_consume(tokens, "<", "Expected '<' after function name")?;
         let protocol = expect_any_ident(tokens, "Expected protocol identifier after '<'")?;
@@ -59,6 +63,16 @@ pub fn parse_trans(args: TokenStream) -> Result<UrlArgs, TokenStream> {
         "Expected a comma after the operations",
     )?;
 
+    let mut aliases = Vec::new();
+    while starts_url_expr(&tokens) {
+        let alias = expect_stream_before_comma_consume(
+            &mut tokens,
+            true,
+            "Expected a comma after the URL",
+        )?;
+        aliases.push(UrlExpr::from_tokens(alias)?);
+    }
+
     let mut middlewares = None;
     let mut config = None;
 
@@ -82,12 +96,42 @@ pub fn parse_trans(args: TokenStream) -> Result<UrlArgs, TokenStream> {
 
     match_punct_consume(&mut tokens, ","); // Optional separator for better readability between middleware and config 
 
+    // `raw,` opts out of the EndpointOutcome wrapper. A handler that is
+    // itself named `raw` is followed by `<` rather than `,`
+    let mut is_raw = false;
+    let mut read_name = None;
+    if let Some(TokenTree::Ident(ident)) = tokens.peek().cloned()
+        && ident.to_string() == "raw"
+    {
+        tokens.next();
+        if match_punct_consume(&mut tokens, ",") {
+            is_raw = true;
+        } else {
+            read_name = Some(ident);
+        }
+    }
+
     return Ok(UrlArgs::new(
         UrlExpr::from_tokens(url_expr)?,
         config,
         middlewares,
-        parse_inner(&mut tokens)?,
-    ));
+        parse_inner(&mut tokens, read_name)?.raw(is_raw),
+    )
+    .with_aliases(aliases));
+}
+
+/// Whether the next tokens are another URL rather than the options or the
+/// handler: a literal, or an app identifier followed by `.` or `:`.
+/// Options are followed by `=` or `,`, and handler names by `<`.
+fn starts_url_expr(tokens: &Peekable<impl Iterator<Item = TokenTree> + Clone>) -> bool {
+    let mut ahead = tokens.clone();
+    match (ahead.next(), ahead.next()) {
+        (Some(TokenTree::Literal(_)), _) => true,
+        (Some(TokenTree::Ident(_)), Some(TokenTree::Punct(punct))) => {
+            matches!(punct.as_char(), '.' | ':')
+        }
+        _ => false,
+    }
 }
 
 /// Expect to be in the following format:
@@ -95,6 +139,7 @@ pub fn parse_trans(args: TokenStream) -> Result<UrlArgs, TokenStream> {
 /// #[url(...)] // Required, Refer to UrlExpr struct
 /// #[config([ ... ])] // Optional
 /// #[middleware([ ... ])] // Optional
+/// #[raw] // Optional, endpoints only
 /// pub fn endpoint_name<Protocol>() {
 ///    ...
 /// }
@@ -127,6 +172,7 @@ pub fn parse_semi_trans(args: TokenStream) -> Result<UrlArgs, TokenStream> {
             )
         })
         .unwrap_or(Ok(vec![]))?;
+    let is_raw = outer_attrs.remove("raw").is_some();
 
     let is_pub = match_ident_consume(&mut tokens, "pub");
     let _ = expect_ident_consume(
@@ -173,12 +219,13 @@ pub fn parse_semi_trans(args: TokenStream) -> Result<UrlArgs, TokenStream> {
             req_var_name,
             fn_cont,
             outer_attrs,
-        ),
+        )
+        .raw(is_raw),
     ));
 }
 
 /// Expect to be in the following format:
-/// #[endpoint(UrlExpr, config = [...], middleware = [...])]
+/// #[endpoint(UrlExpr, middleware = [...], config = [...], raw)]
 /// pub fn endpoint_name<Protocol>() {
 ///    ...
 /// }
@@ -207,6 +254,9 @@ pub fn parse_attr(attr: TokenStream, args: TokenStream) -> Result<UrlArgs, Token
         )?);
     }
 
+    match_punct_consume(&mut attr, ",");
+    let is_raw = match_ident_consume(&mut attr, "raw");
+
     let outer_attrs = parse_outer_attrs(&mut tokens)?;
     let is_pub = match_ident_consume(&mut tokens, "pub");
     let _ = expect_ident_consume(
@@ -253,6 +303,7 @@ pub fn parse_attr(attr: TokenStream, args: TokenStream) -> Result<UrlArgs, Token
             req_var_name,
             fn_cont,
             outer_attrs,
-        ),
+        )
+        .raw(is_raw),
     ));
 }
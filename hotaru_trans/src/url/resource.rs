@@ -0,0 +1,243 @@
+use proc_macro::{Delimiter, Ident, Span, TokenStream};
+
+use crate::helper::*;
+use crate::url::url_func::UrlFunc;
+use crate::url::urlargs::UrlArgs;
+use crate::url::urlexpr::UrlExpr;
+
+/// The routes a resource may declare: method, whether the route is on an
+/// item (`/base/<id>`) rather than the collection, and the suffix of the
+/// handler generated for it.
+const ROUTES: [(&str, bool, &str); 5] = [
+    ("GET", false, "list"),
+    ("POST", false, "create"),
+    ("GET", true, "show"),
+    ("PUT", true, "update"),
+    ("DELETE", true, "delete"),
+];
+
+/// One handler body of a resource.
+struct Route {
+    method: &'static str,
+    item: bool,
+    suffix: &'static str,
+    body: TokenStream,
+}
+
+/// Parse and expand a resource, HTTP only
+/// resource! {
+///   <url-expr>,
+///   middleware = [ ... ],  // Optional, shared by every route
+///   config = [ ... ], // Optional, shared by every route
+///   pub users<HTTP> {
+///     GET { ... }          // GET    /base       -> users_list
+///     POST { ... }         // POST   /base       -> users_create
+///     GET <id> { ... }     // GET    /base/<id>  -> users_show
+///     PUT <id> { ... }     // PUT    /base/<id>  -> users_update
+///     DELETE <id> { ... }  // DELETE /base/<id>  -> users_delete
+///   }
+/// }
+/// Each body becomes a handler like an `endpoint!` body. Routing is by path,
+/// so `/base` and `/base/<id>` are registered through the `endpoint!`
+/// machinery as `users` and `users_item`, which call the handler for the
+/// request's method and fail any other method with 405.
+pub(crate) fn resource_trans(input: TokenStream) -> TokenStream {
+    match parse_and_expand(input) {
+        Ok(tokens) => tokens,
+        Err(err) => err,
+    }
+}
+
+fn parse_and_expand(input: TokenStream) -> Result<TokenStream, TokenStream> {
+    let mut tokens = into_peekable_iter(input);
+    let url_expr =
+        expect_stream_before_comma_consume(&mut tokens, true, "Expected a comma after the URL")?;
+    let url_expr = UrlExpr::from_tokens(url_expr)?;
+
+    let mut middlewares = None;
+    let mut config = None;
+    if match_ident_consume(&mut tokens, "middleware") {
+        tokens.next(); // Consume the `=`
+        middlewares = Some(expect_array_consume(
+            &mut tokens,
+            "Expected an array for middleware",
+        )?);
+    }
+    match_punct_consume(&mut tokens, ",");
+    if match_ident_consume(&mut tokens, "config") {
+        tokens.next(); // Consume the `=`
+        config = Some(expect_array_consume(
+            &mut tokens,
+            "Expected an array for config",
+        )?);
+    }
+    match_punct_consume(&mut tokens, ",");
+
+    let attrs = parse_outer_attrs(&mut tokens)?;
+    let is_pub = match_ident_consume(&mut tokens, "pub");
+    let name = expect_any_ident(&mut tokens, "Expected resource name")?;
+    expect_punct_consume(&mut tokens, "<", "Expected '<' after resource name")?;
+    let protocol = expect_any_ident(&mut tokens, "Expected protocol identifier after '<'")?;
+    expect_punct_consume(&mut tokens, ">", "Expected '>' after protocol identifier")?;
+    let body = expect_group_consume_return_inner(
+        &mut tokens,
+        Delimiter::Brace,
+        "Expected the method handlers inside braces",
+    )?;
+    let (routes, param) = parse_routes(body)?;
+    if routes.is_empty() {
+        return Err(generate_compile_error(
+            name.span(),
+            "Expected at least one method handler",
+        ));
+    }
+
+    let mut output = TokenStream::new();
+    for route in &routes {
+        let handler = UrlFunc::new(
+            is_pub,
+            handler_name(&name, route),
+            protocol.clone(),
+            Ident::new("req", Span::call_site()),
+            route.body.clone(),
+            OuterAttr::new(vec![]),
+        );
+        output.extend(handler.generate_function());
+    }
+
+    let item_url = match &param {
+        Some(param) => Some(url_expr.with_suffix(&format!("/<{}>", param))?),
+        None => None,
+    };
+    let (items, collection): (Vec<&Route>, Vec<&Route>) =
+        routes.iter().partition(|route| route.item);
+    if !collection.is_empty() {
+        let dispatch = dispatch_function(
+            is_pub,
+            name.clone(),
+            &protocol,
+            attrs.clone(),
+            &name,
+            &collection,
+        );
+        output.extend(
+            UrlArgs::new(url_expr, config.clone(), middlewares.clone(), dispatch).expand_endpoint(),
+        );
+    }
+    if let Some(item_url) = item_url {
+        let item_name = Ident::new(&format!("{}_item", name), Span::call_site());
+        let dispatch = dispatch_function(is_pub, item_name, &protocol, attrs, &name, &items);
+        output.extend(UrlArgs::new(item_url, config, middlewares, dispatch).expand_endpoint());
+    }
+    Ok(output)
+}
+
+/// Splits the handler block into its routes and the name of the item
+/// parameter, if any route takes one.
+fn parse_routes(input: TokenStream) -> Result<(Vec<Route>, Option<String>), TokenStream> {
+    let mut tokens = into_peekable_iter(input);
+    let mut routes: Vec<Route> = Vec::new();
+    let mut param: Option<String> = None;
+
+    while tokens.peek().is_some() {
+        let method = expect_any_ident(&mut tokens, "Expected an HTTP method")?;
+        let item = match_punct_consume(&mut tokens, "<");
+        if item {
+            let ident = expect_any_ident(&mut tokens, "Expected a parameter name after '<'")?;
+            expect_punct_consume(&mut tokens, ">", "Expected '>' after parameter name")?;
+            match &param {
+                Some(param) if *param != ident.to_string() => {
+                    return Err(generate_compile_error(
+                        ident.span(),
+                        &format!("Item routes must all use the same parameter, `<{}>`", param),
+                    ));
+                }
+                _ => param = Some(ident.to_string()),
+            }
+        }
+        let body = expect_group_consume_return_inner(
+            &mut tokens,
+            Delimiter::Brace,
+            "Expected the handler body inside braces",
+        )?;
+
+        let method_name = method.to_string();
+        let Some(&(method_str, _, suffix)) = ROUTES
+            .iter()
+            .find(|(m, i, _)| *m == method_name && *i == item)
+        else {
+            let message = if item {
+                "Item routes take GET, PUT or DELETE"
+            } else {
+                "Collection routes take GET or POST"
+            };
+            return Err(generate_compile_error(method.span(), message));
+        };
+        if routes
+            .iter()
+            .any(|r| r.method == method_str && r.item == item)
+        {
+            return Err(generate_compile_error(
+                method.span(),
+                &format!("Duplicate handler for {}", method_name),
+            ));
+        }
+        routes.push(Route {
+            method: method_str,
+            item,
+            suffix,
+            body,
+        });
+    }
+
+    Ok((routes, param))
+}
+
+fn handler_name(resource: &Ident, route: &Route) -> Ident {
+    Ident::new(&format!("{}_{}", resource, route.suffix), Span::call_site())
+}
+
+/// The endpoint for one path: runs the handler for the request's method
+/// and applies its outcome, or fails with `MethodNotAllowed`.
+//
+// Expanded body:
+//
+// match req.method() {
+//     HttpMethod::GET => {
+//         let __outcome = users_list(&mut *req).await;
+//         EndpointOutcome::apply_to(__outcome, req)
+//     }
+//     ...
+//     _ => Err(HttpError::MethodNotAllowed),
+// }
+fn dispatch_function(
+    is_pub: bool,
+    fn_name: Ident,
+    protocol: &Ident,
+    attrs: OuterAttr,
+    resource: &Ident,
+    routes: &[&Route],
+) -> UrlFunc {
+    let mut arms = String::new();
+    for route in routes {
+        arms.push_str(&format!(
+            "hotaru::hotaru_http::http_value::HttpMethod::{} => {{ \
+                let __outcome = {}(&mut *req).await; \
+                hotaru::hotaru_core::protocol::EndpointOutcome::apply_to(__outcome, req) \
+            }} ",
+            route.method,
+            handler_name(resource, route),
+        ));
+    }
+    let body = format!(
+        "match req.method() {{ {arms} _ => Err(hotaru::hotaru_http::HttpError::MethodNotAllowed), }}"
+    );
+    UrlFunc::new(
+        is_pub,
+        fn_name,
+        protocol.clone(),
+        Ident::new("req", Span::call_site()),
+        body.parse().expect("resource! dispatch expansion"),
+        attrs,
+    )
+}
@@ -11,6 +11,9 @@ pub struct UrlFunc {
     pub req_var_name: Ident,
     pub fn_cont: TokenStream,
     pub attrs: OuterAttr,
+    /// The handler takes the context by value and returns it; the wrapper
+    /// passes it through without applying an `EndpointOutcome`
+    pub is_raw: bool,
 }
 
 impl UrlFunc {
@@ -29,10 +32,33 @@ impl UrlFunc {
             fn_cont,
             req_var_name,
             attrs,
+            is_raw: false,
         }
     }
 
+    pub fn raw(mut self, is_raw: bool) -> Self {
+        self.is_raw = is_raw;
+        self
+    }
+
+    /// `<P as Protocol>::Context`
+    fn context_type(&self) -> Vec<TokenTree> {
+        vec![
+            TokenTree::Punct(Punct::new('<', Spacing::Alone)),
+            TokenTree::Ident(self.protocol.clone()),
+            TokenTree::Ident(Ident::new("as", Span::call_site())),
+            TokenTree::Ident(Ident::new("Protocol", Span::call_site())),
+            TokenTree::Punct(Punct::new('>', Spacing::Alone)),
+            TokenTree::Punct(Punct::new(':', Spacing::Joint)),
+            TokenTree::Punct(Punct::new(':', Spacing::Alone)),
+            TokenTree::Ident(Ident::new("Context", Span::call_site())),
+        ]
+    }
+
     pub fn generate_function(&self) -> TokenStream {
+        if self.is_raw {
+            return self.generate_raw_function();
+        }
         let mut arguments = TokenStream::new();
         arguments.extend(vec![
             TokenTree::Ident(self.req_var_name.clone()),
@@ -87,7 +113,47 @@ impl UrlFunc {
         tokens
     }
 
+    /// Raw handler: owns the context and hands it back.
+    //
+    // Expanded form:
+    //
+    // async fn <fn_name>(mut <req>: <P as Protocol>::Context)
+    //     -> <P as Protocol>::Context
+    // { <fn_cont> }
+    fn generate_raw_function(&self) -> TokenStream {
+        let mut arguments = TokenStream::new();
+        arguments.extend(vec![
+            TokenTree::Ident(Ident::new("mut", Span::call_site())),
+            TokenTree::Ident(self.req_var_name.clone()),
+            TokenTree::Punct(Punct::new(':', Spacing::Alone)),
+        ]);
+        arguments.extend(self.context_type());
+
+        let mut tokens = TokenStream::new();
+        tokens.extend(self.attrs.reform());
+        if self.is_pub {
+            tokens.extend(vec![TokenTree::Ident(Ident::new("pub", Span::call_site()))]);
+        }
+        tokens.extend(vec![
+            TokenTree::Ident(Ident::new("async", Span::call_site())),
+            TokenTree::Ident(Ident::new("fn", Span::call_site())),
+            TokenTree::Ident(self.fn_name.clone()),
+            TokenTree::Group(Group::new(Delimiter::Parenthesis, arguments)),
+            TokenTree::Punct(Punct::new('-', Spacing::Joint)),
+            TokenTree::Punct(Punct::new('>', Spacing::Alone)),
+        ]);
+        tokens.extend(self.context_type());
+        tokens.extend(vec![TokenTree::Group(Group::new(
+            Delimiter::Brace,
+            self.fn_cont.clone(),
+        ))]);
+        tokens
+    }
+
     pub(crate) fn wrapper_function(&self) -> TokenStream {
+        if self.is_raw {
+            return self.raw_wrapper_function();
+        }
         let mut arguments = TokenStream::new();
         arguments.extend(vec![
             TokenTree::Ident(Ident::new("mut", Span::call_site())),
@@ -190,6 +256,77 @@ impl UrlFunc {
         tokens
     }
 
+    /// Wrapper of a raw handler: the returned context goes out untouched.
+    //
+    // Expanded form:
+    //
+    // async fn __wrapper_<fn_name>(
+    //     <req>: <P as Protocol>::Context,
+    // ) -> Result<
+    //     <P as Protocol>::Context,
+    //     <<P as Protocol>::Context as RequestContext>::Error,
+    // > {
+    //     Ok(<fn_name>(<req>).await)
+    // }
+    fn raw_wrapper_function(&self) -> TokenStream {
+        let mut arguments = TokenStream::new();
+        arguments.extend(vec![
+            TokenTree::Ident(self.req_var_name.clone()),
+            TokenTree::Punct(Punct::new(':', Spacing::Alone)),
+        ]);
+        arguments.extend(self.context_type());
+
+        // Ok(<fn_name>(<req>).await)
+        let mut call = TokenStream::new();
+        call.extend(vec![
+            TokenTree::Ident(self.fn_name.clone()),
+            TokenTree::Group(Group::new(Delimiter::Parenthesis, {
+                let mut g = TokenStream::new();
+                g.extend(std::iter::once(TokenTree::Ident(self.req_var_name.clone())));
+                g
+            })),
+            TokenTree::Punct(Punct::new('.', Spacing::Alone)),
+            TokenTree::Ident(Ident::new("await", Span::call_site())),
+        ]);
+        let mut body = TokenStream::new();
+        body.extend(vec![
+            TokenTree::Ident(Ident::new("Ok", Span::call_site())),
+            TokenTree::Group(Group::new(Delimiter::Parenthesis, call)),
+        ]);
+
+        let mut tokens = TokenStream::new();
+        tokens.extend(vec![
+            TokenTree::Ident(Ident::new("async", Span::call_site())),
+            TokenTree::Ident(Ident::new("fn", Span::call_site())),
+            TokenTree::Ident(Ident::new(
+                &format!("__wrapper_{}", &self.fn_name),
+                Span::call_site(),
+            )),
+            TokenTree::Group(Group::new(Delimiter::Parenthesis, arguments)),
+            TokenTree::Punct(Punct::new('-', Spacing::Joint)),
+            TokenTree::Punct(Punct::new('>', Spacing::Alone)),
+            TokenTree::Ident(Ident::new("Result", Span::call_site())),
+            TokenTree::Punct(Punct::new('<', Spacing::Alone)),
+        ]);
+        tokens.extend(self.context_type());
+        tokens.extend(vec![
+            TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+            TokenTree::Punct(Punct::new('<', Spacing::Alone)),
+        ]);
+        tokens.extend(self.context_type());
+        tokens.extend(vec![
+            TokenTree::Ident(Ident::new("as", Span::call_site())),
+            TokenTree::Ident(Ident::new("RequestContext", Span::call_site())),
+            TokenTree::Punct(Punct::new('>', Spacing::Alone)),
+            TokenTree::Punct(Punct::new(':', Spacing::Joint)),
+            TokenTree::Punct(Punct::new(':', Spacing::Alone)),
+            TokenTree::Ident(Ident::new("Error", Span::call_site())),
+            TokenTree::Punct(Punct::new('>', Spacing::Alone)),
+            TokenTree::Group(Group::new(Delimiter::Brace, body)),
+        ]);
+        tokens
+    }
+
     /// Emit `__outpoint_final_<fn_name>` — the outpoint chain's final handler.
     /// Lifted to `AsyncFinalHandler<Ctx>` via the blanket `Fn(C) -> Fut` impl.
     //
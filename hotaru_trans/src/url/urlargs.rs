@@ -1,6 +1,7 @@
 use proc_macro::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
 
 use crate::ctor::gen_ctor;
+use crate::helper::generate_compile_error;
 use crate::url::url_func::UrlFunc;
 use crate::url::urlexpr::UrlExpr;
 
@@ -14,6 +15,8 @@ pub enum UrlKind {
 /// Arguments for the `url` macro.
 pub struct UrlArgs {
     pub url_expr: UrlExpr,
+    /// Further URLs served by the same handler, e.g. `endpoint! { APP.url("/a"), APP.url("/b"), ... }`
+    pub aliases: Vec<UrlExpr>,
     pub config: Option<Vec<TokenStream>>,
     pub middlewares: Option<Vec<TokenStream>>,
     pub op: UrlFunc,
@@ -28,12 +31,19 @@ impl UrlArgs {
     ) -> Self {
         UrlArgs {
             url_expr,
+            aliases: Vec::new(),
             config,
             middlewares,
             op,
         }
     }
 
+    /// Registers the handler under `aliases` as well as its own URL.
+    pub fn with_aliases(mut self, aliases: Vec<UrlExpr>) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
     pub fn reg_func(&self, kind: UrlKind) -> TokenStream {
         // Generate constructor attributes using gen_ctor()
         let ctor_attrs = gen_ctor();
@@ -351,12 +361,25 @@ impl UrlArgs {
             ]);
         }
         
+        // Aliases get clones of the binding, so every URL shares the one
+        // handler Arc. Each is its own access point, named `<fn>#<n>`.
+        // APP.url::<HTTP, _, _>("/alias", "fn#1", binding.clone(), params.clone()).expect(..);
+        for (index, alias) in self.aliases.iter().enumerate() {
+            cont.extend(alias.expand(
+                self.op.protocol.clone(),
+                &format!("{}#{}", self.op.fn_name, index + 1),
+                cloned("binding"),
+                cloned("params"),
+            ));
+            cont.extend(std::iter::once(TokenTree::Punct(Punct::new(';', Spacing::Alone))));
+        }
+
         // Modify url_expr to inject the protocol type parameter
         let modified_url_expr = self.url_expr.expand(
             self.op.protocol.clone(), 
-            self.op.fn_name.clone(), 
-            Ident::new("binding", Span::call_site()),
-            Ident::new("params", Span::call_site())
+            &self.op.fn_name.to_string(), 
+            TokenTree::Ident(Ident::new("binding", Span::call_site())).into(),
+            TokenTree::Ident(Ident::new("params", Span::call_site())).into(),
         ); 
 
         cont.extend(modified_url_expr);
@@ -388,6 +411,12 @@ impl UrlArgs {
 
     /// Outpoint orchestrator: __Outpoint_MW_<fn> + __outpoint_final_<fn> + ctor.
     pub fn expand_outpoint(&self) -> TokenStream {
+        if self.op.is_raw {
+            return generate_compile_error(
+                Span::call_site(),
+                "`raw` is only supported by endpoints",
+            );
+        }
         let mut tokens = TokenStream::new();
         tokens.extend(self.op.expand_middleware());
         tokens.extend(self.op.outpoint_final_function());
@@ -396,3 +425,12 @@ impl UrlArgs {
     }
 }
 
+/// `<var>.clone()`
+fn cloned(var: &str) -> TokenStream {
+    TokenStream::from_iter([
+        TokenTree::Ident(Ident::new(var, Span::call_site())),
+        TokenTree::Punct(Punct::new('.', Spacing::Alone)),
+        TokenTree::Ident(Ident::new("clone", Span::call_site())),
+        TokenTree::Group(Group::new(Delimiter::Parenthesis, TokenStream::new())),
+    ])
+}
@@ -7,7 +7,8 @@ use crate::{generate_compile_error, into_peekable_iter};
 pub struct UrlExpr {
     app: Ident,
     method: Ident,
-    literal: Literal 
+    /// `None` for `APP.fallback()`, which takes no path.
+    literal: Option<Literal>
 } 
 
 impl UrlExpr {
@@ -15,7 +16,15 @@ impl UrlExpr {
         Self {
             app,
             method,
-            literal,
+            literal: Some(literal),
+        }
+    }
+
+    pub fn fallback(app: Ident, method: Ident) -> Self {
+        Self {
+            app,
+            method,
+            literal: None,
         }
     }
 
@@ -30,6 +39,7 @@ impl UrlExpr {
     /// APP_IDENTIFIER("path")
     /// APP_IDENTIFIER: "path"
     /// APP_IDENTIFIER.[url|lit_url]("path")
+    /// APP_IDENTIFIER.fallback() // Catch-all for unmatched paths
     /// "path" // Defaults to APP
     pub fn from_tokens(input: TokenStream) -> Result<Self, TokenStream> {
         let mut tokens = into_peekable_iter(input);
@@ -54,6 +64,22 @@ impl UrlExpr {
                     Some(TokenTree::Punct(punct)) if punct.as_char() == '.' => {
                         tokens.next(); // Consume '.' 
                         match tokens.next() {
+                            Some(TokenTree::Ident(method_ident))
+                                if method_ident.to_string() == "fallback" =>
+                            {
+                                match tokens.next() {
+                                    Some(TokenTree::Group(group))
+                                        if group.delimiter() == Delimiter::Parenthesis
+                                            && group.stream().is_empty() =>
+                                    {
+                                        Ok(Self::fallback(app, method_ident))
+                                    }
+                                    _ => Err(generate_compile_error(
+                                        Span::call_site(),
+                                        "Expected empty parentheses after 'fallback'",
+                                    )),
+                                }
+                            }
                             Some(TokenTree::Ident(method_ident))
                                 if method_ident.to_string() == "url"
                                     || method_ident.to_string() == "lit_url" =>
@@ -83,7 +109,7 @@ impl UrlExpr {
                             }
                             _ => Err(generate_compile_error(
                                 Span::call_site(),
-                                "Expected 'url' or 'lit_url' method identifier after '.'",
+                                "Expected 'url', 'lit_url' or 'fallback' method identifier after '.'",
                             )),
                         }
                     }
@@ -108,6 +134,22 @@ impl UrlExpr {
         }
     }
 
+    /// The same URL with `suffix` appended to its path, e.g. `"/users"` +
+    /// `"/<id>"`. Fails for `APP.fallback()`, which has no path to extend.
+    pub fn with_suffix(&self, suffix: &str) -> Result<Self, TokenStream> {
+        let Some(literal) = &self.literal else {
+            return Err(generate_compile_error(
+                Span::call_site(),
+                "Expected a URL path, not a fallback",
+            ));
+        };
+        let raw = literal.to_string();
+        let path = strip_str_literal_quotes(&raw).unwrap_or(raw.as_str());
+        let literal = Literal::string(&format!("{}{}", path.trim_end_matches('/'), suffix));
+        Self::check_url_literal_format(&literal)?;
+        Ok(Self::new(self.app.clone(), self.method.clone(), literal))
+    }
+
     fn check_url_literal_format(lit: &Literal) -> Result<(), TokenStream> {
         // `Literal::to_string()` returns the source repr (e.g. `"/x"`, `r"/x"`,
         // `r#"/x"#`); the URL parser wants the unescaped value, so peel off
@@ -124,9 +166,22 @@ impl UrlExpr {
             .map(|_| ())
     }
 
-    pub fn expand(&self, protocol: Ident, fn_name: Ident, binding: Ident, config: Ident) -> TokenStream {
+    pub fn expand(&self, protocol: Ident, name: &str, binding: TokenStream, config: TokenStream) -> TokenStream {
         // APP.url::<HTTP, _, _>("/path", name, binding, params)
         //  .expect("failed to register endpoint");
+        // APP.fallback::<HTTP, _>(name, binding, params) for fallbacks.
+        let mut generics = vec![TokenTree::Ident(protocol)];
+        let mut args = Vec::new();
+        if let Some(literal) = &self.literal {
+            generics.extend([
+                TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+                TokenTree::Ident(Ident::new("_", Span::call_site())),
+            ]);
+            args.extend([
+                TokenTree::Literal(literal.clone()),
+                TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+            ]);
+        }
         let mut tokens = TokenStream::new();
         tokens.extend(vec![
             TokenTree::Ident(self.app.clone()),
@@ -135,23 +190,22 @@ impl UrlExpr {
             TokenTree::Punct(Punct::new(':', Spacing::Joint)),
             TokenTree::Punct(Punct::new(':', Spacing::Alone)),
             TokenTree::Punct(Punct::new('<', Spacing::Alone)),
-            TokenTree::Ident(protocol),
-            TokenTree::Punct(Punct::new(',', Spacing::Alone)),
-            TokenTree::Ident(Ident::new("_", Span::call_site())),
+        ]);
+        tokens.extend(generics);
+        tokens.extend(vec![
             TokenTree::Punct(Punct::new(',', Spacing::Alone)),
             TokenTree::Ident(Ident::new("_", Span::call_site())),
             TokenTree::Punct(Punct::new('>', Spacing::Alone)),
             TokenTree::Group(Group::new(Delimiter::Parenthesis, {
                 let mut g = TokenStream::new();
+                g.extend(args);
                 g.extend(vec![
-                    TokenTree::Literal(self.literal.clone()), 
-                    TokenTree::Punct(Punct::new(',', Spacing::Alone)),
-                    TokenTree::Literal(Literal::string(&fn_name.to_string())),  
-                    TokenTree::Punct(Punct::new(',', Spacing::Alone)),
-                    TokenTree::Ident(binding),
+                    TokenTree::Literal(Literal::string(name)),  
                     TokenTree::Punct(Punct::new(',', Spacing::Alone)),
-                    TokenTree::Ident(config) 
                 ]);
+                g.extend(binding);
+                g.extend(std::iter::once(TokenTree::Punct(Punct::new(',', Spacing::Alone))));
+                g.extend(config);
                 g
             })),
             TokenTree::Punct(Punct::new('.', Spacing::Alone)),
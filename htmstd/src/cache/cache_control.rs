@@ -0,0 +1,85 @@
+//! Parsing of the `Cache-Control` directives the response cache acts on.
+
+use std::time::Duration;
+
+/// The subset of `Cache-Control` that decides whether a response may be
+/// served from, or stored in, a shared cache.
+///
+/// Unknown directives are ignored, as are malformed `max-age` values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    pub no_store: bool,
+    pub no_cache: bool,
+    pub private: bool,
+    pub public: bool,
+    pub must_revalidate: bool,
+    pub max_age: Option<Duration>,
+    pub s_maxage: Option<Duration>,
+}
+
+impl CacheControl {
+    /// Parses a `Cache-Control` header value; `None` yields no directives.
+    pub fn parse(header: Option<&str>) -> Self {
+        let mut directives = Self::default();
+        let Some(header) = header else {
+            return directives;
+        };
+
+        for directive in header.split(',') {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive.trim(), None),
+            };
+            let seconds = || {
+                value
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .map(Duration::from_secs)
+            };
+            match name.to_ascii_lowercase().as_str() {
+                "no-store" => directives.no_store = true,
+                "no-cache" => directives.no_cache = true,
+                "private" => directives.private = true,
+                "public" => directives.public = true,
+                "must-revalidate" => directives.must_revalidate = true,
+                "max-age" => directives.max_age = seconds(),
+                "s-maxage" => directives.s_maxage = seconds(),
+                _ => {}
+            }
+        }
+        directives
+    }
+
+    /// Freshness lifetime the response grants a shared cache, if any.
+    /// `s-maxage` takes precedence over `max-age`.
+    pub fn shared_max_age(&self) -> Option<Duration> {
+        self.s_maxage.or(self.max_age)
+    }
+
+    /// Whether a shared cache may store this response to a request that
+    /// carried `Authorization` (RFC 9111 §3.5).
+    pub fn allows_authorized(&self) -> bool {
+        self.public || self.must_revalidate || self.s_maxage.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_flags_and_ages() {
+        let cc = CacheControl::parse(Some("public, Max-Age=60, s-maxage=\"30\", no-cache"));
+        assert!(cc.no_cache);
+        assert!(cc.public);
+        assert!(!cc.no_store);
+        assert!(!cc.must_revalidate);
+        assert_eq!(cc.max_age, Some(Duration::from_secs(60)));
+        assert_eq!(cc.shared_max_age(), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn missing_or_malformed_values_are_ignored() {
+        assert_eq!(CacheControl::parse(None), CacheControl::default());
+        assert_eq!(CacheControl::parse(Some("max-age=soon")).max_age, None);
+    }
+}
@@ -0,0 +1,190 @@
+//! The response caching middleware.
+
+use hotaru_core::executable::middleware::AsyncMiddleware;
+use hotaru_core::protocol::{Protocol, RequestContext};
+use hotaru_http::traits::HTTP;
+use hotaru_trans::middleware;
+
+use super::store::ResponseCacheStore;
+
+middleware! {
+    /// Serves cacheable requests from a [`ResponseCacheStore`] and stores the
+    /// cacheable responses the handler produces.
+    ///
+    /// The store comes from the endpoint params, then the runtime config,
+    /// falling back to a process-wide default.
+    pub ResponseCache<HTTP> {
+        let store = req
+            .endpoint()
+            .and_then(|ep| ep.get_params::<ResponseCacheStore>())
+            .or_else(|| req.runtime().and_then(|rt| rt.get_config::<ResponseCacheStore>()))
+            .unwrap_or_default();
+
+        if let Some(cached) = store.lookup(&req.request.meta) {
+            req.response = cached;
+            return Ok(req);
+        }
+
+        let req = next(req).await?;
+        store.store(&req.request.meta, &req.response);
+        Ok(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::ResponseCacheSettings;
+    use hotaru_core::app::common::{RunMode, RuntimeConfig};
+    use hotaru_core::executable::middleware::AsyncFinalHandler;
+    use hotaru_core::executable::{ExecutableBinding, ExecutionChain};
+    use hotaru_core::extensions::{Locals, Params, ParamsClone};
+    use hotaru_core::url::{Children, PathPattern, StepName, UrlNode};
+    use hotaru_http::body::HttpBody;
+    use hotaru_http::context::HttpContext;
+    use hotaru_http::request::HttpRequest;
+    use hotaru_http::safety::HttpSafety;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    /// A clock that only moves when the test advances it
+    #[derive(Clone)]
+    struct TestClock(Arc<Mutex<Instant>>);
+
+    impl TestClock {
+        fn new() -> Self {
+            Self(Arc::new(Mutex::new(Instant::now())))
+        }
+
+        fn advance(&self, by: Duration) {
+            *self.0.lock().unwrap() += by;
+        }
+
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    /// A route behind `ResponseCache`, counting how often its handler runs
+    struct Route {
+        store: ResponseCacheStore,
+        chain: ExecutionChain<HttpContext>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Route {
+        /// The handler answers with the given `Cache-Control`, if any
+        fn new(store: ResponseCacheStore, cache_control: Option<&'static str>) -> Self {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let counter = calls.clone();
+            let handler: Arc<dyn AsyncFinalHandler<HttpContext>> =
+                Arc::new(move |mut ctx: HttpContext| {
+                    let call = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    async move {
+                        ctx.set_body(HttpBody::Text(format!("call {call}")));
+                        if let Some(cache_control) = cache_control {
+                            ctx.response
+                                .meta
+                                .set_attribute("Cache-Control", cache_control);
+                        }
+                        Ok(ctx)
+                    }
+                });
+            Self {
+                store,
+                chain: ExecutionChain::new(vec![Arc::new(ResponseCache)], handler),
+                calls,
+            }
+        }
+
+        /// Sends a GET with `headers`, returning the body
+        async fn get(&self, headers: &[(&str, &str)]) -> String {
+            let mut params = ParamsClone::default();
+            params.set(self.store.clone());
+            let runtime =
+                RuntimeConfig::from_parts(RunMode::default(), Params::new(), Locals::new());
+            let endpoint = UrlNode::new(
+                PathPattern::literal_path("page"),
+                Children::new(),
+                ExecutableBinding::new(),
+                params,
+                StepName::default(),
+            );
+            let mut request = HttpRequest::default();
+            for (name, value) in headers {
+                request.meta.set_attribute(*name, *value);
+            }
+            let ctx = HttpContext::new_server(
+                Arc::new(runtime),
+                Arc::new(endpoint),
+                request,
+                None,
+                None,
+                HttpSafety::default(),
+            );
+            match self.chain.run(ctx).await.unwrap().response.body {
+                HttpBody::Text(text) => text,
+                other => panic!("unexpected body {other:?}"),
+            }
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_request_is_served_from_cache_until_expiry() {
+        let clock = TestClock::new();
+        let store =
+            ResponseCacheStore::new(ResponseCacheSettings::default().ttl(Duration::from_secs(30)))
+                .with_clock({
+                    let clock = clock.clone();
+                    move || clock.now()
+                });
+        let route = Route::new(store, None);
+
+        assert_eq!(route.get(&[]).await, "call 1");
+        clock.advance(Duration::from_secs(29));
+        assert_eq!(route.get(&[]).await, "call 1");
+        assert_eq!(route.calls(), 1);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(route.get(&[]).await, "call 2");
+        assert_eq!(route.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn authorized_responses_are_shared_only_when_allowed() {
+        let alice = [("Authorization", "Bearer alice")];
+        let bob = [("Authorization", "Bearer bob")];
+
+        let route = Route::new(
+            ResponseCacheStore::new(ResponseCacheSettings::default()),
+            None,
+        );
+        assert_eq!(route.get(&alice).await, "call 1");
+        assert_eq!(route.get(&bob).await, "call 2");
+        assert!(route.store.is_empty());
+
+        for allowing in ["public", "must-revalidate", "s-maxage=60"] {
+            let store = ResponseCacheStore::new(ResponseCacheSettings::default());
+            let route = Route::new(store, Some(allowing));
+            assert_eq!(route.get(&alice).await, "call 1");
+            assert_eq!(route.get(&bob).await, "call 1", "{allowing}");
+        }
+    }
+
+    #[tokio::test]
+    async fn virtual_hosts_are_cached_apart() {
+        let route = Route::new(
+            ResponseCacheStore::new(ResponseCacheSettings::default()),
+            None,
+        );
+        assert_eq!(route.get(&[("Host", "a.example")]).await, "call 1");
+        assert_eq!(route.get(&[("Host", "b.example")]).await, "call 2");
+        assert_eq!(route.get(&[("Host", "A.example")]).await, "call 1");
+        assert_eq!(route.calls(), 2);
+    }
+}
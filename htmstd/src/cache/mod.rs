@@ -0,0 +1,21 @@
+//! Route-level response caching for Hotaru/htmstd.
+//!
+//! The module is split by responsibility:
+//! - [`cache_control`]: parsing of the relevant `Cache-Control` directives
+//! - [`settings`]: [`ResponseCacheSettings`] limits and cacheability rules
+//! - [`store`]: the bounded LRU [`ResponseCacheStore`]
+//! - [`middleware`]: the [`ResponseCache`] middleware
+//!
+//! Entries are keyed by method, host, URL and the request headers named by the
+//! response's `Vary`, and expire after the configured TTL or the response's
+//! `max-age`, whichever is shorter.
+
+pub mod cache_control;
+pub mod middleware;
+pub mod settings;
+pub mod store;
+
+pub use self::cache_control::CacheControl;
+pub use self::middleware::ResponseCache;
+pub use self::settings::ResponseCacheSettings;
+pub use self::store::ResponseCacheStore;
@@ -0,0 +1,85 @@
+//! Configuration for [`crate::ResponseCache`].
+
+use std::time::Duration;
+
+use hotaru_http::http_value::HttpMethod;
+
+/// Default time a response stays fresh when it does not say otherwise.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60);
+
+/// Default number of responses kept before the least recently used is
+/// evicted.
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// Limits and cacheability rules for a [`super::ResponseCacheStore`].
+///
+/// By default only `200` responses to `GET` are cached.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseCacheSettings {
+    ttl: Duration,
+    capacity: usize,
+    methods: Vec<HttpMethod>,
+    statuses: Vec<u16>,
+}
+
+impl ResponseCacheSettings {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            ..Self::default()
+        }
+    }
+
+    /// Builder-style setter for the default freshness lifetime.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Builder-style setter for the maximum number of cached responses.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Builder-style setter for the request methods whose responses may be
+    /// cached.
+    pub fn methods<I: IntoIterator<Item = HttpMethod>>(mut self, methods: I) -> Self {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Builder-style setter for the response status codes that may be cached.
+    pub fn statuses<I: IntoIterator<Item = u16>>(mut self, statuses: I) -> Self {
+        self.statuses = statuses.into_iter().collect();
+        self
+    }
+
+    pub fn get_ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn caches_method(&self, method: &HttpMethod) -> bool {
+        self.methods.contains(method)
+    }
+
+    pub fn caches_status(&self, status: u16) -> bool {
+        self.statuses.contains(&status)
+    }
+}
+
+impl Default for ResponseCacheSettings {
+    fn default() -> Self {
+        Self {
+            ttl: DEFAULT_TTL,
+            capacity: DEFAULT_CAPACITY,
+            methods: vec![HttpMethod::GET],
+            statuses: vec![200],
+        }
+    }
+}
@@ -0,0 +1,377 @@
+//! Bounded LRU storage of full responses.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use hotaru_http::meta::HttpMeta;
+use hotaru_http::response::HttpResponse;
+use lazy_static::lazy_static;
+
+use super::cache_control::CacheControl;
+use super::settings::ResponseCacheSettings;
+
+lazy_static! {
+    static ref DEFAULT_STORE: ResponseCacheStore =
+        ResponseCacheStore::new(ResponseCacheSettings::default());
+}
+
+/// One cached variant of a resource.
+#[derive(Debug)]
+struct Entry {
+    /// Request header values named by the response's `Vary`, lowercased
+    /// names paired with the values this variant was stored for.
+    vary: Vec<(String, Option<String>)>,
+    response: HttpResponse,
+    stored_at: Instant,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    /// Variants keyed by method, host and URL (including the query string)
+    entries: HashMap<String, Vec<Entry>>,
+    len: usize,
+    tick: u64,
+}
+
+/// Source of the current time for expiry and `Age`.
+type Clock = Arc<dyn Fn() -> Instant + Send + Sync>;
+
+/// Shared store behind [`crate::ResponseCache`].
+///
+/// Clones share the same entries. Put one in the runtime config (or an
+/// endpoint's params) to give an app or route its own cache; without one the
+/// middleware uses a process-wide store with default settings.
+#[derive(Clone)]
+pub struct ResponseCacheStore {
+    settings: Arc<ResponseCacheSettings>,
+    inner: Arc<Mutex<Inner>>,
+    clock: Clock,
+}
+
+impl fmt::Debug for ResponseCacheStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseCacheStore")
+            .field("settings", &self.settings)
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ResponseCacheStore {
+    fn default() -> Self {
+        DEFAULT_STORE.clone()
+    }
+}
+
+impl ResponseCacheStore {
+    pub fn new(settings: ResponseCacheSettings) -> Self {
+        Self {
+            settings: Arc::new(settings),
+            inner: Arc::new(Mutex::new(Inner::default())),
+            clock: Arc::new(Instant::now),
+        }
+    }
+
+    /// Builder-style setter for the clock, `Instant::now` by default. Lets
+    /// tests move time forward instead of sleeping.
+    pub fn with_clock(mut self, clock: impl Fn() -> Instant + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn settings(&self) -> &ResponseCacheSettings {
+        &self.settings
+    }
+
+    /// Number of cached responses, including ones that have expired but were
+    /// not evicted yet.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.entries.clear();
+        inner.len = 0;
+    }
+
+    /// Returns a fresh cached response for `request`, with an `Age` header.
+    ///
+    /// Returns `None` for methods that are not cached and for requests
+    /// carrying `Cache-Control: no-cache` or `no-store`, which must reach the
+    /// handler.
+    pub fn lookup(&self, request: &HttpMeta) -> Option<HttpResponse> {
+        if !self.settings.caches_method(&request.method()) {
+            return None;
+        }
+        let request_cc = CacheControl::parse(request.get_header("cache-control").as_deref());
+        if request_cc.no_store || request_cc.no_cache {
+            return None;
+        }
+
+        let now = (self.clock)();
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let tick = inner.tick;
+        let variants = inner.entries.get_mut(&key(request))?;
+        let entry = variants.iter_mut().find(|entry| {
+            entry.expires_at > now
+                && entry
+                    .vary
+                    .iter()
+                    .all(|(name, value)| request.get_header(name.as_str()) == *value)
+        })?;
+        entry.last_used = tick;
+
+        let mut response = entry.response.clone();
+        let age = now.duration_since(entry.stored_at).as_secs();
+        response.meta.set_attribute("age", age.to_string());
+        Some(response)
+    }
+
+    /// Stores `response` for `request` if both allow it, returning whether
+    /// it was stored.
+    ///
+    /// The request must use a cached method and not send `no-store`. The
+    /// response must have a cached status, must not carry `no-store`,
+    /// `no-cache`, `private`, `Set-Cookie` or `Vary: *`, and must not grant a
+    /// zero lifetime. `s-maxage` or `max-age` shorten the configured TTL.
+    ///
+    /// A response to a request with `Authorization` is only stored when it
+    /// says `public`, `must-revalidate` or `s-maxage`, so one user's
+    /// authenticated response is not served to others.
+    pub fn store(&self, request: &HttpMeta, response: &HttpResponse) -> bool {
+        if !self.settings.caches_method(&request.method()) {
+            return false;
+        }
+        if CacheControl::parse(request.get_header("cache-control").as_deref()).no_store {
+            return false;
+        }
+        if !self
+            .settings
+            .caches_status(response.meta.start_line.status_code().as_u16())
+        {
+            return false;
+        }
+
+        let response_cc = CacheControl::parse(response.meta.get_header("cache-control").as_deref());
+        if response_cc.no_store || response_cc.no_cache || response_cc.private {
+            return false;
+        }
+        if request.get_header("authorization").is_some() && !response_cc.allows_authorized() {
+            return false;
+        }
+        if response.meta.get_header("set-cookie").is_some()
+            || !response.meta.clone().get_cookies().0.is_empty()
+        {
+            return false;
+        }
+        let ttl = match response_cc.shared_max_age() {
+            Some(max_age) => max_age.min(self.settings.get_ttl()),
+            None => self.settings.get_ttl(),
+        };
+        if ttl == Duration::ZERO || self.settings.get_capacity() == 0 {
+            return false;
+        }
+
+        let vary_names: Vec<String> = response
+            .meta
+            .get_header("vary")
+            .map(|vary| {
+                vary.split(',')
+                    .map(|name| name.trim().to_ascii_lowercase())
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if vary_names.iter().any(|name| name == "*") {
+            return false;
+        }
+        let vary: Vec<(String, Option<String>)> = vary_names
+            .into_iter()
+            .map(|name| {
+                let value = request.get_header(name.as_str());
+                (name, value)
+            })
+            .collect();
+
+        let now = (self.clock)();
+        let mut inner = self.inner.lock().unwrap();
+        inner.tick += 1;
+        let entry = Entry {
+            vary,
+            response: response.clone(),
+            stored_at: now,
+            expires_at: now + ttl,
+            last_used: inner.tick,
+        };
+
+        let variants = inner.entries.entry(key(request)).or_default();
+        let replaced = variants.len();
+        variants.retain(|existing| existing.vary != entry.vary);
+        let removed = replaced - variants.len();
+        variants.push(entry);
+        inner.len = inner.len + 1 - removed;
+
+        while inner.len > self.settings.get_capacity() {
+            inner.evict(now);
+        }
+        true
+    }
+}
+
+impl Inner {
+    /// Drops expired entries, or the least recently used one if none has
+    /// expired.
+    fn evict(&mut self, now: Instant) {
+        let before = self.len;
+        self.entries.retain(|_, variants| {
+            variants.retain(|entry| entry.expires_at > now);
+            !variants.is_empty()
+        });
+        self.len = self.entries.values().map(Vec::len).sum();
+        if self.len < before {
+            return;
+        }
+
+        let oldest = self
+            .entries
+            .iter()
+            .flat_map(|(key, variants)| {
+                variants
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, entry)| (entry.last_used, key, index))
+            })
+            .min_by_key(|(last_used, _, _)| *last_used)
+            .map(|(_, key, index)| (key.clone(), index));
+        if let Some((key, index)) = oldest {
+            if let Some(variants) = self.entries.get_mut(&key) {
+                variants.remove(index);
+                if variants.is_empty() {
+                    self.entries.remove(&key);
+                }
+            }
+            self.len -= 1;
+        }
+    }
+}
+
+/// Method, host and URL: virtual hosts sharing a path are different
+/// resources.
+fn key(request: &HttpMeta) -> String {
+    let host = request.get_header("host").unwrap_or_default();
+    format!(
+        "{} {} {}",
+        request.method().to_string(),
+        host.to_ascii_lowercase(),
+        request.url()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hotaru_http::http_value::HttpMethod;
+    use hotaru_http::http_value::HttpVersion;
+    use hotaru_http::meta::HeaderValue;
+    use hotaru_http::response::response_templates;
+    use hotaru_http::start_line::HttpStartLine;
+
+    fn request(method: HttpMethod, path: &str, headers: &[(&str, &str)]) -> HttpMeta {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), HeaderValue::new(*value)))
+            .collect();
+        HttpMeta::new(
+            HttpStartLine::new_request(HttpVersion::Http11, method, path.to_string()),
+            headers,
+        )
+    }
+
+    fn body(response: &HttpResponse) -> String {
+        match &response.body {
+            hotaru_http::body::HttpBody::Text(text) => text.clone(),
+            other => panic!("unexpected body {other:?}"),
+        }
+    }
+
+    #[test]
+    fn cached_response_is_served_on_hit() {
+        let store = ResponseCacheStore::new(ResponseCacheSettings::default());
+        let get = request(HttpMethod::GET, "/items?page=2", &[]);
+        assert!(store.lookup(&get).is_none());
+
+        assert!(store.store(&get, &response_templates::text_response("page two")));
+        let hit = store.lookup(&get).expect("cache hit");
+        assert_eq!(body(&hit), "page two");
+        assert_eq!(hit.meta.get_header("age").as_deref(), Some("0"));
+
+        // The query string is part of the key
+        assert!(
+            store
+                .lookup(&request(HttpMethod::GET, "/items?page=3", &[]))
+                .is_none()
+        );
+        // Only GET is cached by default
+        let post = request(HttpMethod::POST, "/items?page=2", &[]);
+        assert!(store.lookup(&post).is_none());
+        assert!(!store.store(&post, &response_templates::text_response("created")));
+    }
+
+    #[test]
+    fn no_store_bypasses_the_cache() {
+        let store = ResponseCacheStore::new(ResponseCacheSettings::default());
+        let get = request(HttpMethod::GET, "/report", &[]);
+        assert!(store.store(&get, &response_templates::text_response("cached")));
+
+        let no_store = request(HttpMethod::GET, "/report", &[("cache-control", "no-store")]);
+        assert!(store.lookup(&no_store).is_none());
+        assert!(!store.store(&no_store, &response_templates::text_response("fresh")));
+        assert_eq!(body(&store.lookup(&get).unwrap()), "cached");
+
+        let private = request(HttpMethod::GET, "/private", &[]);
+        let response =
+            response_templates::text_response("secret").add_header("Cache-Control", "no-store");
+        assert!(!store.store(&private, &response));
+        assert!(store.lookup(&private).is_none());
+    }
+
+    #[test]
+    fn vary_headers_select_the_variant() {
+        let store = ResponseCacheStore::new(ResponseCacheSettings::default());
+        let en = request(HttpMethod::GET, "/", &[("accept-language", "en")]);
+        let ja = request(HttpMethod::GET, "/", &[("accept-language", "ja")]);
+        let response =
+            response_templates::text_response("hello").add_header("Vary", "Accept-Language");
+        assert!(store.store(&en, &response));
+
+        assert!(store.lookup(&en).is_some());
+        assert!(store.lookup(&ja).is_none());
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let store = ResponseCacheStore::new(ResponseCacheSettings::default().capacity(2));
+        let a = request(HttpMethod::GET, "/a", &[]);
+        let b = request(HttpMethod::GET, "/b", &[]);
+        let c = request(HttpMethod::GET, "/c", &[]);
+        store.store(&a, &response_templates::text_response("a"));
+        store.store(&b, &response_templates::text_response("b"));
+        store.lookup(&a);
+        store.store(&c, &response_templates::text_response("c"));
+
+        assert_eq!(store.len(), 2);
+        assert!(store.lookup(&a).is_some());
+        assert!(store.lookup(&b).is_none());
+        assert!(store.lookup(&c).is_some());
+    }
+}
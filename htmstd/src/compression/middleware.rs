@@ -0,0 +1,165 @@
+//! The compression middleware.
+
+use hotaru_core::executable::middleware::AsyncMiddleware;
+use hotaru_core::protocol::{Protocol, RequestContext};
+use hotaru_http::body::HttpBody;
+use hotaru_http::context::HttpContext;
+use hotaru_http::traits::HTTP;
+use hotaru_trans::middleware;
+
+use super::settings::{CompressionSettings, NoCompression};
+
+middleware! {
+    /// Compresses responses with a coding the client accepts.
+    ///
+    /// Settings come from the endpoint params, then the runtime config,
+    /// falling back to [`CompressionSettings::default`]. Routes whose params
+    /// hold [`NoCompression`] are left alone, as are responses that already
+    /// carry a `Content-Encoding` or whose body is below the size threshold.
+    pub Compression<HTTP> {
+        let mut req = next(req).await?;
+
+        let endpoint = req.endpoint();
+        if endpoint
+            .as_ref()
+            .is_some_and(|ep| ep.get_params::<NoCompression>().is_some())
+        {
+            return Ok(req);
+        }
+        let settings = endpoint
+            .and_then(|ep| ep.get_params::<CompressionSettings>())
+            .or_else(|| req.runtime().and_then(|rt| rt.get_config::<CompressionSettings>()))
+            .unwrap_or_default();
+
+        compress(&mut req, &settings);
+        Ok(req)
+    }
+}
+
+/// Marks the response for compression if it qualifies.
+fn compress(req: &mut HttpContext, settings: &CompressionSettings) {
+    if req.response.meta.get_header("content-encoding").is_some() {
+        return;
+    }
+    let size = match &req.response.body {
+        HttpBody::Text(text) => text.len(),
+        HttpBody::Binary(bin) => bin.len(),
+        HttpBody::Json(json) => json.into_json().len(),
+        _ => return,
+    };
+    if size < settings.get_min_size() {
+        return;
+    }
+    let Some(coding) = req
+        .header_str("accept-encoding")
+        .and_then(|accept| settings.negotiate(accept))
+    else {
+        return;
+    };
+
+    let meta = &mut req.response.meta;
+    meta.set_attribute("content-encoding", coding.as_str());
+    // The body is compressed on send, from the re-parsed header
+    meta.clear_encoding();
+    let vary = match meta.get_header("vary") {
+        None => "Accept-Encoding".to_string(),
+        Some(vary) if vary.to_ascii_lowercase().contains("accept-encoding") => vary,
+        Some(vary) => format!("{vary}, Accept-Encoding"),
+    };
+    meta.set_attribute("vary", vary);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hotaru_core::app::common::{RunMode, RuntimeConfig};
+    use hotaru_core::executable::middleware::AsyncFinalHandler;
+    use hotaru_core::executable::{ExecutableBinding, ExecutionChain};
+    use hotaru_core::extensions::{Locals, Params, ParamsClone};
+    use hotaru_core::url::{Children, PathPattern, StepName, UrlNode};
+    use hotaru_http::encoding::ContentCoding;
+    use hotaru_http::request::HttpRequest;
+    use hotaru_http::safety::HttpSafety;
+    use std::sync::Arc;
+
+    fn page() -> String {
+        "hotaru ".repeat(512)
+    }
+
+    fn context(params: ParamsClone, accept_encoding: &str) -> HttpContext {
+        let runtime = RuntimeConfig::from_parts(RunMode::default(), Params::new(), Locals::new());
+        let endpoint = UrlNode::new(
+            PathPattern::literal_path("page"),
+            Children::new(),
+            ExecutableBinding::new(),
+            params,
+            StepName::default(),
+        );
+        let mut request = HttpRequest::default();
+        request
+            .meta
+            .set_attribute("Accept-Encoding", accept_encoding);
+        HttpContext::new_server(
+            Arc::new(runtime),
+            Arc::new(endpoint),
+            request,
+            None,
+            None,
+            HttpSafety::default(),
+        )
+    }
+
+    async fn serve(ctx: HttpContext) -> (Option<String>, Vec<u8>) {
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext>> =
+            Arc::new(|mut ctx: HttpContext| async move {
+                ctx.set_body(HttpBody::Text(page()));
+                Ok(ctx)
+            });
+        let chain = ExecutionChain::new(vec![Arc::new(Compression)], handler);
+        let ctx = chain.run(ctx).await.unwrap();
+
+        let mut meta = ctx.response.meta;
+        let wire = ctx.response.body.into_static(&mut meta).await;
+        assert_eq!(meta.get_content_length(), Some(wire.len()));
+        (meta.get_header("content-encoding"), wire)
+    }
+
+    #[tokio::test]
+    async fn gzip_capable_client_gets_gzip() {
+        let (encoding, wire) = serve(context(ParamsClone::default(), "gzip, deflate")).await;
+
+        assert_eq!(encoding.as_deref(), Some("gzip"));
+        assert!(wire.len() < page().len());
+        let decoded = ContentCoding::decode_compressed(&ContentCoding::Gzip, &wire).unwrap();
+        assert_eq!(decoded, page().into_bytes());
+    }
+
+    #[tokio::test]
+    async fn route_flagged_no_compression_is_served_uncompressed() {
+        let mut params = ParamsClone::default();
+        params.set(NoCompression);
+
+        let (encoding, wire) = serve(context(params, "gzip, deflate")).await;
+
+        assert_eq!(encoding, None);
+        assert_eq!(wire, page().into_bytes());
+    }
+
+    #[test]
+    fn negotiate_honours_quality_and_preference() {
+        let settings = CompressionSettings::default();
+        assert_eq!(settings.negotiate("gzip"), Some(ContentCoding::Gzip));
+        assert_eq!(settings.negotiate("br, gzip"), Some(ContentCoding::Gzip));
+        assert_eq!(
+            settings.negotiate("gzip;q=0.5, br;q=0.8"),
+            Some(ContentCoding::Brotli)
+        );
+        assert_eq!(
+            settings.negotiate("gzip;q=0, *"),
+            Some(ContentCoding::Brotli)
+        );
+        assert_eq!(settings.negotiate("identity"), None);
+        assert_eq!(settings.negotiate("gzip;q=0"), None);
+        assert_eq!(settings.negotiate(""), None);
+    }
+}
@@ -0,0 +1,18 @@
+//! Response compression for Hotaru/htmstd.
+//!
+//! The module is split by responsibility:
+//! - [`settings`]: the [`CompressionSettings`] codings and size threshold,
+//!   and the per-route [`NoCompression`] flag
+//! - [`middleware`]: the [`Compression`] middleware
+//!
+//! The middleware only picks the coding and sets `Content-Encoding`; the
+//! body is compressed when the response is written. Routes serving
+//! already-compressed payloads opt out with `config = [NoCompression]`.
+//!
+//! Behind the `compression` feature, which is off by default.
+
+pub mod middleware;
+pub mod settings;
+
+pub use self::middleware::Compression;
+pub use self::settings::{CompressionSettings, NoCompression};
@@ -0,0 +1,113 @@
+//! Configuration for [`crate::Compression`].
+
+use hotaru_http::encoding::ContentCoding;
+
+/// Default smallest body worth compressing, in bytes.
+pub const DEFAULT_MIN_SIZE: usize = 1024;
+
+/// Codings [`crate::Compression`] may apply and the body size it starts at.
+///
+/// By default gzip, Brotli and deflate are offered, in that order of
+/// preference when the client rates them equally.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionSettings {
+    min_size: usize,
+    codings: Vec<ContentCoding>,
+}
+
+impl CompressionSettings {
+    pub fn new(min_size: usize) -> Self {
+        Self {
+            min_size,
+            ..Self::default()
+        }
+    }
+
+    /// Builder-style setter for the smallest body that is compressed.
+    pub fn min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Builder-style setter for the codings offered, most preferred first.
+    pub fn codings<I: IntoIterator<Item = ContentCoding>>(mut self, codings: I) -> Self {
+        self.codings = codings.into_iter().collect();
+        self
+    }
+
+    pub fn get_min_size(&self) -> usize {
+        self.min_size
+    }
+
+    pub fn get_codings(&self) -> &[ContentCoding] {
+        &self.codings
+    }
+
+    /// Picks the coding to use for a request's `Accept-Encoding`.
+    ///
+    /// The offered coding with the highest q-value wins, ties going to the
+    /// earlier one in [`get_codings`](Self::get_codings). `*` stands for
+    /// every coding the header does not name; `q=0` refuses a coding.
+    pub fn negotiate(&self, accept_encoding: &str) -> Option<ContentCoding> {
+        let mut wildcard = None;
+        let mut named = Vec::new();
+        for item in accept_encoding.split(',') {
+            let mut parts = item.split(';');
+            let name = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+            if name.is_empty() {
+                continue;
+            }
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if name == "*" {
+                wildcard = Some(quality);
+            } else {
+                named.push((ContentCoding::from_string(&name), quality));
+            }
+        }
+
+        let mut best: Option<(&ContentCoding, f32)> = None;
+        for coding in &self.codings {
+            let quality = named
+                .iter()
+                .find(|(name, _)| name == coding)
+                .map(|(_, q)| *q)
+                .or(wildcard)
+                .unwrap_or(0.0);
+            if quality > 0.0 && best.is_none_or(|(_, q)| quality > q) {
+                best = Some((coding, quality));
+            }
+        }
+        best.map(|(coding, _)| coding.clone())
+    }
+}
+
+impl Default for CompressionSettings {
+    fn default() -> Self {
+        Self {
+            min_size: DEFAULT_MIN_SIZE,
+            codings: vec![
+                ContentCoding::Gzip,
+                ContentCoding::Brotli,
+                ContentCoding::Deflate,
+            ],
+        }
+    }
+}
+
+/// Endpoint flag that keeps [`crate::Compression`] off a route.
+///
+/// For routes whose payloads are already compressed or must be sent byte
+/// for byte:
+///
+/// ```rust,ignore
+/// endpoint! {
+///     APP.url("/assets/app.js.gz"),
+///     config = [NoCompression],
+///     pub bundle<HTTP> { ... }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoCompression;
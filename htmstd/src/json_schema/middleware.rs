@@ -0,0 +1,179 @@
+//! The JSON Schema validation middleware.
+
+use akari::Value;
+use hotaru_core::executable::middleware::AsyncMiddleware;
+use hotaru_core::protocol::{Protocol, RequestContext};
+use hotaru_http::body::HttpBody;
+use hotaru_http::http_value::StatusCode;
+use hotaru_http::response::response_templates::json_response;
+use hotaru_http::traits::HTTP;
+use hotaru_trans::middleware;
+
+use super::schema::{JsonSchema, SchemaError};
+
+middleware! {
+    /// Answers with `422 Unprocessable Entity` when the request body does
+    /// not match the [`JsonSchema`] in the endpoint params.
+    ///
+    /// The response lists every mismatch as
+    /// `{"errors": [{"path": "/name", "message": "is required"}]}`. A body
+    /// that is not JSON at all is reported at the empty path. Routes without
+    /// a schema are passed through, and the request body is left as it was,
+    /// so the handler reads it as usual.
+    pub ValidateJson<HTTP> {
+        let Some(schema) = req.endpoint().and_then(|ep| ep.get_params::<JsonSchema>()) else {
+            return next(req).await;
+        };
+
+        let errors = match body_json(&req.request.body) {
+            Ok(document) => schema.validate(&document),
+            Err(error) => vec![error],
+        };
+        if errors.is_empty() {
+            return next(req).await;
+        }
+
+        let mut listed = Value::new_list();
+        for error in &errors {
+            listed.push(error.to_value());
+        }
+        let mut body = Value::new_dict();
+        body.set("errors", listed);
+        req.response = json_response(body).status(StatusCode::UNPROCESSABLE_ENTITY);
+        Ok(req)
+    }
+}
+
+/// Reads the request body as JSON without consuming it.
+fn body_json(body: &HttpBody) -> Result<Value, SchemaError> {
+    let not_json = |reason: &str| SchemaError {
+        path: String::new(),
+        message: format!("request body {reason}"),
+    };
+    let text = match body {
+        HttpBody::Json(json) => return Ok(json.clone()),
+        HttpBody::Text(text) => text.clone(),
+        HttpBody::Binary(data) => {
+            String::from_utf8(data.clone()).map_err(|_| not_json("is not UTF-8"))?
+        }
+        HttpBody::Buffer {
+            data,
+            content_coding,
+            ..
+        } => {
+            let data = content_coding
+                .decode_compressed(data.clone())
+                .map_err(|_| not_json("could not be decoded"))?;
+            String::from_utf8(data).map_err(|_| not_json("is not UTF-8"))?
+        }
+        _ => return Err(not_json("is missing")),
+    };
+    Value::from_json(&text).map_err(|_| not_json("is not valid JSON"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hotaru_core::app::common::{RunMode, RuntimeConfig};
+    use hotaru_core::executable::middleware::AsyncFinalHandler;
+    use hotaru_core::executable::{ExecutableBinding, ExecutionChain};
+    use hotaru_core::extensions::{Locals, Params, ParamsClone};
+    use hotaru_core::url::{Children, PathPattern, StepName, UrlNode};
+    use hotaru_http::context::HttpContext;
+    use hotaru_http::encoding::ContentCodings;
+    use hotaru_http::http_value::HttpContentType;
+    use hotaru_http::request::HttpRequest;
+    use hotaru_http::safety::HttpSafety;
+    use std::sync::Arc;
+
+    fn context(body: &str) -> HttpContext {
+        let schema = JsonSchema::from_json(
+            r#"{
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "name": {"type": "string", "minLength": 1},
+                    "age": {"type": "integer", "minimum": 0}
+                }
+            }"#,
+        )
+        .unwrap();
+        let mut params = ParamsClone::default();
+        params.set(schema);
+
+        let runtime = RuntimeConfig::from_parts(RunMode::default(), Params::new(), Locals::new());
+        let endpoint = UrlNode::new(
+            PathPattern::literal_path("users"),
+            Children::new(),
+            ExecutableBinding::new(),
+            params,
+            StepName::default(),
+        );
+        let mut request = HttpRequest::default();
+        request.body = HttpBody::Buffer {
+            data: body.as_bytes().to_vec(),
+            content_type: HttpContentType::ApplicationJson(),
+            content_coding: ContentCodings::new(),
+        };
+        HttpContext::new_server(
+            Arc::new(runtime),
+            Arc::new(endpoint),
+            request,
+            None,
+            None,
+            HttpSafety::default(),
+        )
+    }
+
+    /// Runs the middleware in front of a handler echoing the name it reads
+    async fn serve(ctx: HttpContext) -> HttpContext {
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext>> =
+            Arc::new(|mut ctx: HttpContext| async move {
+                let name = ctx.json().await.map(|json| json.get("name").string());
+                ctx.set_body(HttpBody::Text(name.unwrap_or_default()));
+                Ok(ctx)
+            });
+        let chain = ExecutionChain::new(vec![Arc::new(ValidateJson)], handler);
+        chain.run(ctx).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn missing_required_field_gets_422() {
+        let ctx = serve(context(r#"{"age": 3}"#)).await;
+
+        assert_eq!(
+            ctx.response.meta.start_line.status_code(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        let HttpBody::Json(body) = &ctx.response.body else {
+            panic!("expected a JSON body, got {:?}", ctx.response.body);
+        };
+        let errors = body.get("errors").list();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].get("path").string(), "/name");
+        assert_eq!(errors[0].get("message").string(), "is required");
+    }
+
+    #[tokio::test]
+    async fn valid_body_reaches_the_handler() {
+        let ctx = serve(context(r#"{"name": "Hotaru", "age": 3}"#)).await;
+
+        assert_eq!(ctx.response.meta.start_line.status_code(), StatusCode::OK);
+        assert!(matches!(&ctx.response.body, HttpBody::Text(name) if name == "Hotaru"));
+    }
+
+    #[test]
+    fn schema_reports_each_mismatch() {
+        let schema = JsonSchema::from_json(
+            r#"{"type": "object", "properties": {"tags": {"type": "array", "items": {"type": "string"}}, "age": {"type": "integer", "minimum": 0}}}"#,
+        )
+        .unwrap();
+        let document = Value::from_json(r#"{"tags": ["a", 1], "age": -1.5}"#).unwrap();
+
+        let errors = schema.validate(&document);
+        let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["/age", "/tags/1"]);
+        assert_eq!(errors[0].message, "must be of type integer");
+        assert_eq!(errors[1].message, "must be of type string");
+    }
+}
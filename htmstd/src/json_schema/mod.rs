@@ -0,0 +1,17 @@
+//! JSON Schema validation of request bodies for Hotaru/htmstd.
+//!
+//! The module is split by responsibility:
+//! - [`schema`]: the per-route [`JsonSchema`] and the [`SchemaError`]s it
+//!   reports
+//! - [`middleware`]: the [`ValidateJson`] middleware
+//!
+//! Requests whose JSON body does not match the route's schema are answered
+//! with `422 Unprocessable Entity` and never reach the handler. The body is
+//! only read, so a handler behind the middleware still gets it through
+//! `req.json()`.
+
+pub mod middleware;
+pub mod schema;
+
+pub use self::middleware::ValidateJson;
+pub use self::schema::{JsonSchema, SchemaError};
@@ -0,0 +1,225 @@
+//! The schema a route's JSON bodies must match.
+
+use akari::Value;
+
+/// A JSON Schema for the request bodies of one route.
+///
+/// Put it in the endpoint params:
+///
+/// ```rust,ignore
+/// endpoint! {
+///     APP.url("/users"),
+///     middleware = [.., ValidateJson],
+///     config = [JsonSchema::from_json(r#"{"type": "object", "required": ["name"]}"#).unwrap()],
+///     pub create_user<HTTP> { ... }
+/// }
+/// ```
+///
+/// The keywords checked are `type`, `enum`, `minimum`, `maximum`,
+/// `exclusiveMinimum`, `exclusiveMaximum`, `minLength`, `maxLength`,
+/// `minItems`, `maxItems`, `items`, `required`, `properties` and
+/// `additionalProperties`, plus the `true` / `false` schemas. Other keywords
+/// are ignored.
+#[derive(Debug, Clone)]
+pub struct JsonSchema {
+    schema: Value,
+}
+
+/// One place where a document does not match its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// JSON Pointer to the offending value, e.g. `/items/0/name`; empty
+    /// for the document itself.
+    pub path: String,
+    pub message: String,
+}
+
+impl SchemaError {
+    fn new(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// The error as `{"path": .., "message": ..}`.
+    pub fn to_value(&self) -> Value {
+        let mut value = Value::new_dict();
+        value.set("path", self.path.as_str());
+        value.set("message", self.message.as_str());
+        value
+    }
+}
+
+impl JsonSchema {
+    pub fn new(schema: Value) -> Self {
+        Self { schema }
+    }
+
+    /// Parses the schema from its JSON text.
+    pub fn from_json(schema: &str) -> Result<Self, String> {
+        Value::from_json(schema).map(Self::new)
+    }
+
+    pub fn get_schema(&self) -> &Value {
+        &self.schema
+    }
+
+    /// Checks `document`, returning every mismatch found.
+    ///
+    /// An empty list means the document is valid.
+    pub fn validate(&self, document: &Value) -> Vec<SchemaError> {
+        let mut errors = Vec::new();
+        check(&self.schema, document, "", &mut errors);
+        errors
+    }
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<SchemaError>) {
+    let keywords = match schema {
+        Value::Boolean(false) => {
+            errors.push(SchemaError::new(path, "is not allowed"));
+            return;
+        }
+        Value::Dict(_) => schema,
+        _ => return,
+    };
+    let keyword = |name: &str| match keywords.get(name) {
+        Value::None => None,
+        found => Some(found),
+    };
+
+    if let Some(types) = keyword("type") {
+        let types: Vec<String> = match types {
+            Value::List(types) => types.iter().map(Value::string).collect(),
+            single => vec![single.string()],
+        };
+        if !types.iter().any(|name| has_type(value, name)) {
+            let message = format!("must be of type {}", types.join(" or "));
+            errors.push(SchemaError::new(path, message));
+            // The remaining keywords assume the right type
+            return;
+        }
+    }
+
+    if let Some(Value::List(allowed)) = keyword("enum") {
+        if !allowed.iter().any(|candidate| candidate.equals(value)) {
+            let message = format!("must be one of {}", keywords.get("enum").into_json());
+            errors.push(SchemaError::new(path, message));
+        }
+    }
+
+    match value {
+        Value::Numerical(n) => check_number(*n, &keyword, path, errors),
+        Value::Str(s) => {
+            let length = s.chars().count() as f64;
+            if let Some(Value::Numerical(min)) = keyword("minLength") {
+                if length < *min {
+                    errors.push(SchemaError::new(
+                        path,
+                        format!("must be at least {min} characters"),
+                    ));
+                }
+            }
+            if let Some(Value::Numerical(max)) = keyword("maxLength") {
+                if length > *max {
+                    errors.push(SchemaError::new(
+                        path,
+                        format!("must be at most {max} characters"),
+                    ));
+                }
+            }
+        }
+        Value::List(items) => {
+            let count = items.len() as f64;
+            if let Some(Value::Numerical(min)) = keyword("minItems") {
+                if count < *min {
+                    errors.push(SchemaError::new(
+                        path,
+                        format!("must have at least {min} items"),
+                    ));
+                }
+            }
+            if let Some(Value::Numerical(max)) = keyword("maxItems") {
+                if count > *max {
+                    errors.push(SchemaError::new(
+                        path,
+                        format!("must have at most {max} items"),
+                    ));
+                }
+            }
+            if let Some(item_schema) = keyword("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(item_schema, item, &format!("{path}/{index}"), errors);
+                }
+            }
+        }
+        Value::Dict(fields) => {
+            if let Some(Value::List(required)) = keyword("required") {
+                for name in required {
+                    let name = name.string();
+                    if !fields.contains_key(&name) {
+                        errors.push(SchemaError::new(&pointer(path, &name), "is required"));
+                    }
+                }
+            }
+
+            let properties = keyword("properties");
+            let additional = keyword("additionalProperties");
+            // Sorted so errors come out in a stable order
+            let mut names: Vec<&String> = fields.keys().collect();
+            names.sort();
+            for name in names {
+                let field_path = pointer(path, name);
+                match properties.map(|properties| properties.get(name)) {
+                    Some(Value::None) | None => {
+                        if let Some(additional) = additional {
+                            check(additional, &fields[name], &field_path, errors);
+                        }
+                    }
+                    Some(property) => check(property, &fields[name], &field_path, errors),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_number<'a>(
+    n: f64,
+    keyword: &impl Fn(&str) -> Option<&'a Value>,
+    path: &str,
+    errors: &mut Vec<SchemaError>,
+) {
+    let bounds: [(&str, fn(f64, f64) -> bool, &str); 4] = [
+        ("minimum", |n, bound| n >= bound, "at least"),
+        ("maximum", |n, bound| n <= bound, "at most"),
+        ("exclusiveMinimum", |n, bound| n > bound, "greater than"),
+        ("exclusiveMaximum", |n, bound| n < bound, "less than"),
+    ];
+    for (name, holds, wording) in bounds {
+        if let Some(Value::Numerical(bound)) = keyword(name) {
+            if !holds(n, *bound) {
+                errors.push(SchemaError::new(path, format!("must be {wording} {bound}")));
+            }
+        }
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match (name, value) {
+        ("object", Value::Dict(_))
+        | ("array", Value::List(_))
+        | ("string", Value::Str(_))
+        | ("boolean", Value::Boolean(_))
+        | ("null", Value::None)
+        | ("number", Value::Numerical(_)) => true,
+        ("integer", Value::Numerical(n)) => n.fract() == 0.0,
+        _ => false,
+    }
+}
+
+/// `path` extended by one property, escaped as JSON Pointer asks.
+fn pointer(path: &str, name: &str) -> String {
+    format!("{path}/{}", name.replace('~', "~0").replace('/', "~1"))
+}
@@ -1,7 +1,12 @@
+pub mod cache;
+#[cfg(feature = "compression")]
+pub mod compression;
 pub mod cors;
+pub mod json_schema;
 pub mod language;
 pub mod log;
 pub mod session;
+pub mod timeout;
 
 pub use language::{
     LanguageRange, MAX_QUALITY_MILLIS, PreferredLanguage, PreferredLanguageMiddleware,
@@ -13,5 +18,11 @@ pub use session::Session;
 pub use session::SessionSecret;
 pub use session::{CookieSecurity, CookieSessionSettings};
 
+pub use cache::{CacheControl, ResponseCache, ResponseCacheSettings, ResponseCacheStore};
+#[cfg(feature = "compression")]
+pub use compression::{Compression, CompressionSettings, NoCompression};
+pub use json_schema::{JsonSchema, SchemaError, ValidateJson};
+pub use timeout::{Timeout, TimeoutSettings};
+
 pub use cors::cors::Cors;
 pub use cors::cors_settings;
@@ -0,0 +1,140 @@
+//! The timeout middleware.
+
+use hotaru_core::executable::middleware::{AsyncMiddleware, Deadline, race_deadline};
+use hotaru_core::protocol::{Protocol, RequestContext};
+use hotaru_http::http_value::StatusCode;
+use hotaru_http::protocol::HttpError;
+use hotaru_http::protocol::helpers::error_response_from;
+use hotaru_http::traits::HTTP;
+use hotaru_trans::middleware;
+
+use super::settings::TimeoutSettings;
+
+middleware! {
+    /// Answers with `503 Service Unavailable` when the middleware and handler
+    /// after it run past the limit, dropping their work.
+    ///
+    /// The limit comes from the endpoint params, then the runtime config,
+    /// falling back to [`TimeoutSettings::default`]. The 503 is returned as a
+    /// normal response, so middleware listed before this one still see it.
+    pub Timeout<HTTP> {
+        let limit = req
+            .endpoint()
+            .and_then(|ep| ep.get_params::<TimeoutSettings>())
+            .or_else(|| req.runtime().and_then(|rt| rt.get_config::<TimeoutSettings>()))
+            .unwrap_or_default()
+            .get_limit();
+
+        let status = StatusCode::SERVICE_UNAVAILABLE;
+        match race_deadline(req, next, tokio::time::sleep(limit)).await {
+            Deadline::Finished(result) => result,
+            Deadline::Expired(Some(mut req)) => {
+                req.response = error_response_from(&HttpError::Status(status));
+                req.set_handler_duration(limit);
+                Ok(req)
+            }
+            Deadline::Expired(None) => Err(HttpError::Status(status)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hotaru_core::app::common::{RunMode, RuntimeConfig};
+    use hotaru_core::executable::ExecutableBinding;
+    use hotaru_core::executable::ExecutionChain;
+    use hotaru_core::executable::middleware::AsyncFinalHandler;
+    use hotaru_core::extensions::{Locals, Params, ParamsClone};
+    use hotaru_core::marker::MaybeSendBoxFuture;
+    use hotaru_core::url::{Children, PathPattern, StepName, UrlNode};
+    use hotaru_http::context::HttpContext;
+    use hotaru_http::request::HttpRequest;
+    use hotaru_http::safety::HttpSafety;
+    use std::any::Any;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    type Chained = MaybeSendBoxFuture<'static, Result<HttpContext, HttpError>>;
+    type Next = Box<dyn Fn(HttpContext) -> Chained + Send + Sync>;
+
+    /// Records the status of every response on its way out
+    #[derive(Clone, Default)]
+    struct Logger(Arc<Mutex<Vec<u16>>>);
+
+    impl AsyncMiddleware<HttpContext> for Logger {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn return_self() -> Self {
+            Logger::default()
+        }
+
+        fn handle(&self, ctx: HttpContext, next: Next) -> Chained {
+            let log = self.0.clone();
+            Box::pin(async move {
+                let ctx = next(ctx).await?;
+                let status = ctx.response.meta.start_line.status_code().as_u16();
+                log.lock().unwrap().push(status);
+                Ok(ctx)
+            })
+        }
+    }
+
+    fn context(limit: Duration) -> HttpContext {
+        let mut config = Params::new();
+        config.set(TimeoutSettings::new(limit));
+        let runtime = RuntimeConfig::from_parts(RunMode::default(), config, Locals::new());
+        let endpoint = UrlNode::new(
+            PathPattern::literal_path("slow"),
+            Children::new(),
+            ExecutableBinding::new(),
+            ParamsClone::default(),
+            StepName::default(),
+        );
+        HttpContext::new_server(
+            Arc::new(runtime),
+            Arc::new(endpoint),
+            HttpRequest::default(),
+            None,
+            None,
+            HttpSafety::default(),
+        )
+    }
+
+    fn chain(logger: &Logger, delay: Duration) -> ExecutionChain<HttpContext> {
+        let handler: Arc<dyn AsyncFinalHandler<HttpContext>> =
+            Arc::new(move |ctx: HttpContext| async move {
+                tokio::time::sleep(delay).await;
+                Ok(ctx)
+            });
+        ExecutionChain::new(vec![Arc::new(logger.clone()), Arc::new(Timeout)], handler)
+    }
+
+    #[tokio::test]
+    async fn logger_outside_timeout_logs_the_503() {
+        let logger = Logger::default();
+        let chain = chain(&logger, Duration::from_secs(5));
+
+        let ctx = chain.run(context(Duration::from_millis(20))).await.unwrap();
+
+        assert_eq!(
+            ctx.response.meta.start_line.status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(ctx.handler_duration(), Duration::from_millis(20));
+        assert_eq!(*logger.0.lock().unwrap(), [503]);
+    }
+
+    #[tokio::test]
+    async fn handler_within_the_limit_passes_through() {
+        let logger = Logger::default();
+        let chain = chain(&logger, Duration::ZERO);
+
+        let ctx = chain.run(context(Duration::from_secs(5))).await.unwrap();
+
+        assert_eq!(ctx.response.meta.start_line.status_code(), StatusCode::OK);
+        assert_eq!(*logger.0.lock().unwrap(), [200]);
+    }
+}
@@ -0,0 +1,16 @@
+//! Per-route time limits for Hotaru/htmstd.
+//!
+//! The module is split by responsibility:
+//! - [`settings`]: the [`TimeoutSettings`] limit
+//! - [`middleware`]: the [`Timeout`] middleware
+//!
+//! A request that outlives its limit is answered with
+//! `503 Service Unavailable`. Middleware listed before [`Timeout`] still run
+//! their after-`next` half on that response, so e.g. a logger placed in
+//! front of it records the timeout.
+
+pub mod middleware;
+pub mod settings;
+
+pub use self::middleware::Timeout;
+pub use self::settings::TimeoutSettings;
@@ -0,0 +1,31 @@
+//! Configuration for [`crate::Timeout`].
+
+use std::time::Duration;
+
+/// Default time the rest of the chain may take.
+pub const DEFAULT_LIMIT: Duration = Duration::from_secs(30);
+
+/// How long the middleware and handler inside a [`crate::Timeout`] may run.
+///
+/// Put one in an endpoint's params for a per-route limit, or in the runtime
+/// config for an app-wide one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutSettings {
+    limit: Duration,
+}
+
+impl TimeoutSettings {
+    pub fn new(limit: Duration) -> Self {
+        Self { limit }
+    }
+
+    pub fn get_limit(&self) -> Duration {
+        self.limit
+    }
+}
+
+impl Default for TimeoutSettings {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIMIT)
+    }
+}
@@ -100,7 +100,7 @@ endpoint! {
                     .status(StatusCode::SWITCHING_PROTOCOLS)
                     .header("Connection", "Upgrade")
                     .header("Upgrade", "h2c")
-                    .body(Empty::<Bytes>::new().boxed())
+                    .body(empty_body())
                     .unwrap();
                 HyperResponse { inner: response }
             }
@@ -115,7 +115,7 @@ endpoint! {
                             .status(StatusCode::SWITCHING_PROTOCOLS)
                             .header("Connection", "Upgrade")
                             .header("Upgrade", "h2c")
-                            .body(Empty::<Bytes>::new().boxed())
+                            .body(empty_body())
                             .unwrap();
                         HyperResponse { inner: response }
                     } else {
@@ -123,11 +123,11 @@ endpoint! {
                         let response = Response::builder()
                             .status(StatusCode::OK)
                             .header("Content-Type", "application/json")
-                            .body(Full::new(Bytes::from(json!({
+                            .body(full_body(json!({
                                 "message": "HTTP/1.1 endpoint ready for upgrades",
                                 "hint": "Add 'Connection: Upgrade' and 'Upgrade: h2c' headers to upgrade to HTTP/2",
                                 "supported": ["websocket", "h2c"]
-                            }).to_string())).boxed())
+                            }).to_string()))
                             .unwrap();
                         HyperResponse { inner: response }
                     }
@@ -137,7 +137,7 @@ endpoint! {
                         .status(StatusCode::OK)
                         .header("Content-Type", "application/json")
                         .header("Alt-Svc", "h2c=\":3090\"")  // Advertise h2c support
-                        .body(Full::new(Bytes::from(json!({
+                        .body(full_body(json!({
                             "message": "HTTP/1.1 endpoint - upgrades available",
                             "current_protocol": "HTTP/1.1",
                             "available_upgrades": {
@@ -145,7 +145,7 @@ endpoint! {
                                 "websocket": "Send 'Upgrade: websocket' header"
                             },
                             "note": "Your browser doesn't support h2c upgrades via this method"
-                        }).to_string())).boxed())
+                        }).to_string()))
                         .unwrap();
                     HyperResponse { inner: response }
                 }
@@ -675,8 +675,9 @@ endpoint! {
 
                     // The framework will:
                     // 1. Detect ConnectionStatus::SwitchProtocol in the context
-                    // 2. Use Http2Transport.upgrade_stream_to_websocket(stream_id)
-                    // 3. Switch this stream to WebSocketProtocol
+                    // 2. Send this 200 OK on the CONNECT stream
+                    // 3. Run WebSocket frames over that stream's DATA frames
+                    //    (see h2per::websocket::WebSocketConnection)
                     // 4. Other HTTP/2 streams remain unaffected
 
                     // Return 200 OK for Extended CONNECT
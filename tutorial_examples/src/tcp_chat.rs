@@ -12,6 +12,7 @@ use hotaru_core::connection::{
     Message, Protocol, ProtocolRole, RequestContext, Stream, TcpConnectionStream, TcpReader,
     TcpWriter, Transport,
 };
+use hotaru_core::protocol::{Detection, Extensions};
 
 // ============================================================================
 // Shared Chat State
@@ -189,6 +190,7 @@ impl Message for TcpChatMessage {
 pub struct TcpChatContext {
     pub response: TcpChatMessage,
     role: ProtocolRole,
+    extensions: Extensions,
 }
 
 impl RequestContext for TcpChatContext {
@@ -202,6 +204,14 @@ impl RequestContext for TcpChatContext {
     fn role(&self) -> ProtocolRole {
         self.role
     }
+
+    fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
 }
 
 #[async_trait]
@@ -211,13 +221,11 @@ impl Protocol for TcpChat {
     type Message = TcpChatMessage;
     type Context = TcpChatContext;
 
-    fn detect(initial_bytes: &[u8]) -> bool {
-        let text = String::from_utf8_lossy(initial_bytes);
-        text.starts_with("JOIN ")
-            || text.starts_with("MSG ")
-            || text.starts_with("LIST")
-            || text.starts_with("HISTORY")
-            || text.starts_with("CHAT:")
+    fn detect(initial_bytes: &[u8]) -> Detection {
+        Detection::any_prefix(
+            initial_bytes,
+            &[b"JOIN ", b"MSG ", b"LIST", b"HISTORY", b"CHAT:"],
+        )
     }
 
     fn role(&self) -> ProtocolRole {